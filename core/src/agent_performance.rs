@@ -0,0 +1,616 @@
+//! Productivity analytics and anomaly flags per agent
+//!
+//! Aggregates work session durations, productivity scores and task
+//! completion latency per agent, split into buckets to show trends over the
+//! period, along with the tools each agent called most. Collapsing scores and claims left idle are reported as
+//! [`PerformanceAnomaly`] and recorded as system events for the coordinator.
+
+use crate::error::{Result, TaskError};
+use crate::models::{EventSeverity, SystemEvent, Task, TaskFilter, TaskState, WorkSession};
+use crate::repository::{SystemEventFilter, TaskRepository, WorkSessionFilter};
+use crate::tool_usage::{top_tools, ToolUsageFilter, ToolUsageStat, TOP_TOOLS_PER_AGENT};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Days covered by a performance report when no start is requested
+pub const DEFAULT_PERFORMANCE_PERIOD_DAYS: u32 = 28;
+
+/// Days per trend bucket when none is requested
+pub const DEFAULT_PERFORMANCE_BUCKET_DAYS: u32 = 7;
+
+/// Hours during which an anomaly already recorded is not recorded again
+pub const ANOMALY_EVENT_INTERVAL_HOURS: i64 = 24;
+
+/// When agent performance counts as anomalous
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyThresholds {
+    /// Fraction by which the recent average score must fall below the earlier one
+    pub score_drop_ratio: f64,
+    /// Scored sessions needed both before and within the recent window
+    pub min_scored_sessions: usize,
+    /// Days at the end of the period compared against the rest
+    pub recent_days: u32,
+    /// Minutes a claimed task may go without work sessions (0 disables the check)
+    pub idle_claim_minutes: u32,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            score_drop_ratio: 0.4,
+            min_scored_sessions: 3,
+            recent_days: 7,
+            idle_claim_minutes: 240,
+        }
+    }
+}
+
+/// Work of an agent during one bucket of the period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceBucket {
+    pub start: DateTime<Utc>,
+    /// Sessions started during the bucket
+    pub sessions: usize,
+    pub minutes_worked: i64,
+    pub avg_productivity_score: Option<f64>,
+    pub tasks_completed: usize,
+}
+
+/// Productivity of one agent over the period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentPerformance {
+    pub agent_name: String,
+    /// Sessions started during the period
+    pub sessions: usize,
+    /// Minutes of those sessions; open sessions count until the end of the period
+    pub minutes_worked: i64,
+    pub avg_session_minutes: f64,
+    /// Average score of the scored sessions
+    pub avg_productivity_score: Option<f64>,
+    /// Tasks owned by the agent and completed during the period
+    pub tasks_completed: usize,
+    /// Average minutes from claim to completion
+    pub avg_completion_minutes: Option<f64>,
+    /// Trend of the above, oldest bucket first
+    pub buckets: Vec<PerformanceBucket>,
+    /// Tools the agent called most during the period, most called first
+    #[serde(default)]
+    pub top_tools: Vec<ToolUsageStat>,
+}
+
+/// Performance of an agent that needs a closer look
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PerformanceAnomaly {
+    /// Recent productivity scores fell well below the earlier ones
+    ScoreCollapse {
+        agent_name: String,
+        baseline_score: f64,
+        recent_score: f64,
+    },
+    /// A task stayed claimed without any work session
+    IdleClaim {
+        agent_name: String,
+        task_code: String,
+        idle_minutes: i64,
+    },
+}
+
+impl PerformanceAnomaly {
+    pub fn agent_name(&self) -> &str {
+        match self {
+            PerformanceAnomaly::ScoreCollapse { agent_name, .. }
+            | PerformanceAnomaly::IdleClaim { agent_name, .. } => agent_name,
+        }
+    }
+
+    /// Type of the system event recording the anomaly
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            PerformanceAnomaly::ScoreCollapse { .. } => "agent_score_collapse",
+            PerformanceAnomaly::IdleClaim { .. } => "agent_idle_claim",
+        }
+    }
+
+    /// Agent or task the anomaly is about
+    pub fn entity_id(&self) -> &str {
+        match self {
+            PerformanceAnomaly::ScoreCollapse { agent_name, .. } => agent_name,
+            PerformanceAnomaly::IdleClaim { task_code, .. } => task_code,
+        }
+    }
+
+    /// Warning event describing the anomaly, not yet stored
+    pub fn to_system_event(&self, now: DateTime<Utc>) -> SystemEvent {
+        SystemEvent {
+            id: 0,
+            event_type: self.event_type().to_string(),
+            entity_id: Some(self.entity_id().to_string()),
+            data: serde_json::to_value(self).unwrap_or_default(),
+            triggered_by: None,
+            timestamp: now,
+            severity: EventSeverity::Warning,
+        }
+    }
+}
+
+/// Productivity of every agent between `period_start` and `period_end`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub bucket_days: u32,
+    /// Agents that worked or completed tasks during the period, by name
+    pub agents: Vec<AgentPerformance>,
+    pub anomalies: Vec<PerformanceAnomaly>,
+}
+
+#[derive(Default)]
+struct Totals {
+    sessions: usize,
+    minutes_worked: i64,
+    scores: Vec<f64>,
+    tasks_completed: usize,
+    completion_minutes: Vec<i64>,
+}
+
+impl Totals {
+    fn bucket(&self, start: DateTime<Utc>) -> PerformanceBucket {
+        PerformanceBucket {
+            start,
+            sessions: self.sessions,
+            minutes_worked: self.minutes_worked,
+            avg_productivity_score: average(&self.scores),
+            tasks_completed: self.tasks_completed,
+        }
+    }
+}
+
+fn totals_of<'m, 'a>(
+    agents: &'m mut HashMap<&'a str, (Totals, Vec<Totals>)>,
+    name: &'a str,
+    bucket_count: usize,
+) -> &'m mut (Totals, Vec<Totals>) {
+    agents.entry(name).or_insert_with(|| {
+        (
+            Totals::default(),
+            (0..bucket_count).map(|_| Totals::default()).collect(),
+        )
+    })
+}
+
+/// Number of `bucket_days` buckets covering `[start, end)`, at least one
+fn bucket_count(start: DateTime<Utc>, end: DateTime<Utc>, bucket_days: u32) -> usize {
+    ((end - start).num_days().max(1) as usize)
+        .div_ceil(bucket_days.max(1) as usize)
+        .max(1)
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+impl PerformanceReport {
+    /// Build the report of `tasks` and `sessions` for the period `[start, end)`
+    ///
+    /// Idle claims are judged at `end` from the claims of `tasks` still in progress.
+    pub fn new(
+        tasks: &[Task],
+        sessions: &[WorkSession],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        bucket_days: u32,
+        thresholds: &AnomalyThresholds,
+    ) -> Self {
+        let bucket_days = bucket_days.max(1);
+        let bucket_count = bucket_count(start, end, bucket_days);
+        let bucket_of = |at: DateTime<Utc>| {
+            (((at - start).num_days() / i64::from(bucket_days)) as usize).min(bucket_count - 1)
+        };
+
+        // Per agent: totals for the whole period, then one per bucket
+        let mut agents: HashMap<&str, (Totals, Vec<Totals>)> = HashMap::new();
+        for session in sessions {
+            if session.started_at < start || session.started_at >= end {
+                continue;
+            }
+            let minutes = (session.ended_at.unwrap_or(end).min(end) - session.started_at)
+                .num_minutes()
+                .max(0);
+            let (total, buckets) = totals_of(&mut agents, &session.agent_name, bucket_count);
+            for totals in [total, &mut buckets[bucket_of(session.started_at)]] {
+                totals.sessions += 1;
+                totals.minutes_worked += minutes;
+                totals.scores.extend(session.productivity_score);
+            }
+        }
+        for task in tasks {
+            let (Some(owner), Some(done_at)) = (&task.owner_agent_name, task.done_at) else {
+                continue;
+            };
+            if done_at < start || done_at >= end {
+                continue;
+            }
+            let latency = (done_at - task.claimed_at.unwrap_or(task.inserted_at)).num_minutes();
+            let (total, buckets) = totals_of(&mut agents, owner, bucket_count);
+            for totals in [total, &mut buckets[bucket_of(done_at)]] {
+                totals.tasks_completed += 1;
+                totals.completion_minutes.push(latency.max(0));
+            }
+        }
+
+        let mut performance: Vec<AgentPerformance> = agents
+            .into_iter()
+            .map(|(name, (total, buckets))| AgentPerformance {
+                agent_name: name.to_string(),
+                sessions: total.sessions,
+                minutes_worked: total.minutes_worked,
+                avg_session_minutes: if total.sessions == 0 {
+                    0.0
+                } else {
+                    total.minutes_worked as f64 / total.sessions as f64
+                },
+                avg_productivity_score: average(&total.scores),
+                tasks_completed: total.tasks_completed,
+                avg_completion_minutes: average(
+                    &total
+                        .completion_minutes
+                        .iter()
+                        .map(|&m| m as f64)
+                        .collect::<Vec<_>>(),
+                ),
+                buckets: buckets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, b)| {
+                        b.bucket(start + Duration::days(i as i64 * i64::from(bucket_days)))
+                    })
+                    .collect(),
+                top_tools: Vec::new(),
+            })
+            .collect();
+        performance.sort_by(|a, b| a.agent_name.cmp(&b.agent_name));
+
+        let mut anomalies = score_collapses(sessions, start, end, thresholds);
+        anomalies.extend(idle_claims(tasks, sessions, end, thresholds));
+
+        Self {
+            period_start: start,
+            period_end: end,
+            bucket_days,
+            agents: performance,
+            anomalies,
+        }
+    }
+
+    /// Add the most called tools of each agent in `usage`
+    ///
+    /// Agents that only called tools, such as one polling without ever
+    /// working, are added with empty totals.
+    pub fn with_tool_usage(mut self, usage: &[ToolUsageStat]) -> Self {
+        for stat in usage {
+            if self.agents.iter().any(|a| a.agent_name == stat.agent_name) {
+                continue;
+            }
+            let buckets = (0..bucket_count(self.period_start, self.period_end, self.bucket_days))
+                .map(|i| {
+                    let offset = Duration::days(i as i64 * i64::from(self.bucket_days));
+                    Totals::default().bucket(self.period_start + offset)
+                })
+                .collect();
+            self.agents.push(AgentPerformance {
+                agent_name: stat.agent_name.clone(),
+                sessions: 0,
+                minutes_worked: 0,
+                avg_session_minutes: 0.0,
+                avg_productivity_score: None,
+                tasks_completed: 0,
+                avg_completion_minutes: None,
+                buckets,
+                top_tools: Vec::new(),
+            });
+        }
+        for agent in &mut self.agents {
+            agent.top_tools = top_tools(usage, &agent.agent_name, TOP_TOOLS_PER_AGENT);
+        }
+        self.agents.sort_by(|a, b| a.agent_name.cmp(&b.agent_name));
+        self
+    }
+
+    /// Keep only the performance and anomalies of `agent_name`
+    pub fn for_agent(mut self, agent_name: &str) -> Self {
+        self.agents.retain(|a| a.agent_name == agent_name);
+        self.anomalies.retain(|a| a.agent_name() == agent_name);
+        self
+    }
+}
+
+/// Agents whose recent scores fell below their earlier scores by the drop ratio
+fn score_collapses(
+    sessions: &[WorkSession],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    thresholds: &AnomalyThresholds,
+) -> Vec<PerformanceAnomaly> {
+    let recent_start = end - Duration::days(i64::from(thresholds.recent_days));
+    let mut scores: HashMap<&str, (Vec<f64>, Vec<f64>)> = HashMap::new();
+    for session in sessions {
+        let Some(score) = session.productivity_score else {
+            continue;
+        };
+        if session.started_at < start || session.started_at >= end {
+            continue;
+        }
+        let (baseline, recent) = scores.entry(&session.agent_name).or_default();
+        if session.started_at >= recent_start {
+            recent.push(score);
+        } else {
+            baseline.push(score);
+        }
+    }
+
+    let min_sessions = thresholds.min_scored_sessions.max(1);
+    let mut anomalies: Vec<PerformanceAnomaly> = scores
+        .into_iter()
+        .filter(|(_, (baseline, recent))| {
+            baseline.len() >= min_sessions && recent.len() >= min_sessions
+        })
+        .filter_map(|(agent_name, (baseline, recent))| {
+            let baseline_score = average(&baseline)?;
+            let recent_score = average(&recent)?;
+            (recent_score < baseline_score * (1.0 - thresholds.score_drop_ratio)).then(|| {
+                PerformanceAnomaly::ScoreCollapse {
+                    agent_name: agent_name.to_string(),
+                    baseline_score,
+                    recent_score,
+                }
+            })
+        })
+        .collect();
+    anomalies.sort_by(|a, b| a.agent_name().cmp(b.agent_name()));
+    anomalies
+}
+
+/// Tasks in progress whose owner has not worked on them since the idle threshold
+fn idle_claims(
+    tasks: &[Task],
+    sessions: &[WorkSession],
+    now: DateTime<Utc>,
+    thresholds: &AnomalyThresholds,
+) -> Vec<PerformanceAnomaly> {
+    if thresholds.idle_claim_minutes == 0 {
+        return Vec::new();
+    }
+    tasks
+        .iter()
+        .filter(|t| t.state == TaskState::InProgress)
+        .filter_map(|task| {
+            let owner = task.owner_agent_name.as_ref()?;
+            let claimed_at = task.claimed_at?;
+            let mut last_activity = claimed_at;
+            for session in sessions
+                .iter()
+                .filter(|s| s.task_id == task.id && &s.agent_name == owner)
+            {
+                last_activity = last_activity.max(session.ended_at.unwrap_or(now));
+            }
+            let idle_minutes = (now - last_activity).num_minutes();
+            (idle_minutes >= i64::from(thresholds.idle_claim_minutes)).then(|| {
+                PerformanceAnomaly::IdleClaim {
+                    agent_name: owner.clone(),
+                    task_code: task.code.clone(),
+                    idle_minutes,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Load the tasks and work sessions of `repository` and report on `[start, end)`
+pub async fn performance_report<R: TaskRepository + ?Sized>(
+    repository: &R,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    bucket_days: u32,
+    thresholds: &AnomalyThresholds,
+) -> Result<PerformanceReport> {
+    let tasks = repository.list(TaskFilter::default()).await?;
+    let mut sessions = repository
+        .list_work_sessions(&WorkSessionFilter {
+            since: Some(start),
+            until: Some(end),
+            ..WorkSessionFilter::default()
+        })
+        .await?;
+    // Sessions still open on claimed tasks may have started before the period
+    sessions.extend(
+        repository
+            .list_work_sessions(&WorkSessionFilter {
+                active_only: true,
+                ..WorkSessionFilter::default()
+            })
+            .await?,
+    );
+    sessions.sort_by_key(|s| s.id);
+    sessions.dedup_by_key(|s| s.id);
+    let usage = match repository
+        .tool_usage_stats(&ToolUsageFilter {
+            since: Some(start),
+            until: Some(end),
+            ..ToolUsageFilter::default()
+        })
+        .await
+    {
+        Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+        result => result?,
+    };
+    Ok(
+        PerformanceReport::new(&tasks, &sessions, start, end, bucket_days, thresholds)
+            .with_tool_usage(&usage),
+    )
+}
+
+/// Record `anomalies` as system events, skipping those recorded recently
+///
+/// Returns the number of events recorded.
+pub async fn record_anomalies<R: TaskRepository + ?Sized>(
+    repository: &R,
+    anomalies: &[PerformanceAnomaly],
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let mut recorded = 0;
+    for anomaly in anomalies {
+        let filter = SystemEventFilter {
+            event_type: Some(anomaly.event_type().to_string()),
+            entity_id: Some(anomaly.entity_id().to_string()),
+            since: Some(now - Duration::hours(ANOMALY_EVENT_INTERVAL_HOURS)),
+            limit: Some(1),
+        };
+        if repository.list_system_events(&filter).await?.is_empty() {
+            repository
+                .record_system_event(&anomaly.to_system_event(now))
+                .await?;
+            recorded += 1;
+        }
+    }
+    Ok(recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        id: i32,
+        agent_name: &str,
+        started_at: DateTime<Utc>,
+        minutes: i64,
+        score: Option<f64>,
+    ) -> WorkSession {
+        WorkSession {
+            id,
+            task_id: 1,
+            agent_name: agent_name.to_string(),
+            started_at,
+            ended_at: Some(started_at + Duration::minutes(minutes)),
+            notes: None,
+            productivity_score: score,
+            interruptions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_performance_report() {
+        let end = Utc::now();
+        let start = end - Duration::days(28);
+        let mut sessions = Vec::new();
+        for day in 0..4 {
+            let started_at = start + Duration::days(day);
+            sessions.push(session(
+                day as i32,
+                "backend-dev",
+                started_at,
+                60,
+                Some(0.9),
+            ));
+        }
+        for day in 0..3 {
+            let started_at = end - Duration::days(3) + Duration::hours(day);
+            sessions.push(session(
+                10 + day as i32,
+                "backend-dev",
+                started_at,
+                30,
+                Some(0.2),
+            ));
+        }
+        sessions.push(session(20, "qa-dev", start + Duration::days(1), 45, None));
+
+        let mut done = Task::new(
+            1,
+            "API-01".to_string(),
+            "Endpoint".to_string(),
+            String::new(),
+            Some("backend-dev".to_string()),
+            TaskState::Done,
+            start,
+            Some(end - Duration::days(1)),
+        );
+        done.claimed_at = Some(end - Duration::days(1) - Duration::minutes(90));
+        let mut idle = Task::new(
+            2,
+            "API-02".to_string(),
+            "Tests".to_string(),
+            String::new(),
+            Some("qa-dev".to_string()),
+            TaskState::InProgress,
+            start,
+            None,
+        );
+        idle.claimed_at = Some(end - Duration::hours(6));
+
+        let report = PerformanceReport::new(
+            &[done, idle],
+            &sessions,
+            start,
+            end,
+            DEFAULT_PERFORMANCE_BUCKET_DAYS,
+            &AnomalyThresholds::default(),
+        );
+        let backend = &report.agents[0];
+        assert_eq!(backend.agent_name, "backend-dev");
+        assert_eq!(backend.sessions, 7);
+        assert_eq!(backend.minutes_worked, 330);
+        assert_eq!(backend.tasks_completed, 1);
+        assert_eq!(backend.avg_completion_minutes, Some(90.0));
+        assert_eq!(backend.buckets.len(), 4);
+        assert_eq!(backend.buckets[0].sessions, 4);
+        assert_eq!(backend.buckets[3].tasks_completed, 1);
+
+        assert_eq!(report.anomalies.len(), 2);
+        assert!(matches!(
+            report.anomalies[0],
+            PerformanceAnomaly::ScoreCollapse { ref agent_name, .. } if agent_name == "backend-dev"
+        ));
+        let idle = &report.anomalies[1];
+        assert_eq!(idle.event_type(), "agent_idle_claim");
+        assert_eq!(idle.entity_id(), "API-02");
+
+        let qa = report.for_agent("qa-dev");
+        assert_eq!(qa.agents.len(), 1);
+        assert_eq!(qa.agents[0].avg_productivity_score, None);
+        assert_eq!(qa.anomalies.len(), 1);
+    }
+
+    #[test]
+    fn test_report_lists_top_tools() {
+        let end = Utc::now();
+        let start = end - Duration::days(14);
+        let sessions = [session(1, "backend-dev", start, 60, None)];
+        let stat = |agent_name: &str, tool_name: &str, calls: u64| ToolUsageStat {
+            agent_name: agent_name.to_string(),
+            tool_name: tool_name.to_string(),
+            calls,
+            errors: 0,
+            avg_duration_ms: 3.0,
+            first_called_at: start,
+            last_called_at: end,
+        };
+        let usage = [
+            stat("backend-dev", "claim_task", 3),
+            stat("poller", "list_tasks", 4000),
+        ];
+
+        let report =
+            PerformanceReport::new(&[], &sessions, start, end, 7, &AnomalyThresholds::default())
+                .with_tool_usage(&usage);
+        assert_eq!(report.agents.len(), 2);
+        assert_eq!(report.agents[0].top_tools[0].tool_name, "claim_task");
+        let poller = &report.agents[1];
+        assert_eq!((poller.agent_name.as_str(), poller.sessions), ("poller", 0));
+        assert_eq!(poller.buckets.len(), 2);
+        assert_eq!(poller.top_tools[0].calls, 4000);
+    }
+}
@@ -0,0 +1,226 @@
+//! Digest of what happened while an agent was away
+//!
+//! An agent coming back from idle wants to know what changed for it, not the
+//! whole board: tasks handed to it, messages mentioning it, state changes on
+//! the tasks it owns and claim leases about to run out. With event history
+//! enabled the board at `since` tells exactly which tasks were assigned and
+//! from which state each one moved; without it, claims and timestamps after
+//! `since` are used instead.
+
+use crate::models::{Task, TaskMention, TaskState};
+use crate::protocol::WorkDeadline;
+use crate::task_history::{TaskBoard, TaskStateChange};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A task that became the agent's
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NewAssignment {
+    pub task_id: i32,
+    pub code: String,
+    pub name: String,
+    pub state: TaskState,
+    /// Owner before the assignment, when event history knows it
+    pub previous_owner: Option<String>,
+}
+
+/// Everything relevant to an agent since a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentUpdates {
+    pub agent_name: String,
+    pub since: DateTime<Utc>,
+    /// When the digest was taken; use as `since` on the next call
+    pub as_of: DateTime<Utc>,
+    /// One line counting the updates below
+    pub summary: String,
+    pub new_assignments: Vec<NewAssignment>,
+    /// Mentions of the agent, newest first
+    pub mentions: Vec<TaskMention>,
+    /// State changes of tasks the agent owns; `from_state` is unknown without event history
+    pub state_changes: Vec<TaskStateChange>,
+    /// Claim leases of the agent that ran out or run out within the deadline window
+    pub sla_warnings: Vec<WorkDeadline>,
+}
+
+impl AgentUpdates {
+    /// Build the digest from the tasks the agent owns now
+    ///
+    /// `before` is the board at `since`, when event history covers it.
+    pub fn new(
+        agent_name: &str,
+        since: DateTime<Utc>,
+        as_of: DateTime<Utc>,
+        owned: &[Task],
+        before: Option<&TaskBoard>,
+        mentions: Vec<TaskMention>,
+        deadline_window: Duration,
+    ) -> Self {
+        let before: Option<HashMap<i32, &Task>> =
+            before.map(|board| board.tasks.iter().map(|task| (task.id, task)).collect());
+
+        let mut new_assignments = Vec::new();
+        let mut state_changes = Vec::new();
+        for task in owned.iter().filter(|task| task.updated_at > since) {
+            let previous = before.as_ref().and_then(|tasks| tasks.get(&task.id));
+            let assigned = match &before {
+                Some(_) => previous.and_then(|t| t.owner_agent_name.as_deref()) != Some(agent_name),
+                None => {
+                    task.claimed_at.is_some_and(|at| at > since)
+                        || (task.state == TaskState::Created && task.inserted_at > since)
+                }
+            };
+            if assigned {
+                new_assignments.push(NewAssignment {
+                    task_id: task.id,
+                    code: task.code.clone(),
+                    name: task.name.clone(),
+                    state: task.state,
+                    previous_owner: previous.and_then(|t| t.owner_agent_name.clone()),
+                });
+            }
+            let from_state = previous.map(|t| t.state);
+            if task.state_changed_at > since && from_state != Some(task.state) {
+                state_changes.push(TaskStateChange {
+                    code: task.code.clone(),
+                    name: task.name.clone(),
+                    from_state,
+                    to_state: task.state,
+                });
+            }
+        }
+
+        let mut sla_warnings: Vec<WorkDeadline> = owned
+            .iter()
+            .filter(|task| task.state == TaskState::InProgress)
+            .filter_map(|task| {
+                let due_at = task
+                    .lease_expires_at
+                    .filter(|due| *due <= as_of + deadline_window)?;
+                let kind = match due_at <= as_of {
+                    true => "lease_expired",
+                    false => "lease_expiry",
+                };
+                Some(WorkDeadline {
+                    task_id: task.id,
+                    task_code: task.code.clone(),
+                    due_at,
+                    kind: kind.to_string(),
+                })
+            })
+            .collect();
+        sla_warnings.sort_by_key(|warning| warning.due_at);
+
+        let summary = summarize(&[
+            (new_assignments.len(), "new assignment"),
+            (mentions.len(), "mention"),
+            (state_changes.len(), "state change"),
+            (sla_warnings.len(), "SLA warning"),
+        ]);
+        Self {
+            agent_name: agent_name.to_string(),
+            since,
+            as_of,
+            summary,
+            new_assignments,
+            mentions,
+            state_changes,
+            sla_warnings,
+        }
+    }
+
+    /// Whether nothing happened that concerns the agent
+    pub fn is_empty(&self) -> bool {
+        self.new_assignments.is_empty()
+            && self.mentions.is_empty()
+            && self.state_changes.is_empty()
+            && self.sla_warnings.is_empty()
+    }
+}
+
+/// "2 new assignments, 1 mention" from the non-zero counts
+fn summarize(counts: &[(usize, &str)]) -> String {
+    let parts: Vec<String> = counts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, noun)| match count {
+            1 => format!("1 {noun}"),
+            _ => format!("{count} {noun}s"),
+        })
+        .collect();
+    match parts.is_empty() {
+        true => "No updates".to_string(),
+        false => parts.join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(id: i32, state: TaskState, owner: Option<&str>, at: DateTime<Utc>) -> Task {
+        Task {
+            name: format!("Task {id}"),
+            owner_agent_name: owner.map(str::to_string),
+            ..test_support::task_at(id, &format!("T-{id}"), state, at)
+        }
+    }
+
+    #[test]
+    fn test_updates_from_history() {
+        let now = Utc::now();
+        let since = now - Duration::hours(8);
+        let old = since - Duration::days(1);
+
+        let mut handed_over = task(1, TaskState::Created, Some("dev"), old);
+        handed_over.updated_at = now - Duration::hours(1);
+        let mut progressed = task(2, TaskState::Review, Some("dev"), old);
+        progressed.updated_at = now - Duration::hours(2);
+        progressed.state_changed_at = now - Duration::hours(2);
+        let mut expiring = task(3, TaskState::InProgress, Some("dev"), old);
+        expiring.lease_expires_at = Some(now - Duration::minutes(5));
+        let untouched = task(4, TaskState::InProgress, Some("dev"), old);
+
+        let board = TaskBoard::new(
+            since,
+            Some(old),
+            vec![
+                task(1, TaskState::Created, Some("other"), old),
+                task(2, TaskState::InProgress, Some("dev"), old),
+                task(3, TaskState::InProgress, Some("dev"), old),
+                task(4, TaskState::InProgress, Some("dev"), old),
+            ],
+        );
+        let owned = [handed_over, progressed, expiring, untouched];
+        let updates = AgentUpdates::new(
+            "dev",
+            since,
+            now,
+            &owned,
+            Some(&board),
+            vec![],
+            Duration::minutes(60),
+        );
+
+        assert_eq!(updates.new_assignments.len(), 1);
+        assert_eq!(
+            updates.new_assignments[0].previous_owner.as_deref(),
+            Some("other")
+        );
+        assert_eq!(updates.state_changes.len(), 1);
+        assert_eq!(
+            updates.state_changes[0].from_state,
+            Some(TaskState::InProgress)
+        );
+        assert_eq!(updates.sla_warnings[0].kind, "lease_expired");
+        assert_eq!(
+            updates.summary,
+            "1 new assignment, 1 state change, 1 SLA warning"
+        );
+
+        let quiet = AgentUpdates::new("dev", now, now, &owned[3..], None, vec![], Duration::zero());
+        assert!(quiet.is_empty());
+        assert_eq!(quiet.summary, "No updates");
+    }
+}
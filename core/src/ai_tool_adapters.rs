@@ -7,7 +7,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::claude_subagents::{render_subagent_file, subagent_name, validate_subagent_file};
 use crate::error::{Result, TaskError};
+use crate::manifest_schema::MANIFEST_SCHEMA_VERSION;
 use crate::prompt_templates::EnhancedPromptBuilder;
 use crate::workspace_setup::{
     AgentRegistration, AgenticWorkflowDescription, AiToolType, MainAiFileData,
@@ -330,7 +332,7 @@ impl AiToolAdapter for ClaudeCodeAdapter {
 
     async fn get_main_file_instructions(&self) -> Result<MainAiFileInstructions> {
         use crate::workspace_setup::SectionTemplate;
-        use std::collections::HashMap;
+        use std::collections::BTreeMap;
 
         Ok(MainAiFileInstructions {
             ai_tool_type: AiToolType::ClaudeCode,
@@ -343,7 +345,7 @@ impl AiToolAdapter for ClaudeCodeAdapter {
                     order: 1,
                     required: true,
                     placeholders: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert(
                             "project_name".to_string(),
                             "Name of the project from PRD".to_string(),
@@ -362,7 +364,7 @@ impl AiToolAdapter for ClaudeCodeAdapter {
                     order: 2,
                     required: true,
                     placeholders: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert(
                             "coordination_instructions".to_string(),
                             "Instructions for agent coordination through Axon MCP".to_string(),
@@ -378,7 +380,7 @@ impl AiToolAdapter for ClaudeCodeAdapter {
                 "Specify task handoff procedures".to_string(),
             ],
             examples: {
-                let mut examples = HashMap::new();
+                let mut examples = BTreeMap::new();
                 examples.insert("coordination_example".to_string(),
                     "1. Use list_tasks to find your assigned tasks\n2. Use claim_task to claim available work\n3. Use create_task_message for handoffs".to_string());
                 examples
@@ -413,6 +415,8 @@ impl AiToolAdapter for ClaudeCodeAdapter {
             file_name: self.config.main_file_name.clone(),
             content: content.to_string(),
             sections,
+            token_estimate: None,
+            section_merge: None,
         })
     }
 
@@ -438,40 +442,17 @@ impl AiToolAdapter for ClaudeCodeAdapter {
                 None,
             );
 
-            // Create enhanced agent file content
-            let _file_content = format!(
-                r#"# Agent: {agent_name}
-
-## Role Contract
-{enhanced_prompt}
-
-## Configuration
-- **Name**: {agent_name}
-- **Type**: {ai_tool_type}
-- **Capabilities**: {capabilities}
-- **Dependencies**: {dependencies}
-
-## Usage
-This agent should be instantiated with the above prompt as the system message.
-The contract defines clear expectations, coordination protocols, and escalation procedures.
-
-## 2025 Enhancements
-- Structured contracts with measurable success criteria
-- Lightweight communication protocols with MCP functions
-- Dynamic effort scaling through micro-iterations
-- Clear error handling and escalation procedures
-- Context scoping to prevent information overload
-"#,
-                agent_name = agent.name,
-                enhanced_prompt = enhanced_prompt,
-                ai_tool_type = agent.ai_tool_type,
-                capabilities = agent.capabilities.join(", "),
-                dependencies = agent.dependencies.join(", "),
-            );
+            // Claude Code sub-agent file with the enhanced prompt as system prompt
+            let _file_content = render_subagent_file(&AgentRegistration {
+                prompt: enhanced_prompt,
+                ..agent.clone()
+            });
 
             let file_path = format!(
                 "{}/{}/{}.md",
-                output_dir, self.config.agents_dir, agent.name
+                output_dir,
+                self.config.agents_dir,
+                subagent_name(&agent.name)
             );
 
             // In a real implementation, we would write the file here
@@ -532,7 +513,7 @@ The contract defines clear expectations, coordination protocols, and escalation
         };
 
         Ok(WorkspaceManifest {
-            schema_version: "1.0".to_string(),
+            schema_version: MANIFEST_SCHEMA_VERSION.to_string(),
             ai_tool_type: AiToolType::ClaudeCode,
             project: ProjectMetadata {
                 name: prd.title.clone(),
@@ -580,7 +561,7 @@ The contract defines clear expectations, coordination protocols, and escalation
             issues.push(ValidationIssue {
                 severity: ValidationSeverity::Warning,
                 description: "Agents directory is missing".to_string(),
-                location: Some(agents_dir_path),
+                location: Some(agents_dir_path.clone()),
             });
             recommendations.push(format!(
                 "Create {} directory with agent definition files",
@@ -588,6 +569,37 @@ The contract defines clear expectations, coordination protocols, and escalation
             ));
         }
 
+        // Check the frontmatter of each sub-agent file
+        if let Ok(entries) = std::fs::read_dir(&agents_dir_path) {
+            let mut agent_files: Vec<_> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                .collect();
+            agent_files.sort();
+            for path in agent_files {
+                let location = path.display().to_string();
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => issues.extend(validate_subagent_file(&content, &location)),
+                    Err(e) => issues.push(ValidationIssue {
+                        severity: ValidationSeverity::Warning,
+                        description: format!("Agent file cannot be read: {e}"),
+                        location: Some(location),
+                    }),
+                }
+            }
+            if issues.iter().any(|issue| {
+                issue
+                    .location
+                    .as_deref()
+                    .is_some_and(|location| location.starts_with(&agents_dir_path))
+            }) {
+                recommendations.push(
+                    "Regenerate agent files with YAML frontmatter (name, description, tools)"
+                        .to_string(),
+                );
+            }
+        }
+
         let is_valid = issues
             .iter()
             .all(|issue| !matches!(issue.severity, ValidationSeverity::Critical));
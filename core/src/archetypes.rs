@@ -0,0 +1,218 @@
+//! User-defined project archetypes
+//!
+//! Organizations can describe their own project types in
+//! `.axon/archetypes.yaml` instead of patching the built-in classification.
+//! Each archetype lists the keywords and regular expressions that identify it
+//! in a PRD, the agents to suggest and the workflow to follow:
+//!
+//! ```yaml
+//! archetypes:
+//!   - name: Game
+//!     keywords: [godot, "game engine"]
+//!     patterns: ['\bbevy\b']
+//!     agents:
+//!       - name: gameplay-programmer
+//!         description: Implements game mechanics
+//!         capabilities: [gameplay, rust]
+//!       - name: level-designer
+//!         description: Builds and tunes levels
+//!         capabilities: [level-design]
+//!         depends_on: [gameplay-programmer]
+//!     workflow_steps:
+//!       - 1. Prototype the core loop - gameplay-programmer
+//!       - 2. Build the first level - level-designer
+//! ```
+//!
+//! Custom archetypes are tried in file order before the built-in ones, so an
+//! entry can also take over projects the built-in rules would classify.
+
+use crate::workspace_setup::{SuggestedAgent, WorkspaceSetupError, WorkspaceSetupResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Location of the custom archetypes inside the project root
+pub const ARCHETYPES_FILE: &str = ".axon/archetypes.yaml";
+
+/// Agent suggested for projects of a custom archetype
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchetypeAgent {
+    pub name: String,
+    pub description: String,
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Share of the work in percent; agents without one split the remainder evenly
+    #[serde(default)]
+    pub workload_percentage: Option<f32>,
+}
+
+/// A project archetype defined in `.axon/archetypes.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomArchetype {
+    pub name: String,
+    /// Case-insensitive phrases, any of which identifies the archetype
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Case-insensitive regular expressions, any of which identifies the archetype
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    pub agents: Vec<ArchetypeAgent>,
+    #[serde(default)]
+    pub workflow_steps: Vec<String>,
+    #[serde(default)]
+    pub coordination_patterns: Vec<String>,
+    #[serde(default)]
+    pub task_decomposition_strategy: Option<String>,
+    #[serde(skip)]
+    compiled_patterns: Vec<Regex>,
+}
+
+impl CustomArchetype {
+    /// Whether the lower-cased classification text describes this archetype
+    pub fn matches(&self, content_lower: &str) -> bool {
+        !self.matched_signals(content_lower).is_empty()
+    }
+
+    /// Keywords and patterns of this archetype found in the lower-cased classification text
+    pub fn matched_signals(&self, content_lower: &str) -> Vec<String> {
+        let keywords = self
+            .keywords
+            .iter()
+            .filter(|keyword| content_lower.contains(&keyword.to_lowercase()));
+        let patterns = self
+            .patterns
+            .iter()
+            .zip(&self.compiled_patterns)
+            .filter(|(_, compiled)| compiled.is_match(content_lower))
+            .map(|(pattern, _)| pattern);
+        keywords.chain(patterns).cloned().collect()
+    }
+
+    /// The archetype's agents, at most `max_agents` of them
+    pub fn suggested_agents(&self, max_agents: usize) -> Vec<SuggestedAgent> {
+        let agents = &self.agents[..self.agents.len().min(max_agents)];
+        let assigned: f32 = agents.iter().filter_map(|a| a.workload_percentage).sum();
+        let unassigned = agents
+            .iter()
+            .filter(|a| a.workload_percentage.is_none())
+            .count();
+        let even_share = (100.0 - assigned).max(0.0) / unassigned.max(1) as f32;
+
+        agents
+            .iter()
+            .map(|agent| SuggestedAgent {
+                name: agent.name.clone(),
+                description: agent.description.clone(),
+                required_capabilities: agent.capabilities.clone(),
+                workload_percentage: agent.workload_percentage.unwrap_or(even_share),
+                depends_on: agent.depends_on.clone(),
+            })
+            .collect()
+    }
+
+    fn validate(&mut self) -> WorkspaceSetupResult<()> {
+        let invalid = |reason: String| {
+            WorkspaceSetupError::InvalidConfiguration(format!(
+                "{ARCHETYPES_FILE}: archetype '{}' {reason}",
+                self.name
+            ))
+        };
+        if self.name.trim().is_empty() {
+            return Err(invalid("has no name".to_string()));
+        }
+        if self.keywords.is_empty() && self.patterns.is_empty() {
+            return Err(invalid("needs at least one keyword or pattern".to_string()));
+        }
+        if self.agents.is_empty() {
+            return Err(invalid("defines no agents".to_string()));
+        }
+        if let Some(agent) = self.agents.iter().find(|a| a.capabilities.is_empty()) {
+            return Err(invalid(format!(
+                "gives agent '{}' no capabilities",
+                agent.name
+            )));
+        }
+        self.compiled_patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(&format!("(?i){pattern}"))
+                    .map_err(|e| invalid(format!("has an invalid pattern '{pattern}': {e}")))
+            })
+            .collect::<WorkspaceSetupResult<_>>()?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct ArchetypesFile {
+    #[serde(default)]
+    archetypes: Vec<CustomArchetype>,
+}
+
+/// Parse and validate custom archetypes from YAML
+pub fn parse_archetypes(yaml: &str) -> WorkspaceSetupResult<Vec<CustomArchetype>> {
+    let file: ArchetypesFile = serde_yaml::from_str(yaml).map_err(|e| {
+        WorkspaceSetupError::InvalidConfiguration(format!("{ARCHETYPES_FILE}: {e}"))
+    })?;
+    let mut archetypes = file.archetypes;
+    for archetype in &mut archetypes {
+        archetype.validate()?;
+    }
+    Ok(archetypes)
+}
+
+/// Custom archetypes of the project at `project_root`; none when it has no archetypes file
+pub fn load_archetypes(project_root: &Path) -> WorkspaceSetupResult<Vec<CustomArchetype>> {
+    let path = project_root.join(ARCHETYPES_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let yaml = std::fs::read_to_string(&path)
+        .map_err(|e| WorkspaceSetupError::FileSystemError(format!("{}: {}", path.display(), e)))?;
+    parse_archetypes(&yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_archetypes() {
+        let archetypes = parse_archetypes(
+            r#"
+archetypes:
+  - name: Game
+    keywords: [Godot]
+    patterns: ['\bbevy\b']
+    agents:
+      - name: gameplay-programmer
+        description: Implements game mechanics
+        capabilities: [gameplay]
+        workload_percentage: 60
+      - name: level-designer
+        description: Builds levels
+        capabilities: [level-design]
+      - name: sound-designer
+        description: Creates audio
+        capabilities: [audio]
+"#,
+        )
+        .unwrap();
+        let game = &archetypes[0];
+        assert!(game.matches("a platformer built with godot"));
+        assert!(game.matches("an ecs game on bevy 0.14"));
+        assert!(!game.matches("a bevyhouse booking site"));
+
+        let agents = game.suggested_agents(2);
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].workload_percentage, 60.0);
+        assert_eq!(agents[1].workload_percentage, 40.0);
+
+        let no_rules = "archetypes:\n  - name: Game\n    agents: []\n";
+        assert!(parse_archetypes(no_rules).is_err());
+        let bad_pattern = "archetypes:\n  - name: Game\n    patterns: ['(']\n    agents:\n      - {name: a, description: b, capabilities: [c]}\n";
+        assert!(parse_archetypes(bad_pattern).is_err());
+    }
+}
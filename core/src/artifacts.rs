@@ -0,0 +1,220 @@
+//! Files produced by tasks
+//!
+//! Agents register the files a task produced, so a finished task carries an
+//! auditable record of its output. Every path is resolved inside the project
+//! root and the file's SHA-256 is recorded; a checksum given by the agent must
+//! match the file on disk. Reviewers list the artifacts of a task and see
+//! which files changed or disappeared since they were registered.
+
+use crate::error::{Result, TaskError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Prefix of recorded checksums
+pub const CHECKSUM_PREFIX: &str = "sha256:";
+
+/// Longest artifact kind accepted
+pub const MAX_ARTIFACT_KIND_LENGTH: usize = 32;
+
+/// A file produced by a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskArtifact {
+    pub id: i32,
+    pub task_code: String,
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// What the file is, e.g. `code`, `test`, `doc` or `config`
+    pub kind: String,
+    /// `sha256:` followed by the lowercase hex digest of the file
+    pub checksum: String,
+    pub size_bytes: u64,
+    /// Agent that registered the file
+    pub registered_by: Option<String>,
+    /// When the file was last registered; registering a path again updates it
+    pub registered_at: DateTime<Utc>,
+}
+
+/// Which artifacts to list
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtifactFilter {
+    pub task_code: Option<String>,
+    pub kind: Option<String>,
+    /// Maximum number of artifacts
+    pub limit: Option<u32>,
+}
+
+/// How a registered file compares with the file on disk now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactStatus {
+    Unchanged,
+    Modified,
+    Missing,
+}
+
+/// A registered artifact, checked against the project when requested
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListedArtifact {
+    #[serde(flatten)]
+    pub artifact: TaskArtifact,
+    /// Present when the artifacts were verified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ArtifactStatus>,
+}
+
+/// Check an artifact kind: a short lowercase word with `-` or `_`
+pub fn validate_artifact_kind(kind: &str) -> Result<()> {
+    if kind.is_empty()
+        || kind.len() > MAX_ARTIFACT_KIND_LENGTH
+        || !kind
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(TaskError::Validation(format!(
+            "artifact kind '{kind}' must be 1 to {MAX_ARTIFACT_KIND_LENGTH} lowercase letters, digits, '-' or '_'"
+        )));
+    }
+    Ok(())
+}
+
+/// Canonical form of a checksum given as `sha256:<hex>` or bare hex
+pub fn normalize_checksum(checksum: &str) -> Result<String> {
+    let hex = checksum.trim();
+    let hex = hex.strip_prefix(CHECKSUM_PREFIX).unwrap_or(hex);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(TaskError::Validation(format!(
+            "checksum '{checksum}' is not a SHA-256 hex digest"
+        )));
+    }
+    Ok(format!("{CHECKSUM_PREFIX}{}", hex.to_ascii_lowercase()))
+}
+
+/// Resolve `path` to an existing file inside `root`
+///
+/// Returns the path relative to the root with `/` separators, and the full
+/// path. Absolute paths, `..` and symlinks leading out of the root are rejected.
+pub fn resolve_artifact_path(root: &Path, path: &str) -> Result<(String, PathBuf)> {
+    let invalid = |reason: &str| TaskError::Validation(format!("artifact path '{path}' {reason}"));
+    let relative = Path::new(path.trim());
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return Err(invalid("must be relative to the project root")),
+        }
+    }
+    if parts.is_empty() {
+        return Err(invalid("is empty"));
+    }
+
+    let root = root
+        .canonicalize()
+        .map_err(|e| TaskError::Validation(format!("project root {}: {e}", root.display())))?;
+    let full = root.join(parts.join("/"));
+    let resolved = full
+        .canonicalize()
+        .map_err(|_| invalid("does not exist in the project root"))?;
+    if !resolved.starts_with(&root) {
+        return Err(invalid("leads out of the project root"));
+    }
+    if !resolved.is_file() {
+        return Err(invalid("is not a file"));
+    }
+    Ok((parts.join("/"), full))
+}
+
+/// Checksum and size of the file at `path`
+pub fn file_checksum(path: &Path) -> Result<(String, u64)> {
+    let io_error = |e: std::io::Error| TaskError::Internal(format!("{}: {e}", path.display()));
+    let mut file = std::fs::File::open(path).map_err(io_error)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer).map_err(io_error)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((
+        format!("{CHECKSUM_PREFIX}{}", hex::encode(hasher.finalize())),
+        size,
+    ))
+}
+
+/// Compare a registered artifact with the file in `root`
+pub fn artifact_status(root: &Path, artifact: &TaskArtifact) -> ArtifactStatus {
+    match resolve_artifact_path(root, &artifact.path).and_then(|(_, full)| file_checksum(&full)) {
+        Ok((checksum, _)) if checksum == artifact.checksum => ArtifactStatus::Unchanged,
+        Ok(_) => ArtifactStatus::Modified,
+        Err(_) => ArtifactStatus::Missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_checksum() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+
+        let (relative, full) = resolve_artifact_path(root.path(), "./src/lib.rs").unwrap();
+        assert_eq!(relative, "src/lib.rs");
+        for outside in ["../etc/passwd", "/etc/passwd", "src/missing.rs", "src"] {
+            assert!(
+                resolve_artifact_path(root.path(), outside).is_err(),
+                "{outside}"
+            );
+        }
+
+        let (checksum, size) = file_checksum(&full).unwrap();
+        assert_eq!(size, 14);
+        assert_eq!(
+            normalize_checksum(&checksum[7..].to_uppercase()).unwrap(),
+            checksum
+        );
+        assert!(normalize_checksum("sha256:abc").is_err());
+
+        let mut artifact = TaskArtifact {
+            id: 1,
+            task_code: "API-01".to_string(),
+            path: relative,
+            kind: "code".to_string(),
+            checksum,
+            size_bytes: size,
+            registered_by: None,
+            registered_at: Utc::now(),
+        };
+        assert_eq!(
+            artifact_status(root.path(), &artifact),
+            ArtifactStatus::Unchanged
+        );
+        std::fs::write(root.path().join("src/lib.rs"), "pub fn g() {}\n").unwrap();
+        assert_eq!(
+            artifact_status(root.path(), &artifact),
+            ArtifactStatus::Modified
+        );
+        artifact.path = "src/gone.rs".to_string();
+        assert_eq!(
+            artifact_status(root.path(), &artifact),
+            ArtifactStatus::Missing
+        );
+    }
+
+    #[test]
+    fn test_artifact_kind() {
+        assert!(validate_artifact_kind("test-report").is_ok());
+        assert!(validate_artifact_kind("Code").is_err());
+        assert!(validate_artifact_kind("").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Capability catalog and normalization of free-text capabilities
+//!
+//! Agents and task authors describe capabilities in free text ("Rust",
+//! "rust_dev", "Backend Development"). The catalog maps those spellings onto
+//! canonical capability names, so that work discovery compares like with like.
+
+use crate::error::{Result, TaskError};
+use crate::models::Capability;
+
+/// Maximum length of a capability name or alias
+pub const MAX_CAPABILITY_LENGTH: usize = 64;
+
+/// Reduce a free-text capability to its canonical spelling
+///
+/// Lowercases it and joins the words separated by whitespace, `_` or `-` with
+/// single hyphens, so "Backend  Development" becomes `backend-development`.
+pub fn capability_key(raw: &str) -> String {
+    raw.split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Validate a capability name or alias and return its canonical spelling
+pub fn validate_capability(raw: &str) -> Result<String> {
+    let key = capability_key(raw);
+    if key.is_empty() {
+        return Err(TaskError::Validation(
+            "Capability name cannot be empty".to_string(),
+        ));
+    }
+    if key.len() > MAX_CAPABILITY_LENGTH {
+        return Err(TaskError::Validation(format!(
+            "Capability '{key}' exceeds {MAX_CAPABILITY_LENGTH} characters"
+        )));
+    }
+    Ok(key)
+}
+
+/// The defined capabilities, used to normalize free-text capabilities
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityCatalog {
+    capabilities: Vec<Capability>,
+}
+
+impl CapabilityCatalog {
+    /// Build a catalog from the defined capabilities, including deprecated ones
+    pub fn new(capabilities: Vec<Capability>) -> Self {
+        Self { capabilities }
+    }
+
+    /// Find the capability named or aliased by `raw`
+    pub fn lookup(&self, raw: &str) -> Option<&Capability> {
+        let key = capability_key(raw);
+        self.capabilities
+            .iter()
+            .find(|c| c.name == key || c.aliases.contains(&key))
+    }
+
+    /// Canonical name of a free-text capability
+    ///
+    /// Aliases resolve to their capability and deprecated capabilities to their
+    /// replacement. Unknown capabilities keep their canonical spelling.
+    pub fn normalize(&self, raw: &str) -> String {
+        let mut current = match self.lookup(raw) {
+            Some(capability) => capability,
+            None => return capability_key(raw),
+        };
+        // Follow replacements, at most once per capability in case of cycles
+        for _ in 0..self.capabilities.len() {
+            match current
+                .replaced_by
+                .as_deref()
+                .filter(|_| current.is_deprecated())
+                .and_then(|next| self.lookup(next))
+            {
+                Some(next) if next.name != current.name => current = next,
+                _ => break,
+            }
+        }
+        current.name.clone()
+    }
+
+    /// Normalize a list of capabilities, dropping blanks and duplicates
+    pub fn normalize_all(&self, raw: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(raw.len());
+        for capability in raw {
+            let name = self.normalize(capability);
+            if !name.is_empty() && !normalized.contains(&name) {
+                normalized.push(name);
+            }
+        }
+        normalized
+    }
+
+    /// Check that the aliases of capability `name` don't belong to another capability
+    pub fn check_aliases(&self, name: &str, aliases: &[String]) -> Result<()> {
+        for alias in aliases {
+            if let Some(owner) = self.lookup(alias).filter(|owner| owner.name != name) {
+                return Err(TaskError::DuplicateKey(format!(
+                    "'{alias}' already refers to capability '{}'",
+                    owner.name
+                )));
+            }
+        }
+        if let Some(owner) = self
+            .capabilities
+            .iter()
+            .find(|c| c.aliases.iter().any(|alias| alias == name))
+        {
+            if owner.name != name {
+                return Err(TaskError::DuplicateKey(format!(
+                    "'{name}' is already an alias of capability '{}'",
+                    owner.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn capability(name: &str, aliases: &[&str], replaced_by: Option<&str>) -> Capability {
+        Capability {
+            name: name.to_string(),
+            description: String::new(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            replaced_by: replaced_by.map(str::to_string),
+            deprecated_at: replaced_by.map(|_| Utc::now()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_capability_key() {
+        assert_eq!(
+            capability_key("  Backend  Development "),
+            "backend-development"
+        );
+        assert_eq!(capability_key("rust_dev"), "rust-dev");
+        assert_eq!(capability_key("--"), "");
+        assert!(validate_capability(" _ ").is_err());
+        assert!(validate_capability(&"x".repeat(MAX_CAPABILITY_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_against_catalog() {
+        let catalog = CapabilityCatalog::new(vec![
+            capability("rust", &["rust-lang", "rustlang"], None),
+            capability("frontend", &["ui"], None),
+            capability("web-ui", &["html"], Some("frontend")),
+        ]);
+
+        assert_eq!(catalog.normalize("Rust_Lang"), "rust");
+        assert_eq!(catalog.normalize("HTML"), "frontend");
+        assert_eq!(catalog.normalize("Machine Learning"), "machine-learning");
+        assert_eq!(
+            catalog.normalize_all(&["RustLang".into(), "rust".into(), " ".into(), "UI".into()]),
+            vec!["rust".to_string(), "frontend".to_string()]
+        );
+
+        assert!(catalog.check_aliases("go", &["golang".into()]).is_ok());
+        assert!(catalog.check_aliases("go", &["rustlang".into()]).is_err());
+        assert!(catalog.check_aliases("ui", &[]).is_err());
+        assert!(catalog.check_aliases("rust", &["rust-lang".into()]).is_ok());
+    }
+}
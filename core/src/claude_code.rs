@@ -0,0 +1,90 @@
+//! Registration of the server in Claude Code's MCP settings
+//!
+//! Claude Code keeps per-project MCP servers in the user's settings file
+//! (`~/.claude.json`) under `projects.<project root>.mcpServers`. The helpers
+//! here compute the entry for a running server and merge it into the parsed
+//! settings without touching anything else; reading, backing up and writing
+//! the file is left to the caller.
+
+use crate::error::{Result, TaskError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Name of the server entry in the Claude Code settings
+pub const CLAUDE_CODE_SERVER_NAME: &str = "axon-mcp";
+
+/// `mcpServers` entry of a server listening on `port` of localhost
+pub fn http_server_entry(port: u16) -> Value {
+    json!({
+        "type": "http",
+        "url": format!("http://127.0.0.1:{port}/mcp"),
+    })
+}
+
+/// Outcome of registering the server in a Claude Code settings file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaudeCodeRegistration {
+    pub settings_path: String,
+    pub project_root: String,
+    pub server_name: String,
+    pub entry: Value,
+    /// Entry replaced by the registration, if the server was registered before
+    pub previous_entry: Option<Value>,
+    /// Whether the settings differ from the file (false when already registered)
+    pub changed: bool,
+    /// Copy of the settings file taken before it was rewritten
+    pub backup_path: Option<String>,
+    /// The settings file was left untouched
+    pub dry_run: bool,
+}
+
+/// Set the server entry of `project_root` in parsed settings, returning the entry it replaces
+pub fn upsert_project_server(
+    settings: &mut Value,
+    project_root: &str,
+    entry: Value,
+) -> Result<Option<Value>> {
+    let servers = child_object(settings, "projects")
+        .and_then(|projects| child_object(projects, project_root))
+        .and_then(|project| child_object(project, "mcpServers"))
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| {
+            TaskError::Validation(format!(
+                "Claude Code settings have no object at projects.{project_root}.mcpServers"
+            ))
+        })?;
+    Ok(servers.insert(CLAUDE_CODE_SERVER_NAME.to_string(), entry))
+}
+
+/// Member `key` of a JSON object, inserted as an empty object if missing
+fn child_object<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    let child = value
+        .as_object_mut()?
+        .entry(key)
+        .or_insert_with(|| json!({}));
+    child.is_object().then_some(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upsert_project_server() {
+        let mut settings = json!({"theme": "dark", "projects": {"/other": {"mcpServers": {}}}});
+        let previous = upsert_project_server(&mut settings, "/work", http_server_entry(8499));
+        assert_eq!(previous.unwrap(), None);
+        assert_eq!(settings["theme"], "dark");
+        assert_eq!(
+            settings["projects"]["/work"]["mcpServers"]["axon-mcp"]["url"],
+            "http://127.0.0.1:8499/mcp"
+        );
+
+        let previous = upsert_project_server(&mut settings, "/work", http_server_entry(3000));
+        assert_eq!(previous.unwrap(), Some(http_server_entry(8499)));
+        assert_eq!(settings["projects"].as_object().unwrap().len(), 2);
+
+        let mut settings = json!({"projects": []});
+        assert!(upsert_project_server(&mut settings, "/work", http_server_entry(1)).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Claude Code sub-agent files
+//!
+//! Claude Code loads sub-agents from `.claude/agents/<name>.md`. Each file
+//! starts with YAML frontmatter naming the agent, telling the main agent when
+//! to delegate to it and optionally restricting the tools it may use; the
+//! Markdown after the frontmatter is the sub-agent's system prompt:
+//!
+//! ```markdown
+//! ---
+//! name: backend-dev
+//! description: Backend development agent. Use for tasks requiring api, database.
+//! tools: Read, Grep, Glob, Edit, Write, Bash
+//! ---
+//!
+//! You are backend-dev...
+//! ```
+
+use crate::ai_tool_adapters::{ValidationIssue, ValidationSeverity};
+use crate::claude_code::CLAUDE_CODE_SERVER_NAME;
+use crate::workspace_setup::AgentRegistration;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+/// Built-in Claude Code tools a sub-agent may be granted
+pub const CLAUDE_CODE_TOOLS: &[&str] = &[
+    "Bash",
+    "Edit",
+    "Glob",
+    "Grep",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "Read",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// Values of the optional `model` field
+pub const SUBAGENT_MODELS: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+/// Axon functions every generated sub-agent needs to coordinate
+const COORDINATION_FUNCTIONS: &[&str] = &[
+    "list_tasks",
+    "discover_work",
+    "claim_task",
+    "get_task_context",
+    "start_work_session",
+    "end_work_session",
+    "set_task_state",
+    "create_task_message",
+    "get_task_messages",
+];
+
+/// Capability keywords of agents that only inspect the code
+const READ_ONLY_KEYWORDS: &[&str] = &["review", "audit", "analysis", "research", "planning"];
+
+/// Capability keywords of agents that run commands even when they edit nothing
+const COMMAND_KEYWORDS: &[&str] = &["test", "qa", "security", "performance", "devops", "deploy"];
+
+/// Capability keywords of agents that consult the web
+const WEB_KEYWORDS: &[&str] = &["research", "documentation", "docs", "integration"];
+
+/// Frontmatter keys Claude Code understands
+const FRONTMATTER_KEYS: &[&str] = &["name", "description", "tools", "model", "color"];
+
+/// Frontmatter of a sub-agent file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubagentFrontmatter {
+    /// Lower-case, hyphen-separated identifier
+    pub name: String,
+    /// When the main agent should delegate to the sub-agent, on a single line
+    pub description: String,
+    /// Comma-separated tool allowlist; all tools are inherited when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl SubagentFrontmatter {
+    /// Frontmatter describing a registered agent
+    pub fn from_registration(agent: &AgentRegistration) -> Self {
+        let capabilities = agent.capabilities.join(", ");
+        let description = agent
+            .description
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let description = match (description.is_empty(), capabilities.is_empty()) {
+            (true, true) => format!("Axon agent {}", agent.name),
+            (true, false) => format!("Axon agent for tasks requiring {capabilities}"),
+            (false, true) => description,
+            (false, false) => format!(
+                "{}. Use for tasks requiring {capabilities}.",
+                description.trim_end_matches('.')
+            ),
+        };
+        Self {
+            name: subagent_name(&agent.name),
+            description,
+            tools: Some(subagent_tools(&agent.capabilities).join(", ")),
+            model: None,
+        }
+    }
+}
+
+/// Sub-agent file of a registered agent, with its prompt as the system prompt
+pub fn render_subagent_file(agent: &AgentRegistration) -> String {
+    let frontmatter = SubagentFrontmatter::from_registration(agent);
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    format!("---\n{yaml}---\n\n{}\n", agent.prompt.trim())
+}
+
+/// `name` in the lower-case, hyphen-separated form Claude Code expects
+pub fn subagent_name(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Tool allowlist of an agent with `capabilities`
+///
+/// Every agent may read the code and use the Axon coordination functions.
+/// Agents whose capabilities are all review or research work get no editing
+/// tools; the others may edit files and run commands.
+pub fn subagent_tools(capabilities: &[String]) -> Vec<String> {
+    let has = |keywords: &[&str], capability: &String| {
+        let capability = capability.to_lowercase();
+        keywords.iter().any(|keyword| capability.contains(keyword))
+    };
+    let read_only = !capabilities.is_empty()
+        && capabilities
+            .iter()
+            .all(|capability| has(READ_ONLY_KEYWORDS, capability));
+    let runs_commands = capabilities
+        .iter()
+        .any(|capability| has(COMMAND_KEYWORDS, capability));
+
+    let mut tools = vec!["Read", "Grep", "Glob", "TodoWrite"];
+    if !read_only {
+        tools.extend(["Edit", "MultiEdit", "Write"]);
+    }
+    if !read_only || runs_commands {
+        tools.push("Bash");
+    }
+    if capabilities
+        .iter()
+        .any(|capability| has(WEB_KEYWORDS, capability))
+    {
+        tools.extend(["WebFetch", "WebSearch"]);
+    }
+    tools
+        .into_iter()
+        .map(str::to_string)
+        .chain(
+            COORDINATION_FUNCTIONS
+                .iter()
+                .map(|function| format!("mcp__{CLAUDE_CODE_SERVER_NAME}__{function}")),
+        )
+        .collect()
+}
+
+/// Check a sub-agent file against the format Claude Code loads
+///
+/// `location` is reported with each issue. Files without parseable
+/// frontmatter, a valid name or a description are critical issues because
+/// Claude Code skips them.
+pub fn validate_subagent_file(content: &str, location: &str) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |severity: ValidationSeverity, description: String| {
+        issues.push(ValidationIssue {
+            severity,
+            description,
+            location: Some(location.to_string()),
+        })
+    };
+
+    let Some((yaml, body)) = split_frontmatter(content) else {
+        issue(
+            ValidationSeverity::Critical,
+            "Sub-agent file has no YAML frontmatter delimited by '---' lines".to_string(),
+        );
+        return issues;
+    };
+    let frontmatter = match serde_yaml::from_str::<Value>(yaml) {
+        Ok(Value::Mapping(mapping)) => mapping,
+        Ok(_) => {
+            issue(
+                ValidationSeverity::Critical,
+                "Sub-agent frontmatter is not a mapping".to_string(),
+            );
+            return issues;
+        }
+        Err(e) => {
+            issue(
+                ValidationSeverity::Critical,
+                format!("Sub-agent frontmatter is not valid YAML: {e}"),
+            );
+            return issues;
+        }
+    };
+    let field = |key: &str| frontmatter.get(key);
+
+    match field("name").and_then(Value::as_str) {
+        None => issue(
+            ValidationSeverity::Critical,
+            "Sub-agent frontmatter has no 'name'".to_string(),
+        ),
+        Some(name) if subagent_name(name) != name => issue(
+            ValidationSeverity::Critical,
+            format!("Sub-agent name '{name}' must be lower-case words joined by hyphens"),
+        ),
+        Some(_) => {}
+    }
+
+    match field("description").and_then(Value::as_str) {
+        Some(description) if description.trim().is_empty() => issue(
+            ValidationSeverity::Critical,
+            "Sub-agent description is empty".to_string(),
+        ),
+        Some(description) if description.trim().contains('\n') => issue(
+            ValidationSeverity::Warning,
+            "Sub-agent description spans several lines".to_string(),
+        ),
+        Some(_) => {}
+        None => issue(
+            ValidationSeverity::Critical,
+            "Sub-agent frontmatter has no 'description'".to_string(),
+        ),
+    }
+
+    match field("tools") {
+        None => {}
+        Some(Value::String(tools)) => {
+            for tool in tools.split(',').map(str::trim) {
+                if tool.is_empty() {
+                    issue(
+                        ValidationSeverity::Warning,
+                        "Sub-agent tool list has an empty entry".to_string(),
+                    );
+                } else if !CLAUDE_CODE_TOOLS.contains(&tool) && !tool.starts_with("mcp__") {
+                    issue(
+                        ValidationSeverity::Warning,
+                        format!("Sub-agent tool '{tool}' is not a Claude Code or MCP tool"),
+                    );
+                }
+            }
+        }
+        Some(_) => issue(
+            ValidationSeverity::Critical,
+            "Sub-agent 'tools' must be a comma-separated string".to_string(),
+        ),
+    }
+
+    if let Some(model) = field("model") {
+        if !model
+            .as_str()
+            .is_some_and(|model| SUBAGENT_MODELS.contains(&model))
+        {
+            issue(
+                ValidationSeverity::Warning,
+                format!(
+                    "Sub-agent model must be one of {}",
+                    SUBAGENT_MODELS.join(", ")
+                ),
+            );
+        }
+    }
+
+    for key in frontmatter.keys() {
+        let key = key.as_str().unwrap_or_default();
+        if !FRONTMATTER_KEYS.contains(&key) {
+            issue(
+                ValidationSeverity::Info,
+                format!("Sub-agent frontmatter key '{key}' is ignored by Claude Code"),
+            );
+        }
+    }
+
+    if body.trim().is_empty() {
+        issue(
+            ValidationSeverity::Warning,
+            "Sub-agent file has no system prompt after the frontmatter".to_string(),
+        );
+    }
+    issues
+}
+
+/// Frontmatter and body of a file starting with a `---` delimited block
+fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace_setup::AiToolType;
+
+    fn agent(name: &str, description: &str, capabilities: &[&str]) -> AgentRegistration {
+        AgentRegistration {
+            name: name.to_string(),
+            description: description.to_string(),
+            prompt: "You are an agent.\n\nClaim tasks.".to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_subagent_file() {
+        let backend = agent(
+            "Backend_Dev",
+            "Builds the API: endpoints\nand storage",
+            &["api"],
+        );
+        let file = render_subagent_file(&backend);
+        assert!(file.starts_with("---\nname: backend-dev\n"), "{file}");
+        assert!(file.ends_with("---\n\nYou are an agent.\n\nClaim tasks.\n"));
+        assert!(validate_subagent_file(&file, "backend-dev.md").is_empty());
+
+        let frontmatter = SubagentFrontmatter::from_registration(&backend);
+        assert_eq!(
+            frontmatter.description,
+            "Builds the API: endpoints and storage. Use for tasks requiring api."
+        );
+        let tools = frontmatter.tools.unwrap();
+        assert!(tools.starts_with("Read, Grep, Glob, TodoWrite, Edit, MultiEdit, Write, Bash, "));
+        assert!(tools.contains("mcp__axon-mcp__claim_task"));
+
+        let reviewer = subagent_tools(&["code-review".to_string(), "security-audit".to_string()]);
+        assert!(reviewer.contains(&"Bash".to_string()));
+        assert!(!reviewer.contains(&"Edit".to_string()));
+        let researcher = subagent_tools(&["research".to_string()]);
+        assert!(researcher.contains(&"WebSearch".to_string()));
+        assert!(!researcher.contains(&"Bash".to_string()));
+    }
+
+    #[test]
+    fn test_validate_subagent_file() {
+        let severities = |content: &str| {
+            validate_subagent_file(content, "agent.md")
+                .into_iter()
+                .map(|issue| format!("{:?}: {}", issue.severity, issue.description))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            severities("# Agent: qa\nNo frontmatter."),
+            ["Critical: Sub-agent file has no YAML frontmatter delimited by '---' lines"]
+        );
+        assert_eq!(
+            severities("---\nname: QA Agent\n---\nPrompt"),
+            [
+                "Critical: Sub-agent name 'QA Agent' must be lower-case words joined by hyphens",
+                "Critical: Sub-agent frontmatter has no 'description'",
+            ]
+        );
+        assert_eq!(
+            severities(
+                "---\nname: qa\ndescription: Tests\ntools: Read, Deploy\nmodel: gpt\n\
+                 color: red\nowner: me\n---\n"
+            ),
+            [
+                "Warning: Sub-agent tool 'Deploy' is not a Claude Code or MCP tool",
+                "Warning: Sub-agent model must be one of sonnet, opus, haiku, inherit",
+                "Info: Sub-agent frontmatter key 'owner' is ignored by Claude Code",
+                "Warning: Sub-agent file has no system prompt after the frontmatter",
+            ]
+        );
+        assert_eq!(
+            severities("---\nname: qa\ndescription: Tests\ntools: [Read]\n---\nPrompt"),
+            ["Critical: Sub-agent 'tools' must be a comma-separated string"]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! TODO comments of a codebase as tasks
+//!
+//! Projects joining the server usually carry their backlog in the code:
+//! `TODO`, `FIXME` and `HACK` comments. Scanning the project root, with
+//! `.gitignore` and hidden files respected, turns each comment into a
+//! proposed task. Proposed codes hash the file and the comment text, so they
+//! stay the same when the comment moves within its file and scanning again
+//! does not propose tasks that were already imported.
+
+use crate::error::{Result, TaskError};
+use crate::models::{NewTask, Task};
+use crate::workspace_setup::ResponseStatus;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Files larger than this are not scanned
+pub const MAX_SCANNED_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Longest task name proposed for a comment
+const MAX_TODO_NAME_CHARS: usize = 120;
+
+/// Kind of code comment imported as a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TodoMarker {
+    Todo,
+    Fixme,
+    Hack,
+}
+
+impl TodoMarker {
+    fn parse(marker: &str) -> Option<Self> {
+        match marker {
+            "TODO" => Some(Self::Todo),
+            "FIXME" => Some(Self::Fixme),
+            "HACK" => Some(Self::Hack),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Todo => "TODO",
+            Self::Fixme => "FIXME",
+            Self::Hack => "HACK",
+        }
+    }
+
+    /// Priority of the proposed task: broken code before workarounds before plans
+    fn priority_score(&self) -> f64 {
+        match self {
+            Self::Fixme => 7.0,
+            Self::Hack => 6.0,
+            Self::Todo => 5.0,
+        }
+    }
+}
+
+/// A TODO, FIXME or HACK comment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeTodo {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    pub marker: TodoMarker,
+    /// Comment text after the marker
+    pub text: String,
+}
+
+impl CodeTodo {
+    /// Code of the task proposed for the comment, e.g. `FIXME-3fa2b1c4`
+    pub fn task_code(&self) -> String {
+        let digest = Sha256::digest(format!("{}\n{}", self.path, self.text));
+        format!("{}-{}", self.marker.as_str(), &hex::encode(digest)[..8])
+    }
+
+    /// Task proposed for the comment
+    pub fn to_new_task(&self, owner_agent_name: Option<String>) -> NewTask {
+        let name = match self.text.is_empty() {
+            true => format!("{} in {}", self.marker.as_str(), self.path),
+            false => format!("{}: {}", self.marker.as_str(), truncate(&self.text)),
+        };
+        let description = format!(
+            "{} comment at `{}:{}`:\n\n> {}",
+            self.marker.as_str(),
+            self.path,
+            self.line,
+            if self.text.is_empty() {
+                "(no text)"
+            } else {
+                &self.text
+            }
+        );
+        let mut task = NewTask::new(self.task_code(), name, description, owner_agent_name);
+        task.priority_score = self.marker.priority_score();
+        task
+    }
+}
+
+/// A comment and the task proposed for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposedTodoTask {
+    #[serde(flatten)]
+    pub todo: CodeTodo,
+    pub task_code: String,
+    pub task_name: String,
+    /// A task with the code exists already, so none is created
+    pub exists: bool,
+}
+
+/// Comments found by a scan, and the tasks created once confirmed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeTodoImport {
+    /// `confirmation_required` until the import is confirmed, then `success`
+    pub status: ResponseStatus,
+    pub message: String,
+    pub proposed: Vec<ProposedTodoTask>,
+    pub created: Vec<Task>,
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_TODO_NAME_CHARS) {
+        Some((end, _)) => format!("{}...", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+fn todo_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    // A comment leader, the marker, an optional `(author)` and `:`, then the text
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?://+|#+|/\*+|\*|--|<!--|;+)\s*(TODO|FIXME|HACK)\b(?:\([^)]*\))?:?\s*(.*)$")
+            .expect("valid TODO pattern")
+    })
+}
+
+/// TODO comments in `content`, the text of the file at `path`
+pub fn parse_code_todos(path: &str, content: &str) -> Vec<CodeTodo> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let captures = todo_pattern().captures(line)?;
+            let text = captures[2]
+                .trim()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim()
+                .to_string();
+            Some(CodeTodo {
+                path: path.to_string(),
+                line: index + 1,
+                marker: TodoMarker::parse(&captures[1])?,
+                text,
+            })
+        })
+        .collect()
+}
+
+/// TODO comments of the files under `project_root`, ordered by path and line
+///
+/// `.gitignore`, `.ignore` and hidden files are skipped, as are files over
+/// [`MAX_SCANNED_FILE_BYTES`] and files that are not UTF-8. At most `limit`
+/// comments are returned.
+pub fn scan_code_todos(project_root: &Path, limit: usize) -> Result<Vec<CodeTodo>> {
+    let walker = ignore::WalkBuilder::new(project_root)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    let mut todos = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| TaskError::Internal(format!("scanning TODOs: {e}")))?;
+        if !entry.file_type().is_some_and(|t| t.is_file())
+            || entry
+                .metadata()
+                .map_or(true, |m| m.len() > MAX_SCANNED_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let relative = entry
+            .path()
+            .strip_prefix(project_root)
+            .unwrap_or(entry.path())
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        todos.extend(parse_code_todos(&relative, &content));
+        if todos.len() >= limit {
+            todos.truncate(limit);
+            break;
+        }
+    }
+    Ok(todos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_code_todos() {
+        let source = "fn main() {\n    // TODO(alice): handle errors\n    let todo = 1; // not a TODOs list\n    /* FIXME: leaks memory */\n}\n# HACK\n";
+        let todos = parse_code_todos("src/main.rs", source);
+        assert_eq!(todos.len(), 3);
+        assert_eq!(todos[0].line, 2);
+        assert_eq!(todos[0].text, "handle errors");
+        assert_eq!(todos[1].marker, TodoMarker::Fixme);
+        assert_eq!(todos[1].text, "leaks memory");
+        assert_eq!(todos[2].marker, TodoMarker::Hack);
+
+        let task = todos[1].to_new_task(None);
+        assert!(task.code.starts_with("FIXME-") && task.code.len() == 14);
+        assert_eq!(task.name, "FIXME: leaks memory");
+        assert_eq!(task.priority_score, 7.0);
+
+        let mut moved = todos[1].clone();
+        moved.line = 40;
+        assert_eq!(moved.task_code(), task.code);
+    }
+
+    #[test]
+    fn test_scan_respects_gitignore() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::create_dir_all(root.path().join("target")).unwrap();
+        std::fs::write(
+            root.path().join("src/lib.rs"),
+            "// TODO: docs\n// FIXME: tests\n",
+        )
+        .unwrap();
+        std::fs::write(root.path().join("target/gen.rs"), "// TODO: generated\n").unwrap();
+
+        let todos = scan_code_todos(root.path(), 100).unwrap();
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].path, "src/lib.rs");
+        assert_eq!(scan_code_todos(root.path(), 1).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! Critical path of the remaining work
+//!
+//! Subtasks gate their parent: a task cannot be finished before its
+//! incomplete subtasks are. Walking that hierarchy with each task's estimated
+//! effort gives the longest chain of incomplete work, and for every other task
+//! how long it can slip without delaying the whole project.
+
+use crate::models::{Task, TaskState};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Effort assumed for tasks without an estimate, in minutes
+pub const DEFAULT_EFFORT_MINUTES: u32 = 60;
+
+/// Scheduling figures of one incomplete task, in minutes of effort from now
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskSlack {
+    pub task_id: i32,
+    pub task_code: String,
+    pub state: TaskState,
+    pub owner_agent_name: Option<String>,
+    /// Estimated effort, or the default for tasks without an estimate
+    pub effort_minutes: i64,
+    /// Whether `effort_minutes` is the default rather than the task's estimate
+    pub effort_defaulted: bool,
+    /// Earliest point the task can be finished, after its subtasks
+    pub earliest_finish: i64,
+    /// Latest point the task can be finished without delaying completion
+    pub latest_finish: i64,
+    /// How long the task can slip; 0 on the critical path
+    pub slack_minutes: i64,
+    /// Whether the task is on a longest chain of incomplete work
+    pub critical: bool,
+}
+
+/// Longest chain of incomplete tasks and the slack of every incomplete task
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CriticalPath {
+    /// Effort along the critical path; the least time until all work is done
+    pub total_minutes: i64,
+    /// Codes of the critical path's tasks, in the order they have to be done
+    pub path: Vec<String>,
+    /// Every incomplete task, least slack first
+    pub tasks: Vec<TaskSlack>,
+}
+
+impl CriticalPath {
+    /// Compute the critical path over the incomplete tasks among `tasks`
+    ///
+    /// Done and archived tasks gate nothing. Links to parents that are not
+    /// among the incomplete tasks are ignored, and parent cycles are cut.
+    pub fn compute(tasks: &[Task], default_effort_minutes: u32) -> Self {
+        let tasks: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| !matches!(task.state, TaskState::Done | TaskState::Archived))
+            .collect();
+        let n = tasks.len();
+        let index: HashMap<i32, usize> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| (task.id, i))
+            .collect();
+        let mut parent: Vec<Option<usize>> = tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let parent = task.parent_task_id.and_then(|id| index.get(&id).copied());
+                parent.filter(|&p| p != i)
+            })
+            .collect();
+        cut_cycles(&mut parent);
+
+        let effort: Vec<(i64, bool)> = tasks
+            .iter()
+            .map(|task| match task.estimated_effort.filter(|&e| e > 0) {
+                Some(effort) => (i64::from(effort), false),
+                None => (i64::from(default_effort_minutes.max(1)), true),
+            })
+            .collect();
+
+        // Children before parents
+        let mut pending = vec![0usize; n];
+        for p in parent.iter().flatten() {
+            pending[*p] += 1;
+        }
+        let mut queue: VecDeque<usize> = (0..n).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::with_capacity(n);
+        let mut longest_child = vec![0i64; n];
+        let mut earliest_finish = vec![0i64; n];
+        while let Some(i) = queue.pop_front() {
+            earliest_finish[i] = longest_child[i] + effort[i].0;
+            order.push(i);
+            if let Some(p) = parent[i] {
+                longest_child[p] = longest_child[p].max(earliest_finish[i]);
+                pending[p] -= 1;
+                if pending[p] == 0 {
+                    queue.push_back(p);
+                }
+            }
+        }
+
+        let total_minutes = earliest_finish.iter().copied().max().unwrap_or(0);
+        let mut latest_finish = vec![total_minutes; n];
+        for &i in order.iter().rev() {
+            if let Some(p) = parent[i] {
+                latest_finish[i] = latest_finish[p] - effort[p].0;
+            }
+        }
+
+        // Walk down from the task finishing last, always into its longest subtask
+        let by_finish = |a: &usize, b: &usize| {
+            earliest_finish[*a]
+                .cmp(&earliest_finish[*b])
+                .then(tasks[*b].id.cmp(&tasks[*a].id))
+        };
+        let mut path = Vec::new();
+        let mut current = (0..n).filter(|&i| parent[i].is_none()).max_by(by_finish);
+        while let Some(i) = current {
+            path.push(tasks[i].code.clone());
+            current = (0..n).filter(|&c| parent[c] == Some(i)).max_by(by_finish);
+        }
+        path.reverse();
+
+        let mut slack: Vec<TaskSlack> = (0..n)
+            .map(|i| {
+                let slack_minutes = latest_finish[i] - earliest_finish[i];
+                TaskSlack {
+                    task_id: tasks[i].id,
+                    task_code: tasks[i].code.clone(),
+                    state: tasks[i].state,
+                    owner_agent_name: tasks[i].owner_agent_name.clone(),
+                    effort_minutes: effort[i].0,
+                    effort_defaulted: effort[i].1,
+                    earliest_finish: earliest_finish[i],
+                    latest_finish: latest_finish[i],
+                    slack_minutes,
+                    critical: slack_minutes == 0,
+                }
+            })
+            .collect();
+        slack.sort_by(|a, b| {
+            a.slack_minutes
+                .cmp(&b.slack_minutes)
+                .then(a.earliest_finish.cmp(&b.earliest_finish))
+                .then(a.task_id.cmp(&b.task_id))
+        });
+
+        Self {
+            total_minutes,
+            path,
+            tasks: slack,
+        }
+    }
+}
+
+/// Drop the parent link that closes each cycle of parent links
+fn cut_cycles(parent: &mut [Option<usize>]) {
+    const UNVISITED: u8 = 0;
+    const ON_WALK: u8 = 1;
+    const DONE: u8 = 2;
+
+    let mut mark = vec![UNVISITED; parent.len()];
+    for start in 0..parent.len() {
+        let mut walk = Vec::new();
+        let mut current = start;
+        while mark[current] == UNVISITED {
+            mark[current] = ON_WALK;
+            walk.push(current);
+            match parent[current] {
+                Some(p) if mark[p] == ON_WALK => {
+                    parent[current] = None;
+                    break;
+                }
+                Some(p) => current = p,
+                None => break,
+            }
+        }
+        for i in walk {
+            mark[i] = DONE;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(id: i32, parent: Option<i32>, effort: Option<i32>, state: TaskState) -> Task {
+        Task {
+            name: format!("Task {id}"),
+            parent_task_id: parent,
+            estimated_effort: effort,
+            ..test_support::task(id, &format!("T-{id}"), state)
+        }
+    }
+
+    #[test]
+    fn test_critical_path_through_subtasks() {
+        // 1 <- {2 <- {4, 5}, 3}; 6 is done and gates nothing
+        let tasks = vec![
+            task(1, None, Some(30), TaskState::Created),
+            task(2, Some(1), Some(60), TaskState::InProgress),
+            task(3, Some(1), Some(20), TaskState::Created),
+            task(4, Some(2), Some(120), TaskState::Created),
+            task(5, Some(2), None, TaskState::Created),
+            task(6, Some(3), Some(500), TaskState::Done),
+        ];
+        let critical = CriticalPath::compute(&tasks, 45);
+
+        assert_eq!(critical.total_minutes, 120 + 60 + 30);
+        assert_eq!(critical.path, vec!["T-4", "T-2", "T-1"]);
+
+        let slack: HashMap<&str, i64> = critical
+            .tasks
+            .iter()
+            .map(|t| (t.task_code.as_str(), t.slack_minutes))
+            .collect();
+        assert_eq!(slack["T-1"], 0);
+        assert_eq!(slack["T-4"], 0);
+        assert_eq!(slack["T-5"], 120 - 45);
+        assert_eq!(slack["T-3"], 180 - 20);
+        assert!(!slack.contains_key("T-6"));
+        assert!(critical
+            .tasks
+            .iter()
+            .any(|t| t.task_code == "T-5" && t.effort_defaulted));
+        assert_eq!(critical.tasks.last().unwrap().task_code, "T-3");
+    }
+
+    #[test]
+    fn test_parent_cycles_are_cut() {
+        let tasks = vec![
+            task(1, Some(2), Some(10), TaskState::Created),
+            task(2, Some(1), Some(20), TaskState::Created),
+        ];
+        let critical = CriticalPath::compute(&tasks, 60);
+        assert_eq!(critical.tasks.len(), 2);
+        assert_eq!(critical.total_minutes, 30);
+        assert_eq!(critical.path.len(), 2);
+        assert!(CriticalPath::compute(&[], 60).path.is_empty());
+    }
+}
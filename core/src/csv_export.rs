@@ -0,0 +1,382 @@
+//! CSV export of tasks and work sessions
+//!
+//! Renders tasks and work sessions as RFC 4180 CSV with a header row and a
+//! caller-selected set of columns, so the data can be opened in a spreadsheet
+//! without querying SQLite directly.
+
+use crate::error::{Result, TaskError};
+use crate::models::{Task, WorkSession};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Maximum number of rows returned by one CSV export call
+pub const MAX_CSV_EXPORT_ROWS: u32 = 10_000;
+
+/// Column of a task CSV export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskCsvColumn {
+    Id,
+    Code,
+    Name,
+    Description,
+    State,
+    Owner,
+    CreatedAt,
+    ClaimedAt,
+    DoneAt,
+    LeaseExpiresAt,
+    RequiredCapabilities,
+    ParentTaskId,
+    EstimatedEffort,
+}
+
+impl TaskCsvColumn {
+    /// Every column, in export order
+    pub const ALL: [TaskCsvColumn; 13] = [
+        TaskCsvColumn::Id,
+        TaskCsvColumn::Code,
+        TaskCsvColumn::Name,
+        TaskCsvColumn::Description,
+        TaskCsvColumn::State,
+        TaskCsvColumn::Owner,
+        TaskCsvColumn::CreatedAt,
+        TaskCsvColumn::ClaimedAt,
+        TaskCsvColumn::DoneAt,
+        TaskCsvColumn::LeaseExpiresAt,
+        TaskCsvColumn::RequiredCapabilities,
+        TaskCsvColumn::ParentTaskId,
+        TaskCsvColumn::EstimatedEffort,
+    ];
+
+    /// Columns exported when none are selected
+    pub const DEFAULT: [TaskCsvColumn; 7] = [
+        TaskCsvColumn::Id,
+        TaskCsvColumn::Code,
+        TaskCsvColumn::Name,
+        TaskCsvColumn::State,
+        TaskCsvColumn::Owner,
+        TaskCsvColumn::CreatedAt,
+        TaskCsvColumn::DoneAt,
+    ];
+
+    /// Header name of the column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskCsvColumn::Id => "id",
+            TaskCsvColumn::Code => "code",
+            TaskCsvColumn::Name => "name",
+            TaskCsvColumn::Description => "description",
+            TaskCsvColumn::State => "state",
+            TaskCsvColumn::Owner => "owner",
+            TaskCsvColumn::CreatedAt => "created_at",
+            TaskCsvColumn::ClaimedAt => "claimed_at",
+            TaskCsvColumn::DoneAt => "done_at",
+            TaskCsvColumn::LeaseExpiresAt => "lease_expires_at",
+            TaskCsvColumn::RequiredCapabilities => "required_capabilities",
+            TaskCsvColumn::ParentTaskId => "parent_task_id",
+            TaskCsvColumn::EstimatedEffort => "estimated_effort",
+        }
+    }
+
+    fn value(&self, task: &Task) -> String {
+        match self {
+            TaskCsvColumn::Id => task.id.to_string(),
+            TaskCsvColumn::Code => task.code.clone(),
+            TaskCsvColumn::Name => task.name.clone(),
+            TaskCsvColumn::Description => task.description.clone(),
+            TaskCsvColumn::State => task.state.to_string(),
+            TaskCsvColumn::Owner => task.owner_agent_name.clone().unwrap_or_default(),
+            TaskCsvColumn::CreatedAt => timestamp(Some(task.inserted_at)),
+            TaskCsvColumn::ClaimedAt => timestamp(task.claimed_at),
+            TaskCsvColumn::DoneAt => timestamp(task.done_at),
+            TaskCsvColumn::LeaseExpiresAt => timestamp(task.lease_expires_at),
+            TaskCsvColumn::RequiredCapabilities => task.required_capabilities.join(";"),
+            TaskCsvColumn::ParentTaskId => optional(task.parent_task_id),
+            TaskCsvColumn::EstimatedEffort => optional(task.estimated_effort),
+        }
+    }
+}
+
+impl FromStr for TaskCsvColumn {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|column| column.as_str() == name)
+            .ok_or_else(|| TaskError::Validation(format!("Unknown task column '{name}'")))
+    }
+}
+
+/// Column of a work session CSV export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionCsvColumn {
+    Id,
+    TaskId,
+    TaskCode,
+    AgentName,
+    StartedAt,
+    EndedAt,
+    DurationMinutes,
+    ProductivityScore,
+    Notes,
+}
+
+impl SessionCsvColumn {
+    /// Every column, in export order
+    pub const ALL: [SessionCsvColumn; 9] = [
+        SessionCsvColumn::Id,
+        SessionCsvColumn::TaskId,
+        SessionCsvColumn::TaskCode,
+        SessionCsvColumn::AgentName,
+        SessionCsvColumn::StartedAt,
+        SessionCsvColumn::EndedAt,
+        SessionCsvColumn::DurationMinutes,
+        SessionCsvColumn::ProductivityScore,
+        SessionCsvColumn::Notes,
+    ];
+
+    /// Columns exported when none are selected
+    pub const DEFAULT: [SessionCsvColumn; 6] = [
+        SessionCsvColumn::Id,
+        SessionCsvColumn::TaskCode,
+        SessionCsvColumn::AgentName,
+        SessionCsvColumn::StartedAt,
+        SessionCsvColumn::EndedAt,
+        SessionCsvColumn::DurationMinutes,
+    ];
+
+    /// Header name of the column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionCsvColumn::Id => "id",
+            SessionCsvColumn::TaskId => "task_id",
+            SessionCsvColumn::TaskCode => "task_code",
+            SessionCsvColumn::AgentName => "agent_name",
+            SessionCsvColumn::StartedAt => "started_at",
+            SessionCsvColumn::EndedAt => "ended_at",
+            SessionCsvColumn::DurationMinutes => "duration_minutes",
+            SessionCsvColumn::ProductivityScore => "productivity_score",
+            SessionCsvColumn::Notes => "notes",
+        }
+    }
+
+    fn value(&self, session: &WorkSession, task_codes: &HashMap<i32, String>) -> String {
+        match self {
+            SessionCsvColumn::Id => session.id.to_string(),
+            SessionCsvColumn::TaskId => session.task_id.to_string(),
+            SessionCsvColumn::TaskCode => task_codes
+                .get(&session.task_id)
+                .cloned()
+                .unwrap_or_default(),
+            SessionCsvColumn::AgentName => session.agent_name.clone(),
+            SessionCsvColumn::StartedAt => timestamp(Some(session.started_at)),
+            SessionCsvColumn::EndedAt => timestamp(session.ended_at),
+            SessionCsvColumn::DurationMinutes => optional(
+                session
+                    .ended_at
+                    .map(|ended_at| (ended_at - session.started_at).num_minutes()),
+            ),
+            SessionCsvColumn::ProductivityScore => optional(session.productivity_score),
+            SessionCsvColumn::Notes => session.notes.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl FromStr for SessionCsvColumn {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|column| column.as_str() == name)
+            .ok_or_else(|| TaskError::Validation(format!("Unknown work session column '{name}'")))
+    }
+}
+
+/// Rows rendered as CSV
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvExport {
+    /// Always `"csv"`
+    pub format: String,
+    /// Header names, in column order
+    pub columns: Vec<String>,
+    /// Number of data rows in `content`, excluding the header
+    pub rows: usize,
+    /// Whether the row limit was reached
+    pub truncated: bool,
+    /// Header line followed by one line per row, CRLF-terminated
+    pub content: String,
+}
+
+impl CsvExport {
+    /// Render `tasks` with the selected columns, or the default ones when empty
+    pub fn tasks(tasks: &[Task], columns: &[TaskCsvColumn], limit: Option<u32>) -> Self {
+        let columns = if columns.is_empty() {
+            &TaskCsvColumn::DEFAULT[..]
+        } else {
+            columns
+        };
+        Self::render(
+            columns.iter().map(|c| c.as_str()).collect(),
+            tasks
+                .iter()
+                .map(|task| columns.iter().map(|c| c.value(task)).collect())
+                .collect(),
+            limit,
+        )
+    }
+
+    /// Render `sessions` with the selected columns, or the default ones when empty
+    ///
+    /// `task_codes` maps task IDs to codes for the `task_code` column.
+    pub fn sessions(
+        sessions: &[WorkSession],
+        task_codes: &HashMap<i32, String>,
+        columns: &[SessionCsvColumn],
+        limit: Option<u32>,
+    ) -> Self {
+        let columns = if columns.is_empty() {
+            &SessionCsvColumn::DEFAULT[..]
+        } else {
+            columns
+        };
+        Self::render(
+            columns.iter().map(|c| c.as_str()).collect(),
+            sessions
+                .iter()
+                .map(|session| {
+                    columns
+                        .iter()
+                        .map(|c| c.value(session, task_codes))
+                        .collect()
+                })
+                .collect(),
+            limit,
+        )
+    }
+
+    fn render(header: Vec<&str>, rows: Vec<Vec<String>>, limit: Option<u32>) -> Self {
+        let mut content = csv_line(header.iter().copied());
+        for row in &rows {
+            content.push_str(&csv_line(row.iter().map(String::as_str)));
+        }
+        Self {
+            format: "csv".to_string(),
+            columns: header.into_iter().map(str::to_string).collect(),
+            rows: rows.len(),
+            truncated: limit.is_some_and(|limit| rows.len() >= limit as usize),
+            content,
+        }
+    }
+}
+
+/// Join fields into one CRLF-terminated CSV line
+fn csv_line<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let mut line = fields.map(csv_field).collect::<Vec<_>>().join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Quote a field if it contains a delimiter, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn timestamp(value: Option<DateTime<Utc>>) -> String {
+    value
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskState;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_task_csv_quotes_fields() {
+        let created = Utc.with_ymd_and_hms(2025, 3, 1, 9, 30, 0).unwrap();
+        let task = Task::new(
+            7,
+            "API-07".to_string(),
+            "Parse \"quoted\", values".to_string(),
+            "Line one\nline two".to_string(),
+            None,
+            TaskState::Created,
+            created,
+            None,
+        );
+
+        let export = CsvExport::tasks(
+            std::slice::from_ref(&task),
+            &[
+                TaskCsvColumn::Code,
+                TaskCsvColumn::Name,
+                TaskCsvColumn::Description,
+                TaskCsvColumn::Owner,
+                TaskCsvColumn::CreatedAt,
+            ],
+            None,
+        );
+        assert_eq!(
+            export.content,
+            "code,name,description,owner,created_at\r\n\
+             API-07,\"Parse \"\"quoted\"\", values\",\"Line one\nline two\",,2025-03-01T09:30:00Z\r\n"
+        );
+        assert_eq!(export.rows, 1);
+        assert!(!export.truncated);
+
+        let defaults = CsvExport::tasks(&[task], &[], Some(1));
+        assert_eq!(defaults.columns.len(), TaskCsvColumn::DEFAULT.len());
+        assert!(defaults.truncated);
+    }
+
+    #[test]
+    fn test_session_csv_columns() {
+        let started = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+        let session = WorkSession {
+            id: 3,
+            task_id: 7,
+            agent_name: "backend-dev".to_string(),
+            started_at: started,
+            ended_at: Some(started + chrono::Duration::minutes(95)),
+            notes: None,
+            productivity_score: Some(0.5),
+            interruptions: Vec::new(),
+        };
+        let task_codes = HashMap::from([(7, "API-07".to_string())]);
+
+        let export = CsvExport::sessions(&[session], &task_codes, &[], None);
+        assert_eq!(
+            export.content,
+            "id,task_code,agent_name,started_at,ended_at,duration_minutes\r\n\
+             3,API-07,backend-dev,2025-03-01T09:00:00Z,2025-03-01T10:35:00Z,95\r\n"
+        );
+
+        assert_eq!(
+            "productivity_score".parse::<SessionCsvColumn>().unwrap(),
+            SessionCsvColumn::ProductivityScore
+        );
+        assert!("owner".parse::<SessionCsvColumn>().is_err());
+        assert_eq!(
+            " estimated_effort".parse::<TaskCsvColumn>().unwrap(),
+            TaskCsvColumn::EstimatedEffort
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Custom work-discovery policies
+//!
+//! `discover_work` returns the tasks an agent can take in the repository's
+//! own order. A [`WorkDiscoveryPolicy`] lets a team filter and reorder those
+//! candidates with its own rules, given the asking agent and its current
+//! workload. Policies run on every discovery, so they must be cheap and must
+//! not block.
+
+use crate::error::Result;
+use crate::models::Task;
+use serde::{Deserialize, Serialize};
+
+/// Candidates fetched per requested task when a policy may filter some out
+pub const DISCOVERY_CANDIDATE_FACTOR: u32 = 5;
+
+/// Most candidates handed to a policy in one discovery
+pub const MAX_DISCOVERY_CANDIDATES: u32 = 200;
+
+/// Agent asking for work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiscoveryAgent {
+    pub name: String,
+    /// Capabilities the agent asked with, normalized
+    pub capabilities: Vec<String>,
+}
+
+/// What the asking agent is already working on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryWorkload {
+    /// Tasks the agent owns in the `InProgress` state
+    pub in_progress: u64,
+    /// Tasks the agent owns in the `Blocked` state
+    pub blocked: u64,
+}
+
+/// Filters and reorders the candidates of `discover_work`
+pub trait WorkDiscoveryPolicy: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    /// Candidates to offer `agent`, best first; tasks left out are not offered
+    fn rank(
+        &self,
+        candidates: Vec<Task>,
+        agent: &DiscoveryAgent,
+        workload: &DiscoveryWorkload,
+    ) -> Result<Vec<Task>>;
+}
+
+/// Number of candidates to fetch for `max_tasks` results of a policy
+pub fn discovery_candidate_limit(max_tasks: u32) -> u32 {
+    max_tasks
+        .saturating_mul(DISCOVERY_CANDIDATE_FACTOR)
+        .min(MAX_DISCOVERY_CANDIDATES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_candidate_limit() {
+        assert_eq!(discovery_candidate_limit(10), 50);
+        assert_eq!(discovery_candidate_limit(100), MAX_DISCOVERY_CANDIDATES);
+        assert_eq!(discovery_candidate_limit(0), 0);
+    }
+}
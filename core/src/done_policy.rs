@@ -0,0 +1,316 @@
+//! Definition-of-done policies
+//!
+//! A workspace can ask for more than a state change before a task counts as
+//! done. `.axon/done.yaml` lists the requirements: a number of registered
+//! artifacts, every checklist item of the task description checked, and an
+//! approval message from an agent other than the owner. Moving a task to
+//! `Done` is refused while any requirement is unmet, and the error lists what
+//! is missing.
+//!
+//! ```yaml
+//! min_artifacts: 1
+//! require_checklist: true
+//! require_approval: true
+//! exempt_code_prefixes: [DOC-]
+//! ```
+
+use crate::artifacts::ArtifactFilter;
+use crate::error::{Result, TaskError};
+use crate::models::{Task, TaskMessage, TaskState};
+use crate::repository::{TaskMessageRepository, TaskRepository};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Location of the definition of done inside the project root
+pub const DONE_POLICY_FILE: &str = ".axon/done.yaml";
+
+/// Message type approving a task unless the policy names another
+pub const DEFAULT_APPROVAL_MESSAGE_TYPE: &str = "approval";
+
+/// Requirements a task must meet before it may move to `Done`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DonePolicy {
+    /// Artifacts the task must have registered
+    pub min_artifacts: u32,
+    /// Every `- [ ]` item in the task description must be checked
+    pub require_checklist: bool,
+    /// An agent other than the owner must post an approval message
+    pub require_approval: bool,
+    /// Message type that counts as an approval
+    pub approval_message_type: String,
+    /// Task code prefixes the policy does not apply to
+    pub exempt_code_prefixes: Vec<String>,
+}
+
+impl Default for DonePolicy {
+    fn default() -> Self {
+        Self {
+            min_artifacts: 0,
+            require_checklist: false,
+            require_approval: false,
+            approval_message_type: DEFAULT_APPROVAL_MESSAGE_TYPE.to_string(),
+            exempt_code_prefixes: Vec::new(),
+        }
+    }
+}
+
+/// Kind of definition-of-done requirement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoneRequirement {
+    Artifacts,
+    Checklist,
+    Approval,
+}
+
+/// A requirement a task does not meet yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnmetRequirement {
+    pub requirement: DoneRequirement,
+    /// What is missing
+    pub detail: String,
+}
+
+impl fmt::Display for UnmetRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.detail)
+    }
+}
+
+/// Markdown task list item, `- [ ] text` or `- [x] text`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub checked: bool,
+}
+
+/// Task list items of a Markdown text, in order
+pub fn checklist_items(text: &str) -> Vec<ChecklistItem> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let rest = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))?
+                .trim_start();
+            let checked = match rest.get(..3)? {
+                "[ ]" => false,
+                "[x]" | "[X]" => true,
+                _ => return None,
+            };
+            Some(ChecklistItem {
+                text: rest[3..].trim().to_string(),
+                checked,
+            })
+        })
+        .collect()
+}
+
+impl DonePolicy {
+    /// Parse a policy from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml)
+            .map_err(|e| TaskError::Validation(format!("{DONE_POLICY_FILE}: {e}")))
+    }
+
+    /// Whether the policy requires nothing
+    pub fn is_empty(&self) -> bool {
+        self.min_artifacts == 0 && !self.require_checklist && !self.require_approval
+    }
+
+    /// Whether the policy applies to `task`
+    pub fn applies_to(&self, task: &Task) -> bool {
+        !self.is_empty()
+            && !self
+                .exempt_code_prefixes
+                .iter()
+                .any(|prefix| task.code.starts_with(prefix.as_str()))
+    }
+
+    /// Requirements `task` does not meet
+    ///
+    /// `messages` are the task's messages; only approvals by agents other
+    /// than the owner count.
+    pub fn unmet(
+        &self,
+        task: &Task,
+        artifact_count: usize,
+        messages: &[TaskMessage],
+    ) -> Vec<UnmetRequirement> {
+        let mut unmet = Vec::new();
+        if !self.applies_to(task) {
+            return unmet;
+        }
+        if artifact_count < self.min_artifacts as usize {
+            unmet.push(UnmetRequirement {
+                requirement: DoneRequirement::Artifacts,
+                detail: format!(
+                    "{} artifact(s) registered, {} required",
+                    artifact_count, self.min_artifacts
+                ),
+            });
+        }
+        if self.require_checklist {
+            let open: Vec<String> = checklist_items(&task.description)
+                .into_iter()
+                .filter(|item| !item.checked)
+                .map(|item| item.text)
+                .collect();
+            if !open.is_empty() {
+                unmet.push(UnmetRequirement {
+                    requirement: DoneRequirement::Checklist,
+                    detail: format!("unchecked checklist items: {}", open.join("; ")),
+                });
+            }
+        }
+        if self.require_approval
+            && !messages.iter().any(|message| {
+                message.message_type == self.approval_message_type
+                    && task.owner_agent_name.as_deref() != Some(message.author_agent_name.as_str())
+            })
+        {
+            unmet.push(UnmetRequirement {
+                requirement: DoneRequirement::Approval,
+                detail: format!(
+                    "no '{}' message from an agent other than the owner",
+                    self.approval_message_type
+                ),
+            });
+        }
+        unmet
+    }
+}
+
+/// Reject moving `task` to `state` while it misses the definition of done
+pub async fn check_done_policy<R, M>(
+    policy: &DonePolicy,
+    repository: &R,
+    message_repository: &M,
+    task: &Task,
+    state: TaskState,
+) -> Result<()>
+where
+    R: TaskRepository + ?Sized,
+    M: TaskMessageRepository + ?Sized,
+{
+    if state != TaskState::Done || !policy.applies_to(task) {
+        return Ok(());
+    }
+    let artifacts = match policy.min_artifacts {
+        0 => Vec::new(),
+        min_artifacts => {
+            let filter = ArtifactFilter {
+                task_code: Some(task.code.clone()),
+                limit: Some(min_artifacts),
+                ..Default::default()
+            };
+            repository.list_artifacts(&filter).await?
+        }
+    };
+    let approvals = match policy.require_approval {
+        true => {
+            message_repository
+                .get_messages(
+                    &task.code,
+                    None,
+                    None,
+                    Some(&policy.approval_message_type),
+                    None,
+                    None,
+                )
+                .await?
+        }
+        false => Vec::new(),
+    };
+    let unmet = policy.unmet(task, artifacts.len(), &approvals);
+    if unmet.is_empty() {
+        return Ok(());
+    }
+    Err(TaskError::UnmetRequirements(task.code.clone(), unmet))
+}
+
+/// Definition of done of the project at `project_root`; empty when it has no policy file
+pub fn load_done_policy(project_root: &Path) -> Result<DonePolicy> {
+    let path = project_root.join(DONE_POLICY_FILE);
+    if !path.exists() {
+        return Ok(DonePolicy::default());
+    }
+    let yaml = std::fs::read_to_string(&path)
+        .map_err(|e| TaskError::Validation(format!("{}: {}", path.display(), e)))?;
+    DonePolicy::from_yaml(&yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::Utc;
+
+    fn task(code: &str, description: &str) -> Task {
+        Task {
+            name: "Login page".to_string(),
+            description: description.to_string(),
+            owner_agent_name: Some("frontend-dev".to_string()),
+            ..test_support::task(1, code, TaskState::InProgress)
+        }
+    }
+
+    fn message(author: &str, message_type: &str) -> TaskMessage {
+        TaskMessage {
+            id: 1,
+            uid: None,
+            task_code: "FE-1".to_string(),
+            author_agent_name: author.to_string(),
+            target_agent_name: None,
+            message_type: message_type.to_string(),
+            created_at: Utc::now(),
+            content: "Looks good".to_string(),
+            reply_to_message_id: None,
+            structured_data: None,
+        }
+    }
+
+    #[test]
+    fn test_checklist_items() {
+        let items =
+            checklist_items("Steps:\n- [x] Form\n  * [ ] Validation\n- [link](x)\n- [X] Styles");
+        assert_eq!(items.len(), 3);
+        assert!(items[0].checked && items[2].checked);
+        assert_eq!(items[1].text, "Validation");
+        assert!(!items[1].checked);
+    }
+
+    #[test]
+    fn test_unmet_requirements() {
+        let policy = DonePolicy::from_yaml(
+            "min_artifacts: 1\nrequire_checklist: true\nrequire_approval: true\nexempt_code_prefixes: [DOC-]\n",
+        )
+        .unwrap();
+        let open = task("FE-1", "- [x] Form\n- [ ] Validation");
+
+        let unmet = policy.unmet(&open, 0, &[message("frontend-dev", "approval")]);
+        let kinds: Vec<_> = unmet.iter().map(|u| u.requirement).collect();
+        assert_eq!(
+            kinds,
+            [
+                DoneRequirement::Artifacts,
+                DoneRequirement::Checklist,
+                DoneRequirement::Approval
+            ]
+        );
+        assert_eq!(unmet[1].detail, "unchecked checklist items: Validation");
+
+        let finished = task("FE-1", "- [x] Form\n- [x] Validation");
+        assert!(policy
+            .unmet(&finished, 1, &[message("reviewer", "approval")])
+            .is_empty());
+        assert!(policy
+            .unmet(&task("DOC-1", "- [ ] Draft"), 0, &[])
+            .is_empty());
+
+        assert!(DonePolicy::default().is_empty());
+        assert!(DonePolicy::from_yaml("min_artifact: 1").is_err());
+    }
+}
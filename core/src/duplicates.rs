@@ -0,0 +1,186 @@
+//! Detection of near-identical tasks
+//!
+//! New tasks are compared with open tasks by the trigram similarity of their
+//! name and description, so agents working in parallel notice when someone
+//! already filed the same work.
+
+use crate::models::{Task, TaskState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What happens when a new task resembles an open one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateCheckMode {
+    /// Tasks are not compared
+    Off,
+    /// The task is created and the similar tasks are returned with it
+    #[default]
+    Warn,
+    /// The task is only created once the caller confirms it is not a duplicate
+    Confirm,
+}
+
+/// How new tasks are checked against open tasks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuplicateCheckPolicy {
+    pub mode: DuplicateCheckMode,
+    /// Similarity from 0.0 to 1.0 at which a task counts as a potential duplicate
+    pub threshold: f64,
+    /// Most similar tasks reported
+    pub max_candidates: usize,
+}
+
+impl Default for DuplicateCheckPolicy {
+    fn default() -> Self {
+        Self {
+            mode: DuplicateCheckMode::Warn,
+            threshold: 0.6,
+            max_candidates: 5,
+        }
+    }
+}
+
+/// An open task similar to a new one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateCandidate {
+    pub task_id: i32,
+    pub code: String,
+    pub name: String,
+    pub state: TaskState,
+    pub owner_agent_name: Option<String>,
+    /// Trigram similarity of name and description, from 0.0 to 1.0
+    pub similarity: f64,
+}
+
+/// Character trigrams of the lowercased words of `text`, padded at word boundaries
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let mut trigrams = HashSet::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let chars: Vec<char> = std::iter::once(' ')
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(std::iter::once(' '))
+            .collect();
+        if chars.len() < 3 {
+            continue;
+        }
+        for window in chars.windows(3) {
+            trigrams.insert([window[0], window[1], window[2]]);
+        }
+    }
+    trigrams
+}
+
+/// Jaccard similarity of the trigram sets of `a` and `b`
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(&b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+impl DuplicateCheckPolicy {
+    /// Open tasks among `tasks` similar to a new task, most similar first
+    pub fn find_duplicates(
+        &self,
+        name: &str,
+        description: &str,
+        tasks: &[Task],
+    ) -> Vec<DuplicateCandidate> {
+        if self.mode == DuplicateCheckMode::Off {
+            return Vec::new();
+        }
+        let text = format!("{name} {description}");
+        let mut candidates: Vec<DuplicateCandidate> = tasks
+            .iter()
+            .filter(|task| !matches!(task.state, TaskState::Done | TaskState::Archived))
+            .filter_map(|task| {
+                let similarity =
+                    trigram_similarity(&text, &format!("{} {}", task.name, task.description));
+                (similarity >= self.threshold).then(|| DuplicateCandidate {
+                    task_id: task.id,
+                    code: task.code.clone(),
+                    name: task.name.clone(),
+                    state: task.state,
+                    owner_agent_name: task.owner_agent_name.clone(),
+                    similarity: (similarity * 1000.0).round() / 1000.0,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then(a.task_id.cmp(&b.task_id))
+        });
+        candidates.truncate(self.max_candidates);
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(id: i32, name: &str, description: &str, state: TaskState) -> Task {
+        Task {
+            name: name.to_string(),
+            description: description.to_string(),
+            ..test_support::task(id, &format!("T-{id}"), state)
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        assert_eq!(trigram_similarity("Fix login", "fix LOGIN!"), 1.0);
+        assert_eq!(trigram_similarity("", "anything"), 0.0);
+
+        let tasks = vec![
+            task(
+                1,
+                "Fix login crash",
+                "App crashes after OAuth redirect",
+                TaskState::InProgress,
+            ),
+            task(
+                2,
+                "Fix login crash",
+                "App crashes after OAuth redirect",
+                TaskState::Done,
+            ),
+            task(
+                3,
+                "Write release notes",
+                "Summarize v2 changes",
+                TaskState::Created,
+            ),
+        ];
+        let policy = DuplicateCheckPolicy::default();
+        let duplicates = policy.find_duplicates(
+            "Fix crash on login",
+            "The app crashes after the OAuth redirect",
+            &tasks,
+        );
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].code, "T-1");
+        assert!(duplicates[0].similarity >= 0.6);
+
+        let off = DuplicateCheckPolicy {
+            mode: DuplicateCheckMode::Off,
+            ..policy
+        };
+        assert!(off
+            .find_duplicates(
+                "Fix login crash",
+                "App crashes after OAuth redirect",
+                &tasks
+            )
+            .is_empty());
+    }
+}
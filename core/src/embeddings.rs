@@ -0,0 +1,230 @@
+//! Embeddings for semantic search over tasks, messages and comments
+//!
+//! An [`EmbeddingProvider`] turns text into vectors. [`index_embeddings`]
+//! embeds every document whose text changed since it was last indexed and
+//! stores the vectors through the repository, which answers
+//! [`TaskExtensionRepository::semantic_search`](crate::TaskExtensionRepository::semantic_search)
+//! by cosine distance.
+
+use crate::error::{Result, TaskError};
+use crate::repository::TaskRepository;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Default number of matches returned by a semantic search
+pub const DEFAULT_SEMANTIC_SEARCH_RESULTS: u32 = 10;
+
+/// Largest number of matches a semantic search may return
+pub const MAX_SEMANTIC_SEARCH_RESULTS: u32 = 50;
+
+/// Texts sent to an embedding provider in one request
+pub const EMBEDDING_BATCH_SIZE: usize = 64;
+
+/// Kind of record an embedding was computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingSource {
+    /// Task name and description
+    Task,
+    /// Task message content
+    Message,
+    /// Task comment content
+    Comment,
+}
+
+impl EmbeddingSource {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingSource::Task => "task",
+            EmbeddingSource::Message => "message",
+            EmbeddingSource::Comment => "comment",
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingSource {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "task" => Ok(EmbeddingSource::Task),
+            "message" => Ok(EmbeddingSource::Message),
+            "comment" => Ok(EmbeddingSource::Comment),
+            _ => Err(TaskError::Validation(format!(
+                "Unknown embedding source '{s}'. Expected task, message or comment"
+            ))),
+        }
+    }
+}
+
+/// Text of a record to embed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingDocument {
+    pub source: EmbeddingSource,
+    /// ID of the task, message or comment
+    pub source_id: i32,
+    /// Task the record belongs to
+    pub task_code: String,
+    pub content: String,
+}
+
+/// A record similar to the search query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticMatch {
+    pub source: EmbeddingSource,
+    pub source_id: i32,
+    pub task_code: String,
+    /// Indexed text of the record
+    pub content: String,
+    /// Cosine similarity to the query, 1.0 for identical direction
+    pub score: f32,
+}
+
+/// Which records a semantic search considers
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SemanticSearchFilter {
+    /// Only these kinds of records; all kinds when empty
+    pub sources: Vec<EmbeddingSource>,
+    /// Maximum number of matches
+    pub top_k: u32,
+}
+
+/// Turns text into embedding vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Name of the model; embeddings of different models are stored apart
+    fn model(&self) -> &str;
+
+    /// Embed `texts`, returning one vector per text in the same order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Local provider hashing words and word pairs into a fixed number of dimensions
+///
+/// It needs no model or network access and finds records sharing vocabulary
+/// with the query, but knows nothing about synonyms. Use a model-backed
+/// provider for true semantic matches.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+    model: String,
+}
+
+impl HashingEmbedder {
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            model: format!("hashing-{dimensions}"),
+        }
+    }
+
+    /// Embedding of one text, normalized to unit length
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let mut vector = vec![0.0f32; self.dimensions];
+        let mut add = |feature: &str, weight: f32| {
+            let hash = fnv1a(feature);
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % self.dimensions as u64) as usize] += sign * weight;
+        };
+        for word in &words {
+            add(word, 1.0);
+        }
+        for pair in words.windows(2) {
+            add(&format!("{} {}", pair[0], pair[1]), 0.5);
+        }
+
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
+/// 64-bit FNV-1a, stable across releases unlike `DefaultHasher`
+pub(crate) fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Outcome of an indexing run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingIndexReport {
+    /// Documents embedded and stored
+    pub indexed: usize,
+    /// Embeddings removed because their record no longer exists
+    pub pruned: u64,
+}
+
+/// Embed up to `limit` new or changed documents and drop embeddings of deleted records
+///
+/// Documents are sent to the provider in batches of `batch_size`.
+pub async fn index_embeddings<R: TaskRepository + ?Sized>(
+    repository: &R,
+    provider: &dyn EmbeddingProvider,
+    limit: u32,
+    batch_size: usize,
+) -> Result<EmbeddingIndexReport> {
+    let model = provider.model();
+    let documents = repository.stale_embedding_documents(model, limit).await?;
+    for batch in documents.chunks(batch_size.max(1)) {
+        let texts: Vec<String> = batch.iter().map(|d| d.content.clone()).collect();
+        let vectors = provider.embed(&texts).await?;
+        if vectors.len() != batch.len() {
+            return Err(TaskError::Protocol(format!(
+                "Embedding provider returned {} vector(s) for {} text(s)",
+                vectors.len(),
+                batch.len()
+            )));
+        }
+        let embedded: Vec<(EmbeddingDocument, Vec<f32>)> =
+            batch.iter().cloned().zip(vectors).collect();
+        repository.save_embeddings(model, &embedded).await?;
+    }
+    Ok(EmbeddingIndexReport {
+        indexed: documents.len(),
+        pruned: repository.prune_embeddings(model).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_hashing_embedder() {
+        let embedder = HashingEmbedder::new(256);
+        assert_eq!(embedder.model(), "hashing-256");
+
+        let query = embedder.embed_text("login page crashes");
+        let related = embedder.embed_text("Fix crash on the Login page after OAuth redirect");
+        let unrelated = embedder.embed_text("Write release notes for v2");
+        assert_eq!(query.len(), 256);
+        assert!((cosine(&query, &query) - 1.0).abs() < 1e-5);
+        assert!(cosine(&query, &related) > cosine(&query, &unrelated) + 0.2);
+        assert!(embedder.embed_text("").iter().all(|v| *v == 0.0));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::done_policy::UnmetRequirement;
 use crate::models::TaskState;
 use thiserror::Error;
 
@@ -110,6 +111,10 @@ pub enum TaskError {
     /// Duplicate key error (e.g., workspace_id already exists)
     #[error("Duplicate key: {0}")]
     DuplicateKey(String),
+
+    /// Task does not meet the definition of done of the workspace
+    #[error("Task {0} does not meet the definition of done: {}", join_unmet(.1))]
+    UnmetRequirements(String, Vec<UnmetRequirement>),
 }
 
 impl TaskError {
@@ -182,10 +187,19 @@ impl TaskError {
             TaskError::DuplicateKey(_) => 409,      // Conflict
             TaskError::UnsupportedAiTool(_) => 400, // Bad Request
             TaskError::UnsupportedOperation(_) => 501, // Not Implemented
+            TaskError::UnmetRequirements(_, _) => 422, // Unprocessable Entity
         }
     }
 }
 
+fn join_unmet(unmet: &[UnmetRequirement]) -> String {
+    unmet
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,349 @@
+//! Final report of a completed project
+//!
+//! Once every task that is not archived is done, the project counts as
+//! complete. The final report then sums it up: the timeline of all tasks,
+//! what each agent contributed, how the time worked compares with the
+//! estimates, and risks worth a look before release, such as tasks that
+//! failed along the way or warnings recorded as system events. Reports are
+//! written to `.axon/reports/` as JSON and Markdown.
+
+use crate::critical_path::DEFAULT_EFFORT_MINUTES;
+use crate::error::{Result, TaskError};
+use crate::models::{EventSeverity, SystemEvent, Task, TaskState, WorkSession};
+use crate::status_report::{AgentActivity, StatusReport};
+use crate::timeline::{Timeline, TimelineFormat};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory of final reports inside the project root
+pub const REPORTS_DIR: &str = ".axon/reports";
+
+/// System event recorded when the project is complete
+pub const PROJECT_COMPLETED_EVENT: &str = "project_completed";
+
+/// Prefix of final report file names, followed by the generation time
+const REPORT_FILE_PREFIX: &str = "final-report-";
+
+/// Multiple of its estimate a task may take before it is reported as a risk
+const OVERRUN_FACTOR: i64 = 2;
+
+/// Time worked on the project against the estimates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostSummary {
+    pub sessions: usize,
+    pub worked_minutes: i64,
+    /// Sum of task estimates; tasks without one count as the default effort
+    pub estimated_minutes: i64,
+    /// Tasks without an estimate of their own
+    pub unestimated_tasks: usize,
+}
+
+/// Kind of outstanding risk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskKind {
+    /// The task failed before it was completed
+    FailedAttempts,
+    /// Work on the task took far longer than estimated
+    Overrun,
+    /// A warning or worse was recorded as a system event
+    SystemEvent,
+}
+
+/// Something worth a look before the project is considered finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectRisk {
+    pub kind: RiskKind,
+    pub task_code: Option<String>,
+    pub detail: String,
+}
+
+/// Summary of a completed project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinalReport {
+    pub generated_at: DateTime<Utc>,
+    /// Creation of the first task
+    pub started_at: DateTime<Utc>,
+    /// Completion of the last task
+    pub completed_at: Option<DateTime<Utc>>,
+    pub tasks_done: usize,
+    pub tasks_archived: usize,
+    pub timeline: Timeline,
+    /// Agents by time worked
+    pub agents: Vec<AgentActivity>,
+    pub cost: CostSummary,
+    pub risks: Vec<ProjectRisk>,
+}
+
+/// Whether at least one task exists and every task not archived is done
+pub fn is_project_complete(tasks: &[Task]) -> bool {
+    let mut open = tasks
+        .iter()
+        .filter(|t| t.state != TaskState::Archived)
+        .peekable();
+    open.peek().is_some() && open.all(|t| t.state == TaskState::Done)
+}
+
+impl FinalReport {
+    /// Build the report of `tasks`, their work sessions and the system events of the project
+    pub fn new(
+        tasks: &[Task],
+        sessions: &[WorkSession],
+        events: &[SystemEvent],
+        now: DateTime<Utc>,
+    ) -> Self {
+        let started_at = tasks.iter().map(|t| t.inserted_at).min().unwrap_or(now);
+        let agents = StatusReport::new(tasks, sessions, started_at, now).agents;
+
+        let mut worked: HashMap<i32, i64> = HashMap::new();
+        for session in sessions {
+            let minutes = (session.ended_at.unwrap_or(now) - session.started_at).num_minutes();
+            *worked.entry(session.task_id).or_default() += minutes;
+        }
+        let cost = CostSummary {
+            sessions: sessions.len(),
+            worked_minutes: worked.values().sum(),
+            estimated_minutes: tasks.iter().map(estimated_minutes).sum(),
+            unestimated_tasks: tasks
+                .iter()
+                .filter(|t| t.estimated_effort.is_none_or(|m| m <= 0))
+                .count(),
+        };
+
+        let mut risks = Vec::new();
+        for task in tasks {
+            if task.failure_count > 0 {
+                risks.push(ProjectRisk {
+                    kind: RiskKind::FailedAttempts,
+                    task_code: Some(task.code.clone()),
+                    detail: format!("failed {} time(s) before completion", task.failure_count),
+                });
+            }
+            let minutes = worked.get(&task.id).copied().unwrap_or(0);
+            if task.estimated_effort.is_some() && minutes > estimated_minutes(task) * OVERRUN_FACTOR
+            {
+                risks.push(ProjectRisk {
+                    kind: RiskKind::Overrun,
+                    task_code: Some(task.code.clone()),
+                    detail: format!(
+                        "worked {} minutes against an estimate of {}",
+                        minutes,
+                        estimated_minutes(task)
+                    ),
+                });
+            }
+        }
+        for event in events.iter().filter(|e| e.severity != EventSeverity::Info) {
+            risks.push(ProjectRisk {
+                kind: RiskKind::SystemEvent,
+                task_code: None,
+                detail: format!(
+                    "{:?} {} on {}{}",
+                    event.severity,
+                    event.event_type,
+                    event.timestamp.format("%Y-%m-%d %H:%M"),
+                    event
+                        .entity_id
+                        .as_deref()
+                        .map(|id| format!(" about {id}"))
+                        .unwrap_or_default()
+                ),
+            });
+        }
+
+        Self {
+            generated_at: now,
+            started_at,
+            completed_at: tasks.iter().filter_map(|t| t.done_at).max(),
+            tasks_done: tasks.iter().filter(|t| t.state == TaskState::Done).count(),
+            tasks_archived: tasks
+                .iter()
+                .filter(|t| t.state == TaskState::Archived)
+                .count(),
+            timeline: Timeline::new(tasks, now, TimelineFormat::Mermaid),
+            agents,
+            cost,
+            risks,
+        }
+    }
+
+    /// Markdown rendering of the report
+    pub fn to_markdown(&self) -> String {
+        let mut text = String::from("# Final project report\n\n");
+        let _ = writeln!(
+            text,
+            "Started {}, completed {}. {} task(s) done, {} archived.\n",
+            self.started_at.format("%Y-%m-%d %H:%M UTC"),
+            self.completed_at.map_or("-".to_string(), |at| at
+                .format("%Y-%m-%d %H:%M UTC")
+                .to_string()),
+            self.tasks_done,
+            self.tasks_archived
+        );
+
+        text.push_str("## Contributions\n\n");
+        if self.agents.is_empty() {
+            text.push_str("- none\n");
+        }
+        for agent in &self.agents {
+            let _ = writeln!(
+                text,
+                "- {}: {} session(s), {}h {:02}m, {} task(s) completed",
+                agent.agent_name,
+                agent.sessions,
+                agent.minutes_worked / 60,
+                agent.minutes_worked % 60,
+                agent.tasks_completed
+            );
+        }
+
+        let _ = write!(
+            text,
+            "\n## Cost\n\n{} minutes worked in {} session(s) against {} minutes estimated",
+            self.cost.worked_minutes, self.cost.sessions, self.cost.estimated_minutes
+        );
+        match self.cost.unestimated_tasks {
+            0 => text.push_str(".\n"),
+            n => {
+                let _ = writeln!(
+                    text,
+                    " ({n} task(s) without an estimate counted as {DEFAULT_EFFORT_MINUTES} minutes)."
+                );
+            }
+        }
+
+        text.push_str("\n## Outstanding risks\n\n");
+        if self.risks.is_empty() {
+            text.push_str("- none\n");
+        }
+        for risk in &self.risks {
+            let _ = match &risk.task_code {
+                Some(code) => writeln!(text, "- {code}: {}", risk.detail),
+                None => writeln!(text, "- {}", risk.detail),
+            };
+        }
+
+        if let Some(mermaid) = &self.timeline.mermaid {
+            let _ = write!(text, "\n## Timeline\n\n```mermaid\n{mermaid}```\n");
+        }
+        text
+    }
+}
+
+fn estimated_minutes(task: &Task) -> i64 {
+    task.estimated_effort
+        .filter(|minutes| *minutes > 0)
+        .map_or(i64::from(DEFAULT_EFFORT_MINUTES), i64::from)
+}
+
+/// Write `report` to the reports directory as JSON and Markdown; returns the JSON path
+pub fn write_final_report(project_root: &Path, report: &FinalReport) -> Result<PathBuf> {
+    let dir = project_root.join(REPORTS_DIR);
+    let io_error = |e: std::io::Error| TaskError::Internal(format!("{}: {e}", dir.display()));
+    std::fs::create_dir_all(&dir).map_err(io_error)?;
+
+    let stem = format!(
+        "{REPORT_FILE_PREFIX}{}",
+        report.generated_at.format("%Y%m%dT%H%M%SZ")
+    );
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| TaskError::Serialization(e.to_string()))?;
+    let path = dir.join(format!("{stem}.json"));
+    std::fs::write(&path, json).map_err(io_error)?;
+    std::fs::write(dir.join(format!("{stem}.md")), report.to_markdown()).map_err(io_error)?;
+    Ok(path)
+}
+
+/// The most recent final report written to the project, if any
+pub fn latest_final_report(project_root: &Path) -> Result<Option<FinalReport>> {
+    let dir = project_root.join(REPORTS_DIR);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(TaskError::Internal(format!("{}: {e}", dir.display()))),
+    };
+    // Names embed the generation time, so the greatest name is the latest report
+    let latest = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(REPORT_FILE_PREFIX) && name.ends_with(".json"))
+        .max();
+    let Some(name) = latest else {
+        return Ok(None);
+    };
+    let path = dir.join(name);
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| TaskError::Internal(format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| TaskError::Deserialization(format!("{}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::Duration;
+
+    fn task(id: i32, state: TaskState, at: DateTime<Utc>) -> Task {
+        Task {
+            name: format!("Task {id}"),
+            owner_agent_name: Some("dev".to_string()),
+            done_at: (state == TaskState::Done).then_some(at + Duration::hours(1)),
+            claimed_at: Some(at),
+            estimated_effort: Some(30),
+            ..test_support::task_at(id, &format!("T-{id}"), state, at)
+        }
+    }
+
+    #[test]
+    fn test_project_completion() {
+        let at = Utc::now() - Duration::days(1);
+        assert!(!is_project_complete(&[]));
+        assert!(!is_project_complete(&[task(1, TaskState::Archived, at)]));
+        assert!(!is_project_complete(&[
+            task(1, TaskState::Done, at),
+            task(2, TaskState::Review, at)
+        ]));
+        assert!(is_project_complete(&[
+            task(1, TaskState::Done, at),
+            task(2, TaskState::Archived, at)
+        ]));
+    }
+
+    #[test]
+    fn test_final_report() {
+        let now = Utc::now();
+        let at = now - Duration::days(1);
+        let mut flaky = task(2, TaskState::Done, at);
+        flaky.failure_count = 2;
+        let tasks = [task(1, TaskState::Done, at), flaky];
+        let sessions = [WorkSession {
+            id: 1,
+            task_id: 1,
+            agent_name: "dev".to_string(),
+            started_at: at,
+            ended_at: Some(at + Duration::minutes(90)),
+            notes: None,
+            productivity_score: None,
+            interruptions: Vec::new(),
+        }];
+
+        let report = FinalReport::new(&tasks, &sessions, &[], now);
+        assert_eq!(report.tasks_done, 2);
+        assert_eq!(report.cost.worked_minutes, 90);
+        assert_eq!(report.cost.estimated_minutes, 60);
+        assert_eq!(report.agents[0].tasks_completed, 2);
+        let kinds: Vec<_> = report.risks.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [RiskKind::Overrun, RiskKind::FailedAttempts]);
+
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(latest_final_report(root.path()).unwrap(), None);
+        let path = write_final_report(root.path(), &report).unwrap();
+        assert!(path.with_extension("md").exists());
+        assert_eq!(latest_final_report(root.path()).unwrap(), Some(report));
+    }
+}
@@ -10,9 +10,28 @@
 //!
 //! - [`models`] - Core domain models (Task, TaskState, etc.)
 //! - [`error`] - Error types and result handling
-//! - [`repository`] - Repository trait for data persistence
+//! - [`repository`] - Repository traits for data persistence
+//! - [`repository_decorators`] - Logging, metrics and retry decorators for repositories
 //! - [`protocol`] - Protocol handler trait for MCP operations
 //! - [`validation`] - Business logic validation utilities
+//! - [`task_builder`] - Validating builder for new tasks
+//! - [`message_templates`] - Templates for common coordination messages
+//! - [`mentions`] - `@agent-name` mention parsing
+//! - [`capabilities`] - Capability catalog and normalization
+//! - [`critical_path`] - Critical path and slack of the remaining tasks
+//! - [`timeline`] - Task timelines and Mermaid gantt charts
+//! - [`task_history`] - Point-in-time task boards and diffs rebuilt from the event history
+//! - [`workspace_merge`] - Merging another project's database into the current one
+//! - [`claude_code`] - Registration of the server in Claude Code's MCP settings
+//! - [`csv_export`] - CSV export of tasks and work sessions
+//! - [`duplicates`] - Detection of near-identical tasks on creation
+//! - [`embeddings`] - Embeddings and semantic search over tasks, messages and comments
+//! - [`task_context`] - Context bundles for agents picking up a task
+//! - [`thread_summary`] - Rolling summaries of long message threads
+//! - [`status_report`] - Status reports of project progress over a period
+//! - [`agent_performance`] - Productivity analytics and anomaly flags per agent
+//! - [`tool_usage`] - Tool calls per agent, to spot agents stuck in polling loops
+//! - [`messages`] - Localized catalogs of user-facing messages
 //!
 //! # Example
 //!
@@ -33,82 +52,324 @@
 //! TaskValidator::validate_new_task(&new_task).unwrap();
 //! ```
 
+pub mod agent_performance;
+pub mod agent_updates;
 pub mod ai_tool_adapters;
+pub mod archetypes;
+pub mod artifacts;
+pub mod capabilities;
 pub mod circuit_breaker;
+pub mod claude_code;
+pub mod claude_subagents;
+pub mod code_todos;
+pub mod critical_path;
+pub mod csv_export;
+pub mod discovery_policy;
+pub mod done_policy;
+pub mod duplicates;
+pub mod embeddings;
 pub mod error;
+pub mod final_report;
+pub mod managed_sections;
+pub mod manifest_schema;
 pub mod mcp_v2_extensions;
+pub mod mentions;
+pub mod message_templates;
+pub mod messages;
 pub mod models;
+pub mod pipelines;
+pub mod project_scan;
 pub mod prompt_templates;
 pub mod protocol;
 pub mod repository;
+pub mod repository_decorators;
+pub mod routing;
+pub mod session_log;
+pub mod setup_rollback;
+pub mod setup_wizard;
+pub mod status_report;
+pub mod stuck_tasks;
+pub mod task_builder;
+pub mod task_context;
+pub mod task_history;
+pub mod tasks_markdown;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod text_diff;
+pub mod thread_summary;
+pub mod timeline;
+pub mod token_estimate;
+pub mod tool_usage;
 pub mod validation;
+pub mod work_rebalancing;
+pub mod workspace_doctor;
+pub mod workspace_merge;
 pub mod workspace_setup;
 
 // Re-export commonly used types at the crate root for convenience
+pub use agent_performance::{
+    performance_report, record_anomalies, AgentPerformance, AnomalyThresholds, PerformanceAnomaly,
+    PerformanceBucket, PerformanceReport, ANOMALY_EVENT_INTERVAL_HOURS,
+    DEFAULT_PERFORMANCE_BUCKET_DAYS, DEFAULT_PERFORMANCE_PERIOD_DAYS,
+};
+pub use agent_updates::{AgentUpdates, NewAssignment};
+pub use archetypes::{load_archetypes, ArchetypeAgent, CustomArchetype, ARCHETYPES_FILE};
+pub use artifacts::{
+    artifact_status, file_checksum, normalize_checksum, resolve_artifact_path,
+    validate_artifact_kind, ArtifactFilter, ArtifactStatus, ListedArtifact, TaskArtifact,
+    CHECKSUM_PREFIX, MAX_ARTIFACT_KIND_LENGTH,
+};
+pub use capabilities::{
+    capability_key, validate_capability, CapabilityCatalog, MAX_CAPABILITY_LENGTH,
+};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerAction, CircuitState, FailureType};
+pub use claude_code::{
+    http_server_entry, upsert_project_server, ClaudeCodeRegistration, CLAUDE_CODE_SERVER_NAME,
+};
+pub use claude_subagents::{
+    render_subagent_file, subagent_name, subagent_tools, validate_subagent_file,
+    SubagentFrontmatter, CLAUDE_CODE_TOOLS, SUBAGENT_MODELS,
+};
+pub use code_todos::{
+    parse_code_todos, scan_code_todos, CodeTodo, CodeTodoImport, ProposedTodoTask, TodoMarker,
+    MAX_SCANNED_FILE_BYTES,
+};
+pub use critical_path::{CriticalPath, TaskSlack, DEFAULT_EFFORT_MINUTES};
+pub use csv_export::{CsvExport, SessionCsvColumn, TaskCsvColumn, MAX_CSV_EXPORT_ROWS};
+pub use discovery_policy::{
+    discovery_candidate_limit, DiscoveryAgent, DiscoveryWorkload, WorkDiscoveryPolicy,
+    DISCOVERY_CANDIDATE_FACTOR, MAX_DISCOVERY_CANDIDATES,
+};
+pub use done_policy::{
+    check_done_policy, checklist_items, load_done_policy, ChecklistItem, DonePolicy,
+    DoneRequirement, UnmetRequirement, DEFAULT_APPROVAL_MESSAGE_TYPE, DONE_POLICY_FILE,
+};
+pub use duplicates::{
+    trigram_similarity, DuplicateCandidate, DuplicateCheckMode, DuplicateCheckPolicy,
+};
+pub use embeddings::{
+    index_embeddings, EmbeddingDocument, EmbeddingIndexReport, EmbeddingProvider, EmbeddingSource,
+    HashingEmbedder, SemanticMatch, SemanticSearchFilter, DEFAULT_SEMANTIC_SEARCH_RESULTS,
+    EMBEDDING_BATCH_SIZE, MAX_SEMANTIC_SEARCH_RESULTS,
+};
 pub use error::{Result, TaskError};
+pub use final_report::{
+    is_project_complete, latest_final_report, write_final_report, CostSummary, FinalReport,
+    ProjectRisk, RiskKind, PROJECT_COMPLETED_EVENT, REPORTS_DIR,
+};
+pub use managed_sections::{
+    merge_managed_sections, strip_managed_sections, ManagedSection, SectionConflict, SectionMerge,
+    AGENT_ROSTER_SECTION, MCP_USAGE_SECTION,
+};
+pub use manifest_schema::{
+    read_manifest, read_workspace_context, write_manifest, ManifestUpgrade, MANIFEST_SCHEMA_VERSION,
+};
 pub use mcp_v2_extensions::{
-    AgentWorkload, CapabilityMatcher, ClaimResult, DiscoverWorkResponse, PrerequisiteAction,
-    PriorityCalculator, SimpleKnowledgeEntry, SimpleWorkSession, WorkDiscoveryConfig,
+    AgentMatch, AgentWorkload, CapabilityMatcher, ClaimResult, DiscoverWorkResponse,
+    PrerequisiteAction, PriorityCalculator, SimpleKnowledgeEntry, SimpleWorkSession,
+    WorkDiscoveryConfig,
 };
+pub use mentions::extract_mentions;
+pub use message_templates::{MessageTemplate, MessageTemplateRegistry, TemplatePlaceholder};
+pub use messages::{Locale, MessageCatalog, MessageKey};
 pub use models::{
     AgentProfile,
     AgentStatus,
+    Capability,
     EventSeverity,
+    ExternalLink,
     // MCP v2 New Entity Types
     KnowledgeObject,
+    MessageEffect,
+    MessageEffectRule,
     NewTask,
     SystemEvent,
     Task,
+    TaskClaim,
+    TaskComment,
+    TaskCommentRevision,
     TaskFilter,
+    TaskMention,
     TaskMessage,
     TaskState,
     UpdateTask,
     WorkSession,
     WorkflowDefinition,
 };
+pub use pipelines::{
+    load_pipeline_templates, stage_code, stage_number, Pipeline, PipelineStage, PipelineTemplate,
+    PipelineTemplates, DEFAULT_PIPELINE_TEMPLATE, PIPELINES_FILE, PIPELINE_AUTHOR,
+    STAGE_READY_MESSAGE_TYPE,
+};
+pub use project_scan::{DetectedStack, ProjectScan};
 pub use prompt_templates::{
     generate_enhanced_setup_instructions, AgentContract, CapabilityDefinition, CoordinationRecipe,
-    EnhancedPromptBuilder,
+    EnhancedPromptBuilder, PromptStyle, PromptVerbosity,
 };
 pub use protocol::{
+    // Task Comment Types
+    AddTaskCommentParams,
+    AggregateTasksParams,
+    AppendSessionLogParams,
+    ApplyTaskOperationsParams,
     ArchiveTaskParams,
     AssignTaskParams,
+    AuditLogExport,
     ClaimTaskParams,
     CleanupTimedOutTasksParams,
+    CountTasksParams,
     CreateMainAiFileParams,
+    CreatePipelineParams,
     // Task Messaging Types
     CreateTaskMessageParams,
     CreateTaskParams,
+    DefineCapabilityParams,
+    DeleteTaskCommentParams,
+    DeprecateCapabilityParams,
+    DiffWorkspaceParams,
     // MCP v2 Advanced Multi-Agent Types
     DiscoverWorkParams,
+    EditTaskCommentParams,
     EndWorkSessionParams,
+    ExplainTaskRoutingParams,
+    ExportAuditLogParams,
+    ExportSessionsCsvParams,
+    ExportTasksCsvParams,
+    ExportTimelineParams,
+    FindDuplicateTasksParams,
+    GetAgentPerformanceParams,
     GetAgenticWorkflowDescriptionParams,
+    GetBoardAtParams,
+    GetCriticalPathParams,
+    GetFinalReportParams,
     GetInstructionsForMainAiFileParams,
+    GetMessageTemplatesParams,
+    GetMyMentionsParams,
+    GetMyWorkParams,
+    GetSessionLogParams,
     // Workspace Setup Types
     GetSetupInstructionsParams,
+    GetSetupStatusParams,
+    GetSystemEventsParams,
     GetTaskByCodeParams,
     GetTaskByIdParams,
+    GetTaskByUidParams,
+    GetTaskCommentHistoryParams,
+    GetTaskCommentsParams,
+    GetTaskContextParams,
     GetTaskMessagesParams,
+    GetTasksByCodesParams,
+    GetTasksByIdsParams,
+    GetToolUsageStatsParams,
+    GetUpdatesSinceParams,
     HealthStatus,
+    ListArtifactsParams,
+    ListCapabilitiesParams,
     ListTasksParams,
+    MaintainDatabaseParams,
+    MatchAgentsToTaskParams,
+    MergeWorkspaceParams,
+    MyWork,
     ProtocolHandler,
-    RegisterAgentParams,  
+    PurgeAgentDataParams,
+    RebalanceWorkParams,
+    RegenerateAgentPromptParams,
+    RegisterAgentParams,
+    RegisterArtifactParams,
+    RegisterWithClaudeCodeParams,
     ReleaseTaskParams,
+    RollbackSetupParams,
+    RunReadonlyQueryParams,
+    ScanCodeTodosParams,
+    SemanticSearchParams,
     SetStateParams,
+    SetupWizardParams,
     StartWorkSessionParams,
+    SyncTasksMarkdownParams,
+    TaskAgentMatches,
+    TaskAggregate,
+    TaskBatch,
+    TaskCount,
+    TaskOperationParams,
     UpdateTaskParams,
+    VerifyWorkspaceParams,
+    WorkDeadline,
     WorkSessionInfo,
+    DEFAULT_DEADLINE_WINDOW_MINUTES,
+    DEFAULT_DIFF_MESSAGES,
+    DEFAULT_MY_WORK_LIMIT,
+    MAX_AUDIT_EXPORT_ENTRIES,
+    MAX_BATCH_LOOKUP,
+    MAX_DIFF_MESSAGES,
+    MAX_SYSTEM_EVENTS,
+    MAX_TASK_OPERATIONS,
 };
 pub use repository::{
-    RepositoryStats, TaskMessageRepository, TaskRepository, WorkspaceContextRepository,
+    apply_claim_affinity, AgentPurgeReport, AuditEntry, AuditLogFilter, AuditSource,
+    ClaimLeasePolicy, IdStrategy, MaintenanceOptions, MaintenanceReport, MigrationInfo,
+    MigrationStatus, PurgeMode, QueryResult, ReadOnlyQuery, RepositoryStats, RetentionPolicy,
+    RetentionReport, SystemEventFilter, TaskCrudRepository, TaskExtensionRepository, TaskGroupBy,
+    TaskGroupCount, TaskMessageRepository, TaskOperation, TaskOperationOutcome, TaskRepository,
+    WorkClaimRepository, WorkSessionFilter, WorkSessionPolicy, WorkSessionRepository,
+    WorkspaceContextRepository, DEFAULT_QUERY_ROWS, DEFAULT_QUERY_TIMEOUT_MS, MAX_QUERY_ROWS,
+    MAX_QUERY_TIMEOUT_MS, REMOVED_AGENT_NAME,
+};
+pub use repository_decorators::{
+    LoggingRepository, MethodMetrics, MetricsRepository, RepositoryMetrics, RetryPolicy,
+    RetryingRepository,
+};
+pub use routing::{
+    load_routing_rules, RouteAction, RouteCondition, RouteTarget, RoutingAgent, RoutingDecision,
+    RoutingRule, RoutingRules, RuleEvaluation, ROUTING_AUTHOR, ROUTING_RULES_FILE,
+    ROUTING_SUGGESTION_MESSAGE_TYPE,
+};
+pub use session_log::{SessionLog, SessionLogChunk, SessionLogEntry, MAX_SESSION_LOG_CHUNK_BYTES};
+pub use setup_rollback::{rollback_setup_files, setup_files, RollbackFailure, SetupRollback};
+pub use setup_wizard::{SetupWizardProgress, SetupWizardState, WizardFile, WizardStep};
+pub use status_report::{AgentActivity, ReportTask, StateCount, StatusReport};
+pub use stuck_tasks::{
+    nudge_stuck_tasks, StuckTask, StuckTaskAction, StuckTaskPolicy, StuckTaskReport,
+    ESCALATION_MESSAGE_TYPE, NUDGE_MESSAGE_TYPE, STUCK_TASK_AUTHOR,
+};
+pub use task_builder::{FieldError, NewTaskBuilder, ValidationErrors, MAX_PRIORITY_SCORE};
+pub use task_context::{
+    extract_commit_refs, DependencyChain, TaskContext, TaskSummary, DEFAULT_CONTEXT_TOKEN_BUDGET,
+    MIN_CONTEXT_TOKEN_BUDGET,
+};
+pub use task_history::{
+    ExchangedMessage, TaskBoard, TaskReassignment, TaskStateChange, WorkspaceDiff,
+};
+pub use tasks_markdown::{
+    parse_tasks_markdown, render_tasks_markdown, sync_tasks_markdown, MarkdownStateEdit,
+    MarkdownSyncConflict, MarkdownTaskLine, RejectedMarkdownEdit, TasksMarkdownSync,
+    TASKS_MARKDOWN_FILE,
+};
+pub use text_diff::{unified_diff, DIFF_CONTEXT_LINES};
+pub use thread_summary::{
+    summarize_thread, ExtractiveSummarizer, TaskThread, ThreadSummarizer, ThreadSummary,
+    ThreadSummaryPolicy,
+};
+pub use timeline::{Timeline, TimelineEntry, TimelineFormat};
+pub use token_estimate::{estimate_tokens, TokenBudgets, TokenEstimate};
+pub use tool_usage::{
+    top_tools, ToolCall, ToolUsageFilter, ToolUsageStat, MAX_TOOL_USAGE_STATS, TOP_TOOLS_PER_AGENT,
+};
+pub use validation::{AgentNamePolicy, TaskValidator, MAX_COMMENT_LENGTH, MAX_MESSAGE_DATA_SIZE};
+pub use work_rebalancing::{
+    OverloadedAgent, Reassignment, RebalanceAgent, RebalancePolicy, WorkRebalance,
+    REASSIGNMENT_MESSAGE_TYPE, REBALANCING_AUTHOR,
+};
+pub use workspace_doctor::{
+    agent_file_path, agent_file_problems, verify_workspace, CheckStatus, WorkspaceCheck,
+    WorkspaceVerification, AGENTS_DIR, MAIN_AI_FILE, MANIFEST_FILE,
 };
-pub use validation::TaskValidator;
+pub use workspace_merge::{CodeRename, IdRemap, TaskMatch, WorkspaceMergeReport};
 pub use workspace_setup::{
-    AgentRegistration, AgenticWorkflowDescription, AiToolType, GeneratedFileMetadata,
-    MainAiFileData, MainAiFileInstructions, PrdDocument, SetupInstructions, WorkspaceContext,
-    WorkspaceManifest, WorkspaceSetupConfig, WorkspaceSetupError, WorkspaceSetupResult,
-    WorkspaceSetupService,
+    AgentRegistration, AgenticWorkflowDescription, AiToolType, ArchetypeCandidate,
+    ArchetypeClassification, Clock, FixedClock, GeneratedFileMetadata, MainAiFileData,
+    MainAiFileInstructions, PrdDocument, PromptRegeneration, SetupInstructions, SetupStatus,
+    SetupStepStatus, SystemClock, WorkspaceContext, WorkspaceManifest, WorkspaceSetupConfig,
+    WorkspaceSetupError, WorkspaceSetupResult, WorkspaceSetupService,
 };
 
 /// Current version of the core crate
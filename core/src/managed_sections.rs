@@ -0,0 +1,311 @@
+//! Axon-managed sections of the main AI file
+//!
+//! CLAUDE.md is edited by people and by Axon alike. The parts Axon owns are
+//! delimited by HTML comments, which Markdown renderers hide:
+//!
+//! ```markdown
+//! <!-- axon:begin agent-roster hash=5d6f0c1e2a3b4c7d -->
+//! ## Agent Roster
+//! ...
+//! <!-- axon:end agent-roster -->
+//! ```
+//!
+//! Regenerating the file rewrites only these sections and keeps everything
+//! around them. The hash records the text Axon last wrote; a section whose
+//! text no longer matches it was edited by hand, so the edit is kept and
+//! reported as a conflict unless overwriting is forced.
+
+use crate::embeddings::fnv1a;
+use serde::{Deserialize, Serialize};
+
+/// Section listing the registered agents
+pub const AGENT_ROSTER_SECTION: &str = "agent-roster";
+
+/// Section describing how agents coordinate through MCP functions
+pub const MCP_USAGE_SECTION: &str = "mcp-usage";
+
+/// A section whose content Axon generates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManagedSection {
+    /// Lower-case identifier used in the markers, e.g. `agent-roster`
+    pub name: String,
+    pub content: String,
+}
+
+impl ManagedSection {
+    pub fn new(name: &str, content: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            content: content.trim_end().to_string(),
+        }
+    }
+
+    /// The section with its markers, without a trailing line break
+    pub fn render(&self) -> String {
+        format!(
+            "<!-- axon:begin {} hash={} -->\n{}\n<!-- axon:end {} -->",
+            self.name,
+            content_hash(&self.content),
+            self.content,
+            self.name
+        )
+    }
+}
+
+/// A managed section that was left as it is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionConflict {
+    /// Section name, or `*` when the markers of the whole document are broken
+    pub section: String,
+    pub reason: String,
+}
+
+/// What merging generated sections into a document changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SectionMerge {
+    /// Sections whose content was replaced
+    pub updated: Vec<String>,
+    /// Sections appended because the document did not have them yet
+    pub added: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// Sections kept as they were instead of being updated
+    pub conflicts: Vec<SectionConflict>,
+}
+
+enum Segment {
+    Text(String),
+    Managed {
+        name: String,
+        hash: Option<String>,
+        body: String,
+    },
+}
+
+/// Replace the managed sections of `document` with `sections`
+///
+/// Text outside the markers is preserved, and sections the document does not
+/// contain yet are appended. Sections edited by hand since Axon wrote them
+/// are only replaced when `force` is set. Returns the merged document.
+pub fn merge_managed_sections(
+    document: &str,
+    sections: &[ManagedSection],
+    force: bool,
+) -> (String, SectionMerge) {
+    let mut merge = SectionMerge::default();
+    let mut segments = match parse_segments(document) {
+        Ok(segments) => segments,
+        Err(reason) => {
+            merge.conflicts.push(SectionConflict {
+                section: "*".to_string(),
+                reason,
+            });
+            return (document.to_string(), merge);
+        }
+    };
+
+    for section in sections {
+        let existing = segments.iter_mut().find_map(|segment| match segment {
+            Segment::Managed { name, hash, body } if *name == section.name => Some((hash, body)),
+            _ => None,
+        });
+        let Some((hash, body)) = existing else {
+            segments.push(Segment::Text(section.render()));
+            merge.added.push(section.name.clone());
+            continue;
+        };
+        if *body == section.content {
+            merge.unchanged.push(section.name.clone());
+            continue;
+        }
+        let edited =
+            !body.trim().is_empty() && hash.as_deref() != Some(content_hash(body).as_str());
+        if edited && !force {
+            merge.conflicts.push(SectionConflict {
+                section: section.name.clone(),
+                reason: "edited by hand since Axon generated it; pass force to overwrite"
+                    .to_string(),
+            });
+            continue;
+        }
+        *hash = Some(content_hash(&section.content));
+        *body = section.content.clone();
+        merge.updated.push(section.name.clone());
+    }
+
+    let mut content = segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Managed { name, hash, body } => {
+                let hash = hash
+                    .as_ref()
+                    .map(|h| format!(" hash={h}"))
+                    .unwrap_or_default();
+                format!("<!-- axon:begin {name}{hash} -->\n{body}\n<!-- axon:end {name} -->")
+            }
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    content.push('\n');
+    (content, merge)
+}
+
+/// `document` with its managed sections removed; `None` when it has none
+///
+/// Broken markers are an error, so a document is never partially stripped.
+pub fn strip_managed_sections(document: &str) -> Result<Option<String>, String> {
+    let segments = parse_segments(document)?;
+    if !segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Managed { .. }))
+    {
+        return Ok(None);
+    }
+    let text = segments
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) if !text.is_empty() => Some(text),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok(Some(match text.is_empty() {
+        true => text,
+        false => text + "\n",
+    }))
+}
+
+/// Split a document into free text and managed sections
+fn parse_segments(document: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut text: Vec<&str> = Vec::new();
+    let mut open: Option<(String, Option<String>, Vec<&str>)> = None;
+
+    for (number, line) in document.lines().enumerate() {
+        let number = number + 1;
+        if let Some(marker) = marker(line, "begin") {
+            if let Some((name, ..)) = &open {
+                return Err(format!("line {number}: section '{name}' is not closed"));
+            }
+            let mut parts = marker.split_whitespace();
+            let name = parts.next().unwrap_or_default().to_string();
+            let hash = parts
+                .next()
+                .and_then(|part| part.strip_prefix("hash="))
+                .map(str::to_string);
+            if segments
+                .iter()
+                .any(|segment| matches!(segment, Segment::Managed { name: n, .. } if *n == name))
+            {
+                return Err(format!("line {number}: section '{name}' appears twice"));
+            }
+            segments.push(Segment::Text(join_block(&text)));
+            text.clear();
+            open = Some((name, hash, Vec::new()));
+        } else if let Some(marker) = marker(line, "end") {
+            match open.take() {
+                Some((name, hash, body)) if name == marker.trim() => {
+                    segments.push(Segment::Managed {
+                        name,
+                        hash,
+                        body: body.join("\n").trim_end().to_string(),
+                    });
+                }
+                Some((name, ..)) => {
+                    return Err(format!(
+                        "line {number}: section '{name}' is closed as '{}'",
+                        marker.trim()
+                    ))
+                }
+                None => {
+                    return Err(format!(
+                        "line {number}: section '{}' is closed but never opened",
+                        marker.trim()
+                    ))
+                }
+            }
+        } else if let Some((_, _, body)) = &mut open {
+            body.push(line);
+        } else {
+            text.push(line);
+        }
+    }
+    if let Some((name, ..)) = open {
+        return Err(format!("section '{name}' is not closed"));
+    }
+    segments.push(Segment::Text(join_block(&text)));
+    Ok(segments)
+}
+
+/// Text of an `<!-- axon:<kind> ... -->` marker line
+fn marker<'a>(line: &'a str, kind: &str) -> Option<&'a str> {
+    line.trim()
+        .strip_prefix("<!-- axon:")?
+        .strip_prefix(kind)?
+        .strip_prefix(' ')?
+        .strip_suffix("-->")
+}
+
+/// Lines of free text without the blank lines around them
+fn join_block(lines: &[&str]) -> String {
+    lines.join("\n").trim_matches('\n').trim_end().to_string()
+}
+
+fn content_hash(content: &str) -> String {
+    format!("{:016x}", fnv1a(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_managed_sections() {
+        let roster = ManagedSection::new(AGENT_ROSTER_SECTION, "## Agents\n- backend-dev\n");
+        let usage = ManagedSection::new(MCP_USAGE_SECTION, "## MCP\nUse claim_task.");
+
+        let (created, merge) = merge_managed_sections(
+            "# Shop\n\nOur conventions.\n",
+            std::slice::from_ref(&roster),
+            false,
+        );
+        assert_eq!(merge.added, [AGENT_ROSTER_SECTION]);
+        assert!(created.starts_with("# Shop\n\nOur conventions.\n\n<!-- axon:begin agent-roster"));
+
+        // User text around the section survives regeneration
+        let edited_outside = created.replace("Our conventions.", "Our conventions, revised.")
+            + "\n## Notes\nWritten by hand.\n";
+        let roster_v2 = ManagedSection::new(AGENT_ROSTER_SECTION, "## Agents\n- backend-dev\n- qa");
+        let (updated, merge) =
+            merge_managed_sections(&edited_outside, &[roster_v2.clone(), usage], false);
+        assert_eq!(merge.updated, [AGENT_ROSTER_SECTION]);
+        assert_eq!(merge.added, [MCP_USAGE_SECTION]);
+        assert!(updated.contains("Our conventions, revised."));
+        assert!(updated.contains("- qa\n<!-- axon:end agent-roster -->\n\n## Notes"));
+        let (_, merge) = merge_managed_sections(&updated, std::slice::from_ref(&roster_v2), false);
+        assert_eq!(merge.unchanged, [AGENT_ROSTER_SECTION]);
+
+        // Hand edits inside a managed section are reported, not overwritten
+        let edited_inside = updated.replace("- qa\n", "- qa (part time)\n");
+        let (kept, merge) =
+            merge_managed_sections(&edited_inside, std::slice::from_ref(&roster), false);
+        assert_eq!(kept, edited_inside);
+        assert_eq!(merge.conflicts[0].section, AGENT_ROSTER_SECTION);
+        let (forced, merge) = merge_managed_sections(&edited_inside, &[roster], true);
+        assert_eq!(merge.updated, [AGENT_ROSTER_SECTION]);
+        assert!(!forced.contains("part time"));
+
+        let broken = "<!-- axon:begin agent-roster -->\nno end\n";
+        let (unchanged, merge) = merge_managed_sections(broken, &[roster_v2], false);
+        assert_eq!(unchanged, broken);
+        assert_eq!(merge.conflicts[0].section, "*");
+
+        assert_eq!(
+            strip_managed_sections(&updated).unwrap().as_deref(),
+            Some("# Shop\n\nOur conventions, revised.\n\n## Notes\nWritten by hand.\n")
+        );
+        assert_eq!(strip_managed_sections("# Shop\n").unwrap(), None);
+        assert!(strip_managed_sections(broken).is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! Versioned workspace manifests
+//!
+//! Manifests written by older releases use schema 1.x, which only carried the
+//! project name and description, the AI tool and the agents. Reading goes
+//! through [`read_manifest`], which upgrades 1.x manifests to the current
+//! shape; [`write_manifest`] can produce 1.x manifests for older tooling.
+//! Both report every field they had to drop.
+
+use crate::workspace_setup::{
+    WorkspaceContext, WorkspaceManifest, WorkspaceSetupError, WorkspaceSetupResult,
+};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+/// Schema version of manifests written by this release
+pub const MANIFEST_SCHEMA_VERSION: &str = "2.0";
+
+/// Top-level fields of a 1.x manifest
+const V1_FIELDS: &[&str] = &["schema_version", "ai_tool_type", "project", "agents"];
+
+/// Project fields of a 1.x manifest
+const V1_PROJECT_FIELDS: &[&str] = &["name", "description"];
+
+/// A manifest read from any supported schema version
+#[derive(Debug, Clone)]
+pub struct ManifestUpgrade {
+    /// Manifest in the current shape
+    pub manifest: WorkspaceManifest,
+    /// Schema version the manifest was stored with
+    pub source_version: String,
+    /// Fields that were dropped because the current schema has no place for them
+    pub warnings: Vec<String>,
+}
+
+impl ManifestUpgrade {
+    /// Whether the manifest was stored with an older schema
+    pub fn was_upgraded(&self) -> bool {
+        self.source_version != MANIFEST_SCHEMA_VERSION
+    }
+}
+
+/// Read a manifest stored with schema 1.x or 2.x
+///
+/// Fields missing from 1.x manifests get empty defaults; `created_at` is set
+/// to the time of the upgrade and `axon_version` to `"unknown"`. Manifests
+/// from a newer major schema version are rejected.
+pub fn read_manifest(value: Value) -> WorkspaceSetupResult<ManifestUpgrade> {
+    let Value::Object(mut fields) = value else {
+        return Err(invalid("manifest must be a JSON object"));
+    };
+    let source_version = fields
+        .get("schema_version")
+        .and_then(Value::as_str)
+        .unwrap_or("1.0")
+        .to_string();
+    let original = Value::Object(fields.clone());
+
+    match major_version(&source_version)? {
+        1 => upgrade_v1(&mut fields),
+        2 => {}
+        _ => {
+            return Err(WorkspaceSetupError::UnsupportedSchemaVersion(format!(
+                "manifest schema {source_version} is newer than {MANIFEST_SCHEMA_VERSION}; upgrade axon-mcp to read it"
+            )))
+        }
+    }
+    fields.insert("schema_version".to_string(), json!(MANIFEST_SCHEMA_VERSION));
+
+    let manifest: WorkspaceManifest = serde_json::from_value(Value::Object(fields))
+        .map_err(|e| invalid(&format!("manifest {source_version} is malformed: {e}")))?;
+
+    let current = serde_json::to_value(&manifest).map_err(|e| invalid(&e.to_string()))?;
+    let mut warnings = Vec::new();
+    dropped_fields(&original, &current, "", &mut warnings);
+
+    Ok(ManifestUpgrade {
+        manifest,
+        source_version,
+        warnings,
+    })
+}
+
+/// Serialize a manifest with the given schema version
+///
+/// Writing 1.x keeps only the fields that schema knows and returns a warning
+/// for every dropped field.
+pub fn write_manifest(
+    manifest: &WorkspaceManifest,
+    schema_version: &str,
+) -> WorkspaceSetupResult<(Value, Vec<String>)> {
+    let mut value = serde_json::to_value(manifest).map_err(|e| invalid(&e.to_string()))?;
+    let mut warnings = Vec::new();
+
+    match major_version(schema_version)? {
+        1 => {
+            if let Value::Object(fields) = &mut value {
+                retain_fields(fields, V1_FIELDS, "", &mut warnings);
+                if let Some(Value::Object(project)) = fields.get_mut("project") {
+                    retain_fields(project, V1_PROJECT_FIELDS, "project.", &mut warnings);
+                }
+            }
+        }
+        2 => {}
+        _ => {
+            return Err(WorkspaceSetupError::UnsupportedSchemaVersion(format!(
+                "cannot write manifest schema {schema_version}; the newest known is {MANIFEST_SCHEMA_VERSION}"
+            )))
+        }
+    }
+    value["schema_version"] = json!(schema_version);
+
+    Ok((value, warnings))
+}
+
+/// Read a stored workspace context, upgrading its manifest if needed
+pub fn read_workspace_context(
+    mut value: Value,
+) -> WorkspaceSetupResult<(WorkspaceContext, Vec<String>)> {
+    let mut warnings = Vec::new();
+    if let Some(manifest) = value.get_mut("manifest_data").filter(|m| !m.is_null()) {
+        let upgrade = read_manifest(manifest.take())?;
+        *manifest = serde_json::to_value(&upgrade.manifest).map_err(|e| invalid(&e.to_string()))?;
+        warnings = upgrade.warnings;
+    }
+
+    let context = serde_json::from_value(value)
+        .map_err(|e| invalid(&format!("workspace context is malformed: {e}")))?;
+    Ok((context, warnings))
+}
+
+/// Fill in the fields that 1.x manifests did not have
+fn upgrade_v1(fields: &mut Map<String, Value>) {
+    let ai_tool_type = fields
+        .get("ai_tool_type")
+        .cloned()
+        .unwrap_or_else(|| json!("claude-code"));
+    fields.insert("ai_tool_type".to_string(), ai_tool_type.clone());
+
+    let project = fields
+        .entry("project")
+        .or_insert_with(|| json!({}))
+        .as_object_mut();
+    if let Some(project) = project {
+        for (key, default) in [
+            ("name", json!("Unnamed project")),
+            ("description", json!("")),
+            ("complexity_score", json!(0)),
+            ("primary_domain", json!("software-development")),
+            ("technologies", json!([])),
+        ] {
+            project.entry(key).or_insert(default);
+        }
+    }
+
+    if let Some(agents) = fields.get_mut("agents").and_then(Value::as_array_mut) {
+        for agent in agents.iter_mut().filter_map(Value::as_object_mut) {
+            for (key, default) in [
+                ("description", json!("")),
+                ("prompt", json!("")),
+                ("capabilities", json!([])),
+                ("ai_tool_type", ai_tool_type.clone()),
+                ("dependencies", json!([])),
+            ] {
+                agent.entry(key).or_insert(default);
+            }
+        }
+    }
+
+    for (key, default) in [
+        ("agents", json!([])),
+        (
+            "workflow",
+            json!({
+                "workflow_description": "",
+                "recommended_agent_count": 0,
+                "suggested_agents": [],
+                "task_decomposition_strategy": "",
+                "coordination_patterns": [],
+                "workflow_steps": []
+            }),
+        ),
+        ("setup_instructions", json!([])),
+        ("generated_files", json!([])),
+        ("created_at", json!(Utc::now())),
+        ("axon_version", json!("unknown")),
+    ] {
+        fields.entry(key).or_insert(default);
+    }
+}
+
+/// Remove fields not in `allowed`, recording each as a warning
+fn retain_fields(
+    fields: &mut Map<String, Value>,
+    allowed: &[&str],
+    prefix: &str,
+    warnings: &mut Vec<String>,
+) {
+    fields.retain(|key, _| {
+        let keep = allowed.contains(&key.as_str());
+        if !keep {
+            warnings.push(format!("Dropped field '{prefix}{key}'"));
+        }
+        keep
+    });
+}
+
+/// Record fields present in `original` but missing from `current`
+fn dropped_fields(original: &Value, current: &Value, path: &str, warnings: &mut Vec<String>) {
+    match (original, current) {
+        (Value::Object(original), Value::Object(current)) => {
+            for (key, value) in original {
+                let field = format!("{path}{key}");
+                match current.get(key) {
+                    Some(current) => dropped_fields(value, current, &format!("{field}."), warnings),
+                    None => warnings.push(format!("Dropped field '{field}'")),
+                }
+            }
+        }
+        (Value::Array(original), Value::Array(current)) => {
+            let path = path.trim_end_matches('.');
+            for (index, (value, current)) in original.iter().zip(current).enumerate() {
+                dropped_fields(value, current, &format!("{path}[{index}]."), warnings);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn major_version(schema_version: &str) -> WorkspaceSetupResult<u32> {
+    schema_version
+        .split('.')
+        .next()
+        .and_then(|major| major.trim().parse().ok())
+        .ok_or_else(|| {
+            WorkspaceSetupError::UnsupportedSchemaVersion(format!(
+                "'{schema_version}' is not a valid manifest schema version"
+            ))
+        })
+}
+
+fn invalid(message: &str) -> WorkspaceSetupError {
+    WorkspaceSetupError::InvalidConfiguration(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_v1_manifest() {
+        let upgrade = read_manifest(json!({
+            "schema_version": "1.0",
+            "ai_tool_type": "claude-code",
+            "project": {"name": "Example Project", "description": "Example description"},
+            "agents": [{"name": "backend-dev"}],
+            "enhanced_features": {"prompt_version": "2025.1"}
+        }))
+        .unwrap();
+
+        assert!(upgrade.was_upgraded());
+        assert_eq!(upgrade.manifest.schema_version, MANIFEST_SCHEMA_VERSION);
+        assert_eq!(upgrade.manifest.project.name, "Example Project");
+        assert_eq!(upgrade.manifest.agents[0].name, "backend-dev");
+        assert!(upgrade.manifest.agents[0].capabilities.is_empty());
+        assert_eq!(upgrade.warnings, ["Dropped field 'enhanced_features'"]);
+
+        let (v1, warnings) = write_manifest(&upgrade.manifest, "1.0").unwrap();
+        assert_eq!(v1["schema_version"], "1.0");
+        assert!(v1.get("workflow").is_none());
+        assert!(v1["project"].get("technologies").is_none());
+        assert!(warnings.contains(&"Dropped field 'workflow'".to_string()));
+        assert!(warnings.contains(&"Dropped field 'project.technologies'".to_string()));
+
+        // A downgraded manifest reads back without losing the 1.x fields
+        let roundtrip = read_manifest(v1).unwrap();
+        assert_eq!(
+            roundtrip.manifest.project.description,
+            "Example description"
+        );
+        assert!(roundtrip.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_newer_manifest_is_rejected() {
+        assert!(matches!(
+            read_manifest(json!({"schema_version": "3.0"})),
+            Err(WorkspaceSetupError::UnsupportedSchemaVersion(_))
+        ));
+
+        let manifest = read_manifest(json!({"schema_version": "1.0"}))
+            .unwrap()
+            .manifest;
+        assert!(write_manifest(&manifest, "3.0").is_err());
+        assert!(write_manifest(&manifest, "latest").is_err());
+    }
+}
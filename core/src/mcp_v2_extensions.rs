@@ -58,7 +58,7 @@ impl Default for WorkDiscoveryConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ClaimResult {
     /// Task successfully claimed
-    Success(Task),
+    Success(Box<Task>),
     /// Task already claimed by another agent
     AlreadyClaimed { task_id: i32, claimed_by: String },
     /// Agent lacks required capabilities
@@ -152,6 +152,99 @@ impl CapabilityMatcher {
         let match_ratio = match_count as f64 / task_requirements.len() as f64;
         match_ratio >= 0.5
     }
+
+    /// Score an agent for a task and explain how the score came about
+    pub fn explain_match(
+        &self,
+        agent_name: &str,
+        task_requirements: &[String],
+        agent_capabilities: &[String],
+        agent_specializations: &[String],
+        active_tasks: usize,
+    ) -> AgentMatch {
+        let (matched, missing): (Vec<String>, Vec<String>) = task_requirements
+            .iter()
+            .cloned()
+            .partition(|requirement| agent_capabilities.contains(requirement));
+        let specialized: Vec<&str> = matched
+            .iter()
+            .filter(|capability| agent_specializations.contains(capability))
+            .map(String::as_str)
+            .collect();
+        let meets_requirements = self.meets_requirements(task_requirements, agent_capabilities);
+
+        let mut reasons = Vec::new();
+        if task_requirements.is_empty() {
+            reasons.push("task requires no specific capabilities".to_string());
+        } else {
+            let mut reason = format!(
+                "has {} of {} required capabilities",
+                matched.len(),
+                task_requirements.len()
+            );
+            if !matched.is_empty() {
+                reason.push_str(&format!(" ({})", matched.join(", ")));
+            }
+            reasons.push(reason);
+            if !missing.is_empty() {
+                reasons.push(format!("missing {}", missing.join(", ")));
+            }
+            if !specialized.is_empty() {
+                reasons.push(format!("specialized in {}", specialized.join(", ")));
+            }
+            if !meets_requirements {
+                reasons.push("below the 50% capability minimum".to_string());
+            }
+        }
+        reasons.push(match active_tasks {
+            0 => "no tasks in progress".to_string(),
+            1 => "1 task in progress".to_string(),
+            n => format!("{n} tasks in progress"),
+        });
+
+        AgentMatch {
+            agent_name: agent_name.to_string(),
+            score: self.calculate_match_score(
+                task_requirements,
+                agent_capabilities,
+                agent_specializations,
+            ),
+            meets_requirements,
+            matched_capabilities: matched,
+            missing_capabilities: missing,
+            active_tasks,
+            explanation: reasons.join("; "),
+        }
+    }
+
+    /// Order matches best first; ties go to the agent with fewer tasks in progress
+    pub fn rank(matches: &mut [AgentMatch]) {
+        matches.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.active_tasks.cmp(&b.active_tasks))
+                .then_with(|| a.agent_name.cmp(&b.agent_name))
+        });
+    }
+}
+
+/// How well a registered agent fits a task, with the reasons for its score
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentMatch {
+    /// Registered agent name
+    pub agent_name: String,
+    /// Fit score from [`CapabilityMatcher::calculate_match_score`]
+    pub score: f64,
+    /// Whether the agent has at least half of the required capabilities
+    pub meets_requirements: bool,
+    /// Required capabilities the agent has
+    pub matched_capabilities: Vec<String>,
+    /// Required capabilities the agent lacks
+    pub missing_capabilities: Vec<String>,
+    /// Tasks the agent currently has in progress
+    pub active_tasks: usize,
+    /// Human-readable summary of the above
+    pub explanation: String,
 }
 
 /// Task priority calculator with staleness factor
@@ -371,6 +464,40 @@ mod tests {
         assert!(matcher.meets_requirements(&task_reqs, &agent_caps));
     }
 
+    #[test]
+    fn test_explain_and_rank_matches() {
+        let matcher = CapabilityMatcher::default();
+        let task_reqs = vec!["rust".to_string(), "database".to_string()];
+
+        let mut matches = vec![
+            matcher.explain_match("frontend-dev", &task_reqs, &["react".to_string()], &[], 0),
+            matcher.explain_match(
+                "backend-dev",
+                &task_reqs,
+                &["rust".to_string(), "database".to_string()],
+                &[],
+                2,
+            ),
+            matcher.explain_match(
+                "db-dev",
+                &task_reqs,
+                &["database".to_string(), "rust".to_string()],
+                &[],
+                0,
+            ),
+        ];
+        CapabilityMatcher::rank(&mut matches);
+
+        let names: Vec<&str> = matches.iter().map(|m| m.agent_name.as_str()).collect();
+        assert_eq!(names, vec!["db-dev", "backend-dev", "frontend-dev"]);
+        assert_eq!(matches[2].missing_capabilities, task_reqs);
+        assert!(!matches[2].meets_requirements);
+        assert_eq!(
+            matches[1].explanation,
+            "has 2 of 2 required capabilities (rust, database); 2 tasks in progress"
+        );
+    }
+
     #[test]
     fn test_priority_calculator() {
         let config = WorkDiscoveryConfig::default();
@@ -378,6 +505,7 @@ mod tests {
 
         let mut task = Task {
             id: 1,
+            uid: None,
             code: "TEST-01".to_string(),
             name: "Test".to_string(),
             description: "Test task".to_string(),
@@ -386,6 +514,9 @@ mod tests {
             inserted_at: Utc::now() - chrono::Duration::hours(2),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now() - chrono::Duration::hours(2),
+            state_changed_at: Utc::now() - chrono::Duration::hours(2),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -0,0 +1,60 @@
+//! `@agent-name` mentions in message content
+//!
+//! Mentions are parsed server-side when a message is stored, so agents can
+//! find messages addressed to them in threads they don't follow.
+
+use crate::validation::TaskValidator;
+
+/// Extract the agent names mentioned as `@agent-name` in `content`
+///
+/// Names follow the agent name rules of [`TaskValidator::validate_agent_name`].
+/// An `@` preceded by a word character (as in e-mail addresses) does not start
+/// a mention. Each agent is returned once, in order of first mention.
+pub fn extract_mentions(content: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let starts_mention = c == '@'
+            && !prev.is_some_and(|p| p.is_alphanumeric() || matches!(p, '-' | '_' | '.' | '@'));
+        prev = Some(c);
+        if !starts_mention {
+            continue;
+        }
+
+        let start = index + c.len_utf8();
+        let mut end = start;
+        while let Some(&(i, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '-' || next == '_') {
+                break;
+            }
+            end = i + next.len_utf8();
+            prev = Some(next);
+            chars.next();
+        }
+
+        let name = content[start..end].trim_end_matches(['-', '_']);
+        if TaskValidator::validate_agent_name(name).is_ok() && !mentions.iter().any(|m| m == name) {
+            mentions.push(name.to_string());
+        }
+    }
+
+    mentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_mentions() {
+        assert_eq!(
+            extract_mentions("@backend-dev please sync with @qa_bot, then ping @backend-dev."),
+            ["backend-dev", "qa_bot"]
+        );
+        assert_eq!(extract_mentions("(cc @frontend-dev-)"), ["frontend-dev"]);
+        assert!(extract_mentions("mail me at dev@example.com").is_empty());
+        assert!(extract_mentions("a lone @ sign and @-dash").is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Message templates for common coordination patterns
+//!
+//! Templates keep agent prompts short: instead of spelling out the structure of
+//! a blocker report or handoff, an agent names a template and supplies the
+//! placeholder values, and the server renders the message content. Rendered
+//! messages always share the same headings, so other agents can parse them.
+
+use crate::error::{Result, TaskError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Placeholder that a template expects as `{{name}}` in its body
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplatePlaceholder {
+    /// Placeholder name
+    pub name: String,
+    /// What the value should contain
+    pub description: String,
+    /// Whether rendering fails without a value
+    pub required: bool,
+}
+
+/// Reusable message skeleton
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageTemplate {
+    /// Unique template name
+    pub name: String,
+    /// Message type used for messages rendered from the template
+    pub message_type: String,
+    /// When to use the template
+    pub description: String,
+    /// Markdown body with `{{placeholder}}` markers
+    pub body: String,
+    /// Placeholders used in the body
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
+impl MessageTemplate {
+    /// Substitute `values` into the body
+    ///
+    /// Missing optional placeholders render as an empty string; missing
+    /// required ones and values for unknown placeholders are rejected.
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String> {
+        if let Some(unknown) = values
+            .keys()
+            .find(|key| !self.placeholders.iter().any(|p| &p.name == *key))
+        {
+            return Err(TaskError::Validation(format!(
+                "Template '{}' has no placeholder '{unknown}'",
+                self.name
+            )));
+        }
+
+        let mut substitutions = HashMap::new();
+        for placeholder in &self.placeholders {
+            let value = match values.get(&placeholder.name).map(|v| v.trim()) {
+                Some(value) if !value.is_empty() => value,
+                _ if placeholder.required => {
+                    return Err(TaskError::Validation(format!(
+                        "Template '{}' requires a value for '{}'",
+                        self.name, placeholder.name
+                    )));
+                }
+                _ => "",
+            };
+            substitutions.insert(placeholder.name.as_str(), value);
+        }
+
+        // One pass over the body, so markers inside values stay as written
+        let mut rendered = String::with_capacity(self.body.len());
+        let mut rest = self.body.as_str();
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let marker = &rest[start..];
+            let value = marker[2..]
+                .find("}}")
+                .and_then(|end| Some((end + 4, *substitutions.get(&marker[2..end + 2])?)));
+            match value {
+                Some((len, value)) => {
+                    rendered.push_str(value);
+                    rest = &marker[len..];
+                }
+                None => {
+                    rendered.push_str("{{");
+                    rest = &marker[2..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+}
+
+/// Named collection of message templates
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageTemplateRegistry {
+    templates: BTreeMap<String, MessageTemplate>,
+}
+
+impl MessageTemplateRegistry {
+    /// Registry with the built-in blocker report, handoff summary and review request
+    pub fn new() -> Self {
+        let mut registry = Self {
+            templates: BTreeMap::new(),
+        };
+        registry.register(MessageTemplate {
+            name: "blocker_report".to_string(),
+            message_type: "blocker".to_string(),
+            description: "Report what prevents progress on the task and what is needed to continue"
+                .to_string(),
+            body: "## Blocker\n{{summary}}\n\n## Blocked by\n{{blocked_by}}\n\n## Needed to continue\n{{needed}}\n"
+                .to_string(),
+            placeholders: vec![
+                placeholder("summary", "What is blocked and why", true),
+                placeholder("blocked_by", "Task code, agent or external dependency", true),
+                placeholder("needed", "Decision, input or fix required", false),
+            ],
+        });
+        registry.register(MessageTemplate {
+            name: "handoff_summary".to_string(),
+            message_type: "handoff".to_string(),
+            description: "Hand the task over to another agent with the state of the work"
+                .to_string(),
+            body: "## Handoff\n{{summary}}\n\n## Done\n{{completed}}\n\n## Remaining\n{{remaining}}\n\n## Notes\n{{notes}}\n"
+                .to_string(),
+            placeholders: vec![
+                placeholder("summary", "One-line summary of the handoff", true),
+                placeholder("completed", "Work finished so far", true),
+                placeholder("remaining", "Work left for the receiving agent", true),
+                placeholder("notes", "Pitfalls, links or context", false),
+            ],
+        });
+        registry.register(MessageTemplate {
+            name: "review_request".to_string(),
+            message_type: "review".to_string(),
+            description: "Ask another agent to review finished work".to_string(),
+            body: "## Review request\n{{summary}}\n\n## Changes\n{{changes}}\n\n## Focus on\n{{focus}}\n"
+                .to_string(),
+            placeholders: vec![
+                placeholder("summary", "What should be reviewed", true),
+                placeholder("changes", "Files, commits or artifacts to look at", true),
+                placeholder("focus", "Areas that need particular attention", false),
+            ],
+        });
+        registry
+    }
+
+    /// Add a template, replacing any template with the same name
+    pub fn register(&mut self, template: MessageTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    /// Look up a template by name
+    pub fn get(&self, name: &str) -> Result<&MessageTemplate> {
+        self.templates
+            .get(name)
+            .ok_or_else(|| TaskError::NotFound(format!("Message template '{name}' not found")))
+    }
+
+    /// All templates, ordered by name
+    pub fn list(&self) -> Vec<MessageTemplate> {
+        self.templates.values().cloned().collect()
+    }
+}
+
+impl Default for MessageTemplateRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn placeholder(name: &str, description: &str, required: bool) -> TemplatePlaceholder {
+    TemplatePlaceholder {
+        name: name.to_string(),
+        description: description.to_string(),
+        required,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_builtin_template() {
+        let registry = MessageTemplateRegistry::new();
+        let template = registry.get("blocker_report").unwrap();
+        assert_eq!(template.message_type, "blocker");
+
+        let values = HashMap::from([
+            ("summary".to_string(), "Cannot run migrations".to_string()),
+            ("blocked_by".to_string(), "DB-12".to_string()),
+        ]);
+        let content = template.render(&values).unwrap();
+        assert!(content.contains("## Blocker\nCannot run migrations"));
+        assert!(content.contains("## Blocked by\nDB-12"));
+        assert!(!content.contains("{{"));
+
+        let missing = HashMap::from([("summary".to_string(), "x".to_string())]);
+        assert!(template.render(&missing).is_err());
+
+        let mut unknown = values.clone();
+        unknown.insert("severity".to_string(), "high".to_string());
+        assert!(template.render(&unknown).is_err());
+
+        assert!(registry.get("nope").is_err());
+    }
+
+    #[test]
+    fn test_values_are_not_expanded() {
+        let template = MessageTemplate {
+            name: "note".to_string(),
+            message_type: "comment".to_string(),
+            description: String::new(),
+            body: "{{a}} / {{b}} / {{c}".to_string(),
+            placeholders: vec![
+                placeholder("a", "First", true),
+                placeholder("b", "Second", false),
+            ],
+        };
+        let values = HashMap::from([
+            ("a".to_string(), "uses {{b}}".to_string()),
+            ("b".to_string(), "{{a}}".to_string()),
+        ]);
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "uses {{b}} / {{a}} / {{c}"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Catalogs of user-facing messages
+//!
+//! Messages read by people rather than agents, such as workspace setup
+//! responses and CLI output, are looked up by [`MessageKey`] in the catalog
+//! of the configured [`Locale`]. Any message can be overridden by key, and
+//! `{placeholder}` arguments are filled in by [`MessageCatalog::format`].
+
+use crate::error::{Result, TaskError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Language of user-facing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// Czech
+    Cs,
+}
+
+impl Locale {
+    /// Every supported locale
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Cs];
+
+    /// ISO 639-1 code of the language
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Cs => "cs",
+        }
+    }
+
+    /// Locale of a language tag such as `cs`, `cs-CZ` or `cs_CZ.UTF-8`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code() == language)
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_tag(s).ok_or_else(|| {
+            TaskError::Validation(format!(
+                "Unsupported locale '{s}'. Supported: {}",
+                Self::ALL.map(|l| l.code()).join(", ")
+            ))
+        })
+    }
+}
+
+macro_rules! message_keys {
+    ($($key:ident => $name:literal { en: $en:literal, cs: $cs:literal $(,)? },)+) => {
+        /// User-facing message, identified in overrides by its snake_case name
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum MessageKey {
+            $($key,)+
+        }
+
+        impl MessageKey {
+            /// Every message key
+            pub const ALL: &'static [MessageKey] = &[$(MessageKey::$key,)+];
+
+            /// Name of the message in configuration overrides
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(MessageKey::$key => $name,)+
+                }
+            }
+
+            /// Built-in text of the message in `locale`
+            pub fn text(&self, locale: Locale) -> &'static str {
+                match (self, locale) {
+                    $(
+                        (MessageKey::$key, Locale::En) => $en,
+                        (MessageKey::$key, Locale::Cs) => $cs,
+                    )+
+                }
+            }
+        }
+    };
+}
+
+message_keys! {
+    SetupInstructionsReady => "setup_instructions_ready" {
+        en: "Setup instructions generated successfully. Follow these steps to configure your workspace using Axon MCP functions.",
+        cs: "Instrukce k nastavení byly vygenerovány. Postupujte podle těchto kroků a nastavte pracovní prostor pomocí funkcí Axon MCP.",
+    },
+    PrdValidationFailed => "prd_validation_failed" {
+        en: "PRD validation failed. Please fix these issues before proceeding:\n{errors}",
+        cs: "Kontrola PRD selhala. Než budete pokračovat, opravte tyto problémy:\n{errors}",
+    },
+    WorkflowRecommended => "workflow_recommended" {
+        en: "Analyzed PRD '{title}' and recommend {count} agents for this {complexity} complexity {archetype} project. Review the suggested agents and confirm to proceed.",
+        cs: "PRD '{title}' bylo analyzováno. Pro tento projekt typu {archetype} se složitostí {complexity} doporučujeme tento počet agentů: {count}. Zkontrolujte navržené agenty a potvrďte pokračování.",
+    },
+    ComplexityLow => "complexity_low" { en: "low", cs: "nízkou", },
+    ComplexityMedium => "complexity_medium" { en: "medium", cs: "střední", },
+    ComplexityHigh => "complexity_high" { en: "high", cs: "vysokou", },
+    ComplexityVeryHigh => "complexity_very_high" { en: "very high", cs: "velmi vysokou", },
+    NextStepRegisterAgents => "next_step_register_agents" {
+        en: "Register these agents",
+        cs: "Zaregistrovat tyto agenty",
+    },
+    NextStepRefineAgentCount => "next_step_refine_agent_count" {
+        en: "Modify agent count (currently {count})",
+        cs: "Upravit počet agentů (nyní {count})",
+    },
+    AgentNameEmpty => "agent_name_empty" {
+        en: "Agent name cannot be empty. Please provide a valid agent name (e.g., 'project-manager').",
+        cs: "Název agenta nesmí být prázdný. Zadejte platný název agenta (např. 'project-manager').",
+    },
+    AgentDescriptionEmpty => "agent_description_empty" {
+        en: "Agent description cannot be empty. Please provide a clear description of the agent's role.",
+        cs: "Popis agenta nesmí být prázdný. Uveďte srozumitelný popis role agenta.",
+    },
+    AgentCapabilitiesEmpty => "agent_capabilities_empty" {
+        en: "Agent must have at least one capability. Please specify the agent's skills.",
+        cs: "Agent musí mít alespoň jednu schopnost. Uveďte dovednosti agenta.",
+    },
+    AgentRegistered => "agent_registered" {
+        en: "Agent '{name}' registered successfully with {count} capabilities and enhanced 2025 prompt.",
+        cs: "Agent '{name}' byl zaregistrován s vylepšeným promptem (počet schopností: {count}).",
+    },
+    MainFileInstructionsReady => "main_file_instructions_ready" {
+        en: "Main file instructions generated for {ai_tool}. Use these templates to create your coordination file.",
+        cs: "Instrukce k hlavnímu souboru pro {ai_tool} byly vygenerovány. Podle těchto šablon vytvořte koordinační soubor.",
+    },
+    MainFileContentEmpty => "main_file_content_empty" {
+        en: "File content cannot be empty. Please provide the complete content for the main coordination file.",
+        cs: "Obsah souboru nesmí být prázdný. Zadejte úplný obsah hlavního koordinačního souboru.",
+    },
+    MainFileCreated => "main_file_created" {
+        en: "Main coordination file '{file_name}' created successfully.",
+        cs: "Hlavní koordinační soubor '{file_name}' byl vytvořen.",
+    },
+    ManifestGenerated => "manifest_generated" {
+        en: "Enhanced workspace manifest generated for project '{project}' with {count} agents using 2025 best practices.",
+        cs: "Manifest pracovního prostoru pro projekt '{project}' byl vygenerován (počet agentů: {count}).",
+    },
+    CliSchemaUpToDate => "cli_schema_up_to_date" {
+        en: "Database schema is up to date",
+        cs: "Schéma databáze je aktuální",
+    },
+    CliMigrationsDryRun => "cli_migrations_dry_run" {
+        en: "{count} pending migration(s), nothing applied (dry run)",
+        cs: "Čekající migrace: {count}, nic nebylo provedeno (zkušební běh)",
+    },
+    CliMigrationsApplied => "cli_migrations_applied" {
+        en: "Applied {count} migration(s)",
+        cs: "Provedené migrace: {count}",
+    },
+    CliSchemaVersion => "cli_schema_version" {
+        en: "Schema version: {version} (latest: {latest})",
+        cs: "Verze schématu: {version} (nejnovější: {latest})",
+    },
+    CliSchemaVersionNone => "cli_schema_version_none" {
+        en: "Schema version: none (latest: {latest})",
+        cs: "Verze schématu: žádná (nejnovější: {latest})",
+    },
+    CliMigrationsModified => "cli_migrations_modified" {
+        en: "Applied migrations differ from the ones shipped with this binary",
+        cs: "Provedené migrace se liší od migrací dodaných s tímto programem",
+    },
+    CliAuditExported => "cli_audit_exported" {
+        en: "Exported {count} audit event(s) to {path}",
+        cs: "Události auditu exportované do {path}: {count}",
+    },
+    CliRowsExported => "cli_rows_exported" {
+        en: "Exported {count} row(s) to {path}",
+        cs: "Řádky exportované do {path}: {count}",
+    },
+    CliDigestSent => "cli_digest_sent" {
+        en: "Sent {frequency} digest to {recipients} recipient(s): {completed} task(s) completed, {blocked} blocked",
+        cs: "Souhrn ({frequency}) odeslán, příjemci: {recipients}; dokončené úkoly: {completed}, blokované: {blocked}",
+    },
+    CliServerReady => "cli_server_ready" {
+        en: "Axon MCP Server is ready",
+        cs: "Axon MCP Server je připraven",
+    },
+    CliListeningOn => "cli_listening_on" {
+        en: "Listening on: {address}",
+        cs: "Naslouchá na: {address}",
+    },
+    CliProject => "cli_project" { en: "Project: {project}", cs: "Projekt: {project}", },
+    CliDatabase => "cli_database" { en: "Database: {database}", cs: "Databáze: {database}", },
+    CliRequestLogging => "cli_request_logging" {
+        en: "Request logging: enabled",
+        cs: "Logování požadavků: zapnuto",
+    },
+    CliPressCtrlC => "cli_press_ctrl_c" {
+        en: "Press Ctrl+C to shut down",
+        cs: "Server ukončíte klávesami Ctrl+C",
+    },
+    CliShutdownSignal => "cli_shutdown_signal" {
+        en: "Shutdown signal received, stopping server...",
+        cs: "Přijat signál k ukončení, server se zastavuje...",
+    },
+    CliShutDownCleanly => "cli_shut_down_cleanly" {
+        en: "Axon MCP Server shut down cleanly",
+        cs: "Axon MCP Server byl řádně ukončen",
+    },
+    CliServerError => "cli_server_error" {
+        en: "Server error: {error}",
+        cs: "Chyba serveru: {error}",
+    },
+}
+
+/// Messages in one locale, with configured overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageCatalog {
+    pub locale: Locale,
+    /// Replacement texts keyed by message name, e.g. `cli_server_ready`
+    pub overrides: HashMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// Built-in messages of `locale`
+    pub fn new(locale: Locale) -> Self {
+        Self {
+            locale,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Check that every override names a known message
+    pub fn validate(&self) -> Result<()> {
+        for name in self.overrides.keys() {
+            if !MessageKey::ALL.iter().any(|key| key.as_str() == name) {
+                return Err(TaskError::Validation(format!(
+                    "Unknown message '{name}' in message overrides"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Text of `key` with its placeholders left in
+    pub fn get(&self, key: MessageKey) -> &str {
+        self.overrides
+            .get(key.as_str())
+            .map_or_else(|| key.text(self.locale), String::as_str)
+    }
+
+    /// Text of `key` with each `{name}` placeholder replaced by its argument
+    pub fn format(&self, key: MessageKey, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_catalogs() {
+        assert_eq!(Locale::from_tag("cs_CZ.UTF-8"), Some(Locale::Cs));
+        assert_eq!("EN-us".parse::<Locale>().unwrap(), Locale::En);
+        assert!("de".parse::<Locale>().is_err());
+
+        // Every message has the same placeholders in every locale
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text
+                .split('{')
+                .skip(1)
+                .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+                .collect();
+            names.sort();
+            names
+        };
+        for key in MessageKey::ALL {
+            let english = placeholders(key.text(Locale::En));
+            for locale in Locale::ALL {
+                assert_eq!(placeholders(key.text(locale)), english, "{}", key.as_str());
+            }
+        }
+
+        let mut catalog = MessageCatalog::new(Locale::Cs);
+        assert_eq!(
+            catalog.format(
+                MessageKey::CliRowsExported,
+                &[("count", &3), ("path", &"a.csv")]
+            ),
+            "Řádky exportované do a.csv: 3"
+        );
+        catalog.overrides.insert(
+            "cli_server_ready".to_string(),
+            "Ready on {host}".to_string(),
+        );
+        assert!(catalog.validate().is_ok());
+        assert_eq!(
+            catalog.format(MessageKey::CliServerReady, &[("host", &"localhost")]),
+            "Ready on localhost"
+        );
+
+        catalog
+            .overrides
+            .insert("no_such_message".to_string(), String::new());
+        assert!(catalog.validate().is_err());
+    }
+}
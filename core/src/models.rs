@@ -24,6 +24,7 @@ pub enum TaskCode {
 ///
 /// let task = Task {
 ///     id: 42,
+///     uid: None,
 ///     code: "FEAT-001".to_string(),
 ///     name: "Implement user authentication".to_string(),
 ///     description: "Add JWT-based auth with role-based access control".to_string(),
@@ -32,6 +33,9 @@ pub enum TaskCode {
 ///     inserted_at: Utc::now(),
 ///     done_at: None,
 ///     claimed_at: None,
+///     lease_expires_at: None,
+///     updated_at: Utc::now(),
+///     state_changed_at: Utc::now(),
 ///     workflow_definition_id: None,
 ///     workflow_cursor: None,
 ///     priority_score: 5.0,
@@ -49,6 +53,9 @@ pub enum TaskCode {
 pub struct Task {
     /// Auto-increment primary key
     pub id: i32,
+    /// Stable identifier across databases (UUID or ULID); none under the integer ID strategy
+    #[serde(default)]
+    pub uid: Option<String>,
     /// Human-readable identifier (e.g., "ARCH-01", "DB-15")
     pub code: String,
     /// Brief task title
@@ -65,6 +72,15 @@ pub struct Task {
     pub done_at: Option<DateTime<Utc>>,
     /// Timestamp when task was claimed by an agent (for timeout mechanism)
     pub claimed_at: Option<DateTime<Utc>>,
+    /// When the current claim lapses and the task may be released by cleanup
+    #[serde(default)]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    /// Last modification timestamp, for incremental sync
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    /// When the task entered its current state
+    #[serde(default)]
+    pub state_changed_at: DateTime<Utc>,
 
     // MCP v2 Extensions
     /// Workflow definition ID for structured task execution
@@ -122,7 +138,14 @@ pub struct Task {
 ///     // Safe to move to InProgress
 /// }
 /// ```
-#[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize, PartialEq, Eq)]
+///
+/// # Text Form
+///
+/// States are written in PascalCase (`InProgress`), the form stored in the
+/// database and returned by the API. Parsing, including deserialization,
+/// ignores case and accepts `_`, `-` or spaces between words, so
+/// `"in_progress"`, `"in-progress"` and `"In Progress"` all name `InProgress`.
+#[derive(Debug, Clone, Copy, Hash, Serialize, PartialEq, Eq)]
 pub enum TaskState {
     /// Newly created task
     Created,
@@ -267,12 +290,32 @@ pub struct TaskFilter {
     /// Filter by task state
     pub state: Option<TaskState>,
 
+    /// Match any of these states, together with `state`
+    #[serde(default)]
+    pub states: Vec<TaskState>,
+
+    /// Match any of these owners, together with `owner`
+    #[serde(default)]
+    pub owners: Vec<String>,
+
+    /// Match codes starting with this prefix; a trailing `*` is ignored (`ARCH-*`)
+    #[serde(default)]
+    pub code_prefix: Option<String>,
+
+    /// `true` matches only owned tasks, `false` only unowned ones
+    #[serde(default)]
+    pub has_owner: Option<bool>,
+
     /// Filter tasks created on or after this date
     pub date_from: Option<DateTime<Utc>>,
 
     /// Filter tasks created on or before this date
     pub date_to: Option<DateTime<Utc>>,
 
+    /// Filter tasks modified on or after this date, for incremental sync
+    #[serde(default)]
+    pub modified_since: Option<DateTime<Utc>>,
+
     /// Filter tasks completed on or after this date
     pub completed_after: Option<DateTime<Utc>>,
 
@@ -284,6 +327,67 @@ pub struct TaskFilter {
 
     /// Number of tasks to skip (for pagination)
     pub offset: Option<u32>,
+
+    /// Also search tasks moved to the archive database
+    #[serde(default)]
+    pub include_archive: bool,
+}
+
+impl TaskFilter {
+    /// States to match: `state` and `states` combined, empty for any state
+    pub fn state_set(&self) -> Vec<TaskState> {
+        let mut states: Vec<TaskState> = self.state.into_iter().collect();
+        for state in &self.states {
+            if !states.contains(state) {
+                states.push(*state);
+            }
+        }
+        states
+    }
+
+    /// Owners to match: `owner` and `owners` combined, empty for any owner
+    pub fn owner_set(&self) -> Vec<String> {
+        let mut owners: Vec<String> = self.owner.iter().cloned().collect();
+        for owner in &self.owners {
+            if !owners.contains(owner) {
+                owners.push(owner.clone());
+            }
+        }
+        owners
+    }
+
+    /// Code prefix without its trailing wildcard, if one is set
+    pub fn code_prefix(&self) -> Option<&str> {
+        self.code_prefix
+            .as_deref()
+            .map(|prefix| prefix.trim_end_matches('*'))
+            .filter(|prefix| !prefix.is_empty())
+    }
+
+    /// Whether `task` passes the criteria applied by repositories
+    ///
+    /// Pagination and the archive flag are left to the caller.
+    pub fn matches(&self, task: &Task) -> bool {
+        let states = self.state_set();
+        let owners = self.owner_set();
+        (states.is_empty() || states.contains(&task.state))
+            && (owners.is_empty()
+                || task
+                    .owner_agent_name
+                    .as_ref()
+                    .is_some_and(|owner| owners.contains(owner)))
+            && self
+                .code_prefix()
+                .is_none_or(|prefix| task.code.starts_with(prefix))
+            && self
+                .has_owner
+                .is_none_or(|has_owner| task.owner_agent_name.is_some() == has_owner)
+            && self.date_from.is_none_or(|from| task.inserted_at >= from)
+            && self.date_to.is_none_or(|to| task.inserted_at <= to)
+            && self
+                .modified_since
+                .is_none_or(|since| task.updated_at >= since)
+    }
 }
 
 // MCP v2 New Entity Types
@@ -322,6 +426,9 @@ pub struct KnowledgeObject {
 pub struct TaskMessage {
     /// Auto-increment primary key
     pub id: i32,
+    /// Stable identifier across databases (UUID or ULID); none under the integer ID strategy
+    #[serde(default)]
+    pub uid: Option<String>,
     /// Task code instead of ID
     pub task_code: String,
     /// Author agent name (kebab-case)
@@ -336,19 +443,161 @@ pub struct TaskMessage {
     pub content: String,
     /// Reply to message ID for threading
     pub reply_to_message_id: Option<i32>,
+    /// Machine-readable payload, validated against the message type's schema
+    #[serde(default)]
+    pub structured_data: Option<serde_json::Value>,
+}
+
+/// Mention of an agent as `@agent-name` in a task message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskMention {
+    /// Auto-increment primary key
+    pub id: i32,
+    /// Message containing the mention
+    pub message_id: i32,
+    /// Task code of the message
+    pub task_code: String,
+    /// Agent addressed by the mention
+    pub mentioned_agent_name: String,
+    /// Author of the message
+    pub author_agent_name: String,
+    /// Type of the message
+    pub message_type: String,
+    /// Content of the message
+    pub content: String,
+    /// When the message was posted
+    pub created_at: DateTime<Utc>,
+}
+
+/// Task change applied when a message of a given type is posted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum MessageEffect {
+    /// Move the task to `state`
+    SetState { state: TaskState },
+    /// Reassign the task to the message's target agent
+    AssignToTarget,
+    /// Reassign the task to the message's author, e.g. on an accepted handoff
+    AssignToAuthor,
+}
+
+/// Workflow rule wiring a message type to a task change
+///
+/// Typical rules move a task to `Blocked` on a `blocker` message, to `Review`
+/// on a `completion` message, and hand it to the author of a
+/// `handoff_accepted` reply.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessageEffectRule {
+    /// Message type that triggers the effect
+    pub message_type: String,
+    /// Change applied to the message's task
+    #[serde(flatten)]
+    pub effect: MessageEffect,
+}
+
+/// Lightweight markdown comment on a task
+///
+/// Comments are free-form notes from humans or agents. Unlike [`TaskMessage`]s
+/// they carry no protocol meaning, can be edited by their author and are
+/// soft-deleted so their history stays available.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskComment {
+    /// Auto-increment primary key
+    pub id: i32,
+    /// Code of the commented task
+    pub task_code: String,
+    /// Author agent name (kebab-case)
+    pub author_agent_name: String,
+    /// Markdown content
+    pub content: String,
+    /// Current revision, starting at 1 and incremented on every edit
+    pub revision: i32,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last edit timestamp
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Soft-delete timestamp
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl TaskComment {
+    /// Whether the comment has been deleted
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+}
+
+/// Superseded content of a comment, recorded on every edit and on deletion
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskCommentRevision {
+    /// Auto-increment primary key
+    pub id: i32,
+    /// Comment this revision belongs to
+    pub comment_id: i32,
+    /// Revision number the content had before the change
+    pub revision: i32,
+    /// Markdown content before the change
+    pub content: String,
+    /// Agent that edited or deleted the comment
+    pub edited_by: String,
+    /// When the change happened
+    pub edited_at: DateTime<Utc>,
+}
+
+/// Entry of the capability catalog that agent and task capabilities are normalized against
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Capability {
+    /// Canonical capability name (kebab-case)
+    pub name: String,
+    /// What the capability covers
+    pub description: String,
+    /// Alternative spellings that normalize to this capability
+    pub aliases: Vec<String>,
+    /// Capability that replaces this one after deprecation
+    pub replaced_by: Option<String>,
+    /// When the capability was deprecated
+    pub deprecated_at: Option<DateTime<Utc>>,
+    /// When the capability was first defined
+    pub created_at: DateTime<Utc>,
+    /// When the capability was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Capability {
+    /// Whether the capability has been deprecated
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated_at.is_some()
+    }
+}
+
+/// Link between a task and an item in an external tracker such as a GitHub issue
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalLink {
+    /// External tracker, e.g. `github`
+    pub system: String,
+    /// Code of the linked task
+    pub task_code: String,
+    /// ID of the item in the tracker
+    pub external_id: String,
+    /// Web URL of the item
+    pub url: Option<String>,
+    /// Task state last pushed to the tracker
+    pub synced_state: Option<TaskState>,
+    /// When the link was last synchronized
+    pub synced_at: DateTime<Utc>,
 }
 
 // Note: MessageType is now a String for project flexibility
 // Projects can define their own message types like:
-// - "handoff" - předávací protokoly mezi agenty
-// - "comment" - obecné komentáře
-// - "question" - otázky vyžadující odpověď
-// - "blocker" - blokující problémy
-// - "solution" - řešení a návrhy
-// - "review" - code review komentáře
-// - "specification" - specifikace a požadavky
-// - "test-results" - výsledky testů
-// - etc. - libovolné podle potřeb projektu
+// - "handoff" - handoff protocols between agents
+// - "comment" - general comments
+// - "question" - questions that need an answer
+// - "blocker" - blocking issues
+// - "solution" - solutions and proposals
+// - "review" - code review comments
+// - "specification" - specifications and requirements
+// - "test-results" - test results
+// - etc. - anything else the project needs
 
 /// Knowledge object types for categorizing information
 #[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize, PartialEq, Eq)]
@@ -539,6 +788,21 @@ pub struct WorkSession {
     pub interruptions: Vec<WorkInterruption>,
 }
 
+/// One claim of a task by an agent, kept after the claim ends
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskClaim {
+    /// Claimed task
+    pub task_id: i32,
+    /// Agent that claimed the task
+    pub agent_name: String,
+    /// When the claim was made
+    pub claimed_at: DateTime<Utc>,
+    /// When the claim ended (None while the agent holds the task)
+    pub released_at: Option<DateTime<Utc>>,
+    /// Why the claim ended: `released`, `timed_out`, `reassigned` or `completed`
+    pub release_reason: Option<String>,
+}
+
 /// Handoff package for structured task transitions
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HandoffPackage {
@@ -583,6 +847,7 @@ impl Task {
     ) -> Self {
         Self {
             id,
+            uid: None,
             code,
             name,
             description,
@@ -591,6 +856,9 @@ impl Task {
             inserted_at,
             done_at,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: inserted_at,
+            state_changed_at: inserted_at,
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -643,23 +911,72 @@ impl Task {
     }
 }
 
-impl std::fmt::Display for TaskState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl TaskState {
+    /// Every state, in lifecycle order
+    pub const ALL: [TaskState; 10] = [
+        TaskState::Created,
+        TaskState::InProgress,
+        TaskState::Blocked,
+        TaskState::Review,
+        TaskState::Done,
+        TaskState::Archived,
+        TaskState::PendingDecomposition,
+        TaskState::PendingHandoff,
+        TaskState::Quarantined,
+        TaskState::WaitingForDependency,
+    ];
+
+    /// Canonical PascalCase name of the state
+    pub fn as_str(&self) -> &'static str {
         match self {
-            TaskState::Created => write!(f, "Created"),
-            TaskState::InProgress => write!(f, "InProgress"),
-            TaskState::Blocked => write!(f, "Blocked"),
-            TaskState::Review => write!(f, "Review"),
-            TaskState::Done => write!(f, "Done"),
-            TaskState::Archived => write!(f, "Archived"),
-            TaskState::PendingDecomposition => write!(f, "PendingDecomposition"),
-            TaskState::PendingHandoff => write!(f, "PendingHandoff"),
-            TaskState::Quarantined => write!(f, "Quarantined"),
-            TaskState::WaitingForDependency => write!(f, "WaitingForDependency"),
+            TaskState::Created => "Created",
+            TaskState::InProgress => "InProgress",
+            TaskState::Blocked => "Blocked",
+            TaskState::Review => "Review",
+            TaskState::Done => "Done",
+            TaskState::Archived => "Archived",
+            TaskState::PendingDecomposition => "PendingDecomposition",
+            TaskState::PendingHandoff => "PendingHandoff",
+            TaskState::Quarantined => "Quarantined",
+            TaskState::WaitingForDependency => "WaitingForDependency",
         }
     }
 }
 
+impl std::fmt::Display for TaskState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TaskState {
+    type Err = crate::error::TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect();
+        TaskState::ALL
+            .into_iter()
+            .find(|state| state.as_str().eq_ignore_ascii_case(&key))
+            .ok_or_else(|| {
+                let expected: Vec<&str> = TaskState::ALL.iter().map(TaskState::as_str).collect();
+                crate::error::TaskError::Validation(format!(
+                    "Unknown task state '{s}'; expected one of {}",
+                    expected.join(", ")
+                ))
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskState {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = std::borrow::Cow::<str>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -668,6 +985,7 @@ mod tests {
     fn test_valid_state_transitions() {
         let task = Task {
             id: 1,
+            uid: None,
             code: "TEST-01".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -676,6 +994,9 @@ mod tests {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -739,6 +1060,7 @@ mod tests {
     fn test_no_same_state_transition() {
         let task = Task {
             id: 1,
+            uid: None,
             code: "TEST-01".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -747,6 +1069,9 @@ mod tests {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -760,4 +1085,82 @@ mod tests {
         // Cannot transition to the same state
         assert!(!task.can_transition_to(TaskState::InProgress));
     }
+
+    #[test]
+    fn test_task_state_text_forms() {
+        for state in TaskState::ALL {
+            assert_eq!(state.to_string().parse::<TaskState>().unwrap(), state);
+            let json = serde_json::to_value(state).unwrap();
+            assert_eq!(json, serde_json::json!(state.as_str()));
+            assert_eq!(serde_json::from_value::<TaskState>(json).unwrap(), state);
+        }
+
+        for alias in ["in_progress", "in-progress", "IN PROGRESS", "inprogress"] {
+            assert_eq!(alias.parse::<TaskState>().unwrap(), TaskState::InProgress);
+        }
+        assert_eq!(
+            serde_json::from_str::<TaskState>("\"waiting_for_dependency\"").unwrap(),
+            TaskState::WaitingForDependency
+        );
+        assert!(matches!(
+            "started".parse::<TaskState>(),
+            Err(crate::error::TaskError::Validation(message)) if message.contains("InProgress")
+        ));
+        assert!(serde_json::from_str::<TaskState>("\"started\"").is_err());
+    }
+
+    #[test]
+    fn test_task_filter_matches() {
+        let mut task = Task {
+            id: 1,
+            uid: None,
+            code: "ARCH-01".to_string(),
+            name: "Test Task".to_string(),
+            description: "Test description".to_string(),
+            owner_agent_name: None,
+            state: TaskState::Blocked,
+            inserted_at: Utc::now(),
+            done_at: None,
+            claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
+            workflow_definition_id: None,
+            workflow_cursor: None,
+            priority_score: 5.0,
+            parent_task_id: None,
+            failure_count: 0,
+            required_capabilities: vec![],
+            estimated_effort: None,
+            confidence_threshold: 0.8,
+        };
+
+        let filter = TaskFilter {
+            state: Some(TaskState::Created),
+            states: vec![TaskState::Blocked],
+            code_prefix: Some("ARCH-*".to_string()),
+            has_owner: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(filter.state_set(), [TaskState::Created, TaskState::Blocked]);
+        assert_eq!(filter.code_prefix(), Some("ARCH-"));
+        assert!(filter.matches(&task));
+
+        task.owner_agent_name = Some("agent-2".to_string());
+        assert!(!filter.matches(&task));
+        let filter = TaskFilter {
+            owner: Some("agent-1".to_string()),
+            owners: vec!["agent-2".to_string()],
+            has_owner: Some(true),
+            ..Default::default()
+        };
+        assert!(filter.matches(&task));
+
+        task.code = "arch-01".to_string();
+        assert!(!TaskFilter {
+            code_prefix: Some("ARCH".to_string()),
+            ..Default::default()
+        }
+        .matches(&task));
+    }
 }
@@ -0,0 +1,311 @@
+//! Multi-stage task pipelines
+//!
+//! A pipeline template lists the stages a piece of work passes through, for
+//! example design, implementation and review, each needing other capabilities.
+//! Creating a pipeline makes a pipeline task with one subtask per stage, coded
+//! `<CODE>-1`, `<CODE>-2` and so on. Only the first stage can be picked up;
+//! the later stages and the pipeline task wait for their dependency. When a
+//! stage moves to `Done` the next one is released with a `stage_ready`
+//! message, and after the last stage the pipeline task is released for a
+//! final look.
+//!
+//! Templates in `.axon/pipelines.yaml` are added to the built-in `feature`
+//! template and replace it when they share its name.
+//!
+//! ```yaml
+//! templates:
+//!   - name: bugfix
+//!     description: Reproduce, fix and verify a bug
+//!     stages:
+//!       - name: reproduce
+//!         required_capabilities: [testing]
+//!       - name: fix
+//!         required_capabilities: [development]
+//!         estimated_effort: 120
+//! ```
+
+use crate::error::{Result, TaskError};
+use crate::models::{NewTask, Task};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Location of the pipeline templates inside the project root
+pub const PIPELINES_FILE: &str = ".axon/pipelines.yaml";
+
+/// Template used when a pipeline names none
+pub const DEFAULT_PIPELINE_TEMPLATE: &str = "feature";
+
+/// Author of the messages releasing stages
+pub const PIPELINE_AUTHOR: &str = "pipeline";
+
+/// Message type telling a released stage it can start
+pub const STAGE_READY_MESSAGE_TYPE: &str = "stage_ready";
+
+/// One stage of a pipeline template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineStage {
+    pub name: String,
+    /// Capabilities the stage task requires
+    #[serde(default)]
+    pub required_capabilities: Vec<String>,
+    /// Estimated effort of the stage in minutes
+    #[serde(default)]
+    pub estimated_effort: Option<i32>,
+    /// What the stage is expected to deliver, added to the stage task description
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+/// Named sequence of stages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub stages: Vec<PipelineStage>,
+}
+
+/// Pipeline templates of a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineTemplates {
+    #[serde(default)]
+    pub templates: Vec<PipelineTemplate>,
+}
+
+/// A created pipeline: the pipeline task and its stage tasks in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub template: String,
+    pub pipeline: Task,
+    pub stages: Vec<Task>,
+}
+
+/// Code of stage `number` (1-based) of the pipeline coded `pipeline_code`
+pub fn stage_code(pipeline_code: &str, number: usize) -> String {
+    format!("{pipeline_code}-{number}")
+}
+
+/// Number of the stage coded `code` in the pipeline coded `pipeline_code`
+pub fn stage_number(pipeline_code: &str, code: &str) -> Option<usize> {
+    code.strip_prefix(pipeline_code)?
+        .strip_prefix('-')?
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+}
+
+impl PipelineStage {
+    fn new(name: &str, capability: &str, instructions: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            required_capabilities: vec![capability.to_string()],
+            estimated_effort: None,
+            instructions: Some(instructions.to_string()),
+        }
+    }
+}
+
+impl PipelineTemplate {
+    /// Stage tasks of a pipeline task, in order and not yet linked to it
+    pub fn stage_tasks(&self, pipeline: &NewTask) -> Vec<NewTask> {
+        self.stages
+            .iter()
+            .enumerate()
+            .map(|(index, stage)| {
+                let number = index + 1;
+                let mut description = format!(
+                    "Stage {number} of {} ({}) of pipeline {}.",
+                    self.stages.len(),
+                    stage.name,
+                    pipeline.code
+                );
+                if let Some(instructions) = &stage.instructions {
+                    description = format!("{description} {instructions}");
+                }
+                NewTask {
+                    code: stage_code(&pipeline.code, number),
+                    name: format!("{}: {}", pipeline.name, stage.name),
+                    description: format!("{description}\n\n{}", pipeline.description),
+                    owner_agent_name: None,
+                    parent_task_id: None,
+                    required_capabilities: stage.required_capabilities.clone(),
+                    estimated_effort: stage.estimated_effort,
+                    ..pipeline.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(TaskError::Validation(format!(
+                "{PIPELINES_FILE}: template name cannot be empty"
+            )));
+        }
+        if self.stages.is_empty() {
+            return Err(TaskError::Validation(format!(
+                "{PIPELINES_FILE}: template '{}' has no stages",
+                self.name
+            )));
+        }
+        if self.stages.iter().any(|stage| stage.name.trim().is_empty()) {
+            return Err(TaskError::Validation(format!(
+                "{PIPELINES_FILE}: template '{}' has a stage without a name",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for PipelineTemplates {
+    fn default() -> Self {
+        Self {
+            templates: vec![PipelineTemplate {
+                name: DEFAULT_PIPELINE_TEMPLATE.to_string(),
+                description: "Design, implement and review a feature".to_string(),
+                stages: vec![
+                    PipelineStage::new(
+                        "design",
+                        "architecture",
+                        "Work out the approach and record it in a message or artifact.",
+                    ),
+                    PipelineStage::new(
+                        "implement",
+                        "development",
+                        "Build the feature following the design stage.",
+                    ),
+                    PipelineStage::new(
+                        "review",
+                        "code-review",
+                        "Review the implementation against the design.",
+                    ),
+                ],
+            }],
+        }
+    }
+}
+
+impl PipelineTemplates {
+    /// Parse templates from YAML and add them to the built-in ones
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let parsed: Self = serde_yaml::from_str(yaml)
+            .map_err(|e| TaskError::Validation(format!("{PIPELINES_FILE}: {e}")))?;
+        let mut templates = Self::default();
+        for (index, template) in parsed.templates.iter().enumerate() {
+            template.validate()?;
+            if parsed.templates[..index]
+                .iter()
+                .any(|earlier| earlier.name == template.name)
+            {
+                return Err(TaskError::Validation(format!(
+                    "{PIPELINES_FILE}: template '{}' is defined twice",
+                    template.name
+                )));
+            }
+            match templates
+                .templates
+                .iter_mut()
+                .find(|existing| existing.name == template.name)
+            {
+                Some(existing) => *existing = template.clone(),
+                None => templates.templates.push(template.clone()),
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Template named `name`
+    pub fn get(&self, name: &str) -> Result<&PipelineTemplate> {
+        self.templates
+            .iter()
+            .find(|template| template.name == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.templates.iter().map(|t| t.name.as_str()).collect();
+                TaskError::NotFound(format!(
+                    "Pipeline template '{name}' not found; available: {}",
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+/// Load the pipeline templates of a project, the built-in ones when it has no file
+pub fn load_pipeline_templates(project_root: &Path) -> Result<PipelineTemplates> {
+    let path = project_root.join(PIPELINES_FILE);
+    if !path.exists() {
+        return Ok(PipelineTemplates::default());
+    }
+    let yaml = std::fs::read_to_string(&path)
+        .map_err(|e| TaskError::Validation(format!("{}: {}", path.display(), e)))?;
+    PipelineTemplates::from_yaml(&yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATES: &str = r#"
+templates:
+  - name: bugfix
+    stages:
+      - name: reproduce
+        required_capabilities: [testing]
+      - name: fix
+        required_capabilities: [development]
+        estimated_effort: 120
+  - name: feature
+    stages:
+      - name: build
+"#;
+
+    #[test]
+    fn test_pipeline_templates() {
+        let templates = PipelineTemplates::from_yaml(TEMPLATES).unwrap();
+        let names: Vec<&str> = templates
+            .templates
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, ["feature", "bugfix"]);
+        assert_eq!(templates.get("feature").unwrap().stages.len(), 1);
+        assert!(matches!(
+            templates.get("release"),
+            Err(TaskError::NotFound(_))
+        ));
+        assert!(
+            PipelineTemplates::from_yaml("templates:\n  - name: empty\n    stages: []\n").is_err()
+        );
+        assert!(PipelineTemplates::from_yaml(
+            "templates:\n  - name: a\n    stages: [{name: x}]\n  - name: a\n    stages: [{name: y}]\n"
+        )
+        .is_err());
+
+        let pipeline = NewTask::builder()
+            .code("BUG-7")
+            .name("Login fails")
+            .description("Users cannot log in")
+            .owner("coordinator")
+            .build()
+            .unwrap();
+        let stages = templates.get("bugfix").unwrap().stage_tasks(&pipeline);
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[1].code, "BUG-7-2");
+        assert_eq!(stages[1].name, "Login fails: fix");
+        assert_eq!(stages[1].required_capabilities, ["development"]);
+        assert_eq!(stages[1].estimated_effort, Some(120));
+        assert_eq!(stages[1].owner_agent_name, None);
+        assert!(stages[1]
+            .description
+            .starts_with("Stage 2 of 2 (fix) of pipeline BUG-7."));
+        assert!(stages[1].description.ends_with("Users cannot log in"));
+
+        assert_eq!(stage_number("BUG-7", "BUG-7-2"), Some(2));
+        assert_eq!(stage_number("BUG-7", "BUG-72"), None);
+        assert_eq!(stage_number("BUG-7", "BUG-7-0"), None);
+        assert_eq!(stage_number("BUG-7", "BUG-8-1"), None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Detection of a project's technology stack from its manifest files
+//!
+//! Agent suggestions otherwise rest on the PRD text alone. Scanning the
+//! project root for well-known manifests (`Cargo.toml`, `package.json`,
+//! `go.mod`, `Dockerfile`) lets archetype classification and capability
+//! assignment follow the stack the code is actually written in.
+
+use crate::workspace_setup::SuggestedAgent;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A technology stack, recognised by `Cargo.toml`, `package.json`, `go.mod` or `Dockerfile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectedStack {
+    Rust,
+    Node,
+    Go,
+    Docker,
+}
+
+/// Rust crates that hint at the project's archetype
+const RUST_HINTS: &[(&str, &str)] = &[
+    ("clap", "cli"),
+    ("structopt", "cli"),
+    ("axum", "api"),
+    ("actix-web", "api"),
+    ("rocket", "api"),
+    ("warp", "api"),
+    ("poem", "api"),
+    ("tauri", "tauri"),
+    ("egui", "gui"),
+    ("iced", "gui"),
+];
+
+/// npm packages that hint at the project's archetype
+const NODE_HINTS: &[(&str, &str)] = &[
+    ("react-native", "react native"),
+    ("electron", "electron"),
+    ("react", "frontend"),
+    ("vue", "frontend"),
+    ("@angular/core", "frontend"),
+    ("svelte", "frontend"),
+    ("next", "frontend"),
+    ("express", "api backend"),
+    ("fastify", "api backend"),
+    ("koa", "api backend"),
+    ("@nestjs/core", "api backend"),
+];
+
+/// Go modules that hint at the project's archetype
+const GO_HINTS: &[(&str, &str)] = &[
+    ("github.com/spf13/cobra", "cli"),
+    ("github.com/urfave/cli", "cli"),
+    ("github.com/gin-gonic/gin", "api"),
+    ("github.com/labstack/echo", "api"),
+    ("github.com/gofiber/fiber", "api"),
+    ("github.com/go-chi/chi", "api"),
+];
+
+/// Stacks found in a project root
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectScan {
+    pub stacks: Vec<DetectedStack>,
+    /// Capabilities the detected stacks call for, e.g. `rust` or `typescript`
+    pub capabilities: Vec<String>,
+    /// Words describing what the manifests declare (`cli`, `api`, `frontend`, ...),
+    /// read by archetype classification alongside the PRD
+    pub hints: Vec<String>,
+}
+
+impl ProjectScan {
+    /// Inspect the manifests in `project_root`; unreadable or missing files are skipped
+    pub fn scan(project_root: &Path) -> Self {
+        let mut scan = Self::default();
+        let read = |name: &str| std::fs::read_to_string(project_root.join(name)).ok();
+
+        if let Some(cargo) = read("Cargo.toml") {
+            scan.stacks.push(DetectedStack::Rust);
+            scan.add_capability("rust");
+            for (dependency, hint) in RUST_HINTS {
+                if declares_key(&cargo, dependency) {
+                    scan.add_hint(hint);
+                }
+            }
+            let has_binary = cargo.contains("[[bin]]") || project_root.join("src/main.rs").exists();
+            if !has_binary && (cargo.contains("[lib]") || project_root.join("src/lib.rs").exists())
+            {
+                scan.add_hint("library");
+            }
+        }
+
+        if let Some(package) = read("package.json") {
+            scan.stacks.push(DetectedStack::Node);
+            let package: serde_json::Value = serde_json::from_str(&package).unwrap_or_default();
+            let depends_on = |name: &str| {
+                ["dependencies", "devDependencies"]
+                    .iter()
+                    .any(|section| package[section].get(name).is_some())
+            };
+            if depends_on("typescript") || project_root.join("tsconfig.json").exists() {
+                scan.add_capability("typescript");
+            } else {
+                scan.add_capability("javascript");
+            }
+            for (dependency, hint) in NODE_HINTS {
+                if depends_on(dependency) {
+                    scan.add_hint(hint);
+                }
+            }
+            if package.get("bin").is_some() {
+                scan.add_hint("cli");
+            }
+        }
+
+        if let Some(go_mod) = read("go.mod") {
+            scan.stacks.push(DetectedStack::Go);
+            scan.add_capability("go");
+            for (module, hint) in GO_HINTS {
+                if go_mod.contains(module) {
+                    scan.add_hint(hint);
+                }
+            }
+        }
+
+        if project_root.join("Dockerfile").exists() {
+            scan.stacks.push(DetectedStack::Docker);
+            scan.add_capability("docker");
+        }
+
+        scan
+    }
+
+    /// Whether no known manifest was found
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    /// Give each suggested agent the stack capabilities of its role
+    ///
+    /// Language capabilities go to developers and architects, `docker` to the
+    /// DevOps agent; when no agent has such a role, the first agent takes them.
+    pub fn assign_capabilities(&self, agents: &mut [SuggestedAgent]) {
+        let implements = |agent: &SuggestedAgent| {
+            agent.name.contains("developer") || agent.name.contains("architect")
+        };
+        let operates = |agent: &SuggestedAgent| agent.name.contains("devops");
+
+        for capability in &self.capabilities {
+            let owns: &dyn Fn(&SuggestedAgent) -> bool = if capability == "docker" {
+                &operates
+            } else {
+                &implements
+            };
+            let owners = if agents.iter().any(owns) {
+                agents
+                    .iter_mut()
+                    .filter(|agent| owns(agent))
+                    .collect::<Vec<_>>()
+            } else {
+                agents.iter_mut().take(1).collect()
+            };
+            for agent in owners {
+                if !agent.required_capabilities.contains(capability) {
+                    agent.required_capabilities.push(capability.clone());
+                }
+            }
+        }
+    }
+
+    fn add_capability(&mut self, capability: &str) {
+        self.capabilities.push(capability.to_string());
+    }
+
+    fn add_hint(&mut self, hint: &str) {
+        if !self.hints.iter().any(|h| h == hint) {
+            self.hints.push(hint.to_string());
+        }
+    }
+}
+
+/// Whether a TOML document has a line assigning `key` (e.g. `clap = "4"` or `clap.workspace = true`)
+fn declares_key(toml: &str, key: &str) -> bool {
+    toml.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix(key)
+            .and_then(|rest| rest.trim_start().chars().next())
+            .is_some_and(|next| next == '=' || next == '.')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(name: &str) -> SuggestedAgent {
+        SuggestedAgent {
+            name: name.to_string(),
+            description: String::new(),
+            required_capabilities: vec![],
+            workload_percentage: 50.0,
+            depends_on: vec![],
+        }
+    }
+
+    #[test]
+    fn test_project_scan() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(ProjectScan::scan(root.path()).is_empty());
+
+        std::fs::write(
+            root.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\nclap = { version = \"4\" }\nclap_complete = \"4\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.path().join("package.json"),
+            r#"{"devDependencies": {"typescript": "5", "react": "18"}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.path().join("Dockerfile"), "FROM rust\n").unwrap();
+
+        let scan = ProjectScan::scan(root.path());
+        assert_eq!(
+            scan.stacks,
+            [
+                DetectedStack::Rust,
+                DetectedStack::Node,
+                DetectedStack::Docker
+            ]
+        );
+        assert_eq!(scan.capabilities, ["rust", "typescript", "docker"]);
+        assert_eq!(scan.hints, ["cli", "frontend"]);
+
+        let mut agents = [agent("cli-developer"), agent("qa-tester")];
+        scan.assign_capabilities(&mut agents);
+        assert_eq!(
+            agents[0].required_capabilities,
+            ["rust", "typescript", "docker"]
+        );
+        assert!(agents[1].required_capabilities.is_empty());
+    }
+}
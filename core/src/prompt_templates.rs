@@ -112,7 +112,6 @@ pub struct ErrorHandlingRule {
 #[derive(Clone)]
 pub struct EnhancedPromptBuilder {
     /// Capability catalog
-    #[allow(dead_code)]
     capabilities: HashMap<String, CapabilityDefinition>,
     /// Coordination recipes
     coordination_recipes: HashMap<String, CoordinationRecipe>,
@@ -141,6 +140,52 @@ impl Default for PromptBuilderConfig {
     }
 }
 
+/// How much guidance an agent prompt spells out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptVerbosity {
+    /// Role, deliverables, coordination and output format only
+    Concise,
+    /// Adds the work iteration pattern, error handling and context scope
+    #[default]
+    Standard,
+    /// Adds the inputs, outputs and quality gates of the agent's capabilities
+    Detailed,
+}
+
+/// Presentation options applied when rendering an agent prompt
+///
+/// Stored per workspace and overridable per `register_agent` or
+/// `regenerate_agent_prompt` call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptStyle {
+    /// Embed the JSON agent contract at the top of the prompt
+    pub include_contract: bool,
+    /// Language agents write their messages and documents in (e.g. `Czech`); English if unset
+    pub language: Option<String>,
+    pub verbosity: PromptVerbosity,
+    /// Prefix section headings with emoji
+    pub emoji: bool,
+    /// List the contract's success criteria under the mission
+    pub include_success_criteria: bool,
+    /// Include the MCP coordination protocol section
+    pub include_communication_protocol: bool,
+}
+
+impl Default for PromptStyle {
+    fn default() -> Self {
+        Self {
+            include_contract: true,
+            language: None,
+            verbosity: PromptVerbosity::Standard,
+            emoji: false,
+            include_success_criteria: true,
+            include_communication_protocol: true,
+        }
+    }
+}
+
 impl EnhancedPromptBuilder {
     /// Create new prompt builder with default configuration
     pub fn new() -> Self {
@@ -158,37 +203,119 @@ impl EnhancedPromptBuilder {
         archetype: &ProjectArchetype,
         project_context: &str,
         rolling_context: Option<&str>,
+    ) -> String {
+        self.generate_agent_prompt_with_style(
+            agent,
+            archetype,
+            project_context,
+            rolling_context,
+            &PromptStyle::default(),
+        )
+    }
+
+    /// Generate an agent prompt rendered with the given style options
+    pub fn generate_agent_prompt_with_style(
+        &self,
+        agent: &SuggestedAgent,
+        archetype: &ProjectArchetype,
+        project_context: &str,
+        rolling_context: Option<&str>,
+        style: &PromptStyle,
     ) -> String {
         let contract = self.create_agent_contract(agent, archetype);
         let coordination_recipe = self.get_coordination_recipe(archetype);
+        let heading = |emoji: &str, title: &str| {
+            if style.emoji {
+                format!("=== {emoji} {title} ===")
+            } else {
+                format!("=== {title} ===")
+            }
+        };
+        let concise = style.verbosity == PromptVerbosity::Concise;
+        let mut prompt = String::new();
+
+        if style.include_contract {
+            prompt.push_str(&format!(
+                "[AGENT_CONTRACT_START]\n{}\n[AGENT_CONTRACT_END]\n\n",
+                serde_json::to_string_pretty(&contract)
+                    .unwrap_or_else(|_| "Invalid contract".to_string())
+            ));
+        }
+        prompt.push_str(&format!(
+            "[STATIC_CONTEXT_START]\nPROJECT: {project_context}\nARCHETYPE: {archetype}\n\
+             [STATIC_CONTEXT_END]\n\n"
+        ));
+        if let Some(ctx) = rolling_context {
+            prompt.push_str(&format!(
+                "[ROLLING_CONTEXT_START]\n{ctx}\n[ROLLING_CONTEXT_END]\n"
+            ));
+        }
+        prompt.push('\n');
+
+        prompt.push_str(&format!(
+            "\n{}\nYou are the '{}' agent with the mission: {}\n",
+            heading("🎯", "YOUR ROLE AND MISSION"),
+            agent.name,
+            contract.mission
+        ));
+        if style.include_success_criteria {
+            let success_criteria = contract
+                .success_criteria
+                .iter()
+                .enumerate()
+                .map(|(i, criteria)| format!("{}. {}", i + 1, criteria))
+                .collect::<Vec<_>>()
+                .join("\n");
+            prompt.push_str(&format!(
+                "\nYour success is measured by:\n{success_criteria}\n"
+            ));
+        }
+        if let Some(language) = &style.language {
+            prompt.push_str(&format!(
+                "\n{}\nWrite all task updates, messages, comments and documentation in \
+                 {language}. Keep code identifiers and MCP function names unchanged.\n",
+                heading("🌐", "LANGUAGE")
+            ));
+        }
+        if style.verbosity == PromptVerbosity::Detailed {
+            prompt.push_str(&format!(
+                "\n{}\n{}\n",
+                heading("🧰", "CAPABILITIES"),
+                self.format_capabilities(agent)
+            ));
+        }
 
-        format!(
-            r#"[AGENT_CONTRACT_START]
-{}
-[AGENT_CONTRACT_END]
-
-[STATIC_CONTEXT_START]
-PROJECT: {project_context}
-ARCHETYPE: {archetype}
-[STATIC_CONTEXT_END]
-
-{rolling_context_section}
-
-=== YOUR ROLE AND MISSION ===
-You are the '{agent_name}' agent with the mission: {mission}
-
-Your success is measured by:
-{success_criteria}
-
-=== DELIVERABLES EXPECTED ===
-{deliverables}
-
-=== MCP COORDINATION PROTOCOL ===
-You coordinate with other agents through these exact MCP function patterns:
-
-{coordination_instructions}
+        let deliverables = contract
+            .deliverables
+            .iter()
+            .map(|d| {
+                format!(
+                    "- {} at {}: {}",
+                    d.deliverable_type,
+                    d.path,
+                    d.quality_requirements.join(", ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        prompt.push_str(&format!(
+            "\n{}\n{deliverables}\n",
+            heading("📦", "DELIVERABLES EXPECTED")
+        ));
+
+        if style.include_communication_protocol {
+            prompt.push_str(&format!(
+                "\n{}\nYou coordinate with other agents through these exact MCP function \
+                 patterns:\n\n{}\n",
+                heading("🤝", "MCP COORDINATION PROTOCOL"),
+                self.format_coordination_instructions(&coordination_recipe)
+            ));
+        }
 
-=== WORK ITERATION PATTERN ===
+        if !concise {
+            prompt.push_str(&format!(
+                r#"
+{heading}
 Follow this micro-iteration pattern for dynamic effort scaling:
 
 1. **Claim Work**: Use `claim_task(task_id, "{agent_name}")` to atomically claim your next task
@@ -201,52 +328,52 @@ Follow this micro-iteration pattern for dynamic effort scaling:
    e. Otherwise: continue iteration with refined approach
 4. **Handoff**: Use `create_task_message()` to share results with target agents
 5. **Complete**: Use `set_task_state(task_id, "Done")` and `end_work_session(session_id)`
+"#,
+                heading = heading("🔁", "WORK ITERATION PATTERN"),
+                agent_name = agent.name,
+                max_iterations = self.config.max_iterations,
+            ));
+            prompt.push_str(&format!(
+                "\n{}\n{}\n",
+                heading("🚨", "ERROR HANDLING & ESCALATION"),
+                self.format_error_handling(&coordination_recipe)
+            ));
+        }
 
-=== ERROR HANDLING & ESCALATION ===
-{error_handling}
-
-=== OUTPUT FORMAT ===
-{output_format}
-
-=== CONTEXT SCOPE ===
-Focus only on: {context_scope}
-Ignore information outside your scope to maintain efficiency.
+        prompt.push_str(&format!(
+            "\n{}\n{}\n",
+            heading("📝", "OUTPUT FORMAT"),
+            contract.output_format
+        ));
+
+        if !concise {
+            prompt.push_str(&format!(
+                "\n{}\nFocus only on: {}\nIgnore information outside your scope to maintain \
+                 efficiency.\n\nRemember: You are part of a coordinated team. Always include \
+                 clear context and next steps in your handoffs.\n",
+                heading("🔭", "CONTEXT SCOPE"),
+                contract.context_scope.join(", ")
+            ));
+        }
+        prompt
+    }
 
-Remember: You are part of a coordinated team. Always include clear context and next steps in your handoffs.
-"#,
-            serde_json::to_string_pretty(&contract)
-                .unwrap_or_else(|_| "Invalid contract".to_string()),
-            project_context = project_context,
-            archetype = archetype,
-            rolling_context_section = rolling_context
-                .map(|ctx| format!("[ROLLING_CONTEXT_START]\n{ctx}\n[ROLLING_CONTEXT_END]\n"))
-                .unwrap_or_default(),
-            agent_name = agent.name,
-            mission = contract.mission,
-            success_criteria = contract
-                .success_criteria
-                .iter()
-                .enumerate()
-                .map(|(i, criteria)| format!("{}. {}", i + 1, criteria))
-                .collect::<Vec<_>>()
-                .join("\n"),
-            deliverables = contract
-                .deliverables
-                .iter()
-                .map(|d| format!(
-                    "- {} at {}: {}",
-                    d.deliverable_type,
-                    d.path,
-                    d.quality_requirements.join(", ")
-                ))
-                .collect::<Vec<_>>()
-                .join("\n"),
-            coordination_instructions = self.format_coordination_instructions(&coordination_recipe),
-            max_iterations = self.config.max_iterations,
-            error_handling = self.format_error_handling(&coordination_recipe),
-            output_format = contract.output_format,
-            context_scope = contract.context_scope.join(", "),
-        )
+    /// Inputs, outputs and quality gates of the agent's catalogued capabilities
+    fn format_capabilities(&self, agent: &SuggestedAgent) -> String {
+        agent
+            .required_capabilities
+            .iter()
+            .map(|name| match self.capabilities.get(name) {
+                Some(capability) => format!(
+                    "- {name}: inputs {}; outputs {}; quality {}",
+                    capability.inputs.join(", "),
+                    capability.outputs.join(", "),
+                    capability.quality.join(", ")
+                ),
+                None => format!("- {name}"),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
     /// Create structured agent contract from suggested agent
@@ -695,6 +822,63 @@ mod tests {
         assert!(prompt.contains("cli-developer"));
         assert!(prompt.contains("MCP COORDINATION PROTOCOL"));
         assert!(prompt.contains("ERROR HANDLING & ESCALATION"));
+
+        let without_contract = builder.generate_agent_prompt_with_style(
+            &agent,
+            &ProjectArchetype::CliTool,
+            "Markdown converter CLI tool",
+            None,
+            &PromptStyle {
+                include_contract: false,
+                ..PromptStyle::default()
+            },
+        );
+        assert!(without_contract.starts_with("[STATIC_CONTEXT_START]"));
+        assert!(!without_contract.contains("AGENT_CONTRACT_START"));
+    }
+
+    #[test]
+    fn test_prompt_style_options() {
+        let builder = EnhancedPromptBuilder::new();
+        let agent = SuggestedAgent {
+            name: "cli-developer".to_string(),
+            description: "Develops CLI tools".to_string(),
+            required_capabilities: vec!["cli-development".to_string()],
+            workload_percentage: 100.0,
+            depends_on: vec![],
+        };
+        let render = |style: PromptStyle| {
+            builder.generate_agent_prompt_with_style(
+                &agent,
+                &ProjectArchetype::CliTool,
+                "Markdown converter CLI tool",
+                None,
+                &style,
+            )
+        };
+
+        let concise = render(PromptStyle {
+            language: Some("Czech".to_string()),
+            verbosity: PromptVerbosity::Concise,
+            emoji: true,
+            include_success_criteria: false,
+            include_communication_protocol: false,
+            ..PromptStyle::default()
+        });
+        assert!(concise.contains("=== 🎯 YOUR ROLE AND MISSION ==="));
+        assert!(concise.contains("documentation in Czech"));
+        assert!(!concise.contains("Your success is measured by"));
+        assert!(!concise.contains("MCP COORDINATION PROTOCOL"));
+        assert!(!concise.contains("WORK ITERATION PATTERN"));
+        assert!(concise.contains("OUTPUT FORMAT"));
+
+        let detailed = render(PromptStyle {
+            verbosity: PromptVerbosity::Detailed,
+            ..PromptStyle::default()
+        });
+        assert!(detailed.contains("=== CAPABILITIES ===\n- cli-development: inputs"));
+        assert!(detailed.contains("WORK ITERATION PATTERN"));
+        assert!(!detailed.contains("LANGUAGE"));
     }
 
     #[test]
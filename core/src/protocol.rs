@@ -1,9 +1,33 @@
 use crate::{
+    agent_performance::PerformanceReport,
+    claude_code::ClaudeCodeRegistration,
+    critical_path::CriticalPath,
+    csv_export::{CsvExport, SessionCsvColumn, TaskCsvColumn, MAX_CSV_EXPORT_ROWS},
+    duplicates::DuplicateCandidate,
+    embeddings::{EmbeddingSource, SemanticMatch},
     error::Result,
-    models::{NewTask, Task, TaskFilter, TaskMessage, TaskState, UpdateTask},
+    mcp_v2_extensions::AgentMatch,
+    message_templates::MessageTemplate,
+    models::{
+        Capability, NewTask, SystemEvent, Task, TaskComment, TaskCommentRevision, TaskFilter,
+        TaskMention, TaskMessage, TaskState, UpdateTask, WorkSession,
+    },
+    repository::{
+        AgentPurgeReport, AuditEntry, AuditLogFilter, AuditSource, MaintenanceOptions,
+        MaintenanceReport, MigrationStatus, PurgeMode, QueryResult, ReadOnlyQuery, RetentionReport,
+        SystemEventFilter, TaskGroupBy, TaskGroupCount, TaskOperationOutcome, WorkSessionFilter,
+        DEFAULT_QUERY_ROWS, DEFAULT_QUERY_TIMEOUT_MS,
+    },
+    task_context::TaskContext,
+    task_history::{TaskBoard, WorkspaceDiff},
+    thread_summary::TaskThread,
+    timeline::{Timeline, TimelineFormat},
+    workspace_merge::WorkspaceMergeReport,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Protocol handler trait for MCP operations
 ///
@@ -14,6 +38,51 @@ pub trait ProtocolHandler: Send + Sync {
     /// Create a new task via MCP
     async fn create_task(&self, params: CreateTaskParams) -> Result<Task>;
 
+    /// Create a pipeline task with one linked stage task per stage of a template
+    async fn create_pipeline(
+        &self,
+        params: CreatePipelineParams,
+    ) -> Result<crate::pipelines::Pipeline>;
+
+    /// Bundle a task with its hierarchy, discussion and links for an agent picking it up
+    async fn get_task_context(&self, params: GetTaskContextParams) -> Result<TaskContext>;
+
+    /// Record a file produced by a task, checked against the project root
+    async fn register_artifact(
+        &self,
+        params: RegisterArtifactParams,
+    ) -> Result<crate::artifacts::TaskArtifact>;
+
+    /// List the files tasks produced, optionally verified against the project
+    async fn list_artifacts(
+        &self,
+        params: ListArtifactsParams,
+    ) -> Result<Vec<crate::artifacts::ListedArtifact>>;
+
+    /// Latest final report, written once every task was done
+    async fn get_final_report(
+        &self,
+        params: GetFinalReportParams,
+    ) -> Result<crate::final_report::FinalReport>;
+
+    /// Propose tasks for the TODO comments of a project, and create them once confirmed
+    async fn scan_code_todos(
+        &self,
+        params: ScanCodeTodosParams,
+    ) -> Result<crate::code_todos::CodeTodoImport>;
+
+    /// Apply the edits of `.axon/tasks.md` to the database, then write it anew
+    async fn sync_tasks_markdown(
+        &self,
+        params: SyncTasksMarkdownParams,
+    ) -> Result<crate::tasks_markdown::TasksMarkdownSync>;
+
+    /// Find open tasks similar to a task about to be created
+    async fn find_duplicate_tasks(
+        &self,
+        params: FindDuplicateTasksParams,
+    ) -> Result<Vec<DuplicateCandidate>>;
+
     /// Update an existing task via MCP
     async fn update_task(&self, params: UpdateTaskParams) -> Result<Task>;
 
@@ -26,12 +95,33 @@ pub trait ProtocolHandler: Send + Sync {
     /// Get a task by code via MCP
     async fn get_task_by_code(&self, params: GetTaskByCodeParams) -> Result<Option<Task>>;
 
+    /// Get a task by its stable identifier (UUID or ULID) via MCP
+    async fn get_task_by_uid(&self, params: GetTaskByUidParams) -> Result<Option<Task>>;
+
+    /// Get several tasks by ID in one call
+    async fn get_tasks_by_ids(&self, params: GetTasksByIdsParams) -> Result<TaskBatch<i32>>;
+
+    /// Get several tasks by code in one call
+    async fn get_tasks_by_codes(&self, params: GetTasksByCodesParams) -> Result<TaskBatch<String>>;
+
     /// List tasks via MCP
     async fn list_tasks(&self, params: ListTasksParams) -> Result<Vec<Task>>;
 
+    /// Count tasks matching list filters without returning them
+    async fn count_tasks(&self, params: CountTasksParams) -> Result<TaskCount>;
+
+    /// Count tasks matching list filters per state, owner or capability
+    async fn aggregate_tasks(&self, params: AggregateTasksParams) -> Result<TaskAggregate>;
+
     /// Assign a task to a different agent via MCP
     async fn assign_task(&self, params: AssignTaskParams) -> Result<Task>;
 
+    /// Apply create, set_state, assign and message operations atomically
+    async fn apply_task_operations(
+        &self,
+        params: ApplyTaskOperationsParams,
+    ) -> Result<Vec<TaskOperationOutcome>>;
+
     /// Archive a task via MCP
     async fn archive_task(&self, params: ArchiveTaskParams) -> Result<Task>;
 
@@ -43,9 +133,36 @@ pub trait ProtocolHandler: Send + Sync {
     /// Discover available work for an agent
     async fn discover_work(&self, params: DiscoverWorkParams) -> Result<Vec<Task>>;
 
+    /// Export task timestamps, owners and subtask links as a timeline
+    async fn export_timeline(&self, params: ExportTimelineParams) -> Result<Timeline>;
+
+    /// Export tasks as CSV with the selected columns
+    async fn export_tasks_csv(&self, params: ExportTasksCsvParams) -> Result<CsvExport>;
+
+    /// Export work sessions as CSV with the selected columns
+    async fn export_sessions_csv(&self, params: ExportSessionsCsvParams) -> Result<CsvExport>;
+
+    /// Compute the longest chain of incomplete subtasks and the slack of every task
+    async fn get_critical_path(&self, params: GetCriticalPathParams) -> Result<CriticalPath>;
+
+    /// Find tasks, messages and comments similar in meaning to a free-text query
+    async fn semantic_search(&self, params: SemanticSearchParams) -> Result<Vec<SemanticMatch>>;
+
     /// Claim a task for execution
     async fn claim_task(&self, params: ClaimTaskParams) -> Result<Task>;
 
+    /// Rank registered agents by how well their capabilities fit a task
+    async fn match_agents_to_task(
+        &self,
+        params: MatchAgentsToTaskParams,
+    ) -> Result<TaskAgentMatches>;
+
+    /// Show which routing rule would route a task and to whom, without routing it
+    async fn explain_task_routing(
+        &self,
+        params: ExplainTaskRoutingParams,
+    ) -> Result<crate::routing::RoutingDecision>;
+
     /// Release a previously claimed task
     async fn release_task(&self, params: ReleaseTaskParams) -> Result<Task>;
 
@@ -55,8 +172,88 @@ pub trait ProtocolHandler: Send + Sync {
     /// End a work session
     async fn end_work_session(&self, params: EndWorkSessionParams) -> Result<()>;
 
+    /// Append tool calls, decisions and files touched to an active session's transcript
+    async fn append_session_log(
+        &self,
+        params: AppendSessionLogParams,
+    ) -> Result<crate::session_log::SessionLogEntry>;
+
+    /// Get the transcript of a work session for review
+    async fn get_session_log(
+        &self,
+        params: GetSessionLogParams,
+    ) -> Result<crate::session_log::SessionLog>;
+
+    /// Aggregate productivity per agent and record anomalies as system events
+    async fn get_agent_performance(
+        &self,
+        params: GetAgentPerformanceParams,
+    ) -> Result<PerformanceReport>;
+
+    /// List system events recorded for the coordinator, newest first
+    async fn get_system_events(&self, params: GetSystemEventsParams) -> Result<Vec<SystemEvent>>;
+
+    /// Count tool calls per agent and tool, most called first
+    async fn get_tool_usage_stats(
+        &self,
+        params: GetToolUsageStatsParams,
+    ) -> Result<Vec<crate::tool_usage::ToolUsageStat>>;
+
+    /// Propose moving unattended tasks from overloaded to idle agents, and move them once confirmed
+    async fn rebalance_work(
+        &self,
+        params: RebalanceWorkParams,
+    ) -> Result<crate::work_rebalancing::WorkRebalance>;
+
+    /// Show the task board as it was at a point in time
+    async fn get_board_at(&self, params: GetBoardAtParams) -> Result<TaskBoard>;
+
+    /// Report tasks created, completed and reassigned and messages posted between two times
+    async fn diff_workspace(&self, params: DiffWorkspaceParams) -> Result<WorkspaceDiff>;
+
     /// Clean up tasks that have been claimed longer than the timeout duration
-    async fn cleanup_timed_out_tasks(&self, params: CleanupTimedOutTasksParams) -> Result<Vec<Task>>;
+    async fn cleanup_timed_out_tasks(
+        &self,
+        params: CleanupTimedOutTasksParams,
+    ) -> Result<Vec<Task>>;
+
+    /// Report what the configured retention policy would delete, without deleting it
+    async fn get_retention_report(&self) -> Result<RetentionReport>;
+
+    /// Run database maintenance and report file sizes and integrity findings
+    async fn maintain_database(&self, params: MaintainDatabaseParams) -> Result<MaintenanceReport>;
+
+    /// Report applied and pending schema migrations
+    async fn get_migration_status(&self) -> Result<MigrationStatus>;
+
+    /// Export audit events in a time range as JSON Lines
+    async fn export_audit_log(&self, params: ExportAuditLogParams) -> Result<AuditLogExport>;
+
+    /// Remove or anonymize an agent's messages, sessions, comments and registry entry
+    async fn purge_agent_data(&self, params: PurgeAgentDataParams) -> Result<AgentPurgeReport>;
+
+    /// Merge another project's database into this one, remapping IDs and conflicting codes
+    async fn merge_workspace(&self, params: MergeWorkspaceParams) -> Result<WorkspaceMergeReport>;
+
+    /// Add or update this server's entry in the user's Claude Code MCP settings
+    async fn register_with_claude_code(
+        &self,
+        params: RegisterWithClaudeCodeParams,
+    ) -> Result<ClaudeCodeRegistration>;
+
+    /// Run a single SELECT statement against the database without modifying it
+    async fn run_readonly_query(&self, params: RunReadonlyQueryParams) -> Result<QueryResult>;
+
+    // Capability Catalog
+
+    /// Define a capability, or update its description and aliases
+    async fn define_capability(&self, params: DefineCapabilityParams) -> Result<Capability>;
+
+    /// List the capabilities of the catalog
+    async fn list_capabilities(&self, params: ListCapabilitiesParams) -> Result<Vec<Capability>>;
+
+    /// Deprecate a capability, optionally naming its replacement
+    async fn deprecate_capability(&self, params: DeprecateCapabilityParams) -> Result<Capability>;
 
     // Task Communication & Messaging
 
@@ -66,6 +263,48 @@ pub trait ProtocolHandler: Send + Sync {
     /// Get task messages with optional filtering
     async fn get_task_messages(&self, params: GetTaskMessagesParams) -> Result<Vec<TaskMessage>>;
 
+    /// Get a task thread as a summary of older messages followed by the recent ones
+    async fn get_task_thread(&self, params: GetTaskMessagesParams) -> Result<TaskThread>;
+
+    /// Get messages mentioning an agent
+    async fn get_my_mentions(&self, params: GetMyMentionsParams) -> Result<Vec<TaskMention>>;
+
+    /// Get an agent's claimed tasks, new messages, pending reviews and deadlines
+    async fn get_my_work(&self, params: GetMyWorkParams) -> Result<MyWork>;
+
+    /// Get a digest of assignments, mentions, state changes and SLA warnings
+    /// concerning an agent since a point in time
+    async fn get_updates_since(
+        &self,
+        params: GetUpdatesSinceParams,
+    ) -> Result<crate::agent_updates::AgentUpdates>;
+
+    /// Get message templates for common coordination patterns
+    async fn get_message_templates(
+        &self,
+        params: GetMessageTemplatesParams,
+    ) -> Result<Vec<MessageTemplate>>;
+
+    // Task Comments
+
+    /// Add a markdown comment to a task
+    async fn add_task_comment(&self, params: AddTaskCommentParams) -> Result<TaskComment>;
+
+    /// Edit a comment, keeping the previous content in its history
+    async fn edit_task_comment(&self, params: EditTaskCommentParams) -> Result<TaskComment>;
+
+    /// Soft-delete a comment
+    async fn delete_task_comment(&self, params: DeleteTaskCommentParams) -> Result<TaskComment>;
+
+    /// Get the comments of a task
+    async fn get_task_comments(&self, params: GetTaskCommentsParams) -> Result<Vec<TaskComment>>;
+
+    /// Get the edit history of a comment
+    async fn get_task_comment_history(
+        &self,
+        params: GetTaskCommentHistoryParams,
+    ) -> Result<Vec<TaskCommentRevision>>;
+
     // Workspace Setup & Automation Functions
 
     /// Get setup instructions for AI workspace automation
@@ -86,6 +325,12 @@ pub trait ProtocolHandler: Send + Sync {
         params: RegisterAgentParams,
     ) -> Result<crate::workspace_setup::AgentRegistration>;
 
+    /// Regenerate a registered agent's prompt and compare it with the stored one
+    async fn regenerate_agent_prompt(
+        &self,
+        params: RegenerateAgentPromptParams,
+    ) -> Result<crate::workspace_setup::PromptRegeneration>;
+
     /// Get instructions for creating main AI file (CLAUDE.md, etc.)
     async fn get_instructions_for_main_ai_file(
         &self,
@@ -98,6 +343,29 @@ pub trait ProtocolHandler: Send + Sync {
         params: CreateMainAiFileParams,
     ) -> Result<crate::workspace_setup::MainAiFileData>;
 
+    /// Report which workspace setup steps are complete
+    async fn get_setup_status(
+        &self,
+        params: GetSetupStatusParams,
+    ) -> Result<crate::workspace_setup::SetupStatus>;
+
+    /// Take the next step of the guided workspace setup
+    async fn setup_wizard(
+        &self,
+        params: SetupWizardParams,
+    ) -> Result<crate::setup_wizard::SetupWizardState>;
+
+    /// Check the workspace files and database against the registered agents and the server
+    async fn verify_workspace(
+        &self,
+        params: VerifyWorkspaceParams,
+    ) -> Result<crate::workspace_doctor::WorkspaceVerification>;
+
+    /// Remove the files of an unfinished workspace setup and clear its context
+    async fn rollback_setup(
+        &self,
+        params: RollbackSetupParams,
+    ) -> Result<crate::setup_rollback::SetupRollback>;
 }
 
 /// MCP parameters for creating a new task
@@ -106,6 +374,107 @@ pub trait ProtocolHandler: Send + Sync {
 /// serialization and validation while reusing the domain model.
 pub type CreateTaskParams = NewTask;
 
+/// MCP parameters for creating a multi-stage pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePipelineParams {
+    /// Pipeline template; defaults to `feature`
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Code of the pipeline task; stage tasks are coded `<code>-1`, `<code>-2`, ...
+    pub code: String,
+    pub name: String,
+    pub description: String,
+    /// Owner of the pipeline task; stages are routed like new tasks
+    #[serde(default)]
+    pub owner_agent_name: Option<String>,
+    #[serde(default)]
+    pub priority_score: Option<f64>,
+}
+
+/// MCP parameters for the context bundle of a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTaskContextParams {
+    pub task_code: String,
+    /// Approximate size limit in tokens; defaults to the configured `token_budgets.task_context`
+    pub token_budget: Option<u32>,
+}
+
+/// MCP parameters for recording a file a task produced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterArtifactParams {
+    pub task_code: String,
+    /// Path relative to the project root
+    pub path: String,
+    /// What the file is, e.g. `code`, `test`, `doc` or `config`
+    pub kind: String,
+    /// SHA-256 of the file as `sha256:<hex>` or bare hex; must match the file when given
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Agent registering the file
+    pub agent_name: String,
+}
+
+/// MCP parameters for listing the files tasks produced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListArtifactsParams {
+    #[serde(default)]
+    pub task_code: Option<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Compare every file with the project and report it unchanged, modified or missing
+    #[serde(default)]
+    pub verify: bool,
+}
+
+/// MCP parameters for the final report of a completed project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetFinalReportParams {
+    /// Project whose report is read; the session's workspace root, else the server's
+    #[serde(default)]
+    pub project_root: Option<String>,
+}
+
+/// MCP parameters for importing the TODO comments of a project as tasks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCodeTodosParams {
+    /// Project to scan; the session's workspace root, else the server's
+    #[serde(default)]
+    pub project_root: Option<String>,
+    /// Only comments with these markers; all markers when empty
+    #[serde(default)]
+    pub markers: Vec<crate::code_todos::TodoMarker>,
+    /// Maximum number of comments to scan for, at most `MAX_BATCH_LOOKUP`
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Owner of the created tasks
+    #[serde(default)]
+    pub owner_agent_name: Option<String>,
+    /// Create the proposed tasks; without it the scan only proposes them
+    #[serde(default)]
+    pub confirm: bool,
+    /// Proposed task codes to create on confirmation; all new ones when empty
+    #[serde(default)]
+    pub codes: Vec<String>,
+}
+
+/// MCP parameters for syncing the task database with `.axon/tasks.md`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncTasksMarkdownParams {
+    /// Project whose task file is synced; the session's workspace root, else the server's
+    #[serde(default)]
+    pub project_root: Option<String>,
+}
+
+/// MCP parameters for finding open tasks similar to a new one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindDuplicateTasksParams {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
 /// MCP parameters for updating a task
 ///
 /// Contains the task ID and the update data. The update data reuses
@@ -161,16 +530,119 @@ pub struct GetTaskByCodeParams {
     pub code: String,
 }
 
+/// MCP parameters for getting a task by its stable identifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTaskByUidParams {
+    pub uid: String,
+}
+
+/// Maximum number of tasks requested by one batch lookup
+pub const MAX_BATCH_LOOKUP: usize = 500;
+
+/// MCP parameters for getting several tasks by ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTasksByIdsParams {
+    pub ids: Vec<i32>,
+}
+
+impl GetTasksByIdsParams {
+    /// Reject lookups of more than `MAX_BATCH_LOOKUP` IDs
+    pub fn validate(&self) -> Result<()> {
+        check_batch_lookup(self.ids.len())
+    }
+}
+
+/// MCP parameters for getting several tasks by code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTasksByCodesParams {
+    pub codes: Vec<String>,
+}
+
+impl GetTasksByCodesParams {
+    /// Reject lookups of more than `MAX_BATCH_LOOKUP` codes
+    pub fn validate(&self) -> Result<()> {
+        check_batch_lookup(self.codes.len())
+    }
+}
+
+fn check_batch_lookup(count: usize) -> Result<()> {
+    if count > MAX_BATCH_LOOKUP {
+        return Err(crate::error::TaskError::Validation(format!(
+            "Cannot look up {count} tasks at once; the maximum is {MAX_BATCH_LOOKUP}"
+        )));
+    }
+    Ok(())
+}
+
+/// Result of a batch lookup: the tasks found in request order and the keys matching none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBatch<K> {
+    pub tasks: Vec<Task>,
+    pub missing: Vec<K>,
+}
+
+impl<K: PartialEq> TaskBatch<K> {
+    /// Pair the tasks found with the requested keys that none of them carries
+    pub fn new(requested: Vec<K>, tasks: Vec<Task>, key_of: impl Fn(&Task) -> &K) -> Self {
+        let mut missing: Vec<K> = Vec::new();
+        for key in requested {
+            if !missing.contains(&key) && !tasks.iter().any(|task| key_of(task) == &key) {
+                missing.push(key);
+            }
+        }
+        Self { tasks, missing }
+    }
+}
+
 /// MCP parameters for listing tasks
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ListTasksParams {
     pub owner: Option<String>,
     pub state: Option<TaskState>,
+    #[serde(default)]
+    pub states: Vec<TaskState>,
+    #[serde(default)]
+    pub owners: Vec<String>,
+    pub code_prefix: Option<String>,
+    pub has_owner: Option<bool>,
     pub created_after: Option<String>,
     pub created_before: Option<String>,
+    pub modified_since: Option<String>,
     pub completed_after: Option<String>,
     pub completed_before: Option<String>,
     pub limit: Option<u32>,
+    #[serde(default)]
+    pub include_archive: bool,
+}
+
+/// MCP parameters for counting tasks; takes the filters of `list_tasks`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CountTasksParams {
+    #[serde(flatten)]
+    pub filter: ListTasksParams,
+}
+
+/// Number of tasks matching a filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskCount {
+    pub count: u64,
+}
+
+/// MCP parameters for aggregating tasks; takes the filters of `list_tasks`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateTasksParams {
+    pub group_by: TaskGroupBy,
+    #[serde(flatten)]
+    pub filter: ListTasksParams,
+}
+
+/// Task counts per group of an aggregation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskAggregate {
+    pub group_by: TaskGroupBy,
+    /// Matching tasks; with capability groups a task may be counted in several
+    pub total: u64,
+    pub groups: Vec<TaskGroupCount>,
 }
 
 /// MCP parameters for assigning a task
@@ -180,6 +652,41 @@ pub struct AssignTaskParams {
     pub new_owner: String,
 }
 
+/// Maximum number of operations applied by one `apply_task_operations` call
+pub const MAX_TASK_OPERATIONS: usize = 100;
+
+/// One operation of `apply_task_operations`, selected by its `op` field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TaskOperationParams {
+    Create(CreateTaskParams),
+    SetState(SetStateParams),
+    Assign(AssignTaskParams),
+    Message(CreateTaskMessageParams),
+}
+
+/// MCP parameters for applying an ordered list of operations in one transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyTaskOperationsParams {
+    pub operations: Vec<TaskOperationParams>,
+}
+
+impl ApplyTaskOperationsParams {
+    /// Reject empty batches and batches of more than `MAX_TASK_OPERATIONS`
+    pub fn validate(&self) -> Result<()> {
+        if self.operations.is_empty() {
+            return Err(crate::error::TaskError::empty_field("operations"));
+        }
+        if self.operations.len() > MAX_TASK_OPERATIONS {
+            return Err(crate::error::TaskError::Validation(format!(
+                "Cannot apply {} operations at once; the maximum is {MAX_TASK_OPERATIONS}",
+                self.operations.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
 /// MCP parameters for archiving a task
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchiveTaskParams {
@@ -231,6 +738,11 @@ impl ListTasksParams {
             None => None,
         };
 
+        let modified_since = match &self.modified_since {
+            Some(s) => Some(parse_datetime(s)?),
+            None => None,
+        };
+
         let completed_after = match &self.completed_after {
             Some(s) => Some(parse_datetime(s)?),
             None => None,
@@ -244,12 +756,18 @@ impl ListTasksParams {
         Ok(TaskFilter {
             owner: self.owner.clone(),
             state: self.state,
+            states: self.states.clone(),
+            owners: self.owners.clone(),
+            code_prefix: self.code_prefix.clone(),
+            has_owner: self.has_owner,
             date_from: created_after,
             date_to: created_before,
+            modified_since,
             completed_after,
             completed_before,
             limit: self.limit,
             offset: None, // Currently not exposed in MCP protocol, but could be added later
+            include_archive: self.include_archive,
         })
     }
 }
@@ -269,6 +787,65 @@ pub struct DiscoverWorkParams {
 pub struct ClaimTaskParams {
     pub task_id: i32,
     pub agent_name: String,
+    /// Requested lease in minutes, capped by the server's claim lease policy
+    #[serde(default)]
+    pub lease_minutes: Option<u32>,
+}
+
+/// MCP parameters for exporting a task timeline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportTimelineParams {
+    /// Only tasks owned by this agent
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub include_archive: bool,
+    #[serde(default)]
+    pub format: TimelineFormat,
+}
+
+/// MCP parameters for computing the critical path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetCriticalPathParams {
+    /// Effort assumed for tasks without an estimate; defaults to `DEFAULT_EFFORT_MINUTES`
+    pub default_effort_minutes: Option<u32>,
+}
+
+/// MCP parameters for semantic search over tasks, messages and comments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticSearchParams {
+    /// Free-text description of the work to find
+    pub query: String,
+    /// Number of matches; defaults to `DEFAULT_SEMANTIC_SEARCH_RESULTS`
+    pub top_k: Option<u32>,
+    /// Only these kinds of records; all kinds when empty
+    #[serde(default)]
+    pub sources: Vec<EmbeddingSource>,
+}
+
+/// MCP parameters for ranking agents for a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchAgentsToTaskParams {
+    pub task_id: i32,
+}
+
+/// MCP parameters for explaining how routing rules route a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainTaskRoutingParams {
+    pub task_id: i32,
+    /// Agent left out of the candidates, as when it releases the task
+    #[serde(default)]
+    pub exclude_agent: Option<String>,
+}
+
+/// Registered agents ranked by fit for a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAgentMatches {
+    pub task_id: i32,
+    pub task_code: String,
+    /// Required capabilities after normalization against the capability catalog
+    pub required_capabilities: Vec<String>,
+    /// Best fit first
+    pub agents: Vec<AgentMatch>,
 }
 
 /// MCP parameters for releasing a task
@@ -293,6 +870,119 @@ pub struct EndWorkSessionParams {
     pub productivity_score: Option<f64>,
 }
 
+/// MCP parameters for appending a chunk to a work session transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendSessionLogParams {
+    pub session_id: i32,
+    #[serde(flatten)]
+    pub chunk: crate::session_log::SessionLogChunk,
+}
+
+/// MCP parameters for reading a work session transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSessionLogParams {
+    pub session_id: i32,
+}
+
+/// MCP parameters for productivity analytics per agent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetAgentPerformanceParams {
+    /// Only this agent; all agents when omitted
+    pub agent_name: Option<String>,
+    /// Start of the period; defaults to `DEFAULT_PERFORMANCE_PERIOD_DAYS` before `until`
+    pub since: Option<DateTime<Utc>>,
+    /// End of the period; defaults to now
+    pub until: Option<DateTime<Utc>>,
+    /// Days per trend bucket; defaults to `DEFAULT_PERFORMANCE_BUCKET_DAYS`
+    pub bucket_days: Option<u32>,
+}
+
+/// Maximum number of events returned by one `get_system_events` call
+pub const MAX_SYSTEM_EVENTS: u32 = 500;
+
+/// MCP parameters for listing system events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetSystemEventsParams {
+    pub event_type: Option<String>,
+    /// Agent, task code or other entity the events are about
+    pub entity_id: Option<String>,
+    /// Only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+impl GetSystemEventsParams {
+    /// Convert MCP parameters to a system event filter, capping the number of events
+    pub fn to_filter(&self) -> SystemEventFilter {
+        SystemEventFilter {
+            event_type: self.event_type.clone(),
+            entity_id: self.entity_id.clone(),
+            since: self.since,
+            limit: Some(
+                self.limit
+                    .unwrap_or(MAX_SYSTEM_EVENTS)
+                    .min(MAX_SYSTEM_EVENTS),
+            ),
+        }
+    }
+}
+
+/// MCP parameters for counting tool calls per agent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetToolUsageStatsParams {
+    pub agent_name: Option<String>,
+    pub tool_name: Option<String>,
+    /// Only calls at or after this time; defaults to 24 hours before `until`
+    pub since: Option<DateTime<Utc>>,
+    /// Only calls before this time; defaults to now
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+/// MCP parameters for moving work from overloaded to idle agents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RebalanceWorkParams {
+    /// Execute the proposed reassignments; without it they are only proposed
+    #[serde(default)]
+    pub confirm: bool,
+    /// Codes of the proposed tasks to reassign on confirmation; all when empty
+    #[serde(default)]
+    pub codes: Vec<String>,
+}
+
+/// MCP parameters for the task board at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetBoardAtParams {
+    /// Point in time to show; must not be in the future
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Messages returned by `diff_workspace` unless the caller asks for a different number
+pub const DEFAULT_DIFF_MESSAGES: u32 = 100;
+
+/// Maximum number of messages returned by one `diff_workspace` call
+pub const MAX_DIFF_MESSAGES: u32 = 1000;
+
+/// MCP parameters for comparing the workspace at two points in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffWorkspaceParams {
+    /// Start of the period
+    pub from: DateTime<Utc>,
+    /// End of the period; must not be in the future
+    pub to: DateTime<Utc>,
+    /// Messages to return, oldest first; defaults to `DEFAULT_DIFF_MESSAGES`
+    pub max_messages: Option<u32>,
+}
+
+impl DiffWorkspaceParams {
+    /// Number of messages to return, capped at `MAX_DIFF_MESSAGES`
+    pub fn message_limit(&self) -> u32 {
+        self.max_messages
+            .unwrap_or(DEFAULT_DIFF_MESSAGES)
+            .min(MAX_DIFF_MESSAGES)
+    }
+}
+
 /// Work session information response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkSessionInfo {
@@ -300,6 +990,57 @@ pub struct WorkSessionInfo {
     pub task_id: i32,
     pub agent_name: String,
     pub started_at: chrono::DateTime<chrono::Utc>,
+    /// The task as it was when the session started
+    pub task: Task,
+    /// When the claim on the task lapses and cleanup may release it
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    /// Estimated effort of the task in minutes
+    pub estimated_effort: Option<i32>,
+    /// Minutes logged on the task in earlier, ended sessions of any agent
+    pub minutes_logged: i64,
+    /// Minutes of the estimate still left; none for tasks without an estimate
+    pub expected_minutes: Option<i64>,
+    /// When the task should be done if the estimate holds
+    pub expected_end_at: Option<DateTime<Utc>>,
+    /// Sessions other agents still have open on the task
+    pub other_active_sessions: Vec<WorkSession>,
+}
+
+impl WorkSessionInfo {
+    /// Describe a session just started on `task`, given the task's work sessions
+    pub fn new(
+        session_id: i32,
+        agent_name: String,
+        started_at: DateTime<Utc>,
+        task: Task,
+        sessions: &[WorkSession],
+    ) -> Self {
+        let minutes_logged: i64 = sessions
+            .iter()
+            .filter_map(|s| s.ended_at.map(|ended| (ended - s.started_at).num_minutes()))
+            .sum();
+        let expected_minutes = task
+            .estimated_effort
+            .map(|estimate| (i64::from(estimate) - minutes_logged).max(0));
+        let other_active_sessions = sessions
+            .iter()
+            .filter(|s| s.id != session_id && s.ended_at.is_none() && s.agent_name != agent_name)
+            .cloned()
+            .collect();
+        Self {
+            session_id,
+            task_id: task.id,
+            agent_name,
+            started_at,
+            lease_expires_at: task.lease_expires_at,
+            estimated_effort: task.estimated_effort,
+            minutes_logged,
+            expected_minutes,
+            expected_end_at: expected_minutes.map(|m| started_at + chrono::Duration::minutes(m)),
+            other_active_sessions,
+            task,
+        }
+    }
 }
 
 /// MCP parameters for cleaning up timed-out tasks
@@ -308,17 +1049,245 @@ pub struct CleanupTimedOutTasksParams {
     pub timeout_minutes: i64,
 }
 
+/// MCP parameters for database maintenance; omitted operations are run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintainDatabaseParams {
+    pub integrity_check: Option<bool>,
+    pub checkpoint: Option<bool>,
+    pub vacuum: Option<bool>,
+    pub analyze: Option<bool>,
+}
+
+impl MaintainDatabaseParams {
+    /// Convert MCP parameters to the operations to run
+    pub fn to_maintenance_options(&self) -> MaintenanceOptions {
+        let defaults = MaintenanceOptions::default();
+        MaintenanceOptions {
+            integrity_check: self.integrity_check.unwrap_or(defaults.integrity_check),
+            checkpoint: self.checkpoint.unwrap_or(defaults.checkpoint),
+            vacuum: self.vacuum.unwrap_or(defaults.vacuum),
+            analyze: self.analyze.unwrap_or(defaults.analyze),
+        }
+    }
+}
+
+/// MCP parameters for purging an agent's data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeAgentDataParams {
+    pub agent_name: String,
+    #[serde(default)]
+    pub mode: PurgeMode,
+}
+
+/// MCP parameters for merging another project's database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeWorkspaceParams {
+    /// Database file of the other project
+    pub import_path: String,
+    /// Report what would be merged without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// MCP parameters for registering the server in Claude Code's MCP settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterWithClaudeCodeParams {
+    /// Absolute path of the project the server is registered for
+    pub project_root: String,
+    /// Port the server listens on
+    pub port: u16,
+    /// Settings file to update (default: `~/.claude.json` of the server's user)
+    pub settings_path: Option<String>,
+    /// Report the entry that would be written without changing the file
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// MCP parameters for a read-only SQL query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReadonlyQueryParams {
+    pub sql: String,
+    pub max_rows: Option<u32>,
+    pub timeout_ms: Option<u64>,
+}
+
+impl RunReadonlyQueryParams {
+    /// Convert MCP parameters to a query, applying the default limits
+    pub fn to_query(&self) -> ReadOnlyQuery {
+        ReadOnlyQuery {
+            sql: self.sql.clone(),
+            max_rows: self.max_rows.unwrap_or(DEFAULT_QUERY_ROWS),
+            timeout_ms: self.timeout_ms.unwrap_or(DEFAULT_QUERY_TIMEOUT_MS),
+        }
+    }
+}
+
+/// MCP parameters for defining a capability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefineCapabilityParams {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// MCP parameters for listing capabilities
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListCapabilitiesParams {
+    #[serde(default)]
+    pub include_deprecated: bool,
+}
+
+/// MCP parameters for deprecating a capability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeprecateCapabilityParams {
+    pub name: String,
+    pub replaced_by: Option<String>,
+}
+
+/// Maximum number of audit events returned by one `export_audit_log` call
+pub const MAX_AUDIT_EXPORT_ENTRIES: u32 = 10_000;
+
+/// MCP parameters for exporting the audit log
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportAuditLogParams {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub sources: Option<Vec<AuditSource>>,
+    pub limit: Option<u32>,
+}
+
+impl ExportAuditLogParams {
+    /// Convert MCP parameters to an audit log filter, capping the number of entries
+    pub fn to_filter(&self) -> AuditLogFilter {
+        AuditLogFilter {
+            since: self.since,
+            until: self.until,
+            sources: self.sources.clone().unwrap_or_default(),
+            limit: Some(
+                self.limit
+                    .unwrap_or(MAX_AUDIT_EXPORT_ENTRIES)
+                    .min(MAX_AUDIT_EXPORT_ENTRIES),
+            ),
+        }
+    }
+}
+
+/// Audit events rendered as JSON Lines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogExport {
+    /// Always `"jsonl"`
+    pub format: String,
+    /// Number of lines in `content`
+    pub entries: usize,
+    /// Whether the limit was reached; continue with `since` set to the last timestamp
+    pub truncated: bool,
+    /// One JSON object per line, oldest first
+    pub content: String,
+}
+
+impl AuditLogExport {
+    /// Render audit entries as an export, noting whether `limit` cut it short
+    pub fn new(entries: &[AuditEntry], limit: Option<u32>) -> Self {
+        Self {
+            format: "jsonl".to_string(),
+            entries: entries.len(),
+            truncated: limit.is_some_and(|limit| entries.len() >= limit as usize),
+            content: AuditEntry::to_jsonl(entries),
+        }
+    }
+}
+
+/// MCP parameters for exporting tasks as CSV
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportTasksCsvParams {
+    pub owner: Option<String>,
+    pub state: Option<TaskState>,
+    /// Only tasks created at or after this time
+    pub date_from: Option<DateTime<Utc>>,
+    /// Only tasks created at or before this time
+    pub date_to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub include_archive: bool,
+    /// Columns in output order; defaults to `TaskCsvColumn::DEFAULT`
+    #[serde(default)]
+    pub columns: Vec<TaskCsvColumn>,
+    pub limit: Option<u32>,
+}
+
+impl ExportTasksCsvParams {
+    /// Convert MCP parameters to a task filter, capping the number of rows
+    pub fn to_filter(&self) -> TaskFilter {
+        TaskFilter {
+            owner: self.owner.clone(),
+            state: self.state,
+            date_from: self.date_from,
+            date_to: self.date_to,
+            include_archive: self.include_archive,
+            limit: Some(csv_row_limit(self.limit)),
+            ..TaskFilter::default()
+        }
+    }
+}
+
+/// MCP parameters for exporting work sessions as CSV
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSessionsCsvParams {
+    pub agent_name: Option<String>,
+    pub task_id: Option<i32>,
+    /// Only sessions started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions started before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Columns in output order; defaults to `SessionCsvColumn::DEFAULT`
+    #[serde(default)]
+    pub columns: Vec<SessionCsvColumn>,
+    pub limit: Option<u32>,
+}
+
+impl ExportSessionsCsvParams {
+    /// Convert MCP parameters to a work session filter, capping the number of rows
+    pub fn to_filter(&self) -> WorkSessionFilter {
+        WorkSessionFilter {
+            task_id: self.task_id,
+            agent_name: self.agent_name.clone(),
+            since: self.since,
+            until: self.until,
+            active_only: false,
+            limit: Some(csv_row_limit(self.limit)),
+        }
+    }
+}
+
+fn csv_row_limit(limit: Option<u32>) -> u32 {
+    limit
+        .unwrap_or(MAX_CSV_EXPORT_ROWS)
+        .min(MAX_CSV_EXPORT_ROWS)
+}
+
 // Task Messaging Parameter Types
 
 /// MCP parameters for creating a task message
+///
+/// With `template` set, `content` is rendered from the template and
+/// `template_values`, and `message_type` defaults to the template's type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskMessageParams {
     pub task_code: String,
     pub author_agent_name: String,
     pub target_agent_name: Option<String>,
+    #[serde(default)]
     pub message_type: String,
+    #[serde(default)]
     pub content: String,
     pub reply_to_message_id: Option<i32>,
+    #[serde(default)]
+    pub structured_data: Option<serde_json::Value>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub template_values: HashMap<String, String>,
 }
 
 /// MCP parameters for getting task messages with filtering
@@ -330,6 +1299,122 @@ pub struct GetTaskMessagesParams {
     pub message_type: Option<String>,
     pub reply_to_message_id: Option<i32>,
     pub limit: Option<u32>,
+    /// Return a summary of older messages and the recent ones instead of filtered messages
+    #[serde(default)]
+    pub summarize: bool,
+}
+
+/// MCP parameters for getting the messages that mention an agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMyMentionsParams {
+    pub agent_name: String,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+/// Default number of messages and mentions returned by `get_my_work`
+pub const DEFAULT_MY_WORK_LIMIT: u32 = 20;
+
+/// Default look-ahead of `get_my_work` for deadlines, in minutes
+pub const DEFAULT_DEADLINE_WINDOW_MINUTES: u32 = 60;
+
+/// MCP parameters for getting an agent's work queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMyWorkParams {
+    pub agent_name: String,
+    /// Only messages and mentions posted after this time; pass the previous `as_of`
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum number of messages and of mentions
+    pub limit: Option<u32>,
+    /// How far ahead to report deadlines, in minutes
+    pub deadline_window_minutes: Option<u32>,
+}
+
+/// MCP parameters for the digest of an agent's updates since a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUpdatesSinceParams {
+    pub agent_name: String,
+    /// Start of the digest, typically when the agent last ran or the previous `as_of`
+    pub since: DateTime<Utc>,
+    /// Maximum number of mentions
+    pub limit: Option<u32>,
+    /// How far ahead to warn about expiring claim leases, in minutes
+    pub deadline_window_minutes: Option<u32>,
+}
+
+/// A point in time by which an agent has to act on a task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkDeadline {
+    pub task_id: i32,
+    pub task_code: String,
+    pub due_at: DateTime<Utc>,
+    /// What is due; tasks have no due dates, so this is `"lease_expiry"`, or
+    /// `"lease_expired"` in update digests once the lease has run out
+    pub kind: String,
+}
+
+/// Everything an agent needs at the top of its work loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MyWork {
+    pub agent_name: String,
+    /// When the snapshot was taken; use as `since` on the next call
+    pub as_of: DateTime<Utc>,
+    /// Tasks the agent has in progress
+    pub claimed_tasks: Vec<Task>,
+    /// New messages addressed to the agent, newest first
+    pub messages: Vec<TaskMessage>,
+    /// New mentions of the agent, newest first
+    pub mentions: Vec<TaskMention>,
+    /// Tasks in review whose review was requested from the agent
+    pub pending_reviews: Vec<Task>,
+    /// Claim leases expiring within the deadline window, soonest first
+    pub upcoming_deadlines: Vec<WorkDeadline>,
+}
+
+/// MCP parameters for listing message templates
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GetMessageTemplatesParams {
+    /// Return only the template with this name
+    pub name: Option<String>,
+}
+
+// Task Comment Parameter Types
+
+/// MCP parameters for adding a comment to a task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddTaskCommentParams {
+    pub task_code: String,
+    pub author_agent_name: String,
+    pub content: String,
+}
+
+/// MCP parameters for editing a comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditTaskCommentParams {
+    pub comment_id: i32,
+    pub agent_name: String,
+    pub content: String,
+}
+
+/// MCP parameters for deleting a comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteTaskCommentParams {
+    pub comment_id: i32,
+    pub agent_name: String,
+}
+
+/// MCP parameters for listing the comments of a task
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GetTaskCommentsParams {
+    pub task_code: String,
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+/// MCP parameters for getting the edit history of a comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTaskCommentHistoryParams {
+    pub comment_id: i32,
 }
 
 // Workspace Setup Parameter Types
@@ -356,6 +1441,21 @@ pub struct RegisterAgentParams {
     pub agent_type: String,
     pub capabilities: Vec<String>,
     pub description: Option<String>,
+    /// Prompt style to adopt for the workspace from now on
+    #[serde(default)]
+    pub style_options: Option<crate::prompt_templates::PromptStyle>,
+}
+
+/// MCP parameters for regenerating a registered agent's prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegenerateAgentPromptParams {
+    pub agent_name: String,
+    /// Style to render with (default: the workspace's prompt style)
+    #[serde(default)]
+    pub style_options: Option<crate::prompt_templates::PromptStyle>,
+    /// Replace the stored prompt with the regenerated one (default: only preview the diff)
+    #[serde(default)]
+    pub apply: bool,
 }
 
 /// MCP parameters for getting main AI file instructions
@@ -367,9 +1467,83 @@ pub struct GetInstructionsForMainAiFileParams {
 /// MCP parameters for creating main AI file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMainAiFileParams {
+    /// Content of a new file; an existing file only has its Axon-managed sections updated
     pub content: String,
+    /// Current file content; read from the project root when omitted
+    #[serde(default)]
+    pub existing_content: Option<String>,
+    /// Overwrite managed sections that were edited by hand
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// MCP parameters for the setup progress checklist
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetSetupStatusParams {
+    /// AI tool whose setup steps are listed; defaults to claude-code
+    #[serde(default)]
+    pub ai_tool_type: Option<String>,
+}
+
+/// MCP parameters for a step of the setup wizard
+///
+/// Each input completes one step; a call without the input of the current
+/// step only reports where the setup stands.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetupWizardParams {
+    /// Start over at the PRD step; registered agents and files are kept
+    #[serde(default)]
+    pub restart: bool,
+    /// PRD Markdown, completing the `prd` step
+    #[serde(default)]
+    pub prd_content: Option<String>,
+    /// Names of the suggested agents to register, completing the `agents` step
+    #[serde(default)]
+    pub agents: Option<Vec<String>>,
+    /// Coordination instructions for CLAUDE.md, completing the `main_file` step
+    #[serde(default)]
+    pub main_file_content: Option<String>,
+    /// Confirmation completing the `manifest` step
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+impl SetupWizardParams {
+    /// Step whose input the parameters carry, if any
+    pub fn input_step(&self) -> Option<crate::setup_wizard::WizardStep> {
+        use crate::setup_wizard::WizardStep;
+        if self.prd_content.is_some() {
+            Some(WizardStep::Prd)
+        } else if self.agents.is_some() {
+            Some(WizardStep::Agents)
+        } else if self.main_file_content.is_some() {
+            Some(WizardStep::MainFile)
+        } else if self.confirm {
+            Some(WizardStep::Manifest)
+        } else {
+            None
+        }
+    }
 }
 
+/// MCP parameters for verifying a workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyWorkspaceParams {
+    /// Workspace to check; the session's workspace root, else the server's
+    #[serde(default)]
+    pub project_root: Option<String>,
+}
+
+/// MCP parameters for rolling back a workspace setup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RollbackSetupParams {
+    /// Workspace to roll back; the session's workspace root, else the server's
+    #[serde(default)]
+    pub project_root: Option<String>,
+    /// List what would be removed without removing it
+    #[serde(default)]
+    pub dry_run: bool,
+}
 
 #[cfg(test)]
 mod tests {
@@ -385,6 +1559,8 @@ mod tests {
             completed_after: Some("2023-12-15T00:00:00Z".to_string()),
             completed_before: Some("2023-12-30T23:59:59Z".to_string()),
             limit: Some(10),
+            include_archive: false,
+            ..Default::default()
         };
 
         let filter = params.to_task_filter().unwrap();
@@ -398,6 +1574,85 @@ mod tests {
         assert_eq!(filter.offset, None);
     }
 
+    #[test]
+    fn test_task_batch_reports_missing_keys() {
+        let task = |id: i32, code: &str| {
+            Task::new(
+                id,
+                code.to_string(),
+                code.to_string(),
+                String::new(),
+                None,
+                TaskState::Created,
+                Utc::now(),
+                None,
+            )
+        };
+        let batch = TaskBatch::new(
+            vec![3, 9, 1, 9],
+            vec![task(3, "C-3"), task(1, "C-1")],
+            |task| &task.id,
+        );
+        assert_eq!(batch.tasks.len(), 2);
+        assert_eq!(batch.missing, [9]);
+
+        let params = GetTasksByCodesParams {
+            codes: vec!["C-1".to_string(); MAX_BATCH_LOOKUP + 1],
+        };
+        assert!(matches!(
+            params.validate(),
+            Err(crate::error::TaskError::Validation(_))
+        ));
+        let params = GetTasksByIdsParams {
+            ids: vec![1; MAX_BATCH_LOOKUP],
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn test_apply_task_operations_params() {
+        let params: ApplyTaskOperationsParams = serde_json::from_value(serde_json::json!({
+            "operations": [
+                {"op": "assign", "id": 4, "new_owner": "qa-bot"},
+                {"op": "set_state", "id": 4, "state": "Review"},
+                {"op": "message", "task_code": "QA-4", "author_agent_name": "lead", "content": "Over to QA"}
+            ]
+        }))
+        .unwrap();
+        assert!(params.validate().is_ok());
+        assert!(matches!(
+            &params.operations[1],
+            TaskOperationParams::SetState(SetStateParams {
+                id: 4,
+                state: TaskState::Review
+            })
+        ));
+        assert!(matches!(
+            &params.operations[2],
+            TaskOperationParams::Message(message) if message.content == "Over to QA"
+        ));
+
+        let empty = ApplyTaskOperationsParams {
+            operations: Vec::new(),
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_list_params_accept_state_aliases() {
+        let params: ListTasksParams = serde_json::from_value(serde_json::json!({
+            "state": "in_progress",
+            "states": ["review", "Pending-Handoff"]
+        }))
+        .unwrap();
+        let filter = params.to_task_filter().unwrap();
+        assert_eq!(filter.state, Some(TaskState::InProgress));
+        assert_eq!(
+            filter.states,
+            [TaskState::Review, TaskState::PendingHandoff]
+        );
+    }
+
     #[test]
     fn test_update_task_params_methods() {
         let update_data = UpdateTask::with_basic_fields(
@@ -429,6 +1684,52 @@ mod tests {
         assert_eq!(extracted.owner_agent_name, Some("new-owner".to_string()));
     }
 
+    #[test]
+    fn test_work_session_info() {
+        let now = Utc::now();
+        let task = Task {
+            estimated_effort: Some(120),
+            lease_expires_at: Some(now + chrono::Duration::hours(2)),
+            ..Task::new(
+                7,
+                "API-01".to_string(),
+                "Build API".to_string(),
+                "Endpoints".to_string(),
+                Some("backend-dev".to_string()),
+                TaskState::InProgress,
+                now,
+                None,
+            )
+        };
+        let session = |id, agent: &str, minutes_ago: i64, ended: bool| WorkSession {
+            id,
+            task_id: 7,
+            agent_name: agent.to_string(),
+            started_at: now - chrono::Duration::minutes(minutes_ago),
+            ended_at: ended.then(|| now - chrono::Duration::minutes(minutes_ago - 45)),
+            notes: None,
+            productivity_score: None,
+            interruptions: Vec::new(),
+        };
+        let sessions = vec![
+            session(1, "backend-dev", 120, true),
+            session(2, "qa-dev", 30, false),
+            session(3, "backend-dev", 0, false),
+        ];
+
+        let info = WorkSessionInfo::new(3, "backend-dev".to_string(), now, task, &sessions);
+        assert_eq!(info.task_id, 7);
+        assert_eq!(info.minutes_logged, 45);
+        assert_eq!(info.expected_minutes, Some(75));
+        assert_eq!(
+            info.expected_end_at,
+            Some(now + chrono::Duration::minutes(75))
+        );
+        assert_eq!(info.other_active_sessions.len(), 1);
+        assert_eq!(info.other_active_sessions[0].agent_name, "qa-dev");
+        assert!(info.lease_expires_at.is_some());
+    }
+
     #[test]
     fn test_health_status_default() {
         let health = HealthStatus::default();
@@ -1,16 +1,29 @@
 use crate::{
-    error::Result,
-    models::{NewTask, Task, TaskFilter, TaskMessage, TaskState, UpdateTask},
+    artifacts::{ArtifactFilter, TaskArtifact},
+    embeddings::{EmbeddingDocument, SemanticMatch, SemanticSearchFilter},
+    error::{Result, TaskError},
+    models::{
+        Capability, ExternalLink, NewTask, SystemEvent, Task, TaskClaim, TaskComment,
+        TaskCommentRevision, TaskFilter, TaskMention, TaskMessage, TaskState, UpdateTask,
+        WorkSession,
+    },
+    session_log::{SessionLogChunk, SessionLogEntry},
+    task_history::TaskBoard,
+    thread_summary::ThreadSummary,
+    tool_usage::{ToolCall, ToolUsageFilter, ToolUsageStat},
+    workspace_merge::WorkspaceMergeReport,
     workspace_setup::WorkspaceContext,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-/// Repository trait for task persistence and retrieval operations
+/// Task persistence and retrieval operations
 ///
-/// This trait defines the interface for all task data operations.
 /// Implementations must be thread-safe and support concurrent access.
 #[async_trait]
-pub trait TaskRepository: Send + Sync {
+pub trait TaskCrudRepository: Send + Sync {
     /// Create a new task
     ///
     /// # Arguments
@@ -71,6 +84,69 @@ pub trait TaskRepository: Send + Sync {
     /// * `Err(TaskError::Database)` - If the database operation fails
     async fn get_by_code(&self, code: &str) -> Result<Option<Task>>;
 
+    /// Get several tasks by their numeric IDs
+    ///
+    /// The default implementation looks the tasks up one by one; backends
+    /// override it to fetch them in a single query.
+    ///
+    /// # Arguments
+    /// * `ids` - The task IDs to find
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The tasks found, in the order of their first ID in `ids`
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_by_ids(&self, ids: &[i32]) -> Result<Vec<Task>> {
+        let mut tasks: Vec<Task> = Vec::with_capacity(ids.len());
+        for id in ids {
+            if tasks.iter().any(|task| task.id == *id) {
+                continue;
+            }
+            if let Some(task) = self.get_by_id(*id).await? {
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// Get several tasks by their human-readable codes
+    ///
+    /// The default implementation looks the tasks up one by one; backends
+    /// override it to fetch them in a single query.
+    ///
+    /// # Arguments
+    /// * `codes` - The task codes to find
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The tasks found, in the order of their first code in `codes`
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_by_codes(&self, codes: &[String]) -> Result<Vec<Task>> {
+        let mut tasks: Vec<Task> = Vec::with_capacity(codes.len());
+        for code in codes {
+            if tasks.iter().any(|task| &task.code == code) {
+                continue;
+            }
+            if let Some(task) = self.get_by_code(code).await? {
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// Get a task by its stable identifier (UUID or ULID)
+    ///
+    /// Backends that do not assign stable identifiers find nothing.
+    ///
+    /// # Arguments
+    /// * `uid` - The stable identifier to find
+    ///
+    /// # Returns
+    /// * `Ok(Some(Task))` - The task if found
+    /// * `Ok(None)` - If no task has that identifier
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_by_uid(&self, _uid: &str) -> Result<Option<Task>> {
+        Ok(None)
+    }
+
     /// List tasks matching the given filter criteria
     ///
     /// # Arguments
@@ -120,8 +196,81 @@ pub trait TaskRepository: Send + Sync {
     /// * `Err(TaskError::Database)` - If unable to gather statistics
     async fn get_stats(&self) -> Result<RepositoryStats>;
 
-    // MCP v2 Advanced Multi-Agent Features
+    /// Count tasks matching a filter without loading them
+    ///
+    /// # Arguments
+    /// * `filter` - Filter criteria; limit and offset are ignored
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - Number of matching tasks
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn count_tasks(&self, _filter: &TaskFilter) -> Result<u64> {
+        Err(TaskError::UnsupportedOperation(
+            "task counts are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Count tasks matching a filter per state, owner or capability
+    ///
+    /// # Arguments
+    /// * `filter` - Filter criteria; limit and offset are ignored
+    /// * `group_by` - Attribute to group by
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskGroupCount>)` - Non-empty groups, largest first
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn aggregate_tasks(
+        &self,
+        _filter: &TaskFilter,
+        _group_by: TaskGroupBy,
+    ) -> Result<Vec<TaskGroupCount>> {
+        Err(TaskError::UnsupportedOperation(
+            "task aggregation is not supported by this repository".to_string(),
+        ))
+    }
 
+    /// Apply an ordered list of operations in one transaction
+    ///
+    /// Either every operation is applied or, when one fails, none is.
+    ///
+    /// # Arguments
+    /// * `operations` - Operations to apply, in order
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskOperationOutcome>)` - One outcome per operation, in order
+    /// * `Err(TaskError)` - The error of the first failing operation; nothing was applied
+    async fn apply_operations(
+        &self,
+        _operations: &[TaskOperation],
+    ) -> Result<Vec<TaskOperationOutcome>> {
+        Err(TaskError::UnsupportedOperation(
+            "transactional operations are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Create a pipeline task and its stages in one transaction
+    ///
+    /// The stages become subtasks of the pipeline task. The first stage is
+    /// left `Created`; the pipeline task and the later stages move to
+    /// `WaitingForDependency`.
+    ///
+    /// # Arguments
+    /// * `pipeline` - The pipeline task
+    /// * `stages` - Stage tasks, in order
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The pipeline task followed by its stages
+    /// * `Err(TaskError)` - The error of the first failing insert; nothing was created
+    async fn create_pipeline(&self, _pipeline: &NewTask, _stages: &[NewTask]) -> Result<Vec<Task>> {
+        Err(TaskError::UnsupportedOperation(
+            "pipelines are not supported by this repository".to_string(),
+        ))
+    }
+}
+
+/// Work discovery and claims of tasks by agents
+#[async_trait]
+pub trait WorkClaimRepository: Send + Sync {
     /// Discover available work for an agent based on capabilities
     ///
     /// # Arguments
@@ -152,61 +301,1177 @@ pub trait TaskRepository: Send + Sync {
     /// * `Err(TaskError::Database)` - If the database operation fails
     async fn claim_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
 
-    /// Release a previously claimed task
+    /// Claim a task with a lease after which cleanup may release it
+    ///
+    /// Without a lease the claim falls back to the global cleanup timeout.
+    /// Repositories without lease support ignore `lease`.
+    ///
+    /// # Arguments
+    /// * `task_id` - The task to claim
+    /// * `agent_name` - The agent claiming the task
+    /// * `lease` - How long the claim is held
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The claimed task with `lease_expires_at` set
+    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Err(TaskError::AlreadyClaimed)` - If the task is already claimed
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn claim_task_with_lease(
+        &self,
+        task_id: i32,
+        agent_name: &str,
+        _lease: Option<chrono::Duration>,
+    ) -> Result<Task> {
+        self.claim_task(task_id, agent_name).await
+    }
+
+    /// Release a previously claimed task
+    ///
+    /// # Arguments
+    /// * `task_id` - The task to release
+    /// * `agent_name` - The agent releasing the task
+    ///
+    /// # Returns
+    /// * `Ok(Task)` - The released task
+    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Err(TaskError::NotOwned)` - If the agent doesn't own the task
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
+
+    /// Release tasks that have been claimed for longer than the timeout duration
+    ///
+    /// This function finds tasks in InProgress state where claimed_at is older than
+    /// the specified timeout and releases them back to Created state for re-claiming.
+    ///
+    /// # Arguments
+    /// * `timeout_minutes` - Tasks claimed longer than this many minutes will be released
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Task>)` - The list of tasks that were released due to timeout
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
+
+    /// Claims of a task, oldest first
+    ///
+    /// # Arguments
+    /// * `task_id` - The task whose claims to list
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskClaim>)` - Every claim of the task, the current one last
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn task_claims(&self, _task_id: i32) -> Result<Vec<TaskClaim>> {
+        Err(TaskError::UnsupportedOperation(
+            "claim history is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Timed-out claims that are still the latest claim of their task
+    ///
+    /// # Arguments
+    /// * `since` - Only claims released by cleanup at or after this time
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskClaim>)` - One claim per task whose previous owner has not been replaced
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn timed_out_claims(&self, _since: DateTime<Utc>) -> Result<Vec<TaskClaim>> {
+        Err(TaskError::UnsupportedOperation(
+            "claim history is not supported by this repository".to_string(),
+        ))
+    }
+}
+
+/// Time tracking of the work agents do on claimed tasks
+#[async_trait]
+pub trait WorkSessionRepository: Send + Sync {
+    /// Start a work session for time tracking
+    ///
+    /// # Arguments
+    /// * `task_id` - The task being worked on
+    /// * `agent_name` - The agent starting work
+    ///
+    /// # Returns
+    /// * `Ok(i32)` - The work session ID
+    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32>;
+
+    /// End a work session and record productivity
+    ///
+    /// # Arguments
+    /// * `session_id` - The work session to end
+    /// * `notes` - Optional notes about the work done
+    /// * `productivity_score` - Productivity score (0.0-1.0)
+    ///
+    /// # Returns
+    /// * `Ok(())` - Session ended successfully
+    /// * `Err(TaskError::NotFound)` - If the session doesn't exist
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn end_work_session(
+        &self,
+        session_id: i32,
+        notes: Option<String>,
+        productivity_score: Option<f64>,
+    ) -> Result<()>;
+
+    /// Get a work session by ID
+    ///
+    /// # Arguments
+    /// * `session_id` - The work session to look up
+    ///
+    /// # Returns
+    /// * `Ok(Some(WorkSession))` - The session, active or ended
+    /// * `Ok(None)` - If no session has that ID
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_work_session(&self, _session_id: i32) -> Result<Option<WorkSession>> {
+        Err(TaskError::UnsupportedOperation(
+            "work session lookup is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List work sessions, oldest first
+    ///
+    /// # Arguments
+    /// * `filter` - Task, agent, start time range and maximum number of sessions
+    ///
+    /// # Returns
+    /// * `Ok(Vec<WorkSession>)` - Matching sessions, active or ended
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_work_sessions(&self, _filter: &WorkSessionFilter) -> Result<Vec<WorkSession>> {
+        Err(TaskError::UnsupportedOperation(
+            "work session listing is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Append a chunk to the transcript of an active work session, stored compressed
+    ///
+    /// # Arguments
+    /// * `session_id` - The active work session
+    /// * `chunk` - Transcript text, tool calls, decisions and files touched
+    ///
+    /// # Returns
+    /// * `Ok(SessionLogEntry)` - The stored chunk with its sizes
+    /// * `Err(TaskError::SessionNotFound)` - If the session doesn't exist or has ended
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn append_session_log(
+        &self,
+        _session_id: i32,
+        _chunk: &SessionLogChunk,
+    ) -> Result<SessionLogEntry> {
+        Err(TaskError::UnsupportedOperation(
+            "session logs are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Get the transcript of a work session, oldest chunk first
+    ///
+    /// # Arguments
+    /// * `session_id` - The work session, active or ended
+    ///
+    /// # Returns
+    /// * `Ok(Vec<SessionLogEntry>)` - The decompressed chunks; empty when none were appended
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_session_log(&self, _session_id: i32) -> Result<Vec<SessionLogEntry>> {
+        Err(TaskError::UnsupportedOperation(
+            "session logs are not supported by this repository".to_string(),
+        ))
+    }
+}
+
+/// Optional repository features
+///
+/// Every method defaults to [`TaskError::UnsupportedOperation`], so a backend
+/// without any of them only needs an empty `impl`.
+#[async_trait]
+pub trait TaskExtensionRepository: Send + Sync {
+    /// Delete data older than the retention policy allows
+    ///
+    /// # Arguments
+    /// * `policy` - Maximum age of each kind of data
+    /// * `dry_run` - Only count what would be deleted
+    ///
+    /// # Returns
+    /// * `Ok(RetentionReport)` - Rows deleted, or that would be deleted on a dry run
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn apply_retention(
+        &self,
+        _policy: &RetentionPolicy,
+        _dry_run: bool,
+    ) -> Result<RetentionReport> {
+        Err(TaskError::UnsupportedOperation(
+            "retention policies are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Run database maintenance: integrity check, WAL checkpoint, VACUUM and ANALYZE
+    ///
+    /// # Arguments
+    /// * `options` - Which operations to run
+    ///
+    /// # Returns
+    /// * `Ok(MaintenanceReport)` - Sizes before and after, and integrity findings
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn maintain_database(&self, _options: &MaintenanceOptions) -> Result<MaintenanceReport> {
+        Err(TaskError::UnsupportedOperation(
+            "database maintenance is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Compare the schema migrations shipped with the server to those applied to the database
+    ///
+    /// # Returns
+    /// * `Ok(MigrationStatus)` - Every known migration and whether it is applied
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn migration_status(&self) -> Result<MigrationStatus> {
+        Err(TaskError::UnsupportedOperation(
+            "migration status is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Collect audit events recorded by the repository, oldest first
+    ///
+    /// # Arguments
+    /// * `filter` - Time range, sources and maximum number of entries
+    ///
+    /// # Returns
+    /// * `Ok(Vec<AuditEntry>)` - Matching events in chronological order
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn audit_log(&self, _filter: &AuditLogFilter) -> Result<Vec<AuditEntry>> {
+        Err(TaskError::UnsupportedOperation(
+            "audit log export is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Run a single `SELECT` statement on a connection that cannot write
+    ///
+    /// # Arguments
+    /// * `query` - The statement, row limit and timeout
+    ///
+    /// # Returns
+    /// * `Ok(QueryResult)` - Column names and at most `max_rows` rows
+    /// * `Err(TaskError::Validation)` - If the statement is not a single `SELECT`
+    /// * `Err(TaskError::Database)` - If the query fails or times out
+    async fn run_readonly_query(&self, _query: &ReadOnlyQuery) -> Result<QueryResult> {
+        Err(TaskError::UnsupportedOperation(
+            "read-only queries are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Remove or anonymize everything recorded about an agent
+    ///
+    /// Tasks are kept and handed to [`REMOVED_AGENT_NAME`] so task history
+    /// stays intact.
+    ///
+    /// # Arguments
+    /// * `agent_name` - The agent whose data is purged
+    /// * `mode` - Whether authored records are anonymized or deleted
+    ///
+    /// # Returns
+    /// * `Ok(AgentPurgeReport)` - Rows affected per kind of data
+    /// * `Err(TaskError::Validation)` - If the agent name is invalid
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn purge_agent_data(
+        &self,
+        _agent_name: &str,
+        _mode: PurgeMode,
+    ) -> Result<AgentPurgeReport> {
+        Err(TaskError::UnsupportedOperation(
+            "agent data purge is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List the capability catalog, including deprecated capabilities
+    ///
+    /// # Returns
+    /// * `Ok(Vec<Capability>)` - All defined capabilities, ordered by name
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_capabilities(&self) -> Result<Vec<Capability>> {
+        Err(TaskError::UnsupportedOperation(
+            "capability catalog is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Define a capability, or replace the description and aliases of an existing one
+    ///
+    /// Redefining a deprecated capability restores it.
+    ///
+    /// # Arguments
+    /// * `name` - Canonical capability name
+    /// * `description` - What the capability covers
+    /// * `aliases` - Canonical spellings of alternative names
+    ///
+    /// # Returns
+    /// * `Ok(Capability)` - The stored capability
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn define_capability(
+        &self,
+        _name: &str,
+        _description: &str,
+        _aliases: &[String],
+    ) -> Result<Capability> {
+        Err(TaskError::UnsupportedOperation(
+            "capability catalog is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Mark a capability as deprecated
+    ///
+    /// # Arguments
+    /// * `name` - Canonical capability name
+    /// * `replaced_by` - Capability that free-text uses are normalized to instead
+    ///
+    /// # Returns
+    /// * `Ok(Capability)` - The deprecated capability
+    /// * `Err(TaskError::NotFound)` - If no capability has that name
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn deprecate_capability(
+        &self,
+        _name: &str,
+        _replaced_by: Option<&str>,
+    ) -> Result<Capability> {
+        Err(TaskError::UnsupportedOperation(
+            "capability catalog is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List the links of tasks to items of an external tracker
+    ///
+    /// # Arguments
+    /// * `system` - External tracker, e.g. `github`
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ExternalLink>)` - Links ordered by task code
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_external_links(&self, _system: &str) -> Result<Vec<ExternalLink>> {
+        Err(TaskError::UnsupportedOperation(
+            "external links are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List the links of one task to items of any external tracker
+    ///
+    /// # Arguments
+    /// * `task_code` - Code of the linked task
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ExternalLink>)` - Links ordered by system
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_task_external_links(&self, _task_code: &str) -> Result<Vec<ExternalLink>> {
+        Err(TaskError::UnsupportedOperation(
+            "external links are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Create or update the link of a task to an external tracker item
+    ///
+    /// # Arguments
+    /// * `link` - Link keyed by system and task code
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the link was stored
+    /// * `Err(TaskError::DuplicateKey)` - If the item is already linked to another task
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn save_external_link(&self, _link: &ExternalLink) -> Result<()> {
+        Err(TaskError::UnsupportedOperation(
+            "external links are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Record a file produced by a task, replacing an earlier record of the same path
+    ///
+    /// # Arguments
+    /// * `artifact` - Artifact keyed by task code and path; its `id` is ignored
+    ///
+    /// # Returns
+    /// * `Ok(TaskArtifact)` - The stored artifact with its ID
+    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn save_artifact(&self, _artifact: &TaskArtifact) -> Result<TaskArtifact> {
+        Err(TaskError::UnsupportedOperation(
+            "artifacts are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List registered artifacts, ordered by task code and path
+    ///
+    /// # Arguments
+    /// * `filter` - Task, kind and maximum number of artifacts
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskArtifact>)` - Matching artifacts
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_artifacts(&self, _filter: &ArtifactFilter) -> Result<Vec<TaskArtifact>> {
+        Err(TaskError::UnsupportedOperation(
+            "artifacts are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Store a system event for the coordinator to review
+    ///
+    /// # Arguments
+    /// * `event` - Event to store; its `id` is ignored
+    ///
+    /// # Returns
+    /// * `Ok(i32)` - ID of the stored event
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn record_system_event(&self, _event: &SystemEvent) -> Result<i32> {
+        Err(TaskError::UnsupportedOperation(
+            "system events are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// List stored system events, newest first
+    ///
+    /// # Arguments
+    /// * `filter` - Event type, entity, start time and maximum number of events
+    ///
+    /// # Returns
+    /// * `Ok(Vec<SystemEvent>)` - Matching events
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn list_system_events(&self, _filter: &SystemEventFilter) -> Result<Vec<SystemEvent>> {
+        Err(TaskError::UnsupportedOperation(
+            "system events are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Record a tool call of an agent
+    ///
+    /// # Arguments
+    /// * `call` - Agent, tool, time, duration and outcome of the call
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the call was recorded
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn record_tool_call(&self, _call: &ToolCall) -> Result<()> {
+        Err(TaskError::UnsupportedOperation(
+            "tool usage is not recorded by this repository".to_string(),
+        ))
+    }
+
+    /// Count recorded tool calls per agent and tool, most called first
+    ///
+    /// # Arguments
+    /// * `filter` - Agent, tool, period and maximum number of pairs
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ToolUsageStat>)` - Calls of each agent and tool pair
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn tool_usage_stats(&self, _filter: &ToolUsageFilter) -> Result<Vec<ToolUsageStat>> {
+        Err(TaskError::UnsupportedOperation(
+            "tool usage is not recorded by this repository".to_string(),
+        ))
+    }
+
+    /// Rebuild the task board as it was at a point in time from the event history
+    ///
+    /// # Arguments
+    /// * `at` - Point in time to show
+    ///
+    /// # Returns
+    /// * `Ok(TaskBoard)` - Tasks as they were at `at`; empty before history starts
+    /// * `Err(TaskError::UnsupportedOperation)` - If event history is not recorded
+    async fn board_at(&self, _at: DateTime<Utc>) -> Result<TaskBoard> {
+        Err(TaskError::UnsupportedOperation(
+            "task event history is not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Merge the tasks and messages of another project's database into this one
     ///
     /// # Arguments
-    /// * `task_id` - The task to release
-    /// * `agent_name` - The agent releasing the task
+    /// * `import_path` - Database file to import from; it is only read
+    /// * `dry_run` - Compute the report without keeping any change
     ///
     /// # Returns
-    /// * `Ok(Task)` - The released task
-    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
-    /// * `Err(TaskError::NotOwned)` - If the agent doesn't own the task
+    /// * `Ok(WorkspaceMergeReport)` - What was imported, matched and renamed
+    /// * `Err(TaskError::Validation)` - If the import database does not exist
     /// * `Err(TaskError::Database)` - If the database operation fails
-    async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
+    async fn merge_database(
+        &self,
+        _import_path: &Path,
+        _dry_run: bool,
+    ) -> Result<WorkspaceMergeReport> {
+        Err(TaskError::UnsupportedOperation(
+            "merging databases is not supported by this repository".to_string(),
+        ))
+    }
 
-    /// Start a work session for time tracking
+    /// Tasks, messages and comments without an embedding of `model` for their current text
     ///
     /// # Arguments
-    /// * `task_id` - The task being worked on
-    /// * `agent_name` - The agent starting work
+    /// * `model` - Embedding model the documents are checked against
+    /// * `limit` - Maximum number of documents
     ///
     /// # Returns
-    /// * `Ok(i32)` - The work session ID
-    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Ok(Vec<EmbeddingDocument>)` - Documents to embed
     /// * `Err(TaskError::Database)` - If the database operation fails
-    async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32>;
+    async fn stale_embedding_documents(
+        &self,
+        _model: &str,
+        _limit: u32,
+    ) -> Result<Vec<EmbeddingDocument>> {
+        Err(TaskError::UnsupportedOperation(
+            "embeddings are not supported by this repository".to_string(),
+        ))
+    }
 
-    /// End a work session and record productivity
+    /// Store embeddings of `model`, replacing earlier ones of the same records
     ///
     /// # Arguments
-    /// * `session_id` - The work session to end
-    /// * `notes` - Optional notes about the work done
-    /// * `productivity_score` - Productivity score (0.0-1.0)
+    /// * `model` - Embedding model the vectors were computed with
+    /// * `embeddings` - Documents with their vectors
     ///
     /// # Returns
-    /// * `Ok(())` - Session ended successfully
-    /// * `Err(TaskError::NotFound)` - If the session doesn't exist
+    /// * `Ok(())` - If the embeddings were stored
     /// * `Err(TaskError::Database)` - If the database operation fails
-    async fn end_work_session(
+    async fn save_embeddings(
         &self,
-        session_id: i32,
-        notes: Option<String>,
-        productivity_score: Option<f64>,
-    ) -> Result<()>;
+        _model: &str,
+        _embeddings: &[(EmbeddingDocument, Vec<f32>)],
+    ) -> Result<()> {
+        Err(TaskError::UnsupportedOperation(
+            "embeddings are not supported by this repository".to_string(),
+        ))
+    }
 
-    /// Release tasks that have been claimed for longer than the timeout duration
+    /// Remove embeddings of `model` whose record was deleted
     ///
-    /// This function finds tasks in InProgress state where claimed_at is older than
-    /// the specified timeout and releases them back to Created state for re-claiming.
+    /// # Returns
+    /// * `Ok(u64)` - Number of embeddings removed
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn prune_embeddings(&self, _model: &str) -> Result<u64> {
+        Err(TaskError::UnsupportedOperation(
+            "embeddings are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Records whose embedding of `model` is closest to `query`
+    ///
+    /// Records changed since they were embedded are left out until reindexed.
     ///
     /// # Arguments
-    /// * `timeout_minutes` - Tasks claimed longer than this many minutes will be released
+    /// * `model` - Embedding model `query` was computed with
+    /// * `query` - Embedding of the search text
+    /// * `filter` - Kinds of records and number of matches
     ///
     /// # Returns
-    /// * `Ok(Vec<Task>)` - The list of tasks that were released due to timeout
+    /// * `Ok(Vec<SemanticMatch>)` - Matches, most similar first
     /// * `Err(TaskError::Database)` - If the database operation fails
-    async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
+    async fn semantic_search(
+        &self,
+        _model: &str,
+        _query: &[f32],
+        _filter: &SemanticSearchFilter,
+    ) -> Result<Vec<SemanticMatch>> {
+        Err(TaskError::UnsupportedOperation(
+            "embeddings are not supported by this repository".to_string(),
+        ))
+    }
+}
+
+/// Complete task repository: everything the MCP handler relies on
+///
+/// Implemented for every type implementing the parts it is composed of. All
+/// parts are object safe, so `Arc<dyn TaskRepository>` works as well as a
+/// concrete repository.
+pub trait TaskRepository:
+    TaskCrudRepository + WorkClaimRepository + WorkSessionRepository + TaskExtensionRepository
+{
+}
+
+impl<T> TaskRepository for T where
+    T: TaskCrudRepository
+        + WorkClaimRepository
+        + WorkSessionRepository
+        + TaskExtensionRepository
+        + ?Sized
+{
+}
+
+/// Which stable identifiers a repository assigns to new tasks and messages
+///
+/// Numeric IDs stay the internal keys either way; the stable identifier is
+/// stored alongside them so records keep their identity across databases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdStrategy {
+    /// Numeric IDs only
+    #[default]
+    Integer,
+    /// Random UUID v4 identifiers
+    Uuid,
+    /// Lexicographically sortable ULID identifiers
+    Ulid,
+}
+
+/// Placeholder owner and author of records left behind by a purged agent
+pub const REMOVED_AGENT_NAME: &str = "removed-agent";
+
+/// How a purge treats records authored by the agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeMode {
+    /// Keep the records but attribute them to [`REMOVED_AGENT_NAME`]
+    #[default]
+    Anonymize,
+    /// Delete messages, work sessions and comments authored by the agent
+    Delete,
+}
+
+/// Rows affected by purging an agent's data
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AgentPurgeReport {
+    /// The purged agent
+    pub agent_name: String,
+    /// How authored records were treated
+    pub mode: PurgeMode,
+    /// Tasks handed to [`REMOVED_AGENT_NAME`]
+    pub tasks_reassigned: u64,
+    /// Messages authored by the agent
+    pub messages: u64,
+    /// Messages addressed to the agent, whose target was cleared
+    pub message_targets: u64,
+    /// Mentions of the agent
+    pub mentions: u64,
+    /// Work sessions of the agent
+    pub work_sessions: u64,
+    /// Task claims made by the agent
+    pub claims: u64,
+    /// Comments authored by the agent
+    pub comments: u64,
+    /// Comment revisions edited by the agent
+    pub comment_revisions: u64,
+    /// Task history entries owned by the agent, handed to [`REMOVED_AGENT_NAME`]
+    pub history_entries: u64,
+    /// Entries removed from the workspace agent registry
+    pub registry_entries: u64,
+    /// System events about or triggered by the agent, deleted in either mode
+    pub system_events: u64,
+    /// Artifacts registered by the agent, handed to [`REMOVED_AGENT_NAME`]
+    pub artifacts: u64,
+    /// Recorded tool calls of the agent, deleted in either mode
+    pub tool_calls: u64,
+}
+
+impl AgentPurgeReport {
+    /// Total rows across all kinds of data
+    pub fn total(&self) -> u64 {
+        self.tasks_reassigned
+            + self.messages
+            + self.message_targets
+            + self.mentions
+            + self.work_sessions
+            + self.claims
+            + self.comments
+            + self.comment_revisions
+            + self.history_entries
+            + self.registry_entries
+            + self.system_events
+            + self.artifacts
+            + self.tool_calls
+    }
+}
+
+/// Kind of record an audit event was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSource {
+    /// Task creation, claims and completion
+    Task,
+    /// Messages posted to tasks
+    Message,
+    /// Work sessions started and ended
+    WorkSession,
+    /// Comments added, edited and deleted
+    Comment,
+}
+
+impl AuditSource {
+    /// Every audit source
+    pub const ALL: [AuditSource; 4] = [
+        AuditSource::Task,
+        AuditSource::Message,
+        AuditSource::WorkSession,
+        AuditSource::Comment,
+    ];
+
+    /// Name used in exported entries
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditSource::Task => "task",
+            AuditSource::Message => "message",
+            AuditSource::WorkSession => "work_session",
+            AuditSource::Comment => "comment",
+        }
+    }
+}
+
+/// Which work sessions to list
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkSessionFilter {
+    /// Only sessions on this task
+    pub task_id: Option<i32>,
+    /// Only sessions of this agent
+    pub agent_name: Option<String>,
+    /// Only sessions started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions started before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only sessions not yet ended
+    pub active_only: bool,
+    /// Maximum number of sessions
+    pub limit: Option<u32>,
+}
+
+/// Which system events to list
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemEventFilter {
+    /// Only events of this type
+    pub event_type: Option<String>,
+    /// Only events about this agent, task or other entity
+    pub entity_id: Option<String>,
+    /// Only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum number of events
+    pub limit: Option<u32>,
+}
+
+/// Which audit events to export
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditLogFilter {
+    /// Only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only events before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only events from these sources; empty exports all sources
+    pub sources: Vec<AuditSource>,
+    /// Maximum number of events
+    pub limit: Option<u32>,
+}
+
+/// One audit event, exported as a line of JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the event happened
+    pub timestamp: DateTime<Utc>,
+    /// Kind of record the event was derived from
+    pub source: AuditSource,
+    /// What happened, e.g. `task_claimed` or `comment_edited`
+    pub event: String,
+    /// ID of the task, message, work session or comment
+    pub record_id: i64,
+    /// Code of the task the event belongs to
+    pub task_code: Option<String>,
+    /// Agent that caused the event
+    pub agent_name: Option<String>,
+    /// Event-specific fields
+    pub details: serde_json::Value,
+}
+
+impl AuditEntry {
+    /// Render entries as JSON Lines, one entry per line
+    pub fn to_jsonl(entries: &[AuditEntry]) -> String {
+        entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+/// A schema migration shipped with the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationInfo {
+    /// Migration version, increasing with every schema change
+    pub version: i64,
+    /// Short description taken from the migration file name
+    pub description: String,
+    /// When the migration was applied; `None` while pending
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Whether the applied migration differs from the one shipped with the server
+    pub checksum_mismatch: bool,
+}
+
+/// Schema migration state of a database
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    /// Highest applied migration version; `None` for an empty database
+    pub current_version: Option<i64>,
+    /// Highest migration version shipped with the server
+    pub latest_version: i64,
+    /// All migrations shipped with the server, oldest first
+    pub migrations: Vec<MigrationInfo>,
+}
+
+impl MigrationStatus {
+    /// Migrations not yet applied to the database
+    pub fn pending(&self) -> impl Iterator<Item = &MigrationInfo> {
+        self.migrations.iter().filter(|m| m.applied_at.is_none())
+    }
+
+    /// Whether every migration is applied unchanged
+    pub fn is_up_to_date(&self) -> bool {
+        self.migrations
+            .iter()
+            .all(|m| m.applied_at.is_some() && !m.checksum_mismatch)
+    }
+}
+
+/// Database maintenance operations to run; all are enabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceOptions {
+    /// Check the database for corruption
+    pub integrity_check: bool,
+    /// Move the write-ahead log into the database file and truncate it
+    pub checkpoint: bool,
+    /// Rebuild the database file to reclaim free pages
+    pub vacuum: bool,
+    /// Refresh the query planner statistics
+    pub analyze: bool,
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> Self {
+        Self {
+            integrity_check: true,
+            checkpoint: true,
+            vacuum: true,
+            analyze: true,
+        }
+    }
+}
+
+/// Outcome of a database maintenance run
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    /// Operations that were run, in order
+    pub operations: Vec<String>,
+    /// Size in bytes of the database and its write-ahead log before maintenance
+    pub size_before_bytes: u64,
+    /// Size in bytes of the database and its write-ahead log after maintenance
+    pub size_after_bytes: u64,
+    /// Whether the integrity check passed; `None` if it was not run
+    pub integrity_ok: Option<bool>,
+    /// Problems reported by the integrity check
+    pub integrity_findings: Vec<String>,
+    /// Wall-clock duration of the run in milliseconds
+    pub duration_ms: u64,
+}
+
+impl MaintenanceReport {
+    /// Bytes reclaimed by the run (0 if the database grew)
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.size_before_bytes.saturating_sub(self.size_after_bytes)
+    }
+}
+
+/// Rows returned by a read-only query when none is requested
+pub const DEFAULT_QUERY_ROWS: u32 = 100;
+
+/// Most rows a read-only query may return
+pub const MAX_QUERY_ROWS: u32 = 1_000;
+
+/// Time a read-only query may run when no timeout is requested, in milliseconds
+pub const DEFAULT_QUERY_TIMEOUT_MS: u64 = 5_000;
+
+/// Longest timeout a read-only query may request, in milliseconds
+pub const MAX_QUERY_TIMEOUT_MS: u64 = 30_000;
+
+/// Ad-hoc SQL run against the database without modifying it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadOnlyQuery {
+    /// A single `SELECT` statement, optionally with a `WITH` clause
+    pub sql: String,
+    /// Rows returned before the result is truncated
+    pub max_rows: u32,
+    /// Time the query may run before it is cancelled
+    pub timeout_ms: u64,
+}
+
+impl ReadOnlyQuery {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            max_rows: DEFAULT_QUERY_ROWS,
+            timeout_ms: DEFAULT_QUERY_TIMEOUT_MS,
+        }
+    }
+
+    /// The statement without comments, surrounding whitespace and trailing semicolon
+    ///
+    /// Only a single `SELECT` or `WITH ... SELECT` statement is accepted; the
+    /// repository must still run it on a connection that cannot write.
+    pub fn statement(&self) -> Result<&str> {
+        if self.max_rows == 0 || self.max_rows > MAX_QUERY_ROWS {
+            return Err(TaskError::Validation(format!(
+                "max_rows must be between 1 and {MAX_QUERY_ROWS}"
+            )));
+        }
+        if self.timeout_ms == 0 || self.timeout_ms > MAX_QUERY_TIMEOUT_MS {
+            return Err(TaskError::Validation(format!(
+                "timeout_ms must be between 1 and {MAX_QUERY_TIMEOUT_MS}"
+            )));
+        }
+
+        let mut sql = self.sql.trim();
+        loop {
+            if let Some(rest) = sql.strip_prefix("--") {
+                sql = rest
+                    .split_once('\n')
+                    .map_or("", |(_, rest)| rest)
+                    .trim_start();
+            } else if let Some(rest) = sql.strip_prefix("/*") {
+                sql = rest
+                    .split_once("*/")
+                    .map_or("", |(_, rest)| rest)
+                    .trim_start();
+            } else {
+                break;
+            }
+        }
+        let sql = sql.trim_end().trim_end_matches(';').trim_end();
+        let keyword: String = sql
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect::<String>()
+            .to_ascii_uppercase();
+        if keyword != "SELECT" && keyword != "WITH" {
+            return Err(TaskError::Validation(
+                "Only SELECT statements can be run as read-only queries".to_string(),
+            ));
+        }
+
+        // A semicolon outside quotes and comments would start a second statement
+        let mut quote = None;
+        let mut chars = sql.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"' | '`') => quote = Some(c),
+                (None, '[') => quote = Some(']'),
+                (None, '-') if chars.peek() == Some(&'-') => {
+                    chars.by_ref().find(|&c| c == '\n');
+                }
+                (None, '/') if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for c in chars.by_ref() {
+                        if previous == '*' && c == '/' {
+                            break;
+                        }
+                        previous = c;
+                    }
+                }
+                (None, ';') => {
+                    return Err(TaskError::Validation(
+                        "Read-only queries must be a single statement".to_string(),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(sql)
+    }
+}
+
+/// Result of a read-only query
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    /// Values of each row in column order
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// Whether more rows matched than `max_rows`
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// Lease durations in minutes for claimed tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClaimLeasePolicy {
+    /// Lease of claims that request no duration; 0 leaves them to the cleanup timeout
+    pub default_minutes: u32,
+    /// Longest lease an agent may request
+    pub max_minutes: u32,
+    /// Minutes a task released by cleanup stays reserved for the agent whose claim
+    /// timed out; 0 opens it to every agent at once
+    pub affinity_minutes: u32,
+}
+
+impl ClaimLeasePolicy {
+    /// Lease for a claim requesting `requested_minutes`, capped at `max_minutes`
+    pub fn lease_for(&self, requested_minutes: Option<u32>) -> Option<chrono::Duration> {
+        let minutes = match requested_minutes.filter(|&minutes| minutes > 0) {
+            Some(minutes) => minutes.min(self.max_minutes.max(1)),
+            None => self.default_minutes,
+        };
+        (minutes > 0).then(|| chrono::Duration::minutes(i64::from(minutes)))
+    }
+
+    /// Earliest timeout release whose affinity still holds at `now`
+    pub fn affinity_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.affinity_minutes > 0)
+            .then(|| now - chrono::Duration::minutes(i64::from(self.affinity_minutes)))
+    }
+
+    /// When the affinity of a claim released by cleanup ends
+    pub fn affinity_until(&self, claim: &TaskClaim) -> DateTime<Utc> {
+        claim.released_at.unwrap_or(claim.claimed_at)
+            + chrono::Duration::minutes(i64::from(self.affinity_minutes))
+    }
+}
+
+/// Put tasks reserved for `agent_name` by `affinities` first and drop those reserved for others
+pub fn apply_claim_affinity(
+    tasks: Vec<Task>,
+    affinities: &[TaskClaim],
+    agent_name: &str,
+) -> Vec<Task> {
+    let reserved_for = |task: &Task| {
+        affinities
+            .iter()
+            .find(|claim| claim.task_id == task.id)
+            .map(|claim| claim.agent_name == agent_name)
+    };
+    let (mut reserved, open): (Vec<Task>, Vec<Task>) = tasks
+        .into_iter()
+        .filter(|task| reserved_for(task) != Some(false))
+        .partition(|task| reserved_for(task) == Some(true));
+    reserved.extend(open);
+    reserved
+}
+
+impl Default for ClaimLeasePolicy {
+    fn default() -> Self {
+        Self {
+            default_minutes: 120,
+            max_minutes: 1440,
+            affinity_minutes: 15,
+        }
+    }
+}
+
+/// Limits on work sessions open at the same time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkSessionPolicy {
+    /// Active sessions an agent may hold at once; 0 for no limit
+    pub max_sessions_per_agent: u32,
+    /// Whether a task may have only one active session
+    pub one_session_per_task: bool,
+}
+
+impl WorkSessionPolicy {
+    /// Whether neither limit applies
+    pub fn is_unrestricted(&self) -> bool {
+        self.max_sessions_per_agent == 0 && !self.one_session_per_task
+    }
+
+    /// Check that `agent_name` may start a session on `task_id`
+    ///
+    /// `active` holds the sessions not yet ended of the agent and of the task.
+    pub fn check(&self, task_id: i32, agent_name: &str, active: &[WorkSession]) -> Result<()> {
+        let active: Vec<&WorkSession> = active.iter().filter(|s| s.ended_at.is_none()).collect();
+        if self.one_session_per_task {
+            if let Some(session) = active.iter().find(|s| s.task_id == task_id) {
+                return Err(TaskError::Conflict(format!(
+                    "Task {task_id} already has active work session {} of agent '{}'; \
+                     end it before starting another",
+                    session.id, session.agent_name
+                )));
+            }
+        }
+        let held = active.iter().filter(|s| s.agent_name == agent_name).count();
+        if self.max_sessions_per_agent > 0 && held >= self.max_sessions_per_agent as usize {
+            return Err(TaskError::Conflict(format!(
+                "Agent '{agent_name}' already holds {held} active work session(s), the maximum \
+                 allowed; end one before starting another"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for WorkSessionPolicy {
+    fn default() -> Self {
+        Self {
+            max_sessions_per_agent: 0,
+            one_session_per_task: true,
+        }
+    }
+}
+
+/// Maximum age in days of data that grows without bound; 0 keeps data forever
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Messages (and their mentions) of archived tasks
+    pub archived_task_messages_days: u32,
+    /// Ended work sessions
+    pub ended_work_sessions_days: u32,
+    /// Deleted comments, including their edit history
+    pub deleted_comments_days: u32,
+    /// Recorded tool calls
+    pub tool_usage_days: u32,
+    /// System events such as anomalies and throttled agents
+    pub system_events_days: u32,
+}
+
+/// Rows removed by a retention run, or that a dry run would remove
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// Whether nothing was actually deleted
+    pub dry_run: bool,
+    /// The policy that was applied
+    pub policy: RetentionPolicy,
+    /// Messages of archived tasks
+    pub task_messages: u64,
+    /// Ended work sessions
+    pub work_sessions: u64,
+    /// Deleted comments
+    pub task_comments: u64,
+    /// Recorded tool calls
+    pub tool_calls: u64,
+    /// System events
+    pub system_events: u64,
+}
+
+impl RetentionPolicy {
+    /// Whether any kind of data expires
+    pub fn is_enabled(&self) -> bool {
+        self.archived_task_messages_days > 0
+            || self.ended_work_sessions_days > 0
+            || self.deleted_comments_days > 0
+            || self.tool_usage_days > 0
+            || self.system_events_days > 0
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            archived_task_messages_days: 90,
+            ended_work_sessions_days: 0,
+            deleted_comments_days: 30,
+            tool_usage_days: 30,
+            system_events_days: 90,
+        }
+    }
+}
+
+impl RetentionReport {
+    /// Total rows across all kinds of data
+    pub fn total(&self) -> u64 {
+        self.task_messages
+            + self.work_sessions
+            + self.task_comments
+            + self.tool_calls
+            + self.system_events
+    }
 }
 
 /// Repository statistics for monitoring and analytics
@@ -224,6 +1489,64 @@ pub struct RepositoryStats {
     pub latest_completed: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Attribute tasks are grouped by in an aggregation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskGroupBy {
+    State,
+    /// Owner agent; unowned tasks form a group without a key
+    Owner,
+    /// Required capability, the label tasks carry; a task counts once per capability
+    #[serde(alias = "tag")]
+    Capability,
+}
+
+/// Number of tasks sharing a value of the grouped attribute
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskGroupCount {
+    pub key: Option<String>,
+    pub count: u64,
+}
+
+impl TaskGroupCount {
+    /// Order groups largest first, then by key
+    pub fn sort(groups: &mut [TaskGroupCount]) {
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    }
+}
+
+/// One step of a batch applied atomically by `TaskCrudRepository::apply_operations`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TaskOperation {
+    /// Create a task
+    Create(NewTask),
+    /// Move a task to another state, subject to the usual transition rules
+    SetState { id: i32, state: TaskState },
+    /// Hand a task to another agent
+    Assign { id: i32, new_owner: String },
+    /// Post a message on a task
+    Message {
+        task_code: String,
+        author_agent_name: String,
+        target_agent_name: Option<String>,
+        message_type: String,
+        content: String,
+        reply_to_message_id: Option<i32>,
+        structured_data: Option<serde_json::Value>,
+    },
+}
+
+/// What one applied `TaskOperation` produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOperationOutcome {
+    /// The created or changed task
+    Task(Task),
+    /// The posted message
+    Message(TaskMessage),
+}
+
 /// Repository trait for task message persistence and retrieval
 ///
 /// This trait defines the interface for all task messaging operations.
@@ -239,12 +1562,14 @@ pub trait TaskMessageRepository: Send + Sync {
     /// * `message_type` - The type of message (Comment, Question, Handoff, etc.)
     /// * `content` - The message content
     /// * `reply_to_message_id` - Optional message ID this is replying to
+    /// * `structured_data` - Optional JSON payload, validated by `TaskValidator::validate_message_data`
     ///
     /// # Returns
     /// * `Ok(TaskMessage)` - The created message with assigned ID and timestamp
     /// * `Err(TaskError::NotFound)` - If the task doesn't exist
     /// * `Err(TaskError::Validation)` - If the message data is invalid
     /// * `Err(TaskError::Database)` - If the database operation fails
+    #[allow(clippy::too_many_arguments)]
     async fn create_message(
         &self,
         task_code: &str,
@@ -253,6 +1578,7 @@ pub trait TaskMessageRepository: Send + Sync {
         message_type: &str,
         content: &str,
         reply_to_message_id: Option<i32>,
+        structured_data: Option<&serde_json::Value>,
     ) -> Result<TaskMessage>;
 
     /// Get task messages with optional filtering
@@ -288,6 +1614,181 @@ pub trait TaskMessageRepository: Send + Sync {
     /// * `Ok(Option<TaskMessage>)` - The message if found
     /// * `Err(TaskError::Database)` - If the database operation fails
     async fn get_message_by_id(&self, message_id: i32) -> Result<Option<TaskMessage>>;
+
+    /// Get messages addressed to an agent on any task, newest first
+    ///
+    /// # Arguments
+    /// * `agent_name` - The target agent
+    /// * `since` - Only messages posted after this time
+    /// * `limit` - Optional limit on number of messages returned
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskMessage>)` - Messages whose target is the agent
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_messages_for_agent(
+        &self,
+        _agent_name: &str,
+        _since: Option<DateTime<Utc>>,
+        _limit: Option<u32>,
+    ) -> Result<Vec<TaskMessage>> {
+        Err(TaskError::UnsupportedOperation(
+            "agent inboxes are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Get messages mentioning an agent as `@agent-name`, newest first
+    ///
+    /// Mentions are recorded by `create_message` for every agent named in the
+    /// content except the author.
+    ///
+    /// # Arguments
+    /// * `agent_name` - The mentioned agent
+    /// * `since` - Only mentions in messages posted after this time
+    /// * `limit` - Optional limit on number of mentions returned
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskMention>)` - Matching mentions
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_mentions(
+        &self,
+        _agent_name: &str,
+        _since: Option<DateTime<Utc>>,
+        _limit: Option<u32>,
+    ) -> Result<Vec<TaskMention>> {
+        Err(TaskError::UnsupportedOperation(
+            "mentions are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Get the stored summary of the older messages of a task thread
+    ///
+    /// # Arguments
+    /// * `task_code` - The task whose thread was summarized
+    ///
+    /// # Returns
+    /// * `Ok(Option<ThreadSummary>)` - The summary if the thread has one
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_thread_summary(&self, _task_code: &str) -> Result<Option<ThreadSummary>> {
+        Err(TaskError::UnsupportedOperation(
+            "thread summaries are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Create or replace the summary of a task thread
+    ///
+    /// # Arguments
+    /// * `summary` - Summary keyed by task code
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the summary was stored
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn save_thread_summary(&self, _summary: &ThreadSummary) -> Result<()> {
+        Err(TaskError::UnsupportedOperation(
+            "thread summaries are not supported by this repository".to_string(),
+        ))
+    }
+
+    // Task comments
+    //
+    // Backends without comment storage keep the default implementations,
+    // which report the operation as unsupported.
+
+    /// Add a markdown comment to a task
+    ///
+    /// # Arguments
+    /// * `task_code` - The task code to comment on
+    /// * `author_agent_name` - The agent or human writing the comment
+    /// * `content` - Markdown content, validated by `TaskValidator::validate_comment_content`
+    ///
+    /// # Returns
+    /// * `Ok(TaskComment)` - The created comment at revision 1
+    /// * `Err(TaskError::NotFound)` - If the task doesn't exist
+    /// * `Err(TaskError::Validation)` - If the content is empty or too long
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn create_comment(
+        &self,
+        _task_code: &str,
+        _author_agent_name: &str,
+        _content: &str,
+    ) -> Result<TaskComment> {
+        Err(TaskError::UnsupportedOperation(
+            "task comments are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Replace the content of a comment, keeping the previous content as a revision
+    ///
+    /// # Arguments
+    /// * `comment_id` - The comment to edit
+    /// * `agent_name` - The editing agent, which must be the comment author
+    /// * `content` - New markdown content
+    ///
+    /// # Returns
+    /// * `Ok(TaskComment)` - The comment with incremented revision
+    /// * `Err(TaskError::NotFound)` - If the comment doesn't exist or is deleted
+    /// * `Err(TaskError::Validation)` - If the agent is not the author or the content is invalid
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn update_comment(
+        &self,
+        _comment_id: i32,
+        _agent_name: &str,
+        _content: &str,
+    ) -> Result<TaskComment> {
+        Err(TaskError::UnsupportedOperation(
+            "task comments are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Soft-delete a comment, keeping its content in the revision history
+    ///
+    /// # Arguments
+    /// * `comment_id` - The comment to delete
+    /// * `agent_name` - The deleting agent, which must be the comment author
+    ///
+    /// # Returns
+    /// * `Ok(TaskComment)` - The deleted comment with `deleted_at` set
+    /// * `Err(TaskError::NotFound)` - If the comment doesn't exist or is already deleted
+    /// * `Err(TaskError::Validation)` - If the agent is not the author
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn delete_comment(&self, _comment_id: i32, _agent_name: &str) -> Result<TaskComment> {
+        Err(TaskError::UnsupportedOperation(
+            "task comments are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Get the comments of a task, oldest first
+    ///
+    /// # Arguments
+    /// * `task_code` - The task code to get comments for
+    /// * `include_deleted` - Whether soft-deleted comments are returned
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskComment>)` - The comments (may be empty)
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_comments(
+        &self,
+        _task_code: &str,
+        _include_deleted: bool,
+    ) -> Result<Vec<TaskComment>> {
+        Err(TaskError::UnsupportedOperation(
+            "task comments are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Get the superseded revisions of a comment, oldest first
+    ///
+    /// # Arguments
+    /// * `comment_id` - The comment to get the history for
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TaskCommentRevision>)` - Earlier revisions (empty if never edited)
+    /// * `Err(TaskError::NotFound)` - If the comment doesn't exist
+    /// * `Err(TaskError::Database)` - If the database operation fails
+    async fn get_comment_history(&self, _comment_id: i32) -> Result<Vec<TaskCommentRevision>> {
+        Err(TaskError::UnsupportedOperation(
+            "task comments are not supported by this repository".to_string(),
+        ))
+    }
 }
 
 /// Repository trait for workspace context persistence and retrieval
@@ -357,6 +1858,32 @@ mod tests {
     use super::*;
     use chrono::Utc;
 
+    #[test]
+    fn test_read_only_query_statement() {
+        let query =
+            ReadOnlyQuery::new("-- open work\n  select code from tasks where name = 'a;b';  ");
+        assert_eq!(
+            query.statement().unwrap(),
+            "select code from tasks where name = 'a;b'"
+        );
+        assert!(ReadOnlyQuery::new("WITH t AS (SELECT 1) SELECT * FROM t")
+            .statement()
+            .is_ok());
+
+        for sql in [
+            "DELETE FROM tasks",
+            "PRAGMA query_only = OFF",
+            "SELECT 1; DROP TABLE tasks",
+            "/* SELECT */ UPDATE tasks SET name = 'x'",
+        ] {
+            assert!(ReadOnlyQuery::new(sql).statement().is_err(), "{sql}");
+        }
+
+        let mut query = ReadOnlyQuery::new("SELECT 1");
+        query.max_rows = MAX_QUERY_ROWS + 1;
+        assert!(query.statement().is_err());
+    }
+
     #[tokio::test]
     async fn test_repository_stats_default() {
         let stats = RepositoryStats::default();
@@ -389,4 +1916,113 @@ mod tests {
         assert!(stats.latest_created.is_some());
         assert!(stats.latest_completed.is_some());
     }
+
+    #[test]
+    fn test_claim_lease_policy() {
+        let policy = ClaimLeasePolicy::default();
+        assert_eq!(policy.lease_for(None), Some(chrono::Duration::minutes(120)));
+        assert_eq!(
+            policy.lease_for(Some(0)),
+            Some(chrono::Duration::minutes(120))
+        );
+        assert_eq!(
+            policy.lease_for(Some(30)),
+            Some(chrono::Duration::minutes(30))
+        );
+        assert_eq!(
+            policy.lease_for(Some(10_000)),
+            Some(chrono::Duration::minutes(1440))
+        );
+
+        let no_default = ClaimLeasePolicy {
+            default_minutes: 0,
+            ..policy
+        };
+        assert_eq!(no_default.lease_for(None), None);
+        assert_eq!(
+            no_default.lease_for(Some(15)),
+            Some(chrono::Duration::minutes(15))
+        );
+
+        let now = Utc::now();
+        assert_eq!(
+            policy.affinity_cutoff(now),
+            Some(now - chrono::Duration::minutes(15))
+        );
+        let no_affinity = ClaimLeasePolicy {
+            affinity_minutes: 0,
+            ..policy
+        };
+        assert_eq!(no_affinity.affinity_cutoff(now), None);
+
+        let claim = |task_id: i32, agent_name: &str| TaskClaim {
+            task_id,
+            agent_name: agent_name.to_string(),
+            claimed_at: now - chrono::Duration::hours(3),
+            released_at: Some(now),
+            release_reason: Some("timed_out".to_string()),
+        };
+        assert_eq!(
+            policy.affinity_until(&claim(1, "backend-dev")),
+            now + chrono::Duration::minutes(15)
+        );
+        let tasks: Vec<Task> = (1..=4)
+            .map(|id| {
+                Task::new(
+                    id,
+                    format!("AFF-{id}"),
+                    "Task".to_string(),
+                    "Description".to_string(),
+                    None,
+                    TaskState::Created,
+                    now,
+                    None,
+                )
+            })
+            .collect();
+        let affinities = [claim(2, "qa-bot"), claim(3, "backend-dev")];
+        let ids: Vec<i32> = apply_claim_affinity(tasks, &affinities, "backend-dev")
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, [3, 1, 4]);
+    }
+
+    #[test]
+    fn test_work_session_policy() {
+        let session = |id: i32, task_id: i32, agent_name: &str| WorkSession {
+            id,
+            task_id,
+            agent_name: agent_name.to_string(),
+            started_at: Utc::now(),
+            ended_at: None,
+            notes: None,
+            productivity_score: None,
+            interruptions: Vec::new(),
+        };
+        let active = vec![session(1, 10, "backend-dev"), session(2, 11, "backend-dev")];
+
+        let policy = WorkSessionPolicy::default();
+        assert!(policy.check(12, "backend-dev", &active).is_ok());
+        let err = policy.check(10, "backend-dev", &active).unwrap_err();
+        assert!(matches!(err, TaskError::Conflict(ref m) if m.contains("work session 1")));
+
+        let capped = WorkSessionPolicy {
+            max_sessions_per_agent: 2,
+            ..policy
+        };
+        assert!(capped.check(12, "backend-dev", &active).is_err());
+        assert!(capped.check(12, "qa-dev", &active).is_ok());
+
+        let ended = vec![WorkSession {
+            ended_at: Some(Utc::now()),
+            ..session(1, 10, "backend-dev")
+        }];
+        assert!(capped.check(10, "backend-dev", &ended).is_ok());
+        assert!(WorkSessionPolicy {
+            max_sessions_per_agent: 0,
+            one_session_per_task: false,
+        }
+        .is_unrestricted());
+    }
 }
@@ -0,0 +1,729 @@
+//! Composable decorators around a [`TaskRepository`]
+//!
+//! Cross-cutting concerns of repository access live here instead of in every
+//! handler: [`LoggingRepository`] traces each call, [`MetricsRepository`]
+//! counts calls, errors and time per method, and [`RetryingRepository`] retries
+//! calls failing with a database error. Every decorator is itself a
+//! [`TaskRepository`], so they stack in any order:
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use task_core::{LoggingRepository, RetryPolicy, RetryingRepository, TaskRepository};
+//!
+//! fn decorate<R: TaskRepository + 'static>(repository: Arc<R>) -> Arc<dyn TaskRepository> {
+//!     let retrying = RetryingRepository::new(repository, RetryPolicy::default());
+//!     Arc::new(LoggingRepository::new(Arc::new(retrying)))
+//! }
+//! ```
+
+use crate::{
+    artifacts::{ArtifactFilter, TaskArtifact},
+    embeddings::{EmbeddingDocument, SemanticMatch, SemanticSearchFilter},
+    error::{Result, TaskError},
+    models::{
+        Capability, ExternalLink, NewTask, SystemEvent, Task, TaskClaim, TaskFilter, TaskState,
+        UpdateTask, WorkSession,
+    },
+    repository::{
+        AgentPurgeReport, AuditEntry, AuditLogFilter, MaintenanceOptions, MaintenanceReport,
+        MigrationStatus, PurgeMode, QueryResult, ReadOnlyQuery, RepositoryStats, RetentionPolicy,
+        RetentionReport, SystemEventFilter, TaskCrudRepository, TaskExtensionRepository,
+        TaskGroupBy, TaskGroupCount, TaskOperation, TaskOperationOutcome, TaskRepository,
+        WorkClaimRepository, WorkSessionFilter, WorkSessionRepository,
+    },
+    session_log::{SessionLogChunk, SessionLogEntry},
+    task_history::TaskBoard,
+    tool_usage::{ToolCall, ToolUsageFilter, ToolUsageStat},
+    workspace_merge::WorkspaceMergeReport,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Implement all repository traits for a decorator by routing every call
+/// through its `around(method, call)` method
+macro_rules! decorate_repository {
+    ($decorator:ident) => {
+        #[async_trait]
+        impl<R: TaskRepository + ?Sized> TaskCrudRepository for $decorator<R> {
+            async fn create(&self, task: NewTask) -> Result<Task> {
+                self.around("create", || self.inner.create(task.clone()))
+                    .await
+            }
+
+            async fn update(&self, id: i32, updates: UpdateTask) -> Result<Task> {
+                self.around("update", || self.inner.update(id, updates.clone()))
+                    .await
+            }
+
+            async fn set_state(&self, id: i32, state: TaskState) -> Result<Task> {
+                self.around("set_state", || self.inner.set_state(id, state))
+                    .await
+            }
+
+            async fn get_by_id(&self, id: i32) -> Result<Option<Task>> {
+                self.around("get_by_id", || self.inner.get_by_id(id)).await
+            }
+
+            async fn get_by_code(&self, code: &str) -> Result<Option<Task>> {
+                self.around("get_by_code", || self.inner.get_by_code(code))
+                    .await
+            }
+
+            async fn get_by_ids(&self, ids: &[i32]) -> Result<Vec<Task>> {
+                self.around("get_by_ids", || self.inner.get_by_ids(ids))
+                    .await
+            }
+
+            async fn get_by_codes(&self, codes: &[String]) -> Result<Vec<Task>> {
+                self.around("get_by_codes", || self.inner.get_by_codes(codes))
+                    .await
+            }
+
+            async fn get_by_uid(&self, uid: &str) -> Result<Option<Task>> {
+                self.around("get_by_uid", || self.inner.get_by_uid(uid))
+                    .await
+            }
+
+            async fn list(&self, filter: TaskFilter) -> Result<Vec<Task>> {
+                self.around("list", || self.inner.list(filter.clone()))
+                    .await
+            }
+
+            async fn assign(&self, id: i32, new_owner: &str) -> Result<Task> {
+                self.around("assign", || self.inner.assign(id, new_owner))
+                    .await
+            }
+
+            async fn archive(&self, id: i32) -> Result<Task> {
+                self.around("archive", || self.inner.archive(id)).await
+            }
+
+            async fn health_check(&self) -> Result<()> {
+                self.around("health_check", || self.inner.health_check())
+                    .await
+            }
+
+            async fn get_stats(&self) -> Result<RepositoryStats> {
+                self.around("get_stats", || self.inner.get_stats()).await
+            }
+
+            async fn count_tasks(&self, filter: &TaskFilter) -> Result<u64> {
+                self.around("count_tasks", || self.inner.count_tasks(filter))
+                    .await
+            }
+
+            async fn aggregate_tasks(
+                &self,
+                filter: &TaskFilter,
+                group_by: TaskGroupBy,
+            ) -> Result<Vec<TaskGroupCount>> {
+                self.around("aggregate_tasks", || {
+                    self.inner.aggregate_tasks(filter, group_by)
+                })
+                .await
+            }
+
+            async fn apply_operations(
+                &self,
+                operations: &[TaskOperation],
+            ) -> Result<Vec<TaskOperationOutcome>> {
+                self.around("apply_operations", || {
+                    self.inner.apply_operations(operations)
+                })
+                .await
+            }
+
+            async fn create_pipeline(
+                &self,
+                pipeline: &NewTask,
+                stages: &[NewTask],
+            ) -> Result<Vec<Task>> {
+                self.around("create_pipeline", || {
+                    self.inner.create_pipeline(pipeline, stages)
+                })
+                .await
+            }
+        }
+
+        #[async_trait]
+        impl<R: TaskRepository + ?Sized> WorkClaimRepository for $decorator<R> {
+            async fn discover_work(
+                &self,
+                agent_name: &str,
+                capabilities: &[String],
+                max_tasks: u32,
+            ) -> Result<Vec<Task>> {
+                self.around("discover_work", || {
+                    self.inner
+                        .discover_work(agent_name, capabilities, max_tasks)
+                })
+                .await
+            }
+
+            async fn claim_task(&self, task_id: i32, agent_name: &str) -> Result<Task> {
+                self.around("claim_task", || self.inner.claim_task(task_id, agent_name))
+                    .await
+            }
+
+            async fn claim_task_with_lease(
+                &self,
+                task_id: i32,
+                agent_name: &str,
+                lease: Option<chrono::Duration>,
+            ) -> Result<Task> {
+                self.around("claim_task_with_lease", || {
+                    self.inner.claim_task_with_lease(task_id, agent_name, lease)
+                })
+                .await
+            }
+
+            async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task> {
+                self.around("release_task", || {
+                    self.inner.release_task(task_id, agent_name)
+                })
+                .await
+            }
+
+            async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>> {
+                self.around("cleanup_timed_out_tasks", || {
+                    self.inner.cleanup_timed_out_tasks(timeout_minutes)
+                })
+                .await
+            }
+
+            async fn task_claims(&self, task_id: i32) -> Result<Vec<TaskClaim>> {
+                self.around("task_claims", || self.inner.task_claims(task_id))
+                    .await
+            }
+
+            async fn timed_out_claims(&self, since: DateTime<Utc>) -> Result<Vec<TaskClaim>> {
+                self.around("timed_out_claims", || self.inner.timed_out_claims(since))
+                    .await
+            }
+        }
+
+        #[async_trait]
+        impl<R: TaskRepository + ?Sized> WorkSessionRepository for $decorator<R> {
+            async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32> {
+                self.around("start_work_session", || {
+                    self.inner.start_work_session(task_id, agent_name)
+                })
+                .await
+            }
+
+            async fn end_work_session(
+                &self,
+                session_id: i32,
+                notes: Option<String>,
+                productivity_score: Option<f64>,
+            ) -> Result<()> {
+                self.around("end_work_session", || {
+                    self.inner
+                        .end_work_session(session_id, notes.clone(), productivity_score)
+                })
+                .await
+            }
+
+            async fn get_work_session(&self, session_id: i32) -> Result<Option<WorkSession>> {
+                self.around("get_work_session", || {
+                    self.inner.get_work_session(session_id)
+                })
+                .await
+            }
+
+            async fn list_work_sessions(
+                &self,
+                filter: &WorkSessionFilter,
+            ) -> Result<Vec<WorkSession>> {
+                self.around("list_work_sessions", || {
+                    self.inner.list_work_sessions(filter)
+                })
+                .await
+            }
+
+            async fn append_session_log(
+                &self,
+                session_id: i32,
+                chunk: &SessionLogChunk,
+            ) -> Result<SessionLogEntry> {
+                self.around("append_session_log", || {
+                    self.inner.append_session_log(session_id, chunk)
+                })
+                .await
+            }
+
+            async fn get_session_log(&self, session_id: i32) -> Result<Vec<SessionLogEntry>> {
+                self.around("get_session_log", || self.inner.get_session_log(session_id))
+                    .await
+            }
+        }
+
+        #[async_trait]
+        impl<R: TaskRepository + ?Sized> TaskExtensionRepository for $decorator<R> {
+            async fn apply_retention(
+                &self,
+                policy: &RetentionPolicy,
+                dry_run: bool,
+            ) -> Result<RetentionReport> {
+                self.around("apply_retention", || {
+                    self.inner.apply_retention(policy, dry_run)
+                })
+                .await
+            }
+
+            async fn maintain_database(
+                &self,
+                options: &MaintenanceOptions,
+            ) -> Result<MaintenanceReport> {
+                self.around("maintain_database", || {
+                    self.inner.maintain_database(options)
+                })
+                .await
+            }
+
+            async fn migration_status(&self) -> Result<MigrationStatus> {
+                self.around("migration_status", || self.inner.migration_status())
+                    .await
+            }
+
+            async fn audit_log(&self, filter: &AuditLogFilter) -> Result<Vec<AuditEntry>> {
+                self.around("audit_log", || self.inner.audit_log(filter))
+                    .await
+            }
+
+            async fn run_readonly_query(&self, query: &ReadOnlyQuery) -> Result<QueryResult> {
+                self.around("run_readonly_query", || {
+                    self.inner.run_readonly_query(query)
+                })
+                .await
+            }
+
+            async fn purge_agent_data(
+                &self,
+                agent_name: &str,
+                mode: PurgeMode,
+            ) -> Result<AgentPurgeReport> {
+                self.around("purge_agent_data", || {
+                    self.inner.purge_agent_data(agent_name, mode)
+                })
+                .await
+            }
+
+            async fn list_capabilities(&self) -> Result<Vec<Capability>> {
+                self.around("list_capabilities", || self.inner.list_capabilities())
+                    .await
+            }
+
+            async fn define_capability(
+                &self,
+                name: &str,
+                description: &str,
+                aliases: &[String],
+            ) -> Result<Capability> {
+                self.around("define_capability", || {
+                    self.inner.define_capability(name, description, aliases)
+                })
+                .await
+            }
+
+            async fn deprecate_capability(
+                &self,
+                name: &str,
+                replaced_by: Option<&str>,
+            ) -> Result<Capability> {
+                self.around("deprecate_capability", || {
+                    self.inner.deprecate_capability(name, replaced_by)
+                })
+                .await
+            }
+
+            async fn list_external_links(&self, system: &str) -> Result<Vec<ExternalLink>> {
+                self.around("list_external_links", || {
+                    self.inner.list_external_links(system)
+                })
+                .await
+            }
+
+            async fn list_task_external_links(&self, task_code: &str) -> Result<Vec<ExternalLink>> {
+                self.around("list_task_external_links", || {
+                    self.inner.list_task_external_links(task_code)
+                })
+                .await
+            }
+
+            async fn save_external_link(&self, link: &ExternalLink) -> Result<()> {
+                self.around("save_external_link", || self.inner.save_external_link(link))
+                    .await
+            }
+
+            async fn save_artifact(&self, artifact: &TaskArtifact) -> Result<TaskArtifact> {
+                self.around("save_artifact", || self.inner.save_artifact(artifact))
+                    .await
+            }
+
+            async fn list_artifacts(&self, filter: &ArtifactFilter) -> Result<Vec<TaskArtifact>> {
+                self.around("list_artifacts", || self.inner.list_artifacts(filter))
+                    .await
+            }
+
+            async fn record_system_event(&self, event: &SystemEvent) -> Result<i32> {
+                self.around("record_system_event", || {
+                    self.inner.record_system_event(event)
+                })
+                .await
+            }
+
+            async fn list_system_events(
+                &self,
+                filter: &SystemEventFilter,
+            ) -> Result<Vec<SystemEvent>> {
+                self.around("list_system_events", || {
+                    self.inner.list_system_events(filter)
+                })
+                .await
+            }
+
+            async fn record_tool_call(&self, call: &ToolCall) -> Result<()> {
+                self.around("record_tool_call", || self.inner.record_tool_call(call))
+                    .await
+            }
+
+            async fn tool_usage_stats(
+                &self,
+                filter: &ToolUsageFilter,
+            ) -> Result<Vec<ToolUsageStat>> {
+                self.around("tool_usage_stats", || self.inner.tool_usage_stats(filter))
+                    .await
+            }
+
+            async fn board_at(&self, at: DateTime<Utc>) -> Result<TaskBoard> {
+                self.around("board_at", || self.inner.board_at(at)).await
+            }
+
+            async fn merge_database(
+                &self,
+                import_path: &Path,
+                dry_run: bool,
+            ) -> Result<WorkspaceMergeReport> {
+                self.around("merge_database", || {
+                    self.inner.merge_database(import_path, dry_run)
+                })
+                .await
+            }
+
+            async fn stale_embedding_documents(
+                &self,
+                model: &str,
+                limit: u32,
+            ) -> Result<Vec<EmbeddingDocument>> {
+                self.around("stale_embedding_documents", || {
+                    self.inner.stale_embedding_documents(model, limit)
+                })
+                .await
+            }
+
+            async fn save_embeddings(
+                &self,
+                model: &str,
+                embeddings: &[(EmbeddingDocument, Vec<f32>)],
+            ) -> Result<()> {
+                self.around("save_embeddings", || {
+                    self.inner.save_embeddings(model, embeddings)
+                })
+                .await
+            }
+
+            async fn prune_embeddings(&self, model: &str) -> Result<u64> {
+                self.around("prune_embeddings", || self.inner.prune_embeddings(model))
+                    .await
+            }
+
+            async fn semantic_search(
+                &self,
+                model: &str,
+                query: &[f32],
+                filter: &SemanticSearchFilter,
+            ) -> Result<Vec<SemanticMatch>> {
+                self.around("semantic_search", || {
+                    self.inner.semantic_search(model, query, filter)
+                })
+                .await
+            }
+        }
+    };
+}
+
+/// Repository decorator tracing every call with its duration
+///
+/// Successful calls are logged at debug level, failures as warnings, except
+/// for unsupported operations, which callers routinely probe for.
+pub struct LoggingRepository<R: ?Sized> {
+    inner: Arc<R>,
+}
+
+impl<R: ?Sized> LoggingRepository<R> {
+    /// Wrap a repository
+    pub fn new(inner: Arc<R>) -> Self {
+        Self { inner }
+    }
+
+    async fn around<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let started = Instant::now();
+        let result = call().await;
+        match result {
+            Err(ref e) if !matches!(e, TaskError::UnsupportedOperation(_)) => {
+                warn!(
+                    "Repository call {} failed after {:?}: {}",
+                    method,
+                    started.elapsed(),
+                    e
+                );
+            }
+            _ => debug!("Repository call {} took {:?}", method, started.elapsed()),
+        }
+        result
+    }
+}
+
+decorate_repository!(LoggingRepository);
+
+/// Call statistics of one repository method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    /// Calls made, including failed ones
+    pub calls: u64,
+    /// Calls that returned an error
+    pub errors: u64,
+    /// Time spent in all calls
+    pub total_time: Duration,
+}
+
+impl MethodMetrics {
+    /// Average time per call
+    pub fn average_time(&self) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        self.total_time / self.calls as u32
+    }
+}
+
+/// Shared per-method call statistics recorded by [`MetricsRepository`]
+#[derive(Debug, Clone, Default)]
+pub struct RepositoryMetrics {
+    methods: Arc<Mutex<BTreeMap<&'static str, MethodMetrics>>>,
+}
+
+impl RepositoryMetrics {
+    /// Create empty metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one call of a method
+    pub fn record(&self, method: &'static str, elapsed: Duration, failed: bool) {
+        let mut methods = self.methods.lock().unwrap();
+        let metrics = methods.entry(method).or_default();
+        metrics.calls += 1;
+        metrics.total_time += elapsed;
+        if failed {
+            metrics.errors += 1;
+        }
+    }
+
+    /// Statistics of every method called so far, by method name
+    pub fn snapshot(&self) -> BTreeMap<String, MethodMetrics> {
+        self.methods
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, metrics)| (method.to_string(), *metrics))
+            .collect()
+    }
+
+    /// Statistics summed over all methods
+    pub fn totals(&self) -> MethodMetrics {
+        self.methods
+            .lock()
+            .unwrap()
+            .values()
+            .fold(MethodMetrics::default(), |total, metrics| MethodMetrics {
+                calls: total.calls + metrics.calls,
+                errors: total.errors + metrics.errors,
+                total_time: total.total_time + metrics.total_time,
+            })
+    }
+}
+
+/// Repository decorator counting calls, errors and time per method
+pub struct MetricsRepository<R: ?Sized> {
+    inner: Arc<R>,
+    metrics: RepositoryMetrics,
+}
+
+impl<R: ?Sized> MetricsRepository<R> {
+    /// Wrap a repository, recording into fresh metrics
+    pub fn new(inner: Arc<R>) -> Self {
+        Self::with_metrics(inner, RepositoryMetrics::new())
+    }
+
+    /// Wrap a repository, recording into existing metrics
+    pub fn with_metrics(inner: Arc<R>, metrics: RepositoryMetrics) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Metrics recorded by this decorator
+    pub fn metrics(&self) -> RepositoryMetrics {
+        self.metrics.clone()
+    }
+
+    async fn around<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let started = Instant::now();
+        let result = call().await;
+        self.metrics
+            .record(method, started.elapsed(), result.is_err());
+        result
+    }
+}
+
+decorate_repository!(MetricsRepository);
+
+/// How [`RetryingRepository`] retries failed calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per call including the first one (1 disables retries)
+    pub max_attempts: u32,
+    /// Milliseconds before the first retry, doubled after each further failure
+    pub backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_ms: 50,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether failed calls are retried at all
+    pub fn is_enabled(&self) -> bool {
+        self.max_attempts > 1
+    }
+
+    /// Delay before the given retry (1 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64 << retry.saturating_sub(1).min(16);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
+
+    /// Whether a call failing with this error may succeed when repeated
+    ///
+    /// Only database errors such as a locked SQLite file are transient; domain
+    /// errors like a missing task or a conflicting claim fail the same way again.
+    pub fn is_retryable(error: &TaskError) -> bool {
+        error.is_database()
+    }
+}
+
+/// Repository decorator retrying calls that fail with a database error
+///
+/// Every method is retried, including writes: a failed SQLite statement is
+/// rolled back, so repeating it does not apply a change twice.
+pub struct RetryingRepository<R: ?Sized> {
+    inner: Arc<R>,
+    policy: RetryPolicy,
+}
+
+impl<R: ?Sized> RetryingRepository<R> {
+    /// Wrap a repository
+    pub fn new(inner: Arc<R>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    async fn around<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T>
+    where
+        F: Fn() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Err(e) if attempt < self.policy.max_attempts && RetryPolicy::is_retryable(&e) => {
+                    let delay = self.policy.backoff(attempt);
+                    debug!(
+                        "Retrying repository call {} in {:?} after attempt {} failed: {}",
+                        method, delay, attempt, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+decorate_repository!(RetryingRepository);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            backoff_ms: 10,
+        };
+        assert!(policy.is_enabled());
+        assert!(!RetryPolicy::default().is_enabled());
+        assert_eq!(policy.backoff(1), Duration::from_millis(10));
+        assert_eq!(policy.backoff(2), Duration::from_millis(20));
+        assert_eq!(policy.backoff(3), Duration::from_millis(40));
+
+        assert!(RetryPolicy::is_retryable(&TaskError::Database(
+            "locked".to_string()
+        )));
+        assert!(!RetryPolicy::is_retryable(&TaskError::NotFound(
+            "1".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_repository_metrics() {
+        let metrics = RepositoryMetrics::new();
+        metrics.record("list", Duration::from_millis(4), false);
+        metrics.record("list", Duration::from_millis(2), true);
+        metrics.record("create", Duration::from_millis(6), false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["list"].calls, 2);
+        assert_eq!(snapshot["list"].errors, 1);
+        assert_eq!(snapshot["list"].average_time(), Duration::from_millis(3));
+
+        let totals = metrics.totals();
+        assert_eq!(totals.calls, 3);
+        assert_eq!(totals.errors, 1);
+        assert_eq!(totals.total_time, Duration::from_millis(12));
+    }
+}
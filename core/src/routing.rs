@@ -0,0 +1,359 @@
+//! Declarative task routing
+//!
+//! Routing rules in `.axon/routing.yaml` say who should pick up a task, for
+//! example "tasks requiring `frontend` go to an agent with `ui-design`". Tasks
+//! carry no separate tags, so their required capabilities serve as tags. Rules
+//! are evaluated in order when a task is created or released; the first rule
+//! that matches the task and names an available agent decides. Depending on
+//! its action the agent is assigned the task or only suggested for it.
+//!
+//! ```yaml
+//! rules:
+//!   - name: frontend
+//!     when:
+//!       capabilities: [frontend]
+//!     route_to:
+//!       capability: ui-design
+//!     action: assign
+//! ```
+
+use crate::capabilities::CapabilityCatalog;
+use crate::error::{Result, TaskError};
+use crate::models::Task;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Location of the routing rules inside the project root
+pub const ROUTING_RULES_FILE: &str = ".axon/routing.yaml";
+
+/// Author of the messages suggesting owners
+pub const ROUTING_AUTHOR: &str = "routing";
+
+/// Message type of owner suggestions
+pub const ROUTING_SUGGESTION_MESSAGE_TYPE: &str = "routing_suggestion";
+
+/// What a matching rule does with the agent it picks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteAction {
+    /// The agent becomes the owner of the task
+    Assign,
+    /// The agent is told about the task and may claim it
+    #[default]
+    Suggest,
+}
+
+/// Tasks a rule applies to; every condition given must hold
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouteCondition {
+    /// Capabilities the task must all require
+    pub capabilities: Vec<String>,
+    /// Words of which one must appear in the task name or description
+    pub keywords: Vec<String>,
+    /// Prefix of the task code
+    pub code_prefix: Option<String>,
+}
+
+/// Agent a rule routes to: a registered agent by name, or the least busy
+/// agent with a capability
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouteTarget {
+    pub agent: Option<String>,
+    pub capability: Option<String>,
+}
+
+/// One routing rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRule {
+    pub name: String,
+    #[serde(default)]
+    pub when: RouteCondition,
+    pub route_to: RouteTarget,
+    #[serde(default)]
+    pub action: RouteAction,
+}
+
+/// Registered agent a rule may route to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingAgent {
+    pub name: String,
+    pub capabilities: Vec<String>,
+    /// Tasks the agent has in progress
+    pub active_tasks: usize,
+}
+
+/// Why a rule did or did not decide the routing of a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleEvaluation {
+    pub rule: String,
+    pub matched: bool,
+    pub reason: String,
+}
+
+/// Outcome of routing one task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingDecision {
+    pub task_id: i32,
+    pub task_code: String,
+    /// Rule that decided, if any
+    pub matched_rule: Option<String>,
+    pub action: Option<RouteAction>,
+    /// Agent the task is routed to
+    pub owner: Option<String>,
+    /// Rules evaluated, in order, up to the one that decided
+    pub evaluations: Vec<RuleEvaluation>,
+    /// Whether the decision was carried out; false when only explained
+    pub applied: bool,
+}
+
+/// Routing rules of a project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingRules {
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+}
+
+impl RoutingRule {
+    fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| {
+            TaskError::Validation(format!(
+                "{ROUTING_RULES_FILE}: rule '{}' {reason}",
+                self.name
+            ))
+        };
+        if self.name.trim().is_empty() {
+            return Err(TaskError::Validation(format!(
+                "{ROUTING_RULES_FILE}: every rule needs a name"
+            )));
+        }
+        match (&self.route_to.agent, &self.route_to.capability) {
+            (Some(_), Some(_)) => Err(invalid("must route to an agent or a capability, not both")),
+            (None, None) => Err(invalid("must route to an agent or a capability")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Why `task` does not meet the conditions, or `None` when it does
+    fn mismatch(
+        &self,
+        task: &Task,
+        task_capabilities: &[String],
+        catalog: &CapabilityCatalog,
+    ) -> Option<String> {
+        if let Some(prefix) = &self.when.code_prefix {
+            if !task.code.starts_with(prefix.as_str()) {
+                return Some(format!("code does not start with '{prefix}'"));
+            }
+        }
+        let missing: Vec<String> = catalog
+            .normalize_all(&self.when.capabilities)
+            .into_iter()
+            .filter(|capability| !task_capabilities.contains(capability))
+            .collect();
+        if !missing.is_empty() {
+            return Some(format!("task does not require {}", missing.join(", ")));
+        }
+        if !self.when.keywords.is_empty() {
+            let text = format!("{} {}", task.name, task.description).to_lowercase();
+            if !self
+                .when
+                .keywords
+                .iter()
+                .any(|keyword| text.contains(&keyword.to_lowercase()))
+            {
+                return Some(format!(
+                    "name and description mention none of {}",
+                    self.when.keywords.join(", ")
+                ));
+            }
+        }
+        None
+    }
+
+    /// Agent the rule routes to among `agents`, or why there is none
+    fn pick<'a>(
+        &self,
+        agents: &'a [RoutingAgent],
+        catalog: &CapabilityCatalog,
+    ) -> std::result::Result<&'a RoutingAgent, String> {
+        if let Some(name) = &self.route_to.agent {
+            return agents
+                .iter()
+                .find(|agent| &agent.name == name)
+                .ok_or_else(|| format!("agent '{name}' is not available"));
+        }
+        let capability = catalog.normalize(self.route_to.capability.as_deref().unwrap_or_default());
+        // Least busy first; ties keep the registration order
+        agents
+            .iter()
+            .filter(|agent| {
+                catalog
+                    .normalize_all(&agent.capabilities)
+                    .contains(&capability)
+            })
+            .min_by_key(|agent| agent.active_tasks)
+            .ok_or_else(|| format!("no available agent has capability '{capability}'"))
+    }
+}
+
+impl RoutingRules {
+    /// Parse and validate rules from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let rules: Self = serde_yaml::from_str(yaml)
+            .map_err(|e| TaskError::Validation(format!("{ROUTING_RULES_FILE}: {e}")))?;
+        for rule in &rules.rules {
+            rule.validate()?;
+        }
+        Ok(rules)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Route `task` to one of `agents` without carrying the decision out
+    ///
+    /// Task capabilities are expected normalized by `catalog`; rule and agent
+    /// capabilities are normalized here.
+    pub fn explain(
+        &self,
+        task: &Task,
+        task_capabilities: &[String],
+        agents: &[RoutingAgent],
+        catalog: &CapabilityCatalog,
+    ) -> RoutingDecision {
+        let mut decision = RoutingDecision {
+            task_id: task.id,
+            task_code: task.code.clone(),
+            matched_rule: None,
+            action: None,
+            owner: None,
+            evaluations: Vec::new(),
+            applied: false,
+        };
+        for rule in &self.rules {
+            if let Some(reason) = rule.mismatch(task, task_capabilities, catalog) {
+                decision.evaluations.push(RuleEvaluation {
+                    rule: rule.name.clone(),
+                    matched: false,
+                    reason,
+                });
+                continue;
+            }
+            match rule.pick(agents, catalog) {
+                Ok(agent) => {
+                    decision.evaluations.push(RuleEvaluation {
+                        rule: rule.name.clone(),
+                        matched: true,
+                        reason: format!("routes to {}", agent.name),
+                    });
+                    decision.matched_rule = Some(rule.name.clone());
+                    decision.action = Some(rule.action);
+                    decision.owner = Some(agent.name.clone());
+                    break;
+                }
+                Err(reason) => decision.evaluations.push(RuleEvaluation {
+                    rule: rule.name.clone(),
+                    matched: false,
+                    reason,
+                }),
+            }
+        }
+        decision
+    }
+}
+
+/// Routing rules of the project at `project_root`; none when it has no routing file
+pub fn load_routing_rules(project_root: &Path) -> Result<RoutingRules> {
+    let path = project_root.join(ROUTING_RULES_FILE);
+    if !path.exists() {
+        return Ok(RoutingRules::default());
+    }
+    let yaml = std::fs::read_to_string(&path)
+        .map_err(|e| TaskError::Validation(format!("{}: {}", path.display(), e)))?;
+    RoutingRules::from_yaml(&yaml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskState;
+    use crate::test_support;
+
+    const RULES: &str = r#"
+rules:
+  - name: frontend
+    when:
+      capabilities: [Frontend]
+    route_to:
+      capability: ui_design
+    action: assign
+  - name: docs
+    when:
+      keywords: [readme]
+    route_to:
+      agent: tech-writer
+"#;
+
+    fn task(code: &str, name: &str, capabilities: &[&str]) -> Task {
+        Task {
+            name: name.to_string(),
+            required_capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ..test_support::task(7, code, TaskState::Created)
+        }
+    }
+
+    fn agent(name: &str, capabilities: &[&str], active_tasks: usize) -> RoutingAgent {
+        RoutingAgent {
+            name: name.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            active_tasks,
+        }
+    }
+
+    #[test]
+    fn test_explain_routing() {
+        let rules = RoutingRules::from_yaml(RULES).unwrap();
+        let catalog = CapabilityCatalog::default();
+        let agents = [
+            agent("designer-1", &["UI Design"], 2),
+            agent("designer-2", &["ui-design"], 0),
+            agent("tech-writer", &["docs"], 5),
+        ];
+
+        let frontend = task("FE-1", "Login page", &["frontend"]);
+        let decision = rules.explain(&frontend, &["frontend".to_string()], &agents, &catalog);
+        assert_eq!(decision.matched_rule.as_deref(), Some("frontend"));
+        assert_eq!(decision.owner.as_deref(), Some("designer-2"));
+        assert_eq!(decision.action, Some(RouteAction::Assign));
+        assert!(!decision.applied);
+
+        let docs = task("DOC-1", "Update README", &[]);
+        let decision = rules.explain(&docs, &[], &agents, &catalog);
+        assert_eq!(decision.owner.as_deref(), Some("tech-writer"));
+        assert_eq!(decision.action, Some(RouteAction::Suggest));
+        assert!(!decision.evaluations[0].matched);
+        assert_eq!(
+            decision.evaluations[0].reason,
+            "task does not require frontend"
+        );
+
+        let decision = rules.explain(&frontend, &["frontend".to_string()], &agents[2..], &catalog);
+        assert_eq!(decision.matched_rule, None);
+        assert!(decision.evaluations[0].reason.contains("ui-design"));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let both = "rules:\n  - name: x\n    route_to: { agent: a, capability: b }\n";
+        assert!(RoutingRules::from_yaml(both).is_err());
+        let neither = "rules:\n  - name: x\n    route_to: {}\n";
+        assert!(RoutingRules::from_yaml(neither).is_err());
+        let typo = "rules:\n  - name: x\n    whn: {}\n    route_to: { agent: a }\n";
+        assert!(RoutingRules::from_yaml(typo).is_err());
+    }
+}
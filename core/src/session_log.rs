@@ -0,0 +1,114 @@
+//! Work session transcripts
+//!
+//! While a work session is active its agent can append chunks of its
+//! transcript: free text plus the tool calls it made, the decisions it took
+//! and the files it touched. Repositories store the chunks compressed, and
+//! reviewers read the whole log back for post-mortems once work goes wrong.
+
+use crate::error::{Result, TaskError};
+use crate::models::WorkSession;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Largest chunk accepted, in bytes of its serialized form
+pub const MAX_SESSION_LOG_CHUNK_BYTES: usize = 64 * 1024;
+
+/// One piece of a work session transcript, as appended by the agent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionLogChunk {
+    /// Free-form transcript text
+    pub content: String,
+    pub tool_calls: Vec<String>,
+    pub decisions: Vec<String>,
+    /// Paths of files read or changed
+    pub files_touched: Vec<String>,
+}
+
+impl SessionLogChunk {
+    /// Reject empty chunks and chunks over [`MAX_SESSION_LOG_CHUNK_BYTES`]
+    pub fn validate(&self) -> Result<()> {
+        if self.content.trim().is_empty()
+            && self.tool_calls.is_empty()
+            && self.decisions.is_empty()
+            && self.files_touched.is_empty()
+        {
+            return Err(TaskError::Validation(
+                "session log chunk must not be empty".to_string(),
+            ));
+        }
+        let size = serde_json::to_vec(self)
+            .map_err(|e| TaskError::Internal(e.to_string()))?
+            .len();
+        if size > MAX_SESSION_LOG_CHUNK_BYTES {
+            return Err(TaskError::Validation(format!(
+                "session log chunk is {size} bytes; at most {MAX_SESSION_LOG_CHUNK_BYTES} are accepted"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A stored chunk of a work session transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionLogEntry {
+    pub id: i32,
+    pub session_id: i32,
+    pub appended_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub chunk: SessionLogChunk,
+    /// Size of the chunk before compression
+    pub raw_bytes: u64,
+    /// Size of the chunk as stored
+    pub stored_bytes: u64,
+}
+
+/// The transcript of a work session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub session: WorkSession,
+    /// Chunks, oldest first
+    pub entries: Vec<SessionLogEntry>,
+    /// Every file touched during the session, in order of first mention
+    pub files_touched: Vec<String>,
+    pub raw_bytes: u64,
+    pub stored_bytes: u64,
+}
+
+impl SessionLog {
+    pub fn new(session: WorkSession, entries: Vec<SessionLogEntry>) -> Self {
+        let mut files_touched: Vec<String> = Vec::new();
+        for file in entries.iter().flat_map(|entry| &entry.chunk.files_touched) {
+            if !files_touched.contains(file) {
+                files_touched.push(file.clone());
+            }
+        }
+        Self {
+            raw_bytes: entries.iter().map(|entry| entry.raw_bytes).sum(),
+            stored_bytes: entries.iter().map(|entry| entry.stored_bytes).sum(),
+            session,
+            entries,
+            files_touched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_validation() {
+        assert!(SessionLogChunk::default().validate().is_err());
+        let chunk = SessionLogChunk {
+            files_touched: vec!["src/main.rs".to_string()],
+            ..SessionLogChunk::default()
+        };
+        assert!(chunk.validate().is_ok());
+        let huge = SessionLogChunk {
+            content: "x".repeat(MAX_SESSION_LOG_CHUNK_BYTES),
+            ..SessionLogChunk::default()
+        };
+        assert!(huge.validate().is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Workspace setup rollback
+//!
+//! Setup can stop halfway: a PRD is loaded and some agents are registered,
+//! CLAUDE.md is written but the manifest is not. Rolling back removes the
+//! files the workspace context records as generated, plus the manifest once
+//! one was produced, so the setup can start again from a clean project.
+//! Files Axon merged its managed sections into, like a CLAUDE.md the user
+//! already had, keep their own text and lose only those sections.
+//! Only paths inside the project root are touched; anything else the context
+//! records is reported and left alone.
+
+use crate::managed_sections::strip_managed_sections;
+use crate::workspace_doctor::MANIFEST_FILE;
+use crate::workspace_setup::WorkspaceContext;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+/// A generated file that could not be removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RollbackFailure {
+    pub path: String,
+    pub reason: String,
+}
+
+/// What a setup rollback removed, or would remove on a dry run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetupRollback {
+    pub dry_run: bool,
+    /// Generated files removed from the project root
+    pub removed_files: Vec<String>,
+    /// Files kept with only their Axon-managed sections removed
+    pub stripped_files: Vec<String>,
+    /// Recorded files that were already gone
+    pub missing_files: Vec<String>,
+    /// Files left in place, with why
+    pub failed_files: Vec<RollbackFailure>,
+    /// Agents dropped with the workspace context
+    pub removed_agents: Vec<String>,
+    /// Whether the workspace context was cleared; it is kept when a file could not be removed
+    pub context_cleared: bool,
+}
+
+impl SetupRollback {
+    /// Whether the workspace is back in its pre-setup state
+    pub fn is_complete(&self) -> bool {
+        self.failed_files.is_empty() && (self.dry_run || self.context_cleared)
+    }
+}
+
+/// Files the setup recorded in `context`, relative to the project root
+pub fn setup_files(context: &WorkspaceContext) -> Vec<String> {
+    let mut files: Vec<String> = context
+        .generated_files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect();
+    if context.manifest_data.is_some() && !files.iter().any(|file| file == MANIFEST_FILE) {
+        files.push(MANIFEST_FILE.to_string());
+    }
+    files
+}
+
+/// Remove the files recorded in `context` from the project in `root`
+///
+/// With `dry_run` nothing is removed and the report lists what would be.
+/// Clearing the context itself is up to the caller, which sets
+/// `context_cleared` once it has.
+pub fn rollback_setup_files(
+    root: &Path,
+    context: &WorkspaceContext,
+    dry_run: bool,
+) -> SetupRollback {
+    let mut rollback = SetupRollback {
+        dry_run,
+        removed_agents: context
+            .registered_agents
+            .iter()
+            .map(|agent| agent.name.clone())
+            .collect(),
+        ..SetupRollback::default()
+    };
+    for file in setup_files(context) {
+        let relative = Path::new(&file);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            rollback.failed_files.push(RollbackFailure {
+                path: file,
+                reason: "not a path inside the project root".to_string(),
+            });
+            continue;
+        }
+        let path = root.join(relative);
+        if !path.is_file() {
+            rollback.missing_files.push(file);
+            continue;
+        }
+        let stripped =
+            match std::fs::read_to_string(&path).map(|text| strip_managed_sections(&text)) {
+                Ok(Ok(stripped)) => stripped.filter(|text| !text.trim().is_empty()),
+                Ok(Err(reason)) => {
+                    rollback.failed_files.push(RollbackFailure {
+                        path: file,
+                        reason: format!("managed sections are broken: {reason}"),
+                    });
+                    continue;
+                }
+                // Not text, so nothing Axon merged into
+                Err(_) => None,
+            };
+        let result = match (&stripped, dry_run) {
+            (_, true) => Ok(()),
+            (Some(text), false) => std::fs::write(&path, text),
+            (None, false) => std::fs::remove_file(&path),
+        };
+        match result {
+            Ok(()) if stripped.is_some() => rollback.stripped_files.push(file),
+            Ok(()) => rollback.removed_files.push(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => rollback.missing_files.push(file),
+            Err(e) => rollback.failed_files.push(RollbackFailure {
+                path: file,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    rollback
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managed_sections::{merge_managed_sections, ManagedSection, AGENT_ROSTER_SECTION};
+    use crate::workspace_doctor::MAIN_AI_FILE;
+    use crate::workspace_setup::{AgentRegistration, AiToolType, GeneratedFileMetadata};
+
+    fn generated(path: &str) -> GeneratedFileMetadata {
+        GeneratedFileMetadata {
+            path: path.to_string(),
+            description: "Generated during setup".to_string(),
+            ai_tool_type: AiToolType::ClaudeCode,
+            content_type: "text/markdown".to_string(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rollback_setup_files() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join(MAIN_AI_FILE), "# Project\n").unwrap();
+        std::fs::write(root.path().join("README.md"), "# Readme\n").unwrap();
+        let (merged, _) = merge_managed_sections(
+            "# Team notes\n",
+            &[ManagedSection::new(AGENT_ROSTER_SECTION, "## Agents")],
+            false,
+        );
+        std::fs::write(root.path().join("AGENTS.md"), merged).unwrap();
+        let mut context = WorkspaceContext::new("default".to_string());
+        context.registered_agents.push(AgentRegistration {
+            name: "backend-developer".to_string(),
+            description: String::new(),
+            prompt: String::new(),
+            capabilities: vec![],
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: vec![],
+        });
+        for path in [MAIN_AI_FILE, "AGENTS.md", "docs/SETUP.md", "../outside.md"] {
+            context.generated_files.push(generated(path));
+        }
+
+        let preview = rollback_setup_files(root.path(), &context, true);
+        assert_eq!(preview.removed_files, [MAIN_AI_FILE]);
+        assert_eq!(preview.stripped_files, ["AGENTS.md"]);
+        assert!(root.path().join(MAIN_AI_FILE).exists());
+
+        let rollback = rollback_setup_files(root.path(), &context, false);
+        assert_eq!(rollback.removed_files, [MAIN_AI_FILE]);
+        assert_eq!(rollback.missing_files, ["docs/SETUP.md"]);
+        assert_eq!(rollback.failed_files[0].path, "../outside.md");
+        assert_eq!(rollback.removed_agents, ["backend-developer"]);
+        assert!(!rollback.is_complete());
+        assert!(!root.path().join(MAIN_AI_FILE).exists());
+        assert!(root.path().join("README.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(root.path().join("AGENTS.md")).unwrap(),
+            "# Team notes\n"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Guided workspace setup
+//!
+//! `setup_wizard` leads the calling AI through the setup functions in order:
+//! PRD ingestion, confirming the suggested agents, writing CLAUDE.md and
+//! generating the manifest. Each call answers with the current step, what to
+//! send next and the files produced so far; the caller writes those files.
+//! Progress is kept in the workspace context, so an interrupted setup resumes
+//! at the step it stopped at.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A step of the setup wizard, in the order they are taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardStep {
+    /// Ingest the product requirements document
+    Prd,
+    /// Confirm which of the suggested agents to register
+    Agents,
+    /// Write the main AI coordination file
+    MainFile,
+    /// Generate the workspace manifest
+    Manifest,
+    Complete,
+}
+
+impl WizardStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WizardStep::Prd => "prd",
+            WizardStep::Agents => "agents",
+            WizardStep::MainFile => "main_file",
+            WizardStep::Manifest => "manifest",
+            WizardStep::Complete => "complete",
+        }
+    }
+
+    /// The step after this one
+    pub fn next(&self) -> WizardStep {
+        match self {
+            WizardStep::Prd => WizardStep::Agents,
+            WizardStep::Agents => WizardStep::MainFile,
+            WizardStep::MainFile => WizardStep::Manifest,
+            WizardStep::Manifest | WizardStep::Complete => WizardStep::Complete,
+        }
+    }
+
+    /// `setup_wizard` parameter completing this step
+    pub fn expects(&self) -> Option<&'static str> {
+        match self {
+            WizardStep::Prd => Some("prd_content"),
+            WizardStep::Agents => Some("agents"),
+            WizardStep::MainFile => Some("main_file_content"),
+            WizardStep::Manifest => Some("confirm"),
+            WizardStep::Complete => None,
+        }
+    }
+
+    /// What the calling AI should do at this step
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            WizardStep::Prd => {
+                "Read the project's PRD (usually docs/PRD.md) and send its Markdown as \
+                 `prd_content`. It needs Overview, User Stories, Technical Requirements and \
+                 Success Criteria sections."
+            }
+            WizardStep::Agents => {
+                "Review the suggested agents in `data` with the user and send the names of \
+                 the agents to create as `agents`."
+            }
+            WizardStep::MainFile => {
+                "Write the project coordination instructions for CLAUDE.md following the \
+                 instructions in `data` and send them as `main_file_content`. Axon adds the \
+                 agent roster and MCP usage sections itself."
+            }
+            WizardStep::Manifest => "Send `confirm: true` to generate the workspace manifest.",
+            WizardStep::Complete => {
+                "Setup is complete. Call verify_workspace once the files are written to check \
+                 the result."
+            }
+        }
+    }
+}
+
+/// Progress of the setup wizard, stored in the workspace context
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupWizardProgress {
+    pub step: WizardStep,
+    pub completed_steps: Vec<WizardStep>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SetupWizardProgress {
+    pub fn new() -> Self {
+        let now = Utc::now();
+        Self {
+            step: WizardStep::Prd,
+            completed_steps: Vec::new(),
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Mark the current step as done and move to the next one
+    pub fn advance(&mut self) {
+        if self.step != WizardStep::Complete && !self.completed_steps.contains(&self.step) {
+            self.completed_steps.push(self.step);
+        }
+        self.step = self.step.next();
+        self.updated_at = Utc::now();
+    }
+}
+
+impl Default for SetupWizardProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A file for the calling AI to write, relative to the project root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WizardFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Answer of a `setup_wizard` call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupWizardState {
+    pub step: WizardStep,
+    pub completed_steps: Vec<WizardStep>,
+    pub instructions: String,
+    /// Parameter to send with the next call; `None` once setup is complete
+    pub expects: Option<String>,
+    /// Material for the current step, e.g. the suggested agents
+    pub data: Value,
+    /// Files produced by this call
+    pub files: Vec<WizardFile>,
+}
+
+impl SetupWizardState {
+    pub fn new(progress: &SetupWizardProgress, data: Value, files: Vec<WizardFile>) -> Self {
+        Self {
+            step: progress.step,
+            completed_steps: progress.completed_steps.clone(),
+            instructions: progress.step.instructions().to_string(),
+            expects: progress.step.expects().map(str::to_string),
+            data,
+            files,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wizard_progress() {
+        let mut progress = SetupWizardProgress::new();
+        assert_eq!(progress.step.expects(), Some("prd_content"));
+
+        for expected in [
+            WizardStep::Agents,
+            WizardStep::MainFile,
+            WizardStep::Manifest,
+        ] {
+            progress.advance();
+            assert_eq!(progress.step, expected);
+        }
+        progress.advance();
+        progress.advance();
+        assert_eq!(progress.step, WizardStep::Complete);
+        assert_eq!(progress.completed_steps.len(), 4);
+        assert_eq!(progress.step.expects(), None);
+
+        let state = SetupWizardState::new(&progress, Value::Null, Vec::new());
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["step"], "complete");
+        assert_eq!(json["completed_steps"][2], "main_file");
+    }
+}
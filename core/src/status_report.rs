@@ -0,0 +1,290 @@
+//! Status report of project progress over a period
+//!
+//! Summarizes task counts per state, what was created and completed, which
+//! tasks are blocked and how much each agent worked, as structured data or
+//! as plain text suitable for an email digest.
+
+use crate::models::{Task, TaskState, WorkSession};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// States in the order they are listed in a report
+pub(crate) const REPORT_STATES: [TaskState; 10] = [
+    TaskState::Created,
+    TaskState::InProgress,
+    TaskState::Blocked,
+    TaskState::WaitingForDependency,
+    TaskState::Review,
+    TaskState::PendingHandoff,
+    TaskState::PendingDecomposition,
+    TaskState::Quarantined,
+    TaskState::Done,
+    TaskState::Archived,
+];
+
+/// Number of tasks currently in a state
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StateCount {
+    pub state: TaskState,
+    pub count: usize,
+}
+
+/// A task listed in a report
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportTask {
+    pub code: String,
+    pub name: String,
+    pub owner_agent_name: Option<String>,
+}
+
+impl From<&Task> for ReportTask {
+    fn from(task: &Task) -> Self {
+        Self {
+            code: task.code.clone(),
+            name: task.name.clone(),
+            owner_agent_name: task.owner_agent_name.clone(),
+        }
+    }
+}
+
+/// Work of one agent during the period
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentActivity {
+    pub agent_name: String,
+    /// Work sessions overlapping the period
+    pub sessions: usize,
+    /// Minutes of those sessions within the period; open sessions count until its end
+    pub minutes_worked: i64,
+    /// Tasks owned by the agent and completed during the period
+    pub tasks_completed: usize,
+}
+
+/// Progress of the project between `period_start` and `period_end`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Current task count of every non-empty state
+    pub states: Vec<StateCount>,
+    /// Tasks created during the period
+    pub created: usize,
+    /// Tasks completed during the period, most recent first
+    pub completed: Vec<ReportTask>,
+    /// Tasks currently blocked
+    pub blocked: Vec<ReportTask>,
+    /// Agents that worked or completed tasks during the period, busiest first
+    pub agents: Vec<AgentActivity>,
+}
+
+impl StatusReport {
+    /// Build the report of `tasks` and `sessions` for the period `[start, end)`
+    pub fn new(
+        tasks: &[Task],
+        sessions: &[WorkSession],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        let in_period = |at: DateTime<Utc>| at >= start && at < end;
+
+        let states = REPORT_STATES
+            .iter()
+            .map(|&state| StateCount {
+                state,
+                count: tasks.iter().filter(|t| t.state == state).count(),
+            })
+            .filter(|c| c.count > 0)
+            .collect();
+
+        let mut completed: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| t.done_at.is_some_and(in_period))
+            .collect();
+        completed.sort_by(|a, b| b.done_at.cmp(&a.done_at).then(a.code.cmp(&b.code)));
+
+        let mut agents: HashMap<String, AgentActivity> = HashMap::new();
+        for session in sessions {
+            let session_end = session.ended_at.unwrap_or(end).min(end);
+            let session_start = session.started_at.max(start);
+            if session_start >= end || session_end <= start {
+                continue;
+            }
+            let activity = activity_of(&mut agents, &session.agent_name);
+            activity.sessions += 1;
+            activity.minutes_worked += (session_end - session_start).num_minutes();
+        }
+        for task in &completed {
+            if let Some(ref owner) = task.owner_agent_name {
+                activity_of(&mut agents, owner).tasks_completed += 1;
+            }
+        }
+
+        let mut agents: Vec<AgentActivity> = agents.into_values().collect();
+        agents.sort_by(|a, b| {
+            b.minutes_worked
+                .cmp(&a.minutes_worked)
+                .then(b.tasks_completed.cmp(&a.tasks_completed))
+                .then(a.agent_name.cmp(&b.agent_name))
+        });
+
+        Self {
+            period_start: start,
+            period_end: end,
+            states,
+            created: tasks.iter().filter(|t| in_period(t.inserted_at)).count(),
+            completed: completed.into_iter().map(ReportTask::from).collect(),
+            blocked: tasks
+                .iter()
+                .filter(|t| t.state == TaskState::Blocked)
+                .map(ReportTask::from)
+                .collect(),
+            agents,
+        }
+    }
+
+    /// Plain text rendering of the report
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Status report {} - {} (UTC)\n\n",
+            self.period_start.format("%Y-%m-%d %H:%M"),
+            self.period_end.format("%Y-%m-%d %H:%M")
+        );
+        let _ = writeln!(
+            text,
+            "Tasks: {} created, {} completed",
+            self.created,
+            self.completed.len()
+        );
+        if !self.states.is_empty() {
+            let states: Vec<String> = self
+                .states
+                .iter()
+                .map(|c| format!("{} {}", c.state, c.count))
+                .collect();
+            let _ = writeln!(text, "By state: {}", states.join(", "));
+        }
+
+        write_tasks(&mut text, "Completed", &self.completed);
+        write_tasks(&mut text, "Blocked", &self.blocked);
+
+        text.push_str("\nAgent activity\n");
+        if self.agents.is_empty() {
+            text.push_str("- none\n");
+        }
+        for agent in &self.agents {
+            let _ = writeln!(
+                text,
+                "- {}: {} session(s), {}h {:02}m, {} task(s) completed",
+                agent.agent_name,
+                agent.sessions,
+                agent.minutes_worked / 60,
+                agent.minutes_worked % 60,
+                agent.tasks_completed
+            );
+        }
+        text
+    }
+}
+
+fn activity_of<'a>(
+    agents: &'a mut HashMap<String, AgentActivity>,
+    name: &str,
+) -> &'a mut AgentActivity {
+    agents
+        .entry(name.to_string())
+        .or_insert_with(|| AgentActivity {
+            agent_name: name.to_string(),
+            sessions: 0,
+            minutes_worked: 0,
+            tasks_completed: 0,
+        })
+}
+
+fn write_tasks(text: &mut String, title: &str, tasks: &[ReportTask]) {
+    let _ = writeln!(text, "\n{title}");
+    if tasks.is_empty() {
+        text.push_str("- none\n");
+    }
+    for task in tasks {
+        let _ = write!(text, "- {} {}", task.code, task.name);
+        if let Some(ref owner) = task.owner_agent_name {
+            let _ = write!(text, " ({owner})");
+        }
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_status_report() {
+        let now = Utc::now();
+        let start = now - Duration::days(1);
+        let done = Task::new(
+            1,
+            "API-01".to_string(),
+            "Build API".to_string(),
+            "Endpoints".to_string(),
+            Some("backend-dev".to_string()),
+            TaskState::Done,
+            now - Duration::days(3),
+            Some(now - Duration::hours(2)),
+        );
+        let blocked = Task::new(
+            2,
+            "UI-01".to_string(),
+            "Build UI".to_string(),
+            "Screens".to_string(),
+            Some("frontend-dev".to_string()),
+            TaskState::Blocked,
+            now - Duration::hours(5),
+            None,
+        );
+        let session = |id, agent: &str, started, ended| WorkSession {
+            id,
+            task_id: 1,
+            agent_name: agent.to_string(),
+            started_at: started,
+            ended_at: ended,
+            notes: None,
+            productivity_score: None,
+            interruptions: Vec::new(),
+        };
+        let sessions = vec![
+            // Counts only from the start of the period
+            session(
+                1,
+                "backend-dev",
+                start - Duration::hours(1),
+                Some(start + Duration::hours(2)),
+            ),
+            session(2, "frontend-dev", now - Duration::minutes(45), None),
+            session(
+                3,
+                "frontend-dev",
+                start - Duration::hours(3),
+                Some(start - Duration::hours(2)),
+            ),
+        ];
+
+        let report = StatusReport::new(&[done, blocked], &sessions, start, now);
+        assert_eq!(report.created, 1);
+        assert_eq!(report.completed.len(), 1);
+        assert_eq!(report.blocked[0].code, "UI-01");
+        assert_eq!(report.states.len(), 2);
+        assert_eq!(report.agents[0].agent_name, "backend-dev");
+        assert_eq!(report.agents[0].minutes_worked, 120);
+        assert_eq!(report.agents[0].tasks_completed, 1);
+        assert_eq!(report.agents[1].sessions, 1);
+        assert_eq!(report.agents[1].minutes_worked, 45);
+
+        let text = report.to_text();
+        assert!(text.contains("Tasks: 1 created, 1 completed\nBy state: Blocked 1, Done 1\n"));
+        assert!(text.contains("\nCompleted\n- API-01 Build API (backend-dev)\n"));
+        assert!(text.contains("- backend-dev: 1 session(s), 2h 00m, 1 task(s) completed\n"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Stuck task detection
+//!
+//! A task counts as stuck once it has been in progress for `estimate_factor`
+//! times its estimate and nothing happened on it for `idle_minutes`: no work
+//! session started or ended and no message posted. Its owner then gets a
+//! `nudge` message, at most one per `idle_minutes`. Once `max_nudges` nudges
+//! went unanswered the coordinator gets an `escalation` message; any activity
+//! on the task starts the count over.
+
+use crate::critical_path::DEFAULT_EFFORT_MINUTES;
+use crate::error::{Result, TaskError};
+use crate::models::{Task, TaskFilter, TaskMessage, TaskState, WorkSession};
+use crate::repository::{TaskMessageRepository, TaskRepository, WorkSessionFilter};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Author of nudges and escalations
+pub const STUCK_TASK_AUTHOR: &str = "stuck-task-detector";
+
+/// Message type of reminders sent to the owner of a stuck task
+pub const NUDGE_MESSAGE_TYPE: &str = "nudge";
+
+/// Message type of reports sent to the coordinator about a stuck task
+pub const ESCALATION_MESSAGE_TYPE: &str = "escalation";
+
+/// When a task counts as stuck and who hears about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StuckTaskPolicy {
+    /// Multiple of the task's estimate it may stay in progress
+    pub estimate_factor: f64,
+    /// Minutes without sessions or messages before the owner is nudged, and between nudges
+    pub idle_minutes: u32,
+    /// Unanswered nudges after which the coordinator is told
+    pub max_nudges: u32,
+    /// Agent receiving escalations
+    pub coordinator: String,
+}
+
+impl Default for StuckTaskPolicy {
+    fn default() -> Self {
+        Self {
+            estimate_factor: 2.0,
+            idle_minutes: 60,
+            max_nudges: 3,
+            coordinator: "coordinator".to_string(),
+        }
+    }
+}
+
+/// What the detector does about a stuck task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StuckTaskAction {
+    Nudge,
+    Escalate,
+}
+
+/// A task in progress for too long without activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StuckTask {
+    pub task_id: i32,
+    pub task_code: String,
+    pub owner_agent_name: String,
+    pub in_progress_minutes: i64,
+    pub estimated_minutes: u32,
+    pub last_activity_at: DateTime<Utc>,
+    /// Nudges sent since the last activity
+    pub nudges: u32,
+    /// Step due now; `None` while waiting after a nudge or once escalated
+    pub action: Option<StuckTaskAction>,
+}
+
+/// Outcome of one run of the detector
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StuckTaskReport {
+    pub stuck: Vec<StuckTask>,
+    pub nudged: usize,
+    pub escalated: usize,
+}
+
+impl StuckTaskPolicy {
+    /// Whether `task` is stuck at `now`, given its work sessions and messages
+    pub fn assess(
+        &self,
+        task: &Task,
+        sessions: &[WorkSession],
+        messages: &[TaskMessage],
+        now: DateTime<Utc>,
+    ) -> Option<StuckTask> {
+        if task.state != TaskState::InProgress {
+            return None;
+        }
+        let owner = task.owner_agent_name.as_ref()?;
+        let started = task.claimed_at.unwrap_or(task.state_changed_at);
+        let estimated_minutes = task
+            .estimated_effort
+            .filter(|minutes| *minutes > 0)
+            .map_or(DEFAULT_EFFORT_MINUTES, |minutes| minutes as u32);
+        let in_progress_minutes = (now - started).num_minutes();
+        if (in_progress_minutes as f64) < f64::from(estimated_minutes) * self.estimate_factor {
+            return None;
+        }
+
+        let is_detector = |message: &TaskMessage| message.author_agent_name == STUCK_TASK_AUTHOR;
+        let last_activity_at = sessions
+            .iter()
+            .flat_map(|session| [Some(session.started_at), session.ended_at])
+            .flatten()
+            .chain(
+                messages
+                    .iter()
+                    .filter(|message| !is_detector(message))
+                    .map(|message| message.created_at),
+            )
+            .fold(started, DateTime::max);
+        let idle = Duration::minutes(i64::from(self.idle_minutes));
+        if now - last_activity_at < idle {
+            return None;
+        }
+
+        let sent = |message_type: &str| -> Vec<DateTime<Utc>> {
+            messages
+                .iter()
+                .filter(|message| {
+                    is_detector(message)
+                        && message.message_type == message_type
+                        && message.created_at > last_activity_at
+                })
+                .map(|message| message.created_at)
+                .collect()
+        };
+        let nudges = sent(NUDGE_MESSAGE_TYPE);
+        let action = if !sent(ESCALATION_MESSAGE_TYPE).is_empty() {
+            None
+        } else if nudges.len() >= self.max_nudges as usize {
+            Some(StuckTaskAction::Escalate)
+        } else if nudges.iter().any(|at| now - *at < idle) {
+            None
+        } else {
+            Some(StuckTaskAction::Nudge)
+        };
+
+        Some(StuckTask {
+            task_id: task.id,
+            task_code: task.code.clone(),
+            owner_agent_name: owner.clone(),
+            in_progress_minutes,
+            estimated_minutes,
+            last_activity_at,
+            nudges: nudges.len() as u32,
+            action,
+        })
+    }
+}
+
+/// Find stuck tasks and send the nudges and escalations due at `now`
+pub async fn nudge_stuck_tasks<R, M>(
+    repository: &R,
+    message_repository: &M,
+    policy: &StuckTaskPolicy,
+    now: DateTime<Utc>,
+) -> Result<StuckTaskReport>
+where
+    R: TaskRepository + ?Sized,
+    M: TaskMessageRepository + ?Sized,
+{
+    let tasks = repository
+        .list(TaskFilter {
+            state: Some(TaskState::InProgress),
+            ..TaskFilter::default()
+        })
+        .await?;
+
+    let mut report = StuckTaskReport::default();
+    for task in tasks {
+        let filter = WorkSessionFilter {
+            task_id: Some(task.id),
+            ..WorkSessionFilter::default()
+        };
+        let sessions = match repository.list_work_sessions(&filter).await {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let messages = message_repository
+            .get_messages(&task.code, None, None, None, None, None)
+            .await?;
+        let Some(stuck) = policy.assess(&task, &sessions, &messages, now) else {
+            continue;
+        };
+
+        let data = json!({
+            "in_progress_minutes": stuck.in_progress_minutes,
+            "estimated_minutes": stuck.estimated_minutes,
+            "last_activity_at": stuck.last_activity_at,
+            "nudges": stuck.nudges,
+        });
+        match stuck.action {
+            Some(StuckTaskAction::Nudge) => {
+                let content = format!(
+                    "Task {} has been in progress for {} minutes against an estimate of {}, \
+                     with no activity since {}. Post an update, or release or block the task.",
+                    stuck.task_code,
+                    stuck.in_progress_minutes,
+                    stuck.estimated_minutes,
+                    stuck.last_activity_at.format("%Y-%m-%d %H:%M UTC")
+                );
+                message_repository
+                    .create_message(
+                        &stuck.task_code,
+                        STUCK_TASK_AUTHOR,
+                        Some(&stuck.owner_agent_name),
+                        NUDGE_MESSAGE_TYPE,
+                        &content,
+                        None,
+                        Some(&data),
+                    )
+                    .await?;
+                report.nudged += 1;
+            }
+            Some(StuckTaskAction::Escalate) => {
+                let content = format!(
+                    "Task {} owned by {} is stuck: {} nudges went unanswered and nothing \
+                     happened since {}. Consider reassigning it.",
+                    stuck.task_code,
+                    stuck.owner_agent_name,
+                    stuck.nudges,
+                    stuck.last_activity_at.format("%Y-%m-%d %H:%M UTC")
+                );
+                message_repository
+                    .create_message(
+                        &stuck.task_code,
+                        STUCK_TASK_AUTHOR,
+                        Some(&policy.coordinator),
+                        ESCALATION_MESSAGE_TYPE,
+                        &content,
+                        None,
+                        Some(&data),
+                    )
+                    .await?;
+                report.escalated += 1;
+            }
+            None => {}
+        }
+        report.stuck.push(stuck);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(claimed_at: DateTime<Utc>) -> Task {
+        Task {
+            name: "Build API".to_string(),
+            owner_agent_name: Some("backend-dev".to_string()),
+            claimed_at: Some(claimed_at),
+            estimated_effort: Some(30),
+            ..test_support::task_at(1, "API-01", TaskState::InProgress, claimed_at)
+        }
+    }
+
+    fn message(author: &str, message_type: &str, created_at: DateTime<Utc>) -> TaskMessage {
+        TaskMessage {
+            id: 1,
+            uid: None,
+            task_code: "API-01".to_string(),
+            author_agent_name: author.to_string(),
+            target_agent_name: None,
+            message_type: message_type.to_string(),
+            created_at,
+            content: String::new(),
+            reply_to_message_id: None,
+            structured_data: None,
+        }
+    }
+
+    #[test]
+    fn test_nudge_then_escalate() {
+        let policy = StuckTaskPolicy::default();
+        let now = Utc::now();
+        let task = task(now - Duration::hours(4));
+
+        let fresh = [message(
+            "backend-dev",
+            "comment",
+            now - Duration::minutes(10),
+        )];
+        assert!(policy.assess(&task, &[], &fresh, now).is_none());
+        assert!(policy
+            .assess(
+                &task,
+                &[],
+                &[],
+                task.claimed_at.unwrap() + Duration::minutes(50)
+            )
+            .is_none());
+
+        let stuck = policy.assess(&task, &[], &[], now).unwrap();
+        assert_eq!(stuck.action, Some(StuckTaskAction::Nudge));
+        assert_eq!(stuck.in_progress_minutes, 240);
+
+        let nudged = [message(
+            STUCK_TASK_AUTHOR,
+            NUDGE_MESSAGE_TYPE,
+            now - Duration::minutes(20),
+        )];
+        assert_eq!(
+            policy.assess(&task, &[], &nudged, now).unwrap().action,
+            None
+        );
+
+        let nudges: Vec<_> = (1..=3)
+            .map(|hours| {
+                message(
+                    STUCK_TASK_AUTHOR,
+                    NUDGE_MESSAGE_TYPE,
+                    now - Duration::hours(hours),
+                )
+            })
+            .collect();
+        let stuck = policy.assess(&task, &[], &nudges, now).unwrap();
+        assert_eq!(stuck.nudges, 3);
+        assert_eq!(stuck.action, Some(StuckTaskAction::Escalate));
+
+        let mut escalated = nudges.clone();
+        escalated.push(message(STUCK_TASK_AUTHOR, ESCALATION_MESSAGE_TYPE, now));
+        assert_eq!(
+            policy.assess(&task, &[], &escalated, now).unwrap().action,
+            None
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Validating builder for new tasks
+//!
+//! Each setter checks its value as it is given and records what is wrong with
+//! it, so [`NewTaskBuilder::build`] can report every problem of a task at once
+//! instead of stopping at the first one.
+
+use crate::{
+    capabilities::validate_capability, error::TaskError, models::NewTask, validation::TaskValidator,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Highest task priority score; scores range from 0.0 to this value
+pub const MAX_PRIORITY_SCORE: f64 = 10.0;
+
+/// A task field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Name of the field, as in [`NewTask`]
+    pub field: String,
+    pub message: String,
+}
+
+/// Every problem found while building a [`NewTask`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<String> = self
+            .errors
+            .iter()
+            .map(|error| format!("{}: {}", error.field, error.message))
+            .collect();
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationErrors> for TaskError {
+    fn from(errors: ValidationErrors) -> Self {
+        TaskError::Validation(errors.to_string())
+    }
+}
+
+/// Builder for [`NewTask`], validating each field as it is set
+///
+/// ```rust
+/// use task_core::NewTask;
+///
+/// let task = NewTask::builder()
+///     .code("API-07")
+///     .name("Rate limiting")
+///     .description("Limit requests per agent")
+///     .owner("backend-dev")
+///     .priority(8.0)
+///     .required_capabilities(["rust", "Backend Development"])
+///     .build()
+///     .unwrap();
+/// assert_eq!(task.required_capabilities, ["rust", "backend-development"]);
+///
+/// let errors = NewTask::builder().code("7").priority(11.0).build().unwrap_err();
+/// assert_eq!(errors.errors.len(), 4); // code, priority, and missing name and description
+/// ```
+#[derive(Debug, Clone)]
+pub struct NewTaskBuilder {
+    task: NewTask,
+    errors: Vec<FieldError>,
+}
+
+impl NewTask {
+    /// Start building a new task; see [`NewTaskBuilder`]
+    pub fn builder() -> NewTaskBuilder {
+        NewTaskBuilder {
+            task: NewTask::new(String::new(), String::new(), String::new(), None),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl NewTaskBuilder {
+    /// Human-readable task code, e.g. "ARCH-01"
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.task.code = code.into();
+        let result = TaskValidator::validate_task_code(&self.task.code);
+        self.record("code", result)
+    }
+
+    /// Brief task title
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.task.name = name.into();
+        let result = TaskValidator::validate_task_name(&self.task.name);
+        self.record("name", result)
+    }
+
+    /// Detailed task requirements
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.task.description = description.into();
+        let result = TaskValidator::validate_task_description(&self.task.description);
+        self.record("description", result)
+    }
+
+    /// Agent the task is assigned to
+    pub fn owner(mut self, owner: impl Into<String>) -> Self {
+        let owner = owner.into();
+        let result = TaskValidator::validate_agent_name(&owner);
+        self.task.owner_agent_name = Some(owner);
+        self.record("owner_agent_name", result)
+    }
+
+    /// Priority score from 0.0 (lowest) to [`MAX_PRIORITY_SCORE`]
+    pub fn priority(mut self, priority: f64) -> Self {
+        self.task.priority_score = priority;
+        let result = if (0.0..=MAX_PRIORITY_SCORE).contains(&priority) {
+            Ok(())
+        } else {
+            Err(TaskError::Validation(format!(
+                "Priority must be between 0 and {MAX_PRIORITY_SCORE}"
+            )))
+        };
+        self.record("priority_score", result)
+    }
+
+    /// Capabilities an agent needs for the task, stored in canonical spelling
+    pub fn required_capabilities<I, S>(mut self, capabilities: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut canonical: Vec<String> = Vec::new();
+        let mut result = Ok(());
+        for capability in capabilities {
+            match validate_capability(capability.as_ref()) {
+                Ok(key) if !canonical.contains(&key) => canonical.push(key),
+                Ok(_) => {}
+                // Report the first invalid capability
+                Err(e) if result.is_ok() => result = Err(e),
+                Err(_) => {}
+            }
+        }
+        self.task.required_capabilities = canonical;
+        self.record("required_capabilities", result)
+    }
+
+    /// Task this one is a subtask of
+    pub fn parent(mut self, parent_task_id: i32) -> Self {
+        self.task.parent_task_id = Some(parent_task_id);
+        self
+    }
+
+    /// Estimated effort in minutes; at least one
+    pub fn estimated_effort(mut self, minutes: i32) -> Self {
+        self.task.estimated_effort = Some(minutes);
+        let result = if minutes >= 1 {
+            Ok(())
+        } else {
+            Err(TaskError::Validation(
+                "Estimated effort must be at least 1 minute".to_string(),
+            ))
+        };
+        self.record("estimated_effort", result)
+    }
+
+    /// Workflow definition the task follows
+    pub fn workflow(mut self, workflow_definition_id: i32) -> Self {
+        self.task.workflow_definition_id = Some(workflow_definition_id);
+        self
+    }
+
+    /// Confidence required to complete the task, from 0.0 to 1.0
+    pub fn confidence_threshold(mut self, threshold: f64) -> Self {
+        self.task.confidence_threshold = threshold;
+        let result = if (0.0..=1.0).contains(&threshold) {
+            Ok(())
+        } else {
+            Err(TaskError::Validation(
+                "Confidence threshold must be between 0 and 1".to_string(),
+            ))
+        };
+        self.record("confidence_threshold", result)
+    }
+
+    /// Problems found so far, before the required fields are checked
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Finish the task, or report every invalid or missing field
+    pub fn build(mut self) -> Result<NewTask, ValidationErrors> {
+        for (field, value) in [
+            ("code", &self.task.code),
+            ("name", &self.task.name),
+            ("description", &self.task.description),
+        ] {
+            if value.is_empty() && !self.errors.iter().any(|error| error.field == field) {
+                self.errors.push(FieldError {
+                    field: field.to_string(),
+                    message: "is required".to_string(),
+                });
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(self.task)
+        } else {
+            Err(ValidationErrors {
+                errors: self.errors,
+            })
+        }
+    }
+
+    /// Replace the recorded problem of `field` with the outcome of its latest value
+    fn record(mut self, field: &str, result: crate::error::Result<()>) -> Self {
+        self.errors.retain(|error| error.field != field);
+        if let Err(e) = result {
+            let message = match e {
+                TaskError::Validation(message) => message,
+                other => other.to_string(),
+            };
+            self.errors.push(FieldError {
+                field: field.to_string(),
+                message,
+            });
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_collects_every_error() {
+        let errors = NewTask::builder()
+            .code("1-bad")
+            .name("   ")
+            .owner("bad agent!")
+            .estimated_effort(0)
+            .confidence_threshold(1.5)
+            .build()
+            .unwrap_err();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "code",
+                "name",
+                "owner_agent_name",
+                "estimated_effort",
+                "confidence_threshold",
+                "description"
+            ]
+        );
+        assert_eq!(errors.errors[5].message, "is required");
+
+        let error = TaskError::from(errors);
+        assert!(
+            matches!(&error, TaskError::Validation(m) if m.contains("code: Task code must start"))
+        );
+    }
+
+    #[test]
+    fn test_builder_revalidates_replaced_values() {
+        let builder = NewTask::builder().code("x").priority(-1.0);
+        assert_eq!(builder.errors().len(), 2);
+
+        let task = builder
+            .code("FIX-1")
+            .priority(2.5)
+            .name("Fix")
+            .description("Fix it")
+            .required_capabilities(["Rust", "rust", "SQL_Server"])
+            .parent(3)
+            .build()
+            .unwrap();
+        assert_eq!(task.code, "FIX-1");
+        assert_eq!(task.priority_score, 2.5);
+        assert_eq!(task.required_capabilities, ["rust", "sql-server"]);
+        assert_eq!(task.parent_task_id, Some(3));
+        assert_eq!(task.owner_agent_name, None);
+    }
+}
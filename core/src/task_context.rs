@@ -0,0 +1,315 @@
+//! Context bundle for an agent picking up a task
+//!
+//! Collects the task, where it sits in the task hierarchy, recent discussion,
+//! related records from other tasks, tracker links and referenced commits
+//! into one payload, trimmed to fit a token budget.
+
+use crate::artifacts::TaskArtifact;
+use crate::embeddings::SemanticMatch;
+use crate::models::{ExternalLink, Task, TaskComment, TaskMessage, TaskState};
+use crate::token_estimate::estimate_tokens;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Token budget of a context bundle when none is requested
+pub const DEFAULT_CONTEXT_TOKEN_BUDGET: u32 = 4_000;
+
+/// Smallest token budget accepted; the task itself must fit
+pub const MIN_CONTEXT_TOKEN_BUDGET: u32 = 480;
+
+/// Approximate characters per token of JSON text
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Deepest parent chain followed, guarding against cycles
+const MAX_ANCESTOR_DEPTH: usize = 32;
+
+/// A task referenced from the context of another task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskSummary {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub state: TaskState,
+    pub owner_agent_name: Option<String>,
+}
+
+impl From<&Task> for TaskSummary {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id,
+            code: task.code.clone(),
+            name: task.name.clone(),
+            state: task.state,
+            owner_agent_name: task.owner_agent_name.clone(),
+        }
+    }
+}
+
+/// Where a task sits in the hierarchy of parents and subtasks
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DependencyChain {
+    /// Parent first, then its parent and so on up to the root task
+    pub ancestors: Vec<TaskSummary>,
+    /// Direct subtasks, which gate the task
+    pub subtasks: Vec<TaskSummary>,
+    /// Subtasks not yet done or archived
+    pub incomplete_subtasks: usize,
+    /// One-line description of the above
+    pub summary: String,
+}
+
+impl DependencyChain {
+    /// Chain of `task` among `tasks`
+    pub fn new(task: &Task, tasks: &[Task]) -> Self {
+        let by_id: HashMap<i32, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+        let mut ancestors = Vec::new();
+        let mut parent_id = task.parent_task_id;
+        while let Some(parent) = parent_id.and_then(|id| by_id.get(&id)) {
+            if parent.id == task.id || ancestors.len() == MAX_ANCESTOR_DEPTH {
+                break;
+            }
+            ancestors.push(TaskSummary::from(*parent));
+            parent_id = parent.parent_task_id;
+        }
+
+        let subtasks: Vec<TaskSummary> = tasks
+            .iter()
+            .filter(|t| t.parent_task_id == Some(task.id))
+            .map(TaskSummary::from)
+            .collect();
+        let incomplete_subtasks = subtasks
+            .iter()
+            .filter(|t| !matches!(t.state, TaskState::Done | TaskState::Archived))
+            .count();
+
+        let mut parts = Vec::new();
+        if !ancestors.is_empty() {
+            let chain: Vec<String> = ancestors
+                .iter()
+                .map(|t| format!("{} ({})", t.code, t.state))
+                .collect();
+            parts.push(format!("Subtask of {}", chain.join(" < ")));
+        }
+        if !subtasks.is_empty() {
+            parts.push(format!(
+                "{} of {} subtask(s) incomplete",
+                incomplete_subtasks,
+                subtasks.len()
+            ));
+        }
+        let summary = if parts.is_empty() {
+            "No parent task or subtasks".to_string()
+        } else {
+            parts.join("; ")
+        };
+
+        Self {
+            ancestors,
+            subtasks,
+            incomplete_subtasks,
+            summary,
+        }
+    }
+}
+
+/// Everything an agent needs to start working on a task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskContext {
+    pub task: Task,
+    pub dependencies: DependencyChain,
+    /// Messages on the task, newest first
+    pub recent_messages: Vec<TaskMessage>,
+    /// Comments on the task, newest first
+    pub comments: Vec<TaskComment>,
+    /// Similar records of other tasks, most similar first
+    pub related: Vec<SemanticMatch>,
+    /// Items linked in external trackers
+    pub external_links: Vec<ExternalLink>,
+    /// Files the task produced
+    #[serde(default)]
+    pub artifacts: Vec<TaskArtifact>,
+    /// Commit hashes referenced in messages and comments
+    pub commits: Vec<String>,
+    pub token_budget: u32,
+    /// Approximate size of the bundle in tokens
+    pub estimated_tokens: u32,
+    /// Whether records were dropped or the description shortened to fit the budget
+    pub truncated: bool,
+}
+
+impl TaskContext {
+    /// Bundle the collected records of `task`
+    ///
+    /// Messages and comments are expected newest first.
+    pub fn new(
+        task: Task,
+        dependencies: DependencyChain,
+        recent_messages: Vec<TaskMessage>,
+        comments: Vec<TaskComment>,
+        related: Vec<SemanticMatch>,
+        external_links: Vec<ExternalLink>,
+    ) -> Self {
+        let mut commits = Vec::new();
+        for text in recent_messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .chain(comments.iter().map(|c| c.content.as_str()))
+        {
+            for sha in extract_commit_refs(text) {
+                if !commits.contains(&sha) {
+                    commits.push(sha);
+                }
+            }
+        }
+        Self {
+            task,
+            dependencies,
+            recent_messages,
+            comments,
+            related,
+            external_links,
+            artifacts: Vec::new(),
+            commits,
+            token_budget: 0,
+            estimated_tokens: 0,
+            truncated: false,
+        }
+    }
+
+    /// Add the files registered as produced by the task
+    pub fn with_artifacts(mut self, artifacts: Vec<TaskArtifact>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    /// Approximate size of the bundle as JSON, in tokens
+    pub fn estimate_tokens(&self) -> u32 {
+        serde_json::to_string(self).map_or(0, |json| estimate_tokens(&json))
+    }
+
+    /// Drop the least useful records until the bundle fits `token_budget`
+    ///
+    /// Related records go first, then the oldest comments and messages; the
+    /// task description is shortened only when nothing else is left.
+    pub fn fit_to_budget(mut self, token_budget: u32) -> Self {
+        self.token_budget = token_budget;
+        loop {
+            self.estimated_tokens = self.estimate_tokens();
+            if self.estimated_tokens <= token_budget {
+                return self;
+            }
+            self.truncated = true;
+            if self.related.pop().is_some()
+                || self.comments.pop().is_some()
+                || self.recent_messages.pop().is_some()
+            {
+                continue;
+            }
+            let excess = (self.estimated_tokens - token_budget) as usize * CHARS_PER_TOKEN;
+            let description = &self.task.description;
+            if description.is_empty() {
+                return self;
+            }
+            let keep = description.chars().count().saturating_sub(excess + 1);
+            self.task.description = description.chars().take(keep).collect::<String>() + "…";
+            if keep == 0 {
+                self.estimated_tokens = self.estimate_tokens();
+                return self;
+            }
+        }
+    }
+}
+
+/// Commit hashes in `text`: words of 7 to 40 hex digits mixing letters and digits
+pub fn extract_commit_refs(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| {
+            (7..=40).contains(&word.len())
+                && word.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+                && word.chars().any(|c| c.is_ascii_digit())
+                && word.chars().any(|c| c.is_ascii_alphabetic())
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::Utc;
+
+    fn task(id: i32, code: &str, parent: Option<i32>, state: TaskState) -> Task {
+        Task {
+            parent_task_id: parent,
+            description: "x".repeat(2_000),
+            owner_agent_name: Some("backend-dev".to_string()),
+            ..test_support::task(id, code, state)
+        }
+    }
+
+    fn message(id: i32, content: &str) -> TaskMessage {
+        TaskMessage {
+            id,
+            uid: None,
+            task_code: "API-02".to_string(),
+            author_agent_name: "backend-dev".to_string(),
+            target_agent_name: None,
+            message_type: "comment".to_string(),
+            created_at: Utc::now(),
+            content: content.to_string(),
+            reply_to_message_id: None,
+            structured_data: None,
+        }
+    }
+
+    #[test]
+    fn test_task_context() {
+        let tasks = vec![
+            task(1, "API", None, TaskState::InProgress),
+            task(2, "API-02", Some(1), TaskState::InProgress),
+            task(3, "API-02a", Some(2), TaskState::Done),
+            task(4, "API-02b", Some(2), TaskState::Created),
+        ];
+        let chain = DependencyChain::new(&tasks[1], &tasks);
+        assert_eq!(chain.ancestors[0].code, "API");
+        assert_eq!(chain.incomplete_subtasks, 1);
+        assert_eq!(
+            chain.summary,
+            "Subtask of API (InProgress); 1 of 2 subtask(s) incomplete"
+        );
+
+        assert_eq!(
+            extract_commit_refs("Fixed in 3f9a2c1, see deadbeef and cafe1234567"),
+            vec!["3f9a2c1", "cafe1234567"]
+        );
+
+        let messages = vec![
+            message(2, "Pushed 9e8d7c6b"),
+            message(1, &"older discussion ".repeat(100)),
+        ];
+        let context = TaskContext::new(
+            tasks[1].clone(),
+            chain,
+            messages,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        assert_eq!(context.commits, vec!["9e8d7c6b"]);
+
+        let full = context.clone().fit_to_budget(10_000);
+        assert!(!full.truncated);
+        assert_eq!(full.recent_messages.len(), 2);
+
+        let trimmed = context.clone().fit_to_budget(900);
+        assert!(trimmed.truncated);
+        assert_eq!(trimmed.recent_messages.len(), 1);
+        assert!(trimmed.estimated_tokens <= 900);
+
+        let tight = context.fit_to_budget(MIN_CONTEXT_TOKEN_BUDGET);
+        assert!(tight.recent_messages.is_empty());
+        assert!(tight.task.description.ends_with('…'));
+        assert!(tight.estimated_tokens <= MIN_CONTEXT_TOKEN_BUDGET);
+    }
+}
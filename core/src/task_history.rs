@@ -0,0 +1,279 @@
+//! Point-in-time task boards rebuilt from the task event history
+//!
+//! While event history is enabled, every change to a task is appended to the
+//! history and the whole task table is snapshotted periodically. Replaying the
+//! events recorded after the latest snapshot before a point in time yields the
+//! board as it was then. Comparing two boards shows what changed in between.
+
+use crate::{
+    models::{Task, TaskState},
+    repository::AuditEntry,
+    status_report::{ReportTask, StateCount, REPORT_STATES},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The tasks as they were at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskBoard {
+    /// The point in time the board shows
+    pub at: DateTime<Utc>,
+    /// When history was first recorded; earlier boards are empty
+    pub history_starts_at: Option<DateTime<Utc>>,
+    /// Task count of every non-empty state
+    pub states: Vec<StateCount>,
+    /// Every task that existed at that time, by ID
+    pub tasks: Vec<Task>,
+}
+
+impl TaskBoard {
+    /// Build the board from the tasks as they were at `at`
+    pub fn new(
+        at: DateTime<Utc>,
+        history_starts_at: Option<DateTime<Utc>>,
+        mut tasks: Vec<Task>,
+    ) -> Self {
+        tasks.sort_by_key(|task| task.id);
+        let states = REPORT_STATES
+            .iter()
+            .map(|&state| StateCount {
+                state,
+                count: tasks.iter().filter(|t| t.state == state).count(),
+            })
+            .filter(|c| c.count > 0)
+            .collect();
+
+        Self {
+            at,
+            history_starts_at,
+            states,
+            tasks,
+        }
+    }
+}
+
+/// A task whose owner changed between two points in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskReassignment {
+    pub code: String,
+    pub name: String,
+    pub from_owner: String,
+    pub to_owner: String,
+}
+
+/// A task whose state changed between two points in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskStateChange {
+    pub code: String,
+    pub name: String,
+    /// State at the start; none for tasks created in between
+    pub from_state: Option<TaskState>,
+    pub to_state: TaskState,
+}
+
+/// A message posted to a task between two points in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExchangedMessage {
+    pub id: i64,
+    pub task_code: Option<String>,
+    pub author_agent_name: Option<String>,
+    pub target_agent_name: Option<String>,
+    pub message_type: Option<String>,
+    pub content: Option<String>,
+    pub posted_at: DateTime<Utc>,
+}
+
+impl From<&AuditEntry> for ExchangedMessage {
+    fn from(entry: &AuditEntry) -> Self {
+        let detail = |key: &str| entry.details[key].as_str().map(str::to_string);
+        Self {
+            id: entry.record_id,
+            task_code: entry.task_code.clone(),
+            author_agent_name: entry.agent_name.clone(),
+            target_agent_name: detail("target_agent_name"),
+            message_type: detail("message_type"),
+            content: detail("content"),
+            posted_at: entry.timestamp,
+        }
+    }
+}
+
+/// What changed on the board between two points in time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceDiff {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Tasks that appeared on the board
+    pub created: Vec<ReportTask>,
+    /// Tasks that became done
+    pub completed: Vec<ReportTask>,
+    /// Tasks handed from one owner to another; claims and releases are state changes
+    pub reassigned: Vec<TaskReassignment>,
+    /// Every task whose state differs, including created ones
+    pub state_changes: Vec<TaskStateChange>,
+    /// Messages posted in between, oldest first
+    pub messages: Vec<ExchangedMessage>,
+    /// Whether more messages were posted than returned
+    pub messages_truncated: bool,
+}
+
+impl WorkspaceDiff {
+    /// Compare the boards at the start and end of a period
+    ///
+    /// `messages` are the message audit entries of the period; when
+    /// `max_messages` is exceeded the rest are dropped and the diff says so.
+    pub fn new(
+        from: &TaskBoard,
+        to: &TaskBoard,
+        messages: &[AuditEntry],
+        max_messages: usize,
+    ) -> Self {
+        let before: HashMap<i32, &Task> = from.tasks.iter().map(|task| (task.id, task)).collect();
+
+        let mut created = Vec::new();
+        let mut completed = Vec::new();
+        let mut reassigned = Vec::new();
+        let mut state_changes = Vec::new();
+        for task in &to.tasks {
+            let previous = before.get(&task.id);
+            if previous.is_none() {
+                created.push(ReportTask::from(task));
+            }
+            if task.state == TaskState::Done
+                && previous.is_none_or(|previous| previous.state != TaskState::Done)
+            {
+                completed.push(ReportTask::from(task));
+            }
+            if let Some(previous) = previous {
+                if let (Some(from_owner), Some(to_owner)) =
+                    (&previous.owner_agent_name, &task.owner_agent_name)
+                {
+                    if from_owner != to_owner {
+                        reassigned.push(TaskReassignment {
+                            code: task.code.clone(),
+                            name: task.name.clone(),
+                            from_owner: from_owner.clone(),
+                            to_owner: to_owner.clone(),
+                        });
+                    }
+                }
+            }
+            let from_state = previous.map(|previous| previous.state);
+            if from_state != Some(task.state) {
+                state_changes.push(TaskStateChange {
+                    code: task.code.clone(),
+                    name: task.name.clone(),
+                    from_state,
+                    to_state: task.state,
+                });
+            }
+        }
+
+        Self {
+            from: from.at,
+            to: to.at,
+            created,
+            completed,
+            reassigned,
+            state_changes,
+            messages: messages
+                .iter()
+                .take(max_messages)
+                .map(ExchangedMessage::from)
+                .collect(),
+            messages_truncated: messages.len() > max_messages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskState;
+    use crate::test_support;
+
+    fn task(id: i32, state: TaskState) -> Task {
+        test_support::task(id, &format!("T-{id}"), state)
+    }
+
+    #[test]
+    fn test_workspace_diff() {
+        let start = Utc::now() - chrono::Duration::hours(2);
+        let end = Utc::now();
+        let mut claimed = task(1, TaskState::InProgress);
+        claimed.owner_agent_name = Some("backend-dev".to_string());
+        let mut handed_over = claimed.clone();
+        handed_over.owner_agent_name = Some("frontend-dev".to_string());
+        handed_over.state = TaskState::Done;
+
+        let from = TaskBoard::new(start, None, vec![claimed, task(2, TaskState::Created)]);
+        let to = TaskBoard::new(
+            end,
+            None,
+            vec![
+                handed_over,
+                task(2, TaskState::Created),
+                task(3, TaskState::Done),
+            ],
+        );
+        let messages: Vec<AuditEntry> = (1..=3)
+            .map(|id| AuditEntry {
+                timestamp: end,
+                source: crate::repository::AuditSource::Message,
+                event: "message_posted".to_string(),
+                record_id: id,
+                task_code: Some("T-1".to_string()),
+                agent_name: Some("backend-dev".to_string()),
+                details: serde_json::json!({"message_type": "handoff", "content": "Over to you"}),
+            })
+            .collect();
+
+        let diff = WorkspaceDiff::new(&from, &to, &messages, 2);
+        let codes = |tasks: &[ReportTask]| tasks.iter().map(|t| t.code.clone()).collect::<Vec<_>>();
+        assert_eq!(codes(&diff.created), ["T-3"]);
+        assert_eq!(codes(&diff.completed), ["T-1", "T-3"]);
+        assert_eq!(diff.reassigned.len(), 1);
+        assert_eq!(diff.reassigned[0].from_owner, "backend-dev");
+        assert_eq!(diff.reassigned[0].to_owner, "frontend-dev");
+        assert_eq!(diff.state_changes.len(), 2);
+        assert_eq!(
+            diff.state_changes[0].from_state,
+            Some(TaskState::InProgress)
+        );
+        assert_eq!(diff.state_changes[1].from_state, None);
+        assert_eq!(diff.messages.len(), 2);
+        assert!(diff.messages_truncated);
+        assert_eq!(diff.messages[0].message_type.as_deref(), Some("handoff"));
+        assert_eq!(diff.messages[0].content.as_deref(), Some("Over to you"));
+    }
+
+    #[test]
+    fn test_task_board_counts_states() {
+        let board = TaskBoard::new(
+            Utc::now(),
+            None,
+            vec![
+                task(3, TaskState::Done),
+                task(1, TaskState::Created),
+                task(2, TaskState::Created),
+            ],
+        );
+
+        let ids: Vec<i32> = board.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(
+            board.states,
+            vec![
+                StateCount {
+                    state: TaskState::Created,
+                    count: 2
+                },
+                StateCount {
+                    state: TaskState::Done,
+                    count: 1
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Two-way sync between the task database and `.axon/tasks.md`
+//!
+//! Some people would rather edit a Markdown file than call tools. Syncing
+//! reads the edits made to `.axon/tasks.md` since it was last written,
+//! applies them to the database and writes the file anew from the database.
+//! Each task is one line; ticking its box marks it done, and changing the
+//! state in parentheses moves it to that state:
+//!
+//! ```markdown
+//! - [ ] API-01: Build API (InProgress, @backend-dev) <!-- axon:InProgress -->
+//! ```
+//!
+//! The trailing comment records the state the line was written with. When a
+//! task changed in the database since then, the database wins and the edit
+//! is reported as a conflict. Edits go through the same checks as tool calls:
+//! state transitions must be valid and the definition of done must be met.
+
+use crate::error::{Result, TaskError};
+use crate::models::{Task, TaskFilter, TaskState};
+use crate::repository::TaskRepository;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::future::Future;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Location of the task file inside the project root
+pub const TASKS_MARKDOWN_FILE: &str = ".axon/tasks.md";
+
+/// A task line of the task file, as edited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownTaskLine {
+    pub task_code: String,
+    pub checked: bool,
+    /// State written in parentheses, possibly edited
+    pub state: String,
+    /// State the line was written with
+    pub written_state: TaskState,
+}
+
+impl MarkdownTaskLine {
+    /// State the edit of the line asks for, if the line was edited
+    ///
+    /// A changed state in parentheses wins over the checkbox. Unticking the
+    /// box of a done task asks to reopen it.
+    pub fn requested_state(&self) -> Result<Option<TaskState>> {
+        let state: TaskState = self.state.parse()?;
+        if state != self.written_state {
+            return Ok(Some(state));
+        }
+        let done = self.written_state == TaskState::Done;
+        Ok(match (self.checked, done) {
+            (true, false) => Some(TaskState::Done),
+            (false, true) => Some(TaskState::InProgress),
+            _ => None,
+        })
+    }
+}
+
+/// A state change applied from the task file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkdownStateEdit {
+    pub task_id: i32,
+    pub task_code: String,
+    pub from: TaskState,
+    pub to: TaskState,
+}
+
+/// An edit discarded because the task changed in the database since the file was written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkdownSyncConflict {
+    pub task_code: String,
+    /// State the edit asked for
+    pub file_state: TaskState,
+    /// State kept from the database
+    pub database_state: TaskState,
+}
+
+/// An edit the database refused, e.g. an invalid transition or an unmet definition of done
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedMarkdownEdit {
+    pub task_code: String,
+    pub reason: String,
+}
+
+/// Outcome of a sync
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TasksMarkdownSync {
+    /// Path of the task file, relative to the project root
+    pub path: String,
+    pub applied: Vec<MarkdownStateEdit>,
+    pub conflicts: Vec<MarkdownSyncConflict>,
+    pub rejected: Vec<RejectedMarkdownEdit>,
+    /// Tasks written to the file
+    pub tasks_written: usize,
+}
+
+fn task_line_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"^\s*[-*]\s+\[([ xX])\]\s+([A-Za-z][A-Za-z0-9-]*):\s.*\(([A-Za-z_ -]+?)(?:,\s*@[^)]*)?\)\s*<!--\s*axon:(\w+)\s*-->\s*$",
+        )
+        .expect("valid task line pattern")
+    })
+}
+
+/// Task lines of a task file; other lines, including tasks added by hand, are ignored
+pub fn parse_tasks_markdown(content: &str) -> Vec<MarkdownTaskLine> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let captures = task_line_pattern().captures(line)?;
+            Some(MarkdownTaskLine {
+                task_code: captures[2].to_string(),
+                checked: &captures[1] != " ",
+                state: captures[3].trim().to_string(),
+                written_state: captures[4].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Task file listing `tasks` by state; archived tasks are left out
+pub fn render_tasks_markdown(tasks: &[Task]) -> String {
+    let mut text = String::from(
+        "# Tasks\n\n\
+         <!-- Written by axon-mcp. Tick a box to mark a task done or change the state in\n\
+         parentheses, then sync. Tasks changed in the database since this file was\n\
+         written keep their database state. -->\n",
+    );
+    for state in TaskState::ALL {
+        let mut in_state: Vec<&Task> = tasks.iter().filter(|t| t.state == state).collect();
+        if state == TaskState::Archived || in_state.is_empty() {
+            continue;
+        }
+        in_state.sort_by(|a, b| a.code.cmp(&b.code));
+        let _ = writeln!(text, "\n## {state}\n");
+        for task in in_state {
+            let owner = task
+                .owner_agent_name
+                .as_deref()
+                .map(|owner| format!(", @{owner}"))
+                .unwrap_or_default();
+            let _ = writeln!(
+                text,
+                "- [{}] {}: {} ({state}{owner}) <!-- axon:{state} -->",
+                if state == TaskState::Done { "x" } else { " " },
+                task.code,
+                task.name.replace('\n', " "),
+            );
+        }
+    }
+    text
+}
+
+/// Apply the edits of the project's task file to the database, then write it anew
+///
+/// Each edit is applied with `change_state`, which moves a task to a state the
+/// way a tool call would, checks included, and returns the changed task.
+pub async fn sync_tasks_markdown<R, F, Fut>(
+    repository: &R,
+    project_root: &Path,
+    mut change_state: F,
+) -> Result<TasksMarkdownSync>
+where
+    R: TaskRepository + ?Sized,
+    F: FnMut(&Task, TaskState) -> Fut,
+    Fut: Future<Output = Result<Task>>,
+{
+    let path = project_root.join(TASKS_MARKDOWN_FILE);
+    let io_error = |e: std::io::Error| TaskError::Internal(format!("{}: {e}", path.display()));
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(io_error(e)),
+    };
+
+    let mut sync = TasksMarkdownSync {
+        path: TASKS_MARKDOWN_FILE.to_string(),
+        ..TasksMarkdownSync::default()
+    };
+    let mut tasks = repository.list(TaskFilter::default()).await?;
+    for line in parse_tasks_markdown(&content) {
+        let requested = match line.requested_state() {
+            Ok(Some(state)) => state,
+            Ok(None) => continue,
+            Err(e) => {
+                sync.rejected.push(RejectedMarkdownEdit {
+                    task_code: line.task_code,
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let Some(task) = tasks.iter_mut().find(|t| t.code == line.task_code) else {
+            continue;
+        };
+        if task.state != line.written_state {
+            sync.conflicts.push(MarkdownSyncConflict {
+                task_code: line.task_code,
+                file_state: requested,
+                database_state: task.state,
+            });
+            continue;
+        }
+        match change_state(task, requested).await {
+            Ok(changed) => {
+                sync.applied.push(MarkdownStateEdit {
+                    task_id: task.id,
+                    task_code: line.task_code,
+                    from: task.state,
+                    to: changed.state,
+                });
+                *task = changed;
+            }
+            Err(e) => sync.rejected.push(RejectedMarkdownEdit {
+                task_code: line.task_code,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    let rendered = render_tasks_markdown(&tasks);
+    sync.tasks_written = tasks
+        .iter()
+        .filter(|t| t.state != TaskState::Archived)
+        .count();
+    if rendered != content {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        std::fs::write(&path, rendered).map_err(io_error)?;
+    }
+    Ok(sync)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(code: &str, name: &str, state: TaskState) -> Task {
+        Task {
+            name: name.to_string(),
+            owner_agent_name: Some("backend-dev".to_string()),
+            ..test_support::task(1, code, state)
+        }
+    }
+
+    #[test]
+    fn test_render_and_parse() {
+        let tasks = [
+            task("API-01", "Build API (v2)", TaskState::InProgress),
+            task("API-02", "Docs", TaskState::Done),
+            task("API-03", "Old", TaskState::Archived),
+        ];
+        let rendered = render_tasks_markdown(&tasks);
+        assert!(rendered.contains("\n## InProgress\n"));
+        assert!(!rendered.contains("API-03"));
+
+        let lines = parse_tasks_markdown(&rendered);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].task_code, "API-01");
+        assert!(lines[1].checked);
+        assert!(lines.iter().all(|l| l.requested_state().unwrap().is_none()));
+    }
+
+    #[test]
+    fn test_requested_state() {
+        let edited =
+            "- [x] API-01: Build API (v2) (InProgress, @backend-dev) <!-- axon:InProgress -->\n\
+                      - [ ] API-02: Docs (review) <!-- axon:InProgress -->\n\
+                      - [ ] API-03: Tests (Done) <!-- axon:Done -->\n\
+                      - [ ] API-04: Bench (Finished) <!-- axon:InProgress -->\n\
+                      - [ ] NEW-1: Added by hand\n";
+        let lines = parse_tasks_markdown(edited);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].requested_state().unwrap(), Some(TaskState::Done));
+        assert_eq!(lines[1].requested_state().unwrap(), Some(TaskState::Review));
+        assert_eq!(
+            lines[2].requested_state().unwrap(),
+            Some(TaskState::InProgress)
+        );
+        assert!(lines[3].requested_state().is_err());
+    }
+}
@@ -0,0 +1,23 @@
+//! Fixtures shared by the unit tests of this crate
+
+use crate::models::{Task, TaskState};
+use chrono::{DateTime, Utc};
+
+/// Unowned task named after its code, created now
+pub(crate) fn task(id: i32, code: &str, state: TaskState) -> Task {
+    task_at(id, code, state, Utc::now())
+}
+
+/// Unowned task named after its code, created at `at`
+pub(crate) fn task_at(id: i32, code: &str, state: TaskState, at: DateTime<Utc>) -> Task {
+    Task::new(
+        id,
+        code.to_string(),
+        format!("Task {code}"),
+        String::new(),
+        None,
+        state,
+        at,
+        None,
+    )
+}
@@ -0,0 +1,136 @@
+//! Line-based unified diffs
+//!
+//! Used to show how a regenerated artifact (e.g. an agent prompt) differs from
+//! the stored one. Texts are compared line by line through their longest
+//! common subsequence, which is plenty for documents of a few hundred lines.
+
+/// Unchanged lines shown around each change
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Unified diff from `old` to `new`, empty when the texts have the same lines
+///
+/// Hunks start with `@@ -old_start,old_len +new_start,new_len @@` and prefix
+/// lines with ` `, `-` or `+`.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old, &new);
+    if ops.iter().all(|op| *op == Op::Keep) {
+        return String::new();
+    }
+
+    // Line position in both texts before each op
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        positions.push((o, n));
+        match op {
+            Op::Keep => {
+                o += 1;
+                n += 1;
+            }
+            Op::Remove => o += 1,
+            Op::Add => n += 1,
+        }
+    }
+    positions.push((o, n));
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&i| ops[i] != Op::Keep).collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(DIFF_CONTEXT_LINES);
+        let end = (i + 1 + DIFF_CONTEXT_LINES).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::new();
+    for (start, end) in hunks {
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end),
+            hunk_range(new_start, new_end)
+        ));
+        for i in start..end {
+            let (o, n) = positions[i];
+            let (prefix, line) = match ops[i] {
+                Op::Keep => (' ', old[o]),
+                Op::Remove => ('-', old[o]),
+                Op::Add => ('+', new[n]),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// `start,length` of a hunk side, with `start` 1-based unless the side is empty
+fn hunk_range(start: usize, end: usize) -> String {
+    let first = if end > start { start + 1 } else { start };
+    format!("{first},{}", end - start)
+}
+
+/// Edit script turning `old` into `new`, removals before additions
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+    // lcs[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push(Op::Keep);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Remove);
+            i += 1;
+        } else {
+            ops.push(Op::Add);
+            j += 1;
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb"), "");
+
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n12\n13\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+
+        assert_eq!(unified_diff("", "new\n"), "@@ -0,0 +1,1 @@\n+new\n");
+    }
+}
@@ -0,0 +1,268 @@
+//! Rolling summaries of long message threads
+//!
+//! Once a task has more than [`ThreadSummaryPolicy::threshold`] messages, all
+//! but the most recent ones are condensed by a [`ThreadSummarizer`] into a
+//! stored summary. The summary is extended with each batch of messages that
+//! falls out of the recent window, so agents read the summary and a few
+//! recent messages instead of the whole thread.
+
+use crate::error::Result;
+use crate::models::TaskMessage;
+use crate::repository::TaskMessageRepository;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// When threads are summarized and how much of them stays verbatim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreadSummaryPolicy {
+    /// Messages a thread may have before older ones are summarized (0 disables summaries)
+    pub threshold: u32,
+    /// Most recent messages returned verbatim next to the summary
+    pub keep_recent: u32,
+}
+
+impl Default for ThreadSummaryPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: 30,
+            keep_recent: 10,
+        }
+    }
+}
+
+/// Stored summary of the older messages of a task thread
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadSummary {
+    pub task_code: String,
+    pub summary: String,
+    /// Number of messages the summary covers
+    pub message_count: u32,
+    /// Newest message covered by the summary
+    pub last_message_id: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Summary of the older messages of a thread followed by its recent messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskThread {
+    pub task_code: String,
+    /// Summary of the messages before `messages`; none while the thread is short
+    pub summary: Option<ThreadSummary>,
+    /// Messages not covered by the summary, newest first
+    pub messages: Vec<TaskMessage>,
+    /// Messages in the whole thread
+    pub total_messages: u32,
+}
+
+/// Condenses messages into a summary
+#[async_trait]
+pub trait ThreadSummarizer: Send + Sync {
+    /// Extend `previous` with `messages`, given oldest first
+    async fn summarize(
+        &self,
+        task_code: &str,
+        previous: Option<&str>,
+        messages: &[TaskMessage],
+    ) -> Result<String>;
+}
+
+/// Summarizer keeping the first sentence of each message, without any model
+#[derive(Debug, Clone)]
+pub struct ExtractiveSummarizer {
+    /// Longest excerpt kept per message, in characters
+    pub max_excerpt_chars: usize,
+    /// Lines kept in the summary; older lines are collapsed into a count
+    pub max_lines: usize,
+}
+
+impl Default for ExtractiveSummarizer {
+    fn default() -> Self {
+        Self {
+            max_excerpt_chars: 160,
+            max_lines: 40,
+        }
+    }
+}
+
+impl ExtractiveSummarizer {
+    fn excerpt(&self, content: &str) -> String {
+        let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        let sentence = content
+            .find(". ")
+            .map_or(content.as_str(), |end| &content[..=end]);
+        if sentence.chars().count() <= self.max_excerpt_chars {
+            return sentence.to_string();
+        }
+        let mut excerpt: String = sentence.chars().take(self.max_excerpt_chars).collect();
+        excerpt.push('…');
+        excerpt
+    }
+}
+
+/// Prefix of the line counting collapsed summary lines
+const EARLIER_PREFIX: &str = "- (";
+
+#[async_trait]
+impl ThreadSummarizer for ExtractiveSummarizer {
+    async fn summarize(
+        &self,
+        _task_code: &str,
+        previous: Option<&str>,
+        messages: &[TaskMessage],
+    ) -> Result<String> {
+        let mut earlier = 0usize;
+        let mut lines: Vec<String> = Vec::new();
+        for line in previous.unwrap_or_default().lines() {
+            match line
+                .strip_prefix(EARLIER_PREFIX)
+                .and_then(|rest| rest.split(' ').next())
+                .and_then(|count| count.parse::<usize>().ok())
+            {
+                Some(count) => earlier += count,
+                None => lines.push(line.to_string()),
+            }
+        }
+        for message in messages {
+            let mut line = format!("- [{}] {}", message.message_type, message.author_agent_name);
+            if let Some(ref target) = message.target_agent_name {
+                let _ = write!(line, " -> {target}");
+            }
+            let _ = write!(line, ": {}", self.excerpt(&message.content));
+            lines.push(line);
+        }
+        if lines.len() > self.max_lines {
+            let dropped = lines.len() - self.max_lines;
+            earlier += dropped;
+            lines.drain(..dropped);
+        }
+        if earlier > 0 {
+            lines.insert(0, format!("{EARLIER_PREFIX}{earlier} earlier message(s))"));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Thread of `task_code` with everything but the recent messages summarized
+///
+/// The stored summary is extended with messages that left the recent window
+/// since it was last updated.
+pub async fn summarize_thread<M: TaskMessageRepository + ?Sized>(
+    repository: &M,
+    summarizer: &dyn ThreadSummarizer,
+    policy: &ThreadSummaryPolicy,
+    task_code: &str,
+) -> Result<TaskThread> {
+    let mut messages = repository
+        .get_messages(task_code, None, None, None, None, None)
+        .await?;
+    let total_messages = messages.len() as u32;
+    if policy.threshold == 0
+        || total_messages <= policy.threshold
+        || total_messages <= policy.keep_recent
+    {
+        return Ok(TaskThread {
+            task_code: task_code.to_string(),
+            summary: None,
+            messages,
+            total_messages,
+        });
+    }
+
+    // Messages come newest first; everything past the recent window is summarized
+    let mut older = messages.split_off((policy.keep_recent as usize).min(messages.len()));
+    older.reverse();
+    let stored = repository.get_thread_summary(task_code).await?;
+    let last_summarized = stored.as_ref().map_or(0, |s| s.last_message_id);
+    let pending: Vec<TaskMessage> = older
+        .into_iter()
+        .filter(|m| m.id > last_summarized)
+        .collect();
+
+    let summary = match pending.last() {
+        Some(newest) => {
+            let previous = stored.as_ref().map(|s| s.summary.as_str());
+            let summary = ThreadSummary {
+                task_code: task_code.to_string(),
+                summary: summarizer.summarize(task_code, previous, &pending).await?,
+                message_count: stored.as_ref().map_or(0, |s| s.message_count)
+                    + pending.len() as u32,
+                last_message_id: newest.id,
+                updated_at: Utc::now(),
+            };
+            repository.save_thread_summary(&summary).await?;
+            Some(summary)
+        }
+        None => stored,
+    };
+
+    // The summary may cover messages of the recent window after deletions
+    if let Some(ref summary) = summary {
+        messages.retain(|m| m.id > summary.last_message_id);
+    }
+    Ok(TaskThread {
+        task_code: task_code.to_string(),
+        summary,
+        messages,
+        total_messages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: i32, content: &str) -> TaskMessage {
+        TaskMessage {
+            id,
+            uid: None,
+            task_code: "API-01".to_string(),
+            author_agent_name: "backend-dev".to_string(),
+            target_agent_name: None,
+            message_type: "comment".to_string(),
+            created_at: Utc::now(),
+            content: content.to_string(),
+            reply_to_message_id: None,
+            structured_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extractive_summarizer() {
+        let summarizer = ExtractiveSummarizer {
+            max_excerpt_chars: 20,
+            max_lines: 2,
+        };
+        let first = summarizer
+            .summarize(
+                "API-01",
+                None,
+                &[
+                    message(1, "Schema is done. Migrations next."),
+                    message(2, "A very long message without any sentence break"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            first,
+            "- [comment] backend-dev: Schema is done.\n\
+             - [comment] backend-dev: A very long message …"
+        );
+
+        let rolled = summarizer
+            .summarize("API-01", Some(&first), &[message(3, "Deployed")])
+            .await
+            .unwrap();
+        assert!(rolled.starts_with("- (1 earlier message(s))\n- [comment] backend-dev: A very"));
+        assert!(rolled.ends_with("Deployed"));
+
+        let rolled = summarizer
+            .summarize("API-01", Some(&rolled), &[message(4, "Verified")])
+            .await
+            .unwrap();
+        assert!(rolled.starts_with("- (2 earlier message(s))\n"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Timeline of tasks for visualization
+//!
+//! Renders when tasks were created, claimed and finished, who owns them and
+//! which subtasks gate them, as structured data or as a Mermaid gantt chart.
+
+use crate::models::{Task, TaskState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Output format of a timeline export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineFormat {
+    /// Structured entries only
+    #[default]
+    Json,
+    /// Structured entries plus Mermaid gantt text
+    Mermaid,
+}
+
+/// One task on the timeline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimelineEntry {
+    pub task_id: i32,
+    pub task_code: String,
+    pub name: String,
+    pub state: TaskState,
+    pub owner_agent_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub done_at: Option<DateTime<Utc>>,
+    /// Parent task this one is a subtask of
+    pub parent_task_code: Option<String>,
+    /// Subtasks that gate this task
+    pub depends_on: Vec<String>,
+}
+
+/// Tasks ordered by creation, with optional Mermaid gantt text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Timeline {
+    /// When the timeline was taken; unfinished bars end here
+    pub as_of: DateTime<Utc>,
+    pub tasks: Vec<TimelineEntry>,
+    /// Gantt chart, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mermaid: Option<String>,
+}
+
+impl Timeline {
+    /// Build the timeline of `tasks` as of `as_of`
+    pub fn new(tasks: &[Task], as_of: DateTime<Utc>, format: TimelineFormat) -> Self {
+        let code_of = |id: i32| tasks.iter().find(|t| t.id == id).map(|t| t.code.clone());
+        let mut entries: Vec<TimelineEntry> = tasks
+            .iter()
+            .map(|task| TimelineEntry {
+                task_id: task.id,
+                task_code: task.code.clone(),
+                name: task.name.clone(),
+                state: task.state,
+                owner_agent_name: task.owner_agent_name.clone(),
+                created_at: task.inserted_at,
+                claimed_at: task.claimed_at,
+                done_at: task.done_at,
+                parent_task_code: task.parent_task_id.and_then(code_of),
+                depends_on: tasks
+                    .iter()
+                    .filter(|child| child.parent_task_id == Some(task.id) && child.id != task.id)
+                    .map(|child| child.code.clone())
+                    .collect(),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then(a.task_id.cmp(&b.task_id))
+        });
+
+        let mut timeline = Self {
+            as_of,
+            tasks: entries,
+            mermaid: None,
+        };
+        if format == TimelineFormat::Mermaid {
+            timeline.mermaid = Some(timeline.to_mermaid());
+        }
+        timeline
+    }
+
+    /// Render the timeline as a Mermaid gantt chart with one section per owner
+    ///
+    /// Bars run from the claim (or creation, for unclaimed tasks) to
+    /// completion, or to `as_of` for unfinished tasks.
+    pub fn to_mermaid(&self) -> String {
+        const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+        let mut owners: Vec<Option<&str>> = Vec::new();
+        for entry in &self.tasks {
+            let owner = entry.owner_agent_name.as_deref();
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+
+        let mut out = String::from("gantt\n");
+        out.push_str("    title Task timeline\n");
+        out.push_str("    dateFormat YYYY-MM-DD HH:mm:ss\n");
+        out.push_str("    axisFormat %m-%d %H:%M\n");
+        for owner in owners {
+            out.push_str(&format!(
+                "    section {}\n",
+                mermaid_text(owner.unwrap_or("unassigned"))
+            ));
+            for entry in self
+                .tasks
+                .iter()
+                .filter(|e| e.owner_agent_name.as_deref() == owner)
+            {
+                let start = entry.claimed_at.unwrap_or(entry.created_at);
+                let end = entry.done_at.unwrap_or(self.as_of).max(start);
+                let tag = match entry.state {
+                    TaskState::Done | TaskState::Archived => "done, ",
+                    TaskState::InProgress | TaskState::Review => "active, ",
+                    TaskState::Blocked | TaskState::Quarantined => "crit, ",
+                    _ => "",
+                };
+                out.push_str(&format!(
+                    "    {} {} :{}t{}, {}, {}\n",
+                    mermaid_text(&entry.task_code),
+                    mermaid_text(&entry.name),
+                    tag,
+                    entry.task_id,
+                    start.format(DATE_FORMAT),
+                    end.format(DATE_FORMAT)
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Strip characters with a meaning in Mermaid gantt lines
+fn mermaid_text(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if matches!(c, ':' | ';' | '#' | '\n' | '\r') {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_timeline_with_mermaid() {
+        let now = Utc::now();
+        let epic = Task::new(
+            1,
+            "EPIC-01".to_string(),
+            "Release: v1".to_string(),
+            "Ship it".to_string(),
+            None,
+            TaskState::Created,
+            now - Duration::hours(3),
+            None,
+        );
+        let mut api = Task::new(
+            2,
+            "API-01".to_string(),
+            "Build API".to_string(),
+            "Endpoints".to_string(),
+            Some("backend-dev".to_string()),
+            TaskState::Done,
+            now - Duration::hours(2),
+            Some(now - Duration::minutes(30)),
+        );
+        api.parent_task_id = Some(1);
+        api.claimed_at = Some(now - Duration::hours(1));
+
+        let timeline = Timeline::new(&[api, epic], now, TimelineFormat::Mermaid);
+        assert_eq!(timeline.tasks[0].task_code, "EPIC-01");
+        assert_eq!(timeline.tasks[0].depends_on, vec!["API-01".to_string()]);
+        assert_eq!(
+            timeline.tasks[1].parent_task_code.as_deref(),
+            Some("EPIC-01")
+        );
+
+        let mermaid = timeline.mermaid.unwrap();
+        assert!(mermaid.starts_with("gantt\n"));
+        assert!(mermaid.contains("    section unassigned\n    EPIC-01 Release  v1 :t1, "));
+        assert!(mermaid.contains("    section backend-dev\n    API-01 Build API :done, t2, "));
+
+        let json_only = Timeline::new(&[], now, TimelineFormat::Json);
+        assert!(json_only.mermaid.is_none());
+    }
+}
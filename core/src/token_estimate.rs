@@ -0,0 +1,156 @@
+//! Token-count estimates for generated prompts and files
+//!
+//! Oversized coordination files and agent prompts crowd out the context an
+//! agent has left for its actual work. Text is split the way BPE tokenizers
+//! pre-tokenize it (words with their leading space, digit groups, punctuation
+//! runs, line breaks) and each piece is charged the tokens such tokenizers
+//! typically spend on it. The counts are approximate, but close enough on
+//! prose, Markdown and JSON to tell when a budget is exceeded.
+
+use crate::task_context::DEFAULT_CONTEXT_TOKEN_BUDGET;
+use serde::{Deserialize, Serialize};
+
+/// Letters of a word covered by one token
+const LETTERS_PER_TOKEN: usize = 6;
+
+/// Digits covered by one token
+const DIGITS_PER_TOKEN: usize = 3;
+
+/// Punctuation characters covered by one token
+const SYMBOLS_PER_TOKEN: usize = 3;
+
+/// Approximate number of tokens in `text`
+pub fn estimate_tokens(text: &str) -> u32 {
+    let mut tokens = 0usize;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphabetic() {
+            let (mut ascii, mut other) = (usize::from(c.is_ascii()), usize::from(!c.is_ascii()));
+            while let Some(&next) = chars.peek().filter(|next| next.is_alphabetic()) {
+                if next.is_ascii() {
+                    ascii += 1;
+                } else {
+                    other += 1;
+                }
+                chars.next();
+            }
+            // Non-ASCII letters rarely merge into longer tokens
+            tokens += ascii
+                .div_ceil(LETTERS_PER_TOKEN)
+                .max(usize::from(other == 0))
+                + other;
+        } else if c.is_ascii_digit() {
+            let mut digits = 1usize;
+            while chars.next_if(char::is_ascii_digit).is_some() {
+                digits += 1;
+            }
+            tokens += digits.div_ceil(DIGITS_PER_TOKEN);
+        } else if c == '\n' || c == '\r' {
+            while chars.next_if(|next| next.is_whitespace()).is_some() {}
+            tokens += 1;
+        } else if c.is_whitespace() {
+            // A single space is part of the following word; indentation is a token of its own
+            let mut spaces = 1;
+            while chars
+                .next_if(|next| *next == ' ' || *next == '\t')
+                .is_some()
+            {
+                spaces += 1;
+            }
+            if spaces > 1 {
+                tokens += 1;
+            }
+        } else if c.is_ascii() {
+            let mut symbols = 1usize;
+            while chars.next_if(char::is_ascii_punctuation).is_some() {
+                symbols += 1;
+            }
+            tokens += symbols.div_ceil(SYMBOLS_PER_TOKEN);
+        } else {
+            // Emoji and other symbols take a token per UTF-8 byte pair
+            tokens += c.len_utf8().div_ceil(2);
+        }
+    }
+    tokens as u32
+}
+
+/// Token budgets of generated files, prompts and context bundles
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenBudgets {
+    /// Main AI coordination file, e.g. CLAUDE.md
+    pub main_ai_file: u32,
+    /// A single agent prompt
+    pub agent_prompt: u32,
+    /// `get_task_context` bundles requested without a budget of their own
+    pub task_context: u32,
+}
+
+impl Default for TokenBudgets {
+    fn default() -> Self {
+        Self {
+            main_ai_file: 8_000,
+            agent_prompt: 3_000,
+            task_context: DEFAULT_CONTEXT_TOKEN_BUDGET,
+        }
+    }
+}
+
+/// Estimated size of a generated text compared with its budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenEstimate {
+    pub tokens: u32,
+    pub budget: u32,
+    pub over_budget: bool,
+}
+
+impl TokenEstimate {
+    /// Estimate `text` against `budget`
+    pub fn new(text: &str, budget: u32) -> Self {
+        let tokens = estimate_tokens(text);
+        Self {
+            tokens,
+            budget,
+            over_budget: tokens > budget,
+        }
+    }
+
+    /// Warning about `subject` exceeding its budget, if it does
+    pub fn warning(&self, subject: &str) -> Option<String> {
+        self.over_budget.then(|| {
+            format!(
+                "{subject} is about {} tokens, over its budget of {}; \
+                 oversized coordination files degrade agent performance",
+                self.tokens, self.budget
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Hello world"), 2);
+        assert_eq!(estimate_tokens("coordination 2025"), 4);
+        assert_eq!(estimate_tokens("=== ROLE ===\n"), 4);
+        assert_eq!(estimate_tokens(r#"{"id": 1}"#), 5);
+        assert_eq!(estimate_tokens("čeština 🎯"), 5);
+
+        let prose = "Agents claim tasks, record their work in sessions and hand results \
+                     over to each other through task messages. ";
+        let tokens = estimate_tokens(&prose.repeat(10));
+        assert!((200..=260).contains(&tokens), "{tokens}");
+
+        let estimate = TokenEstimate::new(&prose.repeat(10), 100);
+        assert!(estimate.over_budget);
+        assert!(estimate
+            .warning("CLAUDE.md")
+            .unwrap()
+            .contains("budget of 100"));
+        assert_eq!(TokenEstimate::new(prose, 100).warning("CLAUDE.md"), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! Which tools each agent calls, and how often
+//!
+//! Every tool call made by an identified agent is recorded with its duration
+//! and outcome. Counting them per agent and tool shows where tokens go: an
+//! agent calling `list_tasks` hundreds of times an hour is polling instead of
+//! working, and one whose calls mostly fail is stuck.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tools listed per agent in performance reports
+pub const TOP_TOOLS_PER_AGENT: usize = 5;
+
+/// Maximum number of rows returned by one `get_tool_usage_stats` call
+pub const MAX_TOOL_USAGE_STATS: u32 = 500;
+
+/// One tool call of an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub agent_name: String,
+    pub tool_name: String,
+    pub called_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Whether the call answered with a JSON-RPC error
+    pub is_error: bool,
+}
+
+/// Which tool calls to count
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolUsageFilter {
+    pub agent_name: Option<String>,
+    pub tool_name: Option<String>,
+    /// Only calls at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only calls before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Maximum number of agent and tool pairs
+    pub limit: Option<u32>,
+}
+
+/// Calls of one tool by one agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolUsageStat {
+    pub agent_name: String,
+    pub tool_name: String,
+    pub calls: u64,
+    /// Calls that answered with an error
+    pub errors: u64,
+    pub avg_duration_ms: f64,
+    pub first_called_at: DateTime<Utc>,
+    pub last_called_at: DateTime<Utc>,
+}
+
+impl ToolUsageStat {
+    /// Average calls per minute between the first and the last call
+    pub fn calls_per_minute(&self) -> f64 {
+        let minutes = (self.last_called_at - self.first_called_at).num_seconds() as f64 / 60.0;
+        self.calls as f64 / minutes.max(1.0)
+    }
+}
+
+/// The `limit` most called tools of `agent_name`, most called first
+pub fn top_tools(stats: &[ToolUsageStat], agent_name: &str, limit: usize) -> Vec<ToolUsageStat> {
+    let mut tools: Vec<ToolUsageStat> = stats
+        .iter()
+        .filter(|stat| stat.agent_name == agent_name)
+        .cloned()
+        .collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then(a.tool_name.cmp(&b.tool_name)));
+    tools.truncate(limit);
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn stat(agent_name: &str, tool_name: &str, calls: u64) -> ToolUsageStat {
+        let now = Utc::now();
+        ToolUsageStat {
+            agent_name: agent_name.to_string(),
+            tool_name: tool_name.to_string(),
+            calls,
+            errors: 0,
+            avg_duration_ms: 5.0,
+            first_called_at: now - Duration::minutes(10),
+            last_called_at: now,
+        }
+    }
+
+    #[test]
+    fn test_top_tools() {
+        let stats = vec![
+            stat("backend-dev", "claim_task", 2),
+            stat("backend-dev", "list_tasks", 300),
+            stat("frontend-dev", "list_tasks", 900),
+            stat("backend-dev", "get_task_by_id", 40),
+        ];
+        let top = top_tools(&stats, "backend-dev", 2);
+        let names: Vec<&str> = top.iter().map(|s| s.tool_name.as_str()).collect();
+        assert_eq!(names, ["list_tasks", "get_task_by_id"]);
+        assert_eq!(top[0].calls_per_minute(), 30.0);
+        assert!(top_tools(&stats, "tester", 5).is_empty());
+    }
+}
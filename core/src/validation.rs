@@ -2,6 +2,150 @@ use crate::{
     error::{Result, TaskError},
     models::{NewTask, Task, TaskState},
 };
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Maximum length of a task comment in characters
+pub const MAX_COMMENT_LENGTH: usize = 10_000;
+
+/// Maximum size of a message's structured data in bytes of serialized JSON
+pub const MAX_MESSAGE_DATA_SIZE: usize = 16_384;
+
+/// Policy installed by [`AgentNamePolicy::install`]; the default until then
+static AGENT_NAME_POLICY: RwLock<Option<AgentNamePolicy>> = RwLock::new(None);
+
+/// Rules for agent identifiers, applied wherever an agent name enters the system
+///
+/// Names must start and end with a letter or digit. Between them, only letters,
+/// digits and the configured separators are allowed. The default accepts the
+/// `kebab-case` and `snake_case` names the built-in agent templates use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentNamePolicy {
+    /// Minimum length in characters
+    pub min_length: usize,
+    /// Maximum length in characters
+    pub max_length: usize,
+    /// Whether uppercase letters are allowed
+    pub allow_uppercase: bool,
+    /// Characters allowed between letters and digits
+    pub separators: String,
+    /// Names no agent may use, compared case-insensitively
+    pub reserved_names: Vec<String>,
+}
+
+impl Default for AgentNamePolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 1,
+            max_length: 50,
+            allow_uppercase: true,
+            separators: "-_".to_string(),
+            reserved_names: Vec::new(),
+        }
+    }
+}
+
+impl AgentNamePolicy {
+    /// Strict `kebab-case`: lowercase letters, digits and hyphens
+    pub fn kebab_case() -> Self {
+        Self {
+            allow_uppercase: false,
+            separators: "-".to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Make this the policy used by [`TaskValidator::validate_agent_name`]
+    pub fn install(self) {
+        *AGENT_NAME_POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(self);
+    }
+
+    /// The installed policy, or the default if none was installed
+    pub fn current() -> Self {
+        AGENT_NAME_POLICY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Check `name` against the policy
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the name is valid
+    /// * `Err(TaskError::Validation)` - If the name is invalid or reserved
+    pub fn validate(&self, name: &str) -> Result<()> {
+        if name.is_empty() {
+            return Err(TaskError::empty_field("agent_name"));
+        }
+
+        let length = name.chars().count();
+        if length < self.min_length {
+            return Err(TaskError::Validation(format!(
+                "Agent name must be at least {} characters long",
+                self.min_length
+            )));
+        }
+        if length > self.max_length {
+            return Err(TaskError::Validation(format!(
+                "Agent name must be at most {} characters long",
+                self.max_length
+            )));
+        }
+
+        // Must start and end with alphanumeric characters
+        if !name.chars().next().is_some_and(char::is_alphanumeric) {
+            return Err(TaskError::Validation(
+                "Agent name must start with a letter or number".to_string(),
+            ));
+        }
+        if !name.chars().last().is_some_and(char::is_alphanumeric) {
+            return Err(TaskError::Validation(
+                "Agent name must end with a letter or number".to_string(),
+            ));
+        }
+
+        if !self.allow_uppercase && name.chars().any(char::is_uppercase) {
+            return Err(TaskError::Validation(
+                "Agent name must not contain uppercase letters".to_string(),
+            ));
+        }
+
+        let valid_chars = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || self.separators.contains(c));
+        if !valid_chars {
+            return Err(TaskError::Validation(format!(
+                "Agent name can only contain letters, numbers and {}",
+                self.describe_separators()
+            )));
+        }
+
+        if self
+            .reserved_names
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(name))
+        {
+            return Err(TaskError::Validation(format!(
+                "Agent name '{name}' is reserved"
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn describe_separators(&self) -> String {
+        if self.separators.is_empty() {
+            return "no separators".to_string();
+        }
+        self.separators
+            .chars()
+            .map(|c| format!("'{c}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
 
 /// Validation utilities for task management operations
 pub struct TaskValidator;
@@ -70,9 +214,9 @@ impl TaskValidator {
         Ok(())
     }
 
-    /// Validate an agent name
+    /// Validate an agent name against the installed [`AgentNamePolicy`]
     ///
-    /// Agent names must:
+    /// With the default policy, agent names must:
     /// - Be 1-50 characters long
     /// - Contain only letters, numbers, hyphens, and underscores
     /// - Not start or end with special characters
@@ -84,44 +228,7 @@ impl TaskValidator {
     /// * `Ok(())` - If the name is valid
     /// * `Err(TaskError::Validation)` - If the name is invalid
     pub fn validate_agent_name(name: &str) -> Result<()> {
-        if name.is_empty() {
-            return Err(TaskError::empty_field("agent_name"));
-        }
-
-        if name.len() > 50 {
-            return Err(TaskError::Validation(
-                "Agent name must be at most 50 characters long".to_string(),
-            ));
-        }
-
-        // Must start and end with alphanumeric characters
-        let first_char = name.chars().next().unwrap();
-        let last_char = name.chars().last().unwrap();
-
-        if !first_char.is_alphanumeric() {
-            return Err(TaskError::Validation(
-                "Agent name must start with a letter or number".to_string(),
-            ));
-        }
-
-        if !last_char.is_alphanumeric() {
-            return Err(TaskError::Validation(
-                "Agent name must end with a letter or number".to_string(),
-            ));
-        }
-
-        // Only allow letters, numbers, hyphens, and underscores
-        let valid_chars = name
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
-        if !valid_chars {
-            return Err(TaskError::Validation(
-                "Agent name can only contain letters, numbers, hyphens, and underscores"
-                    .to_string(),
-            ));
-        }
-
-        Ok(())
+        AgentNamePolicy::current().validate(name)
     }
 
     /// Validate a task name
@@ -180,6 +287,80 @@ impl TaskValidator {
         Ok(())
     }
 
+    /// Validate the markdown content of a task comment
+    ///
+    /// Comment content must:
+    /// - Not be empty or only whitespace
+    /// - Be at most [`MAX_COMMENT_LENGTH`] characters long
+    ///
+    /// # Arguments
+    /// * `content` - The comment content to validate
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the content is valid
+    /// * `Err(TaskError::Validation)` - If the content is invalid
+    pub fn validate_comment_content(content: &str) -> Result<()> {
+        if content.trim().is_empty() {
+            return Err(TaskError::empty_field("content"));
+        }
+
+        if content.chars().count() > MAX_COMMENT_LENGTH {
+            return Err(TaskError::Validation(format!(
+                "Comment must be at most {MAX_COMMENT_LENGTH} characters long"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Validate the structured data of a task message
+    ///
+    /// Structured data must be a JSON object of at most
+    /// [`MAX_MESSAGE_DATA_SIZE`] bytes. Well-known message types also require
+    /// specific fields:
+    /// - `blocker` - `blocking_task_code`, a valid task code
+    /// - `handoff` - `to_agent_name`, a valid agent name
+    ///
+    /// Other message types accept any object.
+    ///
+    /// # Arguments
+    /// * `message_type` - Type of the message carrying the data
+    /// * `data` - The structured data to validate
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the data is valid
+    /// * `Err(TaskError::Validation)` - If the data is invalid
+    pub fn validate_message_data(message_type: &str, data: &serde_json::Value) -> Result<()> {
+        let Some(object) = data.as_object() else {
+            return Err(TaskError::Validation(
+                "structured_data must be a JSON object".to_string(),
+            ));
+        };
+
+        if data.to_string().len() > MAX_MESSAGE_DATA_SIZE {
+            return Err(TaskError::Validation(format!(
+                "structured_data must be at most {MAX_MESSAGE_DATA_SIZE} bytes"
+            )));
+        }
+
+        let required_string = |field: &str| {
+            object
+                .get(field)
+                .and_then(|value| value.as_str())
+                .ok_or_else(|| {
+                    TaskError::Validation(format!(
+                        "{message_type} messages require a string structured_data.{field}"
+                    ))
+                })
+        };
+
+        match message_type {
+            "blocker" => Self::validate_task_code(required_string("blocking_task_code")?),
+            "handoff" => Self::validate_agent_name(required_string("to_agent_name")?),
+            _ => Ok(()),
+        }
+    }
+
     /// Validate a complete NewTask structure
     ///
     /// # Arguments
@@ -286,6 +467,25 @@ mod tests {
         assert!(TaskValidator::validate_agent_name("agent 123").is_err());
     }
 
+    #[test]
+    fn test_agent_name_policy() {
+        let kebab = AgentNamePolicy::kebab_case();
+        assert!(kebab.validate("rust-architect").is_ok());
+        assert!(kebab.validate("rust_architect").is_err());
+        assert!(kebab.validate("Rust-Architect").is_err());
+
+        let policy = AgentNamePolicy {
+            min_length: 3,
+            separators: "-.".to_string(),
+            reserved_names: vec!["coordinator".to_string()],
+            ..Default::default()
+        };
+        assert!(policy.validate("team.backend-dev").is_ok());
+        assert!(policy.validate("qa").is_err());
+        assert!(policy.validate("agent_1").is_err());
+        assert!(policy.validate("Coordinator").is_err());
+    }
+
     #[test]
     fn test_valid_task_names() {
         assert!(TaskValidator::validate_task_name("Simple task").is_ok());
@@ -326,6 +526,17 @@ mod tests {
         assert!(TaskValidator::validate_task_description(&"a".repeat(2001)).is_err());
     }
 
+    #[test]
+    fn test_comment_content_limits() {
+        assert!(TaskValidator::validate_comment_content("**Looks good**, merging.").is_ok());
+        assert!(TaskValidator::validate_comment_content(&"ř".repeat(MAX_COMMENT_LENGTH)).is_ok());
+
+        assert!(TaskValidator::validate_comment_content("  \n ").is_err());
+        assert!(
+            TaskValidator::validate_comment_content(&"a".repeat(MAX_COMMENT_LENGTH + 1)).is_err()
+        );
+    }
+
     #[test]
     fn test_validate_new_task() {
         let valid_task = NewTask::new(
@@ -347,6 +558,33 @@ mod tests {
         assert!(TaskValidator::validate_new_task(&invalid_task).is_err());
     }
 
+    #[test]
+    fn test_validate_message_data() {
+        use serde_json::json;
+
+        assert!(TaskValidator::validate_message_data(
+            "blocker",
+            &json!({"blocking_task_code": "DB-12"})
+        )
+        .is_ok());
+        assert!(TaskValidator::validate_message_data("blocker", &json!({})).is_err());
+        assert!(TaskValidator::validate_message_data(
+            "blocker",
+            &json!({"blocking_task_code": 12})
+        )
+        .is_err());
+        assert!(TaskValidator::validate_message_data(
+            "handoff",
+            &json!({"to_agent_name": "Bad Name"})
+        )
+        .is_err());
+        assert!(TaskValidator::validate_message_data("question", &json!({"any": 1})).is_ok());
+        assert!(TaskValidator::validate_message_data("question", &json!([1, 2])).is_err());
+
+        let oversized = json!({"blob": "x".repeat(MAX_MESSAGE_DATA_SIZE)});
+        assert!(TaskValidator::validate_message_data("question", &oversized).is_err());
+    }
+
     #[test]
     fn test_validate_state_transition() {
         let task = Task::new(
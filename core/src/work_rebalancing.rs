@@ -0,0 +1,332 @@
+//! Work stealing between overloaded and idle agents
+//!
+//! Agents sometimes claim more tasks than they work on. A claimed task is
+//! unattended once its owner has no work session running on it and none
+//! started or ended for `idle_minutes`. An agent holding more than
+//! `max_claimed_tasks` tasks in progress, some of them unattended, is
+//! overloaded; a registered agent with nothing in progress is idle. Unattended
+//! tasks above the limit are proposed to idle agents having every capability
+//! the task requires, one task per idle agent. Once the coordinator confirms,
+//! the tasks are reassigned and both agents get a `reassignment` message.
+
+use crate::models::{Task, TaskState, WorkSession};
+use crate::repository::TaskOperation;
+use crate::workspace_setup::ResponseStatus;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Author of reassignment messages
+pub const REBALANCING_AUTHOR: &str = "work-rebalancer";
+
+/// Message type telling both agents about a reassignment
+pub const REASSIGNMENT_MESSAGE_TYPE: &str = "reassignment";
+
+/// When an agent counts as overloaded, and how much work moves at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RebalancePolicy {
+    /// Tasks in progress an agent may hold before it counts as overloaded
+    pub max_claimed_tasks: u32,
+    /// Minutes without work session activity after which a claimed task is unattended
+    pub idle_minutes: u32,
+    /// Most reassignments proposed by one `rebalance_work` call
+    pub max_reassignments: u32,
+}
+
+impl Default for RebalancePolicy {
+    fn default() -> Self {
+        Self {
+            max_claimed_tasks: 2,
+            idle_minutes: 30,
+            max_reassignments: 10,
+        }
+    }
+}
+
+/// Registered agent work may move to, with normalized capabilities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebalanceAgent {
+    pub name: String,
+    pub capabilities: Vec<String>,
+}
+
+/// An agent holding more tasks than it works on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverloadedAgent {
+    pub agent_name: String,
+    pub claimed_tasks: usize,
+    /// Codes of its unattended tasks, least recently worked on first
+    pub unattended_tasks: Vec<String>,
+}
+
+/// A task proposed to move from an overloaded agent to an idle one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reassignment {
+    pub task_id: i32,
+    pub task_code: String,
+    pub from_agent_name: String,
+    pub to_agent_name: String,
+    /// Minutes since the previous owner last worked on the task
+    pub idle_minutes: i64,
+}
+
+impl Reassignment {
+    /// Number of operations applying one reassignment
+    pub const OPERATIONS: usize = 3;
+
+    /// Operations handing the task over and telling both agents
+    pub fn operations(&self) -> Vec<TaskOperation> {
+        let data = json!({
+            "from": self.from_agent_name,
+            "to": self.to_agent_name,
+            "idle_minutes": self.idle_minutes,
+        });
+        let message = |target: &str, content: String| TaskOperation::Message {
+            task_code: self.task_code.clone(),
+            author_agent_name: REBALANCING_AUTHOR.to_string(),
+            target_agent_name: Some(target.to_string()),
+            message_type: REASSIGNMENT_MESSAGE_TYPE.to_string(),
+            content,
+            reply_to_message_id: None,
+            structured_data: Some(data.clone()),
+        };
+        vec![
+            TaskOperation::Assign {
+                id: self.task_id,
+                new_owner: self.to_agent_name.clone(),
+            },
+            message(
+                &self.from_agent_name,
+                format!(
+                    "Task {} was reassigned to {}: you hold more tasks than you work on and \
+                     had not worked on this one for {} minutes. Post any partial work on the task.",
+                    self.task_code, self.to_agent_name, self.idle_minutes
+                ),
+            ),
+            message(
+                &self.to_agent_name,
+                format!(
+                    "Task {} was reassigned to you from {}, who had not worked on it for {} \
+                     minutes. Check its messages for partial work before you start.",
+                    self.task_code, self.from_agent_name, self.idle_minutes
+                ),
+            ),
+        ]
+    }
+}
+
+/// Proposed or executed rebalancing of the claimed work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkRebalance {
+    /// `confirmation_required` until the reassignments are confirmed, then `success`
+    pub status: ResponseStatus,
+    pub message: String,
+    pub overloaded: Vec<OverloadedAgent>,
+    /// Registered agents with no task in progress
+    pub idle_agents: Vec<String>,
+    pub reassignments: Vec<Reassignment>,
+    /// Tasks handed over once confirmed
+    pub reassigned: Vec<Task>,
+}
+
+impl RebalancePolicy {
+    /// Propose reassignments of the tasks in progress at `now`
+    ///
+    /// `sessions` are the work sessions on the tasks of agents holding more than
+    /// `max_claimed_tasks`; task and agent capabilities must be normalized alike.
+    pub fn plan(
+        &self,
+        tasks: &[Task],
+        sessions: &[WorkSession],
+        agents: &[RebalanceAgent],
+        now: DateTime<Utc>,
+    ) -> WorkRebalance {
+        let mut claimed: BTreeMap<&str, Vec<&Task>> = BTreeMap::new();
+        for task in tasks
+            .iter()
+            .filter(|task| task.state == TaskState::InProgress)
+        {
+            if let Some(owner) = task.owner_agent_name.as_deref() {
+                claimed.entry(owner).or_default().push(task);
+            }
+        }
+        let idle_agents: Vec<&RebalanceAgent> = agents
+            .iter()
+            .filter(|agent| !claimed.contains_key(agent.name.as_str()))
+            .collect();
+
+        let idle = Duration::minutes(i64::from(self.idle_minutes));
+        let mut overloaded = Vec::new();
+        let mut candidates = Vec::new();
+        for (owner, owned) in &claimed {
+            if owned.len() <= self.max_claimed_tasks as usize {
+                continue;
+            }
+            let mut unattended: Vec<(DateTime<Utc>, &Task)> = owned
+                .iter()
+                .filter_map(|task| {
+                    let task_sessions = sessions.iter().filter(|session| {
+                        session.task_id == task.id && session.agent_name == *owner
+                    });
+                    let mut last_activity_at = task.claimed_at.unwrap_or(task.state_changed_at);
+                    for session in task_sessions {
+                        // A running session means the task is being worked on
+                        let ended_at = session.ended_at?;
+                        last_activity_at = last_activity_at.max(session.started_at).max(ended_at);
+                    }
+                    (now - last_activity_at >= idle).then_some((last_activity_at, *task))
+                })
+                .collect();
+            if unattended.is_empty() {
+                continue;
+            }
+            unattended.sort_by_key(|(last_activity_at, task)| (*last_activity_at, task.id));
+            let movable = owned.len() - self.max_claimed_tasks as usize;
+            candidates.extend(
+                unattended
+                    .iter()
+                    .take(movable)
+                    .map(|(last_activity_at, task)| (owner, *last_activity_at, *task)),
+            );
+            overloaded.push(OverloadedAgent {
+                agent_name: owner.to_string(),
+                claimed_tasks: owned.len(),
+                unattended_tasks: unattended
+                    .iter()
+                    .map(|(_, task)| task.code.clone())
+                    .collect(),
+            });
+        }
+
+        // Most neglected tasks move first, each idle agent takes at most one
+        candidates.sort_by_key(|(_, last_activity_at, task)| (*last_activity_at, task.id));
+        let mut available = idle_agents.clone();
+        let mut reassignments = Vec::new();
+        for (owner, last_activity_at, task) in candidates {
+            if reassignments.len() >= self.max_reassignments as usize {
+                break;
+            }
+            let Some(index) = available.iter().position(|agent| {
+                task.required_capabilities
+                    .iter()
+                    .all(|capability| agent.capabilities.contains(capability))
+            }) else {
+                continue;
+            };
+            let agent = available.remove(index);
+            reassignments.push(Reassignment {
+                task_id: task.id,
+                task_code: task.code.clone(),
+                from_agent_name: owner.to_string(),
+                to_agent_name: agent.name.clone(),
+                idle_minutes: (now - last_activity_at).num_minutes(),
+            });
+        }
+
+        WorkRebalance {
+            status: ResponseStatus::ConfirmationRequired,
+            message: format!(
+                "{} overloaded and {} idle agent(s); {} reassignment(s) proposed. Resend with \
+                 confirm: true to execute them, optionally limited to the task codes in codes",
+                overloaded.len(),
+                idle_agents.len(),
+                reassignments.len()
+            ),
+            overloaded,
+            idle_agents: idle_agents.iter().map(|agent| agent.name.clone()).collect(),
+            reassignments,
+            reassigned: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn task(id: i32, owner: &str, capabilities: &[&str], claimed_at: DateTime<Utc>) -> Task {
+        Task {
+            owner_agent_name: Some(owner.to_string()),
+            claimed_at: Some(claimed_at),
+            required_capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ..test_support::task_at(id, &format!("T-{id}"), TaskState::InProgress, claimed_at)
+        }
+    }
+
+    fn agent(name: &str, capabilities: &[&str]) -> RebalanceAgent {
+        RebalanceAgent {
+            name: name.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_plan_moves_unattended_work_to_idle_agents() {
+        let now = Utc::now();
+        let hours_ago = |hours: i64| now - Duration::hours(hours);
+        let tasks = vec![
+            task(1, "hoarder", &["rust"], hours_ago(5)),
+            task(2, "hoarder", &["react"], hours_ago(4)),
+            task(3, "hoarder", &["rust"], hours_ago(3)),
+            task(4, "hoarder", &[], hours_ago(2)),
+            task(5, "busy-dev", &[], hours_ago(1)),
+        ];
+        // Task 1 is being worked on, task 3 was until a few minutes ago
+        let session = |task_id: i32, started_at, ended_at| WorkSession {
+            id: task_id,
+            task_id,
+            agent_name: "hoarder".to_string(),
+            started_at,
+            ended_at,
+            notes: None,
+            productivity_score: None,
+            interruptions: Vec::new(),
+        };
+        let sessions = vec![
+            session(1, hours_ago(1), None),
+            session(3, hours_ago(3), Some(now - Duration::minutes(5))),
+        ];
+        let agents = vec![
+            agent("busy-dev", &["rust"]),
+            agent("frontend-dev", &["react"]),
+            agent("rust-dev", &["rust"]),
+        ];
+
+        let plan = RebalancePolicy::default().plan(&tasks, &sessions, &agents, now);
+        assert_eq!(plan.status, ResponseStatus::ConfirmationRequired);
+        assert_eq!(plan.idle_agents, ["frontend-dev", "rust-dev"]);
+        assert_eq!(plan.overloaded.len(), 1);
+        assert_eq!(plan.overloaded[0].claimed_tasks, 4);
+        assert_eq!(plan.overloaded[0].unattended_tasks, ["T-2", "T-4"]);
+        let moves: Vec<(&str, &str)> = plan
+            .reassignments
+            .iter()
+            .map(|r| (r.task_code.as_str(), r.to_agent_name.as_str()))
+            .collect();
+        assert_eq!(moves, [("T-2", "frontend-dev"), ("T-4", "rust-dev")]);
+        assert_eq!(plan.reassignments[0].idle_minutes, 240);
+
+        let operations = plan.reassignments[0].operations();
+        assert_eq!(
+            operations[0],
+            TaskOperation::Assign {
+                id: 2,
+                new_owner: "frontend-dev".to_string()
+            }
+        );
+        assert_eq!(operations.len(), Reassignment::OPERATIONS);
+
+        // Nobody holding no more than the limit is overloaded
+        let relaxed = RebalancePolicy {
+            max_claimed_tasks: 4,
+            ..RebalancePolicy::default()
+        };
+        assert!(relaxed
+            .plan(&tasks, &sessions, &agents, now)
+            .overloaded
+            .is_empty());
+    }
+}
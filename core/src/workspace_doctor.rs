@@ -0,0 +1,420 @@
+//! Workspace verification
+//!
+//! The files Axon generates for a workspace drift as agents are registered,
+//! CLAUDE.md is edited and the server is upgraded. The checks here find the
+//! drift: a missing or unreadable manifest, registered agents without a valid
+//! sub-agent file, CLAUDE.md referring to functions the server does not
+//! offer, and a database schema behind the server. Every failed check says
+//! how to fix it.
+
+use crate::ai_tool_adapters::ValidationSeverity;
+use crate::claude_subagents::{subagent_name, validate_subagent_file};
+use crate::error::Result;
+use crate::manifest_schema::read_manifest;
+use crate::repository::MigrationStatus;
+use crate::workspace_setup::AgentRegistration;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Workspace manifest, relative to the project root
+pub const MANIFEST_FILE: &str = ".axon/manifest.json";
+
+/// Main AI coordination file, relative to the project root
+pub const MAIN_AI_FILE: &str = "CLAUDE.md";
+
+/// Directory of the sub-agent files, relative to the project root
+pub const AGENTS_DIR: &str = ".claude/agents";
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but should be fixed
+    Warn,
+    /// Agents will not work as intended until this is fixed
+    Fail,
+}
+
+/// Result of one workspace check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceCheck {
+    /// Check identifier, e.g. `manifest`
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What to do about a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl WorkspaceCheck {
+    fn pass(name: &str, message: String) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            message,
+            fix: None,
+        }
+    }
+
+    fn problem(name: &str, status: CheckStatus, message: String, fix: String) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message,
+            fix: Some(fix),
+        }
+    }
+}
+
+/// Results of all workspace checks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceVerification {
+    /// Whether no check failed; warnings keep a workspace healthy
+    pub healthy: bool,
+    pub checks: Vec<WorkspaceCheck>,
+}
+
+impl WorkspaceVerification {
+    pub fn new(checks: Vec<WorkspaceCheck>) -> Self {
+        Self {
+            healthy: checks.iter().all(|check| check.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+}
+
+/// Run every check against the workspace in `root`
+///
+/// `known_functions` are the MCP functions the server offers and `schema` the
+/// migration state of its database.
+pub fn verify_workspace(
+    root: &Path,
+    agents: &[AgentRegistration],
+    known_functions: &[String],
+    schema: Result<MigrationStatus>,
+) -> WorkspaceVerification {
+    WorkspaceVerification::new(vec![
+        check_manifest(root),
+        check_agent_files(root, agents),
+        check_main_file_functions(root, agents, known_functions),
+        check_schema(schema),
+    ])
+}
+
+/// The manifest exists and parses with a supported schema version
+pub fn check_manifest(root: &Path) -> WorkspaceCheck {
+    const NAME: &str = "manifest";
+    let regenerate =
+        format!("Call generate_workspace_manifest and save the manifest to {MANIFEST_FILE}");
+    let content = match std::fs::read_to_string(root.join(MANIFEST_FILE)) {
+        Ok(content) => content,
+        Err(e) => {
+            return WorkspaceCheck::problem(
+                NAME,
+                CheckStatus::Fail,
+                format!("{MANIFEST_FILE} cannot be read: {e}"),
+                regenerate,
+            )
+        }
+    };
+    let upgrade = serde_json::from_str(&content)
+        .map_err(|e| e.to_string())
+        .and_then(|value| read_manifest(value).map_err(|e| e.to_string()));
+    match upgrade {
+        Err(e) => WorkspaceCheck::problem(
+            NAME,
+            CheckStatus::Fail,
+            format!("{MANIFEST_FILE} is not a valid manifest: {e}"),
+            regenerate,
+        ),
+        Ok(upgrade) if upgrade.was_upgraded() => WorkspaceCheck::problem(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "{MANIFEST_FILE} uses schema {}, read through an upgrade",
+                upgrade.source_version
+            ),
+            regenerate,
+        ),
+        Ok(upgrade) => WorkspaceCheck::pass(
+            NAME,
+            format!(
+                "{MANIFEST_FILE} is valid ({} agents)",
+                upgrade.manifest.agents.len()
+            ),
+        ),
+    }
+}
+
+/// Sub-agent file of `agent` in the workspace in `root`
+pub fn agent_file_path(root: &Path, agent: &AgentRegistration) -> PathBuf {
+    root.join(AGENTS_DIR)
+        .join(format!("{}.md", subagent_name(&agent.name)))
+}
+
+/// Registered agents whose sub-agent file is missing or rejected by Claude Code
+///
+/// Returns the agent names with what is wrong with their files.
+pub fn agent_file_problems(root: &Path, agents: &[AgentRegistration]) -> Vec<(String, String)> {
+    agents
+        .iter()
+        .filter_map(|agent| {
+            let path = agent_file_path(root, agent);
+            let problem = match std::fs::read_to_string(&path) {
+                Err(_) => "missing".to_string(),
+                Ok(content) => {
+                    let location = path.display().to_string();
+                    validate_subagent_file(&content, &location)
+                        .into_iter()
+                        .find(|issue| matches!(issue.severity, ValidationSeverity::Critical))?
+                        .description
+                }
+            };
+            Some((agent.name.clone(), problem))
+        })
+        .collect()
+}
+
+/// Every registered agent has a sub-agent file Claude Code accepts
+pub fn check_agent_files(root: &Path, agents: &[AgentRegistration]) -> WorkspaceCheck {
+    const NAME: &str = "agent_files";
+    let problems = agent_file_problems(root, agents);
+    if problems.is_empty() {
+        return WorkspaceCheck::pass(
+            NAME,
+            format!(
+                "{} registered agents have valid files in {AGENTS_DIR}",
+                agents.len()
+            ),
+        );
+    }
+    let listed = problems
+        .iter()
+        .map(|(agent, problem)| format!("{agent} ({problem})"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    WorkspaceCheck::problem(
+        NAME,
+        CheckStatus::Fail,
+        format!("Agents without a valid file in {AGENTS_DIR}: {listed}"),
+        "Run `axon-mcp doctor --fix` to write the sub-agent files from the registrations"
+            .to_string(),
+    )
+}
+
+/// CLAUDE.md only refers to MCP functions the server offers
+pub fn check_main_file_functions(
+    root: &Path,
+    agents: &[AgentRegistration],
+    known_functions: &[String],
+) -> WorkspaceCheck {
+    const NAME: &str = "main_file_functions";
+    let content = match std::fs::read_to_string(root.join(MAIN_AI_FILE)) {
+        Ok(content) => content,
+        Err(e) => {
+            return WorkspaceCheck::problem(
+                NAME,
+                CheckStatus::Fail,
+                format!("{MAIN_AI_FILE} cannot be read: {e}"),
+                format!("Call create_main_ai_file and save the content to {MAIN_AI_FILE}"),
+            )
+        }
+    };
+    let references = function_references(&content);
+    let unknown: Vec<_> = references
+        .iter()
+        .filter(|name| !known_functions.contains(name))
+        .filter(|name| !agents.iter().any(|agent| agent.name == **name))
+        .cloned()
+        .collect();
+    if unknown.is_empty() {
+        return WorkspaceCheck::pass(
+            NAME,
+            format!(
+                "{MAIN_AI_FILE} refers to {} known MCP functions",
+                references.len()
+            ),
+        );
+    }
+    WorkspaceCheck::problem(
+        NAME,
+        CheckStatus::Warn,
+        format!(
+            "{MAIN_AI_FILE} refers to functions the server does not offer: {}",
+            unknown.join(", ")
+        ),
+        "Call create_main_ai_file to regenerate the Axon sections, or correct the references"
+            .to_string(),
+    )
+}
+
+/// Names of MCP functions referred to in Markdown
+///
+/// A function is referred to by a code span calling it, `claim_task(...)`, or
+/// by a code span opening a list item, `- claim_task`. Other code spans, e.g.
+/// parameter names, are not references.
+fn function_references(markdown: &str) -> BTreeSet<String> {
+    let mut references = BTreeSet::new();
+    for line in markdown.lines() {
+        let item = line
+            .trim_start()
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start_matches(['-', '*', '.', ' ']);
+        let starts_item = item.len() < line.trim_start().len() && item.starts_with('`');
+        for (index, span) in line.split('`').skip(1).step_by(2).enumerate() {
+            let name: String = span
+                .chars()
+                .take_while(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_')
+                .collect();
+            let rest = &span[name.len()..];
+            let referred = rest.starts_with('(') || (rest.is_empty() && index == 0 && starts_item);
+            if referred && name.contains('_') && name.starts_with(|c: char| c.is_ascii_lowercase())
+            {
+                references.insert(name);
+            }
+        }
+    }
+    references
+}
+
+/// The database schema has every migration of the server applied unchanged
+pub fn check_schema(status: Result<MigrationStatus>) -> WorkspaceCheck {
+    const NAME: &str = "database_schema";
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            return WorkspaceCheck::problem(
+                NAME,
+                CheckStatus::Warn,
+                format!("Schema version cannot be determined: {e}"),
+                "Run `axon-mcp --migration-status` against the database".to_string(),
+            )
+        }
+    };
+    let current = status
+        .current_version
+        .map_or_else(|| "empty".to_string(), |version| version.to_string());
+    let changed: Vec<_> = status
+        .migrations
+        .iter()
+        .filter(|migration| migration.checksum_mismatch)
+        .map(|migration| migration.version.to_string())
+        .collect();
+    if !changed.is_empty() {
+        return WorkspaceCheck::problem(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "Applied migrations differ from the ones shipped with the server: {}",
+                changed.join(", ")
+            ),
+            "Restore the database from a backup made by this server version".to_string(),
+        );
+    }
+    let pending = status.pending().count();
+    if pending > 0 {
+        return WorkspaceCheck::problem(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "Schema version {current} is behind {}; {pending} migrations are pending",
+                status.latest_version
+            ),
+            "Run `axon-mcp --migrate`".to_string(),
+        );
+    }
+    WorkspaceCheck::pass(NAME, format!("Schema version {current} is current"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claude_subagents::render_subagent_file;
+    use crate::repository::MigrationInfo;
+    use crate::workspace_setup::AiToolType;
+
+    fn agent(name: &str) -> AgentRegistration {
+        AgentRegistration {
+            name: name.to_string(),
+            description: "Builds things".to_string(),
+            prompt: "You are an agent.".to_string(),
+            capabilities: vec!["rust".to_string()],
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: Vec::new(),
+        }
+    }
+
+    fn status(check: &WorkspaceCheck) -> (&str, CheckStatus) {
+        (check.name.as_str(), check.status)
+    }
+
+    #[test]
+    fn test_verify_workspace() {
+        let root = tempfile::tempdir().unwrap();
+        let agents = [agent("backend_dev"), agent("qa")];
+        let known = vec!["claim_task".to_string(), "set_task_state".to_string()];
+        let migrations = |applied: bool| {
+            Ok(MigrationStatus {
+                current_version: applied.then_some(2),
+                latest_version: 2,
+                migrations: vec![MigrationInfo {
+                    version: 2,
+                    description: "init".to_string(),
+                    applied_at: applied.then(chrono::Utc::now),
+                    checksum_mismatch: false,
+                }],
+            })
+        };
+
+        let empty = verify_workspace(root.path(), &agents, &known, migrations(false));
+        assert!(!empty.healthy);
+        assert!(empty
+            .checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Fail && check.fix.is_some()));
+        assert!(empty.checks[1].message.contains("backend_dev (missing)"));
+
+        std::fs::create_dir_all(root.path().join(AGENTS_DIR)).unwrap();
+        std::fs::create_dir_all(root.path().join(".axon")).unwrap();
+        for agent in &agents {
+            std::fs::write(
+                agent_file_path(root.path(), agent),
+                render_subagent_file(agent),
+            )
+            .unwrap();
+        }
+        std::fs::write(
+            root.path().join(MANIFEST_FILE),
+            r#"{"schema_version": "1.0", "ai_tool_type": "claude-code", "agents": []}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.path().join(MAIN_AI_FILE),
+            "Use `claim_task(task_id, \"backend_dev\")` with your `task_id`.\n\n\
+             1. `set_task_state` - finish\n- `finish_task` - gone\n- `backend_dev`: an agent",
+        )
+        .unwrap();
+
+        let verification = verify_workspace(root.path(), &agents, &known, migrations(true));
+        assert!(verification.healthy, "{verification:?}");
+        let statuses: Vec<_> = verification.checks.iter().map(status).collect();
+        assert_eq!(
+            statuses,
+            [
+                ("manifest", CheckStatus::Warn),
+                ("agent_files", CheckStatus::Pass),
+                ("main_file_functions", CheckStatus::Warn),
+                ("database_schema", CheckStatus::Pass),
+            ]
+        );
+        assert!(verification.checks[2]
+            .message
+            .ends_with("does not offer: finish_task"));
+
+        std::fs::write(agent_file_path(root.path(), &agents[1]), "# Agent: qa\n").unwrap();
+        assert_eq!(agent_file_problems(root.path(), &agents)[0].0, "qa",);
+    }
+}
@@ -0,0 +1,155 @@
+//! Merging another project's database into the current one
+//!
+//! Imported tasks are matched to local ones by stable identifier first and by
+//! code second. A task sharing its code with a different local task is
+//! imported under a fresh code, and every imported task gets a local ID; the
+//! merge report lists both remappings. Messages follow their tasks, skipping
+//! those already present locally.
+
+use crate::models::{Task, TaskMessage};
+use serde::{Deserialize, Serialize};
+
+/// How an imported task relates to the local tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskMatch {
+    /// The task already exists locally under this ID
+    Existing(i32),
+    /// A different local task uses the same code
+    CodeConflict,
+    /// No local counterpart
+    New,
+}
+
+impl TaskMatch {
+    /// Match `imported` against the local tasks with its stable identifier and its code
+    ///
+    /// Tasks with different stable identifiers are always distinct. Otherwise
+    /// a task with the same code is the same task if its name and description
+    /// are unchanged.
+    pub fn of(imported: &Task, by_uid: Option<&Task>, by_code: Option<&Task>) -> Self {
+        if let Some(local) = by_uid {
+            return Self::Existing(local.id);
+        }
+        match by_code {
+            None => Self::New,
+            Some(local) => {
+                let distinct_uids = matches!(
+                    (&imported.uid, &local.uid),
+                    (Some(imported_uid), Some(local_uid)) if imported_uid != local_uid
+                );
+                if !distinct_uids
+                    && imported.name == local.name
+                    && imported.description == local.description
+                {
+                    Self::Existing(local.id)
+                } else {
+                    Self::CodeConflict
+                }
+            }
+        }
+    }
+}
+
+/// The `attempt`-th candidate code for a task whose code is taken: `CODE-2`, `CODE-3`, ...
+pub fn renamed_code(code: &str, attempt: u32) -> String {
+    format!("{code}-{}", attempt + 1)
+}
+
+/// Whether an imported message duplicates a local message of the same task
+pub fn is_same_message(imported: &TaskMessage, local: &TaskMessage) -> bool {
+    match (&imported.uid, &local.uid) {
+        (Some(imported_uid), Some(local_uid)) => imported_uid == local_uid,
+        _ => {
+            imported.author_agent_name == local.author_agent_name
+                && imported.content == local.content
+                && imported.created_at == local.created_at
+        }
+    }
+}
+
+/// A task imported under a different code than it had
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodeRename {
+    pub imported_code: String,
+    pub code: String,
+}
+
+/// The local ID of an imported task
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IdRemap {
+    pub imported_id: i32,
+    pub id: i32,
+}
+
+/// What merging another project's database changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WorkspaceMergeReport {
+    /// The merged database
+    pub import_path: String,
+    /// Whether the merge was rolled back after computing the report
+    pub dry_run: bool,
+    /// Tasks added to the current database
+    pub tasks_imported: u64,
+    /// Tasks that already existed locally and were left unchanged
+    pub tasks_matched: u64,
+    /// Imported tasks whose code was taken by a different local task
+    pub renamed_codes: Vec<CodeRename>,
+    /// Local IDs of every imported or matched task
+    pub id_remaps: Vec<IdRemap>,
+    /// Messages added to the current database
+    pub messages_imported: u64,
+    /// Messages already present locally
+    pub messages_skipped: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskState;
+    use crate::test_support;
+
+    fn task(id: i32, uid: Option<&str>, code: &str, name: &str) -> Task {
+        Task {
+            uid: uid.map(str::to_string),
+            name: name.to_string(),
+            ..test_support::task(id, code, TaskState::Created)
+        }
+    }
+
+    #[test]
+    fn test_task_match() {
+        let imported = task(1, Some("01A"), "API-1", "Build API");
+
+        let same_uid = task(7, Some("01A"), "API-9", "Renamed");
+        assert_eq!(
+            TaskMatch::of(&imported, Some(&same_uid), None),
+            TaskMatch::Existing(7)
+        );
+
+        let same_code = task(8, None, "API-1", "Build API");
+        assert_eq!(
+            TaskMatch::of(&imported, None, Some(&same_code)),
+            TaskMatch::Existing(8)
+        );
+
+        let other_content = task(9, None, "API-1", "Build UI");
+        assert_eq!(
+            TaskMatch::of(&imported, None, Some(&other_content)),
+            TaskMatch::CodeConflict
+        );
+
+        let other_uid = task(10, Some("01B"), "API-1", "Build API");
+        assert_eq!(
+            TaskMatch::of(&imported, None, Some(&other_uid)),
+            TaskMatch::CodeConflict
+        );
+
+        assert_eq!(TaskMatch::of(&imported, None, None), TaskMatch::New);
+    }
+
+    #[test]
+    fn test_renamed_code() {
+        assert_eq!(renamed_code("API-1", 1), "API-1-2");
+        assert_eq!(renamed_code("API-1", 2), "API-1-3");
+    }
+}
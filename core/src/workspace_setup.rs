@@ -23,10 +23,23 @@
 //! 6. generate_workspace_manifest(metadata) → Create .axon/manifest.json
 //! ```
 
-use crate::prompt_templates::EnhancedPromptBuilder;
+use crate::archetypes::{load_archetypes, CustomArchetype, ARCHETYPES_FILE};
+use crate::managed_sections::{
+    merge_managed_sections, ManagedSection, SectionMerge, AGENT_ROSTER_SECTION, MCP_USAGE_SECTION,
+};
+use crate::manifest_schema::MANIFEST_SCHEMA_VERSION;
+use crate::messages::{MessageCatalog, MessageKey};
+use crate::project_scan::ProjectScan;
+use crate::prompt_templates::{EnhancedPromptBuilder, PromptStyle};
+use crate::setup_wizard::SetupWizardProgress;
+use crate::text_diff::unified_diff;
+use crate::token_estimate::{TokenBudgets, TokenEstimate};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Supported AI tool types for workspace generation
 ///
@@ -56,6 +69,7 @@ pub enum ProjectArchetype {
     DesktopApp,     // Desktop GUI applications
     ApiService,     // Pure API/microservice
     Generic,        // Fallback for unclassifiable projects
+    Custom(String), // Defined in .axon/archetypes.yaml
 }
 
 impl std::fmt::Display for AiToolType {
@@ -68,6 +82,101 @@ impl std::fmt::Display for AiToolType {
     }
 }
 
+/// Evidence for a mobile app; "mobile" alone counts only without "web"
+const MOBILE_SIGNALS: &[&str] = &[
+    "mobile",
+    "ios",
+    "android",
+    "react native",
+    "flutter",
+    "swift",
+    "kotlin",
+    "xamarin",
+    "mobile app",
+];
+const DESKTOP_SIGNALS: &[&str] = &[
+    "desktop",
+    "gui",
+    "electron",
+    "wpf",
+    "qt",
+    "tkinter",
+    ".net maui",
+    "tauri",
+];
+const DATA_PROCESSING_SIGNALS: &[&str] = &[
+    "etl",
+    "data processing",
+    "pipeline",
+    "analytics",
+    "machine learning",
+    "spark",
+    "hadoop",
+    "kafka",
+    "airflow",
+    "big data",
+    "data warehouse",
+];
+const API_SERVICE_SIGNALS: &[&str] = &["api", "microservice"];
+const LIBRARY_SIGNALS: &[&str] = &[
+    "library",
+    "sdk",
+    "framework",
+    "package",
+    "module",
+    "api design",
+    "semantic versioning",
+];
+const FRONTEND_SIGNALS: &[&str] = &[
+    "frontend",
+    "html",
+    "css",
+    "javascript",
+    "react",
+    "vue",
+    "angular",
+];
+const BACKEND_SIGNALS: &[&str] = &[
+    "backend",
+    "server",
+    "database",
+    "api endpoint",
+    "django",
+    "rails",
+    "node.js",
+];
+const WEB_APPLICATION_SIGNALS: &[&str] = &["full-stack", "web application"];
+const CLI_TOOL_SIGNALS: &[&str] = &["cli", "command-line", "converter", "tool"];
+/// Evidence for a script; "script" counts only without "javascript"
+const SCRIPT_SIGNALS: &[&str] = &["automation", "batch", "script"];
+
+/// Evidence reported for each built-in archetype
+const BUILT_IN_SIGNALS: &[(ProjectArchetype, &[&[&str]])] = &[
+    (ProjectArchetype::MobileApp, &[MOBILE_SIGNALS]),
+    (ProjectArchetype::DesktopApp, &[DESKTOP_SIGNALS]),
+    (ProjectArchetype::DataProcessing, &[DATA_PROCESSING_SIGNALS]),
+    (ProjectArchetype::ApiService, &[API_SERVICE_SIGNALS]),
+    (ProjectArchetype::Library, &[LIBRARY_SIGNALS]),
+    (
+        ProjectArchetype::WebApplication,
+        &[FRONTEND_SIGNALS, BACKEND_SIGNALS, WEB_APPLICATION_SIGNALS],
+    ),
+    (ProjectArchetype::CliTool, &[CLI_TOOL_SIGNALS]),
+    (ProjectArchetype::Script, &[SCRIPT_SIGNALS]),
+];
+
+/// Confidence in an archetype backed by `matched` signals against a rival with `rival` signals
+///
+/// More signals raise it (1 → 0.5, 2 → 0.75, ...) and a close rival lowers it.
+fn signal_confidence(matched: usize, rival: usize) -> f32 {
+    if matched == 0 {
+        return 0.0;
+    }
+    let strength = 1.0 - 0.5f32.powi(matched as i32);
+    let margin = matched as f32 / (matched + rival) as f32;
+    (strength * margin * 100.0).round() / 100.0
+}
+
 impl std::fmt::Display for ProjectArchetype {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -80,6 +189,7 @@ impl std::fmt::Display for ProjectArchetype {
             ProjectArchetype::DesktopApp => write!(f, "Desktop App"),
             ProjectArchetype::ApiService => write!(f, "API Service"),
             ProjectArchetype::Generic => write!(f, "Generic Project"),
+            ProjectArchetype::Custom(name) => write!(f, "{name}"),
         }
     }
 }
@@ -449,12 +559,11 @@ impl PrdDocument {
             }
         }
 
-        // Deduplicate and filter out very short items
+        // Deduplicate (keeping document order) and filter out very short items
+        let mut seen = HashSet::new();
         items
             .into_iter()
-            .filter(|item| item.len() > 3)
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
+            .filter(|item| item.len() > 3 && seen.insert(item.clone()))
             .collect()
     }
 }
@@ -482,6 +591,9 @@ pub enum WorkspaceSetupError {
 
     #[error("Invalid configuration: {0}")]
     InvalidConfiguration(String),
+
+    #[error("Unsupported schema version: {0}")]
+    UnsupportedSchemaVersion(String),
 }
 
 pub type WorkspaceSetupResult<T> = std::result::Result<T, WorkspaceSetupError>;
@@ -529,6 +641,30 @@ pub struct AgenticWorkflowDescription {
     pub task_decomposition_strategy: String,
     pub coordination_patterns: Vec<String>,
     pub workflow_steps: Vec<String>,
+    /// How the project archetype was chosen, when it was derived from a PRD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<ArchetypeClassification>,
+}
+
+/// How the project archetype was chosen, so the caller can ask the user to disambiguate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchetypeClassification {
+    pub archetype: String,
+    /// From 0.0 (a guess) to 1.0 (unambiguous)
+    pub confidence: f32,
+    /// Keywords and patterns found in the PRD and the project's manifests
+    pub matched_signals: Vec<String>,
+    /// Other archetypes with supporting signals, most likely first
+    pub runner_ups: Vec<ArchetypeCandidate>,
+    pub explanation: String,
+}
+
+/// An archetype considered but not chosen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchetypeCandidate {
+    pub archetype: String,
+    pub confidence: f32,
+    pub matched_signals: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -550,13 +686,28 @@ pub struct AgentRegistration {
     pub dependencies: Vec<String>,
 }
 
+/// A registered agent's prompt regenerated from the current workspace context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRegeneration {
+    pub agent_name: String,
+    pub previous_prompt: String,
+    pub prompt: String,
+    pub changed: bool,
+    /// Unified diff from the stored prompt to the regenerated one
+    pub diff: String,
+    /// Whether the regenerated prompt replaced the stored one
+    pub applied: bool,
+    /// Size of the regenerated prompt against the agent prompt budget
+    pub token_estimate: TokenEstimate,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MainAiFileInstructions {
     pub ai_tool_type: AiToolType,
     pub file_name: String,
     pub structure_template: Vec<SectionTemplate>,
     pub content_guidelines: Vec<String>,
-    pub examples: HashMap<String, String>,
+    pub examples: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -566,7 +717,7 @@ pub struct SectionTemplate {
     pub template: String,
     pub order: u8,
     pub required: bool,
-    pub placeholders: HashMap<String, String>,
+    pub placeholders: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -575,6 +726,12 @@ pub struct MainAiFileData {
     pub file_name: String,
     pub content: String,
     pub sections: Vec<FileSection>,
+    /// Size of the content against the main AI file budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_estimate: Option<TokenEstimate>,
+    /// How Axon-managed sections were merged into an existing file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_merge: Option<SectionMerge>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -614,7 +771,7 @@ pub struct GeneratedFile {
     pub critical: bool,
 }
 
-/// MCP function parameters - moved to protocol.rs to avoid duplication
+// MCP function parameters - moved to protocol.rs to avoid duplication
 
 /// Type aliases for cleaner MCP function signatures
 pub type SetupInstructionsResponse = WorkspaceSetupResponse<SetupInstructions>;
@@ -624,6 +781,31 @@ pub type MainFileInstructionsResponse = WorkspaceSetupResponse<MainAiFileInstruc
 pub type MainFileDataResponse = WorkspaceSetupResponse<MainAiFileData>;
 pub type WorkspaceManifestResponse = WorkspaceSetupResponse<WorkspaceManifest>;
 
+/// Source of the timestamps written into generated manifests
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at one instant, so the same PRD always yields an identical manifest
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
 /// Configuration for workspace setup service
 #[derive(Debug, Clone)]
 pub struct WorkspaceSetupConfig {
@@ -631,6 +813,13 @@ pub struct WorkspaceSetupConfig {
     pub default_agent_count: u8,
     pub supported_ai_tools: Vec<AiToolType>,
     pub template_base_path: String,
+    /// Clock for manifest timestamps; use a `FixedClock` for golden tests
+    pub clock: Arc<dyn Clock>,
+    /// Project whose manifests (Cargo.toml, package.json, ...) inform agent suggestions
+    /// and whose `.axon/archetypes.yaml` adds custom archetypes
+    pub project_root: Option<PathBuf>,
+    /// Sizes above which generated files and prompts are reported as oversized
+    pub token_budgets: TokenBudgets,
 }
 
 impl Default for WorkspaceSetupConfig {
@@ -640,6 +829,9 @@ impl Default for WorkspaceSetupConfig {
             default_agent_count: 3,
             supported_ai_tools: vec![AiToolType::ClaudeCode],
             template_base_path: ".axon/templates".to_string(),
+            clock: Arc::new(SystemClock),
+            project_root: None,
+            token_budgets: TokenBudgets::default(),
         }
     }
 }
@@ -651,6 +843,7 @@ impl Default for WorkspaceSetupConfig {
 pub struct WorkspaceSetupService {
     config: WorkspaceSetupConfig,
     prompt_builder: EnhancedPromptBuilder,
+    messages: MessageCatalog,
 }
 
 impl Default for WorkspaceSetupService {
@@ -665,6 +858,7 @@ impl WorkspaceSetupService {
         Self {
             config: WorkspaceSetupConfig::default(),
             prompt_builder: EnhancedPromptBuilder::new(),
+            messages: MessageCatalog::default(),
         }
     }
 
@@ -673,9 +867,22 @@ impl WorkspaceSetupService {
         Self {
             config,
             prompt_builder: EnhancedPromptBuilder::new(),
+            messages: MessageCatalog::default(),
         }
     }
 
+    /// Use `messages` for response messages
+    pub fn with_messages(mut self, messages: MessageCatalog) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Report generated files and prompts larger than `token_budgets`
+    pub fn with_token_budgets(mut self, token_budgets: TokenBudgets) -> Self {
+        self.config.token_budgets = token_budgets;
+        self
+    }
+
     /// 1️⃣ GET SETUP INSTRUCTIONS
     pub async fn get_setup_instructions(
         &self,
@@ -745,8 +952,9 @@ impl WorkspaceSetupService {
         };
 
         Ok(WorkspaceSetupResponse::success(
-            "Setup instructions generated successfully. Follow these steps to configure your workspace using Axon MCP functions.".to_string(),
-            instructions
+            self.messages
+                .format(MessageKey::SetupInstructionsReady, &[]),
+            instructions,
         ))
     }
 
@@ -758,15 +966,15 @@ impl WorkspaceSetupService {
         // Validate PRD first
         if !prd.is_valid() {
             let errors = prd.get_validation_errors();
-            let error_msg = format!(
-                "PRD validation failed. Please fix these issues before proceeding:\n{}",
-                errors
-                    .iter()
-                    .enumerate()
-                    .map(|(i, e)| format!("{}. {}", i + 1, e))
-                    .collect::<Vec<_>>()
-                    .join("\n")
-            );
+            let errors = errors
+                .iter()
+                .enumerate()
+                .map(|(i, e)| format!("{}. {}", i + 1, e))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let error_msg = self
+                .messages
+                .format(MessageKey::PrdValidationFailed, &[("errors", &errors)]);
 
             return Ok(WorkspaceSetupResponse::error(
                 error_msg,
@@ -777,27 +985,44 @@ impl WorkspaceSetupService {
                     task_decomposition_strategy: "N/A".to_string(),
                     coordination_patterns: vec![],
                     workflow_steps: vec![],
+                    classification: None,
                 },
             ));
         }
 
         // PHASE 1: Classify project archetype first
-        let archetype = self.classify_project_archetype(prd);
+        let (archetype, custom, classification) = self.resolve_archetype(prd)?;
 
         // PHASE 2: Apply archetype-specific complexity rules
         let (recommended_agent_count, coordination_patterns, task_decomposition_strategy) =
-            self.get_archetype_specific_workflow(&archetype, prd);
+            match &custom {
+                Some(custom) => Self::custom_workflow(custom),
+                None => self.get_archetype_specific_workflow(&archetype, prd),
+            };
 
         // Ensure we respect max agents limit
         let recommended_agent_count = recommended_agent_count.min(self.config.max_agents as u32);
 
         // Generate suggested agents with enhanced prompts based on archetype
-        let suggested_agents = self
-            .generate_suggested_agents_for_archetype(&archetype, prd, recommended_agent_count)
-            .await?;
+        let mut suggested_agents = match &custom {
+            Some(custom) => custom.suggested_agents(recommended_agent_count as usize),
+            None => {
+                self.generate_suggested_agents_for_archetype(
+                    &archetype,
+                    prd,
+                    recommended_agent_count,
+                )
+                .await?
+            }
+        };
+        self.scan_project()
+            .assign_capabilities(&mut suggested_agents);
 
         // Generate workflow steps based on project archetype and agents
-        let workflow_steps = self.generate_workflow_steps(&archetype, &suggested_agents);
+        let workflow_steps = match &custom {
+            Some(custom) if !custom.workflow_steps.is_empty() => custom.workflow_steps.clone(),
+            _ => self.generate_workflow_steps(&archetype, &suggested_agents),
+        };
 
         let workflow = AgenticWorkflowDescription {
             workflow_description: format!(
@@ -812,36 +1037,45 @@ impl WorkspaceSetupService {
             task_decomposition_strategy,
             coordination_patterns,
             workflow_steps,
+            classification: Some(classification),
         };
 
+        let complexity = match prd.calculate_complexity_score() {
+            1..=3 => MessageKey::ComplexityLow,
+            4..=6 => MessageKey::ComplexityMedium,
+            7..=8 => MessageKey::ComplexityHigh,
+            _ => MessageKey::ComplexityVeryHigh,
+        };
         let next_steps = vec![
             NextStep {
-                label: "Register these agents".to_string(),
+                label: self
+                    .messages
+                    .format(MessageKey::NextStepRegisterAgents, &[]),
                 action: "register_agents".to_string(),
                 is_default: true,
             },
             NextStep {
-                label: format!("Modify agent count (currently {recommended_agent_count})"),
+                label: self.messages.format(
+                    MessageKey::NextStepRefineAgentCount,
+                    &[("count", &recommended_agent_count)],
+                ),
                 action: "refine_agent_count".to_string(),
                 is_default: false,
             },
         ];
 
         Ok(WorkspaceSetupResponse::confirmation_required(
-            format!(
-                "Analyzed PRD '{}' and recommend {} agents for this {} complexity {} project. Review the suggested agents and confirm to proceed.",
-                prd.title,
-                recommended_agent_count,
-                match prd.calculate_complexity_score() {
-                    1..=3 => "low",
-                    4..=6 => "medium", 
-                    7..=8 => "high",
-                    _ => "very high",
-                },
-                archetype
+            self.messages.format(
+                MessageKey::WorkflowRecommended,
+                &[
+                    ("title", &prd.title),
+                    ("count", &recommended_agent_count),
+                    ("complexity", &self.messages.get(complexity)),
+                    ("archetype", &archetype),
+                ],
             ),
             workflow,
-            next_steps
+            next_steps,
         ))
     }
 
@@ -853,22 +1087,22 @@ impl WorkspaceSetupService {
         // Validate agent data
         if agent.name.trim().is_empty() {
             return Ok(WorkspaceSetupResponse::error(
-                "Agent name cannot be empty. Please provide a valid agent name (e.g., 'project-manager').".to_string(),
-                agent
+                self.messages.format(MessageKey::AgentNameEmpty, &[]),
+                agent,
             ));
         }
 
         if agent.description.trim().is_empty() {
             return Ok(WorkspaceSetupResponse::error(
-                "Agent description cannot be empty. Please provide a clear description of the agent's role.".to_string(),
-                agent
+                self.messages.format(MessageKey::AgentDescriptionEmpty, &[]),
+                agent,
             ));
         }
 
         if agent.capabilities.is_empty() {
             return Ok(WorkspaceSetupResponse::error(
-                "Agent must have at least one capability. Please specify the agent's skills."
-                    .to_string(),
+                self.messages
+                    .format(MessageKey::AgentCapabilitiesEmpty, &[]),
                 agent,
             ));
         }
@@ -892,10 +1126,9 @@ impl WorkspaceSetupService {
 
         let agent_name = agent.name.clone();
         Ok(WorkspaceSetupResponse::success(
-            format!(
-                "Agent '{}' registered successfully with {} capabilities and enhanced 2025 prompt.",
-                agent.name,
-                agent.capabilities.len()
+            self.messages.format(
+                MessageKey::AgentRegistered,
+                &[("name", &agent.name), ("count", &agent.capabilities.len())],
             ),
             agent,
         )
@@ -919,7 +1152,7 @@ impl WorkspaceSetupService {
                 order: 1,
                 required: true,
                 placeholders: {
-                    let mut map = HashMap::new();
+                    let mut map = BTreeMap::new();
                     map.insert(
                         "project_name".to_string(),
                         "Name of the project from PRD".to_string(),
@@ -937,7 +1170,7 @@ impl WorkspaceSetupService {
                 "Define coordination protocols between agents".to_string(),
             ],
             examples: {
-                let mut examples = HashMap::new();
+                let mut examples = BTreeMap::new();
                 examples.insert("coordination_example".to_string(),
                     "1. Use list_tasks to find your assigned tasks\n2. Use claim_task to claim available work\n3. Use create_task_message for handoffs".to_string());
                 examples
@@ -945,8 +1178,11 @@ impl WorkspaceSetupService {
         };
 
         Ok(WorkspaceSetupResponse::success(
-            format!("Main file instructions generated for {ai_tool_type}. Use these templates to create your coordination file."),
-            instructions
+            self.messages.format(
+                MessageKey::MainFileInstructionsReady,
+                &[("ai_tool", &ai_tool_type)],
+            ),
+            instructions,
         ))
     }
 
@@ -959,13 +1195,15 @@ impl WorkspaceSetupService {
     ) -> WorkspaceSetupResult<MainFileDataResponse> {
         if content.trim().is_empty() {
             return Ok(WorkspaceSetupResponse::error(
-                "File content cannot be empty. Please provide the complete content for the main coordination file.".to_string(),
+                self.messages.format(MessageKey::MainFileContentEmpty, &[]),
                 MainAiFileData {
                     ai_tool_type,
                     file_name: "".to_string(),
                     content: "".to_string(),
                     sections: vec![],
-                }
+                    token_estimate: None,
+                    section_merge: None,
+                },
             ));
         }
 
@@ -981,17 +1219,94 @@ impl WorkspaceSetupService {
             AiToolType::CrewAi => "crew.py".to_string(),
         };
 
+        let token_estimate = TokenEstimate::new(content, self.config.token_budgets.main_ai_file);
         let file_data = MainAiFileData {
             ai_tool_type,
             file_name: file_name.clone(),
             content: content.to_string(),
             sections,
+            token_estimate: Some(token_estimate),
+            section_merge: None,
         };
 
         Ok(WorkspaceSetupResponse::success(
-            format!("Main coordination file '{file_name}' created successfully."),
+            self.messages
+                .format(MessageKey::MainFileCreated, &[("file_name", &file_name)]),
             file_data,
-        ))
+        )
+        .with_logs(token_estimate.warning(&file_name).into_iter().collect()))
+    }
+
+    /// Update the Axon-managed sections of the main file, keeping everything else
+    ///
+    /// The sections are merged into `existing` when the file exists, and into
+    /// `content` when it is created. Sections edited by hand are reported as
+    /// conflicts and kept unless `force` is set.
+    pub async fn update_main_file(
+        &self,
+        existing: Option<&str>,
+        content: &str,
+        ai_tool_type: AiToolType,
+        agents: &[AgentRegistration],
+        force: bool,
+    ) -> WorkspaceSetupResult<MainFileDataResponse> {
+        let base = existing.unwrap_or(content);
+        if base.trim().is_empty() {
+            return self.create_main_file(base, ai_tool_type, None).await;
+        }
+        let (merged, section_merge) =
+            merge_managed_sections(base, &Self::main_file_sections(agents), force);
+        let conflicts = section_merge
+            .conflicts
+            .iter()
+            .map(|conflict| format!("Section '{}' kept: {}", conflict.section, conflict.reason))
+            .collect();
+
+        let mut response = self.create_main_file(&merged, ai_tool_type, None).await?;
+        response.payload.section_merge = Some(section_merge);
+        Ok(response.with_logs(conflicts))
+    }
+
+    /// Sections of the main file Axon owns: the agent roster and MCP usage
+    pub fn main_file_sections(agents: &[AgentRegistration]) -> Vec<ManagedSection> {
+        let roster = if agents.is_empty() {
+            "## Agent Roster\n\n_No agents registered yet; add them with `register_agent`._"
+                .to_string()
+        } else {
+            let rows = agents
+                .iter()
+                .map(|agent| {
+                    let depends_on = if agent.dependencies.is_empty() {
+                        "-".to_string()
+                    } else {
+                        agent.dependencies.join(", ")
+                    };
+                    format!(
+                        "| `{}` | {} | {} |",
+                        agent.name,
+                        agent.capabilities.join(", "),
+                        depends_on
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!(
+                "## Agent Roster\n\n| Agent | Capabilities | Depends on |\n\
+                 |-------|--------------|------------|\n{rows}"
+            )
+        };
+        let usage = "## MCP Coordination\n\n\
+            Agents coordinate through the Axon MCP server:\n\n\
+            1. `discover_work` - find tasks matching your capabilities\n\
+            2. `claim_task` - take ownership of a task before starting it\n\
+            3. `start_work_session` / `end_work_session` - track the time spent\n\
+            4. `get_task_context` - load the task with its history and related work\n\
+            5. `create_task_message` - hand off results, ask questions, report blockers\n\
+            6. `set_task_state` - move the task to Review or Done";
+        vec![
+            ManagedSection::new(AGENT_ROSTER_SECTION, &roster),
+            ManagedSection::new(MCP_USAGE_SECTION, usage),
+        ]
     }
 
     /// 6️⃣ GENERATE WORKSPACE MANIFEST
@@ -1003,6 +1318,7 @@ impl WorkspaceSetupService {
     ) -> WorkspaceSetupResult<WorkspaceManifestResponse> {
         let workflow_response = self.get_agentic_workflow_description(prd).await?;
         let workflow = workflow_response.payload;
+        let (archetype, ..) = self.resolve_archetype(prd)?;
 
         // Ensure all agents have enhanced prompts
         let enhanced_agents: Vec<AgentRegistration> = agents
@@ -1016,7 +1332,7 @@ impl WorkspaceSetupService {
                         workload_percentage: 100.0 / agents.len() as f32,
                         depends_on: agent.dependencies.clone(),
                     },
-                    &self.classify_project_archetype(prd),
+                    &archetype,
                     &format!("Project: {}", prd.title),
                     None,
                 );
@@ -1033,7 +1349,7 @@ impl WorkspaceSetupService {
             .collect();
 
         let manifest = WorkspaceManifest {
-            schema_version: "2.0".to_string(), // Updated for enhanced features
+            schema_version: MANIFEST_SCHEMA_VERSION.to_string(),
             ai_tool_type: AiToolType::ClaudeCode,
             project: ProjectMetadata {
                 name: prd.title.clone(),
@@ -1059,151 +1375,300 @@ impl WorkspaceSetupService {
             } else {
                 vec![]
             },
-            created_at: Utc::now(),
+            created_at: self.config.clock.now(),
             axon_version: "2.0.0".to_string(), // Updated for enhanced features
         };
 
+        let oversized_prompts = manifest
+            .agents
+            .iter()
+            .filter_map(|agent| {
+                TokenEstimate::new(&agent.prompt, self.config.token_budgets.agent_prompt)
+                    .warning(&format!("Prompt of agent '{}'", agent.name))
+            })
+            .collect();
+
         Ok(WorkspaceSetupResponse::success(
-            format!("Enhanced workspace manifest generated for project '{}' with {} agents using 2025 best practices.", 
-                manifest.project.name,
-                manifest.agents.len()
+            self.messages.format(
+                MessageKey::ManifestGenerated,
+                &[
+                    ("project", &manifest.project.name),
+                    ("count", &manifest.agents.len()),
+                ],
             ),
-            manifest
-        ))
+            manifest,
+        )
+        .with_logs(oversized_prompts))
+    }
+
+    /// Regenerate the prompt of a registered agent from the workspace context
+    ///
+    /// The PRD, when given, decides the archetype and project context; the
+    /// other registered agents are passed as rolling context, so the prompt
+    /// follows changes to the PRD and to the team. The context is not modified.
+    pub async fn regenerate_agent_prompt(
+        &self,
+        context: &WorkspaceContext,
+        agent_name: &str,
+        prd: Option<&PrdDocument>,
+        style: &PromptStyle,
+    ) -> WorkspaceSetupResult<PromptRegeneration> {
+        let agents = &context.registered_agents;
+        let agent = agents
+            .iter()
+            .find(|agent| agent.name == agent_name)
+            .ok_or_else(|| {
+                WorkspaceSetupError::AgentGenerationFailed(format!(
+                    "Agent '{agent_name}' is not registered in workspace '{}'",
+                    context.workspace_id
+                ))
+            })?;
+
+        let (archetype, project_context) = match prd {
+            Some(prd) => (
+                self.resolve_archetype(prd)?.0,
+                format!("Project: {}", prd.title),
+            ),
+            None => (
+                ProjectArchetype::Generic,
+                format!("Workspace: {}", context.workspace_id),
+            ),
+        };
+        let team = agents
+            .iter()
+            .filter(|other| other.name != agent.name)
+            .map(|other| {
+                let mut line = format!("- {}: {}", other.name, other.capabilities.join(", "));
+                if !other.dependencies.is_empty() {
+                    line.push_str(&format!(" (depends on {})", other.dependencies.join(", ")));
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+        let rolling_context = (!team.is_empty()).then(|| format!("TEAM:\n{}", team.join("\n")));
+
+        let prompt = self.prompt_builder.generate_agent_prompt_with_style(
+            &SuggestedAgent {
+                name: agent.name.clone(),
+                description: agent.description.clone(),
+                required_capabilities: agent.capabilities.clone(),
+                workload_percentage: 100.0 / agents.len() as f32,
+                depends_on: agent.dependencies.clone(),
+            },
+            &archetype,
+            &project_context,
+            rolling_context.as_deref(),
+            style,
+        );
+        let diff = unified_diff(&agent.prompt, &prompt);
+        let token_estimate = TokenEstimate::new(&prompt, self.config.token_budgets.agent_prompt);
+
+        Ok(PromptRegeneration {
+            agent_name: agent.name.clone(),
+            previous_prompt: agent.prompt.clone(),
+            changed: prompt != agent.prompt,
+            prompt,
+            diff,
+            applied: false,
+            token_estimate,
+        })
     }
 
     // Private helper methods
 
-    /// Classify project archetype based on PRD content analysis
+    /// Stacks detected in the configured project root (empty without one)
+    fn scan_project(&self) -> ProjectScan {
+        self.config
+            .project_root
+            .as_deref()
+            .map(ProjectScan::scan)
+            .unwrap_or_default()
+    }
+
+    /// Custom archetypes of the configured project root (none without one)
+    fn custom_archetypes(&self) -> WorkspaceSetupResult<Vec<CustomArchetype>> {
+        match &self.config.project_root {
+            Some(project_root) => load_archetypes(project_root),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Archetype of the project, with its definition when a custom archetype matched
     ///
-    /// Uses a priority-based approach, checking from most specific to most general
-    /// to avoid keyword overlap issues. Falls back to Generic for unclassifiable projects.
-    fn classify_project_archetype(&self, prd: &PrdDocument) -> ProjectArchetype {
-        let content_lower = format!(
-            "{} {} {}",
+    /// Custom archetypes are tried first, in file order; the built-in
+    /// classification applies when none of them matches.
+    fn resolve_archetype(
+        &self,
+        prd: &PrdDocument,
+    ) -> WorkspaceSetupResult<(
+        ProjectArchetype,
+        Option<CustomArchetype>,
+        ArchetypeClassification,
+    )> {
+        let content_lower = self.classification_text(prd);
+        let custom_archetypes = self.custom_archetypes()?;
+        let custom = custom_archetypes
+            .iter()
+            .find(|archetype| archetype.matches(&content_lower))
+            .cloned();
+        let archetype = match &custom {
+            Some(custom) => ProjectArchetype::Custom(custom.name.clone()),
+            None => self.classify_project_archetype(prd),
+        };
+        let custom_candidates = custom_archetypes
+            .iter()
+            .map(|custom| (custom.name.clone(), custom.matched_signals(&content_lower)))
+            .collect();
+        let classification = self.explain_classification(prd, &archetype, custom_candidates);
+        Ok((archetype, custom, classification))
+    }
+
+    /// Agent count, coordination patterns and decomposition strategy of a custom archetype
+    fn custom_workflow(custom: &CustomArchetype) -> (u32, Vec<String>, String) {
+        let coordination_patterns = if custom.coordination_patterns.is_empty() {
+            vec![format!("{} workflow from {ARCHETYPES_FILE}", custom.name)]
+        } else {
+            custom.coordination_patterns.clone()
+        };
+        let strategy = custom
+            .task_decomposition_strategy
+            .clone()
+            .unwrap_or_else(|| format!("Custom {} archetype workflow", custom.name));
+        (custom.agents.len() as u32, coordination_patterns, strategy)
+    }
+
+    /// Lower-cased PRD title, overview and requirements plus hints from the project's manifests
+    fn classification_text(&self, prd: &PrdDocument) -> String {
+        format!(
+            "{} {} {} {}",
             prd.title.to_lowercase(),
             prd.overview
                 .as_ref()
                 .unwrap_or(&String::new())
                 .to_lowercase(),
-            prd.technical_requirements.join(" ").to_lowercase()
-        );
+            prd.technical_requirements.join(" ").to_lowercase(),
+            self.scan_project().hints.join(" ")
+        )
+    }
+
+    /// Classify project archetype based on PRD content analysis
+    ///
+    /// Uses a priority-based approach, checking from most specific to most general
+    /// to avoid keyword overlap issues. Hints from the project's manifests are
+    /// considered alongside the PRD. Falls back to Generic for unclassifiable projects.
+    fn classify_project_archetype(&self, prd: &PrdDocument) -> ProjectArchetype {
+        let content_lower = self.classification_text(prd);
+        let has = |signals: &[&str]| signals.iter().any(|s| content_lower.contains(s));
 
-        // 1. Mobile/Desktop - very specific patterns
-        if (content_lower.contains("mobile") && !content_lower.contains("web"))
-            || content_lower.contains("ios")
-            || content_lower.contains("android")
-            || content_lower.contains("react native")
-            || content_lower.contains("flutter")
-            || content_lower.contains("swift")
-            || content_lower.contains("kotlin")
-            || content_lower.contains("xamarin")
-            || content_lower.contains("mobile app")
+        // 1. Mobile/Desktop - very specific patterns; "mobile" alone may mean a responsive web app
+        if has(&MOBILE_SIGNALS[1..])
+            || (content_lower.contains("mobile") && !content_lower.contains("web"))
         {
             return ProjectArchetype::MobileApp;
         }
-
-        if content_lower.contains("desktop")
-            || content_lower.contains("gui")
-            || content_lower.contains("electron")
-            || content_lower.contains("wpf")
-            || content_lower.contains("qt")
-            || content_lower.contains("tkinter")
-            || content_lower.contains(".net maui")
-            || content_lower.contains("tauri")
-        {
+        if has(DESKTOP_SIGNALS) {
             return ProjectArchetype::DesktopApp;
         }
 
         // 2. Data Processing - highly specific domain
-        if content_lower.contains("etl")
-            || content_lower.contains("data processing")
-            || content_lower.contains("pipeline")
-            || content_lower.contains("analytics")
-            || content_lower.contains("machine learning")
-            || content_lower.contains("spark")
-            || content_lower.contains("hadoop")
-            || content_lower.contains("kafka")
-            || content_lower.contains("airflow")
-            || content_lower.contains("big data")
-            || content_lower.contains("data warehouse")
-        {
+        if has(DATA_PROCESSING_SIGNALS) {
             return ProjectArchetype::DataProcessing;
         }
 
         // 3. API Service - specific because it lacks frontend
-        if (content_lower.contains("api") || content_lower.contains("microservice"))
-            && !content_lower.contains("frontend")
-            && !content_lower.contains("gui")
-            && !content_lower.contains("html")
-        {
+        if has(API_SERVICE_SIGNALS) && !has(&["frontend", "gui", "html"]) {
             return ProjectArchetype::ApiService;
         }
 
         // 4. Library/SDK - specific development patterns
-        if content_lower.contains("library")
-            || content_lower.contains("sdk")
-            || content_lower.contains("framework")
-            || content_lower.contains("package")
-            || content_lower.contains("module")
-            || content_lower.contains("api design")
-            || content_lower.contains("semantic versioning")
-        {
+        if has(LIBRARY_SIGNALS) {
             return ProjectArchetype::Library;
         }
 
         // 5. Web Application - requires frontend AND backend signals
-        let has_frontend = content_lower.contains("frontend")
-            || content_lower.contains("html")
-            || content_lower.contains("css")
-            || content_lower.contains("javascript")
-            || content_lower.contains("react")
-            || content_lower.contains("vue")
-            || content_lower.contains("angular");
-        let has_backend = content_lower.contains("backend")
-            || content_lower.contains("server")
-            || content_lower.contains("database")
-            || content_lower.contains("api endpoint")
-            || content_lower.contains("django")
-            || content_lower.contains("rails")
-            || content_lower.contains("node.js");
-
-        if (has_frontend && has_backend)
-            || content_lower.contains("full-stack")
-            || content_lower.contains("web application")
-        {
+        if (has(FRONTEND_SIGNALS) && has(BACKEND_SIGNALS)) || has(WEB_APPLICATION_SIGNALS) {
             return ProjectArchetype::WebApplication;
         }
 
         // 6. CLI Tool - often combined with other terms, but prioritize when no GUI/web
-        if (content_lower.contains("cli")
-            || content_lower.contains("command-line")
-            || content_lower.contains("converter")
-            || content_lower.contains("tool"))
-            && !content_lower.contains("frontend")
-            && !content_lower.contains("gui")
-        {
+        if has(CLI_TOOL_SIGNALS) && !has(&["frontend", "gui"]) {
             return ProjectArchetype::CliTool;
         }
 
         // 7. Script - simple automation scripts
-        if content_lower.contains("automation")
-            || content_lower.contains("batch")
+        if has(&SCRIPT_SIGNALS[..2])
             || (content_lower.contains("script") && !content_lower.contains("javascript"))
         {
             return ProjectArchetype::Script;
         }
 
         // 8. FALLBACK: Generic for unclassifiable projects
-        eprintln!("⚠️  ARCHETYPE CLASSIFICATION: Project '{}' could not be classified into a specific archetype", prd.title);
-        eprintln!(
-            "   Content analyzed: {}",
-            content_lower.chars().take(200).collect::<String>()
+        tracing::debug!(
+            "Project '{}' could not be classified into a specific archetype",
+            prd.title
         );
-        eprintln!("   Using Generic archetype with default complexity");
         ProjectArchetype::Generic
     }
 
+    /// Structured classification: the archetype, how sure it is, and what else was considered
+    fn explain_classification(
+        &self,
+        prd: &PrdDocument,
+        archetype: &ProjectArchetype,
+        custom_candidates: Vec<(String, Vec<String>)>,
+    ) -> ArchetypeClassification {
+        let content_lower = self.classification_text(prd);
+        let mut candidates = custom_candidates;
+        candidates.extend(BUILT_IN_SIGNALS.iter().map(|(candidate, groups)| {
+            let matched = groups
+                .iter()
+                .flat_map(|signals| signals.iter())
+                .filter(|s| content_lower.contains(*s))
+                .map(|s| s.to_string())
+                .collect();
+            (candidate.to_string(), matched)
+        }));
+
+        let name = archetype.to_string();
+        let matched_signals = candidates
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|(_, matched)| matched.clone())
+            .unwrap_or_default();
+        let mut runner_ups: Vec<(String, Vec<String>)> = candidates
+            .into_iter()
+            .filter(|(candidate, matched)| *candidate != name && !matched.is_empty())
+            .collect();
+        runner_ups.sort_by_key(|(_, matched)| std::cmp::Reverse(matched.len()));
+        runner_ups.truncate(3);
+
+        let strongest_rival = runner_ups.first().map_or(0, |(_, matched)| matched.len());
+        let confidence = signal_confidence(matched_signals.len(), strongest_rival);
+        let explanation = if matched_signals.is_empty() {
+            "No archetype signals found in the PRD or project manifests; ask the user what kind \
+             of project this is"
+                .to_string()
+        } else {
+            format!("Classified as {name} from: {}", matched_signals.join(", "))
+        };
+        let runner_ups = runner_ups
+            .into_iter()
+            .map(|(archetype, matched)| ArchetypeCandidate {
+                confidence: signal_confidence(matched.len(), matched_signals.len()),
+                archetype,
+                matched_signals: matched,
+            })
+            .collect();
+
+        ArchetypeClassification {
+            archetype: name,
+            confidence,
+            matched_signals,
+            runner_ups,
+            explanation,
+        }
+    }
+
     /// Apply archetype-specific workflow patterns and complexity rules
     fn get_archetype_specific_workflow(
         &self,
@@ -1312,16 +1777,8 @@ impl WorkspaceSetupService {
                     "Data-centric pipeline with quality assurance".to_string(),
                 )
             }
-            ProjectArchetype::Generic => {
-                // Log unclassified projects for future classification improvements
-                println!("⚠️  UNCLASSIFIED PROJECT: '{}' (complexity: {}) - Consider adding classification rules", 
-                    prd.title, prd.calculate_complexity_score());
-                println!(
-                    "   Technical requirements: {:?}",
-                    prd.technical_requirements
-                );
-
-                // Bezpečný fallback pro neidentifikovatelné projekty
+            ProjectArchetype::Generic | ProjectArchetype::Custom(_) => {
+                // Safe fallback for projects that cannot be identified
                 let agent_count = prd.calculate_complexity_score().clamp(2, 4) as u32;
                 (
                     agent_count,
@@ -1627,7 +2084,7 @@ impl WorkspaceSetupService {
                 }
             }
 
-            ProjectArchetype::Generic => {
+            ProjectArchetype::Generic | ProjectArchetype::Custom(_) => {
                 // Fallback agent generation for unclassifiable projects
                 agents.push(SuggestedAgent {
                     name: "project-lead".to_string(),
@@ -1680,7 +2137,7 @@ impl WorkspaceSetupService {
         let workload_per_agent = 100.0 / count as f32;
 
         // Define comprehensive agent roles for web applications
-        let web_app_agents = vec![
+        let web_app_agents = [
             (
                 "project-manager",
                 "Coordinates overall project execution and manages task assignments",
@@ -1877,7 +2334,7 @@ impl WorkspaceSetupService {
                         .to_string(),
                 );
             }
-            ProjectArchetype::Generic => {
+            ProjectArchetype::Generic | ProjectArchetype::Custom(_) => {
                 // Generate generic steps based on available agents
                 if suggested_agents.iter().any(|a| a.name.contains("manager")) {
                     steps.push("1. Project planning and requirements analysis - project-manager defines scope and goals".to_string());
@@ -1931,6 +2388,12 @@ pub struct WorkspaceContext {
     pub registered_agents: Vec<AgentRegistration>,
     pub generated_files: Vec<GeneratedFileMetadata>,
     pub manifest_data: Option<WorkspaceManifest>,
+    /// Style agent prompts of this workspace are generated with
+    #[serde(default)]
+    pub prompt_style: PromptStyle,
+    /// Progress of the setup wizard, once it was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup_wizard: Option<SetupWizardProgress>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -1957,6 +2420,8 @@ impl WorkspaceContext {
             registered_agents: Vec::new(),
             generated_files: Vec::new(),
             manifest_data: None,
+            prompt_style: PromptStyle::default(),
+            setup_wizard: None,
             created_at: now,
             updated_at: now,
         }
@@ -2015,6 +2480,111 @@ impl WorkspaceContext {
         self.version += 1;
         self.updated_at = Utc::now();
     }
+
+    /// Which setup steps are complete, as far as this context records them
+    ///
+    /// Steps of `instructions` the context does not track, such as checking the
+    /// MCP connection, are left out; creating the main file and writing the
+    /// manifest are always included.
+    pub fn setup_status(&self, instructions: &SetupInstructions) -> SetupStatus {
+        let mut steps: Vec<SetupStepStatus> = instructions
+            .setup_steps
+            .iter()
+            .filter_map(|step| self.step_status(&step.id, &step.name))
+            .collect();
+        for (id, name) in [
+            ("create-main-file", "Create Main AI File"),
+            ("write-manifest", "Write Workspace Manifest"),
+        ] {
+            if !steps.iter().any(|step| step.id == id) {
+                steps.extend(self.step_status(id, name));
+            }
+        }
+
+        let completed_steps = steps.iter().filter(|step| step.complete).count();
+        SetupStatus {
+            ai_tool_type: instructions.ai_tool_type,
+            complete: completed_steps == steps.len(),
+            completed_steps,
+            total_steps: steps.len(),
+            agent_count: self.registered_agents.len(),
+            next_step: steps
+                .iter()
+                .find(|step| !step.complete)
+                .map(|step| step.id.clone()),
+            steps,
+        }
+    }
+
+    /// Status of a setup step by its ID; `None` for steps the context does not track
+    fn step_status(&self, id: &str, name: &str) -> Option<SetupStepStatus> {
+        let (complete, detail) = match id {
+            "analyze-prd" if self.has_prd() => (true, "PRD loaded".to_string()),
+            "analyze-prd" => (false, "No PRD loaded".to_string()),
+            "generate-workflow" => match &self.workflow_data {
+                Some(workflow) => (
+                    true,
+                    format!("{} agents suggested", workflow.suggested_agents.len()),
+                ),
+                None => (false, "No workflow generated".to_string()),
+            },
+            "register-agents" | "generate-enhanced-agents" => {
+                let count = self.registered_agents.len();
+                let detail = match count {
+                    1 => "1 agent registered".to_string(),
+                    n => format!("{n} agents registered"),
+                };
+                (count > 0, detail)
+            }
+            "create-main-file" => {
+                let main_file = crate::workspace_doctor::MAIN_AI_FILE;
+                if self
+                    .generated_files
+                    .iter()
+                    .any(|file| file.path == main_file)
+                {
+                    (true, format!("{main_file} created"))
+                } else {
+                    (false, format!("{main_file} not created"))
+                }
+            }
+            "write-manifest" if self.manifest_data.is_some() => {
+                (true, "Manifest written".to_string())
+            }
+            "write-manifest" => (false, "No manifest written".to_string()),
+            _ => return None,
+        };
+        Some(SetupStepStatus {
+            id: id.to_string(),
+            name: name.to_string(),
+            complete,
+            detail,
+        })
+    }
+}
+
+/// Completion of one setup step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupStepStatus {
+    pub id: String,
+    pub name: String,
+    pub complete: bool,
+    /// What the workspace context records for the step, e.g. "3 agents registered"
+    pub detail: String,
+}
+
+/// Setup progress of a workspace, as a checklist of steps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupStatus {
+    pub ai_tool_type: AiToolType,
+    pub steps: Vec<SetupStepStatus>,
+    pub completed_steps: usize,
+    pub total_steps: usize,
+    /// Whether every step is complete
+    pub complete: bool,
+    pub agent_count: usize,
+    /// First step that is not complete yet
+    pub next_step: Option<String>,
 }
 
 #[cfg(test)]
@@ -2068,6 +2638,229 @@ Test project overview.
         assert_eq!(response.payload.ai_tool_type, AiToolType::ClaudeCode);
     }
 
+    #[tokio::test]
+    async fn test_manifest_is_reproducible() {
+        let prd_content = r#"
+# Reproducible Project
+
+## Overview
+A web application with a React frontend and a Node.js backend.
+
+## Objectives
+- Ship the dashboard
+- Ship the reports
+- Ship the dashboard
+
+## Technical Requirements
+- React frontend
+- Node.js backend
+- PostgreSQL database
+"#;
+        let prd = PrdDocument::from_content(prd_content).unwrap();
+        assert_eq!(prd.objectives, ["Ship the dashboard", "Ship the reports"]);
+
+        let created_at = "2025-01-01T00:00:00Z".parse().unwrap();
+        let service = || {
+            WorkspaceSetupService::with_config(WorkspaceSetupConfig {
+                clock: Arc::new(FixedClock(created_at)),
+                ..WorkspaceSetupConfig::default()
+            })
+        };
+        let agents = [AgentRegistration {
+            name: "frontend-developer".to_string(),
+            description: "Builds the dashboard".to_string(),
+            prompt: String::new(),
+            capabilities: vec!["frontend-development".to_string()],
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: vec![],
+        }];
+        let manifest = |service: WorkspaceSetupService| {
+            let prd = prd.clone();
+            let agents = agents.clone();
+            async move {
+                let manifest = service
+                    .generate_workspace_manifest(&prd, &agents, true)
+                    .await
+                    .unwrap()
+                    .payload;
+                serde_json::to_string(&manifest).unwrap()
+            }
+        };
+
+        let first = manifest(service()).await;
+        assert_eq!(first, manifest(service()).await);
+        assert!(first.contains("2025-01-01T00:00:00Z"));
+    }
+
+    #[tokio::test]
+    async fn test_update_main_file_keeps_user_sections() {
+        let service = WorkspaceSetupService::new();
+        let agents = [AgentRegistration {
+            name: "backend-dev".to_string(),
+            description: "Builds the API".to_string(),
+            prompt: String::new(),
+            capabilities: vec!["backend".to_string()],
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: vec![],
+        }];
+
+        let created = service
+            .update_main_file(
+                None,
+                "# Shop\n\nRules.",
+                AiToolType::ClaudeCode,
+                &agents,
+                false,
+            )
+            .await
+            .unwrap()
+            .payload;
+        assert!(created.content.contains("| `backend-dev` | backend | - |"));
+        assert_eq!(
+            created.section_merge.unwrap().added,
+            [AGENT_ROSTER_SECTION, MCP_USAGE_SECTION]
+        );
+
+        let edited = created.content.replace("Rules.", "House rules, amended.");
+        let response = service
+            .update_main_file(Some(&edited), "ignored", AiToolType::ClaudeCode, &[], false)
+            .await
+            .unwrap();
+        let merge = response.payload.section_merge.unwrap();
+        assert!(response.payload.content.contains("House rules, amended."));
+        assert!(response
+            .payload
+            .content
+            .contains("No agents registered yet"));
+        assert_eq!(merge.updated, [AGENT_ROSTER_SECTION]);
+        assert_eq!(merge.unchanged, [MCP_USAGE_SECTION]);
+        assert!(response.logs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_main_file_token_budget() {
+        let service = WorkspaceSetupService::new().with_token_budgets(TokenBudgets {
+            main_ai_file: 50,
+            ..TokenBudgets::default()
+        });
+        let short = service
+            .create_main_file(
+                "# Project\n\nCoordinate through Axon.",
+                AiToolType::ClaudeCode,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(short.logs.is_empty());
+
+        let long = "Agents claim tasks and report progress through task messages.\n".repeat(20);
+        let response = service
+            .create_main_file(&long, AiToolType::ClaudeCode, None)
+            .await
+            .unwrap();
+        let estimate = response.payload.token_estimate.unwrap();
+        assert!(estimate.over_budget && estimate.tokens > 200);
+        assert!(response.logs[0].starts_with("CLAUDE.md is about"));
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_agent_prompt() {
+        let service = WorkspaceSetupService::new();
+        let agent = |name: &str, capability: &str| AgentRegistration {
+            name: name.to_string(),
+            description: format!("The {name}"),
+            prompt: String::new(),
+            capabilities: vec![capability.to_string()],
+            ai_tool_type: AiToolType::ClaudeCode,
+            dependencies: vec![],
+        };
+        let mut context = WorkspaceContext::new("default".to_string());
+        context.register_agent(agent("cli-developer", "rust"));
+        let prd = create_test_prd("Markdown CLI", "A command line tool", &["Rust CLI"]);
+
+        let first = service
+            .regenerate_agent_prompt(
+                &context,
+                "cli-developer",
+                Some(&prd),
+                &PromptStyle::default(),
+            )
+            .await
+            .unwrap();
+        assert!(first.changed);
+        assert!(first.prompt.contains("Project: Markdown CLI"));
+        assert!(first.diff.starts_with("@@ -0,0 +1,"));
+        assert!(!first.token_estimate.over_budget);
+
+        context.registered_agents[0].prompt = first.prompt.clone();
+        let unchanged = service
+            .regenerate_agent_prompt(
+                &context,
+                "cli-developer",
+                Some(&prd),
+                &PromptStyle::default(),
+            )
+            .await
+            .unwrap();
+        assert!(!unchanged.changed);
+        assert!(unchanged.diff.is_empty());
+
+        context.register_agent(agent("qa-tester", "testing"));
+        let with_team = service
+            .regenerate_agent_prompt(
+                &context,
+                "cli-developer",
+                Some(&prd),
+                &PromptStyle::default(),
+            )
+            .await
+            .unwrap();
+        assert!(with_team.diff.contains("+- qa-tester: testing"));
+
+        assert!(service
+            .regenerate_agent_prompt(&context, "unknown", None, &PromptStyle::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_setup_status() {
+        let service = WorkspaceSetupService::new();
+        let instructions = service
+            .get_setup_instructions(AiToolType::ClaudeCode)
+            .await
+            .unwrap()
+            .payload;
+        let mut context = WorkspaceContext::new("default".to_string());
+
+        let status = context.setup_status(&instructions);
+        assert_eq!(status.completed_steps, 0);
+        assert_eq!(status.next_step.as_deref(), Some("analyze-prd"));
+        assert!(status.steps.iter().any(|step| step.id == "write-manifest"));
+
+        context.prd_content = Some("# Project".to_string());
+        for name in ["backend-developer", "qa-tester"] {
+            context.registered_agents.push(AgentRegistration {
+                name: name.to_string(),
+                description: String::new(),
+                prompt: String::new(),
+                capabilities: vec![],
+                ai_tool_type: AiToolType::ClaudeCode,
+                dependencies: vec![],
+            });
+        }
+        let status = context.setup_status(&instructions);
+        let agents = status
+            .steps
+            .iter()
+            .find(|step| step.id == "register-agents")
+            .unwrap();
+        assert!(agents.complete);
+        assert_eq!(agents.detail, "2 agents registered");
+        assert_eq!(status.agent_count, 2);
+        assert!(!status.complete);
+    }
+
     // Helper function to create mock PRD documents for testing
     fn create_test_prd(title: &str, overview: &str, tech_requirements: &[&str]) -> PrdDocument {
         PrdDocument {
@@ -2307,6 +3100,107 @@ Test project overview.
             assert_eq!(archetype, ProjectArchetype::Generic);
         }
 
+        #[tokio::test]
+        async fn test_project_manifests_inform_classification() {
+            let root = tempfile::tempdir().unwrap();
+            std::fs::write(
+                root.path().join("Cargo.toml"),
+                "[package]\nname = \"tool\"\n\n[dependencies]\nclap = \"4\"\n",
+            )
+            .unwrap();
+            let service = WorkspaceSetupService::with_config(WorkspaceSetupConfig {
+                project_root: Some(root.path().to_path_buf()),
+                ..WorkspaceSetupConfig::default()
+            });
+            let prd = create_test_prd(
+                "Some Project",
+                "We want to build something cool",
+                &["modern technology", "innovative solution", "user-friendly"],
+            );
+
+            assert_eq!(
+                service.classify_project_archetype(&prd),
+                ProjectArchetype::CliTool
+            );
+            let workflow = service
+                .get_agentic_workflow_description(&prd)
+                .await
+                .unwrap()
+                .payload;
+            assert!(workflow.suggested_agents[0]
+                .required_capabilities
+                .contains(&"rust".to_string()));
+        }
+
+        #[tokio::test]
+        async fn test_custom_archetype_takes_precedence() {
+            let root = tempfile::tempdir().unwrap();
+            std::fs::create_dir(root.path().join(".axon")).unwrap();
+            std::fs::write(
+                root.path().join(ARCHETYPES_FILE),
+                r#"
+archetypes:
+  - name: Game
+    keywords: [godot]
+    agents:
+      - name: gameplay-programmer
+        description: Implements game mechanics
+        capabilities: [gameplay]
+      - name: level-designer
+        description: Builds levels
+        capabilities: [level-design]
+        depends_on: [gameplay-programmer]
+    workflow_steps:
+      - 1. Prototype the core loop
+"#,
+            )
+            .unwrap();
+            let service = WorkspaceSetupService::with_config(WorkspaceSetupConfig {
+                project_root: Some(root.path().to_path_buf()),
+                ..WorkspaceSetupConfig::default()
+            });
+
+            let prd = create_test_prd("Platformer", "A 2D game", &["Godot 4", "CLI level tool"]);
+            let workflow = service
+                .get_agentic_workflow_description(&prd)
+                .await
+                .unwrap()
+                .payload;
+            assert!(workflow
+                .workflow_description
+                .starts_with("Classified as Game project"));
+            assert_eq!(workflow.recommended_agent_count, 2);
+            assert_eq!(workflow.suggested_agents[1].name, "level-designer");
+            assert_eq!(workflow.workflow_steps, ["1. Prototype the core loop"]);
+
+            let prd = create_test_prd("Converter", "A CLI tool", &["Command-line parsing"]);
+            assert_eq!(
+                service.resolve_archetype(&prd).unwrap().0,
+                ProjectArchetype::CliTool
+            );
+        }
+
+        #[test]
+        fn test_classification_explains_ambiguity() {
+            let service = WorkspaceSetupService::new();
+            let prd = create_test_prd(
+                "JSON Converter",
+                "A command-line converter tool",
+                &["Reusable parsing library", "CLI argument parsing"],
+            );
+            let (archetype, _, classification) = service.resolve_archetype(&prd).unwrap();
+            assert_eq!(archetype, ProjectArchetype::Library);
+            assert_eq!(classification.matched_signals, ["library"]);
+            assert_eq!(classification.runner_ups[0].archetype, "CLI Tool");
+            assert!(classification.confidence < classification.runner_ups[0].confidence);
+
+            let prd = create_test_prd("Some Project", "Something cool", &["user-friendly"]);
+            let (_, _, classification) = service.resolve_archetype(&prd).unwrap();
+            assert_eq!(classification.archetype, "Generic Project");
+            assert_eq!(classification.confidence, 0.0);
+            assert!(classification.runner_ups.is_empty());
+        }
+
         #[test]
         fn test_empty_requirements_classified_as_generic() {
             let service = WorkspaceSetupService::new();
@@ -0,0 +1,30 @@
+-- Task comments: lightweight markdown notes, separate from protocol messages
+CREATE TABLE IF NOT EXISTS task_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_code TEXT NOT NULL,                    -- Task code (e.g., "FEAT-123")
+    author_agent_name TEXT NOT NULL,            -- Author of the comment
+    content TEXT NOT NULL,                      -- Markdown content
+    revision INTEGER NOT NULL DEFAULT 1,        -- Incremented on every edit
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NULL,
+    deleted_at TIMESTAMP NULL,                  -- Soft delete keeps the history
+
+    CHECK (length(trim(task_code)) > 0),
+    CHECK (length(trim(author_agent_name)) > 0),
+    CHECK (length(trim(content)) > 0)
+);
+
+-- Superseded comment content, written on every edit and on deletion
+CREATE TABLE IF NOT EXISTS task_comment_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    comment_id INTEGER NOT NULL,
+    revision INTEGER NOT NULL,                  -- Revision the content had before the change
+    content TEXT NOT NULL,
+    edited_by TEXT NOT NULL,
+    edited_at TIMESTAMP NOT NULL,
+
+    FOREIGN KEY (comment_id) REFERENCES task_comments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_comments_task_code ON task_comments(task_code, created_at);
+CREATE INDEX IF NOT EXISTS idx_task_comment_revisions_comment ON task_comment_revisions(comment_id, revision);
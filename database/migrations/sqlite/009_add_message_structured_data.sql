@@ -0,0 +1,4 @@
+-- Structured JSON payload stored alongside the message text
+-- Validated per message type by TaskValidator::validate_message_data
+
+ALTER TABLE task_messages ADD COLUMN structured_data TEXT NULL;
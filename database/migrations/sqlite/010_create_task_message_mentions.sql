@@ -0,0 +1,12 @@
+-- @agent-name mentions parsed from task message content
+CREATE TABLE IF NOT EXISTS task_message_mentions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    message_id INTEGER NOT NULL,
+    mentioned_agent_name TEXT NOT NULL,         -- Agent addressed by the mention
+    created_at TIMESTAMP NOT NULL,
+
+    UNIQUE (message_id, mentioned_agent_name),
+    FOREIGN KEY (message_id) REFERENCES task_messages(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_message_mentions_agent ON task_message_mentions(mentioned_agent_name, created_at DESC);
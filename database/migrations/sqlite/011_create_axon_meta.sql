@@ -0,0 +1,7 @@
+-- Key/value metadata about the database itself, such as the schema version
+-- and the axon-mcp version that last migrated it
+CREATE TABLE IF NOT EXISTS axon_meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
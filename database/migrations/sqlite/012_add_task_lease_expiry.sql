@@ -0,0 +1,5 @@
+-- Claim leases: when a claim expires, set from the lease requested on claim_task
+ALTER TABLE tasks ADD COLUMN lease_expires_at TIMESTAMP NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_lease_expiry ON tasks(state, lease_expires_at)
+WHERE state = 'InProgress' AND lease_expires_at IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Capability catalog: canonical names that agent and task capabilities are normalized to
+CREATE TABLE IF NOT EXISTS capabilities (
+    name TEXT PRIMARY KEY,                      -- Canonical kebab-case name (e.g., "backend-development")
+    description TEXT NOT NULL DEFAULT '',
+    aliases TEXT NOT NULL DEFAULT '[]',         -- JSON array of alternative spellings
+    replaced_by TEXT NULL,                      -- Replacement of a deprecated capability
+    deprecated_at TIMESTAMP NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CHECK (length(trim(name)) > 0)
+);
@@ -0,0 +1,14 @@
+-- Links between tasks and items in external trackers (e.g. GitHub issues)
+CREATE TABLE IF NOT EXISTS external_links (
+    system TEXT NOT NULL,                       -- External tracker, e.g. "github"
+    task_code TEXT NOT NULL,
+    external_id TEXT NOT NULL,                  -- ID of the item in the tracker (e.g. issue number)
+    url TEXT NULL,
+    synced_state TEXT NULL,                     -- Task state last pushed to the tracker
+    synced_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (system, task_code),
+    UNIQUE (system, external_id),
+    CHECK (length(trim(system)) > 0),
+    CHECK (length(trim(external_id)) > 0)
+);
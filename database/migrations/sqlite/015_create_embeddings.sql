@@ -0,0 +1,15 @@
+-- Embeddings of tasks, messages and comments for semantic search
+CREATE TABLE IF NOT EXISTS embeddings (
+    model TEXT NOT NULL,                        -- Embedding model, e.g. "text-embedding-3-small"
+    source_type TEXT NOT NULL,                  -- "task", "message" or "comment"
+    source_id INTEGER NOT NULL,
+    task_code TEXT NOT NULL,
+    content TEXT NOT NULL,                      -- Text the embedding was computed from
+    embedding BLOB NOT NULL,                    -- float32 vector in sqlite-vec format
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (model, source_type, source_id),
+    CHECK (source_type IN ('task', 'message', 'comment'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_embeddings_task_code ON embeddings(task_code);
@@ -0,0 +1,8 @@
+-- Rolling summaries of the older messages of long task threads
+CREATE TABLE IF NOT EXISTS thread_summaries (
+    task_code TEXT PRIMARY KEY NOT NULL,
+    summary TEXT NOT NULL,
+    message_count INTEGER NOT NULL,             -- Messages covered by the summary
+    last_message_id INTEGER NOT NULL,           -- Newest message covered by the summary
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
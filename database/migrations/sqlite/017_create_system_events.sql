@@ -0,0 +1,15 @@
+-- Events recorded for the coordinator, such as agent performance anomalies
+CREATE TABLE IF NOT EXISTS system_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,                   -- e.g. "agent_idle_claim"
+    entity_id TEXT NULL,                        -- Agent, task code or other entity the event is about
+    data TEXT NOT NULL DEFAULT '{}',            -- JSON details
+    triggered_by TEXT NULL,                     -- Agent that caused the event, if any
+    timestamp TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    severity TEXT NOT NULL DEFAULT 'Info' CHECK (severity IN ('Info', 'Warning', 'Error', 'Critical')),
+
+    CHECK (length(trim(event_type)) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_system_events_lookup ON system_events(event_type, entity_id, timestamp);
+CREATE INDEX IF NOT EXISTS idx_system_events_timestamp ON system_events(timestamp);
@@ -0,0 +1,6 @@
+-- Last modification time of each task, for incremental sync with modified_since
+ALTER TABLE tasks ADD COLUMN updated_at TIMESTAMP NULL;
+
+UPDATE tasks SET updated_at = COALESCE(done_at, claimed_at, inserted_at);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_updated_at ON tasks(updated_at);
@@ -0,0 +1,23 @@
+-- When each task entered its current state, for time-in-state reporting
+ALTER TABLE tasks ADD COLUMN state_changed_at TIMESTAMP NULL;
+
+UPDATE tasks SET state_changed_at = COALESCE(done_at, claimed_at, updated_at, inserted_at);
+
+-- Every claim of a task, kept after the task is released or reclaimed
+CREATE TABLE IF NOT EXISTS task_claims (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    agent_name TEXT NOT NULL,
+    claimed_at TIMESTAMP NOT NULL,
+    released_at TIMESTAMP NULL,                 -- NULL while the agent holds the task
+    release_reason TEXT NULL,                   -- "released", "timed_out" or "completed"
+
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_claims_task ON task_claims(task_id, claimed_at);
+
+-- Claims held before history was recorded
+INSERT INTO task_claims (task_id, agent_name, claimed_at)
+SELECT id, owner_agent_name, claimed_at FROM tasks
+WHERE claimed_at IS NOT NULL AND owner_agent_name IS NOT NULL;
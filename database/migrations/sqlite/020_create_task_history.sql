@@ -0,0 +1,65 @@
+-- Append-only copies of task rows, written by triggers while event history is enabled
+CREATE TABLE IF NOT EXISTS task_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,                   -- "created" or "updated"
+    occurred_at TIMESTAMP NOT NULL,
+    task_id INTEGER NOT NULL,                   -- no foreign key: history outlives archival
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    owner_agent_name TEXT NULL,
+    state TEXT NOT NULL,
+    inserted_at TIMESTAMP NOT NULL,
+    done_at TIMESTAMP NULL,
+    claimed_at TIMESTAMP NULL,
+    lease_expires_at TIMESTAMP NULL,
+    updated_at TIMESTAMP NULL,
+    state_changed_at TIMESTAMP NULL,
+    workflow_definition_id INTEGER NULL,
+    workflow_cursor TEXT NULL,
+    priority_score REAL NULL,
+    parent_task_id INTEGER NULL,
+    failure_count INTEGER NULL,
+    required_capabilities TEXT NULL,
+    estimated_effort INTEGER NULL,
+    confidence_threshold REAL NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_events_occurred ON task_events(occurred_at);
+CREATE INDEX IF NOT EXISTS idx_task_events_task ON task_events(task_id, id);
+
+-- Full copies of the board from which the events after them are replayed
+CREATE TABLE IF NOT EXISTS task_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    taken_at TIMESTAMP NOT NULL,
+    last_event_id INTEGER NOT NULL DEFAULT 0    -- events up to this one are in the snapshot
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_snapshots_taken ON task_snapshots(taken_at);
+
+CREATE TABLE IF NOT EXISTS task_snapshot_rows (
+    snapshot_id INTEGER NOT NULL,
+    task_id INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    owner_agent_name TEXT NULL,
+    state TEXT NOT NULL,
+    inserted_at TIMESTAMP NOT NULL,
+    done_at TIMESTAMP NULL,
+    claimed_at TIMESTAMP NULL,
+    lease_expires_at TIMESTAMP NULL,
+    updated_at TIMESTAMP NULL,
+    state_changed_at TIMESTAMP NULL,
+    workflow_definition_id INTEGER NULL,
+    workflow_cursor TEXT NULL,
+    priority_score REAL NULL,
+    parent_task_id INTEGER NULL,
+    failure_count INTEGER NULL,
+    required_capabilities TEXT NULL,
+    estimated_effort INTEGER NULL,
+    confidence_threshold REAL NULL,
+
+    PRIMARY KEY (snapshot_id, task_id),
+    FOREIGN KEY (snapshot_id) REFERENCES task_snapshots(id) ON DELETE CASCADE
+);
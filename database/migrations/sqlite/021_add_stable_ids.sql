@@ -0,0 +1,6 @@
+-- Stable identifiers (UUID or ULID) kept alongside the numeric keys
+ALTER TABLE tasks ADD COLUMN uid TEXT NULL;
+ALTER TABLE task_messages ADD COLUMN uid TEXT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tasks_uid ON tasks(uid) WHERE uid IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_task_messages_uid ON task_messages(uid) WHERE uid IS NOT NULL;
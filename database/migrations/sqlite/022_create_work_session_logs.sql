@@ -0,0 +1,12 @@
+-- Transcript chunks appended by agents to their work sessions
+CREATE TABLE IF NOT EXISTS work_session_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    appended_at TIMESTAMP NOT NULL,
+    raw_bytes INTEGER NOT NULL,                 -- Size of the chunk's JSON before compression
+    chunk BLOB NOT NULL,                        -- Deflate-compressed JSON of the chunk
+
+    FOREIGN KEY (session_id) REFERENCES work_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_session_logs_session ON work_session_logs(session_id, id);
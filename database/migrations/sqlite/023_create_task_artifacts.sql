@@ -0,0 +1,17 @@
+-- Files produced by tasks, registered by agents for review and audit
+CREATE TABLE IF NOT EXISTS task_artifacts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_code TEXT NOT NULL,                    -- Task code (e.g., "FEAT-123")
+    path TEXT NOT NULL,                         -- Relative to the project root, '/' separated
+    kind TEXT NOT NULL,                         -- e.g. code, test, doc, config
+    checksum TEXT NOT NULL,                     -- 'sha256:' and the hex digest
+    size_bytes INTEGER NOT NULL,
+    registered_by TEXT,
+    registered_at TIMESTAMP NOT NULL,
+
+    CHECK (length(trim(task_code)) > 0),
+    CHECK (length(trim(path)) > 0),
+    UNIQUE (task_code, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_artifacts_kind ON task_artifacts(kind, task_code);
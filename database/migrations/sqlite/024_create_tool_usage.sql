@@ -0,0 +1,15 @@
+-- Tool calls of identified agents, counted to spot agents stuck in polling loops
+CREATE TABLE IF NOT EXISTS tool_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_name TEXT NOT NULL,
+    tool_name TEXT NOT NULL,                    -- MCP method, e.g. "list_tasks"
+    called_at TIMESTAMP NOT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    is_error BOOLEAN NOT NULL DEFAULT FALSE,    -- Answered with a JSON-RPC error
+
+    CHECK (length(trim(agent_name)) > 0),
+    CHECK (length(trim(tool_name)) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tool_usage_agent ON tool_usage(agent_name, called_at);
+CREATE INDEX IF NOT EXISTS idx_tool_usage_called_at ON tool_usage(called_at);
@@ -1,43 +1,23 @@
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqliteRow, Row};
+use sqlx::{sqlite::SqliteRow, Row, TypeInfo, ValueRef};
 use task_core::{
     error::{Result, TaskError},
-    models::{Task, TaskFilter, TaskMessage, TaskState},
+    models::{
+        Capability, EventSeverity, ExternalLink, SystemEvent, Task, TaskClaim, TaskComment,
+        TaskCommentRevision, TaskFilter, TaskMention, TaskMessage, TaskState,
+    },
+    repository::TaskGroupBy,
 };
 
 /// Convert TaskState enum to string for database storage
 pub fn state_to_string(state: TaskState) -> &'static str {
-    match state {
-        TaskState::Created => "Created",
-        TaskState::InProgress => "InProgress",
-        TaskState::Blocked => "Blocked",
-        TaskState::Review => "Review",
-        TaskState::Done => "Done",
-        TaskState::Archived => "Archived",
-        TaskState::PendingDecomposition => "PendingDecomposition",
-        TaskState::PendingHandoff => "PendingHandoff",
-        TaskState::Quarantined => "Quarantined",
-        TaskState::WaitingForDependency => "WaitingForDependency",
-    }
+    state.as_str()
 }
 
 /// Convert string from database to TaskState enum
 pub fn string_to_state(s: &str) -> Result<TaskState> {
-    match s {
-        "Created" => Ok(TaskState::Created),
-        "InProgress" => Ok(TaskState::InProgress),
-        "Blocked" => Ok(TaskState::Blocked),
-        "Review" => Ok(TaskState::Review),
-        "Done" => Ok(TaskState::Done),
-        "Archived" => Ok(TaskState::Archived),
-        "PendingDecomposition" => Ok(TaskState::PendingDecomposition),
-        "PendingHandoff" => Ok(TaskState::PendingHandoff),
-        "Quarantined" => Ok(TaskState::Quarantined),
-        "WaitingForDependency" => Ok(TaskState::WaitingForDependency),
-        _ => Err(TaskError::Database(format!(
-            "Invalid task state in database: {s}"
-        ))),
-    }
+    s.parse()
+        .map_err(|_| TaskError::Database(format!("Invalid task state in database: {s}")))
 }
 
 /// Convert SQLite row to Task model with MCP v2 support
@@ -48,6 +28,18 @@ pub fn row_to_task(row: &SqliteRow) -> Result<Task> {
     let inserted_at: DateTime<Utc> = row.get("inserted_at");
     let done_at: Option<DateTime<Utc>> = row.get("done_at");
     let claimed_at: Option<DateTime<Utc>> = row.try_get("claimed_at").ok().flatten();
+    let lease_expires_at: Option<DateTime<Utc>> = row.try_get("lease_expires_at").ok().flatten();
+    // Rows written before updated_at was tracked fall back to the creation time
+    let updated_at: DateTime<Utc> = row
+        .try_get("updated_at")
+        .ok()
+        .flatten()
+        .unwrap_or(inserted_at);
+    let state_changed_at: DateTime<Utc> = row
+        .try_get("state_changed_at")
+        .ok()
+        .flatten()
+        .unwrap_or(updated_at);
 
     // Parse required_capabilities from JSON string
     let required_capabilities: Vec<String> = row
@@ -60,6 +52,7 @@ pub fn row_to_task(row: &SqliteRow) -> Result<Task> {
     // Create task with MCP v2 fields
     Ok(Task {
         id: row.get("id"),
+        uid: row.try_get("uid").ok().flatten(),
         code: row.get("code"),
         name: row.get("name"),
         description: row.get("description"),
@@ -68,6 +61,9 @@ pub fn row_to_task(row: &SqliteRow) -> Result<Task> {
         inserted_at,
         done_at,
         claimed_at,
+        lease_expires_at,
+        updated_at,
+        state_changed_at,
 
         // MCP v2 fields with proper defaults
         workflow_definition_id: row.try_get("workflow_definition_id").ok().flatten(),
@@ -87,6 +83,7 @@ pub fn row_to_task_message(row: &SqliteRow) -> Result<TaskMessage> {
 
     Ok(TaskMessage {
         id: row.get("id"),
+        uid: row.try_get("uid").ok().flatten(),
         task_code: row.get("task_code"),
         author_agent_name: row.get("author_agent_name"),
         target_agent_name: row.get("target_agent_name"),
@@ -94,6 +91,151 @@ pub fn row_to_task_message(row: &SqliteRow) -> Result<TaskMessage> {
         created_at,
         content: row.get("content"),
         reply_to_message_id: row.get("reply_to_message_id"),
+        structured_data: row
+            .try_get::<Option<String>, _>("structured_data")
+            .ok()
+            .flatten()
+            .and_then(|data| serde_json::from_str(&data).ok()),
+    })
+}
+
+/// Convert SQLite row to TaskComment model
+pub fn row_to_task_comment(row: &SqliteRow) -> Result<TaskComment> {
+    Ok(TaskComment {
+        id: row.get("id"),
+        task_code: row.get("task_code"),
+        author_agent_name: row.get("author_agent_name"),
+        content: row.get("content"),
+        revision: row.get("revision"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        deleted_at: row.get("deleted_at"),
+    })
+}
+
+/// Convert SQLite row to TaskCommentRevision model
+pub fn row_to_task_comment_revision(row: &SqliteRow) -> Result<TaskCommentRevision> {
+    Ok(TaskCommentRevision {
+        id: row.get("id"),
+        comment_id: row.get("comment_id"),
+        revision: row.get("revision"),
+        content: row.get("content"),
+        edited_by: row.get("edited_by"),
+        edited_at: row.get("edited_at"),
+    })
+}
+
+/// Convert SQLite row to Capability model
+pub fn row_to_capability(row: &SqliteRow) -> Result<Capability> {
+    let aliases: String = row.get("aliases");
+    Ok(Capability {
+        name: row.get("name"),
+        description: row.get("description"),
+        aliases: serde_json::from_str(&aliases).unwrap_or_default(),
+        replaced_by: row.get("replaced_by"),
+        deprecated_at: row.get("deprecated_at"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// Convert SQLite row to ExternalLink model
+pub fn row_to_external_link(row: &SqliteRow) -> Result<ExternalLink> {
+    let synced_state: Option<String> = row.get("synced_state");
+    Ok(ExternalLink {
+        system: row.get("system"),
+        task_code: row.get("task_code"),
+        external_id: row.get("external_id"),
+        url: row.get("url"),
+        synced_state: synced_state.as_deref().map(string_to_state).transpose()?,
+        synced_at: row.get("synced_at"),
+    })
+}
+
+/// Convert EventSeverity enum to string for database storage
+pub fn severity_to_string(severity: EventSeverity) -> &'static str {
+    match severity {
+        EventSeverity::Info => "Info",
+        EventSeverity::Warning => "Warning",
+        EventSeverity::Error => "Error",
+        EventSeverity::Critical => "Critical",
+    }
+}
+
+/// Convert SQLite row to SystemEvent model
+pub fn row_to_system_event(row: &SqliteRow) -> Result<SystemEvent> {
+    let severity: String = row.get("severity");
+    let data: String = row.get("data");
+    Ok(SystemEvent {
+        id: row.get("id"),
+        event_type: row.get("event_type"),
+        entity_id: row.get("entity_id"),
+        data: serde_json::from_str(&data).unwrap_or_default(),
+        triggered_by: row.get("triggered_by"),
+        timestamp: row.get("timestamp"),
+        severity: match severity.as_str() {
+            "Info" => EventSeverity::Info,
+            "Warning" => EventSeverity::Warning,
+            "Error" => EventSeverity::Error,
+            "Critical" => EventSeverity::Critical,
+            _ => {
+                return Err(TaskError::Database(format!(
+                    "Invalid event severity in database: {severity}"
+                )))
+            }
+        },
+    })
+}
+
+/// Convert the values of an ad-hoc query row to JSON by their storage class
+///
+/// Blobs are returned as lowercase hex strings.
+pub fn row_to_json_values(row: &SqliteRow) -> Result<Vec<serde_json::Value>> {
+    let mut values = Vec::with_capacity(row.len());
+    for index in 0..row.len() {
+        let raw = row.try_get_raw(index).map_err(sqlx_error_to_task_error)?;
+        let value = if raw.is_null() {
+            serde_json::Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => row.try_get::<i64, _>(index).map(serde_json::Value::from),
+                "REAL" => row.try_get::<f64, _>(index).map(serde_json::Value::from),
+                "BLOB" => row.try_get::<Vec<u8>, _>(index).map(|bytes| {
+                    serde_json::Value::from(
+                        bytes.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+                    )
+                }),
+                _ => row.try_get::<String, _>(index).map(serde_json::Value::from),
+            }
+            .map_err(sqlx_error_to_task_error)?
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Convert SQLite row to TaskMention model
+pub fn row_to_task_mention(row: &SqliteRow) -> Result<TaskMention> {
+    Ok(TaskMention {
+        id: row.get("id"),
+        message_id: row.get("message_id"),
+        task_code: row.get("task_code"),
+        mentioned_agent_name: row.get("mentioned_agent_name"),
+        author_agent_name: row.get("author_agent_name"),
+        message_type: row.get("message_type"),
+        content: row.get("content"),
+        created_at: row.get("created_at"),
+    })
+}
+
+/// Convert SQLite row to TaskClaim model
+pub fn row_to_task_claim(row: &SqliteRow) -> Result<TaskClaim> {
+    Ok(TaskClaim {
+        task_id: row.get("task_id"),
+        agent_name: row.get("agent_name"),
+        claimed_at: row.get("claimed_at"),
+        released_at: row.get("released_at"),
+        release_reason: row.get("release_reason"),
     })
 }
 
@@ -130,52 +272,114 @@ pub fn sqlx_error_to_task_error(err: sqlx::Error) -> TaskError {
     }
 }
 
-/// Build dynamic WHERE clause for task filtering using QueryBuilder with proper type binding
-#[allow(dead_code)] // Used in sqlite.rs but may not be detected by compiler
-pub fn build_filter_query(filter: &TaskFilter) -> sqlx::QueryBuilder<sqlx::Sqlite> {
-    let mut query_builder: sqlx::QueryBuilder<sqlx::Sqlite> =
-        sqlx::QueryBuilder::new("SELECT id, code, name, description, owner_agent_name, state, inserted_at, done_at, claimed_at, workflow_definition_id, workflow_cursor, priority_score, parent_task_id, failure_count, required_capabilities, estimated_effort, confidence_threshold FROM tasks");
-
+/// Append the WHERE clause of `filter` to a query over the tasks table
+fn push_filter_conditions<'a>(
+    query_builder: &mut sqlx::QueryBuilder<'a, sqlx::Sqlite>,
+    filter: &'a TaskFilter,
+) {
     let mut has_conditions = false;
-
-    if let Some(ref owner) = filter.owner {
-        query_builder.push(" WHERE owner_agent_name = ");
-        query_builder.push_bind(owner);
+    let mut next_condition = |query_builder: &mut sqlx::QueryBuilder<sqlx::Sqlite>| {
+        query_builder.push(if has_conditions { " AND " } else { " WHERE " });
         has_conditions = true;
+    };
+
+    let owners = filter.owner_set();
+    if !owners.is_empty() {
+        next_condition(query_builder);
+        query_builder.push("owner_agent_name IN (");
+        let mut separated = query_builder.separated(", ");
+        for owner in owners {
+            separated.push_bind(owner);
+        }
+        query_builder.push(")");
     }
 
-    if let Some(state) = filter.state {
-        if has_conditions {
-            query_builder.push(" AND ");
-        } else {
-            query_builder.push(" WHERE ");
-            has_conditions = true;
+    let states = filter.state_set();
+    if !states.is_empty() {
+        next_condition(query_builder);
+        query_builder.push("state IN (");
+        let mut separated = query_builder.separated(", ");
+        for state in states {
+            separated.push_bind(state_to_string(state));
         }
-        query_builder.push("state = ");
-        query_builder.push_bind(state_to_string(state));
+        query_builder.push(")");
     }
 
-    if let Some(date_from) = filter.date_from {
-        if has_conditions {
-            query_builder.push(" AND ");
+    if let Some(prefix) = filter.code_prefix() {
+        next_condition(query_builder);
+        // instr() matches case-sensitively and needs no escaping, unlike LIKE
+        query_builder.push("instr(code, ");
+        query_builder.push_bind(prefix);
+        query_builder.push(") = 1");
+    }
+
+    if let Some(has_owner) = filter.has_owner {
+        next_condition(query_builder);
+        query_builder.push(if has_owner {
+            "owner_agent_name IS NOT NULL"
         } else {
-            query_builder.push(" WHERE ");
-            has_conditions = true;
-        }
+            "owner_agent_name IS NULL"
+        });
+    }
+
+    if let Some(date_from) = filter.date_from {
+        next_condition(query_builder);
         query_builder.push("inserted_at >= ");
         query_builder.push_bind(date_from.to_rfc3339());
     }
 
     if let Some(date_to) = filter.date_to {
-        if has_conditions {
-            query_builder.push(" AND ");
-        } else {
-            query_builder.push(" WHERE ");
-        }
+        next_condition(query_builder);
         query_builder.push("inserted_at <= ");
         query_builder.push_bind(date_to.to_rfc3339());
     }
 
+    if let Some(modified_since) = filter.modified_since {
+        next_condition(query_builder);
+        query_builder.push("updated_at >= ");
+        query_builder.push_bind(modified_since.to_rfc3339());
+    }
+}
+
+/// Count tasks matching `filter`, per group when `group_by` is given
+///
+/// Rows have a nullable `group_key` and a `count`; limit and offset are ignored.
+pub fn build_count_query(
+    filter: &TaskFilter,
+    group_by: Option<TaskGroupBy>,
+) -> sqlx::QueryBuilder<'_, sqlx::Sqlite> {
+    let (select, group) = match group_by {
+        None => ("SELECT NULL AS group_key, COUNT(*) AS count FROM tasks", ""),
+        Some(TaskGroupBy::State) => (
+            "SELECT state AS group_key, COUNT(*) AS count FROM tasks",
+            " GROUP BY state",
+        ),
+        Some(TaskGroupBy::Owner) => (
+            "SELECT owner_agent_name AS group_key, COUNT(*) AS count FROM tasks",
+            " GROUP BY owner_agent_name",
+        ),
+        Some(TaskGroupBy::Capability) => (
+            "SELECT capability.value AS group_key, COUNT(*) AS count \
+             FROM (SELECT required_capabilities FROM tasks",
+            ") AS matching, \
+             json_each(COALESCE(matching.required_capabilities, '[]')) AS capability \
+             GROUP BY capability.value",
+        ),
+    };
+    let mut query_builder: sqlx::QueryBuilder<sqlx::Sqlite> = sqlx::QueryBuilder::new(select);
+    push_filter_conditions(&mut query_builder, filter);
+    query_builder.push(group);
+    query_builder
+}
+
+/// Build dynamic WHERE clause for task filtering using QueryBuilder with proper type binding
+#[allow(dead_code)] // Used in sqlite.rs but may not be detected by compiler
+pub fn build_filter_query(filter: &TaskFilter) -> sqlx::QueryBuilder<'_, sqlx::Sqlite> {
+    let mut query_builder: sqlx::QueryBuilder<sqlx::Sqlite> =
+        sqlx::QueryBuilder::new("SELECT id, uid, code, name, description, owner_agent_name, state, inserted_at, done_at, claimed_at, lease_expires_at, updated_at, state_changed_at, workflow_definition_id, workflow_cursor, priority_score, parent_task_id, failure_count, required_capabilities, estimated_effort, confidence_threshold FROM tasks");
+
+    push_filter_conditions(&mut query_builder, filter);
+
     query_builder.push(" ORDER BY inserted_at DESC");
 
     if let Some(limit) = filter.limit {
@@ -196,9 +400,9 @@ pub fn build_filter_query(filter: &TaskFilter) -> sqlx::QueryBuilder<sqlx::Sqlit
 pub fn build_work_discovery_query(
     agent_capabilities: &[String],
     limit: Option<i32>,
-) -> sqlx::QueryBuilder<sqlx::Sqlite> {
+) -> sqlx::QueryBuilder<'_, sqlx::Sqlite> {
     let mut query_builder: sqlx::QueryBuilder<sqlx::Sqlite> = sqlx::QueryBuilder::new(
-        r#"SELECT id, code, name, description, owner_agent_name, state, inserted_at, done_at,
+        r#"SELECT id, uid, code, name, description, owner_agent_name, state, inserted_at, done_at,
                   workflow_definition_id, workflow_cursor, priority_score, parent_task_id,
                   failure_count, required_capabilities, estimated_effort, confidence_threshold
            FROM tasks 
@@ -217,7 +421,7 @@ pub fn build_work_discovery_query(
                 query_builder.push(" OR ");
             }
             query_builder.push("required_capabilities LIKE ");
-            query_builder.push_bind(format!("%\"{capability}\""));
+            query_builder.push_bind(format!("%\"{capability}\"%"));
         }
         query_builder.push(")");
     }
@@ -243,14 +447,34 @@ pub fn build_filter_conditions(filter: &TaskFilter) -> (String, Vec<String>) {
     let mut conditions = Vec::new();
     let mut params = Vec::new();
 
-    if let Some(ref owner) = filter.owner {
-        conditions.push("owner_agent_name = ?".to_string());
-        params.push(owner.clone());
+    let owners = filter.owner_set();
+    if !owners.is_empty() {
+        conditions.push(format!(
+            "owner_agent_name IN ({})",
+            vec!["?"; owners.len()].join(", ")
+        ));
+        params.extend(owners);
+    }
+
+    let states = filter.state_set();
+    if !states.is_empty() {
+        conditions.push(format!("state IN ({})", vec!["?"; states.len()].join(", ")));
+        params.extend(
+            states
+                .into_iter()
+                .map(|state| state_to_string(state).to_string()),
+        );
+    }
+
+    if let Some(prefix) = filter.code_prefix() {
+        conditions.push("instr(code, ?) = 1".to_string());
+        params.push(prefix.to_string());
     }
 
-    if let Some(state) = filter.state {
-        conditions.push("state = ?".to_string());
-        params.push(state_to_string(state).to_string());
+    match filter.has_owner {
+        Some(true) => conditions.push("owner_agent_name IS NOT NULL".to_string()),
+        Some(false) => conditions.push("owner_agent_name IS NULL".to_string()),
+        None => {}
     }
 
     if let Some(date_from) = filter.date_from {
@@ -263,6 +487,11 @@ pub fn build_filter_conditions(filter: &TaskFilter) -> (String, Vec<String>) {
         params.push(date_to.to_rfc3339());
     }
 
+    if let Some(modified_since) = filter.modified_since {
+        conditions.push("updated_at >= ?".to_string());
+        params.push(modified_since.to_rfc3339());
+    }
+
     let where_clause = if conditions.is_empty() {
         String::new()
     } else {
@@ -346,7 +575,7 @@ mod tests {
             ..Default::default()
         };
         let (where_clause, params) = build_filter_conditions(&filter);
-        assert_eq!(where_clause, "WHERE owner_agent_name = ?");
+        assert_eq!(where_clause, "WHERE owner_agent_name IN (?)");
         assert_eq!(params, vec!["test-agent"]);
 
         // Test filter with state
@@ -355,24 +584,45 @@ mod tests {
             ..Default::default()
         };
         let (where_clause, params) = build_filter_conditions(&filter);
-        assert_eq!(where_clause, "WHERE state = ?");
+        assert_eq!(where_clause, "WHERE state IN (?)");
         assert_eq!(params, vec!["InProgress"]);
 
+        // Test filter with state lists, code prefix and unowned tasks
+        let filter = TaskFilter {
+            state: Some(TaskState::Created),
+            states: vec![TaskState::Blocked, TaskState::Created],
+            code_prefix: Some("ARCH-*".to_string()),
+            has_owner: Some(false),
+            ..Default::default()
+        };
+        let (where_clause, params) = build_filter_conditions(&filter);
+        assert_eq!(
+            where_clause,
+            "WHERE state IN (?, ?) AND instr(code, ?) = 1 AND owner_agent_name IS NULL"
+        );
+        assert_eq!(params, vec!["Created", "Blocked", "ARCH-"]);
+
         // Test filter with multiple conditions
         let filter = TaskFilter {
             owner: Some("test-agent".to_string()),
             state: Some(TaskState::Done),
+            states: Vec::new(),
+            owners: Vec::new(),
+            code_prefix: None,
+            has_owner: None,
             date_from: Some(Utc::now()),
             date_to: Some(Utc::now()),
+            modified_since: None,
             completed_after: None,
             completed_before: None,
             limit: None,
             offset: None,
+            include_archive: false,
         };
         let (where_clause, params) = build_filter_conditions(&filter);
         assert!(where_clause.starts_with("WHERE"));
-        assert!(where_clause.contains("owner_agent_name = ?"));
-        assert!(where_clause.contains("state = ?"));
+        assert!(where_clause.contains("owner_agent_name IN (?)"));
+        assert!(where_clause.contains("state IN (?)"));
         assert!(where_clause.contains("inserted_at >= ?"));
         assert!(where_clause.contains("inserted_at <= ?"));
         assert_eq!(params.len(), 4);
@@ -387,12 +637,18 @@ mod tests {
         let filter = TaskFilter {
             owner: Some("test-agent".to_string()),
             state: Some(TaskState::InProgress),
+            states: Vec::new(),
+            owners: Vec::new(),
+            code_prefix: None,
+            has_owner: None,
             date_from: Some(Utc::now()),
             date_to: Some(Utc::now()),
+            modified_since: None,
             completed_after: None,
             completed_before: None,
             limit: Some(10),
             offset: Some(5),
+            include_archive: false,
         };
 
         // This should not panic or cause type errors when building
@@ -402,8 +658,8 @@ mod tests {
         // The query should contain the expected SQL structure
         let sql = query.sql();
         assert!(sql.contains("SELECT"));
-        assert!(sql.contains("WHERE owner_agent_name = "));
-        assert!(sql.contains("AND state = "));
+        assert!(sql.contains("WHERE owner_agent_name IN ("));
+        assert!(sql.contains("AND state IN ("));
         assert!(sql.contains("AND inserted_at >= "));
         assert!(sql.contains("AND inserted_at <= "));
         assert!(sql.contains("ORDER BY inserted_at DESC"));
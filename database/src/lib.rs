@@ -16,7 +16,7 @@
 //!
 //! ```rust
 //! use database::SqliteTaskRepository;
-//! use task_core::repository::TaskRepository;
+//! use task_core::repository::TaskCrudRepository;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -38,7 +38,7 @@ mod common;
 mod sqlite;
 mod workspace_context_repository;
 
-pub use sqlite::SqliteTaskRepository;
+pub use sqlite::{ArchivalReport, PendingMigration, SqliteTaskRepository};
 pub use workspace_context_repository::SqliteWorkspaceContextRepository;
 
 // Re-export commonly used types from task-core for convenience
@@ -46,7 +46,8 @@ pub use task_core::{
     error::{Result, TaskError},
     models::{NewTask, Task, TaskFilter, TaskMessage, TaskState, UpdateTask},
     repository::{
-        RepositoryStats, TaskMessageRepository, TaskRepository, WorkspaceContextRepository,
+        RepositoryStats, TaskCrudRepository, TaskExtensionRepository, TaskMessageRepository,
+        TaskRepository, WorkClaimRepository, WorkSessionRepository, WorkspaceContextRepository,
     },
     GeneratedFileMetadata, WorkspaceContext,
 };
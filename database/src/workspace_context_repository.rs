@@ -4,6 +4,7 @@ use sqlx::{Row, SqlitePool};
 use std::sync::Arc;
 use task_core::{
     error::{Result, TaskError},
+    read_workspace_context,
     workspace_setup::WorkspaceContext,
     WorkspaceContextRepository,
 };
@@ -70,11 +71,20 @@ impl WorkspaceContextRepository for SqliteWorkspaceContextRepository {
         match row {
             Some(row) => {
                 let data_str: String = row.get("data");
-                let context: WorkspaceContext = serde_json::from_str(&data_str).map_err(|e| {
+                let data: serde_json::Value = serde_json::from_str(&data_str).map_err(|e| {
                     TaskError::Deserialization(format!(
                         "Failed to deserialize WorkspaceContext: {e}"
                     ))
                 })?;
+                // Contexts stored by older releases may hold a 1.x manifest
+                let (context, warnings) = read_workspace_context(data).map_err(|e| {
+                    TaskError::Deserialization(format!(
+                        "Failed to deserialize WorkspaceContext: {e}"
+                    ))
+                })?;
+                for warning in warnings {
+                    tracing::warn!("Workspace {} manifest upgrade: {}", workspace_id, warning);
+                }
                 Ok(Some(context))
             }
             None => Ok(None),
@@ -324,4 +334,37 @@ mod tests {
 
         repo.health_check().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_get_upgrades_v1_manifest() {
+        let pool = setup_test_db().await;
+        let mut data = serde_json::to_value(WorkspaceContext::new("legacy".to_string())).unwrap();
+        data["manifest_data"] = serde_json::json!({
+            "schema_version": "1.0",
+            "ai_tool_type": "claude-code",
+            "project": {"name": "Legacy", "description": "Stored by 0.3"}
+        });
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO workspace_contexts (workspace_id, data, version, created_at, updated_at) \
+             VALUES ('legacy', ?, 1, ?, ?)",
+        )
+        .bind(data.to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let repo = SqliteWorkspaceContextRepository::new(Arc::new(pool));
+        let manifest = repo
+            .get_by_id("legacy")
+            .await
+            .unwrap()
+            .unwrap()
+            .manifest_data
+            .unwrap();
+        assert_eq!(manifest.schema_version, task_core::MANIFEST_SCHEMA_VERSION);
+        assert_eq!(manifest.project.name, "Legacy");
+    }
 }
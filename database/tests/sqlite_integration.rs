@@ -1,5 +1,5 @@
 use database::{
-    NewTask, SqliteTaskRepository, TaskError, TaskFilter, TaskRepository, TaskState, UpdateTask,
+    NewTask, SqliteTaskRepository, TaskCrudRepository, TaskError, TaskFilter, TaskState, UpdateTask,
 };
 use std::time::Duration;
 use tokio::time::Instant;
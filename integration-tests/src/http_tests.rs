@@ -5,13 +5,10 @@
 
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use std::{
-    path::PathBuf,
-    time::Duration,
-};
+use std::{path::PathBuf, time::Duration};
 use tokio::{
-    process::{Command, Child},
-    time::{timeout, sleep},
+    process::{Child, Command},
+    time::{sleep, timeout},
 };
 use tracing::{debug, info};
 
@@ -19,7 +16,9 @@ use tracing::{debug, info};
 pub struct HttpTestHarness {
     server_process: Option<Child>,
     server_url: String,
+    #[allow(dead_code)]
     project_root: PathBuf,
+    #[allow(dead_code)]
     server_port: u16,
 }
 
@@ -36,16 +35,17 @@ impl HttpTestHarness {
         info!("📍 Server Port: {}", server_port);
 
         // Create project directories
-        tokio::fs::create_dir_all(&project_root).await
+        tokio::fs::create_dir_all(&project_root)
+            .await
             .context("Failed to create project directory")?;
-            
+
         // Generate unique project name for testing
         let uuid_string = uuid::Uuid::new_v4().to_string();
         let project_name = format!("integration-test-{}", &uuid_string[..8]);
-        
+
         // Start the HTTP MCP server
         info!("🔧 Starting Axon MCP server for HTTP testing");
-        
+
         let mut server_command = Command::new(&axon_binary);
         server_command
             .arg("--start")
@@ -58,20 +58,21 @@ impl HttpTestHarness {
             .env("RUST_LOG", "info")
             .kill_on_drop(true);
 
-        let server_process = server_command.spawn()
+        let server_process = server_command
+            .spawn()
             .context("Failed to start Axon MCP server")?;
 
         let server_url = format!("http://127.0.0.1:{}/mcp", server_port);
-        
+
         // Wait for server to be ready
         info!("⏳ Waiting for server to be ready at {}", server_url);
         let mut ready = false;
         for attempt in 1..=30 {
             sleep(Duration::from_millis(500)).await;
-            
+
             // Try to make a simple HTTP request to check if server is running
             if let Ok(response) = reqwest::Client::new()
-                .get(&format!("http://127.0.0.1:{}/health", server_port))
+                .get(format!("http://127.0.0.1:{}/health", server_port))
                 .timeout(Duration::from_secs(2))
                 .send()
                 .await
@@ -81,14 +82,16 @@ impl HttpTestHarness {
                     break;
                 }
             }
-            
+
             if attempt % 5 == 0 {
                 info!("🔄 Server not ready yet, attempt {}/30", attempt);
             }
         }
 
         if !ready {
-            return Err(anyhow::anyhow!("Server did not become ready within 15 seconds"));
+            return Err(anyhow::anyhow!(
+                "Server did not become ready within 15 seconds"
+            ));
         }
 
         info!("✅ HTTP MCP server ready at {}", server_url);
@@ -104,7 +107,7 @@ impl HttpTestHarness {
     /// Make an MCP JSON-RPC request to the HTTP endpoint
     async fn make_mcp_request(&self, method: &str, params: Option<Value>) -> Result<Value> {
         let client = reqwest::Client::new();
-        
+
         let request_body = json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -113,7 +116,10 @@ impl HttpTestHarness {
         });
 
         debug!("📤 Making MCP request: {}", method);
-        debug!("📄 Request body: {}", serde_json::to_string_pretty(&request_body)?);
+        debug!(
+            "📄 Request body: {}",
+            serde_json::to_string_pretty(&request_body)?
+        );
 
         let response = timeout(
             Duration::from_secs(30),
@@ -121,24 +127,31 @@ impl HttpTestHarness {
                 .post(&self.server_url)
                 .header("Content-Type", "application/json")
                 .json(&request_body)
-                .send()
-        ).await
+                .send(),
+        )
+        .await
         .context("Timeout waiting for HTTP response")?
         .context("Failed to send HTTP request")?;
 
         let status = response.status();
-        let response_text = response.text().await
+        let response_text = response
+            .text()
+            .await
             .context("Failed to read response body")?;
 
         debug!("📥 Response status: {}", status);
         debug!("📄 Response body: {}", response_text);
 
         if !status.is_success() {
-            return Err(anyhow::anyhow!("HTTP request failed with status {}: {}", status, response_text));
+            return Err(anyhow::anyhow!(
+                "HTTP request failed with status {}: {}",
+                status,
+                response_text
+            ));
         }
 
-        let response_json: Value = serde_json::from_str(&response_text)
-            .context("Failed to parse JSON response")?;
+        let response_json: Value =
+            serde_json::from_str(&response_text).context("Failed to parse JSON response")?;
 
         // Check for JSON-RPC error
         if let Some(error) = response_json.get("error") {
@@ -146,9 +159,10 @@ impl HttpTestHarness {
         }
 
         // Return the result field
-        response_json.get("result")
+        response_json
+            .get("result")
             .ok_or_else(|| anyhow::anyhow!("No result field in JSON-RPC response"))
-            .map(|r| r.clone())
+            .cloned()
     }
 
     /// Run all HTTP integration tests
@@ -172,18 +186,23 @@ impl HttpTestHarness {
         info!("🔍 Testing health_check via HTTP");
 
         let result = self.make_mcp_request("health_check", None).await?;
-        
-        let status = result.get("status")
+
+        let status = result
+            .get("status")
             .and_then(|s| s.as_str())
             .context("Missing or invalid status in health check response")?;
 
         if status != "healthy" {
-            return Err(anyhow::anyhow!("Server reports unhealthy status: {}", status));
+            return Err(anyhow::anyhow!(
+                "Server reports unhealthy status: {}",
+                status
+            ));
         }
 
-        let version = result.get("version")
+        let version = result
+            .get("version")
             .context("Missing version in health check response")?;
-        
+
         info!("✅ health_check - PASSED (version: {})", version);
         Ok(())
     }
@@ -193,38 +212,59 @@ impl HttpTestHarness {
         info!("🔍 Testing task management functions via HTTP");
 
         // Test create_task
-        let create_result = self.make_mcp_request("create_task", Some(json!({
-            "code": "HTTP-TEST-001",
-            "name": "HTTP Test Task",
-            "description": "A test task for HTTP MCP integration testing",
-            "owner_agent_name": "http-test-agent"
-        }))).await?;
-
-        let task_id = create_result.get("id")
+        let create_result = self
+            .make_mcp_request(
+                "create_task",
+                Some(json!({
+                    "code": "HTTP-TEST-001",
+                    "name": "HTTP Test Task",
+                    "description": "A test task for HTTP MCP integration testing",
+                    "owner_agent_name": "http-test-agent"
+                })),
+            )
+            .await?;
+
+        let task_id = create_result
+            .get("id")
             .and_then(|id| id.as_i64())
             .context("Task ID not found in create_task response")?;
 
         info!("📝 Created task with ID: {}", task_id);
 
         // Test get_task_by_id
-        let get_result = self.make_mcp_request("get_task_by_id", Some(json!({
-            "id": task_id
-        }))).await?;
-
-        let retrieved_name = get_result.get("name")
+        let get_result = self
+            .make_mcp_request(
+                "get_task_by_id",
+                Some(json!({
+                    "id": task_id
+                })),
+            )
+            .await?;
+
+        let retrieved_name = get_result
+            .get("name")
             .and_then(|n| n.as_str())
             .context("Task name not found in get_task_by_id response")?;
 
         if retrieved_name != "HTTP Test Task" {
-            return Err(anyhow::anyhow!("Retrieved task name mismatch: {}", retrieved_name));
+            return Err(anyhow::anyhow!(
+                "Retrieved task name mismatch: {}",
+                retrieved_name
+            ));
         }
 
         // Test get_task_by_code
-        let get_by_code_result = self.make_mcp_request("get_task_by_code", Some(json!({
-            "code": "HTTP-TEST-001"
-        }))).await?;
-
-        let code_task_id = get_by_code_result.get("id")
+        let get_by_code_result = self
+            .make_mcp_request(
+                "get_task_by_code",
+                Some(json!({
+                    "code": "HTTP-TEST-001"
+                })),
+            )
+            .await?;
+
+        let code_task_id = get_by_code_result
+            .get("id")
             .and_then(|id| id.as_i64())
             .context("Task ID not found in get_task_by_code response")?;
 
@@ -233,25 +273,41 @@ impl HttpTestHarness {
         }
 
         // Test update_task
-        let _update_result = self.make_mcp_request("update_task", Some(json!({
-            "id": task_id,
-            "name": "Updated HTTP Test Task",
-            "description": "Updated description for HTTP testing"
-        }))).await?;
+        let _update_result = self
+            .make_mcp_request(
+                "update_task",
+                Some(json!({
+                    "id": task_id,
+                    "name": "Updated HTTP Test Task",
+                    "description": "Updated description for HTTP testing"
+                })),
+            )
+            .await?;
 
         // Test set_task_state
-        let _state_result = self.make_mcp_request("set_task_state", Some(json!({
-            "id": task_id,
-            "state": "InProgress"
-        }))).await?;
+        let _state_result = self
+            .make_mcp_request(
+                "set_task_state",
+                Some(json!({
+                    "id": task_id,
+                    "state": "InProgress"
+                })),
+            )
+            .await?;
 
         // Test list_tasks
-        let list_result = self.make_mcp_request("list_tasks", Some(json!({
-            "limit": 10
-        }))).await?;
-        
+        let list_result = self
+            .make_mcp_request(
+                "list_tasks",
+                Some(json!({
+                    "limit": 10
+                })),
+            )
+            .await?;
+
         // list_tasks returns array directly
-        let tasks = list_result.as_array()
+        let tasks = list_result
+            .as_array()
             .context("Expected array from list_tasks response")?;
 
         if tasks.is_empty() {
@@ -259,10 +315,15 @@ impl HttpTestHarness {
         }
 
         // Test assign_task
-        let _assign_result = self.make_mcp_request("assign_task", Some(json!({
-            "id": task_id,
-            "new_owner": "new-agent"
-        }))).await?;
+        let _assign_result = self
+            .make_mcp_request(
+                "assign_task",
+                Some(json!({
+                    "id": task_id,
+                    "new_owner": "new-agent"
+                })),
+            )
+            .await?;
 
         info!("✅ Task management functions - PASSED");
         Ok(())
@@ -273,11 +334,17 @@ impl HttpTestHarness {
         info!("🔍 Testing workspace setup functions via HTTP");
 
         // Test get_setup_instructions
-        let setup_result = self.make_mcp_request("get_setup_instructions", Some(json!({
-            "ai_tool_type": "claude-code"
-        }))).await?;
-
-        let instructions = setup_result.get("instructions")
+        let setup_result = self
+            .make_mcp_request(
+                "get_setup_instructions",
+                Some(json!({
+                    "ai_tool_type": "claude-code"
+                })),
+            )
+            .await?;
+
+        let instructions = setup_result
+            .get("instructions")
             .context("Instructions not found in get_setup_instructions response")?;
 
         if instructions.as_str().unwrap_or("").is_empty() {
@@ -285,11 +352,17 @@ impl HttpTestHarness {
         }
 
         // Test get_agentic_workflow_description
-        let workflow_result = self.make_mcp_request("get_agentic_workflow_description", Some(json!({
-            "requested_agent_count": 3
-        }))).await?;
-
-        let description = workflow_result.get("description")
+        let workflow_result = self
+            .make_mcp_request(
+                "get_agentic_workflow_description",
+                Some(json!({
+                    "requested_agent_count": 3
+                })),
+            )
+            .await?;
+
+        let description = workflow_result
+            .get("description")
             .context("Description not found in get_agentic_workflow_description response")?;
 
         if description.as_str().unwrap_or("").is_empty() {
@@ -297,19 +370,30 @@ impl HttpTestHarness {
         }
 
         // Test register_agent
-        let _register_result = self.make_mcp_request("register_agent", Some(json!({
-            "agent_name": "http-test-agent",
-            "capabilities": ["rust", "testing", "http"],
-            "contact_info": "test-agent@localhost"
-        }))).await?;
+        let _register_result = self
+            .make_mcp_request(
+                "register_agent",
+                Some(json!({
+                    "agent_name": "http-test-agent",
+                    "capabilities": ["rust", "testing", "http"],
+                    "contact_info": "test-agent@localhost"
+                })),
+            )
+            .await?;
 
         // Test get_instructions_for_main_ai_file
-        let ai_file_result = self.make_mcp_request("get_instructions_for_main_ai_file", Some(json!({
-            "ai_tool_type": "claude-code",
-            "project_context": "HTTP MCP testing project"
-        }))).await?;
-
-        let ai_instructions = ai_file_result.get("instructions")
+        let ai_file_result = self
+            .make_mcp_request(
+                "get_instructions_for_main_ai_file",
+                Some(json!({
+                    "ai_tool_type": "claude-code",
+                    "project_context": "HTTP MCP testing project"
+                })),
+            )
+            .await?;
+
+        let ai_instructions = ai_file_result
+            .get("instructions")
             .context("Instructions not found in get_instructions_for_main_ai_file response")?;
 
         if ai_instructions.as_str().unwrap_or("").is_empty() {
@@ -325,37 +409,55 @@ impl HttpTestHarness {
         info!("🔍 Testing agent coordination functions via HTTP");
 
         // First create a task to work with
-        let create_result = self.make_mcp_request("create_task", Some(json!({
-            "code": "HTTP-COORD-001",
-            "name": "HTTP Coordination Test Task",
-            "description": "A task for testing HTTP agent coordination",
-            "required_capabilities": ["rust", "coordination"]
-        }))).await?;
-
-        let task_id = create_result.get("id")
+        let create_result = self
+            .make_mcp_request(
+                "create_task",
+                Some(json!({
+                    "code": "HTTP-COORD-001",
+                    "name": "HTTP Coordination Test Task",
+                    "description": "A task for testing HTTP agent coordination",
+                    "required_capabilities": ["rust", "coordination"]
+                })),
+            )
+            .await?;
+
+        let task_id = create_result
+            .get("id")
             .and_then(|id| id.as_i64())
             .context("Task ID not found in coordination test task creation")?;
 
         // Test discover_work
-        let discover_result = self.make_mcp_request("discover_work", Some(json!({
-            "agent_name": "http-test-agent",
-            "capabilities": ["rust", "testing", "coordination"],
-            "max_tasks": 5
-        }))).await?;
+        let discover_result = self
+            .make_mcp_request(
+                "discover_work",
+                Some(json!({
+                    "agent_name": "http-test-agent",
+                    "capabilities": ["rust", "testing", "coordination"],
+                    "max_tasks": 5
+                })),
+            )
+            .await?;
 
         // discover_work returns array directly
-        let discovered_tasks = discover_result.as_array()
+        let discovered_tasks = discover_result
+            .as_array()
             .context("Expected array from discover_work response")?;
 
         info!("🔍 Discovered {} available tasks", discovered_tasks.len());
 
         // Test claim_task
-        let claim_result = self.make_mcp_request("claim_task", Some(json!({
-            "task_id": task_id,
-            "agent_name": "http-test-agent"
-        }))).await?;
-
-        let claimed = claim_result.get("success")
+        let claim_result = self
+            .make_mcp_request(
+                "claim_task",
+                Some(json!({
+                    "task_id": task_id,
+                    "agent_name": "http-test-agent"
+                })),
+            )
+            .await?;
+
+        let claimed = claim_result
+            .get("success")
             .and_then(|s| s.as_bool())
             .context("Success field not found in claim_task response")?;
 
@@ -364,29 +466,45 @@ impl HttpTestHarness {
         }
 
         // Test start_work_session
-        let session_result = self.make_mcp_request("start_work_session", Some(json!({
-            "task_id": task_id,
-            "agent_name": "http-test-agent"
-        }))).await?;
-
-        let session_id = session_result.get("session_id")
+        let session_result = self
+            .make_mcp_request(
+                "start_work_session",
+                Some(json!({
+                    "task_id": task_id,
+                    "agent_name": "http-test-agent"
+                })),
+            )
+            .await?;
+
+        let session_id = session_result
+            .get("session_id")
             .and_then(|s| s.as_i64())
             .context("Session ID not found in start_work_session response")?;
 
         info!("🔧 Started work session with ID: {}", session_id);
 
         // Test end_work_session
-        let _end_session_result = self.make_mcp_request("end_work_session", Some(json!({
-            "session_id": session_id,
-            "notes": "Completed HTTP coordination testing work session",
-            "productivity_score": 0.95
-        }))).await?;
+        let _end_session_result = self
+            .make_mcp_request(
+                "end_work_session",
+                Some(json!({
+                    "session_id": session_id,
+                    "notes": "Completed HTTP coordination testing work session",
+                    "productivity_score": 0.95
+                })),
+            )
+            .await?;
 
         // Test release_task
-        let _release_result = self.make_mcp_request("release_task", Some(json!({
-            "task_id": task_id,
-            "agent_name": "http-test-agent"
-        }))).await?;
+        let _release_result = self
+            .make_mcp_request(
+                "release_task",
+                Some(json!({
+                    "task_id": task_id,
+                    "agent_name": "http-test-agent"
+                })),
+            )
+            .await?;
 
         info!("✅ Agent coordination functions - PASSED");
         Ok(())
@@ -397,28 +515,40 @@ impl HttpTestHarness {
         info!("🔍 Testing messaging functions via HTTP");
 
         // Test create_task_message
-        let message_result = self.make_mcp_request("create_task_message", Some(json!({
-            "task_code": "HTTP-TEST-001",
-            "author_agent_name": "http-test-agent",
-            "target_agent_name": "http-other-agent",
-            "message_type": "handoff",
-            "content": "This is an HTTP test message for integration testing"
-        }))).await?;
-
-        let message_id = message_result.get("id")
+        let message_result = self
+            .make_mcp_request(
+                "create_task_message",
+                Some(json!({
+                    "task_code": "HTTP-TEST-001",
+                    "author_agent_name": "http-test-agent",
+                    "target_agent_name": "http-other-agent",
+                    "message_type": "handoff",
+                    "content": "This is an HTTP test message for integration testing"
+                })),
+            )
+            .await?;
+
+        let message_id = message_result
+            .get("id")
             .and_then(|id| id.as_i64())
             .context("Message ID not found in create_task_message response")?;
 
         info!("💬 Created message with ID: {}", message_id);
 
         // Test get_task_messages
-        let get_messages_result = self.make_mcp_request("get_task_messages", Some(json!({
-            "task_code": "HTTP-TEST-001",
-            "limit": 10
-        }))).await?;
+        let get_messages_result = self
+            .make_mcp_request(
+                "get_task_messages",
+                Some(json!({
+                    "task_code": "HTTP-TEST-001",
+                    "limit": 10
+                })),
+            )
+            .await?;
 
-        // get_task_messages returns array directly  
-        let messages = get_messages_result.as_array()
+        // get_task_messages returns array directly
+        let messages = get_messages_result
+            .as_array()
             .context("Expected array from get_task_messages response")?;
 
         if messages.is_empty() {
@@ -426,14 +556,20 @@ impl HttpTestHarness {
         }
 
         // Test targeted message retrieval
-        let targeted_messages_result = self.make_mcp_request("get_task_messages", Some(json!({
-            "task_code": "HTTP-TEST-001",
-            "target_agent_name": "http-other-agent",
-            "message_type": "handoff"
-        }))).await?;
+        let targeted_messages_result = self
+            .make_mcp_request(
+                "get_task_messages",
+                Some(json!({
+                    "task_code": "HTTP-TEST-001",
+                    "target_agent_name": "http-other-agent",
+                    "message_type": "handoff"
+                })),
+            )
+            .await?;
 
         // get_task_messages returns array directly
-        let targeted_messages = targeted_messages_result.as_array()
+        let targeted_messages = targeted_messages_result
+            .as_array()
             .context("Expected array from targeted messages")?;
 
         if targeted_messages.is_empty() {
@@ -449,42 +585,66 @@ impl HttpTestHarness {
         info!("🔍 Testing additional MCP functions via HTTP");
 
         // Test create_main_ai_file
-        let create_ai_result = self.make_mcp_request("create_main_ai_file", Some(json!({
-            "ai_tool_type": "claude-code",
-            "project_context": "HTTP MCP integration test project",
-            "target_filename": "CLAUDE_HTTP_TEST.md"
-        }))).await?;
-
-        let file_path = create_ai_result.get("file_path")
+        let create_ai_result = self
+            .make_mcp_request(
+                "create_main_ai_file",
+                Some(json!({
+                    "ai_tool_type": "claude-code",
+                    "project_context": "HTTP MCP integration test project",
+                    "target_filename": "CLAUDE_HTTP_TEST.md"
+                })),
+            )
+            .await?;
+
+        let file_path = create_ai_result
+            .get("file_path")
             .context("File path not found in create_main_ai_file response")?;
 
         info!("📁 Created AI file: {}", file_path);
 
         // Test get_workspace_manifest
-        let manifest_result = self.make_mcp_request("get_workspace_manifest", None).await?;
+        let manifest_result = self
+            .make_mcp_request("get_workspace_manifest", None)
+            .await?;
 
-        let manifest = manifest_result.get("manifest")
+        let manifest = manifest_result
+            .get("manifest")
             .context("Manifest not found in get_workspace_manifest response")?;
 
-        if manifest.as_object().unwrap_or(&serde_json::Map::new()).is_empty() {
+        if manifest
+            .as_object()
+            .unwrap_or(&serde_json::Map::new())
+            .is_empty()
+        {
             return Err(anyhow::anyhow!("Empty workspace manifest received"));
         }
 
         // Test archive_task (using a task we created earlier)
-        let create_archive_task = self.make_mcp_request("create_task", Some(json!({
-            "code": "HTTP-ARCHIVE-001",
-            "name": "Task to Archive",
-            "description": "This task will be archived for testing"
-        }))).await?;
-
-        let archive_task_id = create_archive_task.get("id")
+        let create_archive_task = self
+            .make_mcp_request(
+                "create_task",
+                Some(json!({
+                    "code": "HTTP-ARCHIVE-001",
+                    "name": "Task to Archive",
+                    "description": "This task will be archived for testing"
+                })),
+            )
+            .await?;
+
+        let archive_task_id = create_archive_task
+            .get("id")
             .and_then(|id| id.as_i64())
             .context("Archive task ID not found")?;
 
-        let _archive_result = self.make_mcp_request("archive_task", Some(json!({
-            "id": archive_task_id,
-            "reason": "HTTP integration test completed"
-        }))).await?;
+        let _archive_result = self
+            .make_mcp_request(
+                "archive_task",
+                Some(json!({
+                    "id": archive_task_id,
+                    "reason": "HTTP integration test completed"
+                })),
+            )
+            .await?;
 
         info!("✅ Additional MCP functions - PASSED");
         Ok(())
@@ -495,7 +655,7 @@ impl Drop for HttpTestHarness {
     fn drop(&mut self) {
         if let Some(mut process) = self.server_process.take() {
             info!("🛑 Shutting down HTTP MCP server");
-            let _ = process.kill();
+            let _ = process.start_kill();
         }
     }
 }
@@ -515,4 +675,4 @@ pub async fn run_http_integration_tests(
     harness.run_all_tests().await?;
 
     Ok(())
-}
\ No newline at end of file
+}
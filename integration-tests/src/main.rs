@@ -3,20 +3,21 @@
 //! This binary provides end-to-end testing of the axon-mcp server using manual Content-Length framing.
 
 mod manual_tests;
+#[allow(dead_code)] // rmcp SDK harness kept for manual runs; not wired into main yet
 mod rmcp_tests;
 
 use anyhow::Result;
-use manual_tests::{run_manual_tests, ManualTestArgs};
 use clap::Parser;
+use manual_tests::{run_manual_tests, ManualTestArgs};
 
 /// Main entry point for integration tests
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
     let args = ManualTestArgs::parse();
-    
+
     // Run manual integration tests
     run_manual_tests(args).await?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,15 +1,12 @@
 //! Manual Content-Length Integration Tests for Axon MCP Server
 //!
-//! This module provides integration testing using manual Content-Length framing 
+//! This module provides integration testing using manual Content-Length framing
 //! that was previously working successfully.
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use serde_json::{json, Value};
-use std::{
-    path::PathBuf,
-    time::Duration,
-};
+use std::{path::PathBuf, time::Duration};
 use tokio::{
     io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
     process::{Child, Command},
@@ -25,11 +22,11 @@ pub struct ManualTestArgs {
     /// Path to the axon-mcp binary to test
     #[arg(short, long)]
     pub axon_binary: PathBuf,
-    
+
     /// Directory where axon-mcp should store its SQLite database
     #[arg(short, long)]
     pub database_dir: PathBuf,
-    
+
     /// Project root directory for axon-mcp
     #[arg(short, long)]
     pub project_root: PathBuf,
@@ -51,14 +48,16 @@ impl ManualTestHarness {
         info!("📍 Project Root: {:?}", args.project_root);
 
         // Create directories
-        tokio::fs::create_dir_all(&args.database_dir).await
+        tokio::fs::create_dir_all(&args.database_dir)
+            .await
             .context("Failed to create database directory")?;
-        tokio::fs::create_dir_all(&args.project_root).await
+        tokio::fs::create_dir_all(&args.project_root)
+            .await
             .context("Failed to create project directory")?;
 
         // Generate database path
         let db_path = args.database_dir.join("test-axon-mcp.sqlite");
-        
+
         info!("🔧 Starting axon-mcp server process");
 
         // Start axon-mcp server process
@@ -78,7 +77,10 @@ impl ManualTestHarness {
 
         // Check if the process is still running
         if let Ok(Some(exit_status)) = child.try_wait() {
-            return Err(anyhow::anyhow!("Server process exited early with status: {}", exit_status));
+            return Err(anyhow::anyhow!(
+                "Server process exited early with status: {}",
+                exit_status
+            ));
         }
 
         info!("✅ Server process started successfully");
@@ -91,7 +93,9 @@ impl ManualTestHarness {
             _project_root: args.project_root,
         };
 
-        harness.perform_handshake().await
+        harness
+            .perform_handshake()
+            .await
             .context("Failed to complete MCP handshake")?;
 
         info!("✅ MCP handshake completed successfully");
@@ -122,18 +126,21 @@ impl ManualTestHarness {
             }
         });
 
-        self.send_message(&init_request).await
+        self.send_message(&init_request)
+            .await
             .context("Failed to send initialize request")?;
 
         // Read initialize response
-        let response = self.read_message().await
+        let response = self
+            .read_message()
+            .await
             .context("Failed to read initialize response")?;
 
         debug!("Initialize response: {}", response);
 
         // Parse and validate response
-        let response_json: Value = serde_json::from_str(&response)
-            .context("Failed to parse initialize response")?;
+        let response_json: Value =
+            serde_json::from_str(&response).context("Failed to parse initialize response")?;
 
         if response_json["result"]["protocolVersion"] != "2024-11-05" {
             return Err(anyhow::anyhow!("Unexpected protocol version in response"));
@@ -146,7 +153,8 @@ impl ManualTestHarness {
             "params": {}
         });
 
-        self.send_message(&initialized_notification).await
+        self.send_message(&initialized_notification)
+            .await
             .context("Failed to send initialized notification")?;
 
         info!("✅ MCP handshake completed");
@@ -157,23 +165,29 @@ impl ManualTestHarness {
     async fn send_message(&mut self, message: &Value) -> Result<()> {
         let message_str = serde_json::to_string(message)?;
         let header = format!("Content-Length: {}\r\n\r\n", message_str.len());
-        
-        let stdin = self.child.stdin.as_mut()
+
+        let stdin = self
+            .child
+            .stdin
+            .as_mut()
             .context("Failed to access stdin")?;
-        
+
         stdin.write_all(header.as_bytes()).await?;
         stdin.write_all(message_str.as_bytes()).await?;
         stdin.flush().await?;
-        
+
         debug!("Sent message: {}", message_str);
         Ok(())
     }
 
     /// Read MCP message with Content-Length framing
     async fn read_message(&mut self) -> Result<String> {
-        let stdout = self.child.stdout.as_mut()
+        let stdout = self
+            .child
+            .stdout
+            .as_mut()
             .context("Failed to access stdout")?;
-        
+
         let mut reader = BufReader::new(stdout);
 
         // Read headers
@@ -181,8 +195,8 @@ impl ManualTestHarness {
         loop {
             let mut line = String::new();
             reader.read_line(&mut line).await?;
-            if line == "\r\n" || line == "\n" { 
-                break; 
+            if line == "\r\n" || line == "\n" {
+                break;
             }
             headers.push_str(&line);
         }
@@ -199,7 +213,7 @@ impl ManualTestHarness {
         // Read exact body
         let mut body = vec![0u8; content_length];
         reader.read_exact(&mut body).await?;
-        
+
         let message = String::from_utf8(body)?;
         debug!("Received message: {}", message);
         Ok(message)
@@ -217,21 +231,23 @@ impl ManualTestHarness {
             }
         });
 
-        self.send_message(&request).await
+        self.send_message(&request)
+            .await
             .context("Failed to send tool call request")?;
 
-        let response = timeout(
-            Duration::from_secs(10),
-            self.read_message()
-        ).await
-        .context("Timeout waiting for tool response")?
-        .context("Failed to read tool response")?;
+        let response = timeout(Duration::from_secs(10), self.read_message())
+            .await
+            .context("Timeout waiting for tool response")?
+            .context("Failed to read tool response")?;
 
-        let response_json: Value = serde_json::from_str(&response)
-            .context("Failed to parse tool response")?;
+        let response_json: Value =
+            serde_json::from_str(&response).context("Failed to parse tool response")?;
 
         if response_json["error"].is_object() {
-            return Err(anyhow::anyhow!("Tool call failed: {}", response_json["error"]));
+            return Err(anyhow::anyhow!(
+                "Tool call failed: {}",
+                response_json["error"]
+            ));
         }
 
         Ok(response_json["result"].clone())
@@ -258,10 +274,13 @@ impl ManualTestHarness {
 
         let result = self.call_tool("health_check", json!({})).await?;
         debug!("Health check result: {:?}", result);
-        
+
         // Parse and validate the health status
         if result["status"] != "healthy" {
-            return Err(anyhow::anyhow!("Server reports unhealthy status: {:?}", result));
+            return Err(anyhow::anyhow!(
+                "Server reports unhealthy status: {:?}",
+                result
+            ));
         }
 
         info!("✅ health_check - PASSED");
@@ -273,34 +292,50 @@ impl ManualTestHarness {
         info!("🔍 Testing task management functions");
 
         // Test create_task
-        let create_result = self.call_tool("create_task", json!({
-            "code": "TEST-001",
-            "name": "Test Task",
-            "description": "A test task for integration testing",
-            "owner_agent_name": "test-agent"
-        })).await?;
+        let create_result = self
+            .call_tool(
+                "create_task",
+                json!({
+                    "code": "TEST-001",
+                    "name": "Test Task",
+                    "description": "A test task for integration testing",
+                    "owner_agent_name": "test-agent"
+                }),
+            )
+            .await?;
 
         debug!("Create task result: {:?}", create_result);
 
-        // Parse task from response  
-        let task_id = create_result["id"].as_i64()
+        // Parse task from response
+        let task_id = create_result["id"]
+            .as_i64()
             .context("Task ID not found in response")?;
 
         info!("📝 Created task with ID: {}", task_id);
 
         // Test get_task_by_id
-        let get_result = self.call_tool("get_task_by_id", json!({
-            "id": task_id
-        })).await?;
+        let get_result = self
+            .call_tool(
+                "get_task_by_id",
+                json!({
+                    "id": task_id
+                }),
+            )
+            .await?;
 
         debug!("Get task result: {:?}", get_result);
 
         // Test update_task
-        let update_result = self.call_tool("update_task", json!({
-            "id": task_id,
-            "name": "Updated Test Task",
-            "description": "Updated description for testing"
-        })).await?;
+        let update_result = self
+            .call_tool(
+                "update_task",
+                json!({
+                    "id": task_id,
+                    "name": "Updated Test Task",
+                    "description": "Updated description for testing"
+                }),
+            )
+            .await?;
 
         debug!("Update task result: {:?}", update_result);
 
@@ -317,16 +352,26 @@ impl ManualTestHarness {
         info!("🔍 Testing workspace setup functions");
 
         // Test get_setup_instructions
-        let setup_result = self.call_tool("get_setup_instructions", json!({
-            "ai_tool_type": "claude-code"
-        })).await?;
+        let setup_result = self
+            .call_tool(
+                "get_setup_instructions",
+                json!({
+                    "ai_tool_type": "claude-code"
+                }),
+            )
+            .await?;
 
         debug!("Setup instructions result: {:?}", setup_result);
 
         // Test get_agentic_workflow_description
-        let workflow_result = self.call_tool("get_agentic_workflow_description", json!({
-            "requested_agent_count": 3
-        })).await?;
+        let workflow_result = self
+            .call_tool(
+                "get_agentic_workflow_description",
+                json!({
+                    "requested_agent_count": 3
+                }),
+            )
+            .await?;
 
         debug!("Workflow description result: {:?}", workflow_result);
 
@@ -339,55 +384,77 @@ impl ManualTestHarness {
         info!("🔍 Testing agent coordination functions");
 
         // First create an unassigned task to work with
-        let create_result = self.call_tool("create_task", json!({
-            "code": "COORD-001",
-            "name": "Coordination Test Task",
-            "description": "A task for testing agent coordination"
-        })).await?;
+        let create_result = self
+            .call_tool(
+                "create_task",
+                json!({
+                    "code": "COORD-001",
+                    "name": "Coordination Test Task",
+                    "description": "A task for testing agent coordination"
+                }),
+            )
+            .await?;
 
         let task_id = create_result["id"].as_i64().unwrap();
 
         // Test discover_work with timeout mechanism
         info!("🔍 Testing discover_work - first checking for rare capabilities to trigger timeout");
-        
+
         // First try to discover work with rare capabilities (should trigger timeout)
-        let discover_future = self.call_tool("discover_work", json!({
-            "agent_name": "timeout-test-agent",
-            "capabilities": ["rare-capability-xyz", "nonexistent-skill"],
-            "max_tasks": 5
-        }));
+        let discover_future = self.call_tool(
+            "discover_work",
+            json!({
+                "agent_name": "timeout-test-agent",
+                "capabilities": ["rare-capability-xyz", "nonexistent-skill"],
+                "max_tasks": 5
+            }),
+        );
 
         // Use timeout to prevent long waiting
         let discover_result = match timeout(Duration::from_secs(10), discover_future).await {
             Ok(result) => {
                 info!("✅ Found existing tasks for discover_work");
                 result?
-            },
+            }
             Err(_) => {
-                info!("⏰ Timeout waiting for discover_work (waited 10s) - creating task for agent");
-                
+                info!(
+                    "⏰ Timeout waiting for discover_work (waited 10s) - creating task for agent"
+                );
+
                 // Create a task that matches the agent's capabilities
-                let new_task_result = self.call_tool("create_task", json!({
-                    "code": "DISCOVER-TEST-001",
-                    "name": "Discoverable Test Task",
-                    "description": "A task created to test discover_work functionality",
-                    "required_capabilities": ["rare-capability-xyz", "nonexistent-skill"]
-                })).await?;
-                
-                info!("📝 Created discoverable task with ID: {}", new_task_result["id"]);
-                
+                let new_task_result = self
+                    .call_tool(
+                        "create_task",
+                        json!({
+                            "code": "DISCOVER-TEST-001",
+                            "name": "Discoverable Test Task",
+                            "description": "A task created to test discover_work functionality",
+                            "required_capabilities": ["rare-capability-xyz", "nonexistent-skill"]
+                        }),
+                    )
+                    .await?;
+
+                info!(
+                    "📝 Created discoverable task with ID: {}",
+                    new_task_result["id"]
+                );
+
                 // Now try discover_work again - should find the new task
                 let retry_result = timeout(
                     Duration::from_secs(5),
-                    self.call_tool("discover_work", json!({
-                        "agent_name": "timeout-test-agent", 
-                        "capabilities": ["rare-capability-xyz", "nonexistent-skill"],
-                        "max_tasks": 5
-                    }))
-                ).await
+                    self.call_tool(
+                        "discover_work",
+                        json!({
+                            "agent_name": "timeout-test-agent",
+                            "capabilities": ["rare-capability-xyz", "nonexistent-skill"],
+                            "max_tasks": 5
+                        }),
+                    ),
+                )
+                .await
                 .context("Timeout even after creating task for discover_work")?
                 .context("Failed to discover work after creating task")?;
-                
+
                 info!("✅ Successfully discovered work after creating task");
                 retry_result
             }
@@ -396,29 +463,44 @@ impl ManualTestHarness {
         debug!("Discover work result: {:?}", discover_result);
 
         // Test claim_task
-        let claim_result = self.call_tool("claim_task", json!({
-            "task_id": task_id,
-            "agent_name": "test-agent"
-        })).await?;
+        let claim_result = self
+            .call_tool(
+                "claim_task",
+                json!({
+                    "task_id": task_id,
+                    "agent_name": "test-agent"
+                }),
+            )
+            .await?;
 
         debug!("Claim task result: {:?}", claim_result);
 
         // Test start_work_session
-        let session_result = self.call_tool("start_work_session", json!({
-            "task_id": task_id,
-            "agent_name": "test-agent"
-        })).await?;
+        let session_result = self
+            .call_tool(
+                "start_work_session",
+                json!({
+                    "task_id": task_id,
+                    "agent_name": "test-agent"
+                }),
+            )
+            .await?;
 
         debug!("Start work session result: {:?}", session_result);
 
         let session_id = session_result["session_id"].as_i64().unwrap();
 
         // Test end_work_session
-        let end_session_result = self.call_tool("end_work_session", json!({
-            "session_id": session_id,
-            "notes": "Completed testing work session",
-            "productivity_score": 0.9
-        })).await?;
+        let end_session_result = self
+            .call_tool(
+                "end_work_session",
+                json!({
+                    "session_id": session_id,
+                    "notes": "Completed testing work session",
+                    "productivity_score": 0.9
+                }),
+            )
+            .await?;
 
         debug!("End work session result: {:?}", end_session_result);
 
@@ -431,21 +513,31 @@ impl ManualTestHarness {
         info!("🔍 Testing messaging functions");
 
         // Test create_task_message
-        let message_result = self.call_tool("create_task_message", json!({
-            "task_code": "TEST-001",
-            "author_agent_name": "test-agent",
-            "target_agent_name": "other-agent",
-            "message_type": "handoff",
-            "content": "This is a test message for integration testing"
-        })).await?;
+        let message_result = self
+            .call_tool(
+                "create_task_message",
+                json!({
+                    "task_code": "TEST-001",
+                    "author_agent_name": "test-agent",
+                    "target_agent_name": "other-agent",
+                    "message_type": "handoff",
+                    "content": "This is a test message for integration testing"
+                }),
+            )
+            .await?;
 
         debug!("Create message result: {:?}", message_result);
 
         // Test get_task_messages
-        let get_messages_result = self.call_tool("get_task_messages", json!({
-            "task_code": "TEST-001",
-            "limit": 10
-        })).await?;
+        let get_messages_result = self
+            .call_tool(
+                "get_task_messages",
+                json!({
+                    "task_code": "TEST-001",
+                    "limit": 10
+                }),
+            )
+            .await?;
 
         debug!("Get messages result: {:?}", get_messages_result);
 
@@ -457,7 +549,7 @@ impl ManualTestHarness {
 /// Terminate child process properly
 impl Drop for ManualTestHarness {
     fn drop(&mut self) {
-        let _ = self.child.kill();
+        let _ = self.child.start_kill();
     }
 }
 
@@ -472,4 +564,4 @@ pub async fn run_manual_tests(args: ManualTestArgs) -> Result<()> {
     harness.run_all_tests().await?;
 
     Ok(())
-}
\ No newline at end of file
+}
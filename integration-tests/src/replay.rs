@@ -0,0 +1,175 @@
+//! Replay of recorded JSON-RPC sessions
+//!
+//! Starts a fresh axon-mcp server (new project root, empty database) and posts
+//! the requests of a recording made with `axon-mcp --record` in their original
+//! order. Sessions opened by `initialize` are mapped to the new server's
+//! session IDs, and the agent named in each request is sent again. Responses
+//! are compared with the recorded ones after dropping values that differ
+//! between runs (timestamps, session IDs, stable identifiers).
+
+use anyhow::{Context, Result};
+use mcp_protocol::{read_recording, RecordedExchange};
+use serde_json::Value;
+use std::{collections::HashMap, path::Path, process::Stdio, time::Duration};
+use tokio::{
+    process::{Child, Command},
+    time::sleep,
+};
+use tracing::{info, warn};
+
+/// Object keys whose values legitimately differ between two runs
+const VOLATILE_KEYS: &[&str] = &["timestamp", "session_id", "uid", "started_at", "expires_at"];
+
+/// A response that differs from the recorded one
+#[derive(Debug)]
+pub struct ReplayMismatch {
+    /// Position of the exchange in the recording, starting at 1
+    pub index: usize,
+    pub method: String,
+    pub recorded: Option<Value>,
+    pub replayed: Option<Value>,
+}
+
+/// Outcome of replaying a recording
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+/// Fresh server the recording is replayed against
+struct ReplayServer {
+    _process: Child,
+    _project_root: tempfile::TempDir,
+    base_url: String,
+}
+
+impl ReplayServer {
+    async fn start(axon_binary: &Path, port: u16) -> Result<Self> {
+        let project_root = tempfile::tempdir().context("Failed to create project root")?;
+        let process = Command::new(axon_binary)
+            .arg("--start")
+            .arg("--port")
+            .arg(port.to_string())
+            .arg("--project")
+            .arg("replay")
+            .arg("--project-root")
+            .arg(project_root.path())
+            .env("RUST_LOG", "warn")
+            .stdout(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start axon-mcp")?;
+
+        let base_url = format!("http://127.0.0.1:{port}");
+        for _ in 0..30 {
+            sleep(Duration::from_millis(500)).await;
+            let health = reqwest::Client::new()
+                .get(format!("{base_url}/health"))
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await;
+            if health.is_ok_and(|response| response.status().is_success()) {
+                return Ok(Self {
+                    _process: process,
+                    _project_root: project_root,
+                    base_url,
+                });
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Server did not become ready within 15 seconds"
+        ))
+    }
+}
+
+/// Replay `recording` against a fresh server started from `axon_binary` on `port`
+pub async fn replay_recording(
+    axon_binary: &Path,
+    recording: &Path,
+    port: u16,
+) -> Result<ReplayReport> {
+    let exchanges = read_recording(recording)
+        .with_context(|| format!("Failed to read recording {}", recording.display()))?;
+    info!(
+        "Replaying {} exchanges from {}",
+        exchanges.len(),
+        recording.display()
+    );
+
+    let server = ReplayServer::start(axon_binary, port).await?;
+    let client = reqwest::Client::new();
+    let mut sessions: HashMap<String, String> = HashMap::new();
+    let mut report = ReplayReport::default();
+
+    for (index, exchange) in exchanges.iter().enumerate() {
+        let method = exchange
+            .request
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut request = client
+            .post(format!("{}{}", server.base_url, exchange.path))
+            .json(&exchange.request);
+        if let Some(agent_name) = &exchange.agent_name {
+            request = request.header("X-Agent-Name", agent_name);
+        }
+        let replayed_session = exchange.session_id.as_ref().and_then(|id| sessions.get(id));
+        if let (Some(session), false) = (replayed_session, method == "initialize") {
+            request = request.header("Mcp-Session-Id", session);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Exchange {} ({method}) failed", index + 1))?;
+        if method == "initialize" {
+            let new_session = response
+                .headers()
+                .get("mcp-session-id")
+                .and_then(|v| v.to_str().ok());
+            if let (Some(recorded), Some(new)) = (&exchange.session_id, new_session) {
+                sessions.insert(recorded.clone(), new.to_string());
+            }
+        }
+        let body = response.text().await?;
+        let replayed = serde_json::from_str::<Value>(&body).ok();
+        report.replayed += 1;
+
+        if !responses_match(exchange, replayed.as_ref()) {
+            warn!(
+                "Exchange {} ({method}) returned a different response",
+                index + 1
+            );
+            report.mismatches.push(ReplayMismatch {
+                index: index + 1,
+                method: method.to_string(),
+                recorded: exchange.response.clone(),
+                replayed,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Whether the replayed response equals the recorded one, ignoring volatile values
+fn responses_match(exchange: &RecordedExchange, replayed: Option<&Value>) -> bool {
+    let normalize = |value: Option<&Value>| {
+        value.cloned().map(|mut value| {
+            strip_volatile(&mut value);
+            value
+        })
+    };
+    normalize(exchange.response.as_ref()) == normalize(replayed)
+}
+
+fn strip_volatile(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, _| !VOLATILE_KEYS.contains(&key.as_str()) && !key.ends_with("_at"));
+            object.values_mut().for_each(strip_volatile);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_volatile),
+        _ => {}
+    }
+}
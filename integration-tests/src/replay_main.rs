@@ -0,0 +1,78 @@
+//! Recording Replay Binary
+//!
+//! Re-executes a session recorded with `axon-mcp --record` against a fresh
+//! database and reports every response that differs from the recording.
+
+mod replay;
+
+use anyhow::Result;
+use clap::Parser;
+use std::path::PathBuf;
+use tracing::{error, info};
+
+/// Command line arguments for replaying a recording
+#[derive(Parser)]
+#[command(name = "axon-replay")]
+#[command(about = "Replay a recorded JSON-RPC session against a fresh Axon MCP server")]
+#[command(version = env!("CARGO_PKG_VERSION"))]
+struct ReplayArgs {
+    /// Recording file from .axon/recordings/
+    pub recording: PathBuf,
+
+    /// Path to the axon-mcp binary to replay against
+    #[arg(short, long, default_value = "./target/debug/axon-mcp")]
+    pub axon_binary: PathBuf,
+
+    /// Port for the fresh server to listen on
+    #[arg(long, default_value = "8892")]
+    pub server_port: u16,
+
+    /// Print the recorded and replayed response of every mismatch
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = ReplayArgs::parse();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    if !args.axon_binary.exists() {
+        return Err(anyhow::anyhow!(
+            "Axon binary not found at {:?}. Please build it first with: cargo build --bin axon-mcp",
+            args.axon_binary
+        ));
+    }
+
+    let report =
+        replay::replay_recording(&args.axon_binary, &args.recording, args.server_port).await?;
+    for mismatch in &report.mismatches {
+        error!(
+            "Exchange {} ({}) differs from the recording",
+            mismatch.index, mismatch.method
+        );
+        if args.verbose {
+            error!("  recorded: {}", serde_json::to_string(&mismatch.recorded)?);
+            error!("  replayed: {}", serde_json::to_string(&mismatch.replayed)?);
+        }
+    }
+
+    if !report.mismatches.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} of {} exchanges differ from the recording",
+            report.mismatches.len(),
+            report.replayed
+        ));
+    }
+    info!(
+        "All {} exchanges reproduced the recorded responses",
+        report.replayed
+    );
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Admin tool namespace
+//!
+//! Operations that can destroy or rewrite shared state are only callable by
+//! admin clients and are left out of `tools/list` for everyone else. A client
+//! becomes admin by presenting the configured API key as
+//! `Authorization: Bearer <key>`, either on each request or once on
+//! `initialize`, which marks the whole session as admin. Without a configured
+//! key the admin tools are unavailable.
+
+use axum::http::{header, HeaderMap};
+use serde_json::Value;
+
+/// Methods that require the admin role
+pub const ADMIN_METHODS: &[&str] = &[
+    "cleanup_timed_out_tasks",
+    "export_audit_log",
+    "list_background_jobs",
+    "maintain_database",
+    "merge_workspace",
+    "purge_agent_data",
+    "register_with_claude_code",
+    "run_readonly_query",
+];
+
+/// Whether `method` belongs to the admin namespace
+pub fn is_admin_method(method: &str) -> bool {
+    ADMIN_METHODS.contains(&method)
+}
+
+/// Verifies admin API keys presented by clients
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdminAuth {
+    api_key: Option<String>,
+}
+
+impl AdminAuth {
+    /// Grant the admin role to clients presenting `api_key`; `None` disables admin tools
+    pub fn new(api_key: Option<String>) -> Self {
+        Self {
+            api_key: api_key.filter(|key| !key.is_empty()),
+        }
+    }
+
+    /// Whether admin tools can be unlocked at all
+    pub fn is_enabled(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    /// Whether the request headers carry the admin API key
+    pub fn is_admin_request(&self, headers: &HeaderMap) -> bool {
+        let Some(expected) = &self.api_key else {
+            return false;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|presented| constant_time_eq(presented.trim(), expected))
+    }
+}
+
+/// Remove admin tools from a `tools/list` result
+pub fn hide_admin_tools(tools_list: &mut Value) {
+    if let Some(tools) = tools_list.get_mut("tools").and_then(|t| t.as_array_mut()) {
+        tools.retain(|tool| {
+            !tool
+                .get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(is_admin_method)
+        });
+    }
+}
+
+/// Compare secrets without leaking the position of the first mismatch
+pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bearer(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {key}").parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_admin_key_verification() {
+        let auth = AdminAuth::new(Some("s3cret".into()));
+        assert!(auth.is_admin_request(&bearer("s3cret")));
+        assert!(!auth.is_admin_request(&bearer("wrong")));
+        assert!(!auth.is_admin_request(&HeaderMap::new()));
+
+        let disabled = AdminAuth::new(Some(String::new()));
+        assert!(!disabled.is_enabled());
+        assert!(!disabled.is_admin_request(&bearer("")));
+    }
+
+    #[test]
+    fn test_hide_admin_tools() {
+        let mut tools =
+            json!({"tools": [{"name": "list_tasks"}, {"name": "cleanup_timed_out_tasks"}]});
+        hide_admin_tools(&mut tools);
+        assert_eq!(tools["tools"].as_array().unwrap().len(), 1);
+        assert_eq!(tools["tools"][0]["name"], "list_tasks");
+    }
+}
@@ -0,0 +1,498 @@
+//! Agent identity and ownership checks
+//!
+//! Clients name the agent they act for in the `X-Agent-Name` header, either on
+//! each request or once on `initialize`, which binds the whole session to that
+//! agent. Agents with a configured token are reserved: their name is only
+//! accepted together with `X-Agent-Token`. Once a caller is identified, it may
+//! only act under its own name and only mutate tasks and work sessions it owns;
+//! coordinators and admin clients may act on any agent's work. Anonymous
+//! callers may only mutate unowned work, and never use coordinator methods.
+
+use crate::admin::{constant_time_eq, is_admin_method};
+use crate::error::McpError;
+use crate::read_only::is_allowed_when_read_only;
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use task_core::AgentNamePolicy;
+
+/// Header naming the agent a request acts for
+pub const AGENT_NAME_HEADER: &str = "x-agent-name";
+
+/// Header carrying the token of a reserved agent
+pub const AGENT_TOKEN_HEADER: &str = "x-agent-token";
+
+/// Check a mutating tool applies to its caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guard {
+    /// Any caller; anonymous ones only while no identity is required
+    Open,
+    /// The parameter names the agent performing the call
+    ActingAgent(&'static str),
+    /// The parameter is the ID of a task only its owner may change
+    OwnedTask(&'static str),
+    /// The parameter is the code of a task only its owner may change
+    OwnedTaskCode(&'static str),
+    /// The parameter is the ID of a work session only its agent may change
+    OwnedSession(&'static str),
+    /// Changes other agents' work or project files; coordinators only
+    Coordinator,
+    /// [`Guard::Coordinator`] when the boolean parameter is set
+    CoordinatorWhen(&'static str),
+}
+
+/// Every tool that changes state, with the checks applied to its callers
+///
+/// Tools missing here and from the read-only and admin lists are reserved for
+/// coordinators. `create_task_message` may also change its task through
+/// message effects, which the server checks against the task's owner.
+pub const MUTATING_METHODS: &[(&str, &[Guard])] = &[
+    ("create_task", &[Guard::Open]),
+    ("update_task", &[Guard::OwnedTask("id")]),
+    ("set_task_state", &[Guard::OwnedTask("id")]),
+    ("assign_task", &[Guard::OwnedTask("id")]),
+    ("archive_task", &[Guard::OwnedTask("id")]),
+    ("claim_task", &[Guard::ActingAgent("agent_name")]),
+    ("release_task", &[Guard::ActingAgent("agent_name")]),
+    ("start_work_session", &[Guard::ActingAgent("agent_name")]),
+    ("end_work_session", &[Guard::OwnedSession("session_id")]),
+    ("append_session_log", &[Guard::OwnedSession("session_id")]),
+    (
+        "create_task_message",
+        &[Guard::ActingAgent("author_agent_name")],
+    ),
+    (
+        "add_task_comment",
+        &[Guard::ActingAgent("author_agent_name")],
+    ),
+    ("edit_task_comment", &[Guard::ActingAgent("agent_name")]),
+    ("delete_task_comment", &[Guard::ActingAgent("agent_name")]),
+    (
+        "register_artifact",
+        &[
+            Guard::ActingAgent("agent_name"),
+            Guard::OwnedTaskCode("task_code"),
+        ],
+    ),
+    ("apply_task_operations", &[Guard::Coordinator]),
+    ("rebalance_work", &[Guard::Coordinator]),
+    ("create_pipeline", &[Guard::Coordinator]),
+    ("sync_tasks_markdown", &[Guard::Coordinator]),
+    ("scan_code_todos", &[Guard::CoordinatorWhen("confirm")]),
+    ("rollback_setup", &[Guard::Coordinator]),
+    ("register_agent", &[Guard::Open]),
+    ("regenerate_agent_prompt", &[Guard::Open]),
+    ("create_main_ai_file", &[Guard::Open]),
+    ("setup_wizard", &[Guard::Open]),
+    ("define_capability", &[Guard::Open]),
+    ("deprecate_capability", &[Guard::Open]),
+];
+
+/// Checks applied to callers of `method`; `None` for methods that change nothing
+pub fn method_guards(method: &str) -> Option<&'static [Guard]> {
+    if let Some((_, guards)) = MUTATING_METHODS.iter().find(|(name, _)| *name == method) {
+        return Some(guards);
+    }
+    let changes_state = crate::server::is_tool(method)
+        && !is_allowed_when_read_only(method)
+        && !is_admin_method(method);
+    changes_state.then_some(&[Guard::Coordinator])
+}
+
+/// Work whose owner must match the calling agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnedWork {
+    /// A task, by ID
+    Task(i32),
+    /// A task, by code
+    TaskCode(String),
+    /// A work session, by ID
+    WorkSession(i32),
+}
+
+/// Agent identity settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentAuth {
+    /// Reject task and session mutations from callers that name no agent
+    pub require_identity: bool,
+    /// Agents that may act on any agent's work
+    pub coordinators: Vec<String>,
+    /// Tokens of reserved agents; their names are only accepted with the token
+    pub tokens: HashMap<String, String>,
+}
+
+/// The agent a request acts for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Caller {
+    /// Authenticated agent name; `None` for anonymous callers
+    pub agent_name: Option<String>,
+    /// Whether the caller is a coordinator or admin
+    pub is_coordinator: bool,
+}
+
+impl AgentAuth {
+    /// Read the agent named in the request headers, verifying reserved names
+    pub fn identify(&self, headers: &HeaderMap) -> Result<Option<String>, McpError> {
+        let Some(name) = header_value(headers, AGENT_NAME_HEADER) else {
+            return Ok(None);
+        };
+        AgentNamePolicy::current()
+            .validate(name)
+            .map_err(|e| McpError::Forbidden(format!("invalid {AGENT_NAME_HEADER}: {e}")))?;
+
+        if let Some(expected) = self.tokens.get(name) {
+            let presented = header_value(headers, AGENT_TOKEN_HEADER);
+            if !presented.is_some_and(|token| constant_time_eq(token, expected)) {
+                return Err(McpError::Forbidden(format!(
+                    "agent '{name}' is reserved and requires its {AGENT_TOKEN_HEADER}"
+                )));
+            }
+        }
+        Ok(Some(name.to_string()))
+    }
+
+    /// Resolve the caller of a request on a session bound to `session_agent`
+    ///
+    /// A request may repeat the session's agent but not switch to another one.
+    pub fn caller(
+        &self,
+        headers: &HeaderMap,
+        session_agent: Option<&str>,
+        is_admin: bool,
+    ) -> Result<Caller, McpError> {
+        let agent_name = match (self.identify(headers)?, session_agent) {
+            (Some(named), Some(bound)) if named != bound => {
+                return Err(McpError::Forbidden(format!(
+                    "session is bound to agent '{bound}'; cannot act as '{named}'"
+                )))
+            }
+            (named, bound) => named.or_else(|| bound.map(str::to_string)),
+        };
+        let is_coordinator = is_admin
+            || agent_name
+                .as_ref()
+                .is_some_and(|name| self.coordinators.contains(name));
+
+        Ok(Caller {
+            agent_name,
+            is_coordinator,
+        })
+    }
+
+    /// Check the agent named in the parameters of `method` against the caller
+    ///
+    /// Returns the task or work session whose owner must still be verified.
+    pub fn authorize(
+        &self,
+        caller: &Caller,
+        method: &str,
+        params: &Value,
+    ) -> Result<Option<OwnedWork>, McpError> {
+        let Some(guards) = method_guards(method) else {
+            return Ok(None);
+        };
+        let param = |name: &str| params.get(name).and_then(Value::as_str);
+        let param_id = |name: &str| {
+            params
+                .get(name)
+                .and_then(Value::as_i64)
+                .and_then(|id| i32::try_from(id).ok())
+        };
+        let mut owned = None;
+        let mut acting = None;
+        let mut coordinator_only = false;
+        for guard in guards {
+            match *guard {
+                Guard::Open => {}
+                Guard::ActingAgent(name) => acting = Some(param(name).unwrap_or_default()),
+                Guard::OwnedTask(name) => owned = param_id(name).map(OwnedWork::Task),
+                Guard::OwnedTaskCode(name) => {
+                    owned = param(name).map(|code| OwnedWork::TaskCode(code.to_string()))
+                }
+                Guard::OwnedSession(name) => owned = param_id(name).map(OwnedWork::WorkSession),
+                Guard::Coordinator => coordinator_only = true,
+                Guard::CoordinatorWhen(name) => {
+                    coordinator_only |= params.get(name).and_then(Value::as_bool) == Some(true)
+                }
+            }
+        }
+
+        if caller.is_coordinator {
+            return Ok(None);
+        }
+
+        let Some(agent_name) = &caller.agent_name else {
+            if self.require_identity || coordinator_only {
+                return Err(McpError::Forbidden(format!(
+                    "{method} requires an agent identity in {AGENT_NAME_HEADER}"
+                )));
+            }
+            // Anonymous callers cannot use a reserved agent's name
+            if let Some(name) = acting.filter(|name| self.tokens.contains_key(*name)) {
+                return Err(McpError::Forbidden(format!(
+                    "agent '{name}' is reserved and requires its {AGENT_TOKEN_HEADER}"
+                )));
+            }
+            return Ok(owned);
+        };
+
+        if coordinator_only {
+            return Err(McpError::Forbidden(format!(
+                "{method} is reserved for coordinators; agent '{agent_name}' is not one"
+            )));
+        }
+        if let Some(name) = acting.filter(|name| *name != agent_name) {
+            return Err(McpError::Forbidden(format!(
+                "agent '{agent_name}' cannot act as '{name}'"
+            )));
+        }
+        Ok(owned)
+    }
+}
+
+/// Agent a call is attributed to: the caller, else the agent its parameters act for
+pub fn attributed_agent(caller: Option<&Caller>, method: &str, params: &Value) -> Option<String> {
+    caller
+        .and_then(|caller| caller.agent_name.clone())
+        .or_else(|| {
+            let param = acting_param(method).unwrap_or("agent_name");
+            params
+                .get(param)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+        })
+}
+
+impl Caller {
+    /// Check that the caller may change work owned by `owner`
+    ///
+    /// Unowned work is always allowed; owned work only to its owner, named in
+    /// `X-Agent-Name`, and to coordinators.
+    pub fn check_owner(&self, work: &OwnedWork, owner: Option<&str>) -> Result<(), McpError> {
+        let Some(owner) = owner.filter(|owner| !owner.is_empty()) else {
+            return Ok(());
+        };
+        if self.is_coordinator || self.agent_name.as_deref() == Some(owner) {
+            return Ok(());
+        }
+        let work = match work {
+            OwnedWork::Task(id) => format!("task {id}"),
+            OwnedWork::TaskCode(code) => format!("task {code}"),
+            OwnedWork::WorkSession(id) => format!("work session {id}"),
+        };
+        let identify = if self.agent_name.is_none() {
+            format!("; identify as its owner in {AGENT_NAME_HEADER}")
+        } else {
+            String::new()
+        };
+        Err(McpError::Forbidden(format!(
+            "{work} belongs to agent '{owner}'; only its owner or a coordinator may change it{identify}"
+        )))
+    }
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Parameter naming the agent that performs `method`
+fn acting_param(method: &str) -> Option<&'static str> {
+    method_guards(method)?.iter().find_map(|guard| match guard {
+        Guard::ActingAgent(param) => Some(*param),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn agent_headers(name: &str, token: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AGENT_NAME_HEADER, name.parse().unwrap());
+        if let Some(token) = token {
+            headers.insert(AGENT_TOKEN_HEADER, token.parse().unwrap());
+        }
+        headers
+    }
+
+    fn auth() -> AgentAuth {
+        AgentAuth {
+            require_identity: false,
+            coordinators: vec!["project-lead".into()],
+            tokens: HashMap::from([("backend-dev".to_string(), "b4ck".to_string())]),
+        }
+    }
+
+    #[test]
+    fn test_reserved_agents_require_token() {
+        let auth = auth();
+        assert_eq!(
+            auth.identify(&agent_headers("backend-dev", Some("b4ck")))
+                .unwrap()
+                .as_deref(),
+            Some("backend-dev")
+        );
+        assert!(auth
+            .identify(&agent_headers("backend-dev", Some("wrong")))
+            .is_err());
+        assert!(auth.identify(&agent_headers("backend-dev", None)).is_err());
+        assert!(auth.identify(&HeaderMap::new()).unwrap().is_none());
+
+        // Anonymous callers cannot claim work in a reserved agent's name
+        let anonymous = Caller::default();
+        let claim = json!({"task_id": 1, "agent_name": "backend-dev"});
+        assert!(auth.authorize(&anonymous, "claim_task", &claim).is_err());
+    }
+
+    #[test]
+    fn test_caller_cannot_impersonate() {
+        let auth = auth();
+        let caller = auth
+            .caller(&agent_headers("frontend-dev", None), None, false)
+            .unwrap();
+
+        let claim = json!({"task_id": 1, "agent_name": "qa-bot"});
+        assert!(auth.authorize(&caller, "claim_task", &claim).is_err());
+        assert!(auth
+            .caller(&agent_headers("frontend-dev", None), Some("qa-bot"), false)
+            .is_err());
+
+        let owned = auth
+            .authorize(&caller, "end_work_session", &json!({"session_id": 7}))
+            .unwrap();
+        assert_eq!(owned, Some(OwnedWork::WorkSession(7)));
+        assert!(caller
+            .check_owner(&OwnedWork::WorkSession(7), Some("qa-bot"))
+            .is_err());
+        assert!(caller
+            .check_owner(&OwnedWork::WorkSession(7), Some("frontend-dev"))
+            .is_ok());
+
+        // Leaving out the header does not get around the owner
+        let anonymous = Caller::default();
+        let owned = auth
+            .authorize(&anonymous, "end_work_session", &json!({"session_id": 7}))
+            .unwrap();
+        assert_eq!(owned, Some(OwnedWork::WorkSession(7)));
+        assert!(anonymous
+            .check_owner(&OwnedWork::WorkSession(7), Some("qa-bot"))
+            .is_err());
+        assert!(anonymous.check_owner(&OwnedWork::Task(3), None).is_ok());
+        let artifact = json!({"task_code": "API-01", "agent_name": "frontend-dev"});
+        assert_eq!(
+            auth.authorize(&caller, "register_artifact", &artifact)
+                .unwrap(),
+            Some(OwnedWork::TaskCode("API-01".to_string()))
+        );
+
+        let lead = auth
+            .caller(&agent_headers("project-lead", None), None, false)
+            .unwrap();
+        assert!(lead.is_coordinator);
+        assert_eq!(auth.authorize(&lead, "claim_task", &claim).unwrap(), None);
+
+        let strict = AgentAuth {
+            require_identity: true,
+            ..auth
+        };
+        assert!(strict
+            .authorize(&Caller::default(), "update_task", &json!({"id": 1}))
+            .is_err());
+        assert!(strict
+            .authorize(&Caller::default(), "list_tasks", &json!({}))
+            .is_ok());
+
+        let message = json!({"author_agent_name": "qa-bot"});
+        assert_eq!(
+            attributed_agent(None, "create_task_message", &message).as_deref(),
+            Some("qa-bot")
+        );
+        assert_eq!(
+            attributed_agent(Some(&caller), "claim_task", &claim).as_deref(),
+            Some("frontend-dev")
+        );
+        assert_eq!(attributed_agent(None, "list_tasks", &json!({})), None);
+    }
+
+    #[test]
+    fn test_batch_operations_are_reserved_for_coordinators() {
+        let auth = auth();
+        let batch = json!({"operations": [{"op": "set_state", "id": 1, "state": "Done"}]});
+        let agent = auth
+            .caller(&agent_headers("frontend-dev", None), None, false)
+            .unwrap();
+        assert!(auth
+            .authorize(&agent, "apply_task_operations", &batch)
+            .is_err());
+        assert!(auth
+            .authorize(&agent, "rebalance_work", &json!({"confirm": true}))
+            .is_err());
+
+        let lead = auth
+            .caller(&agent_headers("project-lead", None), None, false)
+            .unwrap();
+        assert_eq!(
+            auth.authorize(&lead, "apply_task_operations", &batch)
+                .unwrap(),
+            None
+        );
+        assert!(auth
+            .authorize(&Caller::default(), "apply_task_operations", &batch)
+            .is_err());
+        assert!(auth
+            .authorize(&Caller::default(), "rebalance_work", &json!({}))
+            .is_err());
+
+        let strict = AgentAuth {
+            require_identity: true,
+            ..auth
+        };
+        assert!(strict
+            .authorize(&Caller::default(), "apply_task_operations", &batch)
+            .is_err());
+    }
+
+    #[test]
+    fn test_every_mutating_tool_is_declared() {
+        let undeclared: Vec<String> = crate::server::tool_names()
+            .into_iter()
+            .filter(|tool| !is_allowed_when_read_only(tool) && !is_admin_method(tool))
+            .filter(|tool| !MUTATING_METHODS.iter().any(|(name, _)| name == tool))
+            .collect();
+        assert!(
+            undeclared.is_empty(),
+            "undeclared mutating tools: {undeclared:?}"
+        );
+        for (name, _) in MUTATING_METHODS {
+            assert!(crate::server::is_tool(name), "{name} is not a tool");
+        }
+
+        let auth = auth();
+        let agent = auth
+            .caller(&agent_headers("frontend-dev", None), None, false)
+            .unwrap();
+        for method in ["sync_tasks_markdown", "create_pipeline", "rollback_setup"] {
+            assert!(auth.authorize(&agent, method, &json!({})).is_err());
+        }
+        assert!(auth
+            .authorize(&agent, "scan_code_todos", &json!({"confirm": true}))
+            .is_err());
+        assert!(auth
+            .authorize(&agent, "scan_code_todos", &json!({}))
+            .is_ok());
+        let admin = auth.caller(&HeaderMap::new(), None, true).unwrap();
+        assert!(auth
+            .authorize(&admin, "sync_tasks_markdown", &json!({}))
+            .is_ok());
+    }
+}
@@ -0,0 +1,343 @@
+//! OpenAPI and JSON Schema export of the tool surface
+//!
+//! Clients in other languages are generated from a machine-readable
+//! description of the tools. Both documents are built from
+//! [`tool_definitions`], the same definitions `tools/list` serves, so they
+//! cannot drift from what the server accepts. Each tool is a JSON-RPC 2.0
+//! method posted to `/mcp`; the OpenAPI document describes that endpoint with
+//! one request and response variant per tool, and the standalone schemas
+//! describe each tool's params and result.
+//!
+//! Results serialized from a task carry a full schema. Other results accept
+//! any JSON value; `docs/API.md` documents their fields.
+
+use crate::server::tool_definitions;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// JSON Schema dialect of the standalone schemas, shared with OpenAPI 3.1
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Tools whose result is a single task, `null` when it was looked up and not found
+const TASK_RESULT_TOOLS: &[(&str, bool)] = &[
+    ("create_task", false),
+    ("update_task", false),
+    ("set_task_state", false),
+    ("assign_task", false),
+    ("archive_task", false),
+    ("claim_task", false),
+    ("release_task", false),
+    ("get_task_by_id", true),
+    ("get_task_by_code", true),
+    ("get_task_by_uid", true),
+];
+
+/// Tools whose result is a list of tasks
+const TASK_LIST_RESULT_TOOLS: &[&str] = &["list_tasks", "discover_work", "cleanup_timed_out_tasks"];
+
+/// A tool as exported
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    /// Schema of the JSON-RPC `params`
+    pub input: Value,
+    /// Schema of the JSON-RPC `result`; references `Task` as `{ref_prefix}Task`
+    pub output: Value,
+}
+
+/// Schema of a task as returned by the task tools
+pub(crate) fn task_schema() -> Value {
+    let timestamp = json!({"type": "string", "format": "date-time"});
+    let optional_timestamp = json!({"type": ["string", "null"], "format": "date-time"});
+    json!({
+        "type": "object",
+        "required": ["id", "code", "name", "description", "state", "inserted_at", "updated_at"],
+        "properties": {
+            "id": {"type": "integer"},
+            "uid": {"type": ["string", "null"]},
+            "code": {"type": "string"},
+            "name": {"type": "string"},
+            "description": {"type": "string"},
+            "owner_agent_name": {"type": ["string", "null"]},
+            "state": {"type": "string", "enum": task_core::TaskState::ALL.map(|s| s.as_str())},
+            "inserted_at": timestamp,
+            "done_at": optional_timestamp,
+            "claimed_at": optional_timestamp,
+            "lease_expires_at": optional_timestamp,
+            "updated_at": timestamp,
+            "state_changed_at": timestamp,
+            "time_in_state_seconds": {"type": "integer", "minimum": 0},
+            "required_capabilities": {"type": "array", "items": {"type": "string"}}
+        },
+        "additionalProperties": true
+    })
+}
+
+/// Schema of a tool's result, referencing the task schema under `ref_prefix`
+fn output_schema(tool: &str, ref_prefix: &str) -> Value {
+    let task = json!({"$ref": format!("{ref_prefix}Task")});
+    if let Some((_, nullable)) = TASK_RESULT_TOOLS.iter().find(|(name, _)| *name == tool) {
+        return match nullable {
+            true => json!({"oneOf": [task, {"type": "null"}]}),
+            false => task,
+        };
+    }
+    if TASK_LIST_RESULT_TOOLS.contains(&tool) {
+        return json!({"type": "array", "items": task});
+    }
+    json!({"description": format!("Result of {tool}; see docs/API.md for its fields")})
+}
+
+/// Every tool with its input and output schema, in `tools/list` order
+pub fn tool_specs(ref_prefix: &str) -> Vec<ToolSpec> {
+    tool_definitions()["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| {
+            let name = tool["name"].as_str()?.to_string();
+            Some(ToolSpec {
+                description: tool["description"].as_str().unwrap_or_default().to_string(),
+                input: tool["inputSchema"].clone(),
+                output: output_schema(&name, ref_prefix),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// `create_task` as `CreateTask`
+pub(crate) fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// OpenAPI 3.1 document of the HTTP transport
+pub fn openapi_document(version: &str) -> Value {
+    let tools = tool_specs("#/components/schemas/");
+    let id_schema = json!({"type": ["string", "integer", "null"]});
+    let mut schemas = Map::new();
+    schemas.insert("Task".to_string(), task_schema());
+    schemas.insert(
+        "JsonRpcError".to_string(),
+        json!({
+            "type": "object",
+            "required": ["jsonrpc", "id", "error"],
+            "properties": {
+                "jsonrpc": {"const": "2.0"},
+                "id": id_schema,
+                "error": {
+                    "type": "object",
+                    "required": ["code", "message"],
+                    "properties": {
+                        "code": {"type": "integer"},
+                        "message": {"type": "string"},
+                        "data": {}
+                    }
+                }
+            }
+        }),
+    );
+
+    let mut requests = Vec::new();
+    let mut responses = Vec::new();
+    let mut mapping = Map::new();
+    for tool in &tools {
+        let base = pascal_case(&tool.name);
+        let schema_ref =
+            |suffix: &str| json!({"$ref": format!("#/components/schemas/{base}{suffix}")});
+        schemas.insert(format!("{base}Input"), tool.input.clone());
+        schemas.insert(format!("{base}Output"), tool.output.clone());
+        schemas.insert(
+            format!("{base}Request"),
+            json!({
+                "type": "object",
+                "description": tool.description,
+                "required": ["jsonrpc", "id", "method"],
+                "properties": {
+                    "jsonrpc": {"const": "2.0"},
+                    "id": id_schema,
+                    "method": {"const": tool.name},
+                    "params": schema_ref("Input")
+                }
+            }),
+        );
+        schemas.insert(
+            format!("{base}Response"),
+            json!({
+                "type": "object",
+                "required": ["jsonrpc", "id", "result"],
+                "properties": {
+                    "jsonrpc": {"const": "2.0"},
+                    "id": id_schema,
+                    "result": schema_ref("Output")
+                }
+            }),
+        );
+        requests.push(schema_ref("Request"));
+        responses.push(schema_ref("Response"));
+        mapping.insert(
+            tool.name.clone(),
+            json!(format!("#/components/schemas/{base}Request")),
+        );
+    }
+    responses.push(json!({"$ref": "#/components/schemas/JsonRpcError"}));
+
+    json!({
+        "openapi": "3.1.0",
+        "jsonSchemaDialect": JSON_SCHEMA_DIALECT,
+        "info": {
+            "title": "Axon MCP",
+            "version": version,
+            "description": "Task coordination for AI agents. Every tool is a JSON-RPC 2.0 method posted to /mcp."
+        },
+        "paths": {
+            "/mcp": {
+                "post": {
+                    "operationId": "callTool",
+                    "summary": "Call a tool",
+                    "parameters": [{
+                        "name": "Mcp-Session-Id",
+                        "in": "header",
+                        "required": false,
+                        "description": "Session from initialize, when the server requires sessions",
+                        "schema": {"type": "string"}
+                    }],
+                    "requestBody": {
+                        "required": true,
+                        "content": {"application/json": {"schema": {
+                            "oneOf": requests,
+                            "discriminator": {"propertyName": "method", "mapping": mapping}
+                        }}}
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "JSON-RPC result or error",
+                            "content": {"application/json": {"schema": {"oneOf": responses}}}
+                        }
+                    },
+                    "security": [{}, {"bearerAuth": []}]
+                }
+            },
+            "/health": {
+                "get": {
+                    "operationId": "health",
+                    "summary": "Liveness check",
+                    "responses": {"200": {"description": "The server is up"}}
+                }
+            }
+        },
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "Agent or admin API key, when configured"
+                }
+            }
+        },
+        "x-mcp-tools": tools.iter().map(|tool| tool.name.as_str()).collect::<Vec<_>>()
+    })
+}
+
+/// Standalone JSON Schemas by file name: `<tool>.input.json` and `<tool>.output.json`
+pub fn standalone_schemas() -> BTreeMap<String, Value> {
+    let mut schemas = BTreeMap::new();
+    for tool in tool_specs("#/$defs/") {
+        let mut input = tool.input;
+        input["$schema"] = json!(JSON_SCHEMA_DIALECT);
+        input["title"] = json!(format!("{} params", tool.name));
+        schemas.insert(format!("{}.input.json", tool.name), input);
+
+        let mut output = tool.output;
+        output["$schema"] = json!(JSON_SCHEMA_DIALECT);
+        output["title"] = json!(format!("{} result", tool.name));
+        if output.to_string().contains("#/$defs/Task") {
+            output["$defs"] = json!({"Task": task_schema()});
+        }
+        schemas.insert(format!("{}.output.json", tool.name), output);
+    }
+    schemas
+}
+
+/// Write `openapi.json` and `schemas/*.json` to `dir`; returns the files written
+pub fn write_api_spec(dir: &Path, version: &str) -> std::io::Result<Vec<PathBuf>> {
+    let schema_dir = dir.join("schemas");
+    std::fs::create_dir_all(&schema_dir)?;
+    let pretty = |value: &Value| serde_json::to_string_pretty(value).unwrap_or_default() + "\n";
+
+    let mut written = vec![dir.join("openapi.json")];
+    std::fs::write(&written[0], pretty(&openapi_document(version)))?;
+    for (name, schema) in standalone_schemas() {
+        let path = schema_dir.join(name);
+        std::fs::write(&path, pretty(&schema))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tool_names;
+
+    /// Every `$ref` in `value`
+    fn refs(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    found.push(target.clone());
+                }
+                map.values().for_each(|v| refs(v, found));
+            }
+            Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_openapi_covers_every_tool() {
+        let document = openapi_document("1.0.0");
+        let schemas = document["components"]["schemas"].as_object().unwrap();
+        let names = tool_names();
+        assert_eq!(
+            document["x-mcp-tools"].as_array().unwrap().len(),
+            names.len()
+        );
+        assert_eq!(schemas["CreateTaskInput"]["required"][0], "code");
+        assert_eq!(
+            schemas["GetTaskByIdOutput"]["oneOf"][1],
+            json!({"type": "null"})
+        );
+
+        let mut found = Vec::new();
+        refs(&document, &mut found);
+        for target in found {
+            let name = target.strip_prefix("#/components/schemas/").unwrap();
+            assert!(schemas.contains_key(name), "dangling {target}");
+        }
+    }
+
+    #[test]
+    fn test_standalone_schemas() {
+        let schemas = standalone_schemas();
+        assert_eq!(schemas.len(), tool_names().len() * 2);
+        let list = &schemas["list_tasks.output.json"];
+        assert_eq!(list["items"]["$ref"], "#/$defs/Task");
+        assert!(list["$defs"]["Task"].is_object());
+        assert_eq!(
+            schemas["claim_task.input.json"]["$schema"],
+            JSON_SCHEMA_DIALECT
+        );
+    }
+}
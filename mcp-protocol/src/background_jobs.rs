@@ -0,0 +1,159 @@
+//! Status of periodic background jobs
+//!
+//! The job scheduler lives in the server binary; this module holds the shared
+//! registry it reports into, so the `list_background_jobs` tool can show when
+//! each job last ran and how it went.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Result of the most recent run of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    /// Registered but not run yet
+    Pending,
+    /// Currently executing
+    Running,
+    /// Last run completed successfully
+    Succeeded,
+    /// Last run returned an error or panicked
+    Failed,
+}
+
+/// Last-run status and counters of one background job
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobStatus {
+    /// Unique job name
+    pub name: String,
+    /// Base interval between runs, in seconds
+    pub interval_secs: u64,
+    /// Maximum random delay added to each run, in seconds
+    pub jitter_secs: u64,
+    /// Outcome of the most recent run
+    pub last_outcome: JobOutcome,
+    /// Summary returned by the last successful run
+    pub last_summary: Option<String>,
+    /// Error of the last failed run
+    pub last_error: Option<String>,
+    /// When the most recent run started
+    pub last_started_at: Option<DateTime<Utc>>,
+    /// When the most recent run finished
+    pub last_finished_at: Option<DateTime<Utc>>,
+    /// Duration of the most recent finished run, in milliseconds
+    pub last_duration_ms: Option<u64>,
+    /// When the next run is due
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// Completed runs, successful or not
+    pub run_count: u64,
+    /// Failed runs
+    pub failure_count: u64,
+}
+
+/// Job statuses shared between the scheduler and request handlers
+#[derive(Debug, Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<BTreeMap<String, JobStatus>>>,
+}
+
+impl JobRegistry {
+    /// Add a job in the `pending` state, replacing any job with the same name
+    pub fn register(&self, name: &str, interval: Duration, jitter: Duration) {
+        self.jobs.lock().unwrap().insert(
+            name.to_string(),
+            JobStatus {
+                name: name.to_string(),
+                interval_secs: interval.as_secs(),
+                jitter_secs: jitter.as_secs(),
+                last_outcome: JobOutcome::Pending,
+                last_summary: None,
+                last_error: None,
+                last_started_at: None,
+                last_finished_at: None,
+                last_duration_ms: None,
+                next_run_at: None,
+                run_count: 0,
+                failure_count: 0,
+            },
+        );
+    }
+
+    /// Record when the next run of `name` is due
+    pub fn record_scheduled(&self, name: &str, next_run_at: DateTime<Utc>) {
+        self.update(name, |job| job.next_run_at = Some(next_run_at));
+    }
+
+    /// Record that a run of `name` has started
+    pub fn record_started(&self, name: &str) {
+        self.update(name, |job| {
+            job.last_outcome = JobOutcome::Running;
+            job.last_started_at = Some(Utc::now());
+            job.next_run_at = None;
+        });
+    }
+
+    /// Record the result of a run of `name`
+    pub fn record_finished(&self, name: &str, result: Result<String, String>, elapsed: Duration) {
+        self.update(name, |job| {
+            job.run_count += 1;
+            job.last_finished_at = Some(Utc::now());
+            job.last_duration_ms = Some(elapsed.as_millis().min(u64::MAX as u128) as u64);
+            match result {
+                Ok(summary) => {
+                    job.last_outcome = JobOutcome::Succeeded;
+                    job.last_summary = Some(summary);
+                    job.last_error = None;
+                }
+                Err(error) => {
+                    job.last_outcome = JobOutcome::Failed;
+                    job.last_error = Some(error);
+                    job.failure_count += 1;
+                }
+            }
+        });
+    }
+
+    /// Status of every registered job, ordered by name
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        self.jobs.lock().unwrap().values().cloned().collect()
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut JobStatus)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(name) {
+            apply(job);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_run_bookkeeping() {
+        let registry = JobRegistry::default();
+        registry.register("cleanup", Duration::from_secs(60), Duration::from_secs(5));
+        assert_eq!(registry.snapshot()[0].last_outcome, JobOutcome::Pending);
+
+        registry.record_started("cleanup");
+        registry.record_finished(
+            "cleanup",
+            Ok("released 2 tasks".into()),
+            Duration::from_millis(12),
+        );
+        registry.record_started("cleanup");
+        registry.record_finished("cleanup", Err("database locked".into()), Duration::ZERO);
+
+        let job = &registry.snapshot()[0];
+        assert_eq!(job.last_outcome, JobOutcome::Failed);
+        assert_eq!(job.run_count, 2);
+        assert_eq!(job.failure_count, 1);
+        assert_eq!(job.last_summary.as_deref(), Some("released 2 tasks"));
+        assert_eq!(job.last_error.as_deref(), Some("database locked"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Claude Code settings file
+//!
+//! Writes the server's `mcpServers` entry into the user's Claude Code
+//! settings. The previous file is copied next to it before being replaced, and
+//! a dry run reports the entry without writing anything.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use task_core::{
+    upsert_project_server, ClaudeCodeRegistration, Result, TaskError, CLAUDE_CODE_SERVER_NAME,
+};
+
+/// Default location of the user's Claude Code settings (`~/.claude.json`)
+pub fn default_settings_path() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".claude.json"))
+}
+
+/// Register `entry` as the server of `project_root` in the settings file at `settings_path`
+///
+/// The file is created if missing. Unless `dry_run` is set or the entry is
+/// already current, an existing file is first copied to
+/// `<file>.<timestamp>.bak` and then replaced atomically.
+pub fn register_with_claude_code(
+    settings_path: &Path,
+    project_root: &Path,
+    entry: Value,
+    dry_run: bool,
+) -> Result<ClaudeCodeRegistration> {
+    let exists = settings_path.exists();
+    let mut settings = if exists {
+        let json =
+            std::fs::read_to_string(settings_path).map_err(|e| io_error(settings_path, e))?;
+        serde_json::from_str(&json).map_err(|e| {
+            TaskError::Deserialization(format!("{}: {}", settings_path.display(), e))
+        })?
+    } else {
+        json!({})
+    };
+
+    let project = project_root.display().to_string();
+    let previous_entry = upsert_project_server(&mut settings, &project, entry.clone())?;
+    let changed = previous_entry.as_ref() != Some(&entry);
+
+    let mut backup_path = None;
+    if changed && !dry_run {
+        if exists {
+            let backup = backup_file_path(settings_path);
+            std::fs::copy(settings_path, &backup).map_err(|e| io_error(&backup, e))?;
+            backup_path = Some(backup.display().to_string());
+        }
+        let json = serde_json::to_string_pretty(&settings)
+            .map_err(|e| TaskError::Serialization(e.to_string()))?;
+        let temporary = settings_path.with_extension("json.tmp");
+        std::fs::write(&temporary, json).map_err(|e| io_error(&temporary, e))?;
+        std::fs::rename(&temporary, settings_path).map_err(|e| io_error(settings_path, e))?;
+    }
+
+    Ok(ClaudeCodeRegistration {
+        settings_path: settings_path.display().to_string(),
+        project_root: project,
+        server_name: CLAUDE_CODE_SERVER_NAME.to_string(),
+        entry,
+        previous_entry,
+        changed,
+        backup_path,
+        dry_run,
+    })
+}
+
+/// Timestamped backup path next to `path`
+fn backup_file_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", Utc::now().format("%Y%m%d%H%M%S")));
+    path.with_file_name(name)
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> TaskError {
+    TaskError::Internal(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use task_core::http_server_entry;
+
+    #[test]
+    fn test_register_with_claude_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = dir.path().join(".claude.json");
+        let project = Path::new("/work/app");
+
+        let preview =
+            register_with_claude_code(&settings, project, http_server_entry(8499), true).unwrap();
+        assert!(preview.changed);
+        assert!(!settings.exists());
+
+        let created =
+            register_with_claude_code(&settings, project, http_server_entry(8499), false).unwrap();
+        assert_eq!(created.backup_path, None);
+        let unchanged =
+            register_with_claude_code(&settings, project, http_server_entry(8499), false).unwrap();
+        assert!(!unchanged.changed);
+        assert_eq!(unchanged.backup_path, None);
+
+        let updated =
+            register_with_claude_code(&settings, project, http_server_entry(3000), false).unwrap();
+        assert_eq!(updated.previous_entry, Some(http_server_entry(8499)));
+        let backup = std::fs::read_to_string(updated.backup_path.unwrap()).unwrap();
+        assert!(backup.contains("127.0.0.1:8499"));
+        let written = std::fs::read_to_string(&settings).unwrap();
+        assert!(written.contains("127.0.0.1:3000"));
+    }
+}
@@ -0,0 +1,485 @@
+//! Typed clients of the HTTP transport for TypeScript and Python
+//!
+//! Agent frameworks outside Rust (AutoGen, CrewAI, ...) call the server over
+//! HTTP. Rather than hand-writing bindings, they generate a thin client from
+//! the same [`tool_specs`] the API spec is exported from: one params type per
+//! tool, the task type, and a client class with one method per tool that
+//! posts the JSON-RPC request to `/mcp`. The clients need nothing beyond the
+//! standard library (`fetch` in TypeScript, `urllib` in Python 3.11+).
+
+use crate::api_spec::{pascal_case, task_schema, tool_specs};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Python keywords, which cannot name a field of a class-syntax `TypedDict`
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Language of a generated client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientLanguage {
+    TypeScript,
+    Python,
+}
+
+impl ClientLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TypeScript => "TypeScript",
+            Self::Python => "Python",
+        }
+    }
+
+    /// Conventional file name of the client
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::TypeScript => "axon-client.ts",
+            Self::Python => "axon_client.py",
+        }
+    }
+}
+
+/// Source of a client for `language`, generated for server `version`
+pub fn generate_client(language: ClientLanguage, version: &str) -> String {
+    match language {
+        ClientLanguage::TypeScript => typescript_client(version),
+        ClientLanguage::Python => python_client(version),
+    }
+}
+
+/// `create_task` as `createTask`
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+/// `type` of a schema, without `null`, and whether `null` is allowed
+fn schema_types(schema: &Value) -> (Vec<&str>, bool) {
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(name) => vec![name.as_str()],
+        Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    let nullable = types.contains(&"null");
+    (
+        types.into_iter().filter(|t| *t != "null").collect(),
+        nullable,
+    )
+}
+
+/// First line of a description
+fn summary(description: &str) -> &str {
+    description.lines().next().unwrap_or_default().trim()
+}
+
+/// Schema properties with whether each is required, in schema order
+fn properties(schema: &Value) -> Vec<(&String, &Value, bool)> {
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    schema["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, property)| (name, property, required.contains(&name.as_str())))
+        .collect()
+}
+
+fn typescript_type(schema: &Value) -> String {
+    if let Some(target) = schema["$ref"].as_str() {
+        return target.rsplit('/').next().unwrap_or("unknown").to_string();
+    }
+    if let Some(variants) = schema["oneOf"].as_array() {
+        return variants
+            .iter()
+            .map(typescript_type)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    let (types, nullable) = schema_types(schema);
+    if types.is_empty() && nullable {
+        return "null".to_string();
+    }
+    let mut rendered = match (types.as_slice(), schema["enum"].as_array()) {
+        (_, Some(values)) => values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | "),
+        (["string"], _) => "string".to_string(),
+        (["integer"] | ["number"], _) => "number".to_string(),
+        (["boolean"], _) => "boolean".to_string(),
+        (["array"], _) => format!("Array<{}>", typescript_type(&schema["items"])),
+        (["object"], _) if schema["properties"].is_object() => {
+            let fields: Vec<String> = properties(schema)
+                .into_iter()
+                .map(|(name, property, required)| {
+                    let optional = if required { "" } else { "?" };
+                    format!("{name}{optional}: {}", typescript_type(property))
+                })
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        (["object"], _) => "Record<string, unknown>".to_string(),
+        _ => "unknown".to_string(),
+    };
+    if nullable {
+        rendered.push_str(" | null");
+    }
+    rendered
+}
+
+fn typescript_interface(out: &mut String, name: &str, description: &str, schema: &Value) {
+    let _ = writeln!(out, "/** {} */", summary(description).replace("*/", "* /"));
+    let _ = writeln!(out, "export interface {name} {{");
+    for (field, property, required) in properties(schema) {
+        if let Some(doc) = property["description"].as_str() {
+            let _ = writeln!(out, "  /** {} */", summary(doc).replace("*/", "* /"));
+        }
+        let optional = if required { "" } else { "?" };
+        let _ = writeln!(out, "  {field}{optional}: {};", typescript_type(property));
+    }
+    let _ = writeln!(out, "}}\n");
+}
+
+fn typescript_client(version: &str) -> String {
+    let tools = tool_specs("#/components/schemas/");
+    let mut out = format!(
+        "// Client of the axon-mcp {version} HTTP transport, generated by `axon-mcp gen-client --lang ts`.\n\
+         // Do not edit; generate it again after upgrading the server.\n\n"
+    );
+    typescript_interface(
+        &mut out,
+        "Task",
+        "A task as returned by the task tools",
+        &task_schema(),
+    );
+    for tool in &tools {
+        let name = format!("{}Params", pascal_case(&tool.name));
+        typescript_interface(
+            &mut out,
+            &name,
+            &format!("Params of {}", tool.name),
+            &tool.input,
+        );
+    }
+
+    out.push_str(TYPESCRIPT_RUNTIME);
+    for tool in &tools {
+        let params = format!("{}Params", pascal_case(&tool.name));
+        let default = match tool.input["required"]
+            .as_array()
+            .is_some_and(|r| !r.is_empty())
+        {
+            true => "",
+            false => " = {}",
+        };
+        let _ = writeln!(
+            out,
+            "\n  /** {} */\n  {}(params: {params}{default}): Promise<{}> {{\n    return this.call(\"{}\", params);\n  }}",
+            summary(&tool.description).replace("*/", "* /"),
+            camel_case(&tool.name),
+            typescript_type(&tool.output),
+            tool.name,
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+const TYPESCRIPT_RUNTIME: &str = r#"/** JSON-RPC error returned by the server */
+export class AxonError extends Error {
+  constructor(readonly code: number, message: string, readonly data?: unknown) {
+    super(message);
+    this.name = "AxonError";
+  }
+}
+
+export interface AxonClientOptions {
+  /** Sent as `Authorization: Bearer`, when the server requires a key */
+  apiKey?: string;
+  /** Agent the client acts for, sent as `X-Agent-Name` */
+  agentName?: string;
+  /** Token of the agent, sent as `X-Agent-Token` */
+  agentToken?: string;
+}
+
+/** Client of the /mcp endpoint; one method per tool */
+export class AxonClient {
+  private sessionId?: string;
+  private nextId = 1;
+
+  /** `baseUrl` is the server address, e.g. `http://127.0.0.1:3000` */
+  constructor(readonly baseUrl: string, private readonly options: AxonClientOptions = {}) {}
+
+  /** Open a session; needed when the server requires sessions */
+  async initialize(clientName = "axon-client"): Promise<unknown> {
+    return this.call("initialize", {
+      protocolVersion: "2025-06-18",
+      capabilities: {},
+      clientInfo: { name: clientName },
+    });
+  }
+
+  /** Call `method` with `params` and return its result */
+  async call<T>(method: string, params: unknown): Promise<T> {
+    const headers: Record<string, string> = { "Content-Type": "application/json" };
+    if (this.options.apiKey) headers["Authorization"] = `Bearer ${this.options.apiKey}`;
+    if (this.options.agentName) headers["X-Agent-Name"] = this.options.agentName;
+    if (this.options.agentToken) headers["X-Agent-Token"] = this.options.agentToken;
+    if (this.sessionId) headers["Mcp-Session-Id"] = this.sessionId;
+
+    const response = await fetch(`${this.baseUrl.replace(/\/$/, "")}/mcp`, {
+      method: "POST",
+      headers,
+      body: JSON.stringify({ jsonrpc: "2.0", id: this.nextId++, method, params }),
+    });
+    const sessionId = response.headers.get("Mcp-Session-Id");
+    if (sessionId) this.sessionId = sessionId;
+    if (!response.ok) {
+      throw new AxonError(response.status, `HTTP ${response.status}: ${await response.text()}`);
+    }
+    const body = await response.json();
+    if (body.error) throw new AxonError(body.error.code, body.error.message, body.error.data);
+    return body.result as T;
+  }
+"#;
+
+fn python_type(schema: &Value) -> String {
+    if schema["$ref"].is_string() {
+        return "Task".to_string();
+    }
+    if let Some(variants) = schema["oneOf"].as_array() {
+        return variants
+            .iter()
+            .map(python_type)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    let (types, nullable) = schema_types(schema);
+    if types.is_empty() && nullable {
+        return "None".to_string();
+    }
+    let mut rendered = match (types.as_slice(), schema["enum"].as_array()) {
+        (_, Some(values)) => format!(
+            "Literal[{}]",
+            values
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        (["string"], _) => "str".to_string(),
+        (["integer"], _) => "int".to_string(),
+        (["number"], _) => "float".to_string(),
+        (["boolean"], _) => "bool".to_string(),
+        (["array"], _) => format!("list[{}]", python_type(&schema["items"])),
+        (["object"], _) => "dict[str, Any]".to_string(),
+        _ => "Any".to_string(),
+    };
+    if nullable {
+        rendered.push_str(" | None");
+    }
+    rendered
+}
+
+fn python_typed_dict(out: &mut String, name: &str, description: &str, schema: &Value) {
+    let fields: Vec<(&String, String)> = properties(schema)
+        .into_iter()
+        .map(|(field, property, required)| {
+            let rendered = python_type(property);
+            match required {
+                true => (field, rendered),
+                false => (field, format!("NotRequired[{rendered}]")),
+            }
+        })
+        .collect();
+    let doc = summary(description).replace('\\', "\\\\").replace('"', "'");
+
+    // Fields named after keywords need the functional syntax
+    if fields
+        .iter()
+        .any(|(field, _)| PYTHON_KEYWORDS.contains(&field.as_str()))
+    {
+        let _ = writeln!(out, "# {doc}\n{name} = TypedDict(\"{name}\", {{");
+        for (field, rendered) in fields {
+            let _ = writeln!(out, "    \"{field}\": {rendered},");
+        }
+        let _ = writeln!(out, "}})\n\n");
+        return;
+    }
+    let _ = writeln!(out, "class {name}(TypedDict):\n    \"\"\"{doc}\"\"\"\n");
+    for (field, rendered) in fields {
+        let _ = writeln!(out, "    {field}: {rendered}");
+    }
+    let _ = writeln!(out, "\n");
+}
+
+fn python_client(version: &str) -> String {
+    let tools = tool_specs("#/components/schemas/");
+    let mut out = format!(
+        "\"\"\"Client of the axon-mcp {version} HTTP transport, generated by `axon-mcp gen-client --lang py`.\n\n\
+         Do not edit; generate it again after upgrading the server. Requires Python 3.11+.\n\"\"\"\n\n"
+    );
+    out.push_str(PYTHON_IMPORTS);
+    python_typed_dict(
+        &mut out,
+        "Task",
+        "A task as returned by the task tools",
+        &task_schema(),
+    );
+    for tool in &tools {
+        let name = format!("{}Params", pascal_case(&tool.name));
+        python_typed_dict(
+            &mut out,
+            &name,
+            &format!("Params of {}", tool.name),
+            &tool.input,
+        );
+    }
+
+    out.push_str(PYTHON_RUNTIME);
+    for tool in &tools {
+        let params = format!("{}Params", pascal_case(&tool.name));
+        let default = match tool.input["required"]
+            .as_array()
+            .is_some_and(|r| !r.is_empty())
+        {
+            true => String::new(),
+            false => " | None = None".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "\n    def {}(self, params: {params}{default}) -> {}:\n        \"\"\"{}\"\"\"\n        return self.call(\"{}\", params)",
+            tool.name,
+            python_type(&tool.output),
+            summary(&tool.description).replace('\\', "\\\\").replace('"', "'"),
+            tool.name,
+        );
+    }
+    out
+}
+
+const PYTHON_IMPORTS: &str = r#"import json
+import urllib.error
+import urllib.request
+from typing import Any, Literal, NotRequired, TypedDict
+
+
+"#;
+
+const PYTHON_RUNTIME: &str = r#"class AxonError(Exception):
+    """JSON-RPC error returned by the server"""
+
+    def __init__(self, code: int, message: str, data: Any = None) -> None:
+        super().__init__(message)
+        self.code = code
+        self.data = data
+
+
+class AxonClient:
+    """Client of the /mcp endpoint; one method per tool"""
+
+    def __init__(
+        self,
+        base_url: str,
+        *,
+        api_key: str | None = None,
+        agent_name: str | None = None,
+        agent_token: str | None = None,
+        timeout: float = 60.0,
+    ) -> None:
+        """`base_url` is the server address, e.g. `http://127.0.0.1:3000`"""
+        self.base_url = base_url.rstrip("/")
+        self.api_key = api_key
+        self.agent_name = agent_name
+        self.agent_token = agent_token
+        self.timeout = timeout
+        self.session_id: str | None = None
+        self._next_id = 1
+
+    def initialize(self, client_name: str = "axon-client") -> Any:
+        """Open a session; needed when the server requires sessions"""
+        return self.call(
+            "initialize",
+            {
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {"name": client_name},
+            },
+        )
+
+    def call(self, method: str, params: Any = None) -> Any:
+        """Call `method` with `params` and return its result"""
+        headers = {"Content-Type": "application/json"}
+        if self.api_key:
+            headers["Authorization"] = f"Bearer {self.api_key}"
+        if self.agent_name:
+            headers["X-Agent-Name"] = self.agent_name
+        if self.agent_token:
+            headers["X-Agent-Token"] = self.agent_token
+        if self.session_id:
+            headers["Mcp-Session-Id"] = self.session_id
+
+        body = {"jsonrpc": "2.0", "id": self._next_id, "method": method, "params": params or {}}
+        self._next_id += 1
+        request = urllib.request.Request(
+            f"{self.base_url}/mcp", data=json.dumps(body).encode(), headers=headers, method="POST"
+        )
+        try:
+            with urllib.request.urlopen(request, timeout=self.timeout) as response:
+                self.session_id = response.headers.get("Mcp-Session-Id") or self.session_id
+                reply = json.loads(response.read())
+        except urllib.error.HTTPError as e:
+            raise AxonError(e.code, f"HTTP {e.code}: {e.read().decode(errors='replace')}") from e
+        if reply.get("error"):
+            error = reply["error"]
+            raise AxonError(error["code"], error["message"], error.get("data"))
+        return reply.get("result")
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typescript_client() {
+        let source = generate_client(ClientLanguage::TypeScript, "1.0.0");
+        assert!(source.contains("export interface CreateTaskParams {\n"));
+        assert!(source.contains("  code: string;\n"));
+        assert!(source.contains("createTask(params: CreateTaskParams): Promise<Task>"));
+        assert!(source.contains("getTaskById(params: GetTaskByIdParams): Promise<Task | null>"));
+        assert!(source.contains("listTasks(params: ListTasksParams = {}): Promise<Array<Task>>"));
+        assert_eq!(
+            typescript_type(&json!({"type": ["string", "null"], "enum": ["a", "b"]})),
+            "\"a\" | \"b\" | null"
+        );
+    }
+
+    #[test]
+    fn test_python_client() {
+        let source = generate_client(ClientLanguage::Python, "1.0.0");
+        assert!(source.contains("class CreateTaskParams(TypedDict):\n"));
+        assert!(source.contains("    code: str\n"));
+        assert!(source.contains("def create_task(self, params: CreateTaskParams) -> Task:"));
+        assert!(source.contains(
+            "def list_tasks(self, params: ListTasksParams | None = None) -> list[Task]:"
+        ));
+        assert_eq!(
+            python_type(&json!({"type": "array", "items": {"type": "integer"}})),
+            "list[int]"
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Requests from the server to connected clients
+//!
+//! Some features ask the client rather than answer it: sampling asks its
+//! model for a completion, elicitation asks its user for input, roots asks
+//! for its workspace directories. Requests go out on the `GET /mcp` event
+//! stream of a session, like notifications but with an `id`; the client posts
+//! the JSON-RPC response back to `/mcp`, where it is matched to the waiting
+//! request. Only sessions that declared the capability a request needs on
+//! `initialize` are asked.
+
+use crate::notifications::NotificationHub;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use task_core::{Result, TaskError};
+use tokio::sync::oneshot;
+
+/// Client capabilities the server sends requests for
+const USED_CAPABILITIES: &[&str] = &["sampling", "elicitation", "roots"];
+
+/// Answer of a client to a request: its `result`, or its `error`
+type ClientAnswer = std::result::Result<Value, Value>;
+
+/// A request awaiting its answer, with the session it was sent to
+type PendingRequest = (String, oneshot::Sender<ClientAnswer>);
+
+/// Removes a request from the pending ones however its wait ends, including
+/// when the future awaiting it is dropped
+struct PendingGuard<'a> {
+    pending: &'a Mutex<HashMap<String, PendingRequest>>,
+    id: String,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Sends requests to clients and collects their answers
+#[derive(Debug, Clone)]
+pub struct ClientRequests {
+    notifications: NotificationHub,
+    /// Sessions with the capabilities they declared, oldest first
+    sessions: Arc<Mutex<Vec<(String, Value)>>>,
+    pending: Arc<Mutex<HashMap<String, PendingRequest>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ClientRequests {
+    /// Send requests through `notifications`
+    pub fn new(notifications: NotificationHub) -> Self {
+        Self {
+            notifications,
+            sessions: Arc::new(Mutex::new(Vec::new())),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Remember a session initialized with `capabilities`, if it can be asked
+    /// anything
+    pub fn session_opened(&self, session_id: &str, capabilities: Option<&Value>) {
+        let Some(capabilities) = capabilities else {
+            return;
+        };
+        if USED_CAPABILITIES
+            .iter()
+            .any(|name| capabilities.get(name).is_some_and(Value::is_object))
+        {
+            self.sessions
+                .lock()
+                .unwrap()
+                .push((session_id.to_string(), capabilities.clone()));
+        }
+    }
+
+    /// Forget a terminated session
+    pub fn session_closed(&self, session_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|(s, _)| s != session_id);
+    }
+
+    /// Whether the client of `session_id` declared `capability`
+    pub fn supports(&self, session_id: &str, capability: &str) -> bool {
+        self.sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(s, c)| s == session_id && c.get(capability).is_some_and(Value::is_object))
+    }
+
+    /// Send a request to the client of `session_id` and await its result
+    pub async fn request(
+        &self,
+        session_id: &str,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value> {
+        let id = format!("axon-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(id.clone(), (session_id.to_string(), sender));
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            id: id.clone(),
+        };
+        self.notifications.publish_message(
+            Some(session_id.to_string()),
+            json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}),
+        );
+
+        let answer = tokio::time::timeout(timeout, receiver).await;
+        match answer {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(TaskError::Protocol(format!(
+                "Client refused {method}: {}",
+                error["message"].as_str().unwrap_or("no reason given")
+            ))),
+            Ok(Err(_)) => Err(TaskError::Internal(format!("{method} request dropped"))),
+            Err(_) => {
+                // A client that stops answering is not asked again
+                self.session_closed(session_id);
+                Err(TaskError::Protocol(format!(
+                    "Client did not answer {method} within {} s",
+                    timeout.as_secs()
+                )))
+            }
+        }
+    }
+
+    /// Hand a JSON-RPC response posted on `session_id` to the request
+    /// awaiting it
+    ///
+    /// Returns `false` when no request with its id was sent to the session.
+    pub fn resolve(&self, session_id: Option<&str>, response: &Value) -> bool {
+        let Some(id) = response.get("id").and_then(Value::as_str) else {
+            return false;
+        };
+        let sender = {
+            let mut pending = self.pending.lock().unwrap();
+            match pending.get(id) {
+                Some((target, _)) if Some(target.as_str()) == session_id => pending.remove(id),
+                _ => None,
+            }
+        };
+        let Some((_, sender)) = sender else {
+            return false;
+        };
+        let answer = match response.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        sender.send(answer).is_ok()
+    }
+
+    /// Number of requests awaiting an answer
+    #[cfg(test)]
+    pub(crate) fn pending_requests(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_round_trip() {
+        let hub = NotificationHub::default();
+        let requests = ClientRequests::new(hub.clone());
+        requests.session_opened("plain", Some(&json!({"roots": {}})));
+        requests.session_opened("asking", Some(&json!({"elicitation": {}})));
+        assert!(!requests.supports("plain", "elicitation"));
+        assert!(requests.supports("asking", "elicitation"));
+        assert!(!requests.supports("asking", "sampling"));
+
+        let mut stream = hub.subscribe();
+        let client = requests.clone();
+        let answer = tokio::spawn(async move {
+            let request = stream.recv().await.unwrap();
+            assert!(request.is_visible_to(Some("asking")));
+            assert!(!request.is_visible_to(Some("plain")));
+            let response = json!({
+                "jsonrpc": "2.0",
+                "id": request.message["id"],
+                "result": {"action": "accept"}
+            });
+            assert!(!client.resolve(Some("plain"), &response));
+            assert!(client.resolve(Some("asking"), &response));
+        });
+
+        let result = requests
+            .request(
+                "asking",
+                "elicitation/create",
+                json!({}),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        answer.await.unwrap();
+        assert_eq!(result["action"], "accept");
+        let late = json!({"jsonrpc": "2.0", "id": "axon-1", "result": {}});
+        assert!(!requests.resolve(Some("asking"), &late));
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_request_times_out() {
+        let requests = ClientRequests::new(NotificationHub::default());
+        requests.session_opened("gone", Some(&json!({"sampling": {}})));
+        let timeout = Duration::from_millis(10);
+        assert!(requests
+            .request("gone", "sampling/createMessage", json!({}), timeout)
+            .await
+            .is_err());
+        assert!(!requests.supports("gone", "sampling"));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_request_is_forgotten() {
+        let requests = ClientRequests::new(NotificationHub::default());
+        requests.session_opened("slow", Some(&json!({"elicitation": {}})));
+        let request = requests.request(
+            "slow",
+            "elicitation/create",
+            json!({}),
+            Duration::from_secs(60),
+        );
+        // The caller gives up long before the request would time out
+        assert!(tokio::time::timeout(Duration::from_millis(10), request)
+            .await
+            .is_err());
+        assert_eq!(requests.pending_requests(), 0);
+        assert!(requests.supports("slow", "elicitation"));
+    }
+}
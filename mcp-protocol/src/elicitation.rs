@@ -0,0 +1,162 @@
+//! User confirmation of `confirmation_required` results (MCP elicitation)
+//!
+//! Some tools propose before they act: `create_task` when the task resembles
+//! open ones, `scan_code_todos` before creating tasks, `rebalance_work` before
+//! reassigning them. Left to the agent, the proposal is confirmed by whatever
+//! the model makes of the message. When the calling client declares the
+//! `elicitation` capability, the server asks its user instead with an
+//! `elicitation/create` request, and on acceptance resends the call with the
+//! tool's confirmation flag set.
+//!
+//! The prompt runs inside the HTTP request of the call, so it ends in time for
+//! the server's request timeout; an unanswered prompt leaves the confirmation
+//! to the agent.
+
+use crate::client_requests::ClientRequests;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use task_core::{Result, TaskError};
+
+/// How long the user may take to answer
+pub const DEFAULT_ELICITATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Most of the remaining request time kept for resending the confirmed call
+pub const CONFIRMED_CALL_RESERVE: Duration = Duration::from_secs(5);
+
+/// How long the user may take to answer in a request limited to
+/// `request_timeout`
+///
+/// A quarter of the time the request has left, at most
+/// [`CONFIRMED_CALL_RESERVE`], is kept for resending the confirmed call.
+/// `None` when no time is left to ask.
+pub fn elicitation_timeout(
+    request_timeout: Option<Duration>,
+    elapsed: Duration,
+) -> Option<Duration> {
+    let Some(request_timeout) = request_timeout else {
+        return Some(DEFAULT_ELICITATION_TIMEOUT);
+    };
+    let remaining = request_timeout.saturating_sub(elapsed);
+    let wait = remaining - (remaining / 4).min(CONFIRMED_CALL_RESERVE);
+    (!wait.is_zero()).then(|| wait.min(DEFAULT_ELICITATION_TIMEOUT))
+}
+
+/// Tools answering `confirmation_required`, with the param confirming the
+/// proposal
+const CONFIRMATION_FLAGS: &[(&str, &str)] = &[
+    ("create_task", "confirm_duplicate"),
+    ("scan_code_todos", "confirm"),
+    ("rebalance_work", "confirm"),
+];
+
+/// Param confirming the proposals of `method`, if it makes any
+pub fn confirmation_flag(method: &str) -> Option<&'static str> {
+    CONFIRMATION_FLAGS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, flag)| *flag)
+}
+
+/// Whether a JSON-RPC response asks for confirmation
+pub fn needs_confirmation(response: &Value) -> bool {
+    response["result"]["status"] == "confirmation_required"
+}
+
+/// How the user answered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitationAction {
+    /// Submitted the form
+    Accept,
+    /// Explicitly refused
+    Decline,
+    /// Dismissed the prompt
+    Cancel,
+}
+
+/// Ask the user of `session_id` to confirm `message`; returns their answer and
+/// whether they confirmed
+pub async fn confirm_with_user(
+    requests: &ClientRequests,
+    session_id: &str,
+    message: &str,
+    timeout: Duration,
+) -> Result<(ElicitationAction, bool)> {
+    let params = json!({
+        "message": message,
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "confirm": {
+                    "type": "boolean",
+                    "title": "Proceed",
+                    "description": "Carry out the proposed change"
+                }
+            },
+            "required": ["confirm"]
+        }
+    });
+    let result = requests
+        .request(session_id, "elicitation/create", params, timeout)
+        .await?;
+    let action: ElicitationAction = serde_json::from_value(result["action"].clone())
+        .map_err(|e| TaskError::Protocol(format!("Invalid elicitation answer: {e}")))?;
+    let confirmed = action == ElicitationAction::Accept && result["content"]["confirm"] == true;
+    Ok((action, confirmed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationHub;
+
+    #[tokio::test]
+    async fn test_confirm_with_user() {
+        assert_eq!(confirmation_flag("scan_code_todos"), Some("confirm"));
+        assert_eq!(confirmation_flag("list_tasks"), None);
+        assert!(needs_confirmation(
+            &json!({"result": {"status": "confirmation_required"}})
+        ));
+
+        let hub = NotificationHub::default();
+        let requests = ClientRequests::new(hub.clone());
+        requests.session_opened("user", Some(&json!({"elicitation": {}})));
+        let mut stream = hub.subscribe();
+        let client = requests.clone();
+        tokio::spawn(async move {
+            for content in [json!({"confirm": true}), json!({"confirm": false})] {
+                let sent = stream.recv().await.unwrap();
+                assert_eq!(sent.message["method"], "elicitation/create");
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": sent.message["id"],
+                    "result": {"action": "accept", "content": content}
+                });
+                client.resolve(Some("user"), &response);
+            }
+        });
+
+        let timeout = Duration::from_secs(5);
+        let answer = confirm_with_user(&requests, "user", "Create 3 tasks?", timeout).await;
+        assert_eq!(answer.unwrap(), (ElicitationAction::Accept, true));
+        let answer = confirm_with_user(&requests, "user", "Create 3 tasks?", timeout).await;
+        assert_eq!(answer.unwrap(), (ElicitationAction::Accept, false));
+
+        let secs = Duration::from_secs;
+        assert_eq!(
+            elicitation_timeout(None, secs(10)),
+            Some(DEFAULT_ELICITATION_TIMEOUT)
+        );
+        assert_eq!(elicitation_timeout(Some(secs(30)), secs(2)), Some(secs(23)));
+        assert_eq!(
+            elicitation_timeout(Some(secs(8)), Duration::ZERO),
+            Some(secs(6))
+        );
+        assert_eq!(
+            elicitation_timeout(Some(secs(3600)), Duration::ZERO),
+            Some(DEFAULT_ELICITATION_TIMEOUT)
+        );
+        assert_eq!(elicitation_timeout(Some(secs(30)), secs(31)), None);
+    }
+}
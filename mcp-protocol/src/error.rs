@@ -2,7 +2,7 @@
 //!
 //! Maps internal task errors to MCP-compliant JSON-RPC error codes.
 
-use ::task_core::TaskError;
+use ::task_core::{TaskError, UnmetRequirement};
 use serde_json::{json, Value};
 use thiserror::Error;
 
@@ -29,6 +29,25 @@ pub enum McpError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Throttled: {message}")]
+    Throttled {
+        message: String,
+        retry_after_secs: i64,
+    },
+
+    #[error("Unmet requirements: {message}")]
+    UnmetRequirements {
+        message: String,
+        task_code: String,
+        unmet: Vec<UnmetRequirement>,
+    },
 }
 
 impl McpError {
@@ -42,11 +61,46 @@ impl McpError {
             McpError::Database(_) => -32005,
             McpError::Protocol(_) => -32006,
             McpError::Serialization(_) => -32007,
+            McpError::Timeout(_) => -32008,
+            McpError::Forbidden(_) => -32009,
+            McpError::UnmetRequirements { .. } => -32010,
+            McpError::Throttled { .. } => -32011,
         }
     }
 
     /// Convert to JSON-RPC error response
     pub fn to_json_rpc_error(&self, id: Option<Value>) -> Value {
+        if let McpError::UnmetRequirements {
+            task_code, unmet, ..
+        } = self
+        {
+            return json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": self.to_error_code(),
+                    "message": self.to_string(),
+                    "data": {
+                        "task_code": task_code,
+                        "unmet_requirements": unmet
+                    }
+                },
+                "id": id
+            });
+        }
+        if let McpError::Throttled {
+            retry_after_secs, ..
+        } = self
+        {
+            return json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": self.to_error_code(),
+                    "message": self.to_string(),
+                    "data": {"retry_after_secs": retry_after_secs}
+                },
+                "id": id
+            });
+        }
         json!({
             "jsonrpc": "2.0",
             "error": {
@@ -104,6 +158,11 @@ impl From<TaskError> for McpError {
             TaskError::UnsupportedOperation(op) => {
                 McpError::Validation(format!("Unsupported operation: {op}"))
             }
+            TaskError::UnmetRequirements(ref task_code, ref unmet) => McpError::UnmetRequirements {
+                message: err.to_string(),
+                task_code: task_code.clone(),
+                unmet: unmet.clone(),
+            },
         }
     }
 }
@@ -159,6 +218,36 @@ mod tests {
             McpError::Protocol("bad request".into()).to_error_code(),
             -32006
         );
+        assert_eq!(McpError::Timeout("slow".into()).to_error_code(), -32008);
+        assert_eq!(McpError::Forbidden("admin".into()).to_error_code(), -32009);
+
+        let throttled = McpError::Throttled {
+            message: "slow down".into(),
+            retry_after_secs: 30,
+        };
+        let json_error = throttled.to_json_rpc_error(Some(json!(1)));
+        assert_eq!(json_error["error"]["code"], -32011);
+        assert_eq!(json_error["error"]["data"]["retry_after_secs"], 30);
+    }
+
+    #[test]
+    fn test_unmet_requirements_data() {
+        use ::task_core::DoneRequirement;
+
+        let error = McpError::from(TaskError::UnmetRequirements(
+            "FE-1".to_string(),
+            vec![UnmetRequirement {
+                requirement: DoneRequirement::Approval,
+                detail: "no 'approval' message from an agent other than the owner".to_string(),
+            }],
+        ));
+        let json_error = error.to_json_rpc_error(Some(json!(1)));
+        assert_eq!(json_error["error"]["code"], -32010);
+        assert_eq!(json_error["error"]["data"]["task_code"], "FE-1");
+        assert_eq!(
+            json_error["error"]["data"]["unmet_requirements"][0]["requirement"],
+            "approval"
+        );
     }
 
     #[test]
@@ -2,41 +2,156 @@
 //!
 //! Implements the ProtocolHandler trait for MCP communication.
 
+use crate::roots::is_within;
 use crate::serialization::*;
 use ::task_core::error::Result;
+use ::task_core::workspace_setup::ResponseStatus;
+use ::task_core::MessageCatalog;
 use ::task_core::TaskError;
+use ::task_core::{
+    apply_claim_affinity, AgentPurgeReport, AggregateTasksParams, AppendSessionLogParams,
+    ApplyTaskOperationsParams, AuditLogExport, ClaimLeasePolicy, CountTasksParams,
+    ExplainTaskRoutingParams, ExportAuditLogParams, GetSessionLogParams, GetTaskByUidParams,
+    GetTasksByCodesParams, GetTasksByIdsParams, HealthStatus, MaintainDatabaseParams,
+    MaintenanceReport, MessageEffect, MessageEffectRule, MigrationStatus, NewTask, ProtocolHandler,
+    PurgeAgentDataParams, QueryResult, RetentionPolicy, RetentionReport, RouteAction, RoutingAgent,
+    RoutingDecision, RoutingRules, RunReadonlyQueryParams, SessionLog, SessionLogEntry, Task,
+    TaskAggregate, TaskBatch, TaskClaim, TaskCount, TaskMessage, TaskMessageRepository,
+    TaskOperation, TaskOperationOutcome, TaskOperationParams, TaskRepository, TaskValidator,
+    WorkspaceContextRepository, ROUTING_AUTHOR, ROUTING_SUGGESTION_MESSAGE_TYPE,
+};
+use ::task_core::{
+    artifact_status, file_checksum, normalize_checksum, resolve_artifact_path,
+    validate_artifact_kind, ArtifactFilter, ListArtifactsParams, ListedArtifact,
+    RegisterArtifactParams, TaskArtifact,
+};
+use ::task_core::{
+    check_done_policy, DonePolicy, DuplicateCandidate, DuplicateCheckPolicy,
+    FindDuplicateTasksParams,
+};
+use ::task_core::{
+    discovery_candidate_limit, DiscoveryAgent, DiscoveryWorkload, WorkDiscoveryPolicy,
+};
+use ::task_core::{http_server_entry, ClaudeCodeRegistration, RegisterWithClaudeCodeParams};
+use ::task_core::{
+    index_embeddings, EmbeddingProvider, SemanticMatch, SemanticSearchFilter, SemanticSearchParams,
+    DEFAULT_SEMANTIC_SEARCH_RESULTS, EMBEDDING_BATCH_SIZE, MAX_SEMANTIC_SEARCH_RESULTS,
+};
+use ::task_core::{
+    is_project_complete, latest_final_report, write_final_report, EventSeverity, FinalReport,
+    GetFinalReportParams, SystemEventFilter, PROJECT_COMPLETED_EVENT, REPORTS_DIR,
+};
+use ::task_core::{
+    performance_report, record_anomalies, AnomalyThresholds, GetAgentPerformanceParams,
+    GetSystemEventsParams, PerformanceReport, SystemEvent, DEFAULT_PERFORMANCE_BUCKET_DAYS,
+    DEFAULT_PERFORMANCE_PERIOD_DAYS,
+};
+use ::task_core::{
+    render_subagent_file, subagent_name, SetupWizardParams, SetupWizardProgress, SetupWizardState,
+    WizardFile, WizardStep, AGENTS_DIR, MAIN_AI_FILE, MANIFEST_FILE,
+};
+use ::task_core::{rollback_setup_files, RollbackSetupParams, SetupRollback};
+use ::task_core::{
+    scan_code_todos, CodeTodoImport, ProposedTodoTask, ScanCodeTodosParams, MAX_BATCH_LOOKUP,
+    MAX_TASK_OPERATIONS,
+};
+use ::task_core::{
+    stage_code, stage_number, CreatePipelineParams, Pipeline, PipelineTemplates,
+    DEFAULT_PIPELINE_TEMPLATE, PIPELINE_AUTHOR, STAGE_READY_MESSAGE_TYPE,
+};
+use ::task_core::{
+    summarize_thread, ExtractiveSummarizer, TaskThread, ThreadSummarizer, ThreadSummaryPolicy,
+};
+use ::task_core::{sync_tasks_markdown, SyncTasksMarkdownParams, TasksMarkdownSync};
+use ::task_core::{
+    validate_capability, Capability, CapabilityCatalog, DefineCapabilityParams,
+    DeprecateCapabilityParams, ListCapabilitiesParams,
+};
+use ::task_core::{verify_workspace, VerifyWorkspaceParams, WorkspaceVerification};
+use ::task_core::{
+    AddTaskCommentParams, DeleteTaskCommentParams, EditTaskCommentParams,
+    GetTaskCommentHistoryParams, GetTaskCommentsParams, TaskComment, TaskCommentRevision,
+};
 use ::task_core::{
     AgentRegistration, AgenticWorkflowDescription, CreateMainAiFileParams,
     GetAgenticWorkflowDescriptionParams, GetInstructionsForMainAiFileParams,
-    GetSetupInstructionsParams, MainAiFileData, MainAiFileInstructions,
-    RegisterAgentParams, SetupInstructions, WorkspaceSetupService,
+    GetSetupInstructionsParams, MainAiFileData, MainAiFileInstructions, PromptRegeneration,
+    RegenerateAgentPromptParams, RegisterAgentParams, SetupInstructions, WorkspaceSetupConfig,
+    WorkspaceSetupService,
+};
+use ::task_core::{
+    AgentUpdates, CreateTaskMessageParams, GetMessageTemplatesParams, GetMyMentionsParams,
+    GetMyWorkParams, GetTaskMessagesParams, GetUpdatesSinceParams, MessageTemplate,
+    MessageTemplateRegistry, MyWork, TaskMention, WorkDeadline, DEFAULT_DEADLINE_WINDOW_MINUTES,
+    DEFAULT_MY_WORK_LIMIT,
+};
+use ::task_core::{
+    AuditLogFilter, AuditSource, DiffWorkspaceParams, GetBoardAtParams, TaskBoard, WorkspaceDiff,
+};
+use ::task_core::{
+    CapabilityMatcher, ClaimTaskParams, CleanupTimedOutTasksParams, CriticalPath,
+    DiscoverWorkParams, EndWorkSessionParams, ExportTimelineParams, GetCriticalPathParams,
+    MatchAgentsToTaskParams, ReleaseTaskParams, StartWorkSessionParams, TaskAgentMatches,
+    TaskFilter, TaskState, Timeline, WorkSessionFilter, WorkSessionInfo, WorkSessionPolicy,
+    DEFAULT_EFFORT_MINUTES,
 };
+use ::task_core::{CsvExport, ExportSessionsCsvParams, ExportTasksCsvParams, SessionCsvColumn};
 use ::task_core::{
-    ClaimTaskParams, CleanupTimedOutTasksParams, DiscoverWorkParams, EndWorkSessionParams, 
-    ReleaseTaskParams, StartWorkSessionParams, WorkSessionInfo,
+    DependencyChain, GetTaskContextParams, TaskContext, TokenBudgets, MIN_CONTEXT_TOKEN_BUDGET,
 };
-use ::task_core::{CreateTaskMessageParams, GetTaskMessagesParams};
+use ::task_core::{GetSetupStatusParams, SetupStatus};
+use ::task_core::{GetToolUsageStatsParams, ToolUsageFilter, ToolUsageStat, MAX_TOOL_USAGE_STATS};
+use ::task_core::{MergeWorkspaceParams, WorkspaceMergeReport};
 use ::task_core::{
-    HealthStatus, NewTask, ProtocolHandler, Task, TaskMessage, TaskMessageRepository,
-    TaskRepository, WorkspaceContextRepository,
+    Reassignment, RebalanceAgent, RebalancePolicy, RebalanceWorkParams, WorkRebalance,
 };
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
 
 // Maximum attempts for get-or-modify loops to handle race conditions
 const MAX_ATTEMPTS: u8 = 5;
 
+/// State changes buffered for slow subscribers before they start missing some
+pub const STATE_CHANGE_CAPACITY: usize = 256;
+
+// Records embedded before a semantic search; the rest is left to the indexing job
+const SEARCH_INDEX_LIMIT: u32 = 500;
+
+// Most recent messages and most similar records of other tasks in a task context
+const CONTEXT_MESSAGE_LIMIT: u32 = 50;
+const CONTEXT_RELATED_LIMIT: usize = 5;
+
 /// MCP Task Handler that bridges MCP protocol with TaskRepository, TaskMessageRepository, and WorkspaceContextRepository
 #[derive(Clone)]
-pub struct McpTaskHandler<R, M, W> {
+pub struct McpTaskHandler<R: ?Sized, M, W> {
     repository: Arc<R>,
     message_repository: Arc<M>,
     workspace_context_repository: Arc<W>,
     workspace_setup_service: WorkspaceSetupService,
+    message_templates: MessageTemplateRegistry,
+    message_effects: Vec<MessageEffectRule>,
+    retention_policy: RetentionPolicy,
+    claim_lease_policy: ClaimLeasePolicy,
+    work_session_policy: WorkSessionPolicy,
+    rebalance_policy: RebalancePolicy,
+    anomaly_thresholds: AnomalyThresholds,
+    embedding_provider: Option<Arc<dyn EmbeddingProvider>>,
+    discovery_policy: Option<Arc<dyn WorkDiscoveryPolicy>>,
+    duplicate_check_policy: DuplicateCheckPolicy,
+    thread_summary_policy: ThreadSummaryPolicy,
+    thread_summarizer: Arc<dyn ThreadSummarizer>,
+    token_budgets: TokenBudgets,
+    routing_rules: Arc<RoutingRules>,
+    done_policy: Arc<DonePolicy>,
+    pipeline_templates: Arc<PipelineTemplates>,
+    state_changes: broadcast::Sender<Task>,
     _project_root: Option<std::path::PathBuf>,
 }
 
-impl<R, M, W> McpTaskHandler<R, M, W> {
+impl<R: ?Sized, M, W> McpTaskHandler<R, M, W> {
     /// Create new MCP task handler
     pub fn new(
         repository: Arc<R>,
@@ -48,8 +163,28 @@ impl<R, M, W> McpTaskHandler<R, M, W> {
             repository,
             message_repository,
             workspace_context_repository: workspace_context_repository.clone(),
-            workspace_setup_service: WorkspaceSetupService::new(),
-            _project_root: _project_root,
+            workspace_setup_service: WorkspaceSetupService::with_config(WorkspaceSetupConfig {
+                project_root: _project_root.clone(),
+                ..WorkspaceSetupConfig::default()
+            }),
+            message_templates: MessageTemplateRegistry::new(),
+            message_effects: Vec::new(),
+            retention_policy: RetentionPolicy::default(),
+            claim_lease_policy: ClaimLeasePolicy::default(),
+            work_session_policy: WorkSessionPolicy::default(),
+            rebalance_policy: RebalancePolicy::default(),
+            anomaly_thresholds: AnomalyThresholds::default(),
+            embedding_provider: None,
+            discovery_policy: None,
+            duplicate_check_policy: DuplicateCheckPolicy::default(),
+            thread_summary_policy: ThreadSummaryPolicy::default(),
+            thread_summarizer: Arc::new(ExtractiveSummarizer::default()),
+            token_budgets: TokenBudgets::default(),
+            routing_rules: Arc::new(RoutingRules::default()),
+            done_policy: Arc::new(DonePolicy::default()),
+            pipeline_templates: Arc::new(PipelineTemplates::default()),
+            state_changes: broadcast::channel(STATE_CHANGE_CAPACITY).0,
+            _project_root,
         }
     }
 
@@ -62,182 +197,2327 @@ impl<R, M, W> McpTaskHandler<R, M, W> {
     pub fn message_repository(&self) -> Arc<M> {
         self.message_repository.clone()
     }
-}
 
-#[async_trait]
-impl<
-        R: TaskRepository + Send + Sync,
-        M: TaskMessageRepository + Send + Sync,
-        W: WorkspaceContextRepository + Send + Sync,
-    > ProtocolHandler for McpTaskHandler<R, M, W>
-{
-    async fn create_task(&self, params: CreateTaskParams) -> Result<Task> {
-        let new_task = NewTask::new(
-            params.code,
-            params.name,
-            params.description,
-            params.owner_agent_name,
-        );
+    /// Apply task changes when messages of the configured types are posted
+    pub fn with_message_effects(mut self, message_effects: Vec<MessageEffectRule>) -> Self {
+        self.message_effects = message_effects;
+        self
+    }
 
-        self.repository.create(new_task).await
+    /// Retention policy reported by `get_retention_report`
+    pub fn with_retention_policy(mut self, retention_policy: RetentionPolicy) -> Self {
+        self.retention_policy = retention_policy;
+        self
     }
 
-    async fn update_task(&self, params: UpdateTaskParams) -> Result<Task> {
-        self.repository
-            .update(params.id, params.into_update_data())
-            .await
+    /// Lease given to claims and the longest lease agents may request
+    pub fn with_claim_lease_policy(mut self, claim_lease_policy: ClaimLeasePolicy) -> Self {
+        self.claim_lease_policy = claim_lease_policy;
+        self
     }
 
-    async fn set_task_state(&self, params: SetStateParams) -> Result<Task> {
-        self.repository.set_state(params.id, params.state).await
+    /// Limits on concurrent sessions enforced by `start_work_session`
+    pub fn with_work_session_policy(mut self, work_session_policy: WorkSessionPolicy) -> Self {
+        self.work_session_policy = work_session_policy;
+        self
     }
 
-    async fn get_task_by_id(&self, params: GetTaskByIdParams) -> Result<Option<Task>> {
-        self.repository.get_by_id(params.id).await
+    /// When `rebalance_work` counts agents as overloaded, and how much work it moves
+    pub fn with_rebalance_policy(mut self, rebalance_policy: RebalancePolicy) -> Self {
+        self.rebalance_policy = rebalance_policy;
+        self
     }
 
-    async fn get_task_by_code(&self, params: GetTaskByCodeParams) -> Result<Option<Task>> {
-        self.repository.get_by_code(&params.code).await
+    /// When `get_agent_performance` flags agent performance as anomalous
+    pub fn with_anomaly_thresholds(mut self, anomaly_thresholds: AnomalyThresholds) -> Self {
+        self.anomaly_thresholds = anomaly_thresholds;
+        self
     }
 
-    async fn list_tasks(&self, params: ListTasksParams) -> Result<Vec<Task>> {
-        let filter = params.to_task_filter()?;
+    /// Language and overrides of workspace setup response messages
+    pub fn with_message_catalog(mut self, messages: MessageCatalog) -> Self {
+        self.workspace_setup_service = self.workspace_setup_service.with_messages(messages);
+        self
+    }
 
-        // Pagination is now handled at the database level for performance
-        self.repository.list(filter).await
+    /// Token budgets of generated files and prompts and of default task context bundles
+    pub fn with_token_budgets(mut self, token_budgets: TokenBudgets) -> Self {
+        self.token_budgets = token_budgets;
+        self.workspace_setup_service = self
+            .workspace_setup_service
+            .with_token_budgets(token_budgets);
+        self
     }
 
-    async fn assign_task(&self, params: AssignTaskParams) -> Result<Task> {
-        self.repository.assign(params.id, &params.new_owner).await
+    /// Provider embedding records and queries for `semantic_search`
+    pub fn with_embedding_provider(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedding_provider = Some(provider);
+        self
     }
 
-    async fn archive_task(&self, params: ArchiveTaskParams) -> Result<Task> {
-        self.repository.archive(params.id).await
+    /// Policy filtering and reordering the tasks `discover_work` offers
+    pub fn with_discovery_policy(mut self, policy: Arc<dyn WorkDiscoveryPolicy>) -> Self {
+        self.discovery_policy = Some(policy);
+        self
     }
 
-    async fn health_check(&self) -> Result<HealthStatus> {
-        self.repository.health_check().await?;
+    /// How new tasks are checked against open tasks by `find_duplicate_tasks`
+    pub fn with_duplicate_check_policy(mut self, policy: DuplicateCheckPolicy) -> Self {
+        self.duplicate_check_policy = policy;
+        self
+    }
 
-        let health = HealthStatus {
-            status: "healthy".to_string(),
-            database: true,
-            protocol: true,
-            timestamp: chrono::Utc::now(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
+    /// Policy applied when tasks are created
+    pub fn duplicate_check_policy(&self) -> DuplicateCheckPolicy {
+        self.duplicate_check_policy
+    }
+
+    /// Rules routing tasks to agents when they are created or released
+    pub fn with_routing_rules(mut self, rules: RoutingRules) -> Self {
+        self.routing_rules = Arc::new(rules);
+        self
+    }
+
+    /// Requirements a task must meet before it may move to `Done`
+    pub fn with_done_policy(mut self, policy: DonePolicy) -> Self {
+        self.done_policy = Arc::new(policy);
+        self
+    }
+
+    /// Templates `create_pipeline` materializes stage tasks from
+    pub fn with_pipeline_templates(mut self, templates: PipelineTemplates) -> Self {
+        self.pipeline_templates = Arc::new(templates);
+        self
+    }
+
+    /// Announce tasks moved to a new state on `sender` instead of a channel of its own
+    pub fn with_state_changes(mut self, sender: broadcast::Sender<Task>) -> Self {
+        self.state_changes = sender;
+        self
+    }
+
+    /// Follow the tasks moved to a new state, whichever call or follow-up moved them
+    pub fn subscribe_state_changes(&self) -> broadcast::Receiver<Task> {
+        self.state_changes.subscribe()
+    }
+
+    /// When long threads are summarized, and the summarizer condensing them
+    pub fn with_thread_summaries(
+        mut self,
+        policy: ThreadSummaryPolicy,
+        summarizer: Arc<dyn ThreadSummarizer>,
+    ) -> Self {
+        self.thread_summary_policy = policy;
+        self.thread_summarizer = summarizer;
+        self
+    }
+}
+
+impl<R: TaskRepository + ?Sized, M, W> McpTaskHandler<R, M, W> {
+    /// Load the capability catalog; repositories without one yield an empty catalog
+    async fn capability_catalog(&self) -> Result<CapabilityCatalog> {
+        match self.repository.list_capabilities().await {
+            Ok(capabilities) => Ok(CapabilityCatalog::new(capabilities)),
+            Err(TaskError::UnsupportedOperation(_)) => Ok(CapabilityCatalog::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Map free-text capabilities onto the catalog's canonical names
+    async fn normalize_capabilities(&self, capabilities: &[String]) -> Result<Vec<String>> {
+        if capabilities.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self.capability_catalog().await?.normalize_all(capabilities))
+    }
+
+    /// Timed-out claims whose previous owner still has claim affinity
+    async fn claim_affinities(&self) -> Result<Vec<TaskClaim>> {
+        let Some(since) = self.claim_lease_policy.affinity_cutoff(chrono::Utc::now()) else {
+            return Ok(Vec::new());
         };
+        match self.repository.timed_out_claims(since).await {
+            Ok(claims) => Ok(claims),
+            Err(TaskError::UnsupportedOperation(_)) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
 
-        Ok(health)
+    /// Discovered tasks with those reserved for `agent_name` first, including ones
+    /// discovery left out, and those reserved for others dropped
+    async fn with_claim_affinity(
+        &self,
+        mut tasks: Vec<Task>,
+        affinities: &[TaskClaim],
+        agent_name: &str,
+        max_tasks: u32,
+    ) -> Result<Vec<Task>> {
+        for claim in affinities
+            .iter()
+            .filter(|claim| claim.agent_name == agent_name)
+        {
+            if tasks.iter().any(|task| task.id == claim.task_id) {
+                continue;
+            }
+            let task = self.repository.get_by_id(claim.task_id).await?;
+            tasks.extend(task.filter(|task| task.state == TaskState::Created));
+        }
+        let mut tasks = apply_claim_affinity(tasks, affinities, agent_name);
+        tasks.truncate(max_tasks as usize);
+        Ok(tasks)
     }
 
-    // MCP v2 Advanced Multi-Agent Features
+    /// PRD of the workspace: the stored content, else `docs/PRD.md` in the project root
+    ///
+    /// Missing or unparsable PRDs yield `None`.
+    fn workspace_prd(
+        &self,
+        context: &::task_core::WorkspaceContext,
+    ) -> Option<::task_core::PrdDocument> {
+        let content = match &context.prd_content {
+            Some(content) => content.clone(),
+            None => {
+                let root = self._project_root.as_ref()?;
+                std::fs::read_to_string(root.join("docs").join("PRD.md")).ok()?
+            }
+        };
+        ::task_core::PrdDocument::from_content(&content).ok()
+    }
 
-    async fn discover_work(&self, params: DiscoverWorkParams) -> Result<Vec<Task>> {
-        let max_tasks = params.max_tasks.unwrap_or(10); // Default to 10 tasks if not specified
-        self.repository
-            .discover_work(&params.agent_name, &params.capabilities, max_tasks)
-            .await
+    /// Project root the server was started with
+    pub fn project_root(&self) -> Option<&std::path::Path> {
+        self._project_root.as_deref()
     }
 
-    async fn claim_task(&self, params: ClaimTaskParams) -> Result<Task> {
-        // Validate agent name format at protocol layer
-        if params.agent_name.trim().is_empty() {
-            return Err(::task_core::TaskError::Validation(
-                "Agent name cannot be empty".to_string(),
+    /// Project root named by a request, else the server's project root
+    ///
+    /// The server passes the session's workspace root; with a configured project
+    /// root, any directory outside it is rejected.
+    fn workspace_root(&self, project_root: Option<&str>) -> Result<PathBuf> {
+        let root = match (project_root, &self._project_root) {
+            (Some(root), _) => PathBuf::from(root),
+            (None, Some(root)) => root.clone(),
+            (None, None) => {
+                return Err(TaskError::Validation(
+                    "project_root is required when the server has no project root".to_string(),
+                ))
+            }
+        };
+        if !root.is_dir() {
+            return Err(TaskError::Validation(format!(
+                "project_root is not a directory: {}",
+                root.display()
+            )));
+        }
+        if let Some(configured) = &self._project_root {
+            if !is_within(&root, configured) {
+                return Err(TaskError::Validation(format!(
+                    "project_root {} is outside the server's project root {}",
+                    root.display(),
+                    configured.display()
+                )));
+            }
+        }
+        Ok(root)
+    }
+
+    /// Validate task creation parameters and normalize their capabilities
+    ///
+    /// Every invalid field is reported at once.
+    async fn new_task(&self, params: CreateTaskParams) -> Result<NewTask> {
+        let required_capabilities = self
+            .normalize_capabilities(&params.required_capabilities)
+            .await?;
+        let mut builder = NewTask::builder()
+            .code(params.code)
+            .name(params.name)
+            .description(params.description)
+            .priority(params.priority_score)
+            .confidence_threshold(params.confidence_threshold)
+            .required_capabilities(required_capabilities);
+        if let Some(owner) = params.owner_agent_name {
+            builder = builder.owner(owner);
+        }
+        if let Some(parent_task_id) = params.parent_task_id {
+            builder = builder.parent(parent_task_id);
+        }
+        if let Some(minutes) = params.estimated_effort {
+            builder = builder.estimated_effort(minutes);
+        }
+        if let Some(workflow_definition_id) = params.workflow_definition_id {
+            builder = builder.workflow(workflow_definition_id);
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Whether a message changes its task through an effect only the task's
+    /// owner may cause
+    ///
+    /// Accepting a handoff moves the task to the message's author and is
+    /// checked when the effect is applied.
+    pub fn message_changes_task(&self, params: &CreateTaskMessageParams) -> bool {
+        let message_type = match &params.template {
+            Some(name) if params.message_type.is_empty() => {
+                match self.message_templates.get(name) {
+                    Ok(template) => template.message_type.clone(),
+                    Err(_) => return false,
+                }
+            }
+            _ => params.message_type.clone(),
+        };
+        self.message_effects.iter().any(|rule| {
+            rule.message_type == message_type && rule.effect != MessageEffect::AssignToAuthor
+        })
+    }
+
+    /// Validate message parameters and resolve their type and content,
+    /// rendering the template when one is named
+    fn render_message(&self, params: &CreateTaskMessageParams) -> Result<(String, String)> {
+        let (message_type, content) = match &params.template {
+            Some(name) => {
+                let template = self.message_templates.get(name)?;
+                let message_type = if params.message_type.is_empty() {
+                    template.message_type.clone()
+                } else {
+                    params.message_type.clone()
+                };
+                (message_type, template.render(&params.template_values)?)
+            }
+            None => (params.message_type.clone(), params.content.clone()),
+        };
+        if message_type.trim().is_empty() {
+            return Err(TaskError::Validation(
+                "message_type is required when no template is used".to_string(),
             ));
         }
+        TaskValidator::validate_agent_name(&params.author_agent_name)?;
+        if let Some(target) = &params.target_agent_name {
+            TaskValidator::validate_agent_name(target)?;
+        }
+        Ok((message_type, content))
+    }
+
+    /// Reject a new session on `task_id` that would break the work session policy
+    ///
+    /// Repositories that cannot list sessions are not checked.
+    async fn check_work_session_policy(&self, task_id: i32, agent_name: &str) -> Result<()> {
+        let mut active = Vec::new();
+        for filter in [
+            WorkSessionFilter {
+                task_id: Some(task_id),
+                active_only: true,
+                ..WorkSessionFilter::default()
+            },
+            WorkSessionFilter {
+                agent_name: Some(agent_name.to_string()),
+                active_only: true,
+                ..WorkSessionFilter::default()
+            },
+        ] {
+            match self.repository.list_work_sessions(&filter).await {
+                Ok(sessions) => active.extend(sessions),
+                Err(TaskError::UnsupportedOperation(_)) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+        active.sort_by_key(|s| s.id);
+        active.dedup_by_key(|s| s.id);
+        self.work_session_policy.check(task_id, agent_name, &active)
+    }
+
+    /// Record the completion of the project once every task is done
+    ///
+    /// Runs after tasks move to `Done`. The task change is already stored, so
+    /// failures are logged instead of failing the request.
+    async fn check_project_completion(&self) {
+        if let Err(e) = self.complete_project().await {
+            warn!("Project completion not recorded: {}", e);
+        }
+    }
 
-        // Validate agent name format (kebab-case)
-        if !params
-            .agent_name
-            .chars()
-            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    /// Write the final report and record a `project_completed` event
+    ///
+    /// The report lands in the server's project root, when it has one. A
+    /// completion already recorded after the last task was done is not repeated.
+    async fn complete_project(&self) -> Result<()> {
+        let tasks = self.repository.list(TaskFilter::default()).await?;
+        if !is_project_complete(&tasks) {
+            return Ok(());
+        }
+        let completed_at = tasks.iter().filter_map(|t| t.done_at).max();
+        let previous = match self
+            .repository
+            .list_system_events(&SystemEventFilter {
+                event_type: Some(PROJECT_COMPLETED_EVENT.to_string()),
+                limit: Some(1),
+                ..SystemEventFilter::default()
+            })
+            .await
         {
-            return Err(::task_core::TaskError::Validation(
-                "Agent name must be in kebab-case format (lowercase letters, numbers, and hyphens only)".to_string()
-            ));
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        if previous
+            .first()
+            .is_some_and(|event| completed_at.is_some_and(|at| event.timestamp >= at))
+        {
+            return Ok(());
         }
 
-        // Call repository with validated parameters
-        let claimed_task = self
+        let sessions = match self
             .repository
-            .claim_task(params.task_id, &params.agent_name)
-            .await?;
+            .list_work_sessions(&WorkSessionFilter::default())
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let started_at = tasks.iter().map(|t| t.inserted_at).min();
+        let events = match self
+            .repository
+            .list_system_events(&SystemEventFilter {
+                since: started_at,
+                ..SystemEventFilter::default()
+            })
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let now = chrono::Utc::now();
+        let report = FinalReport::new(&tasks, &sessions, &events, now);
+        let report_path = match &self._project_root {
+            Some(root) => Some(write_final_report(root, &report)?),
+            None => None,
+        };
+        info!(
+            "Project completed with {} task(s) done{}",
+            report.tasks_done,
+            report_path
+                .as_ref()
+                .map(|path| format!("; final report written to {}", path.display()))
+                .unwrap_or_default()
+        );
 
-        // Protocol layer validation: ensure claimed task is in InProgress state
-        if claimed_task.state != ::task_core::TaskState::InProgress {
-            return Err(::task_core::TaskError::Internal(format!(
-                "CRITICAL: claim_task succeeded but task {} is in state {:?}, expected InProgress",
-                claimed_task.id, claimed_task.state
-            )));
+        let event = SystemEvent {
+            id: 0,
+            event_type: PROJECT_COMPLETED_EVENT.to_string(),
+            entity_id: None,
+            data: serde_json::json!({
+                "report_path": report_path,
+                "tasks_done": report.tasks_done,
+                "tasks_archived": report.tasks_archived,
+                "risks": report.risks.len(),
+            }),
+            triggered_by: None,
+            timestamp: now,
+            severity: EventSeverity::Info,
+        };
+        match self.repository.record_system_event(&event).await {
+            Ok(_) | Err(TaskError::UnsupportedOperation(_)) => Ok(()),
+            Err(e) => Err(e),
         }
+    }
+}
 
-        // Ensure task ownership is correctly set
-        if claimed_task.owner_agent_name.as_deref() != Some(&params.agent_name) {
-            return Err(::task_core::TaskError::Internal(format!(
-                "CRITICAL: claim_task succeeded but task {} owner is {:?}, expected Some('{}')",
-                claimed_task.id, claimed_task.owner_agent_name, params.agent_name
+impl<R: ?Sized, M: TaskMessageRepository, W> McpTaskHandler<R, M, W> {
+    /// Extend the summary of a task thread that grew past the threshold
+    ///
+    /// Runs after a message is stored, so failures are logged instead of
+    /// failing the request.
+    async fn refresh_thread_summary(&self, task_code: &str) {
+        let policy = &self.thread_summary_policy;
+        if policy.threshold == 0 {
+            return;
+        }
+        let recent = self
+            .message_repository
+            .get_messages(
+                task_code,
+                None,
+                None,
+                None,
+                None,
+                Some(policy.threshold + 1),
+            )
+            .await;
+        if recent.is_ok_and(|messages| messages.len() as u32 <= policy.threshold) {
+            return;
+        }
+        match summarize_thread(
+            self.message_repository.as_ref(),
+            self.thread_summarizer.as_ref(),
+            policy,
+            task_code,
+        )
+        .await
+        {
+            Ok(_) | Err(TaskError::UnsupportedOperation(_)) => {}
+            Err(e) => warn!("Thread summary of task {} not updated: {}", task_code, e),
+        }
+    }
+}
+
+impl<R, M, W> McpTaskHandler<R, M, W>
+where
+    R: TaskRepository + ?Sized,
+    M: TaskMessageRepository,
+    W: WorkspaceContextRepository,
+{
+    /// Route `task` by the routing rules without carrying the decision out
+    async fn explain_routing(
+        &self,
+        task: &Task,
+        exclude_agent: Option<&str>,
+    ) -> Result<RoutingDecision> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let registered = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .map(|context| context.registered_agents)
+            .unwrap_or_default();
+        let mut active_tasks: std::collections::HashMap<String, usize> =
+            std::collections::HashMap::new();
+        if !registered.is_empty() && !self.routing_rules.is_empty() {
+            let in_progress = self
+                .repository
+                .list(TaskFilter {
+                    state: Some(TaskState::InProgress),
+                    ..TaskFilter::default()
+                })
+                .await?;
+            for owner in in_progress
+                .iter()
+                .filter_map(|t| t.owner_agent_name.as_ref())
+            {
+                *active_tasks.entry(owner.clone()).or_default() += 1;
+            }
+        }
+        let agents: Vec<RoutingAgent> = registered
+            .into_iter()
+            .filter(|agent| Some(agent.name.as_str()) != exclude_agent)
+            .map(|agent| RoutingAgent {
+                active_tasks: active_tasks.get(&agent.name).copied().unwrap_or(0),
+                name: agent.name,
+                capabilities: agent.capabilities,
+            })
+            .collect();
+
+        let catalog = self.capability_catalog().await?;
+        let task_capabilities = catalog.normalize_all(&task.required_capabilities);
+        Ok(self
+            .routing_rules
+            .explain(task, &task_capabilities, &agents, &catalog))
+    }
+
+    /// Assign `task` or suggest an owner for it as the routing rules decide
+    ///
+    /// Routing never fails the request that created or released the task;
+    /// problems are logged and the task is returned as it was.
+    async fn route_task(&self, task: Task, exclude_agent: Option<&str>) -> Task {
+        if self.routing_rules.is_empty() {
+            return task;
+        }
+        let decision = match self.explain_routing(&task, exclude_agent).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                warn!("Routing of task {} failed: {}", task.code, e);
+                return task;
+            }
+        };
+        let (Some(rule), Some(action), Some(owner)) =
+            (&decision.matched_rule, decision.action, &decision.owner)
+        else {
+            return task;
+        };
+        match action {
+            RouteAction::Assign => match self.repository.assign(task.id, owner).await {
+                Ok(assigned) => {
+                    info!(
+                        "Task {} assigned to {} by routing rule '{}'",
+                        task.code, owner, rule
+                    );
+                    assigned
+                }
+                Err(e) => {
+                    warn!(
+                        "Routing rule '{}' could not assign task {}: {}",
+                        rule, task.code, e
+                    );
+                    task
+                }
+            },
+            RouteAction::Suggest => {
+                let data = serde_json::json!({ "rule": rule, "agent": owner });
+                let content = format!("Routing rule '{rule}' suggests {owner} for this task");
+                if let Err(e) = self
+                    .message_repository
+                    .create_message(
+                        &task.code,
+                        ROUTING_AUTHOR,
+                        Some(owner),
+                        ROUTING_SUGGESTION_MESSAGE_TYPE,
+                        &content,
+                        None,
+                        Some(&data),
+                    )
+                    .await
+                {
+                    warn!(
+                        "Routing suggestion for task {} not posted: {}",
+                        task.code, e
+                    );
+                }
+                task
+            }
+        }
+    }
+
+    /// Advance the pipelines of tasks that moved to `Done`, then check
+    /// whether the project is complete
+    ///
+    /// Like [`Self::check_project_completion`] this never fails the request;
+    /// a pipeline that cannot advance is logged.
+    async fn after_tasks_done(&self, done: &[Task]) {
+        for task in done {
+            if let Err(e) = self.advance_pipeline(task).await {
+                warn!("Pipeline of stage {} not advanced: {}", task.code, e);
+            }
+        }
+        self.check_project_completion().await;
+    }
+
+    /// Release the stage after `stage`, or its pipeline task after the last stage
+    async fn advance_pipeline(&self, stage: &Task) -> Result<()> {
+        let Some(parent_id) = stage.parent_task_id else {
+            return Ok(());
+        };
+        let Some(pipeline) = self.repository.get_by_id(parent_id).await? else {
+            return Ok(());
+        };
+        let Some(number) = stage_number(&pipeline.code, &stage.code) else {
+            return Ok(());
+        };
+        if pipeline.state != TaskState::WaitingForDependency {
+            return Ok(());
+        }
+        let next = self
+            .repository
+            .get_by_code(&stage_code(&pipeline.code, number + 1))
+            .await?
+            .filter(|next| next.parent_task_id == Some(pipeline.id));
+        let by = stage
+            .owner_agent_name
+            .as_deref()
+            .map(|owner| format!(" by {owner}"))
+            .unwrap_or_default();
+        let (released, content) = match next {
+            Some(next) if next.state == TaskState::WaitingForDependency => {
+                let content = format!(
+                    "Stage {number} ({}) of pipeline {} was done{by}; this stage can start. \
+                     Check the messages and artifacts of {} first.",
+                    stage.code, pipeline.code, stage.code
+                );
+                (next, content)
+            }
+            // Released already, or moved on by hand
+            Some(_) => return Ok(()),
+            None => {
+                let content = format!(
+                    "The last stage ({}) of pipeline {} was done{by}. Check the results of \
+                     all stages and close the pipeline task.",
+                    stage.code, pipeline.code
+                );
+                (pipeline.clone(), content)
+            }
+        };
+
+        let data = serde_json::json!({
+            "pipeline": pipeline.code,
+            "completed_stage": stage.code,
+            "stage": number,
+        });
+        let operations = [
+            TaskOperation::SetState {
+                id: released.id,
+                state: TaskState::Created,
+            },
+            TaskOperation::Message {
+                task_code: released.code.clone(),
+                author_agent_name: PIPELINE_AUTHOR.to_string(),
+                target_agent_name: released.owner_agent_name.clone(),
+                message_type: STAGE_READY_MESSAGE_TYPE.to_string(),
+                content,
+                reply_to_message_id: None,
+                structured_data: Some(data),
+            },
+        ];
+        let outcomes = self.repository.apply_operations(&operations).await?;
+        info!(
+            "Task {} released after stage {} of pipeline {}",
+            released.code, stage.code, pipeline.code
+        );
+        if let Some(TaskOperationOutcome::Task(task)) = outcomes.into_iter().next() {
+            self.state_changed(&task);
+            if task.id != pipeline.id {
+                self.route_task(task, None).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Announce that `task` entered its state; nobody listening is fine
+    fn state_changed(&self, task: &Task) {
+        let _ = self.state_changes.send(task.clone());
+    }
+
+    /// Reject moving `task` to `Done` while it misses the definition of done
+    async fn check_done_policy(&self, task: &Task, state: TaskState) -> Result<()> {
+        check_done_policy(
+            &self.done_policy,
+            &*self.repository,
+            &*self.message_repository,
+            task,
+            state,
+        )
+        .await
+    }
+
+    /// [`Self::check_done_policy`] for the task with `id`
+    async fn check_done_policy_by_id(&self, id: i32, state: TaskState) -> Result<()> {
+        if state != TaskState::Done || self.done_policy.is_empty() {
+            return Ok(());
+        }
+        let task = self
+            .repository
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| TaskError::not_found_id(id))?;
+        self.check_done_policy(&task, state).await
+    }
+
+    /// Move the task with `id` to `state` under the done policy, advancing
+    /// pipelines and project completion when it is done
+    async fn change_task_state(&self, id: i32, state: TaskState) -> Result<Task> {
+        self.check_done_policy_by_id(id, state).await?;
+        let task = self.repository.set_state(id, state).await?;
+        self.state_changed(&task);
+        if task.state == TaskState::Done {
+            self.after_tasks_done(std::slice::from_ref(&task)).await;
+        }
+        Ok(task)
+    }
+
+    /// Hand the task with `id` to `new_owner`
+    async fn change_task_owner(&self, id: i32, new_owner: &str) -> Result<Task> {
+        TaskValidator::validate_agent_name(new_owner)?;
+        self.repository.assign(id, new_owner).await
+    }
+
+    /// Check that `message` accepts a handoff of `task` to its author
+    ///
+    /// An owned task only changes hands when the message replies to a message
+    /// its owner addressed to the author.
+    async fn check_handoff_accepted(&self, task: &Task, message: &TaskMessage) -> Result<()> {
+        let author = message.author_agent_name.as_str();
+        let Some(owner) = task
+            .owner_agent_name
+            .as_deref()
+            .filter(|owner| !owner.is_empty() && *owner != author)
+        else {
+            return Ok(());
+        };
+        let offer = match message.reply_to_message_id {
+            Some(id) => self.message_repository.get_message_by_id(id).await?,
+            None => None,
+        };
+        let offered = offer.is_some_and(|offer| {
+            offer.task_code == task.code
+                && offer.author_agent_name == owner
+                && offer.target_agent_name.as_deref() == Some(author)
+        });
+        if !offered {
+            return Err(TaskError::Validation(format!(
+                "task {} belongs to '{owner}'; '{author}' can only take it over by replying to \
+                 a handoff '{owner}' addressed to them",
+                task.code
             )));
         }
+        Ok(())
+    }
+
+    /// Apply the workflow's message effects to the task of a new message
+    ///
+    /// The message is already stored, so effects that cannot be applied (for
+    /// example an invalid state transition) are logged instead of failing the
+    /// request.
+    async fn apply_message_effects(&self, message: &TaskMessage) {
+        for rule in self
+            .message_effects
+            .iter()
+            .filter(|rule| rule.message_type == message.message_type)
+        {
+            if let Err(e) = self.apply_message_effect(&rule.effect, message).await {
+                warn!(
+                    "Message effect {:?} for {} message {} on task {} not applied: {}",
+                    rule.effect, message.message_type, message.id, message.task_code, e
+                );
+            }
+        }
+    }
+
+    async fn apply_message_effect(
+        &self,
+        effect: &MessageEffect,
+        message: &TaskMessage,
+    ) -> Result<()> {
+        let task = self
+            .repository
+            .get_by_code(&message.task_code)
+            .await?
+            .ok_or_else(|| TaskError::not_found_code(&message.task_code))?;
+
+        let new_owner = match effect {
+            MessageEffect::SetState { state } => {
+                if task.state != *state {
+                    let task = self.change_task_state(task.id, *state).await?;
+                    info!(
+                        "Task {} moved to {:?} by {} message",
+                        task.code, state, message.message_type
+                    );
+                }
+                return Ok(());
+            }
+            MessageEffect::AssignToTarget => {
+                message.target_agent_name.as_deref().ok_or_else(|| {
+                    TaskError::Validation("message has no target agent to assign".to_string())
+                })?
+            }
+            MessageEffect::AssignToAuthor => {
+                self.check_handoff_accepted(&task, message).await?;
+                message.author_agent_name.as_str()
+            }
+        };
+
+        if task.owner_agent_name.as_deref() != Some(new_owner) {
+            self.change_task_owner(task.id, new_owner).await?;
+            info!(
+                "Task {} reassigned to {} by {} message",
+                task.code, new_owner, message.message_type
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<
+        R: TaskRepository + Send + Sync + ?Sized,
+        M: TaskMessageRepository + Send + Sync,
+        W: WorkspaceContextRepository + Send + Sync,
+    > ProtocolHandler for McpTaskHandler<R, M, W>
+{
+    async fn create_task(&self, params: CreateTaskParams) -> Result<Task> {
+        let new_task = self.new_task(params).await?;
+        let task = self.repository.create(new_task).await?;
+        Ok(self.route_task(task, None).await)
+    }
+
+    async fn create_pipeline(&self, params: CreatePipelineParams) -> Result<Pipeline> {
+        let template = self.pipeline_templates.get(
+            params
+                .template
+                .as_deref()
+                .unwrap_or(DEFAULT_PIPELINE_TEMPLATE),
+        )?;
+        let mut builder = NewTask::builder()
+            .code(params.code)
+            .name(params.name)
+            .description(params.description);
+        if let Some(owner) = params.owner_agent_name {
+            builder = builder.owner(owner);
+        }
+        if let Some(priority) = params.priority_score {
+            builder = builder.priority(priority);
+        }
+        let pipeline = builder.build()?;
+        let mut stages = Vec::with_capacity(template.stages.len());
+        for stage in template.stage_tasks(&pipeline) {
+            stages.push(self.new_task(stage).await?);
+        }
+
+        let mut tasks = self
+            .repository
+            .create_pipeline(&pipeline, &stages)
+            .await?
+            .into_iter();
+        let pipeline = tasks
+            .next()
+            .ok_or_else(|| TaskError::not_found_code(&pipeline.code))?;
+        let mut stages: Vec<Task> = tasks.collect();
+        if let Some(first) = stages.first_mut() {
+            *first = self.route_task(first.clone(), None).await;
+        }
+        info!(
+            "Pipeline {} created from template '{}' with {} stages",
+            pipeline.code,
+            template.name,
+            stages.len()
+        );
+        Ok(Pipeline {
+            template: template.name.clone(),
+            pipeline,
+            stages,
+        })
+    }
+
+    async fn get_task_context(&self, params: GetTaskContextParams) -> Result<TaskContext> {
+        let token_budget = params
+            .token_budget
+            .unwrap_or(self.token_budgets.task_context);
+        if token_budget < MIN_CONTEXT_TOKEN_BUDGET {
+            return Err(TaskError::Validation(format!(
+                "token_budget must be at least {MIN_CONTEXT_TOKEN_BUDGET}"
+            )));
+        }
+        let task = self
+            .repository
+            .get_by_code(&params.task_code)
+            .await?
+            .ok_or_else(|| TaskError::not_found_code(&params.task_code))?;
+
+        let tasks = self.repository.list(TaskFilter::default()).await?;
+        let dependencies = DependencyChain::new(&task, &tasks);
+        let recent_messages = self
+            .message_repository
+            .get_messages(
+                &task.code,
+                None,
+                None,
+                None,
+                None,
+                Some(CONTEXT_MESSAGE_LIMIT),
+            )
+            .await?;
+        let mut comments = match self
+            .message_repository
+            .get_comments(&task.code, false)
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        comments.reverse();
+
+        // Records of other tasks stand in for shared knowledge when search is enabled
+        let related = if self.embedding_provider.is_some() {
+            let params = SemanticSearchParams {
+                query: format!("{} {}", task.name, task.description),
+                top_k: Some(MAX_SEMANTIC_SEARCH_RESULTS),
+                sources: Vec::new(),
+            };
+            let mut matches = self.semantic_search(params).await?;
+            matches.retain(|m| m.task_code != task.code);
+            matches.truncate(CONTEXT_RELATED_LIMIT);
+            matches
+        } else {
+            Vec::new()
+        };
+        let external_links = match self.repository.list_task_external_links(&task.code).await {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let artifact_filter = ArtifactFilter {
+            task_code: Some(task.code.clone()),
+            ..Default::default()
+        };
+        let artifacts = match self.repository.list_artifacts(&artifact_filter).await {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+
+        Ok(TaskContext::new(
+            task,
+            dependencies,
+            recent_messages,
+            comments,
+            related,
+            external_links,
+        )
+        .with_artifacts(artifacts)
+        .fit_to_budget(token_budget))
+    }
+
+    async fn register_artifact(&self, params: RegisterArtifactParams) -> Result<TaskArtifact> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        validate_artifact_kind(&params.kind)?;
+        let expected = params
+            .checksum
+            .as_deref()
+            .map(normalize_checksum)
+            .transpose()?;
+        if self
+            .repository
+            .get_by_code(&params.task_code)
+            .await?
+            .is_none()
+        {
+            return Err(TaskError::not_found_code(&params.task_code));
+        }
+
+        let root = self.workspace_root(None)?;
+        let (path, full_path) = resolve_artifact_path(&root, &params.path)?;
+        let (checksum, size_bytes) = file_checksum(&full_path)?;
+        if let Some(expected) = expected {
+            if expected != checksum {
+                return Err(TaskError::Validation(format!(
+                    "checksum of {path} is {checksum}, not {expected}"
+                )));
+            }
+        }
+
+        self.repository
+            .save_artifact(&TaskArtifact {
+                id: 0,
+                task_code: params.task_code,
+                path,
+                kind: params.kind,
+                checksum,
+                size_bytes,
+                registered_by: Some(params.agent_name),
+                registered_at: chrono::Utc::now(),
+            })
+            .await
+    }
+
+    async fn list_artifacts(&self, params: ListArtifactsParams) -> Result<Vec<ListedArtifact>> {
+        let root = match params.verify {
+            true => Some(self.workspace_root(None)?),
+            false => None,
+        };
+        let filter = ArtifactFilter {
+            task_code: params.task_code,
+            kind: params.kind,
+            limit: params.limit,
+        };
+        let artifacts = self.repository.list_artifacts(&filter).await?;
+        Ok(artifacts
+            .into_iter()
+            .map(|artifact| ListedArtifact {
+                status: root.as_deref().map(|root| artifact_status(root, &artifact)),
+                artifact,
+            })
+            .collect())
+    }
+
+    async fn get_final_report(&self, params: GetFinalReportParams) -> Result<FinalReport> {
+        let root = self.workspace_root(params.project_root.as_deref())?;
+        latest_final_report(&root)?.ok_or_else(|| {
+            TaskError::NotFound(format!(
+                "no final report in {}; one is written once every task is done",
+                root.join(REPORTS_DIR).display()
+            ))
+        })
+    }
+
+    async fn scan_code_todos(&self, params: ScanCodeTodosParams) -> Result<CodeTodoImport> {
+        let root = self.workspace_root(params.project_root.as_deref())?;
+        let limit = params
+            .limit
+            .map_or(MAX_BATCH_LOOKUP, |limit| limit as usize)
+            .clamp(1, MAX_BATCH_LOOKUP);
+        if let Some(owner) = &params.owner_agent_name {
+            TaskValidator::validate_agent_name(owner)?;
+        }
+
+        let mut todos = scan_code_todos(&root, limit)?;
+        if !params.markers.is_empty() {
+            todos.retain(|todo| params.markers.contains(&todo.marker));
+        }
+        // Identical comments in one file share a code; the first one stands for all
+        let mut codes = Vec::with_capacity(todos.len());
+        todos.retain(|todo| {
+            let code = todo.task_code();
+            let new = !codes.contains(&code);
+            if new {
+                codes.push(code);
+            }
+            new
+        });
+        let existing: Vec<String> = self
+            .repository
+            .get_by_codes(&codes)
+            .await?
+            .into_iter()
+            .map(|task| task.code)
+            .collect();
+        let proposed: Vec<ProposedTodoTask> = todos
+            .into_iter()
+            .zip(codes)
+            .map(|(todo, task_code)| ProposedTodoTask {
+                task_name: todo.to_new_task(None).name,
+                exists: existing.contains(&task_code),
+                task_code,
+                todo,
+            })
+            .collect();
+        let new_count = proposed.iter().filter(|p| !p.exists).count();
+
+        if !params.confirm {
+            return Ok(CodeTodoImport {
+                status: ResponseStatus::ConfirmationRequired,
+                message: format!(
+                    "Found {} TODO comment(s), {} without a task; resend with confirm: true \
+                     to create them, optionally limited to the task codes in codes",
+                    proposed.len(),
+                    new_count
+                ),
+                proposed,
+                created: Vec::new(),
+            });
+        }
+
+        if let Some(unknown) = params
+            .codes
+            .iter()
+            .find(|code| !proposed.iter().any(|p| &p.task_code == *code))
+        {
+            return Err(TaskError::Validation(format!(
+                "{unknown} is not among the proposed task codes"
+            )));
+        }
+        let mut operations = Vec::new();
+        for proposal in proposed.iter().filter(|p| {
+            !p.exists && (params.codes.is_empty() || params.codes.contains(&p.task_code))
+        }) {
+            let task = proposal.todo.to_new_task(params.owner_agent_name.clone());
+            operations.push(TaskOperation::Create(self.new_task(task).await?));
+        }
+        let mut created = Vec::with_capacity(operations.len());
+        for batch in operations.chunks(MAX_TASK_OPERATIONS) {
+            for outcome in self.repository.apply_operations(batch).await? {
+                if let TaskOperationOutcome::Task(task) = outcome {
+                    created.push(task);
+                }
+            }
+        }
+        Ok(CodeTodoImport {
+            status: ResponseStatus::Success,
+            message: format!("Created {} task(s) from TODO comments", created.len()),
+            proposed,
+            created,
+        })
+    }
+
+    async fn sync_tasks_markdown(
+        &self,
+        params: SyncTasksMarkdownParams,
+    ) -> Result<TasksMarkdownSync> {
+        let root = self.workspace_root(params.project_root.as_deref())?;
+        // Edits are state changes like any other, with the same checks and follow-ups
+        sync_tasks_markdown(&*self.repository, &root, |task, state| {
+            self.change_task_state(task.id, state)
+        })
+        .await
+    }
+
+    async fn find_duplicate_tasks(
+        &self,
+        params: FindDuplicateTasksParams,
+    ) -> Result<Vec<DuplicateCandidate>> {
+        let policy = self.duplicate_check_policy;
+        if policy.max_candidates == 0 || params.name.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let tasks = self.repository.list(TaskFilter::default()).await?;
+        Ok(policy.find_duplicates(&params.name, &params.description, &tasks))
+    }
+
+    async fn update_task(&self, params: UpdateTaskParams) -> Result<Task> {
+        let id = params.id;
+        let mut update_data = params.into_update_data();
+        if let Some(capabilities) = &update_data.required_capabilities {
+            let capabilities = self.normalize_capabilities(capabilities).await?;
+            update_data.required_capabilities = Some(capabilities);
+        }
+        self.repository.update(id, update_data).await
+    }
+
+    async fn set_task_state(&self, params: SetStateParams) -> Result<Task> {
+        self.change_task_state(params.id, params.state).await
+    }
+
+    async fn get_task_by_id(&self, params: GetTaskByIdParams) -> Result<Option<Task>> {
+        self.repository.get_by_id(params.id).await
+    }
+
+    async fn get_task_by_code(&self, params: GetTaskByCodeParams) -> Result<Option<Task>> {
+        self.repository.get_by_code(&params.code).await
+    }
+
+    async fn get_task_by_uid(&self, params: GetTaskByUidParams) -> Result<Option<Task>> {
+        self.repository.get_by_uid(&params.uid).await
+    }
+
+    async fn get_tasks_by_ids(&self, params: GetTasksByIdsParams) -> Result<TaskBatch<i32>> {
+        params.validate()?;
+        let tasks = self.repository.get_by_ids(&params.ids).await?;
+        Ok(TaskBatch::new(params.ids, tasks, |task| &task.id))
+    }
+
+    async fn get_tasks_by_codes(&self, params: GetTasksByCodesParams) -> Result<TaskBatch<String>> {
+        params.validate()?;
+        let tasks = self.repository.get_by_codes(&params.codes).await?;
+        Ok(TaskBatch::new(params.codes, tasks, |task| &task.code))
+    }
+
+    async fn list_tasks(&self, params: ListTasksParams) -> Result<Vec<Task>> {
+        let filter = params.to_task_filter()?;
+
+        // Pagination is now handled at the database level for performance
+        self.repository.list(filter).await
+    }
+
+    async fn count_tasks(&self, params: CountTasksParams) -> Result<TaskCount> {
+        let filter = params.filter.to_task_filter()?;
+        Ok(TaskCount {
+            count: self.repository.count_tasks(&filter).await?,
+        })
+    }
+
+    async fn aggregate_tasks(&self, params: AggregateTasksParams) -> Result<TaskAggregate> {
+        let filter = params.filter.to_task_filter()?;
+        let (total, groups) = tokio::try_join!(
+            self.repository.count_tasks(&filter),
+            self.repository.aggregate_tasks(&filter, params.group_by)
+        )?;
+        Ok(TaskAggregate {
+            group_by: params.group_by,
+            total,
+            groups,
+        })
+    }
+
+    async fn apply_task_operations(
+        &self,
+        params: ApplyTaskOperationsParams,
+    ) -> Result<Vec<TaskOperationOutcome>> {
+        params.validate()?;
+        let mut operations = Vec::with_capacity(params.operations.len());
+        for operation in params.operations {
+            operations.push(match operation {
+                TaskOperationParams::Create(params) => {
+                    TaskOperation::Create(self.new_task(params).await?)
+                }
+                TaskOperationParams::SetState(params) => {
+                    self.check_done_policy_by_id(params.id, params.state)
+                        .await?;
+                    TaskOperation::SetState {
+                        id: params.id,
+                        state: params.state,
+                    }
+                }
+                TaskOperationParams::Assign(params) => {
+                    TaskValidator::validate_agent_name(&params.new_owner)?;
+                    TaskOperation::Assign {
+                        id: params.id,
+                        new_owner: params.new_owner,
+                    }
+                }
+                TaskOperationParams::Message(params) => {
+                    let (message_type, content) = self.render_message(&params)?;
+                    TaskOperation::Message {
+                        task_code: params.task_code,
+                        author_agent_name: params.author_agent_name,
+                        target_agent_name: params.target_agent_name,
+                        message_type,
+                        content,
+                        reply_to_message_id: params.reply_to_message_id,
+                        structured_data: params.structured_data,
+                    }
+                }
+            });
+        }
+
+        let outcomes = self.repository.apply_operations(&operations).await?;
+        for (operation, outcome) in operations.iter().zip(&outcomes) {
+            if let (TaskOperation::SetState { .. }, TaskOperationOutcome::Task(task)) =
+                (operation, outcome)
+            {
+                self.state_changed(task);
+            }
+        }
+        // Message follow-ups run only once the whole batch is committed
+        for outcome in &outcomes {
+            if let TaskOperationOutcome::Message(message) = outcome {
+                self.apply_message_effects(message).await;
+                self.refresh_thread_summary(&message.task_code).await;
+            }
+        }
+        let done: Vec<Task> = outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                TaskOperationOutcome::Task(task) if task.state == TaskState::Done => {
+                    Some(task.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        if !done.is_empty() {
+            self.after_tasks_done(&done).await;
+        }
+        Ok(outcomes)
+    }
+
+    async fn assign_task(&self, params: AssignTaskParams) -> Result<Task> {
+        self.change_task_owner(params.id, &params.new_owner).await
+    }
+
+    async fn archive_task(&self, params: ArchiveTaskParams) -> Result<Task> {
+        self.repository.archive(params.id).await
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus> {
+        self.repository.health_check().await?;
+
+        let health = HealthStatus {
+            status: "healthy".to_string(),
+            database: true,
+            protocol: true,
+            timestamp: chrono::Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+
+        Ok(health)
+    }
+
+    // MCP v2 Advanced Multi-Agent Features
+
+    async fn discover_work(&self, params: DiscoverWorkParams) -> Result<Vec<Task>> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        let max_tasks = params.max_tasks.unwrap_or(10); // Default to 10 tasks if not specified
+        let capabilities = self.normalize_capabilities(&params.capabilities).await?;
+        let affinities = self.claim_affinities().await?;
+        // Tasks reserved for other agents are dropped from the results
+        let reserved = affinities
+            .iter()
+            .filter(|claim| claim.agent_name != params.agent_name)
+            .count() as u32;
+        let Some(policy) = &self.discovery_policy else {
+            let tasks = self
+                .repository
+                .discover_work(&params.agent_name, &capabilities, max_tasks + reserved)
+                .await?;
+            return self
+                .with_claim_affinity(tasks, &affinities, &params.agent_name, max_tasks)
+                .await;
+        };
+
+        let candidates = self
+            .repository
+            .discover_work(
+                &params.agent_name,
+                &capabilities,
+                discovery_candidate_limit(max_tasks) + reserved,
+            )
+            .await?;
+        let owned = self
+            .repository
+            .list(TaskFilter {
+                owner: Some(params.agent_name.clone()),
+                ..TaskFilter::default()
+            })
+            .await?;
+        let workload = DiscoveryWorkload {
+            in_progress: owned
+                .iter()
+                .filter(|task| task.state == TaskState::InProgress)
+                .count() as u64,
+            blocked: owned
+                .iter()
+                .filter(|task| task.state == TaskState::Blocked)
+                .count() as u64,
+        };
+        let agent = DiscoveryAgent {
+            name: params.agent_name,
+            capabilities,
+        };
+
+        // A broken policy must not keep agents from finding work
+        let tasks = match policy.rank(candidates.clone(), &agent, &workload) {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                warn!(
+                    "Work discovery policy {} failed, using the default order: {}",
+                    policy.name(),
+                    e
+                );
+                candidates
+            }
+        };
+        self.with_claim_affinity(tasks, &affinities, &agent.name, max_tasks)
+            .await
+    }
+
+    async fn match_agents_to_task(
+        &self,
+        params: MatchAgentsToTaskParams,
+    ) -> Result<TaskAgentMatches> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let task = self
+            .repository
+            .get_by_id(params.task_id)
+            .await?
+            .ok_or_else(|| TaskError::not_found_id(params.task_id))?;
+        let agents = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .map(|context| context.registered_agents)
+            .unwrap_or_default();
+
+        // Agents may have registered before the catalog learned their spellings
+        let catalog = self.capability_catalog().await?;
+        let required_capabilities = catalog.normalize_all(&task.required_capabilities);
+
+        let mut active_tasks: std::collections::HashMap<String, usize> =
+            std::collections::HashMap::new();
+        if !agents.is_empty() {
+            let in_progress = self
+                .repository
+                .list(TaskFilter {
+                    state: Some(TaskState::InProgress),
+                    ..TaskFilter::default()
+                })
+                .await?;
+            for owner in in_progress
+                .iter()
+                .filter_map(|t| t.owner_agent_name.as_ref())
+            {
+                *active_tasks.entry(owner.clone()).or_default() += 1;
+            }
+        }
+
+        let matcher = CapabilityMatcher::default();
+        let mut matches: Vec<_> = agents
+            .iter()
+            .map(|agent| {
+                matcher.explain_match(
+                    &agent.name,
+                    &required_capabilities,
+                    &catalog.normalize_all(&agent.capabilities),
+                    &[],
+                    active_tasks.get(&agent.name).copied().unwrap_or(0),
+                )
+            })
+            .collect();
+        CapabilityMatcher::rank(&mut matches);
+
+        Ok(TaskAgentMatches {
+            task_id: task.id,
+            task_code: task.code,
+            required_capabilities,
+            agents: matches,
+        })
+    }
+
+    async fn explain_task_routing(
+        &self,
+        params: ExplainTaskRoutingParams,
+    ) -> Result<RoutingDecision> {
+        let task = self
+            .repository
+            .get_by_id(params.task_id)
+            .await?
+            .ok_or_else(|| TaskError::not_found_id(params.task_id))?;
+        self.explain_routing(&task, params.exclude_agent.as_deref())
+            .await
+    }
+
+    async fn export_timeline(&self, params: ExportTimelineParams) -> Result<Timeline> {
+        let tasks = self
+            .repository
+            .list(TaskFilter {
+                owner: params.owner,
+                include_archive: params.include_archive,
+                ..TaskFilter::default()
+            })
+            .await?;
+        Ok(Timeline::new(&tasks, chrono::Utc::now(), params.format))
+    }
+
+    async fn export_tasks_csv(&self, params: ExportTasksCsvParams) -> Result<CsvExport> {
+        let filter = params.to_filter();
+        let limit = filter.limit;
+        let tasks = self.repository.list(filter).await?;
+        Ok(CsvExport::tasks(&tasks, &params.columns, limit))
+    }
+
+    async fn export_sessions_csv(&self, params: ExportSessionsCsvParams) -> Result<CsvExport> {
+        let filter = params.to_filter();
+        let sessions = self.repository.list_work_sessions(&filter).await?;
+
+        let mut task_codes = std::collections::HashMap::new();
+        if params.columns.is_empty() || params.columns.contains(&SessionCsvColumn::TaskCode) {
+            for task_id in sessions.iter().map(|session| session.task_id) {
+                if task_codes.contains_key(&task_id) {
+                    continue;
+                }
+                if let Some(task) = self.repository.get_by_id(task_id).await? {
+                    task_codes.insert(task_id, task.code);
+                }
+            }
+        }
+        Ok(CsvExport::sessions(
+            &sessions,
+            &task_codes,
+            &params.columns,
+            filter.limit,
+        ))
+    }
+
+    async fn get_critical_path(&self, params: GetCriticalPathParams) -> Result<CriticalPath> {
+        let tasks = self.repository.list(TaskFilter::default()).await?;
+        Ok(CriticalPath::compute(
+            &tasks,
+            params
+                .default_effort_minutes
+                .unwrap_or(DEFAULT_EFFORT_MINUTES),
+        ))
+    }
+
+    async fn semantic_search(&self, params: SemanticSearchParams) -> Result<Vec<SemanticMatch>> {
+        let provider = self.embedding_provider.as_deref().ok_or_else(|| {
+            TaskError::UnsupportedOperation(
+                "semantic search is not enabled; configure [embeddings]".to_string(),
+            )
+        })?;
+        if params.query.trim().is_empty() {
+            return Err(TaskError::empty_field("query"));
+        }
+        let top_k = params.top_k.unwrap_or(DEFAULT_SEMANTIC_SEARCH_RESULTS);
+        if top_k == 0 || top_k > MAX_SEMANTIC_SEARCH_RESULTS {
+            return Err(TaskError::Validation(format!(
+                "top_k must be between 1 and {MAX_SEMANTIC_SEARCH_RESULTS}"
+            )));
+        }
+
+        // Catch up on records changed since the last background indexing run
+        index_embeddings(
+            self.repository.as_ref(),
+            provider,
+            SEARCH_INDEX_LIMIT,
+            EMBEDDING_BATCH_SIZE,
+        )
+        .await?;
+        let query = provider
+            .embed(std::slice::from_ref(&params.query))
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                TaskError::Protocol("embedding provider returned no vector".to_string())
+            })?;
+        let filter = SemanticSearchFilter {
+            sources: params.sources,
+            top_k,
+        };
+        self.repository
+            .semantic_search(provider.model(), &query, &filter)
+            .await
+    }
+
+    async fn claim_task(&self, params: ClaimTaskParams) -> Result<Task> {
+        // Validate agent name format at protocol layer
+        if params.agent_name.trim().is_empty() {
+            return Err(::task_core::TaskError::Validation(
+                "Agent name cannot be empty".to_string(),
+            ));
+        }
+
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+
+        // A task released by cleanup waits for its previous owner first
+        let affinities = self.claim_affinities().await?;
+        if let Some(claim) = affinities
+            .iter()
+            .find(|claim| claim.task_id == params.task_id && claim.agent_name != params.agent_name)
+        {
+            return Err(TaskError::Conflict(format!(
+                "Task {} is reserved for '{}' until {} because their claim timed out",
+                params.task_id,
+                claim.agent_name,
+                self.claim_lease_policy.affinity_until(claim).to_rfc3339()
+            )));
+        }
+
+        // Call repository with validated parameters
+        let lease = self.claim_lease_policy.lease_for(params.lease_minutes);
+        let claimed_task = self
+            .repository
+            .claim_task_with_lease(params.task_id, &params.agent_name, lease)
+            .await?;
+
+        // Protocol layer validation: ensure claimed task is in InProgress state
+        if claimed_task.state != ::task_core::TaskState::InProgress {
+            return Err(::task_core::TaskError::Internal(format!(
+                "CRITICAL: claim_task succeeded but task {} is in state {:?}, expected InProgress",
+                claimed_task.id, claimed_task.state
+            )));
+        }
+
+        // Ensure task ownership is correctly set
+        if claimed_task.owner_agent_name.as_deref() != Some(&params.agent_name) {
+            return Err(::task_core::TaskError::Internal(format!(
+                "CRITICAL: claim_task succeeded but task {} owner is {:?}, expected Some('{}')",
+                claimed_task.id, claimed_task.owner_agent_name, params.agent_name
+            )));
+        }
+
+        Ok(claimed_task)
+    }
+
+    async fn release_task(&self, params: ReleaseTaskParams) -> Result<Task> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        let task = self
+            .repository
+            .release_task(params.task_id, &params.agent_name)
+            .await?;
+        Ok(self.route_task(task, Some(&params.agent_name)).await)
+    }
+
+    async fn start_work_session(&self, params: StartWorkSessionParams) -> Result<WorkSessionInfo> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        if !self.work_session_policy.is_unrestricted() {
+            self.check_work_session_policy(params.task_id, &params.agent_name)
+                .await?;
+        }
+        let session_id = self
+            .repository
+            .start_work_session(params.task_id, &params.agent_name)
+            .await?;
+        let task = self
+            .repository
+            .get_by_id(params.task_id)
+            .await?
+            .ok_or_else(|| TaskError::not_found_id(params.task_id))?;
+        let sessions = match self
+            .repository
+            .list_work_sessions(&WorkSessionFilter {
+                task_id: Some(params.task_id),
+                ..WorkSessionFilter::default()
+            })
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let started_at = sessions
+            .iter()
+            .find(|s| s.id == session_id)
+            .map_or_else(chrono::Utc::now, |s| s.started_at);
+        Ok(WorkSessionInfo::new(
+            session_id,
+            params.agent_name,
+            started_at,
+            task,
+            &sessions,
+        ))
+    }
+
+    async fn end_work_session(&self, params: EndWorkSessionParams) -> Result<()> {
+        self.repository
+            .end_work_session(params.session_id, params.notes, params.productivity_score)
+            .await
+    }
+
+    async fn append_session_log(&self, params: AppendSessionLogParams) -> Result<SessionLogEntry> {
+        params.chunk.validate()?;
+        self.repository
+            .append_session_log(params.session_id, &params.chunk)
+            .await
+    }
+
+    async fn get_session_log(&self, params: GetSessionLogParams) -> Result<SessionLog> {
+        let session = self
+            .repository
+            .get_work_session(params.session_id)
+            .await?
+            .ok_or_else(|| {
+                TaskError::NotFound(format!("Work session {} not found", params.session_id))
+            })?;
+        let entries = self.repository.get_session_log(params.session_id).await?;
+        Ok(SessionLog::new(session, entries))
+    }
+
+    async fn get_agent_performance(
+        &self,
+        params: GetAgentPerformanceParams,
+    ) -> Result<PerformanceReport> {
+        if let Some(ref agent_name) = params.agent_name {
+            TaskValidator::validate_agent_name(agent_name)?;
+        }
+        let now = chrono::Utc::now();
+        let end = params.until.unwrap_or(now);
+        let start = params.since.unwrap_or_else(|| {
+            end - chrono::Duration::days(i64::from(DEFAULT_PERFORMANCE_PERIOD_DAYS))
+        });
+        if start >= end {
+            return Err(TaskError::Validation(
+                "since must be before until".to_string(),
+            ));
+        }
+
+        let mut report = performance_report(
+            &*self.repository,
+            start,
+            end,
+            params
+                .bucket_days
+                .unwrap_or(DEFAULT_PERFORMANCE_BUCKET_DAYS),
+            &self.anomaly_thresholds,
+        )
+        .await?;
+        if let Some(ref agent_name) = params.agent_name {
+            report = report.for_agent(agent_name);
+        }
+
+        // Only anomalies of the current state are worth recording, not past periods
+        if params.until.is_none() {
+            match record_anomalies(&*self.repository, &report.anomalies, now).await {
+                Ok(0) | Err(TaskError::UnsupportedOperation(_)) => {}
+                Ok(recorded) => info!("Recorded {} agent performance anomaly event(s)", recorded),
+                Err(e) => warn!("Failed to record agent performance anomalies: {}", e),
+            }
+        }
+        Ok(report)
+    }
+
+    async fn get_system_events(&self, params: GetSystemEventsParams) -> Result<Vec<SystemEvent>> {
+        self.repository
+            .list_system_events(&params.to_filter())
+            .await
+    }
+
+    async fn get_tool_usage_stats(
+        &self,
+        params: GetToolUsageStatsParams,
+    ) -> Result<Vec<ToolUsageStat>> {
+        if let Some(ref agent_name) = params.agent_name {
+            TaskValidator::validate_agent_name(agent_name)?;
+        }
+        let until = params.until.unwrap_or_else(chrono::Utc::now);
+        let since = params
+            .since
+            .unwrap_or_else(|| until - chrono::Duration::hours(24));
+        if since >= until {
+            return Err(TaskError::Validation(
+                "since must be before until".to_string(),
+            ));
+        }
+
+        self.repository
+            .tool_usage_stats(&ToolUsageFilter {
+                agent_name: params.agent_name,
+                tool_name: params.tool_name,
+                since: Some(since),
+                until: Some(until),
+                limit: Some(
+                    params
+                        .limit
+                        .unwrap_or(MAX_TOOL_USAGE_STATS)
+                        .min(MAX_TOOL_USAGE_STATS),
+                ),
+            })
+            .await
+    }
+
+    async fn rebalance_work(&self, params: RebalanceWorkParams) -> Result<WorkRebalance> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let policy = self.rebalance_policy;
+        let catalog = self.capability_catalog().await?;
+        let mut tasks = self
+            .repository
+            .list(TaskFilter {
+                state: Some(TaskState::InProgress),
+                ..TaskFilter::default()
+            })
+            .await?;
+        for task in &mut tasks {
+            task.required_capabilities = catalog.normalize_all(&task.required_capabilities);
+        }
+        let agents: Vec<RebalanceAgent> = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .map(|context| context.registered_agents)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|agent| RebalanceAgent {
+                capabilities: catalog.normalize_all(&agent.capabilities),
+                name: agent.name,
+            })
+            .collect();
+
+        // Only agents above the limit can hold work to move
+        let mut claimed: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        for owner in tasks
+            .iter()
+            .filter_map(|task| task.owner_agent_name.as_deref())
+        {
+            *claimed.entry(owner).or_default() += 1;
+        }
+        let mut sessions = Vec::new();
+        for task in &tasks {
+            let owner = task.owner_agent_name.as_deref().unwrap_or_default();
+            if claimed.get(owner).copied().unwrap_or(0) <= policy.max_claimed_tasks as usize {
+                continue;
+            }
+            let filter = WorkSessionFilter {
+                task_id: Some(task.id),
+                ..WorkSessionFilter::default()
+            };
+            match self.repository.list_work_sessions(&filter).await {
+                Ok(found) => sessions.extend(found),
+                Err(TaskError::UnsupportedOperation(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut rebalance = policy.plan(&tasks, &sessions, &agents, chrono::Utc::now());
+        if !params.confirm {
+            return Ok(rebalance);
+        }
+
+        if let Some(unknown) = params.codes.iter().find(|code| {
+            !rebalance
+                .reassignments
+                .iter()
+                .any(|r| &r.task_code == *code)
+        }) {
+            return Err(TaskError::Validation(format!(
+                "{unknown} is not among the proposed reassignments"
+            )));
+        }
+        rebalance
+            .reassignments
+            .retain(|r| params.codes.is_empty() || params.codes.contains(&r.task_code));
+        // Each reassignment is applied whole: the handover and both messages
+        let per_batch = MAX_TASK_OPERATIONS / Reassignment::OPERATIONS;
+        for batch in rebalance.reassignments.chunks(per_batch) {
+            let operations: Vec<TaskOperation> =
+                batch.iter().flat_map(Reassignment::operations).collect();
+            for outcome in self.repository.apply_operations(&operations).await? {
+                if let TaskOperationOutcome::Task(task) = outcome {
+                    rebalance.reassigned.push(task);
+                }
+            }
+        }
+        info!(
+            "Rebalancing reassigned {} task(s) from overloaded to idle agents",
+            rebalance.reassigned.len()
+        );
+        rebalance.status = ResponseStatus::Success;
+        rebalance.message = format!(
+            "Reassigned {} task(s) from overloaded to idle agents",
+            rebalance.reassigned.len()
+        );
+        Ok(rebalance)
+    }
+
+    async fn get_board_at(&self, params: GetBoardAtParams) -> Result<TaskBoard> {
+        if params.timestamp > chrono::Utc::now() {
+            return Err(TaskError::Validation(
+                "timestamp must not be in the future".to_string(),
+            ));
+        }
+        self.repository.board_at(params.timestamp).await
+    }
+
+    async fn diff_workspace(&self, params: DiffWorkspaceParams) -> Result<WorkspaceDiff> {
+        if params.from > params.to {
+            return Err(TaskError::Validation(
+                "from must not be after to".to_string(),
+            ));
+        }
+        if params.to > chrono::Utc::now() {
+            return Err(TaskError::Validation(
+                "to must not be in the future".to_string(),
+            ));
+        }
+
+        let from = self.repository.board_at(params.from).await?;
+        let to = self.repository.board_at(params.to).await?;
+        let limit = params.message_limit();
+        // One extra message tells whether the list was truncated
+        let messages = self
+            .repository
+            .audit_log(&AuditLogFilter {
+                since: Some(params.from),
+                until: Some(params.to),
+                sources: vec![AuditSource::Message],
+                limit: Some(limit + 1),
+            })
+            .await?;
+        Ok(WorkspaceDiff::new(&from, &to, &messages, limit as usize))
+    }
+
+    async fn cleanup_timed_out_tasks(
+        &self,
+        params: CleanupTimedOutTasksParams,
+    ) -> Result<Vec<Task>> {
+        self.repository
+            .cleanup_timed_out_tasks(params.timeout_minutes)
+            .await
+    }
+
+    async fn get_retention_report(&self) -> Result<RetentionReport> {
+        self.repository
+            .apply_retention(&self.retention_policy, true)
+            .await
+    }
+
+    async fn maintain_database(&self, params: MaintainDatabaseParams) -> Result<MaintenanceReport> {
+        let report = self
+            .repository
+            .maintain_database(&params.to_maintenance_options())
+            .await?;
+        info!(
+            "Database maintenance ran {:?}, reclaimed {} bytes",
+            report.operations,
+            report.reclaimed_bytes()
+        );
+        Ok(report)
+    }
+
+    async fn get_migration_status(&self) -> Result<MigrationStatus> {
+        self.repository.migration_status().await
+    }
+
+    async fn export_audit_log(&self, params: ExportAuditLogParams) -> Result<AuditLogExport> {
+        let filter = params.to_filter();
+        let entries = self.repository.audit_log(&filter).await?;
+        Ok(AuditLogExport::new(&entries, filter.limit))
+    }
+
+    async fn purge_agent_data(&self, params: PurgeAgentDataParams) -> Result<AgentPurgeReport> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let mut report = self
+            .repository
+            .purge_agent_data(&params.agent_name, params.mode)
+            .await?;
+
+        if let Some(mut context) = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+        {
+            let before = context.registered_agents.len();
+            context
+                .registered_agents
+                .retain(|agent| agent.name != params.agent_name);
+            report.registry_entries = (before - context.registered_agents.len()) as u64;
+            if report.registry_entries > 0 {
+                context.updated_at = chrono::Utc::now();
+                self.workspace_context_repository.update(context).await?;
+            }
+        }
+
+        info!(
+            "Purged data of agent {} ({:?}), {} rows affected",
+            params.agent_name,
+            report.mode,
+            report.total()
+        );
+        Ok(report)
+    }
+
+    async fn merge_workspace(&self, params: MergeWorkspaceParams) -> Result<WorkspaceMergeReport> {
+        if params.import_path.trim().is_empty() {
+            return Err(TaskError::empty_field("import_path"));
+        }
+
+        let report = self
+            .repository
+            .merge_database(Path::new(&params.import_path), params.dry_run)
+            .await?;
+        info!(
+            "Merged {} ({}): {} tasks imported, {} matched, {} renamed, {} messages imported",
+            report.import_path,
+            if report.dry_run { "dry run" } else { "applied" },
+            report.tasks_imported,
+            report.tasks_matched,
+            report.renamed_codes.len(),
+            report.messages_imported
+        );
+        Ok(report)
+    }
+
+    async fn register_with_claude_code(
+        &self,
+        params: RegisterWithClaudeCodeParams,
+    ) -> Result<ClaudeCodeRegistration> {
+        let project_root = Path::new(&params.project_root);
+        if !project_root.is_absolute() || !project_root.is_dir() {
+            return Err(TaskError::Validation(format!(
+                "project_root must be an absolute path of an existing directory: {}",
+                params.project_root
+            )));
+        }
+        if params.port == 0 {
+            return Err(TaskError::Validation("port must not be 0".to_string()));
+        }
+        let settings_path = match &params.settings_path {
+            Some(path) => PathBuf::from(path),
+            None => crate::claude_settings::default_settings_path().ok_or_else(|| {
+                TaskError::Configuration("Cannot locate the home directory".to_string())
+            })?,
+        };
+
+        let registration = crate::claude_settings::register_with_claude_code(
+            &settings_path,
+            project_root,
+            http_server_entry(params.port),
+            params.dry_run,
+        )?;
+        if registration.changed && !registration.dry_run {
+            info!(
+                "Registered {} for {} in {}",
+                registration.server_name, registration.project_root, registration.settings_path
+            );
+        }
+        Ok(registration)
+    }
+
+    async fn run_readonly_query(&self, params: RunReadonlyQueryParams) -> Result<QueryResult> {
+        self.repository.run_readonly_query(&params.to_query()).await
+    }
+
+    // Task Messaging Implementation
+
+    async fn define_capability(&self, params: DefineCapabilityParams) -> Result<Capability> {
+        let name = validate_capability(&params.name)?;
+        if params.description.len() > 300 {
+            return Err(TaskError::Validation(
+                "Capability description cannot exceed 300 characters".to_string(),
+            ));
+        }
+        let mut aliases: Vec<String> = Vec::with_capacity(params.aliases.len());
+        for alias in &params.aliases {
+            let alias = validate_capability(alias)?;
+            if alias != name && !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+        self.capability_catalog()
+            .await?
+            .check_aliases(&name, &aliases)?;
+
+        self.repository
+            .define_capability(&name, params.description.trim(), &aliases)
+            .await
+    }
+
+    async fn list_capabilities(&self, params: ListCapabilitiesParams) -> Result<Vec<Capability>> {
+        let mut capabilities = self.repository.list_capabilities().await?;
+        if !params.include_deprecated {
+            capabilities.retain(|capability| !capability.is_deprecated());
+        }
+        Ok(capabilities)
+    }
+
+    async fn deprecate_capability(&self, params: DeprecateCapabilityParams) -> Result<Capability> {
+        let catalog = self.capability_catalog().await?;
+        let name = catalog
+            .lookup(&params.name)
+            .map(|capability| capability.name.clone())
+            .ok_or_else(|| {
+                TaskError::NotFound(format!("Capability '{}' not found", params.name))
+            })?;
+
+        let replaced_by = match &params.replaced_by {
+            Some(replacement) => {
+                let replacement = catalog
+                    .lookup(replacement)
+                    .filter(|capability| !capability.is_deprecated())
+                    .ok_or_else(|| {
+                        TaskError::Validation(format!(
+                            "Replacement capability '{replacement}' is not defined or is deprecated"
+                        ))
+                    })?;
+                if replacement.name == name {
+                    return Err(TaskError::Validation(format!(
+                        "Capability '{name}' cannot replace itself"
+                    )));
+                }
+                Some(replacement.name.clone())
+            }
+            None => None,
+        };
+
+        self.repository
+            .deprecate_capability(&name, replaced_by.as_deref())
+            .await
+    }
+
+    async fn create_task_message(&self, params: CreateTaskMessageParams) -> Result<TaskMessage> {
+        let (message_type, content) = self.render_message(&params)?;
+
+        let message = self
+            .message_repository
+            .create_message(
+                &params.task_code,
+                &params.author_agent_name,
+                params.target_agent_name.as_deref(),
+                &message_type,
+                &content,
+                params.reply_to_message_id,
+                params.structured_data.as_ref(),
+            )
+            .await?;
+
+        self.apply_message_effects(&message).await;
+        self.refresh_thread_summary(&message.task_code).await;
+        Ok(message)
+    }
+
+    async fn get_task_messages(&self, params: GetTaskMessagesParams) -> Result<Vec<TaskMessage>> {
+        self.message_repository
+            .get_messages(
+                &params.task_code,
+                params.author_agent_name.as_deref(),
+                params.target_agent_name.as_deref(),
+                params.message_type.as_deref(),
+                params.reply_to_message_id,
+                params.limit,
+            )
+            .await
+    }
 
-        Ok(claimed_task)
+    async fn get_task_thread(&self, params: GetTaskMessagesParams) -> Result<TaskThread> {
+        if params.author_agent_name.is_some()
+            || params.target_agent_name.is_some()
+            || params.message_type.is_some()
+            || params.reply_to_message_id.is_some()
+            || params.limit.is_some()
+        {
+            return Err(TaskError::Validation(
+                "summarize covers the whole thread and cannot be combined with filters or limit"
+                    .to_string(),
+            ));
+        }
+        summarize_thread(
+            self.message_repository.as_ref(),
+            self.thread_summarizer.as_ref(),
+            &self.thread_summary_policy,
+            &params.task_code,
+        )
+        .await
     }
 
-    async fn release_task(&self, params: ReleaseTaskParams) -> Result<Task> {
-        self.repository
-            .release_task(params.task_id, &params.agent_name)
+    async fn get_my_mentions(&self, params: GetMyMentionsParams) -> Result<Vec<TaskMention>> {
+        self.message_repository
+            .get_mentions(&params.agent_name, params.since, params.limit)
             .await
     }
 
-    async fn start_work_session(&self, params: StartWorkSessionParams) -> Result<WorkSessionInfo> {
-        let session_id = self
+    async fn get_my_work(&self, params: GetMyWorkParams) -> Result<MyWork> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        let as_of = chrono::Utc::now();
+        let limit = Some(params.limit.unwrap_or(DEFAULT_MY_WORK_LIMIT));
+        let window = chrono::Duration::minutes(i64::from(
+            params
+                .deadline_window_minutes
+                .unwrap_or(DEFAULT_DEADLINE_WINDOW_MINUTES),
+        ));
+
+        let claimed_tasks = self
             .repository
-            .start_work_session(params.task_id, &params.agent_name)
+            .list(TaskFilter {
+                owner: Some(params.agent_name.clone()),
+                state: Some(TaskState::InProgress),
+                ..TaskFilter::default()
+            })
             .await?;
-        Ok(WorkSessionInfo {
-            session_id,
-            task_id: params.task_id,
+
+        let messages = match self
+            .message_repository
+            .get_messages_for_agent(&params.agent_name, params.since, limit)
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+        let mentions = match self
+            .message_repository
+            .get_mentions(&params.agent_name, params.since, limit)
+            .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+
+        // Reviews are requested with `review` messages, as sent by the review_request template
+        let mut pending_reviews = Vec::new();
+        let in_review = self
+            .repository
+            .list(TaskFilter {
+                state: Some(TaskState::Review),
+                ..TaskFilter::default()
+            })
+            .await?;
+        for task in in_review {
+            if task.owner_agent_name.as_deref() == Some(params.agent_name.as_str()) {
+                continue;
+            }
+            let requests = self
+                .message_repository
+                .get_messages(
+                    &task.code,
+                    None,
+                    Some(&params.agent_name),
+                    Some("review"),
+                    None,
+                    Some(1),
+                )
+                .await?;
+            if !requests.is_empty() {
+                pending_reviews.push(task);
+            }
+        }
+
+        let mut upcoming_deadlines: Vec<WorkDeadline> = claimed_tasks
+            .iter()
+            .filter_map(|task| {
+                let due_at = task.lease_expires_at.filter(|due| *due <= as_of + window)?;
+                Some(WorkDeadline {
+                    task_id: task.id,
+                    task_code: task.code.clone(),
+                    due_at,
+                    kind: "lease_expiry".to_string(),
+                })
+            })
+            .collect();
+        upcoming_deadlines.sort_by_key(|deadline| deadline.due_at);
+
+        Ok(MyWork {
             agent_name: params.agent_name,
-            started_at: chrono::Utc::now(),
+            as_of,
+            claimed_tasks,
+            messages,
+            mentions,
+            pending_reviews,
+            upcoming_deadlines,
         })
     }
 
-    async fn end_work_session(&self, params: EndWorkSessionParams) -> Result<()> {
-        self.repository
-            .end_work_session(params.session_id, params.notes, params.productivity_score)
+    async fn get_updates_since(&self, params: GetUpdatesSinceParams) -> Result<AgentUpdates> {
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        let as_of = chrono::Utc::now();
+        if params.since > as_of {
+            return Err(TaskError::Validation(
+                "since must not be in the future".to_string(),
+            ));
+        }
+        let window = chrono::Duration::minutes(i64::from(
+            params
+                .deadline_window_minutes
+                .unwrap_or(DEFAULT_DEADLINE_WINDOW_MINUTES),
+        ));
+
+        let owned = self
+            .repository
+            .list(TaskFilter {
+                owner: Some(params.agent_name.clone()),
+                ..TaskFilter::default()
+            })
+            .await?;
+        // The board at `since` is only trusted when history already covered that time
+        let before = match self.repository.board_at(params.since).await {
+            Ok(board)
+                if board
+                    .history_starts_at
+                    .is_some_and(|start| start <= params.since) =>
+            {
+                Some(board)
+            }
+            Ok(_) | Err(TaskError::UnsupportedOperation(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let mentions = match self
+            .message_repository
+            .get_mentions(
+                &params.agent_name,
+                Some(params.since),
+                Some(params.limit.unwrap_or(DEFAULT_MY_WORK_LIMIT)),
+            )
             .await
+        {
+            Err(TaskError::UnsupportedOperation(_)) => Vec::new(),
+            result => result?,
+        };
+
+        Ok(AgentUpdates::new(
+            &params.agent_name,
+            params.since,
+            as_of,
+            &owned,
+            before.as_ref(),
+            mentions,
+            window,
+        ))
     }
 
-    async fn cleanup_timed_out_tasks(&self, params: CleanupTimedOutTasksParams) -> Result<Vec<Task>> {
-        self.repository
-            .cleanup_timed_out_tasks(params.timeout_minutes)
-            .await
+    async fn get_message_templates(
+        &self,
+        params: GetMessageTemplatesParams,
+    ) -> Result<Vec<MessageTemplate>> {
+        match params.name {
+            Some(name) => Ok(vec![self.message_templates.get(&name)?.clone()]),
+            None => Ok(self.message_templates.list()),
+        }
     }
 
-    // Task Messaging Implementation
+    // Task Comments Implementation
 
-    async fn create_task_message(&self, params: CreateTaskMessageParams) -> Result<TaskMessage> {
+    async fn add_task_comment(&self, params: AddTaskCommentParams) -> Result<TaskComment> {
         self.message_repository
-            .create_message(
+            .create_comment(
                 &params.task_code,
                 &params.author_agent_name,
-                params.target_agent_name.as_deref(),
-                &params.message_type,
                 &params.content,
-                params.reply_to_message_id,
             )
             .await
     }
 
-    async fn get_task_messages(&self, params: GetTaskMessagesParams) -> Result<Vec<TaskMessage>> {
+    async fn edit_task_comment(&self, params: EditTaskCommentParams) -> Result<TaskComment> {
         self.message_repository
-            .get_messages(
-                &params.task_code,
-                params.author_agent_name.as_deref(),
-                params.target_agent_name.as_deref(),
-                params.message_type.as_deref(),
-                params.reply_to_message_id,
-                params.limit,
-            )
+            .update_comment(params.comment_id, &params.agent_name, &params.content)
+            .await
+    }
+
+    async fn delete_task_comment(&self, params: DeleteTaskCommentParams) -> Result<TaskComment> {
+        self.message_repository
+            .delete_comment(params.comment_id, &params.agent_name)
+            .await
+    }
+
+    async fn get_task_comments(&self, params: GetTaskCommentsParams) -> Result<Vec<TaskComment>> {
+        self.message_repository
+            .get_comments(&params.task_code, params.include_deleted)
+            .await
+    }
+
+    async fn get_task_comment_history(
+        &self,
+        params: GetTaskCommentHistoryParams,
+    ) -> Result<Vec<TaskCommentRevision>> {
+        self.message_repository
+            .get_comment_history(params.comment_id)
             .await
     }
 
@@ -269,7 +2549,7 @@ impl<
     ) -> Result<AgenticWorkflowDescription> {
         // Get agent count (default to 3 if not specified)
         let agent_count = params.requested_agent_count.unwrap_or(3);
-        
+
         // Create static workflow description with agent count placeholder filled
         let workflow_prompt = format!(
             r#"# AI Agent Workflow Instructions
@@ -322,7 +2602,8 @@ This workflow scales effectively with {} agents working in parallel."#,
             workflow_description: workflow_prompt,
             recommended_agent_count: agent_count,
             suggested_agents: vec![], // Empty - let AI decide based on the prompt
-            task_decomposition_strategy: "MCP-based coordination with parallel execution".to_string(),
+            task_decomposition_strategy: "MCP-based coordination with parallel execution"
+                .to_string(),
             coordination_patterns: vec![
                 "Task claiming and assignment".to_string(),
                 "Message-based communication".to_string(),
@@ -335,12 +2616,13 @@ This workflow scales effectively with {} agents working in parallel."#,
                 "4. Coordinate handoffs through task assignment".to_string(),
                 "5. Complete tasks with proper state management".to_string(),
             ],
+            classification: None,
         })
     }
 
     async fn register_agent(&self, params: RegisterAgentParams) -> Result<AgentRegistration> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
+
         // Basic validation
         if params.agent_name.trim().is_empty() {
             return Err(::task_core::TaskError::Validation(
@@ -356,16 +2638,8 @@ This workflow scales effectively with {} agents working in parallel."#,
             }
         }
 
-        // Validate name format (kebab-case)
-        if !params
-            .agent_name
-            .chars()
-            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
-        {
-            return Err(::task_core::TaskError::Validation(
-                "Agent name must be in kebab-case format (lowercase letters, numbers, and hyphens only)".to_string()
-            ));
-        }
+        TaskValidator::validate_agent_name(&params.agent_name)?;
+        let capabilities = self.normalize_capabilities(&params.capabilities).await?;
 
         // Get-or-modify pattern with retry loop for race condition handling
         let mut attempt = 0u8;
@@ -379,7 +2653,9 @@ This workflow scales effectively with {} agents working in parallel."#,
             // 2. Check if context exists and prepare for modification
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 3. Duplicate-agent guard (in case another peer registered same name first)
@@ -395,19 +2671,41 @@ This workflow scales effectively with {} agents working in parallel."#,
             }
 
             // 4. Construct new AgentRegistration
-            let agent_registration = AgentRegistration {
+            let mut agent_registration = AgentRegistration {
                 name: params.agent_name.clone(),
                 description: params.description.clone().unwrap_or_default(),
                 prompt: format!("Agent: {}, Type: {}", params.agent_name, params.agent_type),
-                capabilities: params.capabilities.clone(),
+                capabilities: capabilities.clone(),
                 ai_tool_type: ::task_core::workspace_setup::AiToolType::ClaudeCode,
                 dependencies: Vec::new(),
             };
 
-            // 5. Mutate context
+            // 5. Mutate context, adopting the requested prompt style for the workspace
+            if let Some(style) = &params.style_options {
+                workspace_context.prompt_style = style.clone();
+            }
             workspace_context
                 .registered_agents
                 .push(agent_registration.clone());
+            let prd = self.workspace_prd(&workspace_context);
+            let generated = self
+                .workspace_setup_service
+                .regenerate_agent_prompt(
+                    &workspace_context,
+                    &params.agent_name,
+                    prd.as_ref(),
+                    &workspace_context.prompt_style,
+                )
+                .await
+                .map_err(|e| TaskError::Protocol(format!("Workspace setup error: {e}")))?;
+            let subject = format!("Prompt of agent '{}'", params.agent_name);
+            if let Some(warning) = generated.token_estimate.warning(&subject) {
+                tracing::warn!("{warning}");
+            }
+            agent_registration.prompt = generated.prompt;
+            if let Some(agent) = workspace_context.registered_agents.last_mut() {
+                agent.prompt = agent_registration.prompt.clone();
+            }
             workspace_context.updated_at = chrono::Utc::now();
 
             // 6. Persist with get-or-modify pattern
@@ -441,6 +2739,71 @@ This workflow scales effectively with {} agents working in parallel."#,
         }
     }
 
+    async fn regenerate_agent_prompt(
+        &self,
+        params: RegenerateAgentPromptParams,
+    ) -> Result<PromptRegeneration> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let mut attempt = 0u8;
+        loop {
+            let mut workspace_context = self
+                .workspace_context_repository
+                .get_by_id(DEFAULT_WORKSPACE_ID)
+                .await?
+                .ok_or_else(|| TaskError::UnknownAgent(params.agent_name.clone()))?;
+            if !workspace_context
+                .registered_agents
+                .iter()
+                .any(|agent| agent.name == params.agent_name)
+            {
+                return Err(TaskError::UnknownAgent(params.agent_name));
+            }
+
+            let prd = self.workspace_prd(&workspace_context);
+            let mut regeneration = self
+                .workspace_setup_service
+                .regenerate_agent_prompt(
+                    &workspace_context,
+                    &params.agent_name,
+                    prd.as_ref(),
+                    params
+                        .style_options
+                        .as_ref()
+                        .unwrap_or(&workspace_context.prompt_style),
+                )
+                .await
+                .map_err(|e| TaskError::Protocol(format!("Workspace setup error: {e}")))?;
+            if !params.apply || !regeneration.changed {
+                return Ok(regeneration);
+            }
+
+            // Store the new prompt; a concurrent change to the context restarts from its state
+            for agent in &mut workspace_context.registered_agents {
+                if agent.name == params.agent_name {
+                    agent.prompt = regeneration.prompt.clone();
+                }
+            }
+            workspace_context.updated_at = chrono::Utc::now();
+            match self
+                .workspace_context_repository
+                .update(workspace_context)
+                .await
+            {
+                Ok(_) => {
+                    regeneration.applied = true;
+                    return Ok(regeneration);
+                }
+                Err(TaskError::Conflict(_)) if attempt < MAX_ATTEMPTS => {
+                    attempt += 1;
+                    tokio::time::sleep(tokio::time::Duration::from_millis(10 * attempt as u64))
+                        .await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     async fn get_instructions_for_main_ai_file(
         &self,
         _params: GetInstructionsForMainAiFileParams,
@@ -459,19 +2822,34 @@ This workflow scales effectively with {} agents working in parallel."#,
 
     async fn create_main_ai_file(&self, params: CreateMainAiFileParams) -> Result<MainAiFileData> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
-        // Generate the main AI file (only once – outside the retry loop)
+
+        // Merge the Axon-managed sections into the current file (once – outside the retry loop)
+        let agents = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .map(|context| context.registered_agents)
+            .unwrap_or_default();
+        let existing = params.existing_content.clone().or_else(|| {
+            let root = self._project_root.as_ref()?;
+            std::fs::read_to_string(root.join("CLAUDE.md")).ok()
+        });
         let response = self
             .workspace_setup_service
-            .create_main_file(
+            .update_main_file(
+                existing.as_deref(),
                 &params.content,
                 ::task_core::workspace_setup::AiToolType::ClaudeCode,
-                None, // No project name provided in simplified interface
+                &agents,
+                params.force,
             )
             .await
             .map_err(|e| {
                 ::task_core::TaskError::Protocol(format!("Main AI file creation error: {e}"))
             })?;
+        for warning in &response.logs {
+            tracing::warn!("{warning}");
+        }
 
         // Pre-build the file metadata so it can be reused when we retry
         let file_metadata = ::task_core::workspace_setup::GeneratedFileMetadata {
@@ -493,7 +2871,9 @@ This workflow scales effectively with {} agents working in parallel."#,
 
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 2. Avoid duplicate insertion on retry
@@ -539,12 +2919,346 @@ This workflow scales effectively with {} agents working in parallel."#,
         }
     }
 
+    async fn verify_workspace(
+        &self,
+        params: VerifyWorkspaceParams,
+    ) -> Result<WorkspaceVerification> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let root = self.workspace_root(params.project_root.as_deref())?;
+        let agents = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .map(|context| context.registered_agents)
+            .unwrap_or_default();
+        let known_functions = crate::server::tool_names();
+        let schema = self.repository.migration_status().await;
+        Ok(verify_workspace(&root, &agents, &known_functions, schema))
+    }
+
+    async fn rollback_setup(&self, params: RollbackSetupParams) -> Result<SetupRollback> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+
+        let root = self.workspace_root(params.project_root.as_deref())?;
+        let Some(context) = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+        else {
+            return Ok(SetupRollback {
+                dry_run: params.dry_run,
+                ..SetupRollback::default()
+            });
+        };
+
+        let mut rollback = rollback_setup_files(&root, &context, params.dry_run);
+        if params.dry_run {
+            return Ok(rollback);
+        }
+        // Keep the context while files remain, so a retry knows what to remove
+        if rollback.failed_files.is_empty() {
+            match self
+                .workspace_context_repository
+                .delete(DEFAULT_WORKSPACE_ID)
+                .await
+            {
+                Ok(()) | Err(TaskError::NotFound(_)) => rollback.context_cleared = true,
+                Err(e) => return Err(e),
+            }
+        }
+        info!(
+            "Rolled back workspace setup: {} files removed, {} stripped of managed sections, {} agents dropped, context cleared: {}",
+            rollback.removed_files.len(),
+            rollback.stripped_files.len(),
+            rollback.removed_agents.len(),
+            rollback.context_cleared
+        );
+        Ok(rollback)
+    }
+
+    async fn get_setup_status(&self, params: GetSetupStatusParams) -> Result<SetupStatus> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+        use task_core::workspace_setup::{AiToolType, WorkspaceContext};
+
+        let ai_tool_type = match params.ai_tool_type.as_deref() {
+            None | Some("claude-code") => AiToolType::ClaudeCode,
+            Some(other) => {
+                return Err(TaskError::Validation(format!(
+                    "Unsupported ai_tool_type '{other}'; supported: claude-code"
+                )))
+            }
+        };
+        let instructions = self
+            .workspace_setup_service
+            .get_setup_instructions(ai_tool_type)
+            .await
+            .map_err(|e| TaskError::Protocol(format!("Workspace setup error: {e}")))?
+            .payload;
+        let context = self
+            .workspace_context_repository
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await?
+            .unwrap_or_else(|| WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string()));
+        Ok(context.setup_status(&instructions))
+    }
+
+    async fn setup_wizard(&self, params: SetupWizardParams) -> Result<SetupWizardState> {
+        use task_core::protocol::DEFAULT_WORKSPACE_ID;
+        use task_core::workspace_setup::{
+            AiToolType, GeneratedFileMetadata, PrdDocument, ResponseStatus, WorkspaceContext,
+            WorkspaceSetupError,
+        };
+
+        let setup_error =
+            |e: WorkspaceSetupError| TaskError::Protocol(format!("Workspace setup error: {e}"));
+        let mut attempt = 0u8;
+        loop {
+            let maybe_context = self
+                .workspace_context_repository
+                .get_by_id(DEFAULT_WORKSPACE_ID)
+                .await?;
+            let context_exists = maybe_context.is_some();
+            let mut context = maybe_context
+                .unwrap_or_else(|| WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string()));
+            let mut progress = match (&context.setup_wizard, params.restart) {
+                (Some(progress), false) => progress.clone(),
+                _ => SetupWizardProgress::new(),
+            };
+
+            if let Some(step) = params.input_step().filter(|step| *step != progress.step) {
+                return Err(TaskError::Validation(format!(
+                    "The setup wizard is at step '{}', which expects {}; '{}' input was sent",
+                    progress.step.as_str(),
+                    progress.step.expects().unwrap_or("no input"),
+                    step.as_str()
+                )));
+            }
+
+            let mut files = Vec::new();
+            match progress.step {
+                WizardStep::Prd => {
+                    if let Some(content) = &params.prd_content {
+                        let prd = PrdDocument::from_content(content).map_err(setup_error)?;
+                        let response = self
+                            .workspace_setup_service
+                            .get_agentic_workflow_description(&prd)
+                            .await
+                            .map_err(setup_error)?;
+                        if matches!(response.status, ResponseStatus::Error) {
+                            return Err(TaskError::Validation(response.message));
+                        }
+                        context.prd_content = Some(content.clone());
+                        context.workflow_data = Some(response.payload);
+                        progress.advance();
+                    }
+                }
+                WizardStep::Agents => {
+                    if let Some(names) = &params.agents {
+                        let suggested = context
+                            .workflow_data
+                            .as_ref()
+                            .map(|workflow| workflow.suggested_agents.clone())
+                            .unwrap_or_default();
+                        if names.is_empty() {
+                            return Err(TaskError::Validation(
+                                "Confirm at least one of the suggested agents".to_string(),
+                            ));
+                        }
+                        let mut chosen = Vec::new();
+                        for name in names {
+                            let agent = suggested
+                                .iter()
+                                .find(|agent| agent.name == *name)
+                                .ok_or_else(|| {
+                                    TaskError::Validation(format!(
+                                        "'{name}' is not a suggested agent"
+                                    ))
+                                })?;
+                            TaskValidator::validate_agent_name(&agent.name)?;
+                            chosen.push(agent);
+                        }
+                        for agent in &chosen {
+                            if context
+                                .registered_agents
+                                .iter()
+                                .any(|a| a.name == agent.name)
+                            {
+                                continue;
+                            }
+                            context.registered_agents.push(AgentRegistration {
+                                name: agent.name.clone(),
+                                description: agent.description.clone(),
+                                prompt: String::new(),
+                                capabilities: agent.required_capabilities.clone(),
+                                ai_tool_type: AiToolType::ClaudeCode,
+                                dependencies: agent
+                                    .depends_on
+                                    .iter()
+                                    .filter(|dependency| names.contains(dependency))
+                                    .cloned()
+                                    .collect(),
+                            });
+                        }
+                        let prd = self.workspace_prd(&context);
+                        for agent in &chosen {
+                            let generated = self
+                                .workspace_setup_service
+                                .regenerate_agent_prompt(
+                                    &context,
+                                    &agent.name,
+                                    prd.as_ref(),
+                                    &context.prompt_style,
+                                )
+                                .await
+                                .map_err(setup_error)?;
+                            if let Some(registered) = context
+                                .registered_agents
+                                .iter_mut()
+                                .find(|a| a.name == agent.name)
+                            {
+                                registered.prompt = generated.prompt;
+                                files.push(WizardFile {
+                                    path: format!(
+                                        "{AGENTS_DIR}/{}.md",
+                                        subagent_name(&registered.name)
+                                    ),
+                                    content: render_subagent_file(registered),
+                                });
+                            }
+                        }
+                        progress.advance();
+                    }
+                }
+                WizardStep::MainFile => {
+                    if let Some(content) = &params.main_file_content {
+                        let existing = self
+                            ._project_root
+                            .as_ref()
+                            .and_then(|root| std::fs::read_to_string(root.join(MAIN_AI_FILE)).ok());
+                        let response = self
+                            .workspace_setup_service
+                            .update_main_file(
+                                existing.as_deref(),
+                                content,
+                                AiToolType::ClaudeCode,
+                                &context.registered_agents,
+                                false,
+                            )
+                            .await
+                            .map_err(setup_error)?;
+                        for warning in &response.logs {
+                            tracing::warn!("{warning}");
+                        }
+                        let file = response.payload;
+                        if !context
+                            .generated_files
+                            .iter()
+                            .any(|f| f.path == file.file_name)
+                        {
+                            context.generated_files.push(GeneratedFileMetadata {
+                                path: file.file_name.clone(),
+                                description: "Main AI coordination file for Claude Code"
+                                    .to_string(),
+                                ai_tool_type: AiToolType::ClaudeCode,
+                                content_type: "text/markdown".to_string(),
+                                created_at: chrono::Utc::now(),
+                            });
+                        }
+                        files.push(WizardFile {
+                            path: file.file_name,
+                            content: file.content,
+                        });
+                        progress.advance();
+                    }
+                }
+                WizardStep::Manifest => {
+                    if params.confirm {
+                        let prd = self.workspace_prd(&context).ok_or_else(|| {
+                            TaskError::Validation(
+                                "The workspace has no valid PRD; restart the setup wizard"
+                                    .to_string(),
+                            )
+                        })?;
+                        let manifest = self
+                            .workspace_setup_service
+                            .generate_workspace_manifest(&prd, &context.registered_agents, true)
+                            .await
+                            .map_err(setup_error)?
+                            .payload;
+                        files.push(WizardFile {
+                            path: MANIFEST_FILE.to_string(),
+                            content: serde_json::to_string_pretty(&manifest)
+                                .map_err(|e| TaskError::Serialization(e.to_string()))?,
+                        });
+                        context.manifest_data = Some(manifest);
+                        progress.advance();
+                    }
+                }
+                WizardStep::Complete => {}
+            }
+
+            let data = match progress.step {
+                WizardStep::Agents => serde_json::json!({
+                    "suggested_agents": context
+                        .workflow_data
+                        .as_ref()
+                        .map(|workflow| workflow.suggested_agents.clone())
+                        .unwrap_or_default(),
+                }),
+                WizardStep::MainFile => {
+                    let instructions = self
+                        .workspace_setup_service
+                        .get_main_file_instructions(AiToolType::ClaudeCode)
+                        .await
+                        .map_err(setup_error)?
+                        .payload;
+                    serde_json::to_value(instructions)
+                        .map_err(|e| TaskError::Serialization(e.to_string()))?
+                }
+                WizardStep::Complete => serde_json::json!({
+                    "agents": context
+                        .registered_agents
+                        .iter()
+                        .map(|agent| agent.name.clone())
+                        .collect::<Vec<_>>(),
+                }),
+                WizardStep::Prd | WizardStep::Manifest => serde_json::Value::Null,
+            };
+            let state = SetupWizardState::new(&progress, data, files);
+
+            context.setup_wizard = Some(progress);
+            context.updated_at = chrono::Utc::now();
+            let write_result = if context_exists {
+                self.workspace_context_repository.update(context).await
+            } else {
+                self.workspace_context_repository.create(context).await
+            };
+            match write_result {
+                Ok(_) => return Ok(state),
+                Err(TaskError::DuplicateKey(_)) | Err(TaskError::Conflict(_)) => {
+                    if attempt >= MAX_ATTEMPTS {
+                        return Err(TaskError::Conflict(format!(
+                            "Workspace concurrently modified after {MAX_ATTEMPTS} attempts; please retry"
+                        )));
+                    }
+                    attempt += 1;
+                    tokio::time::sleep(tokio::time::Duration::from_millis(10 * attempt as u64))
+                        .await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ::task_core::{RepositoryStats, TaskFilter, TaskState, UpdateTask};
+    use ::task_core::{
+        RepositoryStats, TaskCrudRepository, TaskExtensionRepository, TaskFilter, TaskState,
+        UpdateTask, WorkClaimRepository, WorkSessionRepository,
+    };
     use mockall::mock;
     use mockall::predicate::*;
 
@@ -552,7 +3266,7 @@ mod tests {
         TestRepository {}
 
         #[async_trait]
-        impl TaskRepository for TestRepository {
+        impl TaskCrudRepository for TestRepository {
             async fn create(&self, task: NewTask) -> Result<Task>;
             async fn update(&self, id: i32, updates: UpdateTask) -> Result<Task>;
             async fn set_state(&self, id: i32, state: TaskState) -> Result<Task>;
@@ -563,13 +3277,26 @@ mod tests {
             async fn archive(&self, id: i32) -> Result<Task>;
             async fn health_check(&self) -> Result<()>;
             async fn get_stats(&self) -> Result<RepositoryStats>;
+            async fn apply_operations(&self, operations: &[TaskOperation]) -> Result<Vec<TaskOperationOutcome>>;
+        }
+
+        #[async_trait]
+        impl WorkClaimRepository for TestRepository {
             async fn discover_work(&self, agent_name: &str, capabilities: &[String], max_tasks: u32) -> Result<Vec<Task>>;
             async fn claim_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
             async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
+            async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
+            async fn timed_out_claims(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<TaskClaim>>;
+        }
+
+        #[async_trait]
+        impl WorkSessionRepository for TestRepository {
             async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32>;
             async fn end_work_session(&self, session_id: i32, notes: Option<String>, productivity_score: Option<f64>) -> Result<()>;
-            async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
         }
+
+        #[async_trait]
+        impl TaskExtensionRepository for TestRepository {}
     }
 
     // Simple mock that implements both traits for testing
@@ -585,9 +3312,11 @@ mod tests {
             message_type: &str,
             content: &str,
             reply_to_message_id: Option<i32>,
+            structured_data: Option<&serde_json::Value>,
         ) -> Result<TaskMessage> {
             Ok(TaskMessage {
                 id: 1,
+                uid: None,
                 task_code: task_code.to_string(),
                 author_agent_name: author_agent_name.to_string(),
                 target_agent_name: None,
@@ -595,6 +3324,7 @@ mod tests {
                 created_at: chrono::Utc::now(),
                 content: content.to_string(),
                 reply_to_message_id,
+                structured_data: structured_data.cloned(),
             })
         }
 
@@ -659,4 +3389,420 @@ mod tests {
         // Basic test that handler can be created
         // Test passes if handler creation doesn't panic
     }
+
+    #[tokio::test]
+    async fn test_get_board_at_rejects_future_timestamps() {
+        let handler = McpTaskHandler::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+
+        let future = chrono::Utc::now() + chrono::Duration::hours(1);
+        let result = handler
+            .get_board_at(GetBoardAtParams { timestamp: future })
+            .await;
+        assert!(matches!(result, Err(TaskError::Validation(_))));
+
+        // Repositories without event history report it as unsupported
+        let past = chrono::Utc::now() - chrono::Duration::hours(1);
+        let result = handler
+            .get_board_at(GetBoardAtParams { timestamp: past })
+            .await;
+        assert!(matches!(result, Err(TaskError::UnsupportedOperation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_diff_workspace_validates_period() {
+        let handler = McpTaskHandler::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+        let now = chrono::Utc::now();
+
+        for (from, to) in [
+            (
+                now - chrono::Duration::hours(1),
+                now - chrono::Duration::hours(2),
+            ),
+            (
+                now - chrono::Duration::hours(1),
+                now + chrono::Duration::hours(1),
+            ),
+        ] {
+            let result = handler
+                .diff_workspace(DiffWorkspaceParams {
+                    from,
+                    to,
+                    max_messages: None,
+                })
+                .await;
+            assert!(matches!(result, Err(TaskError::Validation(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocker_message_blocks_task() {
+        let task = Task::new(
+            1,
+            "API-01".to_string(),
+            "Build API".to_string(),
+            "Implement the endpoints".to_string(),
+            Some("backend-dev".to_string()),
+            TaskState::InProgress,
+            chrono::Utc::now(),
+            None,
+        );
+        let mut blocked = task.clone();
+        blocked.state = TaskState::Blocked;
+
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_get_by_code()
+            .with(eq("API-01"))
+            .returning(move |_| Ok(Some(task.clone())));
+        mock_repo
+            .expect_set_state()
+            .with(eq(1), eq(TaskState::Blocked))
+            .times(1)
+            .returning(move |_, _| Ok(blocked.clone()));
+
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_message_effects(vec![MessageEffectRule {
+            message_type: "blocker".to_string(),
+            effect: MessageEffect::SetState {
+                state: TaskState::Blocked,
+            },
+        }]);
+
+        for message_type in ["comment", "blocker"] {
+            handler
+                .create_task_message(CreateTaskMessageParams {
+                    task_code: "API-01".to_string(),
+                    author_agent_name: "backend-dev".to_string(),
+                    target_agent_name: None,
+                    message_type: message_type.to_string(),
+                    content: "Waiting for the schema".to_string(),
+                    reply_to_message_id: None,
+                    structured_data: None,
+                    template: None,
+                    template_values: Default::default(),
+                })
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handoff_acceptance_needs_an_offer() {
+        let task = |code: &str, owner: Option<&str>| {
+            Task::new(
+                1,
+                code.to_string(),
+                "Build API".to_string(),
+                "Implement the endpoints".to_string(),
+                owner.map(str::to_string),
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            )
+        };
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_get_by_code()
+            .with(eq("API-01"))
+            .returning(move |_| Ok(Some(task("API-01", Some("backend-dev")))));
+        mock_repo
+            .expect_get_by_code()
+            .with(eq("API-02"))
+            .returning(move |_| Ok(Some(task("API-02", None))));
+        // Only the unowned task changes hands
+        mock_repo
+            .expect_assign()
+            .with(eq(1), eq("qa-bot"))
+            .times(1)
+            .returning(move |_, _| Ok(task("API-02", Some("qa-bot"))));
+
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_message_effects(vec![MessageEffectRule {
+            message_type: "handoff_accepted".to_string(),
+            effect: MessageEffect::AssignToAuthor,
+        }]);
+
+        for task_code in ["API-01", "API-02"] {
+            handler
+                .create_task_message(CreateTaskMessageParams {
+                    task_code: task_code.to_string(),
+                    author_agent_name: "qa-bot".to_string(),
+                    target_agent_name: None,
+                    message_type: "handoff_accepted".to_string(),
+                    content: "Taking it".to_string(),
+                    reply_to_message_id: Some(7),
+                    structured_data: None,
+                    template: None,
+                    template_values: Default::default(),
+                })
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_done_policy_rejects_unchecked_checklist() {
+        let task = Task::new(
+            1,
+            "API-01".to_string(),
+            "Build API".to_string(),
+            "- [x] Endpoints\n- [ ] Tests".to_string(),
+            Some("backend-dev".to_string()),
+            TaskState::InProgress,
+            chrono::Utc::now(),
+            None,
+        );
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_get_by_id()
+            .with(eq(1))
+            .returning(move |_| Ok(Some(task.clone())));
+
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_done_policy(DonePolicy {
+            require_checklist: true,
+            ..DonePolicy::default()
+        });
+
+        let result = handler
+            .set_task_state(SetStateParams {
+                id: 1,
+                state: TaskState::Done,
+            })
+            .await;
+        match result {
+            Err(TaskError::UnmetRequirements(code, unmet)) => {
+                assert_eq!(code, "API-01");
+                assert_eq!(unmet.len(), 1);
+                assert_eq!(unmet[0].detail, "unchecked checklist items: Tests");
+            }
+            other => panic!("expected unmet requirements, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_done_stage_releases_next_pipeline_stage() {
+        let task = |id: i32, code: &str, parent: Option<i32>, state: TaskState| {
+            let mut task = Task::new(
+                id,
+                code.to_string(),
+                code.to_string(),
+                "Pipeline fixture".to_string(),
+                Some("architect".to_string()),
+                state,
+                chrono::Utc::now(),
+                None,
+            );
+            task.parent_task_id = parent;
+            task
+        };
+        let pipeline = task(1, "FEAT-1", None, TaskState::WaitingForDependency);
+        let design = task(2, "FEAT-1-1", Some(1), TaskState::Done);
+        let mut implement = task(3, "FEAT-1-2", Some(1), TaskState::WaitingForDependency);
+        implement.owner_agent_name = None;
+        let released = Task {
+            state: TaskState::Created,
+            ..implement.clone()
+        };
+
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_set_state()
+            .with(eq(2), eq(TaskState::Done))
+            .returning(move |_, _| Ok(design.clone()));
+        mock_repo
+            .expect_get_by_id()
+            .with(eq(1))
+            .returning(move |_| Ok(Some(pipeline.clone())));
+        mock_repo
+            .expect_get_by_code()
+            .with(eq("FEAT-1-2"))
+            .returning(move |_| Ok(Some(implement.clone())));
+        mock_repo
+            .expect_apply_operations()
+            .withf(|operations| {
+                operations[0]
+                    == TaskOperation::SetState {
+                        id: 3,
+                        state: TaskState::Created,
+                    }
+                    && matches!(
+                        &operations[1],
+                        TaskOperation::Message { task_code, message_type, content, .. }
+                            if task_code == "FEAT-1-2"
+                                && message_type == STAGE_READY_MESSAGE_TYPE
+                                && content.contains("FEAT-1-1")
+                    )
+            })
+            .times(1)
+            .returning(move |_| Ok(vec![TaskOperationOutcome::Task(released.clone())]));
+        mock_repo.expect_list().returning(|_| Ok(vec![]));
+
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+        let task = handler
+            .set_task_state(SetStateParams {
+                id: 2,
+                state: TaskState::Done,
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.code, "FEAT-1-1");
+    }
+
+    #[tokio::test]
+    async fn test_completing_last_task_writes_final_report() {
+        let mut done = Task::new(
+            1,
+            "API-01".to_string(),
+            "Build API".to_string(),
+            String::new(),
+            Some("backend-dev".to_string()),
+            TaskState::Done,
+            chrono::Utc::now(),
+            None,
+        );
+        done.done_at = Some(chrono::Utc::now());
+        let listed = done.clone();
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_set_state()
+            .with(eq(1), eq(TaskState::Done))
+            .returning(move |_, _| Ok(done.clone()));
+        mock_repo
+            .expect_list()
+            .returning(move |_| Ok(vec![listed.clone()]));
+
+        let root = tempfile::tempdir().unwrap();
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            Some(root.path().to_path_buf()),
+        );
+        let params = GetFinalReportParams::default();
+        assert!(matches!(
+            handler.get_final_report(params.clone()).await,
+            Err(TaskError::NotFound(_))
+        ));
+        let elsewhere = tempfile::tempdir().unwrap();
+        let outside = GetFinalReportParams {
+            project_root: Some(elsewhere.path().display().to_string()),
+        };
+        assert!(matches!(
+            handler.get_final_report(outside).await,
+            Err(TaskError::Validation(_))
+        ));
+
+        let mut state_changes = handler.subscribe_state_changes();
+        handler
+            .set_task_state(SetStateParams {
+                id: 1,
+                state: TaskState::Done,
+            })
+            .await
+            .unwrap();
+        assert_eq!(state_changes.try_recv().unwrap().state, TaskState::Done);
+        let report = handler.get_final_report(params).await.unwrap();
+        assert_eq!(report.tasks_done, 1);
+        assert_eq!(report.timeline.tasks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_claim_affinity_after_timeout() {
+        let now = chrono::Utc::now();
+        let tasks: Vec<Task> = (1..=3)
+            .map(|id| {
+                Task::new(
+                    id,
+                    format!("API-0{id}"),
+                    "Build API".to_string(),
+                    "Implement the endpoints".to_string(),
+                    None,
+                    TaskState::Created,
+                    now,
+                    None,
+                )
+            })
+            .collect();
+
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_timed_out_claims()
+            .times(3)
+            .returning(move |_| {
+                Ok(vec![TaskClaim {
+                    task_id: 2,
+                    agent_name: "backend-dev".to_string(),
+                    claimed_at: now - chrono::Duration::hours(3),
+                    released_at: Some(now),
+                    release_reason: Some("timed_out".to_string()),
+                }])
+            });
+        mock_repo
+            .expect_discover_work()
+            .times(2)
+            .returning(move |_, _, _| Ok(tasks.clone()));
+        let handler = McpTaskHandler::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+        let discover = |agent_name: &str| DiscoverWorkParams {
+            agent_name: agent_name.to_string(),
+            capabilities: Vec::new(),
+            max_tasks: Some(2),
+        };
+        let ids = |tasks: Vec<Task>| tasks.iter().map(|task| task.id).collect::<Vec<_>>();
+
+        // The previous owner sees the task first, everyone else not at all
+        let owner = handler
+            .discover_work(discover("backend-dev"))
+            .await
+            .unwrap();
+        assert_eq!(ids(owner), [2, 1]);
+        let other = handler.discover_work(discover("qa-bot")).await.unwrap();
+        assert_eq!(ids(other), [1, 3]);
+
+        let result = handler
+            .claim_task(ClaimTaskParams {
+                task_id: 2,
+                agent_name: "qa-bot".to_string(),
+                lease_minutes: None,
+            })
+            .await;
+        assert!(
+            matches!(result, Err(TaskError::Conflict(message)) if message.contains("backend-dev"))
+        );
+    }
 }
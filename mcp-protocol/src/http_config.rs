@@ -0,0 +1,176 @@
+//! HTTP transport tuning for the MCP server
+//!
+//! Controls response compression, connection keep-alive, request timeouts,
+//! CORS and session expiry on the axum router. Defaults are tuned for chatty
+//! local agents that keep a connection open and exchange large task lists.
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+/// HTTP transport configuration for [`crate::McpServer`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpConfig {
+    /// Compress responses with gzip/deflate when the client sends `Accept-Encoding`
+    pub compression: bool,
+    /// Keep HTTP/1.1 connections open between requests
+    pub keep_alive: bool,
+    /// Seconds an idle keep-alive connection may wait for the next request (0 = no limit)
+    pub keep_alive_timeout_secs: u64,
+    /// Seconds a single request may take before the server answers 408 (0 = no limit)
+    pub request_timeout_secs: u64,
+    /// Cross-origin access for browser-hosted MCP clients
+    pub cors: CorsConfig,
+    /// Seconds of inactivity after which an `Mcp-Session-Id` expires
+    pub session_ttl_secs: u64,
+    /// Reject requests other than `initialize` that carry no `Mcp-Session-Id`
+    pub require_session: bool,
+}
+
+/// CORS policy for browser-based dashboards and web MCP clients
+///
+/// CORS is disabled while `allowed_origins` is empty. Use `"*"` to allow any origin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to call the server, e.g. `http://localhost:5173`
+    pub allowed_origins: Vec<String>,
+    /// Request headers browsers may send
+    pub allowed_headers: Vec<String>,
+    /// HTTP methods browsers may use
+    pub allowed_methods: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_headers: vec![
+                "content-type".to_string(),
+                "authorization".to_string(),
+                "mcp-protocol-version".to_string(),
+                "mcp-session-id".to_string(),
+                "x-agent-name".to_string(),
+                "x-agent-token".to_string(),
+            ],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string(), "DELETE".to_string()],
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            compression: true,
+            keep_alive: true,
+            keep_alive_timeout_secs: 75,
+            request_timeout_secs: 30,
+            cors: CorsConfig::default(),
+            session_ttl_secs: 3600,
+            require_session: false,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Idle keep-alive timeout, or `None` when disabled
+    pub fn keep_alive_timeout(&self) -> Option<Duration> {
+        (self.keep_alive_timeout_secs > 0)
+            .then(|| Duration::from_secs(self.keep_alive_timeout_secs))
+    }
+
+    /// Per-request timeout, or `None` when disabled
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
+}
+
+impl CorsConfig {
+    /// Whether any cross-origin access is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    /// Check that every origin, header and method can be used in an HTTP header
+    pub fn validate(&self) -> Result<(), String> {
+        for origin in self.allowed_origins.iter().filter(|o| o.as_str() != "*") {
+            HeaderValue::from_str(origin).map_err(|_| format!("Invalid CORS origin: {origin}"))?;
+        }
+        for header in &self.allowed_headers {
+            HeaderName::from_bytes(header.as_bytes())
+                .map_err(|_| format!("Invalid CORS header: {header}"))?;
+        }
+        for method in &self.allowed_methods {
+            Method::from_bytes(method.as_bytes())
+                .map_err(|_| format!("Invalid CORS method: {method}"))?;
+        }
+        Ok(())
+    }
+
+    /// Build the tower-http layer; entries that fail to parse are skipped
+    pub fn to_layer(&self) -> CorsLayer {
+        let allow_origin = if self.allowed_origins.iter().any(|o| o == "*") {
+            AllowOrigin::any()
+        } else {
+            AllowOrigin::list(self.allowed_origins.iter().filter_map(|origin| {
+                HeaderValue::from_str(origin)
+                    .inspect_err(|_| warn!("Ignoring invalid CORS origin: {}", origin))
+                    .ok()
+            }))
+        };
+
+        let headers: Vec<HeaderName> = self
+            .allowed_headers
+            .iter()
+            .filter_map(|header| HeaderName::from_bytes(header.as_bytes()).ok())
+            .collect();
+        let methods: Vec<Method> = self
+            .allowed_methods
+            .iter()
+            .filter_map(|method| Method::from_bytes(method.as_bytes()).ok())
+            .collect();
+
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_headers(headers)
+            .allow_methods(methods)
+            .expose_headers([
+                HeaderName::from_static("mcp-protocol-version"),
+                HeaderName::from_static("mcp-session-id"),
+            ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_disables_timeouts() {
+        let config = HttpConfig {
+            keep_alive_timeout_secs: 0,
+            request_timeout_secs: 0,
+            ..HttpConfig::default()
+        };
+        assert_eq!(config.keep_alive_timeout(), None);
+        assert_eq!(config.request_timeout(), None);
+
+        let defaults = HttpConfig::default();
+        assert_eq!(defaults.request_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_cors_disabled_by_default() {
+        let cors = CorsConfig::default();
+        assert!(!cors.is_enabled());
+        assert!(cors.validate().is_ok());
+
+        let invalid = CorsConfig {
+            allowed_methods: vec!["GET POST".to_string()],
+            ..CorsConfig::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
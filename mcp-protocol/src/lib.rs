@@ -22,14 +22,14 @@
 //! async fn start_server() -> Result<(), Box<dyn std::error::Error>> {
 //!     // In real usage, you would use database::SqliteTaskRepository
 //!     // let repository = Arc::new(database::SqliteTaskRepository::new("tasks.db").await?);
-//!     # use task_core::{TaskRepository, TaskMessageRepository, WorkspaceContextRepository, Task, TaskMessage, NewTask, UpdateTask, TaskFilter, TaskState, RepositoryStats};
+//!     # use task_core::{TaskCrudRepository, WorkClaimRepository, WorkSessionRepository, TaskExtensionRepository, TaskMessageRepository, WorkspaceContextRepository, Task, TaskMessage, NewTask, UpdateTask, TaskFilter, TaskState, RepositoryStats};
 //!     # use task_core::error::Result;
 //!     # use async_trait::async_trait;
 //!     # struct MockRepo;
 //!     # struct MockMessageRepo;
 //!     # struct MockWorkspaceRepo;
 //!     # #[async_trait]
-//!     # impl TaskRepository for MockRepo {
+//!     # impl TaskCrudRepository for MockRepo {
 //!     #     async fn create(&self, _task: NewTask) -> Result<Task> { unimplemented!() }
 //!     #     async fn update(&self, _id: i32, _updates: UpdateTask) -> Result<Task> { unimplemented!() }
 //!     #     async fn set_state(&self, _id: i32, _state: TaskState) -> Result<Task> { unimplemented!() }
@@ -40,16 +40,23 @@
 //!     #     async fn archive(&self, _id: i32) -> Result<Task> { unimplemented!() }
 //!     #     async fn health_check(&self) -> Result<()> { unimplemented!() }
 //!     #     async fn get_stats(&self) -> Result<RepositoryStats> { unimplemented!() }
+//!     # }
+//!     # #[async_trait]
+//!     # impl WorkClaimRepository for MockRepo {
 //!     #     async fn discover_work(&self, _agent_name: &str, _capabilities: &[String], _max_tasks: u32) -> Result<Vec<Task>> { unimplemented!() }
 //!     #     async fn claim_task(&self, _task_id: i32, _agent_name: &str) -> Result<Task> { unimplemented!() }
 //!     #     async fn release_task(&self, _task_id: i32, _agent_name: &str) -> Result<Task> { unimplemented!() }
+//!     #     async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> { unimplemented!() }
+//!     # }
+//!     # #[async_trait]
+//!     # impl WorkSessionRepository for MockRepo {
 //!     #     async fn start_work_session(&self, _task_id: i32, _agent_name: &str) -> Result<i32> { unimplemented!() }
 //!     #     async fn end_work_session(&self, _session_id: i32, _notes: Option<String>, _productivity_score: Option<f64>) -> Result<()> { unimplemented!() }
-//!     #     async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> { unimplemented!() }
 //!     # }
+//!     # impl TaskExtensionRepository for MockRepo {}
 //!     # #[async_trait]
 //!     # impl TaskMessageRepository for MockMessageRepo {
-//!     #     async fn create_message(&self, _task_code: &str, _author_agent_name: &str, _target_agent_name: Option<&str>, _message_type: &str, _content: &str, _reply_to_message_id: Option<i32>) -> Result<TaskMessage> { unimplemented!() }
+//!     #     async fn create_message(&self, _task_code: &str, _author_agent_name: &str, _target_agent_name: Option<&str>, _message_type: &str, _content: &str, _reply_to_message_id: Option<i32>, _structured_data: Option<&serde_json::Value>) -> Result<TaskMessage> { unimplemented!() }
 //!     #     async fn get_messages(&self, _task_code: &str, _author_agent_name: Option<&str>, _target_agent_name: Option<&str>, _message_type: Option<&str>, _reply_to_message_id: Option<i32>, _limit: Option<u32>) -> Result<Vec<TaskMessage>> { unimplemented!() }
 //!     #     async fn get_message_by_id(&self, _message_id: i32) -> Result<Option<TaskMessage>> { unimplemented!() }
 //!     # }
@@ -70,19 +77,56 @@
 //! }
 //! ```
 
+// The tools/list schema is a single json! literal that outgrows the default limit
+#![recursion_limit = "256"]
+
+pub mod admin;
+pub mod agent_identity;
+pub mod api_spec;
+pub mod background_jobs;
+pub mod claude_settings;
+pub mod client_codegen;
+pub mod client_requests;
+pub mod elicitation;
 pub mod error;
 pub mod handler;
+pub mod http_config;
+pub mod listeners;
+pub mod method_timeouts;
+pub mod notifications;
+pub mod protocol_version;
+pub mod read_only;
+pub mod recorder;
 pub mod request_logger;
+pub mod roots;
+pub mod runaway_agents;
+pub mod sampling;
 // pub mod rmcp_handler; // Temporarily disabled - complex trait issues
 pub mod serialization;
 pub mod server;
+pub mod session;
 
 // Re-export key types for easier usage
+pub use admin::{AdminAuth, ADMIN_METHODS};
+pub use agent_identity::{AgentAuth, Caller};
+pub use api_spec::{openapi_document, standalone_schemas, tool_specs, write_api_spec, ToolSpec};
+pub use background_jobs::{JobOutcome, JobRegistry, JobStatus};
+pub use client_codegen::{generate_client, ClientLanguage};
+pub use client_requests::ClientRequests;
 pub use error::*;
-pub use handler::McpTaskHandler;
+pub use handler::{McpTaskHandler, STATE_CHANGE_CAPACITY};
+pub use http_config::{CorsConfig, HttpConfig};
+pub use listeners::ListenerConfig;
+pub use method_timeouts::{MethodKind, MethodTimeouts, TimeoutMetrics};
+pub use notifications::{NotificationHub, ServerNotification};
+pub use protocol_version::ProtocolVersion;
+pub use recorder::{read_recording, RecordedExchange, Recorder};
+pub use runaway_agents::{RunawayDetector, RunawayPattern, RunawayPolicy};
+pub use sampling::{on_behalf_of, ClientSampler, SamplingRequest};
 // pub use rmcp_handler::RmcpTaskHandler; // Temporarily disabled
 pub use serialization::*;
-pub use server::McpServer;
+pub use server::{tool_definitions, tool_names, McpServer};
+pub use session::{McpSession, SessionStore, MAX_SESSION_TTL_SECS};
 
 // Re-export core types for external consumers
 pub use task_core::{
@@ -0,0 +1,108 @@
+//! Additional listen addresses with their own access requirements
+//!
+//! Besides its main address, the server can listen on further addresses, e.g.
+//! loopback for a local agent and a tailnet IP for a teammate's agent. All
+//! listeners serve the same board and sessions; each one may additionally
+//! require an access token, an agent identity on mutations, or serve lookups
+//! only.
+
+use crate::admin::{constant_time_eq, AdminAuth};
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// A listen address and the requirements applied to requests arriving on it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListenerConfig {
+    /// Socket address to bind, e.g. `100.64.0.7:3000`
+    pub address: String,
+    /// Token every request must present as `Authorization: Bearer` (the admin key is accepted too)
+    pub access_token: Option<String>,
+    /// Reject task and session mutations from callers that name no agent
+    pub require_identity: bool,
+    /// Serve lookups only, rejecting every tool that changes the board
+    pub read_only: bool,
+}
+
+impl ListenerConfig {
+    /// Whether `presented` (a bearer token) grants access to this listener
+    fn admits(&self, presented: Option<&str>) -> bool {
+        match self
+            .access_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+        {
+            Some(expected) => presented.is_some_and(|token| constant_time_eq(token, expected)),
+            None => true,
+        }
+    }
+}
+
+/// Requirements of the listener a request arrived on; admin clients pass any listener
+#[derive(Debug, Clone, Default)]
+struct ListenerGuard {
+    listener: ListenerConfig,
+    admin_auth: AdminAuth,
+}
+
+/// Wrap the server's router with the requirements of one listener
+pub(crate) fn guard_router(
+    router: Router,
+    listener: ListenerConfig,
+    admin_auth: AdminAuth,
+) -> Router {
+    let guard = Arc::new(ListenerGuard {
+        listener,
+        admin_auth,
+    });
+    router.layer(middleware::from_fn_with_state(guard, enforce_listener))
+}
+
+/// Reject requests without the listener's access token and tag the rest with its requirements
+///
+/// `/health` stays open so load balancers and healthchecks need no token.
+async fn enforce_listener(
+    State(guard): State<Arc<ListenerGuard>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    let admitted =
+        guard.listener.admits(presented) || guard.admin_auth.is_admin_request(request.headers());
+    if request.uri().path() != "/health" && !admitted {
+        let error = json!({"error": "this listener requires an access token"});
+        return (StatusCode::UNAUTHORIZED, Json(error)).into_response();
+    }
+    request.extensions_mut().insert(guard.listener.clone());
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listener_admits() {
+        let open = ListenerConfig::default();
+        assert!(open.admits(None));
+
+        let guarded = ListenerConfig {
+            access_token: Some("tailnet-secret".to_string()),
+            ..ListenerConfig::default()
+        };
+        assert!(!guarded.admits(None));
+        assert!(!guarded.admits(Some("wrong")));
+        assert!(guarded.admits(Some("tailnet-secret")));
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-method execution budgets
+//!
+//! Every MCP method runs under a time budget chosen by its kind: quick reads,
+//! task mutations, and slower workspace generation. A method that overruns its
+//! budget is cancelled, answered with a typed timeout error and counted in
+//! [`TimeoutMetrics`], so a stuck SQLite query cannot hang an agent forever.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Methods that never modify state
+pub const READ_ONLY_METHODS: &[&str] = &[
+    "get_task_by_id",
+    "get_task_by_code",
+    "get_task_by_uid",
+    "get_tasks_by_ids",
+    "get_tasks_by_codes",
+    "list_tasks",
+    "count_tasks",
+    "aggregate_tasks",
+    "health_check",
+    "discover_work",
+    "match_agents_to_task",
+    "explain_task_routing",
+    "find_duplicate_tasks",
+    "list_artifacts",
+    "get_final_report",
+    "get_critical_path",
+    "export_timeline",
+    "export_tasks_csv",
+    "export_sessions_csv",
+    "get_task_messages",
+    "get_task_comments",
+    "get_task_comment_history",
+    "get_message_templates",
+    "get_my_mentions",
+    "get_my_work",
+    "get_updates_since",
+    "get_session_log",
+    "get_system_events",
+    "get_tool_usage_stats",
+    "get_board_at",
+    "diff_workspace",
+    "get_retention_report",
+    "get_migration_status",
+    "list_capabilities",
+    "get_setup_instructions",
+    "get_agentic_workflow_description",
+    "get_instructions_for_main_ai_file",
+    "get_setup_status",
+    "verify_workspace",
+    "list_background_jobs",
+    "run_readonly_query",
+    "tools/list",
+];
+
+/// Methods that enforce a timeout given in their parameters; only an override limits them
+pub const SELF_TIMED_METHODS: &[&str] = &["run_readonly_query"];
+
+/// Methods that generate workspace files or agent definitions
+pub const WORKSPACE_METHODS: &[&str] = &[
+    "get_setup_instructions",
+    "get_agentic_workflow_description",
+    "register_agent",
+    "get_instructions_for_main_ai_file",
+    "create_main_ai_file",
+    "verify_workspace",
+    "setup_wizard",
+    "get_setup_status",
+    "rollback_setup",
+    "scan_code_todos",
+    "sync_tasks_markdown",
+];
+
+/// Budget class of an MCP method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    /// Lookups and listings
+    Read,
+    /// Task and message mutations
+    Write,
+    /// Workspace setup and file generation
+    Workspace,
+}
+
+impl MethodKind {
+    /// Classify a method by name; unknown methods are treated as writes
+    pub fn of(method: &str) -> Self {
+        if WORKSPACE_METHODS.contains(&method) {
+            Self::Workspace
+        } else if READ_ONLY_METHODS.contains(&method) {
+            Self::Read
+        } else {
+            Self::Write
+        }
+    }
+}
+
+/// Execution budgets in seconds (0 disables the limit)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MethodTimeouts {
+    /// Budget for read-only methods
+    pub read_timeout: u64,
+    /// Budget for methods that modify tasks or messages
+    pub write_timeout: u64,
+    /// Budget for workspace setup and file generation
+    pub workspace_timeout: u64,
+    /// Budgets for individual methods, taking precedence over their kind
+    pub overrides: HashMap<String, u64>,
+}
+
+impl Default for MethodTimeouts {
+    fn default() -> Self {
+        Self {
+            read_timeout: 2,
+            write_timeout: 5,
+            workspace_timeout: 10,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl MethodTimeouts {
+    /// Budget for `method`, or `None` when unlimited
+    pub fn budget_for(&self, method: &str) -> Option<Duration> {
+        let secs = match self.overrides.get(method) {
+            Some(secs) => *secs,
+            None if SELF_TIMED_METHODS.contains(&method) => 0,
+            None => match MethodKind::of(method) {
+                MethodKind::Read => self.read_timeout,
+                MethodKind::Write => self.write_timeout,
+                MethodKind::Workspace => self.workspace_timeout,
+            },
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+/// Overrun counters per method, shared across requests
+#[derive(Debug, Clone, Default)]
+pub struct TimeoutMetrics {
+    overruns: Arc<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl TimeoutMetrics {
+    /// Count one budget overrun of `method`
+    pub fn record_overrun(&self, method: &str) {
+        *self
+            .overruns
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Overrun counts per method
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.overruns.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_by_kind_and_override() {
+        let mut timeouts = MethodTimeouts::default();
+        assert_eq!(
+            timeouts.budget_for("list_tasks"),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            timeouts.budget_for("create_task"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            timeouts.budget_for("create_main_ai_file"),
+            Some(Duration::from_secs(10))
+        );
+
+        assert_eq!(timeouts.budget_for("run_readonly_query"), None);
+
+        timeouts.overrides.insert("list_tasks".to_string(), 0);
+        assert_eq!(timeouts.budget_for("list_tasks"), None);
+        timeouts
+            .overrides
+            .insert("run_readonly_query".to_string(), 60);
+        assert_eq!(
+            timeouts.budget_for("run_readonly_query"),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_overrun_metrics() {
+        let metrics = TimeoutMetrics::default();
+        metrics.record_overrun("list_tasks");
+        metrics.record_overrun("list_tasks");
+        assert_eq!(metrics.snapshot().get("list_tasks"), Some(&2));
+    }
+}
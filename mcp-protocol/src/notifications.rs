@@ -0,0 +1,155 @@
+//! Server-to-client notifications over SSE
+//!
+//! Notifications are JSON-RPC messages without an `id`, delivered on the
+//! `GET /mcp` event stream of a session (or the legacy `/mcp/v1` stream).
+//! Every notification gets a monotonically increasing SSE event id and a
+//! bounded history is kept so clients reconnecting with `Last-Event-ID`
+//! receive what they missed. Requests to the client, such as sampling
+//! requests, travel the same way.
+
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::broadcast;
+
+/// Number of notifications retained for `Last-Event-ID` replay
+pub const DEFAULT_REPLAY_CAPACITY: usize = 256;
+
+/// A notification queued for delivery
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerNotification {
+    /// SSE event id, unique and increasing for the server lifetime
+    pub event_id: u64,
+    /// Target session, or `None` to broadcast to every stream
+    pub session_id: Option<String>,
+    /// Complete JSON-RPC message: a notification, or a request to the client
+    pub message: Value,
+}
+
+impl ServerNotification {
+    /// Whether a stream opened for `session_id` should receive this notification
+    pub fn is_visible_to(&self, session_id: Option<&str>) -> bool {
+        match &self.session_id {
+            None => true,
+            Some(target) => Some(target.as_str()) == session_id,
+        }
+    }
+}
+
+/// Fan-out hub shared by request handlers and open SSE streams
+#[derive(Debug, Clone)]
+pub struct NotificationHub {
+    sender: broadcast::Sender<ServerNotification>,
+    history: Arc<Mutex<VecDeque<ServerNotification>>>,
+    next_event_id: Arc<AtomicU64>,
+    capacity: usize,
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_CAPACITY)
+    }
+}
+
+impl NotificationHub {
+    /// Create a hub that keeps the last `capacity` notifications for replay
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            next_event_id: Arc::new(AtomicU64::new(1)),
+            capacity,
+        }
+    }
+
+    /// Publish a JSON-RPC notification; returns its SSE event id
+    pub fn publish(&self, session_id: Option<String>, method: &str, params: Value) -> u64 {
+        self.publish_message(
+            session_id,
+            json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+            }),
+        )
+    }
+
+    /// Publish a complete JSON-RPC message, e.g. a request to the client;
+    /// returns its SSE event id
+    pub fn publish_message(&self, session_id: Option<String>, message: Value) -> u64 {
+        let notification = ServerNotification {
+            event_id: self.next_event_id.fetch_add(1, Ordering::SeqCst),
+            session_id,
+            message,
+        };
+
+        {
+            let mut history = self.history.lock().unwrap();
+            if history.len() == self.capacity {
+                history.pop_front();
+            }
+            history.push_back(notification.clone());
+        }
+
+        // No open streams is not an error; the history still allows replay
+        let _ = self.sender.send(notification.clone());
+        notification.event_id
+    }
+
+    /// Subscribe to notifications published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerNotification> {
+        self.sender.subscribe()
+    }
+
+    /// Retained notifications after `last_event_id` visible to `session_id`
+    pub fn replay_after(
+        &self,
+        last_event_id: u64,
+        session_id: Option<&str>,
+    ) -> Vec<ServerNotification> {
+        self.history
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| n.event_id > last_event_id && n.is_visible_to(session_id))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_respects_session_and_capacity() {
+        let hub = NotificationHub::new(2);
+        let first = hub.publish(None, "notifications/message", json!({"n": 1}));
+        hub.publish(Some("a".into()), "notifications/message", json!({"n": 2}));
+        hub.publish(Some("b".into()), "notifications/message", json!({"n": 3}));
+
+        // The first notification fell out of the two-entry history
+        let replay = hub.replay_after(0, Some("a"));
+        assert_eq!(replay.len(), 1);
+        assert_eq!(replay[0].message["params"]["n"], 2);
+        assert!(replay.iter().all(|n| n.event_id > first));
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_notifications() {
+        let hub = NotificationHub::default();
+        let mut receiver = hub.subscribe();
+        let event_id = hub.publish(None, "notifications/message", json!({}));
+
+        let received = receiver.recv().await.unwrap();
+        assert_eq!(received.event_id, event_id);
+        assert_eq!(received.message["method"], "notifications/message");
+    }
+}
@@ -0,0 +1,104 @@
+//! MCP protocol version negotiation
+//!
+//! The server speaks several revisions of the MCP specification. The version is
+//! agreed during `initialize`, carried on later requests via the
+//! `MCP-Protocol-Version` header, and used to adapt response shapes to what the
+//! client understands.
+
+use std::fmt;
+
+/// MCP specification revisions supported by this server, oldest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    /// Original HTTP+SSE revision
+    V2024_11_05,
+    /// Streamable HTTP transport, tool annotations
+    V2025_03_26,
+    /// Current revision: MCP-Protocol-Version header, no JSON-RPC batching
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    /// Version offered when the client asks for one we do not support
+    pub const LATEST: Self = Self::V2025_06_18;
+
+    /// Version assumed when a client sends no `MCP-Protocol-Version` header
+    /// and has no session, as mandated by the 2025-06-18 specification
+    pub const DEFAULT_FALLBACK: Self = Self::V2025_03_26;
+
+    /// All supported versions, newest first
+    pub const SUPPORTED: [Self; 3] = [Self::V2025_06_18, Self::V2025_03_26, Self::V2024_11_05];
+
+    /// Wire representation, e.g. `2025-06-18`
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::V2024_11_05 => "2024-11-05",
+            Self::V2025_03_26 => "2025-03-26",
+            Self::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    /// Parse a wire version; `None` for unknown versions
+    pub fn parse(version: &str) -> Option<Self> {
+        Self::SUPPORTED
+            .into_iter()
+            .find(|supported| supported.as_str() == version)
+    }
+
+    /// Pick the version to use for a client's `initialize` request
+    ///
+    /// A supported requested version is echoed back; otherwise the server
+    /// answers with its latest version and the client decides whether to proceed.
+    pub fn negotiate(requested: Option<&str>) -> Self {
+        requested.and_then(Self::parse).unwrap_or(Self::LATEST)
+    }
+
+    /// Comma-separated list of supported versions for error messages
+    pub fn supported_list() -> String {
+        Self::SUPPORTED
+            .iter()
+            .map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Whether `tools/list` entries may carry `annotations` (added in 2025-03-26)
+    pub fn supports_tool_annotations(self) -> bool {
+        self >= Self::V2025_03_26
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(
+            ProtocolVersion::negotiate(Some("2024-11-05")),
+            ProtocolVersion::V2024_11_05
+        );
+        assert_eq!(
+            ProtocolVersion::negotiate(Some("1999-01-01")),
+            ProtocolVersion::LATEST
+        );
+        assert_eq!(ProtocolVersion::negotiate(None), ProtocolVersion::LATEST);
+        assert_eq!(ProtocolVersion::parse("2025-13-01"), None);
+    }
+
+    #[test]
+    fn test_feature_gates() {
+        assert!(!ProtocolVersion::V2024_11_05.supports_tool_annotations());
+        assert!(ProtocolVersion::V2025_06_18.supports_tool_annotations());
+        assert_eq!(
+            ProtocolVersion::supported_list(),
+            "2025-06-18, 2025-03-26, 2024-11-05"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Read-only server mode
+//!
+//! A read-only server answers lookups and listings but rejects every tool that
+//! changes tasks, messages, agents or workspace files, and leaves those tools
+//! out of `tools/list`. It suits boards shared with stakeholders and observer
+//! endpoints running next to the server agents work against.
+
+use crate::method_timeouts::READ_ONLY_METHODS;
+use serde_json::Value;
+
+/// Lookups that may refresh derived data (search indexes, anomaly events) but
+/// never change the board, so they stay available in read-only mode
+pub const DERIVED_DATA_METHODS: &[&str] = &[
+    "get_task_context",
+    "semantic_search",
+    "get_agent_performance",
+    "export_audit_log",
+];
+
+/// Whether a read-only server serves `method`
+pub fn is_allowed_when_read_only(method: &str) -> bool {
+    READ_ONLY_METHODS.contains(&method) || DERIVED_DATA_METHODS.contains(&method)
+}
+
+/// Remove the tools a read-only server rejects from a `tools/list` result
+pub fn hide_mutating_tools(tools_list: &mut Value) {
+    if let Some(tools) = tools_list.get_mut("tools").and_then(|t| t.as_array_mut()) {
+        tools.retain(|tool| {
+            tool.get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(is_allowed_when_read_only)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_allowed_when_read_only() {
+        assert!(is_allowed_when_read_only("list_tasks"));
+        assert!(is_allowed_when_read_only("semantic_search"));
+        assert!(is_allowed_when_read_only("tools/list"));
+        assert!(!is_allowed_when_read_only("create_task"));
+        assert!(!is_allowed_when_read_only("merge_workspace"));
+        assert!(!is_allowed_when_read_only("unknown_method"));
+    }
+
+    #[test]
+    fn test_hide_mutating_tools() {
+        let mut tools = json!({"tools": [{"name": "list_tasks"}, {"name": "claim_task"}]});
+        hide_mutating_tools(&mut tools);
+        assert_eq!(tools["tools"].as_array().unwrap().len(), 1);
+        assert_eq!(tools["tools"][0]["name"], "list_tasks");
+    }
+}
@@ -0,0 +1,180 @@
+//! Recording of JSON-RPC exchanges
+//!
+//! In record mode every JSON-RPC request posted to the server is written
+//! together with its response to a JSON Lines file under `.axon/recordings/`.
+//! A recording can be replayed against a fresh database to reproduce a bug
+//! reported by a user (see the `axon-replay` tool in `integration-tests`).
+//! `Authorization` and `X-Agent-Token` headers are never recorded.
+
+use crate::agent_identity::AGENT_NAME_HEADER;
+use crate::session::MCP_SESSION_ID_HEADER;
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, Method};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Directory of recordings inside the project root
+pub const RECORDINGS_DIR: &str = ".axon/recordings";
+
+/// One JSON-RPC request and the response the server gave
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub recorded_at: DateTime<Utc>,
+    /// HTTP path the request was posted to
+    pub path: String,
+    /// Mcp-Session-Id sent with the request, or assigned by `initialize`
+    pub session_id: Option<String>,
+    /// Agent named in the X-Agent-Name header
+    pub agent_name: Option<String>,
+    pub status: u16,
+    pub request: Value,
+    /// `None` for notifications, which get no response body
+    pub response: Option<Value>,
+}
+
+/// Appends exchanges to a recording file
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a new recording in `project_root/.axon/recordings/`
+    pub fn create(project_root: &Path) -> std::io::Result<Self> {
+        let dir = project_root.join(RECORDINGS_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let name = format!(
+            "{}-{}.jsonl",
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            std::process::id()
+        );
+        let path = dir.join(name);
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// File the exchanges are written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one exchange as a JSON line
+    pub fn record(&self, exchange: &RecordedExchange) {
+        let line = match serde_json::to_string(exchange) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize recorded exchange: {}", e);
+                return;
+            }
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{line}").and_then(|_| file.flush()) {
+            warn!("Failed to write recording {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Read the exchanges of a recording file
+pub fn read_recording(path: &Path) -> std::io::Result<Vec<RecordedExchange>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(std::io::Error::other))
+        .collect()
+}
+
+/// Record POSTed JSON-RPC requests and their responses
+pub(crate) async fn record_exchange(
+    State(recorder): State<Arc<Recorder>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let path = request.uri().path().to_string();
+    let agent_name = header_value(request.headers(), AGENT_NAME_HEADER);
+    let request_session = header_value(request.headers(), MCP_SESSION_ID_HEADER);
+    let (parts, body) = request.into_parts();
+    let Ok(request_body) = axum::body::to_bytes(body, usize::MAX).await else {
+        return axum::http::StatusCode::BAD_REQUEST.into_response();
+    };
+    let response = next
+        .run(Request::from_parts(parts, Body::from(request_body.clone())))
+        .await;
+
+    let (parts, body) = response.into_parts();
+    let response_body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_default();
+    recorder.record(&RecordedExchange {
+        recorded_at: Utc::now(),
+        path,
+        session_id: request_session.or_else(|| header_value(&parts.headers, MCP_SESSION_ID_HEADER)),
+        agent_name,
+        status: parts.status.as_u16(),
+        request: parse_json(&request_body).unwrap_or(Value::Null),
+        response: parse_json(&response_body),
+    });
+    Response::from_parts(parts, Body::from(response_body))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn parse_json(body: &Bytes) -> Option<Value> {
+    serde_json::from_slice(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let recorder = Recorder::create(root.path()).unwrap();
+        assert!(recorder
+            .path()
+            .starts_with(root.path().join(RECORDINGS_DIR)));
+
+        let exchange = RecordedExchange {
+            recorded_at: Utc::now(),
+            path: "/mcp".to_string(),
+            session_id: Some("session-1".to_string()),
+            agent_name: Some("rust-architect".to_string()),
+            status: 200,
+            request: json!({"jsonrpc": "2.0", "id": 1, "method": "list_tasks"}),
+            response: Some(json!({"jsonrpc": "2.0", "id": 1, "result": []})),
+        };
+        recorder.record(&exchange);
+        recorder.record(&RecordedExchange {
+            response: None,
+            ..exchange.clone()
+        });
+
+        let recorded = read_recording(recorder.path()).unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0], exchange);
+        assert_eq!(recorded[1].response, None);
+    }
+}
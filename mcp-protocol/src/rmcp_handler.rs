@@ -5,24 +5,17 @@
 use crate::serialization::*;
 use ::task_core::error::Result;
 use ::task_core::TaskError;
+use ::task_core::WorkSessionInfo;
 use ::task_core::{
-    AgentRegistration, AgenticWorkflowDescription, WorkspaceSetupService,
+    AgentRegistration, AgenticWorkflowDescription, WorkspaceSetupConfig, WorkspaceSetupService,
 };
 use ::task_core::{
-    WorkSessionInfo,
+    HealthStatus, NewTask, TaskMessageRepository, TaskRepository, WorkspaceContextRepository,
 };
-use ::task_core::{
-    HealthStatus, NewTask, TaskMessageRepository,
-    TaskRepository, WorkspaceContextRepository,
-};
-use rmcp::{
-    model::*,
-    tool, tool_router, tool_handler,
-    ServerHandler, ErrorData as McpError,
-};
-use std::future::Future;
 use rmcp::schemars::JsonSchema;
+use rmcp::{model::*, tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::sync::Arc;
 
 // Maximum attempts for get-or-modify loops to handle race conditions
@@ -259,7 +252,10 @@ impl<R, M, W> RmcpTaskHandler<R, M, W> {
             repository,
             message_repository,
             workspace_context_repository: workspace_context_repository.clone(),
-            workspace_setup_service: WorkspaceSetupService::new(),
+            workspace_setup_service: WorkspaceSetupService::with_config(WorkspaceSetupConfig {
+                project_root: project_root.clone(),
+                ..WorkspaceSetupConfig::default()
+            }),
             tool_router: Self::tool_router(),
             _project_root: project_root,
         }
@@ -285,10 +281,7 @@ impl<
 {
     /// Create a new task
     #[tool(description = "Create a new task with code, name, description, and owner")]
-    async fn create_task(
-        &self,
-        request: CreateTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
+    async fn create_task(&self, request: CreateTaskRequest) -> Result<CallToolResult, McpError> {
         let new_task = NewTask::new(
             request.code,
             request.name,
@@ -312,16 +305,14 @@ impl<
 
     /// Update an existing task
     #[tool(description = "Update an existing task's properties")]
-    async fn update_task(
-        &self,
-        request: UpdateTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
+    async fn update_task(&self, request: UpdateTaskRequest) -> Result<CallToolResult, McpError> {
         let update_data = UpdateTaskParams {
             id: request.id,
             name: request.name,
             description: request.description,
             owner_agent_name: request.owner_agent_name,
-        }.into_update_data();
+        }
+        .into_update_data();
 
         match self.repository.update(request.id, update_data).await {
             Ok(task) => {
@@ -344,16 +335,11 @@ impl<
         request: SetTaskStateRequest,
     ) -> Result<CallToolResult, McpError> {
         use ::task_core::TaskState;
-        
-        let state = match request.state.as_str() {
-            "Created" => TaskState::Created,
-            "InProgress" => TaskState::InProgress,
-            "Blocked" => TaskState::Blocked,
-            "Review" => TaskState::Review,
-            "Done" => TaskState::Done,
-            "Archived" => TaskState::Archived,
-            _ => return Err(McpError::invalid_params(format!("Invalid state: {}", request.state))),
-        };
+
+        let state: TaskState = request
+            .state
+            .parse()
+            .map_err(|e: TaskError| McpError::invalid_params(e.to_string()))?;
 
         match self.repository.set_state(request.id, state).await {
             Ok(task) => {
@@ -382,7 +368,9 @@ impl<
                     serde_json::to_string_pretty(&task_json).unwrap(),
                 )]))
             }
-            Ok(None) => Ok(CallToolResult::success(vec![Content::text("null".to_string())])),
+            Ok(None) => Ok(CallToolResult::success(vec![Content::text(
+                "null".to_string(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -401,17 +389,18 @@ impl<
                     serde_json::to_string_pretty(&task_json).unwrap(),
                 )]))
             }
-            Ok(None) => Ok(CallToolResult::success(vec![Content::text("null".to_string())])),
+            Ok(None) => Ok(CallToolResult::success(vec![Content::text(
+                "null".to_string(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
 
     /// List tasks with optional filtering
-    #[tool(description = "List tasks with optional filtering by owner, state, date range, and limit")]
-    async fn list_tasks(
-        &self,
-        request: ListTasksRequest,
-    ) -> Result<CallToolResult, McpError> {
+    #[tool(
+        description = "List tasks with optional filtering by owner, state, date range, and limit"
+    )]
+    async fn list_tasks(&self, request: ListTasksRequest) -> Result<CallToolResult, McpError> {
         let params = ListTasksParams {
             owner: request.owner,
             state: request.state,
@@ -422,15 +411,17 @@ impl<
             limit: request.limit,
         };
 
-        let filter = params.to_task_filter()
+        let filter = params
+            .to_task_filter()
             .map_err(|e| McpError::invalid_params(format!("Filter error: {}", e)))?;
 
         match self.repository.list(filter).await {
             Ok(tasks) => {
-                let task_jsons: Result<Vec<_>, _> = tasks.iter().map(serialize_task_for_mcp).collect();
+                let task_jsons: Result<Vec<_>, _> =
+                    tasks.iter().map(serialize_task_for_mcp).collect();
                 let task_jsons = task_jsons
                     .map_err(|e| McpError::internal(format!("Serialization error: {}", e)))?;
-                
+
                 Ok(CallToolResult::success(vec![Content::text(
                     serde_json::to_string_pretty(&task_jsons).unwrap(),
                 )]))
@@ -441,10 +432,7 @@ impl<
 
     /// Assign task to a different agent
     #[tool(description = "Assign a task to a different agent")]
-    async fn assign_task(
-        &self,
-        request: AssignTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
+    async fn assign_task(&self, request: AssignTaskRequest) -> Result<CallToolResult, McpError> {
         match self.repository.assign(request.id, &request.new_owner).await {
             Ok(task) => {
                 let task_json = serialize_task_for_mcp(&task)
@@ -460,10 +448,7 @@ impl<
 
     /// Archive a completed task
     #[tool(description = "Archive a completed task")]
-    async fn archive_task(
-        &self,
-        request: ArchiveTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
+    async fn archive_task(&self, request: ArchiveTaskRequest) -> Result<CallToolResult, McpError> {
         match self.repository.archive(request.id).await {
             Ok(task) => {
                 let task_json = serialize_task_for_mcp(&task)
@@ -504,13 +489,18 @@ impl<
         request: DiscoverWorkRequest,
     ) -> Result<CallToolResult, McpError> {
         let max_tasks = request.max_tasks.unwrap_or(10);
-        
-        match self.repository.discover_work(&request.agent_name, &request.capabilities, max_tasks).await {
+
+        match self
+            .repository
+            .discover_work(&request.agent_name, &request.capabilities, max_tasks)
+            .await
+        {
             Ok(tasks) => {
-                let task_jsons: Result<Vec<_>, _> = tasks.iter().map(serialize_task_for_mcp).collect();
+                let task_jsons: Result<Vec<_>, _> =
+                    tasks.iter().map(serialize_task_for_mcp).collect();
                 let task_jsons = task_jsons
                     .map_err(|e| McpError::internal(format!("Serialization error: {}", e)))?;
-                
+
                 Ok(CallToolResult::success(vec![Content::text(
                     serde_json::to_string_pretty(&task_jsons).unwrap(),
                 )]))
@@ -521,13 +511,12 @@ impl<
 
     /// Atomically claim a task for execution
     #[tool(description = "Atomically claim a task for execution")]
-    async fn claim_task(
-        &self,
-        request: ClaimTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
+    async fn claim_task(&self, request: ClaimTaskRequest) -> Result<CallToolResult, McpError> {
         // Validate agent name format at protocol layer
         if request.agent_name.trim().is_empty() {
-            return Err(McpError::invalid_params("Agent name cannot be empty".to_string()));
+            return Err(McpError::invalid_params(
+                "Agent name cannot be empty".to_string(),
+            ));
         }
 
         // Validate agent name format (kebab-case)
@@ -541,7 +530,11 @@ impl<
             ));
         }
 
-        match self.repository.claim_task(request.task_id, &request.agent_name).await {
+        match self
+            .repository
+            .claim_task(request.task_id, &request.agent_name)
+            .await
+        {
             Ok(task) => {
                 // Protocol layer validation: ensure claimed task is in InProgress state
                 if task.state != ::task_core::TaskState::InProgress {
@@ -573,11 +566,12 @@ impl<
 
     /// Release a claimed task back to the pool
     #[tool(description = "Release a claimed task back to the pool")]
-    async fn release_task(
-        &self,
-        request: ReleaseTaskRequest,
-    ) -> Result<CallToolResult, McpError> {
-        match self.repository.release_task(request.task_id, &request.agent_name).await {
+    async fn release_task(&self, request: ReleaseTaskRequest) -> Result<CallToolResult, McpError> {
+        match self
+            .repository
+            .release_task(request.task_id, &request.agent_name)
+            .await
+        {
             Ok(task) => {
                 let task_json = serialize_task_for_mcp(&task)
                     .map_err(|e| McpError::internal(format!("Serialization error: {}", e)))?;
@@ -596,7 +590,11 @@ impl<
         &self,
         request: StartWorkSessionRequest,
     ) -> Result<CallToolResult, McpError> {
-        match self.repository.start_work_session(request.task_id, &request.agent_name).await {
+        match self
+            .repository
+            .start_work_session(request.task_id, &request.agent_name)
+            .await
+        {
             Ok(session_id) => {
                 let session_info = WorkSessionInfo {
                     session_id,
@@ -618,11 +616,15 @@ impl<
         &self,
         request: EndWorkSessionRequest,
     ) -> Result<CallToolResult, McpError> {
-        match self.repository.end_work_session(
-            request.session_id,
-            request.notes,
-            request.productivity_score,
-        ).await {
+        match self
+            .repository
+            .end_work_session(
+                request.session_id,
+                request.notes,
+                request.productivity_score,
+            )
+            .await
+        {
             Ok(()) => Ok(CallToolResult::success(vec![Content::text(
                 r#"{"status": "success", "message": "Work session ended"}"#.to_string(),
             )])),
@@ -637,19 +639,21 @@ impl<
         &self,
         request: CreateTaskMessageRequest,
     ) -> Result<CallToolResult, McpError> {
-        match self.message_repository.create_message(
-            &request.task_code,
-            &request.author_agent_name,
-            request.target_agent_name.as_deref(),
-            &request.message_type,
-            &request.content,
-            request.reply_to_message_id,
-        ).await {
-            Ok(message) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&message).unwrap(),
-                )]))
-            }
+        match self
+            .message_repository
+            .create_message(
+                &request.task_code,
+                &request.author_agent_name,
+                request.target_agent_name.as_deref(),
+                &request.message_type,
+                &request.content,
+                request.reply_to_message_id,
+            )
+            .await
+        {
+            Ok(message) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&message).unwrap(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -660,19 +664,21 @@ impl<
         &self,
         request: GetTaskMessagesRequest,
     ) -> Result<CallToolResult, McpError> {
-        match self.message_repository.get_messages(
-            &request.task_code,
-            request.author_agent_name.as_deref(),
-            request.target_agent_name.as_deref(),
-            request.message_type.as_deref(),
-            request.reply_to_message_id,
-            request.limit,
-        ).await {
-            Ok(messages) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&messages).unwrap(),
-                )]))
-            }
+        match self
+            .message_repository
+            .get_messages(
+                &request.task_code,
+                request.author_agent_name.as_deref(),
+                request.target_agent_name.as_deref(),
+                request.message_type.as_deref(),
+                request.reply_to_message_id,
+                request.limit,
+            )
+            .await
+        {
+            Ok(messages) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&messages).unwrap(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -694,11 +700,9 @@ impl<
             .get_setup_instructions(ai_tool_type)
             .await
         {
-            Ok(response) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&response.payload).unwrap(),
-                )]))
-            }
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&response.payload).unwrap(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Workspace setup error: {}", e))),
         }
     }
@@ -711,7 +715,7 @@ impl<
     ) -> Result<CallToolResult, McpError> {
         // Get agent count (default to 3 if not specified)
         let agent_count = request.requested_agent_count.unwrap_or(3);
-        
+
         // Create static workflow description with agent count placeholder filled
         let workflow_prompt = format!(
             r#"# AI Agent Workflow Instructions
@@ -764,7 +768,8 @@ This workflow scales effectively with {} agents working in parallel."#,
             workflow_description: workflow_prompt,
             recommended_agent_count: agent_count,
             suggested_agents: vec![], // Empty - let AI decide based on the prompt
-            task_decomposition_strategy: "MCP-based coordination with parallel execution".to_string(),
+            task_decomposition_strategy: "MCP-based coordination with parallel execution"
+                .to_string(),
             coordination_patterns: vec![
                 "Task claiming and assignment".to_string(),
                 "Message-based communication".to_string(),
@@ -777,6 +782,7 @@ This workflow scales effectively with {} agents working in parallel."#,
                 "4. Coordinate handoffs through task assignment".to_string(),
                 "5. Complete tasks with proper state management".to_string(),
             ],
+            classification: None,
         };
 
         Ok(CallToolResult::success(vec![Content::text(
@@ -791,15 +797,19 @@ This workflow scales effectively with {} agents working in parallel."#,
         request: RegisterAgentRequest,
     ) -> Result<CallToolResult, McpError> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
+
         // Basic validation
         if request.agent_name.trim().is_empty() {
-            return Err(McpError::invalid_params("Agent name cannot be empty".to_string()));
+            return Err(McpError::invalid_params(
+                "Agent name cannot be empty".to_string(),
+            ));
         }
 
         if let Some(ref desc) = request.description {
             if desc.len() > 300 {
-                return Err(McpError::invalid_params("Agent description cannot exceed 300 characters".to_string()));
+                return Err(McpError::invalid_params(
+                    "Agent description cannot exceed 300 characters".to_string(),
+                ));
             }
         }
 
@@ -827,7 +837,9 @@ This workflow scales effectively with {} agents working in parallel."#,
             // 2. Check if context exists and prepare for modification
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 3. Duplicate-agent guard (in case another peer registered same name first)
@@ -846,7 +858,10 @@ This workflow scales effectively with {} agents working in parallel."#,
             let agent_registration = AgentRegistration {
                 name: request.agent_name.clone(),
                 description: request.description.clone().unwrap_or_default(),
-                prompt: format!("Agent: {}, Type: {}", request.agent_name, request.agent_type),
+                prompt: format!(
+                    "Agent: {}, Type: {}",
+                    request.agent_name, request.agent_type
+                ),
                 capabilities: request.capabilities.clone(),
                 ai_tool_type: ::task_core::workspace_setup::AiToolType::ClaudeCode,
                 dependencies: Vec::new(),
@@ -872,39 +887,18 @@ This workflow scales effectively with {} agents working in parallel."#,
             match write_result {
                 Ok(_) => {
                     // Also create agent file in filesystem if project root is available
-                    if let Some(_project_root) = &self._project_root {
-                        // Create agent file in .claude/agents/ directory
-                        // This matches our existing filesystem integration
-                        let agent_dir = std::path::Path::new(".claude/agents");
-                        if let Err(e) = std::fs::create_dir_all(agent_dir) {
+                    if let Some(project_root) = &self._project_root {
+                        // Create a Claude Code sub-agent file in .claude/agents/
+                        let agent_dir = project_root.join(".claude/agents");
+                        if let Err(e) = std::fs::create_dir_all(&agent_dir) {
                             tracing::warn!("Failed to create agent directory: {}", e);
                         } else {
-                            let agent_file = agent_dir.join(format!("{}.md", request.agent_name));
-                            let agent_content = format!(
-                                r#"# Agent: {}
-
-## Type
-{}
-
-## Description
-{}
-
-## Capabilities
-{}
-
-## Prompt
-{}
-
----
-*Generated by MCP Task Server*
-"#,
-                                request.agent_name,
-                                request.agent_type,
-                                request.description.unwrap_or_default(),
-                                request.capabilities.join(", "),
-                                agent_registration.prompt
-                            );
-                            
+                            let agent_file = agent_dir.join(format!(
+                                "{}.md",
+                                ::task_core::subagent_name(&request.agent_name)
+                            ));
+                            let agent_content =
+                                ::task_core::render_subagent_file(&agent_registration);
                             if let Err(e) = std::fs::write(&agent_file, agent_content) {
                                 tracing::warn!("Failed to write agent file: {}", e);
                             }
@@ -935,7 +929,9 @@ This workflow scales effectively with {} agents working in parallel."#,
     }
 
     /// Get instructions for creating main AI coordination file
-    #[tool(description = "Get instructions for creating the main AI coordination file (CLAUDE.md, etc.)")]
+    #[tool(
+        description = "Get instructions for creating the main AI coordination file (CLAUDE.md, etc.)"
+    )]
     async fn get_instructions_for_main_ai_file(
         &self,
         _request: GetInstructionsForMainAiFileRequest,
@@ -946,12 +942,13 @@ This workflow scales effectively with {} agents working in parallel."#,
             .get_main_file_instructions(::task_core::workspace_setup::AiToolType::ClaudeCode)
             .await
         {
-            Ok(response) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&response.payload).unwrap(),
-                )]))
-            }
-            Err(e) => Err(McpError::internal(format!("Main AI file instructions error: {}", e))),
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&response.payload).unwrap(),
+            )])),
+            Err(e) => Err(McpError::internal(format!(
+                "Main AI file instructions error: {}",
+                e
+            ))),
         }
     }
 
@@ -962,7 +959,7 @@ This workflow scales effectively with {} agents working in parallel."#,
         request: CreateMainAiFileRequest,
     ) -> Result<CallToolResult, McpError> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
+
         // Generate the main AI file (only once – outside the retry loop)
         let response = self
             .workspace_setup_service
@@ -995,7 +992,9 @@ This workflow scales effectively with {} agents working in parallel."#,
 
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 2. Avoid duplicate insertion on retry
@@ -1022,9 +1021,11 @@ This workflow scales effectively with {} agents working in parallel."#,
             };
 
             match write_result {
-                Ok(_) => return Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&response.payload).unwrap(),
-                )])),
+                Ok(_) => {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string_pretty(&response.payload).unwrap(),
+                    )]))
+                }
                 Err(TaskError::DuplicateKey(_)) | Err(TaskError::Conflict(_)) => {
                     // Race condition detected
                     if attempt >= MAX_ATTEMPTS {
@@ -1059,4 +1060,4 @@ impl<
             capabilities: ServerCapabilities::builder().enable_tools().build(),
         }
     }
-}
\ No newline at end of file
+}
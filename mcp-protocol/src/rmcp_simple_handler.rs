@@ -5,14 +5,11 @@
 use crate::serialization::*;
 use ::task_core::TaskError;
 use ::task_core::{
-    AgentRegistration, AgenticWorkflowDescription, WorkspaceSetupService,
-    HealthStatus, NewTask, TaskMessageRepository, TaskRepository, WorkspaceContextRepository,
-    WorkSessionInfo,
+    AgentRegistration, AgenticWorkflowDescription, HealthStatus, NewTask, TaskMessageRepository,
+    TaskRepository, WorkSessionInfo, WorkspaceContextRepository, WorkspaceSetupService,
 };
 use rmcp::{
-    model::*,
-    tool, tool_router, tool_handler,
-    ServerHandler, ErrorData as McpError, ServiceExt,
+    model::*, tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler, ServiceExt,
 };
 use std::sync::Arc;
 
@@ -91,16 +88,11 @@ impl<
     async fn create_task(
         &self,
         code: String,
-        name: String, 
+        name: String,
         description: String,
         owner_agent_name: String,
     ) -> std::result::Result<CallToolResult, McpError> {
-        let new_task = NewTask::new(
-            code,
-            name,
-            description,
-            Some(owner_agent_name),
-        );
+        let new_task = NewTask::new(code, name, description, Some(owner_agent_name));
 
         match self.repository.create(new_task).await {
             Ok(task) => {
@@ -118,10 +110,7 @@ impl<
 
     /// Get task by ID
     #[tool(description = "Retrieve a task by its ID")]
-    async fn get_task_by_id(
-        &self,
-        id: i32,
-    ) -> std::result::Result<CallToolResult, McpError> {
+    async fn get_task_by_id(&self, id: i32) -> std::result::Result<CallToolResult, McpError> {
         match self.repository.get_by_id(id).await {
             Ok(Some(task)) => {
                 let task_json = serialize_task_for_mcp(&task)
@@ -130,7 +119,9 @@ impl<
                     serde_json::to_string_pretty(&task_json).unwrap(),
                 )]))
             }
-            Ok(None) => Ok(CallToolResult::success(vec![Content::text("null".to_string())])),
+            Ok(None) => Ok(CallToolResult::success(vec![Content::text(
+                "null".to_string(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -149,7 +140,9 @@ impl<
                     serde_json::to_string_pretty(&task_json).unwrap(),
                 )]))
             }
-            Ok(None) => Ok(CallToolResult::success(vec![Content::text("null".to_string())])),
+            Ok(None) => Ok(CallToolResult::success(vec![Content::text(
+                "null".to_string(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -158,7 +151,7 @@ impl<
     #[tool(description = "List all tasks")]
     async fn list_tasks(&self) -> Result<CallToolResult, McpError> {
         use ::task_core::TaskFilter;
-        
+
         let filter = TaskFilter {
             owner: None,
             state: None,
@@ -171,10 +164,11 @@ impl<
 
         match self.repository.list(filter).await {
             Ok(tasks) => {
-                let task_jsons: Result<Vec<_>, _> = tasks.iter().map(serialize_task_for_mcp).collect();
+                let task_jsons: Result<Vec<_>, _> =
+                    tasks.iter().map(serialize_task_for_mcp).collect();
                 let task_jsons = task_jsons
                     .map_err(|e| McpError::internal(format!("Serialization error: {}", e)))?;
-                
+
                 Ok(CallToolResult::success(vec![Content::text(
                     serde_json::to_string_pretty(&task_jsons).unwrap(),
                 )]))
@@ -197,7 +191,7 @@ impl<
         confidence_threshold: Option<f64>,
     ) -> std::result::Result<CallToolResult, McpError> {
         use ::task_core::UpdateTask;
-        
+
         let updates = UpdateTask {
             code,
             name,
@@ -231,20 +225,10 @@ impl<
         state: String,
     ) -> std::result::Result<CallToolResult, McpError> {
         use ::task_core::TaskState;
-        
-        let state = match state.as_str() {
-            "Created" => TaskState::Created,
-            "InProgress" => TaskState::InProgress,
-            "Blocked" => TaskState::Blocked,
-            "Review" => TaskState::Review,
-            "Done" => TaskState::Done,
-            "Archived" => TaskState::Archived,
-            "PendingDecomposition" => TaskState::PendingDecomposition, 
-            "PendingHandoff" => TaskState::PendingHandoff,
-            "Quarantined" => TaskState::Quarantined,
-            "WaitingForDependency" => TaskState::WaitingForDependency,
-            _ => return Err(McpError::invalid_params(format!("Invalid task state: {}", state))),
-        };
+
+        let state: TaskState = state
+            .parse()
+            .map_err(|e: TaskError| McpError::invalid_params(e.to_string()))?;
 
         match self.repository.set_state(id, state).await {
             Ok(task) => {
@@ -281,10 +265,7 @@ impl<
 
     /// Archive task
     #[tool(description = "Move task to archived state with audit trail")]
-    async fn archive_task(
-        &self,
-        id: i32,
-    ) -> std::result::Result<CallToolResult, McpError> {
+    async fn archive_task(&self, id: i32) -> std::result::Result<CallToolResult, McpError> {
         match self.repository.archive(id).await {
             Ok(task) => {
                 let task_json = serialize_task_for_mcp(&task)
@@ -307,13 +288,18 @@ impl<
         max_tasks: Option<u32>,
     ) -> std::result::Result<CallToolResult, McpError> {
         let max_tasks = max_tasks.unwrap_or(10);
-        
-        match self.repository.discover_work(&agent_name, &capabilities, max_tasks).await {
+
+        match self
+            .repository
+            .discover_work(&agent_name, &capabilities, max_tasks)
+            .await
+        {
             Ok(tasks) => {
-                let task_jsons: Result<Vec<_>, _> = tasks.iter().map(serialize_task_for_mcp).collect();
+                let task_jsons: Result<Vec<_>, _> =
+                    tasks.iter().map(serialize_task_for_mcp).collect();
                 let task_jsons = task_jsons
                     .map_err(|e| McpError::internal(format!("Serialization error: {}", e)))?;
-                
+
                 Ok(CallToolResult::success(vec![Content::text(
                     serde_json::to_string_pretty(&task_jsons).unwrap(),
                 )]))
@@ -402,7 +388,11 @@ impl<
         task_id: i32,
         agent_name: String,
     ) -> std::result::Result<CallToolResult, McpError> {
-        match self.repository.start_work_session(task_id, &agent_name).await {
+        match self
+            .repository
+            .start_work_session(task_id, &agent_name)
+            .await
+        {
             Ok(session_id) => {
                 let session_info = WorkSessionInfo {
                     session_id,
@@ -428,12 +418,14 @@ impl<
         notes: Option<String>,
         productivity_score: Option<f64>,
     ) -> std::result::Result<CallToolResult, McpError> {
-        match self.repository.end_work_session(session_id, notes, productivity_score).await {
-            Ok(()) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    "Work session ended successfully".to_string(),
-                )]))
-            }
+        match self
+            .repository
+            .end_work_session(session_id, notes, productivity_score)
+            .await
+        {
+            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
+                "Work session ended successfully".to_string(),
+            )])),
             Err(TaskError::NotFound(msg)) => Err(McpError::invalid_params(msg)),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
@@ -450,7 +442,8 @@ impl<
         content: String,
         reply_to_message_id: Option<i32>,
     ) -> std::result::Result<CallToolResult, McpError> {
-        match self.message_repository
+        match self
+            .message_repository
             .create_message(
                 &task_code,
                 &author_agent_name,
@@ -461,11 +454,9 @@ impl<
             )
             .await
         {
-            Ok(message) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&message).unwrap(),
-                )]))
-            }
+            Ok(message) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&message).unwrap(),
+            )])),
             Err(TaskError::NotFound(msg)) => Err(McpError::invalid_params(msg)),
             Err(TaskError::Validation(msg)) => Err(McpError::invalid_params(msg)),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
@@ -483,7 +474,8 @@ impl<
         reply_to_message_id: Option<i32>,
         limit: Option<u32>,
     ) -> std::result::Result<CallToolResult, McpError> {
-        match self.message_repository
+        match self
+            .message_repository
             .get_messages(
                 &task_code,
                 author_agent_name.as_deref(),
@@ -494,11 +486,9 @@ impl<
             )
             .await
         {
-            Ok(messages) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&messages).unwrap(),
-                )]))
-            }
+            Ok(messages) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&messages).unwrap(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Database error: {}", e))),
         }
     }
@@ -520,11 +510,9 @@ impl<
             .get_setup_instructions(ai_tool_type)
             .await
         {
-            Ok(response) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&response.payload).unwrap(),
-                )]))
-            }
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&response.payload).unwrap(),
+            )])),
             Err(e) => Err(McpError::internal(format!("Workspace setup error: {}", e))),
         }
     }
@@ -537,7 +525,7 @@ impl<
     ) -> std::result::Result<CallToolResult, McpError> {
         // Get agent count (default to 3 if not specified)
         let agent_count = requested_agent_count.unwrap_or(3);
-        
+
         // Create static workflow description with agent count placeholder filled
         let workflow_prompt = format!(
             r#"# AI Agent Workflow Instructions
@@ -590,7 +578,8 @@ This workflow scales effectively with {} agents working in parallel."#,
             workflow_description: workflow_prompt,
             recommended_agent_count: agent_count,
             suggested_agents: vec![], // Empty - let AI decide based on the prompt
-            task_decomposition_strategy: "MCP-based coordination with parallel execution".to_string(),
+            task_decomposition_strategy: "MCP-based coordination with parallel execution"
+                .to_string(),
             coordination_patterns: vec![
                 "Task claiming and assignment".to_string(),
                 "Message-based communication".to_string(),
@@ -603,6 +592,7 @@ This workflow scales effectively with {} agents working in parallel."#,
                 "4. Coordinate handoffs through task assignment".to_string(),
                 "5. Complete tasks with proper state management".to_string(),
             ],
+            classification: None,
         };
 
         Ok(CallToolResult::success(vec![Content::text(
@@ -620,7 +610,7 @@ This workflow scales effectively with {} agents working in parallel."#,
         capabilities: Vec<String>,
     ) -> std::result::Result<CallToolResult, McpError> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
+
         // Basic validation
         if agent_name.trim().is_empty() {
             return Err(McpError::invalid_params("Agent name cannot be empty"));
@@ -628,7 +618,9 @@ This workflow scales effectively with {} agents working in parallel."#,
 
         if let Some(ref desc) = description {
             if desc.len() > 300 {
-                return Err(McpError::invalid_params("Agent description cannot exceed 300 characters"));
+                return Err(McpError::invalid_params(
+                    "Agent description cannot exceed 300 characters",
+                ));
             }
         }
 
@@ -655,7 +647,9 @@ This workflow scales effectively with {} agents working in parallel."#,
             // 2. Check if context exists and prepare for modification
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 3. Duplicate-agent guard (in case another peer registered same name first)
@@ -733,12 +727,13 @@ This workflow scales effectively with {} agents working in parallel."#,
             .get_main_file_instructions(::task_core::workspace_setup::AiToolType::ClaudeCode)
             .await
         {
-            Ok(response) => {
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string_pretty(&response.payload).unwrap(),
-                )]))
-            }
-            Err(e) => Err(McpError::internal(format!("Main AI file instructions error: {}", e))),
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&response.payload).unwrap(),
+            )])),
+            Err(e) => Err(McpError::internal(format!(
+                "Main AI file instructions error: {}",
+                e
+            ))),
         }
     }
 
@@ -749,7 +744,7 @@ This workflow scales effectively with {} agents working in parallel."#,
         content: String,
     ) -> std::result::Result<CallToolResult, McpError> {
         use task_core::protocol::DEFAULT_WORKSPACE_ID;
-        
+
         // Generate the main AI file (only once – outside the retry loop)
         let response = self
             .workspace_setup_service
@@ -782,7 +777,9 @@ This workflow scales effectively with {} agents working in parallel."#,
 
             let context_exists = maybe_context.is_some();
             let mut workspace_context = maybe_context.unwrap_or_else(|| {
-                ::task_core::workspace_setup::WorkspaceContext::new(DEFAULT_WORKSPACE_ID.to_string())
+                ::task_core::workspace_setup::WorkspaceContext::new(
+                    DEFAULT_WORKSPACE_ID.to_string(),
+                )
             });
 
             // 2. Avoid duplicate insertion on retry
@@ -825,7 +822,7 @@ This workflow scales effectively with {} agents working in parallel."#,
                     // Small exponential back-off to reduce contention
                     tokio::time::sleep(tokio::time::Duration::from_millis(10 * attempt as u64))
                         .await;
-                    continue; 
+                    continue;
                 }
                 Err(e) => return Err(McpError::internal(format!("Database error: {}", e))),
             }
@@ -847,4 +844,4 @@ impl<
             capabilities: ServerCapabilities::builder().enable_tools().build(),
         }
     }
-}
\ No newline at end of file
+}
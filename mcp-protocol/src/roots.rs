@@ -0,0 +1,185 @@
+//! Project root discovery through the client's workspace roots (MCP roots)
+//!
+//! A server started without `--project-root` cannot tell where the project
+//! lives, and one started with it serves every client the same directory.
+//! A client that declares the `roots` capability is asked for its roots with
+//! `roots/list` once the session is initialized, and again when it announces
+//! `notifications/roots/list_changed`. The first `file://` root becomes the
+//! session's project root, passed to tools that accept `project_root`; without
+//! it the server's own root applies. Clients cannot name any other root, and a
+//! server started with `--project-root` ignores listed roots outside it. The
+//! database stays the one opened at startup: roots scope files, not tasks.
+
+use crate::client_requests::ClientRequests;
+use crate::error::McpError;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+use task_core::Result;
+
+/// How long a client may take to list its roots
+pub const DEFAULT_ROOTS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Ask the client of `session_id` for its roots; returns the path of the first
+/// local one
+pub async fn request_project_root(
+    requests: &ClientRequests,
+    session_id: &str,
+    timeout: Duration,
+) -> Result<Option<String>> {
+    let result = requests
+        .request(session_id, "roots/list", json!({}), timeout)
+        .await?;
+    Ok(result["roots"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|root| root["uri"].as_str())
+        .find_map(file_uri_path))
+}
+
+/// Local path of a `file://` URI, percent-decoded
+pub fn file_uri_path(uri: &str) -> Option<String> {
+    let rest = uri.strip_prefix("file://")?;
+    // Only the local host is reachable
+    let path = match rest.find('/') {
+        Some(0) => rest,
+        Some(start) if rest[..start].eq_ignore_ascii_case("localhost") => &rest[start..],
+        _ => return None,
+    };
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+
+    // file:///C:/work is C:/work on Windows
+    match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(path[1..].to_string()),
+        _ => Some(path),
+    }
+}
+
+/// Whether `path` is the directory `root` or inside it, following symlinks
+pub fn is_within(path: &Path, root: &Path) -> bool {
+    match (path.canonicalize(), root.canonicalize()) {
+        (Ok(path), Ok(root)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+/// Set `project_root` on tool call params to the session's root
+///
+/// A call may repeat the session's root but not name another directory.
+pub fn with_project_root(
+    mut params: Value,
+    project_root: Option<&str>,
+) -> std::result::Result<Value, McpError> {
+    if params.is_null() {
+        params = json!({});
+    }
+    let Some(params_map) = params.as_object_mut() else {
+        return Ok(params);
+    };
+    match (params_map.get("project_root"), project_root) {
+        (None | Some(Value::Null), _) => {}
+        (Some(named), Some(root)) if named.as_str() == Some(root) => {}
+        (Some(named), _) => {
+            return Err(McpError::Forbidden(format!(
+                "project_root {named} is not the workspace root of this session; leave it out"
+            )))
+        }
+    }
+    match project_root {
+        Some(root) => params_map.insert("project_root".to_string(), json!(root)),
+        None => params_map.remove("project_root"),
+    };
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationHub;
+
+    #[test]
+    fn test_file_uri_path() {
+        assert_eq!(
+            file_uri_path("file:///home/dev/my%20app").as_deref(),
+            Some("/home/dev/my app")
+        );
+        assert_eq!(
+            file_uri_path("file://localhost/srv/app").as_deref(),
+            Some("/srv/app")
+        );
+        assert_eq!(
+            file_uri_path("file:///C:/work/app").as_deref(),
+            Some("C:/work/app")
+        );
+        assert_eq!(file_uri_path("file://build-host/srv/app"), None);
+        assert_eq!(file_uri_path("https://example.com/app"), None);
+
+        assert!(with_project_root(json!({"project_root": "/explicit"}), Some("/client")).is_err());
+        assert!(with_project_root(json!({"project_root": "/explicit"}), None).is_err());
+        let params = with_project_root(json!({"project_root": "/client"}), Some("/client"));
+        assert_eq!(params.unwrap()["project_root"], "/client");
+        assert_eq!(
+            with_project_root(Value::Null, Some("/client")).unwrap()["project_root"],
+            "/client"
+        );
+        assert!(with_project_root(json!({}), None).unwrap()["project_root"].is_null());
+
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("app")).unwrap();
+        assert!(is_within(&root.path().join("app"), root.path()));
+        assert!(is_within(root.path(), root.path()));
+        assert!(!is_within(
+            &root.path().join("app/.."),
+            &root.path().join("app")
+        ));
+        assert!(!is_within(&root.path().join("missing"), root.path()));
+    }
+
+    #[tokio::test]
+    async fn test_request_project_root() {
+        let hub = NotificationHub::default();
+        let requests = ClientRequests::new(hub.clone());
+        requests.session_opened("editor", Some(&json!({"roots": {"listChanged": true}})));
+        let mut stream = hub.subscribe();
+        let client = requests.clone();
+        tokio::spawn(async move {
+            let sent = stream.recv().await.unwrap();
+            assert_eq!(sent.message["method"], "roots/list");
+            client.resolve(
+                Some("editor"),
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": sent.message["id"],
+                    "result": {"roots": [
+                        {"uri": "https://example.com/docs", "name": "Docs"},
+                        {"uri": "file:///home/dev/app", "name": "App"}
+                    ]}
+                }),
+            );
+        });
+
+        let root = request_project_root(&requests, "editor", Duration::from_secs(5)).await;
+        assert_eq!(root.unwrap().as_deref(), Some("/home/dev/app"));
+    }
+}
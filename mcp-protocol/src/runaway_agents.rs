@@ -0,0 +1,354 @@
+//! Detection and throttling of runaway agents
+//!
+//! An agent stuck in a loop burns tokens without moving work forward: it
+//! calls the same tool with the same parameters over and over, or claims and
+//! releases the same task again and again. The detector watches the calls of
+//! each agent authenticated by its header or session, never a name taken from
+//! the parameters; once a pattern shows, the agent is throttled for a while
+//! and the pattern is reported so the coordinator can step in. Detection is
+//! off unless enabled, since legitimate polling can look like a loop.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+use task_core::{EventSeverity, SystemEvent};
+
+/// Seconds over which identical calls are counted
+const REPEATED_CALL_WINDOW_SECS: i64 = 60;
+
+/// When an agent counts as running away, and how long it is throttled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunawayPolicy {
+    pub enabled: bool,
+    /// Calls of one tool with identical params allowed per minute
+    pub max_identical_calls_per_minute: u32,
+    /// Releases of the same task allowed within the cycle window
+    pub max_claim_cycles: u32,
+    pub claim_cycle_window_minutes: u32,
+    /// Seconds the agent's calls are rejected once a pattern is detected
+    pub throttle_seconds: u32,
+}
+
+impl Default for RunawayPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_identical_calls_per_minute: 20,
+            max_claim_cycles: 3,
+            claim_cycle_window_minutes: 10,
+            throttle_seconds: 60,
+        }
+    }
+}
+
+/// Pathological behavior of an agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RunawayPattern {
+    /// The same tool was called with identical params too often
+    RepeatedCall {
+        agent_name: String,
+        tool_name: String,
+        params: Value,
+        calls: usize,
+        window_seconds: i64,
+    },
+    /// The same task was claimed and released too often
+    ClaimPingPong {
+        agent_name: String,
+        task_id: i64,
+        cycles: usize,
+        window_minutes: u32,
+    },
+}
+
+impl RunawayPattern {
+    pub fn agent_name(&self) -> &str {
+        match self {
+            RunawayPattern::RepeatedCall { agent_name, .. }
+            | RunawayPattern::ClaimPingPong { agent_name, .. } => agent_name,
+        }
+    }
+
+    /// Type of the system event recording the pattern
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            RunawayPattern::RepeatedCall { .. } => "runaway_repeated_call",
+            RunawayPattern::ClaimPingPong { .. } => "runaway_claim_ping_pong",
+        }
+    }
+
+    /// What the agent did, for people
+    pub fn describe(&self) -> String {
+        match self {
+            RunawayPattern::RepeatedCall {
+                agent_name,
+                tool_name,
+                calls,
+                window_seconds,
+                ..
+            } => format!(
+                "agent '{agent_name}' called {tool_name} {calls} times in {window_seconds} s with identical params"
+            ),
+            RunawayPattern::ClaimPingPong {
+                agent_name,
+                task_id,
+                cycles,
+                window_minutes,
+            } => format!(
+                "agent '{agent_name}' claimed and released task {task_id} {cycles} times in {window_minutes} min"
+            ),
+        }
+    }
+
+    /// Warning event describing the pattern, not yet stored
+    pub fn to_system_event(&self, now: DateTime<Utc>) -> SystemEvent {
+        SystemEvent {
+            id: 0,
+            event_type: self.event_type().to_string(),
+            entity_id: Some(self.agent_name().to_string()),
+            data: serde_json::to_value(self).unwrap_or_default(),
+            triggered_by: Some(self.agent_name().to_string()),
+            timestamp: now,
+            severity: EventSeverity::Warning,
+        }
+    }
+}
+
+/// An agent whose calls are rejected for now
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throttle {
+    pub pattern: RunawayPattern,
+    pub until: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct AgentActivity {
+    /// Recent calls: time, tool and serialized params
+    calls: VecDeque<(DateTime<Utc>, String, String)>,
+    /// Recent successful releases: time and task ID
+    releases: VecDeque<(DateTime<Utc>, i64)>,
+    throttle: Option<Throttle>,
+}
+
+/// Watches the calls of identified agents for runaway patterns
+#[derive(Debug, Clone, Default)]
+pub struct RunawayDetector {
+    policy: RunawayPolicy,
+    agents: Arc<Mutex<HashMap<String, AgentActivity>>>,
+}
+
+impl RunawayDetector {
+    pub fn new(policy: RunawayPolicy) -> Self {
+        Self {
+            policy,
+            agents: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The throttle on `agent_name` at `now`, if any
+    pub fn throttle(&self, agent_name: &str, now: DateTime<Utc>) -> Option<Throttle> {
+        let mut agents = self.agents.lock().unwrap();
+        let activity = agents.get_mut(agent_name)?;
+        match &activity.throttle {
+            Some(throttle) if throttle.until > now => Some(throttle.clone()),
+            Some(_) => {
+                activity.throttle = None;
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Record a call of `tool_name`; returns the pattern it completes, throttling the agent
+    pub fn observe(
+        &self,
+        agent_name: &str,
+        tool_name: &str,
+        params: &Value,
+        succeeded: bool,
+        now: DateTime<Utc>,
+    ) -> Option<RunawayPattern> {
+        if !self.policy.enabled {
+            return None;
+        }
+        let mut agents = self.agents.lock().unwrap();
+        let activity = agents.entry(agent_name.to_string()).or_default();
+
+        let key = params.to_string();
+        let call_cutoff = now - Duration::seconds(REPEATED_CALL_WINDOW_SECS);
+        activity.calls.retain(|(at, _, _)| *at > call_cutoff);
+        activity
+            .calls
+            .push_back((now, tool_name.to_string(), key.clone()));
+        let identical = activity
+            .calls
+            .iter()
+            .filter(|(_, tool, params)| tool == tool_name && *params == key)
+            .count();
+
+        let cycle_cutoff =
+            now - Duration::minutes(i64::from(self.policy.claim_cycle_window_minutes));
+        activity.releases.retain(|(at, _)| *at > cycle_cutoff);
+        let released = (tool_name == "release_task" && succeeded)
+            .then(|| params.get("task_id").and_then(Value::as_i64))
+            .flatten();
+        if let Some(task_id) = released {
+            activity.releases.push_back((now, task_id));
+        }
+        let cycles = released.map_or(0, |task_id| {
+            activity
+                .releases
+                .iter()
+                .filter(|(_, id)| *id == task_id)
+                .count()
+        });
+
+        let pattern = if identical > self.policy.max_identical_calls_per_minute as usize {
+            RunawayPattern::RepeatedCall {
+                agent_name: agent_name.to_string(),
+                tool_name: tool_name.to_string(),
+                params: params.clone(),
+                calls: identical,
+                window_seconds: REPEATED_CALL_WINDOW_SECS,
+            }
+        } else if cycles > self.policy.max_claim_cycles as usize {
+            RunawayPattern::ClaimPingPong {
+                agent_name: agent_name.to_string(),
+                task_id: released.unwrap_or_default(),
+                cycles,
+                window_minutes: self.policy.claim_cycle_window_minutes,
+            }
+        } else {
+            return None;
+        };
+
+        // Start counting afresh once the throttle lifts
+        activity.calls.clear();
+        activity.releases.clear();
+        activity.throttle = Some(Throttle {
+            pattern: pattern.clone(),
+            until: now + Duration::seconds(i64::from(self.policy.throttle_seconds)),
+        });
+        Some(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy() -> RunawayPolicy {
+        RunawayPolicy {
+            enabled: true,
+            max_identical_calls_per_minute: 3,
+            max_claim_cycles: 2,
+            ..RunawayPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_repeated_calls_throttle_agent() {
+        let detector = RunawayDetector::new(policy());
+        let now = Utc::now();
+        let params = json!({"state": "Created"});
+        for i in 0..3 {
+            let at = now + Duration::seconds(i);
+            assert_eq!(
+                detector.observe("poller", "list_tasks", &params, true, at),
+                None
+            );
+        }
+        // Different params and other agents are counted apart
+        let other = json!({"state": "Done"});
+        assert_eq!(
+            detector.observe("poller", "list_tasks", &other, true, now),
+            None
+        );
+        assert_eq!(
+            detector.observe("worker", "list_tasks", &params, true, now),
+            None
+        );
+
+        let pattern = detector
+            .observe(
+                "poller",
+                "list_tasks",
+                &params,
+                true,
+                now + Duration::seconds(3),
+            )
+            .unwrap();
+        assert_eq!(pattern.event_type(), "runaway_repeated_call");
+        assert!(pattern.describe().contains("list_tasks 4 times"));
+        let throttle = detector
+            .throttle("poller", now + Duration::seconds(10))
+            .unwrap();
+        assert_eq!(throttle.pattern, pattern);
+        assert!(detector.throttle("worker", now).is_none());
+        assert!(detector
+            .throttle("poller", now + Duration::seconds(70))
+            .is_none());
+
+        // Calls spread beyond the window are fine
+        let detector = RunawayDetector::new(policy());
+        for i in 0..10 {
+            let at = now + Duration::seconds(i * 30);
+            assert_eq!(
+                detector.observe("poller", "list_tasks", &params, true, at),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_claim_ping_pong() {
+        let detector = RunawayDetector::new(RunawayPolicy {
+            max_identical_calls_per_minute: 10,
+            ..policy()
+        });
+        let now = Utc::now();
+        let release = json!({"task_id": 7, "agent_name": "flaky"});
+        for i in 0..2 {
+            let at = now + Duration::minutes(i);
+            detector.observe("flaky", "claim_task", &release, true, at);
+            assert_eq!(
+                detector.observe("flaky", "release_task", &release, true, at),
+                None
+            );
+        }
+        // A failed release is no cycle
+        assert_eq!(
+            detector.observe("flaky", "release_task", &release, false, now),
+            None
+        );
+
+        let pattern = detector
+            .observe(
+                "flaky",
+                "release_task",
+                &release,
+                true,
+                now + Duration::minutes(3),
+            )
+            .unwrap();
+        assert!(matches!(
+            pattern,
+            RunawayPattern::ClaimPingPong {
+                task_id: 7,
+                cycles: 3,
+                ..
+            }
+        ));
+        assert_eq!(
+            pattern.to_system_event(now).entity_id.as_deref(),
+            Some("flaky")
+        );
+    }
+}
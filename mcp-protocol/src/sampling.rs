@@ -0,0 +1,147 @@
+//! LLM completions requested from the connected client (MCP sampling)
+//!
+//! Features that want a model's help, such as thread summaries, need not
+//! bring their own API key: a client that declares the `sampling` capability
+//! on `initialize` runs `sampling/createMessage` requests on its own model.
+//! Only the client whose request is being served is asked, so one agent's
+//! data never reaches another user's model; work done outside a request
+//! finds no sampler.
+
+use crate::client_requests::ClientRequests;
+use serde_json::json;
+use std::future::Future;
+use std::time::Duration;
+use task_core::{Result, TaskError};
+
+tokio::task_local! {
+    static REQUEST_SESSION: Option<String>;
+}
+
+/// Run `future` on behalf of the client of `session_id`, letting it sample
+pub async fn on_behalf_of<F: Future>(session_id: Option<String>, future: F) -> F::Output {
+    REQUEST_SESSION.scope(session_id, future).await
+}
+
+/// How long a client may take to answer, including any approval by its user
+pub const DEFAULT_SAMPLING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A completion to request
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingRequest {
+    pub system_prompt: String,
+    /// User message the model answers
+    pub prompt: String,
+    pub max_tokens: u32,
+}
+
+/// Asks the models of connected clients for completions
+#[derive(Debug, Clone)]
+pub struct ClientSampler {
+    requests: ClientRequests,
+    timeout: Duration,
+}
+
+impl ClientSampler {
+    /// Sampler sending its requests through `requests`
+    pub fn new(requests: ClientRequests) -> Self {
+        Self {
+            requests,
+            timeout: DEFAULT_SAMPLING_TIMEOUT,
+        }
+    }
+
+    /// How long to wait for a client's answer
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether the client being served can sample
+    pub fn is_available(&self) -> bool {
+        self.session().is_some()
+    }
+
+    fn session(&self) -> Option<String> {
+        REQUEST_SESSION
+            .try_with(Clone::clone)
+            .ok()
+            .flatten()
+            .filter(|session_id| self.requests.supports(session_id, "sampling"))
+    }
+
+    /// Text the client's model answers `request` with
+    pub async fn create_message(&self, request: SamplingRequest) -> Result<String> {
+        let Some(session_id) = self.session() else {
+            return Err(TaskError::UnsupportedOperation(
+                "The requesting client does not support sampling".to_string(),
+            ));
+        };
+        let params = json!({
+            "messages": [{
+                "role": "user",
+                "content": {"type": "text", "text": request.prompt}
+            }],
+            "systemPrompt": request.system_prompt,
+            "includeContext": "none",
+            "maxTokens": request.max_tokens
+        });
+        let result = self
+            .requests
+            .request(&session_id, "sampling/createMessage", params, self.timeout)
+            .await?;
+        result["content"]["text"]
+            .as_str()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+            .ok_or_else(|| TaskError::Protocol("Client sampled no text".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationHub;
+
+    #[tokio::test]
+    async fn test_create_message() {
+        let hub = NotificationHub::default();
+        let requests = ClientRequests::new(hub.clone());
+        let sampler = ClientSampler::new(requests.clone());
+        let request = SamplingRequest {
+            system_prompt: "Summarize".to_string(),
+            prompt: "Thread".to_string(),
+            max_tokens: 100,
+        };
+        assert!(matches!(
+            sampler.create_message(request.clone()).await,
+            Err(TaskError::UnsupportedOperation(_))
+        ));
+        requests.session_opened("sampling", Some(&json!({"sampling": {}})));
+        assert!(!sampler.is_available());
+        let other = on_behalf_of(Some("other".to_string()), async { sampler.is_available() });
+        assert!(!other.await);
+
+        let mut stream = hub.subscribe();
+        let answer = tokio::spawn(async move {
+            let sent = stream.recv().await.unwrap();
+            assert_eq!(sent.message["method"], "sampling/createMessage");
+            assert_eq!(sent.message["params"]["maxTokens"], 100);
+            requests.resolve(
+                Some("sampling"),
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": sent.message["id"],
+                    "result": {"role": "assistant", "content": {"type": "text", "text": " Summary "}}
+                }),
+            )
+        });
+
+        let requested = sampler.create_message(request);
+        let text = on_behalf_of(Some("sampling".to_string()), requested)
+            .await
+            .unwrap();
+        assert!(answer.await.unwrap());
+        assert_eq!(text, "Summary");
+    }
+}
@@ -3,26 +3,229 @@
 //! Handles conversion between internal Task types and MCP JSON format.
 
 use crate::error::McpError;
-use ::task_core::Task;
-use serde::Deserialize;
+use ::task_core::{
+    Task, TaskBatch, TaskClaim, TaskComment, TaskCommentRevision, TaskOperationOutcome,
+};
+use chrono::Utc;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+/// Number of most recent comments included in task summaries
+const COMMENT_SUMMARY_LATEST: usize = 3;
+
+/// Maximum length of comment excerpts in task summaries, in characters
+const COMMENT_EXCERPT_CHARS: usize = 160;
+
 /// Serialize task for MCP response
 pub fn serialize_task_for_mcp(task: &Task) -> Result<Value, McpError> {
     let task_json = json!({
         "id": task.id,
+        "uid": task.uid,
         "code": task.code,
         "name": task.name,
         "description": task.description,
         "owner_agent_name": task.owner_agent_name,
         "state": task.state,
         "inserted_at": task.inserted_at.to_rfc3339(),
-        "done_at": task.done_at.map(|dt| dt.to_rfc3339())
+        "done_at": task.done_at.map(|dt| dt.to_rfc3339()),
+        "claimed_at": task.claimed_at.map(|dt| dt.to_rfc3339()),
+        "lease_expires_at": task.lease_expires_at.map(|dt| dt.to_rfc3339()),
+        "updated_at": task.updated_at.to_rfc3339(),
+        "state_changed_at": task.state_changed_at.to_rfc3339(),
+        "time_in_state_seconds": (Utc::now() - task.state_changed_at).num_seconds().max(0),
+        "required_capabilities": task.required_capabilities
+    });
+
+    Ok(task_json)
+}
+
+/// Serialize the claims of a task, oldest first, for the `claim_history` field
+pub fn serialize_claim_history_for_mcp(claims: &[TaskClaim]) -> Value {
+    claims
+        .iter()
+        .map(|claim| {
+            json!({
+                "agent_name": claim.agent_name,
+                "claimed_at": claim.claimed_at.to_rfc3339(),
+                "released_at": claim.released_at.map(|dt| dt.to_rfc3339()),
+                "release_reason": claim.release_reason
+            })
+        })
+        .collect()
+}
+
+/// Serialize a batch lookup result: the tasks found and the keys matching none
+pub fn serialize_task_batch_for_mcp<K: Serialize>(batch: &TaskBatch<K>) -> Result<Value, McpError> {
+    let tasks = batch
+        .tasks
+        .iter()
+        .map(serialize_task_for_mcp)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(json!({
+        "tasks": tasks,
+        "missing": batch.missing
+    }))
+}
+
+/// Serialize the outcomes of `apply_task_operations`, one entry per operation
+pub fn serialize_operation_outcomes_for_mcp(
+    outcomes: &[TaskOperationOutcome],
+) -> Result<Value, McpError> {
+    let results = outcomes
+        .iter()
+        .map(|outcome| match outcome {
+            TaskOperationOutcome::Task(task) => Ok(json!({"task": serialize_task_for_mcp(task)?})),
+            TaskOperationOutcome::Message(message) => Ok(json!({"message": message})),
+        })
+        .collect::<Result<Vec<_>, McpError>>()?;
+
+    Ok(json!({"results": results}))
+}
+
+/// Serialize task with a summary of its comments (count and latest excerpts)
+pub fn serialize_task_with_comments_for_mcp(
+    task: &Task,
+    comments: &[TaskComment],
+) -> Result<Value, McpError> {
+    let mut task_json = serialize_task_for_mcp(task)?;
+    let latest: Vec<Value> = comments
+        .iter()
+        .rev()
+        .take(COMMENT_SUMMARY_LATEST)
+        .map(|comment| {
+            json!({
+                "id": comment.id,
+                "author_agent_name": comment.author_agent_name,
+                "created_at": comment.created_at.to_rfc3339(),
+                "excerpt": markdown_excerpt(&comment.content, COMMENT_EXCERPT_CHARS)
+            })
+        })
+        .collect();
+
+    task_json["comments"] = json!({
+        "count": comments.len(),
+        "latest": latest
     });
 
     Ok(task_json)
 }
 
+/// Serialize comment for MCP response, with its markdown rendered to HTML
+pub fn serialize_comment_for_mcp(comment: &TaskComment) -> Value {
+    json!({
+        "id": comment.id,
+        "task_code": comment.task_code,
+        "author_agent_name": comment.author_agent_name,
+        "content": comment.content,
+        "content_html": render_markdown_html(&comment.content),
+        "revision": comment.revision,
+        "created_at": comment.created_at.to_rfc3339(),
+        "updated_at": comment.updated_at.map(|dt| dt.to_rfc3339()),
+        "deleted_at": comment.deleted_at.map(|dt| dt.to_rfc3339())
+    })
+}
+
+/// Serialize superseded comment revision for MCP response
+pub fn serialize_comment_revision_for_mcp(revision: &TaskCommentRevision) -> Value {
+    json!({
+        "comment_id": revision.comment_id,
+        "revision": revision.revision,
+        "content": revision.content,
+        "edited_by": revision.edited_by,
+        "edited_at": revision.edited_at.to_rfc3339()
+    })
+}
+
+/// URL schemes links and images may use; relative URLs are always allowed
+const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Whether `url` is relative or uses one of the safe schemes
+///
+/// Browsers ignore whitespace and control characters inside a scheme, so
+/// they are dropped before it is read.
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => {
+            SAFE_URL_SCHEMES.contains(&url[..end].to_ascii_lowercase().as_str())
+        }
+        _ => true,
+    }
+}
+
+/// Replace an unsafe link or image destination with an inert one
+fn safe_destination(url: CowStr<'_>) -> CowStr<'_> {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// Render markdown to HTML; raw HTML in the source is escaped, not passed
+/// through, and links and images keep only http, https, mailto and relative URLs
+pub fn render_markdown_html(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_destination(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_destination(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    });
+
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, events);
+    rendered
+}
+
+/// Plain-text excerpt of markdown, truncated to `max_chars` characters
+pub fn markdown_excerpt(markdown: &str, max_chars: usize) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) | Event::Html(t) | Event::InlineHtml(t) => {
+                text.push_str(&t)
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let mut excerpt: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    excerpt.push('…');
+    excerpt
+}
+
 /// Deserialize MCP parameters
 pub fn deserialize_mcp_params<T>(params: Value) -> Result<T, McpError>
 where
@@ -76,6 +279,89 @@ mod tests {
         assert_eq!(params.name, "Test Task");
     }
 
+    #[test]
+    fn test_markdown_rendering_and_excerpts() {
+        let markdown = "## Status\n\n**Blocked** on `DB-02`, see <script>alert(1)</script>";
+
+        let rendered = render_markdown_html(markdown);
+        assert!(rendered.contains("<h2>Status</h2>"));
+        assert!(rendered.contains("<strong>Blocked</strong>"));
+        assert!(!rendered.contains("<script>"));
+
+        assert_eq!(
+            markdown_excerpt(markdown, 200),
+            "Status Blocked on DB-02, see <script>alert(1)</script>"
+        );
+        assert_eq!(markdown_excerpt(markdown, 10), "Status Bl…");
+    }
+
+    #[test]
+    fn test_render_markdown_html_neutralises_unsafe_urls() {
+        for markdown in [
+            "[x](javascript:alert(document.cookie))",
+            "![x](javascript:alert(1))",
+            "[x](JavaScript:alert(1))",
+            "[x](java&#9;script:alert(1))",
+            "<javascript:alert(1)>",
+            "[x](data:text/html;base64,PHNjcmlwdD4=)",
+            "![x](data:image/svg+xml;base64,PHN2Zz4=)",
+            "[x][ref]\n\n[ref]: vbscript:msgbox(1)",
+        ] {
+            let rendered = render_markdown_html(markdown);
+            assert!(
+                rendered.contains(r##"href="#""##) || rendered.contains(r##"src="#""##),
+                "{markdown} rendered as {rendered}"
+            );
+            let lowered = rendered.to_ascii_lowercase();
+            assert!(
+                !lowered.contains(r#"="javascript"#) && !lowered.contains(r#"="data"#),
+                "{markdown} rendered as {rendered}"
+            );
+        }
+
+        let rendered = render_markdown_html(
+            "[site](https://example.com) [mail](mailto:dev@example.com) \
+             [doc](docs/API.md#tools) ![diagram](/img/flow.png)",
+        );
+        assert!(rendered.contains(r#"href="https://example.com""#));
+        assert!(rendered.contains(r#"href="mailto:dev@example.com""#));
+        assert!(rendered.contains(r#"href="docs/API.md#tools""#));
+        assert!(rendered.contains(r#"src="/img/flow.png""#));
+    }
+
+    #[test]
+    fn test_task_claim_metadata() {
+        let mut task = Task::new(
+            7,
+            "CLM-01".to_string(),
+            "Claimed".to_string(),
+            "Claim metadata".to_string(),
+            Some("backend-dev".to_string()),
+            ::task_core::TaskState::InProgress,
+            Utc::now(),
+            None,
+        );
+        let claimed_at = Utc::now() - chrono::Duration::minutes(5);
+        task.claimed_at = Some(claimed_at);
+        task.state_changed_at = claimed_at;
+
+        let value = serialize_task_for_mcp(&task).unwrap();
+        assert_eq!(value["claimed_at"], claimed_at.to_rfc3339());
+        assert_eq!(value["state_changed_at"], claimed_at.to_rfc3339());
+        let seconds = value["time_in_state_seconds"].as_i64().unwrap();
+        assert!((300..310).contains(&seconds));
+
+        let history = serialize_claim_history_for_mcp(&[TaskClaim {
+            task_id: 7,
+            agent_name: "qa-bot".to_string(),
+            claimed_at,
+            released_at: Some(claimed_at),
+            release_reason: Some("released".to_string()),
+        }]);
+        assert_eq!(history[0]["agent_name"], "qa-bot");
+        assert_eq!(history[0]["release_reason"], "released");
+    }
+
     #[test]
     fn test_success_response() {
         let response = create_success_response(Some(json!(1)), json!({"success": true}));
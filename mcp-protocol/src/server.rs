@@ -4,42 +4,109 @@
 //! and legacy Server-Sent Events for backward compatibility.
 
 use axum::{
-    extract::State,
+    extract::{Extension, State},
     http::{header, HeaderMap, StatusCode},
     middleware,
-    response::Sse,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
     routing::{get, post},
     Json, Router,
 };
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
 use serde_json::{json, Value};
-use std::{net::SocketAddr, sync::Arc};
-use tokio::sync::mpsc;
+use std::{
+    borrow::Cow,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, mpsc},
+};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tracing::info;
+use tower_http::{compression::CompressionLayer, timeout::TimeoutLayer};
+use tracing::{debug, info, warn};
 
-use crate::{error::McpError, handler::McpTaskHandler, serialization::*};
+use crate::{
+    admin::{hide_admin_tools, is_admin_method, AdminAuth},
+    agent_identity::{attributed_agent, AgentAuth, Caller, OwnedWork},
+    background_jobs::JobRegistry,
+    client_requests::ClientRequests,
+    elicitation::{confirm_with_user, confirmation_flag, elicitation_timeout, needs_confirmation},
+    error::McpError,
+    handler::McpTaskHandler,
+    http_config::HttpConfig,
+    listeners::{guard_router, ListenerConfig},
+    method_timeouts::{MethodTimeouts, TimeoutMetrics, READ_ONLY_METHODS},
+    notifications::{NotificationHub, ServerNotification},
+    protocol_version::ProtocolVersion,
+    read_only::{hide_mutating_tools, is_allowed_when_read_only},
+    recorder::{record_exchange, Recorder},
+    roots::{is_within, request_project_root, with_project_root, DEFAULT_ROOTS_TIMEOUT},
+    runaway_agents::{RunawayDetector, RunawayPattern, RunawayPolicy},
+    sampling::{on_behalf_of, ClientSampler},
+    serialization::*,
+    session::{McpSession, SessionStore, MCP_SESSION_ID_HEADER},
+};
 use ::task_core::{
-    ClaimTaskParams, CreateTaskMessageParams, DiscoverWorkParams, EndWorkSessionParams,
-    GetTaskMessagesParams, ProtocolHandler, ReleaseTaskParams, StartWorkSessionParams,
-    TaskMessageRepository, TaskRepository, WorkspaceContextRepository,
+    extract_mentions, AddTaskCommentParams, AnomalyThresholds, ClaimLeasePolicy, ClaimTaskParams,
+    CreateTaskMessageParams, DeleteTaskCommentParams, DiscoverWorkParams, DonePolicy,
+    DuplicateCheckMode, DuplicateCheckPolicy, EditTaskCommentParams, EmbeddingProvider,
+    EndWorkSessionParams, FindDuplicateTasksParams, GetAgentPerformanceParams,
+    GetMessageTemplatesParams, GetMyMentionsParams, GetSystemEventsParams,
+    GetTaskCommentHistoryParams, GetTaskCommentsParams, GetTaskMessagesParams,
+    GetToolUsageStatsParams, MessageCatalog, MessageEffectRule, PipelineTemplates, ProtocolHandler,
+    ReleaseTaskParams, RetentionPolicy, RoutingRules, StartWorkSessionParams, Task, TaskError,
+    TaskMessageRepository, TaskRepository, ThreadSummarizer, ThreadSummaryPolicy, TokenBudgets,
+    ToolCall, WorkDiscoveryPolicy, WorkSessionPolicy, WorkspaceContextRepository,
 };
 
 /// MCP Protocol Version as required by 2025-06-18 specification
-const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+const MCP_PROTOCOL_VERSION: &str = ProtocolVersion::LATEST.as_str();
 
 /// Shared server state for handlers
 #[derive(Clone)]
-pub struct McpServerState<R, M, W> {
+pub struct McpServerState<R: ?Sized, M, W> {
     pub handler: McpTaskHandler<R, M, W>,
+    pub sessions: SessionStore,
+    pub require_session: bool,
+    pub notifications: NotificationHub,
+    pub client_requests: ClientRequests,
+    pub method_timeouts: MethodTimeouts,
+    pub timeout_metrics: TimeoutMetrics,
+    pub background_jobs: JobRegistry,
+    pub admin_auth: AdminAuth,
+    pub agent_auth: AgentAuth,
+    pub runaway_agents: RunawayDetector,
+    pub read_only: bool,
+    /// Time a request may take before the HTTP layer answers 408
+    pub request_timeout: Option<std::time::Duration>,
 }
 
 /// MCP Server with multiple transport support
-pub struct McpServer<R, M, W> {
+pub struct McpServer<R: ?Sized, M, W> {
     handler: McpTaskHandler<R, M, W>,
+    http_config: HttpConfig,
+    method_timeouts: MethodTimeouts,
+    background_jobs: JobRegistry,
+    admin_auth: AdminAuth,
+    agent_auth: AgentAuth,
+    runaway_policy: RunawayPolicy,
+    read_only: bool,
+    recorder: Option<Arc<Recorder>>,
+    notifications: NotificationHub,
+    client_requests: ClientRequests,
 }
 
 impl<
-        R: TaskRepository + Send + Sync + 'static,
+        R: TaskRepository + Send + Sync + ?Sized + 'static,
         M: TaskMessageRepository + Send + Sync + 'static,
         W: WorkspaceContextRepository + Send + Sync + 'static,
     > McpServer<R, M, W>
@@ -51,6 +118,7 @@ impl<
         workspace_context_repository: Arc<W>,
         project_root: Option<std::path::PathBuf>,
     ) -> Self {
+        let notifications = NotificationHub::default();
         Self {
             handler: McpTaskHandler::new(
                 repository,
@@ -58,21 +126,216 @@ impl<
                 workspace_context_repository,
                 project_root,
             ),
+            http_config: HttpConfig::default(),
+            method_timeouts: MethodTimeouts::default(),
+            background_jobs: JobRegistry::default(),
+            admin_auth: AdminAuth::default(),
+            agent_auth: AgentAuth::default(),
+            runaway_policy: RunawayPolicy::default(),
+            read_only: false,
+            recorder: None,
+            client_requests: ClientRequests::new(notifications.clone()),
+            notifications,
         }
     }
 
+    /// Override HTTP transport tuning (compression, keep-alive, timeouts)
+    pub fn with_http_config(mut self, http_config: HttpConfig) -> Self {
+        self.http_config = http_config;
+        self
+    }
+
+    /// Override per-method execution budgets
+    pub fn with_method_timeouts(mut self, method_timeouts: MethodTimeouts) -> Self {
+        self.method_timeouts = method_timeouts;
+        self
+    }
+
+    /// Report background job status from a scheduler started by the caller
+    pub fn with_background_jobs(mut self, background_jobs: JobRegistry) -> Self {
+        self.background_jobs = background_jobs;
+        self
+    }
+
+    /// Apply task changes when messages of the configured types are posted
+    pub fn with_message_effects(mut self, message_effects: Vec<MessageEffectRule>) -> Self {
+        self.handler = self.handler.with_message_effects(message_effects);
+        self
+    }
+
+    /// Retention policy reported by `get_retention_report`
+    pub fn with_retention_policy(mut self, retention_policy: RetentionPolicy) -> Self {
+        self.handler = self.handler.with_retention_policy(retention_policy);
+        self
+    }
+
+    /// Lease given to claims and the longest lease agents may request
+    pub fn with_claim_lease_policy(mut self, claim_lease_policy: ClaimLeasePolicy) -> Self {
+        self.handler = self.handler.with_claim_lease_policy(claim_lease_policy);
+        self
+    }
+
+    /// Limits on concurrent sessions enforced by `start_work_session`
+    pub fn with_work_session_policy(mut self, work_session_policy: WorkSessionPolicy) -> Self {
+        self.handler = self.handler.with_work_session_policy(work_session_policy);
+        self
+    }
+
+    /// When `rebalance_work` counts agents as overloaded, and how much work it moves
+    pub fn with_rebalance_policy(mut self, rebalance_policy: ::task_core::RebalancePolicy) -> Self {
+        self.handler = self.handler.with_rebalance_policy(rebalance_policy);
+        self
+    }
+
+    /// When `get_agent_performance` flags agent performance as anomalous
+    pub fn with_anomaly_thresholds(mut self, anomaly_thresholds: AnomalyThresholds) -> Self {
+        self.handler = self.handler.with_anomaly_thresholds(anomaly_thresholds);
+        self
+    }
+
+    /// Language and overrides of workspace setup response messages
+    pub fn with_message_catalog(mut self, messages: MessageCatalog) -> Self {
+        self.handler = self.handler.with_message_catalog(messages);
+        self
+    }
+
+    /// Provider embedding records and queries for `semantic_search`
+    pub fn with_embedding_provider(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        self.handler = self.handler.with_embedding_provider(provider);
+        self
+    }
+
+    /// Policy filtering and reordering the tasks `discover_work` offers
+    pub fn with_discovery_policy(mut self, policy: Arc<dyn WorkDiscoveryPolicy>) -> Self {
+        self.handler = self.handler.with_discovery_policy(policy);
+        self
+    }
+
+    /// Rules routing tasks to agents when they are created or released
+    pub fn with_routing_rules(mut self, rules: RoutingRules) -> Self {
+        self.handler = self.handler.with_routing_rules(rules);
+        self
+    }
+
+    /// Requirements a task must meet before it may move to `Done`
+    pub fn with_done_policy(mut self, policy: DonePolicy) -> Self {
+        self.handler = self.handler.with_done_policy(policy);
+        self
+    }
+
+    /// Templates `create_pipeline` materializes stage tasks from
+    pub fn with_pipeline_templates(mut self, templates: PipelineTemplates) -> Self {
+        self.handler = self.handler.with_pipeline_templates(templates);
+        self
+    }
+
+    /// Announce tasks moved to a new state on `sender`, e.g. to run hooks
+    pub fn with_state_changes(mut self, sender: broadcast::Sender<Task>) -> Self {
+        self.handler = self.handler.with_state_changes(sender);
+        self
+    }
+
+    /// How new tasks are checked against open tasks before they are created
+    pub fn with_duplicate_check_policy(mut self, policy: DuplicateCheckPolicy) -> Self {
+        self.handler = self.handler.with_duplicate_check_policy(policy);
+        self
+    }
+
+    /// Token budgets of generated files and prompts and of default task context bundles
+    pub fn with_token_budgets(mut self, token_budgets: TokenBudgets) -> Self {
+        self.handler = self.handler.with_token_budgets(token_budgets);
+        self
+    }
+
+    /// When long threads are summarized, and the summarizer condensing them
+    pub fn with_thread_summaries(
+        mut self,
+        policy: ThreadSummaryPolicy,
+        summarizer: Arc<dyn ThreadSummarizer>,
+    ) -> Self {
+        self.handler = self.handler.with_thread_summaries(policy, summarizer);
+        self
+    }
+
+    /// Unlock admin tools for clients presenting `api_key` as a bearer token
+    pub fn with_admin_api_key(mut self, api_key: Option<String>) -> Self {
+        self.admin_auth = AdminAuth::new(api_key);
+        self
+    }
+
+    /// Tie task and work session mutations to the calling agent's identity
+    pub fn with_agent_auth(mut self, agent_auth: AgentAuth) -> Self {
+        self.agent_auth = agent_auth;
+        self
+    }
+
+    /// Throttle agents stuck in loops and report them to the coordinator
+    pub fn with_runaway_policy(mut self, policy: RunawayPolicy) -> Self {
+        self.runaway_policy = policy;
+        self
+    }
+
+    /// Reject every tool that changes tasks, messages, agents or workspace files
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Record every JSON-RPC exchange for later replay
+    pub fn with_recorder(mut self, recorder: Option<Arc<Recorder>>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    /// Hub the server publishes task changes and mentions to
+    ///
+    /// Subscribers outside the server, such as task lifecycle hooks, see the
+    /// same notifications as SSE clients.
+    pub fn notifications(&self) -> NotificationHub {
+        self.notifications.clone()
+    }
+
+    /// Sampler asking connected clients' models for completions
+    pub fn sampler(&self) -> ClientSampler {
+        ClientSampler::new(self.client_requests.clone())
+    }
+
     /// Start the MCP server for local PC usage
     pub async fn serve(self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let app = self.create_router();
-
         let socket_addr: SocketAddr = addr
             .parse()
             .map_err(|e| format!("Invalid address '{addr}': {e}"))?;
 
-        info!("Starting MCP server on {}", socket_addr);
+        let listener = TcpListener::bind(socket_addr).await?;
+        self.serve_listener(listener).await
+    }
+
+    /// Serve on an already bound listener, e.g. one bound to port 0
+    pub async fn serve_listener(
+        self,
+        listener: TcpListener,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.serve_listeners(vec![(listener, ListenerConfig::default())])
+            .await
+    }
+
+    /// Serve the same board on several bound listeners, each with its own requirements
+    pub async fn serve_listeners(
+        self,
+        listeners: Vec<(TcpListener, ListenerConfig)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let http_config = Arc::new(self.http_config.clone());
+        let admin_auth = self.admin_auth.clone();
+        let router = self.create_router();
 
-        let listener = tokio::net::TcpListener::bind(socket_addr).await?;
-        axum::serve(listener, app).await?;
+        let mut connections = tokio::task::JoinSet::new();
+        for (listener, config) in listeners {
+            info!("Starting MCP server on {}", listener.local_addr()?);
+            let app = guard_router(router.clone(), config, admin_auth.clone());
+            let http_config = http_config.clone();
+            connections.spawn(async move { serve_connections(listener, app, &http_config).await });
+        }
+        connections.join_next().await;
 
         Ok(())
     }
@@ -81,21 +344,103 @@ impl<
     fn create_router(self) -> Router {
         let state = Arc::new(McpServerState {
             handler: self.handler,
+            sessions: SessionStore::new(self.http_config.session_ttl_secs)
+                .with_client_requests(self.client_requests.clone()),
+            require_session: self.http_config.require_session,
+            notifications: self.notifications,
+            client_requests: self.client_requests,
+            method_timeouts: self.method_timeouts,
+            timeout_metrics: TimeoutMetrics::default(),
+            background_jobs: self.background_jobs,
+            admin_auth: self.admin_auth,
+            agent_auth: self.agent_auth,
+            runaway_agents: RunawayDetector::new(self.runaway_policy),
+            read_only: self.read_only,
+            request_timeout: self.http_config.request_timeout(),
         });
 
-        Router::new()
-            .route("/mcp", post(rpc_handler)) // MCP 2025-06-18 Streamable HTTP transport
+        let mut router = Router::new()
+            .route(
+                "/mcp",
+                get(session_stream_handler)
+                    .post(rpc_handler)
+                    .delete(delete_session_handler),
+            ) // MCP 2025-06-18 Streamable HTTP transport
             .route("/mcp/v1", get(sse_handler)) // Legacy SSE support (deprecated)
             .route("/mcp/v1/rpc", post(rpc_handler)) // Legacy RPC support (deprecated)
             .route("/health", get(health_handler))
-            .layer(middleware::from_fn(crate::request_logger::mcp_request_logging_middleware))
-            .with_state(state)
+            .layer(middleware::from_fn(
+                crate::request_logger::mcp_request_logging_middleware,
+            ));
+
+        if let Some(recorder) = self.recorder {
+            router = router.layer(middleware::from_fn_with_state(recorder, record_exchange));
+        }
+
+        if let Some(timeout) = self.http_config.request_timeout() {
+            router = router.layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                timeout,
+            ));
+        }
+
+        // SSE streams are excluded by the default compression predicate
+        if self.http_config.compression {
+            router = router.layer(CompressionLayer::new().gzip(true).deflate(true));
+        }
+
+        // Outermost so preflight requests are answered before any other layer
+        if self.http_config.cors.is_enabled() {
+            router = router.layer(self.http_config.cors.to_layer());
+        }
+
+        router.with_state(state)
+    }
+}
+
+/// Accept connections and serve them with the configured keep-alive behaviour
+///
+/// `axum::serve` does not expose hyper's connection settings, so connections are
+/// driven directly through hyper-util's auto (HTTP/1.1 + HTTP/2) builder.
+async fn serve_connections(listener: TcpListener, app: Router, http_config: &HttpConfig) {
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .keep_alive(http_config.keep_alive)
+        .header_read_timeout(http_config.keep_alive_timeout());
+    builder.http2().timer(TokioTimer::new());
+
+    loop {
+        let (stream, remote_addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+
+        // Small JSON-RPC responses should not wait on Nagle's algorithm
+        if let Err(e) = stream.set_nodelay(true) {
+            debug!("Failed to set TCP_NODELAY for {}: {}", remote_addr, e);
+        }
+
+        let builder = builder.clone();
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(e) = builder
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Connection from {} closed with error: {}", remote_addr, e);
+            }
+        });
     }
 }
 
 /// Execute MCP method - shared logic for both server instances and handlers
 async fn execute_mcp_method<
-    R: TaskRepository + Send + Sync,
+    R: TaskRepository + Send + Sync + ?Sized,
     M: TaskMessageRepository + Send + Sync,
     W: WorkspaceContextRepository + Send + Sync,
 >(
@@ -103,21 +448,162 @@ async fn execute_mcp_method<
     method: &str,
     params: Value,
     id: Option<Value>,
+    protocol_version: ProtocolVersion,
 ) -> Value {
     match method {
         "create_task" => {
+            let confirm_duplicate = params
+                .get("confirm_duplicate")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
             let params: CreateTaskParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
+            let duplicates = match handler
+                .find_duplicate_tasks(FindDuplicateTasksParams {
+                    name: params.name.clone(),
+                    description: params.description.clone(),
+                })
+                .await
+            {
+                Ok(duplicates) => duplicates,
+                Err(e) => return McpError::from(e).to_json_rpc_error(id),
+            };
+            let mode = handler.duplicate_check_policy().mode;
+            if mode == DuplicateCheckMode::Confirm && !duplicates.is_empty() && !confirm_duplicate {
+                return create_success_response(
+                    id,
+                    json!({
+                        "status": "confirmation_required",
+                        "potential_duplicates": duplicates,
+                        "message": format!(
+                            "Task {} was not created because it resembles {} open task(s); \
+                             resend with confirm_duplicate: true to create it anyway",
+                            params.code,
+                            duplicates.len()
+                        ),
+                    }),
+                );
+            }
             match handler.create_task(params).await {
                 Ok(task) => match serialize_task_for_mcp(&task) {
-                    Ok(value) => create_success_response(id, value),
+                    Ok(mut value) => {
+                        if !duplicates.is_empty() {
+                            value["potential_duplicates"] = json!(duplicates);
+                        }
+                        create_success_response(id, value)
+                    }
                     Err(e) => e.to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
+        "create_pipeline" => {
+            let params: ::task_core::CreatePipelineParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.create_pipeline(params).await {
+                Ok(pipeline) => match serde_json::to_value(pipeline) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_task_context" => {
+            let params: ::task_core::GetTaskContextParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_task_context(params).await {
+                Ok(context) => match serde_json::to_value(context) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "register_artifact" => {
+            let params: ::task_core::RegisterArtifactParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.register_artifact(params).await {
+                Ok(artifact) => match serde_json::to_value(artifact) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "list_artifacts" => {
+            let params: ::task_core::ListArtifactsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.list_artifacts(params).await {
+                Ok(artifacts) => match serde_json::to_value(artifacts) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "scan_code_todos" => {
+            let params: ::task_core::ScanCodeTodosParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.scan_code_todos(params).await {
+                Ok(import) => match serde_json::to_value(import) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "sync_tasks_markdown" => {
+            let params: ::task_core::SyncTasksMarkdownParams = match deserialize_mcp_params(params)
+            {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.sync_tasks_markdown(params).await {
+                Ok(sync) => match serde_json::to_value(sync) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_final_report" => {
+            let params: ::task_core::GetFinalReportParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_final_report(params).await {
+                Ok(report) => match serde_json::to_value(report) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "find_duplicate_tasks" => {
+            let params: FindDuplicateTasksParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.find_duplicate_tasks(params).await {
+                Ok(duplicates) => match serde_json::to_value(duplicates) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
         "update_task" => {
             let params: UpdateTaskParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
@@ -150,7 +636,7 @@ async fn execute_mcp_method<
                 Err(e) => return e.to_json_rpc_error(id),
             };
             match handler.get_task_by_id(params).await {
-                Ok(Some(task)) => match serialize_task_for_mcp(&task) {
+                Ok(Some(task)) => match serialize_task_details(handler, &task).await {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => e.to_json_rpc_error(id),
                 },
@@ -164,7 +650,21 @@ async fn execute_mcp_method<
                 Err(e) => return e.to_json_rpc_error(id),
             };
             match handler.get_task_by_code(params).await {
-                Ok(Some(task)) => match serialize_task_for_mcp(&task) {
+                Ok(Some(task)) => match serialize_task_details(handler, &task).await {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Ok(None) => create_success_response(id, Value::Null),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_task_by_uid" => {
+            let params: ::task_core::GetTaskByUidParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_task_by_uid(params).await {
+                Ok(Some(task)) => match serialize_task_details(handler, &task).await {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => e.to_json_rpc_error(id),
                 },
@@ -172,6 +672,32 @@ async fn execute_mcp_method<
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
+        "get_tasks_by_ids" => {
+            let params: ::task_core::GetTasksByIdsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_tasks_by_ids(params).await {
+                Ok(batch) => match serialize_task_batch_for_mcp(&batch) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_tasks_by_codes" => {
+            let params: ::task_core::GetTasksByCodesParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_tasks_by_codes(params).await {
+                Ok(batch) => match serialize_task_batch_for_mcp(&batch) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
         "list_tasks" => {
             let params: ListTasksParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
@@ -189,6 +715,46 @@ async fn execute_mcp_method<
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
+        "count_tasks" => {
+            let params: ::task_core::CountTasksParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.count_tasks(params).await {
+                Ok(count) => match serde_json::to_value(count) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "aggregate_tasks" => {
+            let params: ::task_core::AggregateTasksParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.aggregate_tasks(params).await {
+                Ok(aggregate) => match serde_json::to_value(aggregate) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "apply_task_operations" => {
+            let params: ::task_core::ApplyTaskOperationsParams =
+                match deserialize_mcp_params(params) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_json_rpc_error(id),
+                };
+            match handler.apply_task_operations(params).await {
+                Ok(outcomes) => match serialize_operation_outcomes_for_mcp(&outcomes) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
         "assign_task" => {
             let params: AssignTaskParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
@@ -222,6 +788,20 @@ async fn execute_mcp_method<
             },
             Err(e) => McpError::from(e).to_json_rpc_error(id),
         },
+        "get_migration_status" => match handler.get_migration_status().await {
+            Ok(status) => match serde_json::to_value(status) {
+                Ok(value) => create_success_response(id, value),
+                Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+            },
+            Err(e) => McpError::from(e).to_json_rpc_error(id),
+        },
+        "get_retention_report" => match handler.get_retention_report().await {
+            Ok(report) => match serde_json::to_value(report) {
+                Ok(value) => create_success_response(id, value),
+                Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+            },
+            Err(e) => McpError::from(e).to_json_rpc_error(id),
+        },
         // MCP v2 Advanced Multi-Agent Functions
         "discover_work" => {
             let params: DiscoverWorkParams = match deserialize_mcp_params(params) {
@@ -240,448 +820,1765 @@ async fn execute_mcp_method<
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "claim_task" => {
-            let params: ClaimTaskParams = match deserialize_mcp_params(params) {
+        "export_timeline" => {
+            let params: ::task_core::ExportTimelineParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.claim_task(params).await {
-                Ok(task) => match serialize_task_for_mcp(&task) {
+            match handler.export_timeline(params).await {
+                Ok(timeline) => match serde_json::to_value(timeline) {
                     Ok(value) => create_success_response(id, value),
-                    Err(e) => e.to_json_rpc_error(id),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "release_task" => {
-            let params: ReleaseTaskParams = match deserialize_mcp_params(params) {
+        "export_tasks_csv" => {
+            let params: ::task_core::ExportTasksCsvParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.release_task(params).await {
-                Ok(task) => match serialize_task_for_mcp(&task) {
+            match handler.export_tasks_csv(params).await {
+                Ok(export) => match serde_json::to_value(export) {
                     Ok(value) => create_success_response(id, value),
-                    Err(e) => e.to_json_rpc_error(id),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "start_work_session" => {
-            let params: StartWorkSessionParams = match deserialize_mcp_params(params) {
+        "export_sessions_csv" => {
+            let params: ::task_core::ExportSessionsCsvParams = match deserialize_mcp_params(params)
+            {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.start_work_session(params).await {
-                Ok(session_info) => match serde_json::to_value(session_info) {
+            match handler.export_sessions_csv(params).await {
+                Ok(export) => match serde_json::to_value(export) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "end_work_session" => {
-            let params: EndWorkSessionParams = match deserialize_mcp_params(params) {
+        "get_critical_path" => {
+            let params: ::task_core::GetCriticalPathParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.end_work_session(params).await {
-                Ok(()) => create_success_response(id, Value::Null),
+            match handler.get_critical_path(params).await {
+                Ok(critical_path) => match serde_json::to_value(critical_path) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "cleanup_timed_out_tasks" => {
-            let params: ::task_core::CleanupTimedOutTasksParams = match deserialize_mcp_params(params) {
+        "semantic_search" => {
+            let params: ::task_core::SemanticSearchParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.cleanup_timed_out_tasks(params).await {
-                Ok(tasks) => {
-                    let task_values: Result<Vec<_>, _> =
-                        tasks.iter().map(serialize_task_for_mcp).collect();
-                    match task_values {
-                        Ok(values) => create_success_response(id, Value::Array(values)),
-                        Err(e) => e.to_json_rpc_error(id),
-                    }
-                }
+            match handler.semantic_search(params).await {
+                Ok(matches) => match serde_json::to_value(matches) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        // Task Messaging Functions
-        "create_task_message" => {
-            let params: CreateTaskMessageParams = match deserialize_mcp_params(params) {
+        "explain_task_routing" => {
+            let params: ::task_core::ExplainTaskRoutingParams = match deserialize_mcp_params(params)
+            {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.create_task_message(params).await {
-                Ok(message) => match serde_json::to_value(message) {
+            match handler.explain_task_routing(params).await {
+                Ok(decision) => match serde_json::to_value(decision) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "get_task_messages" => {
-            let params: GetTaskMessagesParams = match deserialize_mcp_params(params) {
+        "match_agents_to_task" => {
+            let params: ::task_core::MatchAgentsToTaskParams = match deserialize_mcp_params(params)
+            {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.get_task_messages(params).await {
-                Ok(messages) => match serde_json::to_value(messages) {
+            match handler.match_agents_to_task(params).await {
+                Ok(matches) => match serde_json::to_value(matches) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        // Workspace Setup Functions
-        "get_setup_instructions" => {
-            let params: ::task_core::GetSetupInstructionsParams =
+        "claim_task" => {
+            let params: ClaimTaskParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.claim_task(params).await {
+                Ok(task) => match serialize_task_for_mcp(&task) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "release_task" => {
+            let params: ReleaseTaskParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.release_task(params).await {
+                Ok(task) => match serialize_task_for_mcp(&task) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => e.to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "start_work_session" => {
+            let params: StartWorkSessionParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.start_work_session(params).await {
+                Ok(session_info) => match serde_json::to_value(session_info) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "end_work_session" => {
+            let params: EndWorkSessionParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.end_work_session(params).await {
+                Ok(()) => create_success_response(id, Value::Null),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "append_session_log" => {
+            let params: ::task_core::AppendSessionLogParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.append_session_log(params).await {
+                Ok(entry) => match serde_json::to_value(entry) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_session_log" => {
+            let params: ::task_core::GetSessionLogParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_session_log(params).await {
+                Ok(log) => match serde_json::to_value(log) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_agent_performance" => {
+            let params: GetAgentPerformanceParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_agent_performance(params).await {
+                Ok(report) => match serde_json::to_value(report) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_system_events" => {
+            let params: GetSystemEventsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_system_events(params).await {
+                Ok(events) => match serde_json::to_value(events) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_tool_usage_stats" => {
+            let params: GetToolUsageStatsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_tool_usage_stats(params).await {
+                Ok(stats) => match serde_json::to_value(stats) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "rebalance_work" => {
+            let params: ::task_core::RebalanceWorkParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.rebalance_work(params).await {
+                Ok(rebalance) => match serde_json::to_value(rebalance) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_board_at" => {
+            let params: ::task_core::GetBoardAtParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_board_at(params).await {
+                Ok(board) => match serde_json::to_value(board) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "diff_workspace" => {
+            let params: ::task_core::DiffWorkspaceParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.diff_workspace(params).await {
+                Ok(diff) => match serde_json::to_value(diff) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "cleanup_timed_out_tasks" => {
+            let params: ::task_core::CleanupTimedOutTasksParams =
                 match deserialize_mcp_params(params) {
                     Ok(p) => p,
                     Err(e) => return e.to_json_rpc_error(id),
                 };
-            match handler.get_setup_instructions(params).await {
-                Ok(instructions) => match serde_json::to_value(instructions) {
+            match handler.cleanup_timed_out_tasks(params).await {
+                Ok(tasks) => {
+                    let task_values: Result<Vec<_>, _> =
+                        tasks.iter().map(serialize_task_for_mcp).collect();
+                    match task_values {
+                        Ok(values) => create_success_response(id, Value::Array(values)),
+                        Err(e) => e.to_json_rpc_error(id),
+                    }
+                }
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "maintain_database" => {
+            let params: ::task_core::MaintainDatabaseParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.maintain_database(params).await {
+                Ok(report) => match serde_json::to_value(report) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "get_agentic_workflow_description" => {
-            let params: ::task_core::GetAgenticWorkflowDescriptionParams =
+        "export_audit_log" => {
+            let params: ::task_core::ExportAuditLogParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.export_audit_log(params).await {
+                Ok(export) => match serde_json::to_value(export) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "purge_agent_data" => {
+            let params: ::task_core::PurgeAgentDataParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.purge_agent_data(params).await {
+                Ok(report) => match serde_json::to_value(report) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "merge_workspace" => {
+            let params: ::task_core::MergeWorkspaceParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.merge_workspace(params).await {
+                Ok(report) => match serde_json::to_value(report) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "register_with_claude_code" => {
+            let params: ::task_core::RegisterWithClaudeCodeParams =
                 match deserialize_mcp_params(params) {
                     Ok(p) => p,
                     Err(e) => return e.to_json_rpc_error(id),
                 };
-            match handler.get_agentic_workflow_description(params).await {
-                Ok(workflow) => match serde_json::to_value(workflow) {
+            match handler.register_with_claude_code(params).await {
+                Ok(registration) => match serde_json::to_value(registration) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "register_agent" => {
-            let params: ::task_core::RegisterAgentParams = match deserialize_mcp_params(params) {
+        "run_readonly_query" => {
+            let params: ::task_core::RunReadonlyQueryParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.register_agent(params).await {
-                Ok(agent) => match serde_json::to_value(agent) {
+            match handler.run_readonly_query(params).await {
+                Ok(result) => match serde_json::to_value(result) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "get_instructions_for_main_ai_file" => {
-            let params: ::task_core::GetInstructionsForMainAiFileParams =
+        // Capability Catalog
+        "define_capability" => {
+            let params: ::task_core::DefineCapabilityParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.define_capability(params).await {
+                Ok(capability) => match serde_json::to_value(capability) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "list_capabilities" => {
+            let params: ::task_core::ListCapabilitiesParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.list_capabilities(params).await {
+                Ok(capabilities) => match serde_json::to_value(capabilities) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "deprecate_capability" => {
+            let params: ::task_core::DeprecateCapabilityParams =
                 match deserialize_mcp_params(params) {
                     Ok(p) => p,
                     Err(e) => return e.to_json_rpc_error(id),
                 };
-            match handler.get_instructions_for_main_ai_file(params).await {
-                Ok(instructions) => match serde_json::to_value(instructions) {
+            match handler.deprecate_capability(params).await {
+                Ok(capability) => match serde_json::to_value(capability) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "create_main_ai_file" => {
-            let params: ::task_core::CreateMainAiFileParams = match deserialize_mcp_params(params) {
+        // Task Messaging Functions
+        "create_task_message" => {
+            let params: CreateTaskMessageParams = match deserialize_mcp_params(params) {
                 Ok(p) => p,
                 Err(e) => return e.to_json_rpc_error(id),
             };
-            match handler.create_main_ai_file(params).await {
-                Ok(file_data) => match serde_json::to_value(file_data) {
+            match handler.create_task_message(params).await {
+                Ok(message) => match serde_json::to_value(message) {
                     Ok(value) => create_success_response(id, value),
                     Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                 },
                 Err(e) => McpError::from(e).to_json_rpc_error(id),
             }
         }
-        "tools/list" => {
-            // Return list of all available tools per MCP specification
-            let tools_list = json!({
-                "tools": [
-                    {
-                        "name": "create_task",
-                        "description": "Create a new task",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "code": {"type": "string"},
-                                "name": {"type": "string"},
-                                "description": {"type": "string"},
-                                "owner_agent_name": {"type": "string"}
-                            },
-                            "required": ["code", "name", "description", "owner_agent_name"]
-                        }
-                    },
-                    {
-                        "name": "update_task",
-                        "description": "Update an existing task",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "id": {"type": "integer"},
-                                "name": {"type": "string"},
-                                "description": {"type": "string"}
-                            },
-                            "required": ["id"]
-                        }
-                    },
-                    {
-                        "name": "set_task_state",
-                        "description": "Set task state",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "id": {"type": "integer"},
-                                "state": {"type": "string", "enum": ["Created", "InProgress", "Blocked", "Review", "Done", "Archived"]}
-                            },
-                            "required": ["id", "state"]
-                        }
-                    },
-                    {
-                        "name": "get_task_by_id",
-                        "description": "Get task by ID",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "id": {"type": "integer"}
-                            },
-                            "required": ["id"]
-                        }
-                    },
-                    {
-                        "name": "get_task_by_code",
-                        "description": "Get task by code",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "code": {"type": "string"}
-                            },
-                            "required": ["code"]
-                        }
-                    },
-                    {
-                        "name": "list_tasks",
-                        "description": "List tasks with optional filtering",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "owner_agent_name": {"type": "string"},
-                                "state": {"type": "string"},
-                                "limit": {"type": "integer"},
-                                "offset": {"type": "integer"}
-                            }
-                        }
-                    },
-                    {
-                        "name": "assign_task",
-                        "description": "Assign task to a different agent",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "id": {"type": "integer"},
-                                "new_owner_agent_name": {"type": "string"}
-                            },
-                            "required": ["id", "new_owner_agent_name"]
-                        }
-                    },
-                    {
-                        "name": "archive_task",
-                        "description": "Archive a completed task",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "id": {"type": "integer"}
-                            },
-                            "required": ["id"]
-                        }
-                    },
-                    {
-                        "name": "health_check",
-                        "description": "Check server health",
-                        "inputSchema": {
-                            "type": "object"
-                        }
+        "get_task_messages" => {
+            let params: GetTaskMessagesParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            if params.summarize {
+                return match handler.get_task_thread(params).await {
+                    Ok(thread) => match serde_json::to_value(thread) {
+                        Ok(value) => create_success_response(id, value),
+                        Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
                     },
-                    {
-                        "name": "discover_work",
-                        "description": "Discover available work based on agent capabilities",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_name": {"type": "string"},
-                                "capabilities": {"type": "array", "items": {"type": "string"}},
-                                "max_tasks": {"type": "integer"}
-                            },
-                            "required": ["agent_name", "capabilities"]
-                        }
+                    Err(e) => McpError::from(e).to_json_rpc_error(id),
+                };
+            }
+            match handler.get_task_messages(params).await {
+                Ok(messages) => match serde_json::to_value(messages) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_my_mentions" => {
+            let params: GetMyMentionsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_my_mentions(params).await {
+                Ok(mentions) => match serde_json::to_value(mentions) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_my_work" => {
+            let params: ::task_core::GetMyWorkParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_my_work(params).await {
+                Ok(work) => match serde_json::to_value(work) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_updates_since" => {
+            let params: ::task_core::GetUpdatesSinceParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_updates_since(params).await {
+                Ok(updates) => match serde_json::to_value(updates) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_message_templates" => {
+            let params: GetMessageTemplatesParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_message_templates(params).await {
+                Ok(templates) => match serde_json::to_value(templates) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        // Task Comments
+        "add_task_comment" => {
+            let params: AddTaskCommentParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.add_task_comment(params).await {
+                Ok(comment) => create_success_response(id, serialize_comment_for_mcp(&comment)),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "edit_task_comment" => {
+            let params: EditTaskCommentParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.edit_task_comment(params).await {
+                Ok(comment) => create_success_response(id, serialize_comment_for_mcp(&comment)),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "delete_task_comment" => {
+            let params: DeleteTaskCommentParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.delete_task_comment(params).await {
+                Ok(comment) => create_success_response(id, serialize_comment_for_mcp(&comment)),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_task_comments" => {
+            let params: GetTaskCommentsParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_task_comments(params).await {
+                Ok(comments) => create_success_response(
+                    id,
+                    Value::Array(comments.iter().map(serialize_comment_for_mcp).collect()),
+                ),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_task_comment_history" => {
+            let params: GetTaskCommentHistoryParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_task_comment_history(params).await {
+                Ok(revisions) => create_success_response(
+                    id,
+                    Value::Array(
+                        revisions
+                            .iter()
+                            .map(serialize_comment_revision_for_mcp)
+                            .collect(),
+                    ),
+                ),
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        // Workspace Setup Functions
+        "get_setup_instructions" => {
+            let params: ::task_core::GetSetupInstructionsParams =
+                match deserialize_mcp_params(params) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_json_rpc_error(id),
+                };
+            match handler.get_setup_instructions(params).await {
+                Ok(instructions) => match serde_json::to_value(instructions) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_agentic_workflow_description" => {
+            let params: ::task_core::GetAgenticWorkflowDescriptionParams =
+                match deserialize_mcp_params(params) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_json_rpc_error(id),
+                };
+            match handler.get_agentic_workflow_description(params).await {
+                Ok(workflow) => match serde_json::to_value(workflow) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "register_agent" => {
+            let params: ::task_core::RegisterAgentParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.register_agent(params).await {
+                Ok(agent) => match serde_json::to_value(agent) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "regenerate_agent_prompt" => {
+            let params: ::task_core::RegenerateAgentPromptParams =
+                match deserialize_mcp_params(params) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_json_rpc_error(id),
+                };
+            match handler.regenerate_agent_prompt(params).await {
+                Ok(regeneration) => match serde_json::to_value(regeneration) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_instructions_for_main_ai_file" => {
+            let params: ::task_core::GetInstructionsForMainAiFileParams =
+                match deserialize_mcp_params(params) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_json_rpc_error(id),
+                };
+            match handler.get_instructions_for_main_ai_file(params).await {
+                Ok(instructions) => match serde_json::to_value(instructions) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "create_main_ai_file" => {
+            let params: ::task_core::CreateMainAiFileParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.create_main_ai_file(params).await {
+                Ok(file_data) => match serde_json::to_value(file_data) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "get_setup_status" => {
+            let params: ::task_core::GetSetupStatusParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.get_setup_status(params).await {
+                Ok(status) => match serde_json::to_value(status) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "rollback_setup" => {
+            let params: ::task_core::RollbackSetupParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.rollback_setup(params).await {
+                Ok(rollback) => match serde_json::to_value(rollback) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "setup_wizard" => {
+            let params: ::task_core::SetupWizardParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.setup_wizard(params).await {
+                Ok(state) => match serde_json::to_value(state) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "verify_workspace" => {
+            let params: ::task_core::VerifyWorkspaceParams = match deserialize_mcp_params(params) {
+                Ok(p) => p,
+                Err(e) => return e.to_json_rpc_error(id),
+            };
+            match handler.verify_workspace(params).await {
+                Ok(verification) => match serde_json::to_value(verification) {
+                    Ok(value) => create_success_response(id, value),
+                    Err(e) => McpError::Serialization(e.to_string()).to_json_rpc_error(id),
+                },
+                Err(e) => McpError::from(e).to_json_rpc_error(id),
+            }
+        }
+        "tools/list" => {
+            // Return list of all available tools per MCP specification
+            let mut tools_list = tool_definitions();
+            if protocol_version.supports_tool_annotations() {
+                annotate_tools(&mut tools_list);
+            }
+            create_success_response(id, tools_list)
+        }
+        _ => McpError::Protocol(format!("Unknown method: {method}")).to_json_rpc_error(id),
+    }
+}
+
+/// Serialize a task with its comment summary and claim history
+///
+/// Repositories without comment or claim history support leave the
+/// corresponding field out.
+async fn serialize_task_details<
+    R: TaskRepository + Send + Sync + ?Sized,
+    M: TaskMessageRepository + Send + Sync,
+    W: WorkspaceContextRepository + Send + Sync,
+>(
+    handler: &McpTaskHandler<R, M, W>,
+    task: &Task,
+) -> Result<Value, McpError> {
+    let params = GetTaskCommentsParams {
+        task_code: task.code.clone(),
+        include_deleted: false,
+    };
+    let mut value = match handler.get_task_comments(params).await {
+        Ok(comments) => serialize_task_with_comments_for_mcp(task, &comments)?,
+        Err(TaskError::UnsupportedOperation(_)) => serialize_task_for_mcp(task)?,
+        Err(e) => return Err(McpError::from(e)),
+    };
+    match handler.repository().task_claims(task.id).await {
+        Ok(claims) => value["claim_history"] = serialize_claim_history_for_mcp(&claims),
+        Err(TaskError::UnsupportedOperation(_)) => {}
+        Err(e) => return Err(McpError::from(e)),
+    }
+    Ok(value)
+}
+
+/// Tools offered by the server, as listed by `tools/list`
+pub fn tool_definitions() -> Value {
+    let prompt_style_schema = json!({
+        "type": "object",
+        "properties": {
+            "include_contract": {"type": "boolean", "default": true},
+            "language": {"type": "string"},
+            "verbosity": {
+                "type": "string",
+                "enum": ["concise", "standard", "detailed"],
+                "default": "standard"
+            },
+            "emoji": {"type": "boolean", "default": false},
+            "include_success_criteria": {"type": "boolean", "default": true},
+            "include_communication_protocol": {"type": "boolean", "default": true}
+        }
+    });
+    json!({
+        "tools": [
+            {
+                "name": "create_task",
+                "description": "Create a new task",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "code": {"type": "string"},
+                        "name": {"type": "string"},
+                        "description": {"type": "string"},
+                        "owner_agent_name": {"type": "string"},
+                        "required_capabilities": {"type": "array", "items": {"type": "string"}},
+                        "parent_task_id": {"type": "integer", "description": "Task this one is a subtask of; subtasks gate their parent"},
+                        "estimated_effort": {"type": "integer", "minimum": 1, "description": "Estimated effort in minutes"},
+                        "confirm_duplicate": {"type": "boolean", "default": false, "description": "Create the task even though it resembles open tasks"}
                     },
-                    {
-                        "name": "claim_task",
-                        "description": "Atomically claim a task for execution",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "task_id": {"type": "integer"},
-                                "agent_name": {"type": "string"}
-                            },
-                            "required": ["task_id", "agent_name"]
-                        }
+                    "required": ["code", "name", "description", "owner_agent_name"]
+                }
+            },
+            {
+                "name": "create_pipeline",
+                "description": "Create a pipeline task with one subtask per stage of a template, coded <code>-1, <code>-2 and so on, each requiring the capabilities of its stage. Only the first stage can be claimed; when a stage moves to Done the next one is released with a stage_ready message, and after the last stage the pipeline task itself",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "template": {"type": "string", "default": "feature", "description": "Pipeline template; feature is design, implement and review. More come from .axon/pipelines.yaml"},
+                        "code": {"type": "string", "description": "Code of the pipeline task; leave room for the stage suffix"},
+                        "name": {"type": "string"},
+                        "description": {"type": "string"},
+                        "owner_agent_name": {"type": "string", "description": "Owner of the pipeline task; stages are routed like new tasks"},
+                        "priority_score": {"type": "number", "minimum": 0, "maximum": 10}
                     },
-                    {
-                        "name": "release_task",
-                        "description": "Release a claimed task back to the pool",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "task_id": {"type": "integer"},
-                                "agent_name": {"type": "string"}
-                            },
-                            "required": ["task_id", "agent_name"]
-                        }
+                    "required": ["code", "name", "description"]
+                }
+            },
+            {
+                "name": "register_artifact",
+                "description": "Record a file a task produced. The path must exist inside the project root; its SHA-256 is recorded and must match checksum when one is given. Registering a path again updates it",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "path": {"type": "string", "description": "Path relative to the project root"},
+                        "kind": {"type": "string", "description": "What the file is, e.g. code, test, doc or config"},
+                        "checksum": {"type": "string", "description": "Expected SHA-256 as sha256:<hex> or bare hex"},
+                        "agent_name": {"type": "string"}
                     },
-                    {
-                        "name": "start_work_session",
-                        "description": "Start a work session for task tracking",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "task_id": {"type": "integer"},
-                                "agent_name": {"type": "string"}
-                            },
-                            "required": ["task_id", "agent_name"]
-                        }
+                    "required": ["task_code", "path", "kind", "agent_name"]
+                }
+            },
+            {
+                "name": "list_artifacts",
+                "description": "List the files tasks produced. With verify, each file is compared with the project and reported unchanged, modified or missing",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "kind": {"type": "string"},
+                        "limit": {"type": "integer", "minimum": 1},
+                        "verify": {"type": "boolean", "default": false}
+                    }
+                }
+            },
+            {
+                "name": "scan_code_todos",
+                "description": "Find the TODO, FIXME and HACK comments of the project, skipping files ignored by .gitignore, and propose a task for each. Returns status confirmation_required with the proposals; resend with confirm: true to create the tasks that do not exist yet",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string", "description": "Project to scan; only the session's workspace root, which is the default"},
+                        "markers": {"type": "array", "items": {"type": "string", "enum": ["TODO", "FIXME", "HACK"]}},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 500},
+                        "owner_agent_name": {"type": "string"},
+                        "confirm": {"type": "boolean", "default": false},
+                        "codes": {"type": "array", "items": {"type": "string"}, "description": "Proposed task codes to create; all new ones when empty"}
+                    }
+                }
+            },
+            {
+                "name": "sync_tasks_markdown",
+                "description": "Apply the checkbox and state edits made to .axon/tasks.md to the tasks (coordinators only), then write the file anew from the database. Tasks changed in the database since the file was written keep their database state and are reported as conflicts",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string", "description": "Project whose task file is synced; only the session's workspace root, which is the default"}
+                    }
+                }
+            },
+            {
+                "name": "get_final_report",
+                "description": "Get the final report written once every task that is not archived is done: timeline, per-agent contributions, time worked against estimates and outstanding risks",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string", "description": "Project whose report is read; only the session's workspace root, which is the default"}
+                    }
+                }
+            },
+            {
+                "name": "find_duplicate_tasks",
+                "description": "Find open tasks whose name and description resemble a task about to be created, ordered by similarity",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "description": {"type": "string"}
                     },
-                    {
-                        "name": "end_work_session",
-                        "description": "End a work session with productivity metrics",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "session_id": {"type": "integer"},
-                                "productivity_score": {"type": "number"}
-                            },
-                            "required": ["session_id"]
+                    "required": ["name"]
+                }
+            },
+            {
+                "name": "update_task",
+                "description": "Update an existing task",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "name": {"type": "string"},
+                        "description": {"type": "string"}
+                    },
+                    "required": ["id"]
+                }
+            },
+            {
+                "name": "set_task_state",
+                "description": "Set task state",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "state": {"type": "string", "enum": ["Created", "InProgress", "Blocked", "Review", "Done", "Archived"]}
+                    },
+                    "required": ["id", "state"]
+                }
+            },
+            {
+                "name": "apply_task_operations",
+                "description": "Apply an ordered list of operations in one transaction: all succeed or none is applied. Each operation names its kind in `op` (create, set_state, assign, message) and takes the parameters of create_task, set_task_state, assign_task or create_task_message. Returns one result per operation holding the task or message it produced",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "operations": {
+                            "type": "array",
+                            "minItems": 1,
+                            "maxItems": ::task_core::MAX_TASK_OPERATIONS,
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "op": {"type": "string", "enum": ["create", "set_state", "assign", "message"]}
+                                },
+                                "required": ["op"]
+                            }
                         }
                     },
-                    {
-                        "name": "create_task_message",
-                        "description": "Create a message within a task context",
-                        "inputSchema": {
+                    "required": ["operations"]
+                }
+            },
+            {
+                "name": "get_task_by_id",
+                "description": "Get task by ID. Tasks moved to the archive database are not found; list them with list_tasks and include_archive",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"}
+                    },
+                    "required": ["id"]
+                }
+            },
+            {
+                "name": "get_task_by_code",
+                "description": "Get task by code. Tasks moved to the archive database are not found; list them with list_tasks and include_archive",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "code": {"type": "string"}
+                    },
+                    "required": ["code"]
+                }
+            },
+            {
+                "name": "get_task_by_uid",
+                "description": "Get task by its stable identifier (UUID or ULID), which survives export and import across databases",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "uid": {"type": "string"}
+                    },
+                    "required": ["uid"]
+                }
+            },
+            {
+                "name": "get_tasks_by_ids",
+                "description": "Get several tasks by ID in one call. Returns the tasks found in request order and the IDs matching no task",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "ids": {"type": "array", "items": {"type": "integer"}, "maxItems": ::task_core::MAX_BATCH_LOOKUP}
+                    },
+                    "required": ["ids"]
+                }
+            },
+            {
+                "name": "get_tasks_by_codes",
+                "description": "Get several tasks by code in one call. Returns the tasks found in request order and the codes matching no task",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "codes": {"type": "array", "items": {"type": "string"}, "maxItems": ::task_core::MAX_BATCH_LOOKUP}
+                    },
+                    "required": ["codes"]
+                }
+            },
+            {
+                "name": "list_tasks",
+                "description": "List tasks with optional filtering",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "owner_agent_name": {"type": "string"},
+                        "state": {"type": "string"},
+                        "states": {"type": "array", "items": {"type": "string"}, "description": "Match any of these states, together with state"},
+                        "owners": {"type": "array", "items": {"type": "string"}, "description": "Match any of these owners, together with owner"},
+                        "code_prefix": {"type": "string", "description": "Match codes starting with this prefix, e.g. ARCH- or ARCH-*"},
+                        "has_owner": {"type": "boolean", "description": "true lists only owned tasks, false only unowned ones"},
+                        "modified_since": {"type": "string", "format": "date-time", "description": "Only tasks modified at or after this RFC 3339 time, for incremental sync"},
+                        "limit": {"type": "integer"},
+                        "offset": {"type": "integer"},
+                        "include_archive": {"type": "boolean", "description": "Also list tasks moved to the archive database"}
+                    }
+                }
+            },
+            {
+                "name": "count_tasks",
+                "description": "Count tasks matching the filters of list_tasks without returning them",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "owner": {"type": "string"},
+                        "state": {"type": "string"},
+                        "states": {"type": "array", "items": {"type": "string"}},
+                        "owners": {"type": "array", "items": {"type": "string"}},
+                        "code_prefix": {"type": "string"},
+                        "has_owner": {"type": "boolean"},
+                        "created_after": {"type": "string", "format": "date-time"},
+                        "created_before": {"type": "string", "format": "date-time"},
+                        "modified_since": {"type": "string", "format": "date-time"},
+                        "include_archive": {"type": "boolean"}
+                    }
+                }
+            },
+            {
+                "name": "aggregate_tasks",
+                "description": "Count tasks matching the filters of list_tasks per state, owner or required capability ('tag' is accepted for capability). Returns the total and the non-empty groups, largest first; unowned tasks form an owner group with a null key",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "group_by": {"type": "string", "enum": ["state", "owner", "capability", "tag"]},
+                        "owner": {"type": "string"},
+                        "state": {"type": "string"},
+                        "states": {"type": "array", "items": {"type": "string"}},
+                        "owners": {"type": "array", "items": {"type": "string"}},
+                        "code_prefix": {"type": "string"},
+                        "has_owner": {"type": "boolean"},
+                        "created_after": {"type": "string", "format": "date-time"},
+                        "created_before": {"type": "string", "format": "date-time"},
+                        "modified_since": {"type": "string", "format": "date-time"},
+                        "include_archive": {"type": "boolean"}
+                    },
+                    "required": ["group_by"]
+                }
+            },
+            {
+                "name": "assign_task",
+                "description": "Assign task to a different agent",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "new_owner_agent_name": {"type": "string"}
+                    },
+                    "required": ["id", "new_owner_agent_name"]
+                }
+            },
+            {
+                "name": "archive_task",
+                "description": "Archive a completed task",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "id": {"type": "integer"}
+                    },
+                    "required": ["id"]
+                }
+            },
+            {
+                "name": "health_check",
+                "description": "Check server health",
+                "inputSchema": {
+                    "type": "object"
+                }
+            },
+            {
+                "name": "get_retention_report",
+                "description": "Dry run of the retention policy: count the messages, work sessions, comments, tool calls and system events the next purge would delete",
+                "inputSchema": {
+                    "type": "object"
+                }
+            },
+            {
+                "name": "get_migration_status",
+                "description": "List the schema migrations shipped with the server and whether each is applied to the database",
+                "inputSchema": {
+                    "type": "object"
+                }
+            },
+            {
+                "name": "discover_work",
+                "description": "Discover available work based on agent capabilities",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "capabilities": {"type": "array", "items": {"type": "string"}},
+                        "max_tasks": {"type": "integer"}
+                    },
+                    "required": ["agent_name", "capabilities"]
+                }
+            },
+            {
+                "name": "export_timeline",
+                "description": "Export a timeline of tasks: created, claimed and done timestamps, owners and subtask dependencies. With format 'mermaid', also returns a Mermaid gantt chart with one section per owner",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "owner": {"type": "string"},
+                        "include_archive": {"type": "boolean", "default": false},
+                        "format": {"type": "string", "enum": ["json", "mermaid"], "default": "json"}
+                    }
+                }
+            },
+            {
+                "name": "export_tasks_csv",
+                "description": "Export tasks as CSV (RFC 4180, header row) for spreadsheets, with selectable columns and owner, state and creation date filters",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "owner": {"type": "string"},
+                        "state": {"type": "string", "enum": ["Created", "InProgress", "Blocked", "Review", "Done", "Archived"]},
+                        "date_from": {"type": "string", "format": "date-time", "description": "Only tasks created at or after this time"},
+                        "date_to": {"type": "string", "format": "date-time", "description": "Only tasks created at or before this time"},
+                        "include_archive": {"type": "boolean", "default": false},
+                        "columns": {"type": "array", "items": {"type": "string", "enum": ["id", "code", "name", "description", "state", "owner", "created_at", "claimed_at", "done_at", "lease_expires_at", "required_capabilities", "parent_task_id", "estimated_effort"]}, "description": "Defaults to id, code, name, state, owner, created_at, done_at"},
+                        "limit": {"type": "integer", "maximum": ::task_core::MAX_CSV_EXPORT_ROWS}
+                    }
+                }
+            },
+            {
+                "name": "export_sessions_csv",
+                "description": "Export work sessions as CSV (RFC 4180, header row) for spreadsheets, with selectable columns and agent, task and start time filters",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "task_id": {"type": "integer"},
+                        "since": {"type": "string", "format": "date-time", "description": "Only sessions started at or after this time"},
+                        "until": {"type": "string", "format": "date-time", "description": "Only sessions started before this time"},
+                        "columns": {"type": "array", "items": {"type": "string", "enum": ["id", "task_id", "task_code", "agent_name", "started_at", "ended_at", "duration_minutes", "productivity_score", "notes"]}, "description": "Defaults to id, task_code, agent_name, started_at, ended_at, duration_minutes"},
+                        "limit": {"type": "integer", "maximum": ::task_core::MAX_CSV_EXPORT_ROWS}
+                    }
+                }
+            },
+            {
+                "name": "get_critical_path",
+                "description": "Compute the longest chain of incomplete tasks, where subtasks (parent_task_id) gate their parent, weighted by estimated_effort; returns slack per task so work that gates completion can be prioritized",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "default_effort_minutes": {"type": "integer", "minimum": 1, "default": ::task_core::DEFAULT_EFFORT_MINUTES}
+                    }
+                }
+            },
+            {
+                "name": "get_task_context",
+                "description": "Get everything needed to start on a task in one payload: the task, its parent chain and subtasks, recent messages and comments, related records of other tasks, tracker links and referenced commits; older records are dropped to fit the token budget",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "token_budget": {"type": "integer", "minimum": ::task_core::MIN_CONTEXT_TOKEN_BUDGET, "default": ::task_core::DEFAULT_CONTEXT_TOKEN_BUDGET, "description": "Approximate size limit of the payload in tokens"}
+                    },
+                    "required": ["task_code"]
+                }
+            },
+            {
+                "name": "semantic_search",
+                "description": "Find tasks, messages and comments related in meaning to a free-text query, to reuse prior work instead of relying on exact-match filters; results are ordered by similarity score",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string", "description": "Description of the work to find"},
+                        "top_k": {"type": "integer", "minimum": 1, "maximum": ::task_core::MAX_SEMANTIC_SEARCH_RESULTS, "default": ::task_core::DEFAULT_SEMANTIC_SEARCH_RESULTS},
+                        "sources": {"type": "array", "items": {"type": "string", "enum": ["task", "message", "comment"]}, "description": "Only these kinds of records; all when omitted"}
+                    },
+                    "required": ["query"]
+                }
+            },
+            {
+                "name": "match_agents_to_task",
+                "description": "Rank registered agents by how well their capabilities fit a task, with an explanation per agent",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "integer"}
+                    },
+                    "required": ["task_id"]
+                }
+            },
+            {
+                "name": "explain_task_routing",
+                "description": "Show which routing rule from .axon/routing.yaml would route a task and to which agent, with the reason each rule matched or not, without assigning anything",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "integer"},
+                        "exclude_agent": {"type": "string", "description": "Agent left out of the candidates, as when it releases the task"}
+                    },
+                    "required": ["task_id"]
+                }
+            },
+            {
+                "name": "claim_task",
+                "description": "Atomically claim a task for execution",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "integer"},
+                        "agent_name": {"type": "string"},
+                        "lease_minutes": {"type": "integer", "minimum": 1, "description": "How long to hold the claim; capped by the server's maximum lease. Defaults to the server's default lease"}
+                    },
+                    "required": ["task_id", "agent_name"]
+                }
+            },
+            {
+                "name": "release_task",
+                "description": "Release a claimed task back to the pool",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "integer"},
+                        "agent_name": {"type": "string"}
+                    },
+                    "required": ["task_id", "agent_name"]
+                }
+            },
+            {
+                "name": "start_work_session",
+                "description": "Start a work session for task tracking; returns the task, its lease, logged and expected minutes, and other agents active on it",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "integer"},
+                        "agent_name": {"type": "string"}
+                    },
+                    "required": ["task_id", "agent_name"]
+                }
+            },
+            {
+                "name": "end_work_session",
+                "description": "End a work session with productivity metrics",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "session_id": {"type": "integer"},
+                        "productivity_score": {"type": "number"}
+                    },
+                    "required": ["session_id"]
+                }
+            },
+            {
+                "name": "append_session_log",
+                "description": "Append a chunk to the transcript of an active work session: free text, tool calls made, decisions taken and file paths touched. Chunks are stored compressed for later review",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "session_id": {"type": "integer"},
+                        "content": {"type": "string"},
+                        "tool_calls": {"type": "array", "items": {"type": "string"}},
+                        "decisions": {"type": "array", "items": {"type": "string"}},
+                        "files_touched": {"type": "array", "items": {"type": "string"}}
+                    },
+                    "required": ["session_id"]
+                }
+            },
+            {
+                "name": "get_session_log",
+                "description": "Get the full transcript of a work session, oldest chunk first, with every file touched and the raw and stored sizes, for reviews and post-mortems",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "session_id": {"type": "integer"}
+                    },
+                    "required": ["session_id"]
+                }
+            },
+            {
+                "name": "get_agent_performance",
+                "description": "Productivity per agent over a period: sessions, minutes worked, average productivity score, tasks completed and completion latency, with trend buckets. Anomalies (score collapse, idle claims) are listed and, for the current period, recorded as system events",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string", "description": "Only this agent"},
+                        "since": {"type": "string", "format": "date-time", "description": "Start of the period (default: 28 days before until)"},
+                        "until": {"type": "string", "format": "date-time", "description": "End of the period (default: now)"},
+                        "bucket_days": {"type": "integer", "minimum": 1, "description": "Days per trend bucket (default: 7)"}
+                    }
+                }
+            },
+            {
+                "name": "get_system_events",
+                "description": "List system events recorded for the coordinator, such as agent performance anomalies, newest first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "event_type": {"type": "string", "description": "e.g. agent_score_collapse or agent_idle_claim"},
+                        "entity_id": {"type": "string", "description": "Agent name or task code the events are about"},
+                        "since": {"type": "string", "format": "date-time"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 500}
+                    }
+                }
+            },
+            {
+                "name": "get_tool_usage_stats",
+                "description": "Count the tool calls of each agent per tool, most called first, with errors, average duration and first and last call. An agent calling the same read tool hundreds of times is likely stuck polling",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string", "description": "Only this agent"},
+                        "tool_name": {"type": "string", "description": "Only this tool"},
+                        "since": {"type": "string", "format": "date-time", "description": "Start of the period (default: 24 hours before until)"},
+                        "until": {"type": "string", "format": "date-time", "description": "End of the period (default: now)"},
+                        "limit": {"type": "integer", "minimum": 1, "maximum": 500}
+                    }
+                }
+            },
+            {
+                "name": "rebalance_work",
+                "description": "Find overloaded agents, holding more tasks in progress than they work on, and idle registered agents, and propose handing each unattended task to an idle agent with the capabilities it requires. Returns status confirmation_required with the proposals; resend with confirm: true to reassign the tasks and message both agents. Reserved for coordinators",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "confirm": {"type": "boolean", "default": false},
+                        "codes": {"type": "array", "items": {"type": "string"}, "description": "Codes of the proposed tasks to reassign; all when empty"}
+                    }
+                }
+            },
+            {
+                "name": "get_board_at",
+                "description": "Show the task board as it was at a point in time, rebuilt from the task event history (requires database.event_history)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "timestamp": {"type": "string", "format": "date-time", "description": "Point in time to show, e.g. 2024-05-01T14:00:00Z"}
+                    },
+                    "required": ["timestamp"]
+                }
+            },
+            {
+                "name": "diff_workspace",
+                "description": "Compare the workspace at two points in time: tasks created, completed and reassigned, state changes and messages posted in between (requires database.event_history)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "from": {"type": "string", "format": "date-time", "description": "Start of the period"},
+                        "to": {"type": "string", "format": "date-time", "description": "End of the period, not in the future"},
+                        "max_messages": {"type": "integer", "minimum": 0, "maximum": 1000, "description": "Messages to return, oldest first (default: 100)"}
+                    },
+                    "required": ["from", "to"]
+                }
+            },
+            {
+                "name": "create_task_message",
+                "description": "Create a message within a task context. Pass `template` and `template_values` instead of `content` to render a message template (see get_message_templates)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "author_agent_name": {"type": "string"},
+                        "target_agent_name": {"type": "string"},
+                        "message_type": {"type": "string"},
+                        "content": {"type": "string"},
+                        "reply_to_message_id": {"type": "integer"},
+                        "structured_data": {
+                            "type": "object",
+                            "description": "Machine-readable payload; blocker messages require blocking_task_code, handoff messages require to_agent_name"
+                        },
+                        "template": {"type": "string"},
+                        "template_values": {
                             "type": "object",
-                            "properties": {
-                                "task_code": {"type": "string"},
-                                "author_agent_name": {"type": "string"},
-                                "target_agent_name": {"type": "string"},
-                                "message_type": {"type": "string"},
-                                "content": {"type": "string"},
-                                "reply_to_message_id": {"type": "integer"}
-                            },
-                            "required": ["task_code", "author_agent_name", "message_type", "content"]
+                            "additionalProperties": {"type": "string"}
                         }
                     },
-                    {
-                        "name": "get_task_messages",
-                        "description": "Get messages from a task with filtering",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "task_code": {"type": "string"},
-                                "author_agent_name": {"type": "string"},
-                                "target_agent_name": {"type": "string"},
-                                "message_type": {"type": "string"},
-                                "reply_to_message_id": {"type": "integer"},
-                                "limit": {"type": "integer"}
-                            },
-                            "required": ["task_code"]
-                        }
+                    "required": ["task_code", "author_agent_name"]
+                }
+            },
+            {
+                "name": "get_task_messages",
+                "description": "Get messages from a task with filtering",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "author_agent_name": {"type": "string"},
+                        "target_agent_name": {"type": "string"},
+                        "message_type": {"type": "string"},
+                        "reply_to_message_id": {"type": "integer"},
+                        "limit": {"type": "integer"},
+                        "summarize": {"type": "boolean", "default": false, "description": "Return {summary, messages, total_messages}: a rolling summary of older messages plus the recent ones, to keep long threads small. Cannot be combined with filters or limit"}
+                    },
+                    "required": ["task_code"]
+                }
+            },
+            {
+                "name": "get_my_mentions",
+                "description": "Get messages that mention you as @agent-name, newest first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "since": {"type": "string", "format": "date-time"},
+                        "limit": {"type": "integer"}
+                    },
+                    "required": ["agent_name"]
+                }
+            },
+            {
+                "name": "get_my_work",
+                "description": "Get an agent's work queue in one call: tasks it has in progress, new messages addressed to it, new mentions, reviews requested from it and claim leases about to expire. Pass the returned as_of as since on the next call to get only new messages",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "since": {"type": "string", "format": "date-time"},
+                        "limit": {"type": "integer", "minimum": 1, "default": ::task_core::DEFAULT_MY_WORK_LIMIT},
+                        "deadline_window_minutes": {"type": "integer", "minimum": 0, "default": ::task_core::DEFAULT_DEADLINE_WINDOW_MINUTES}
+                    },
+                    "required": ["agent_name"]
+                }
+            },
+            {
+                "name": "get_updates_since",
+                "description": "Get a compact digest for an agent returning from idle: tasks assigned to it, mentions of it, state changes on its tasks and claim leases that ran out or are about to, since a point in time. Pass the returned as_of as since on the next call",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "since": {"type": "string", "format": "date-time"},
+                        "limit": {"type": "integer", "minimum": 1, "default": ::task_core::DEFAULT_MY_WORK_LIMIT, "description": "Maximum number of mentions"},
+                        "deadline_window_minutes": {"type": "integer", "minimum": 0, "default": ::task_core::DEFAULT_DEADLINE_WINDOW_MINUTES}
+                    },
+                    "required": ["agent_name", "since"]
+                }
+            },
+            {
+                "name": "get_message_templates",
+                "description": "List message templates (blocker report, handoff summary, review request) and their placeholders",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"}
+                    }
+                }
+            },
+            {
+                "name": "add_task_comment",
+                "description": "Add a markdown comment to a task (lightweight note, not a protocol message)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "author_agent_name": {"type": "string"},
+                        "content": {"type": "string", "maxLength": ::task_core::MAX_COMMENT_LENGTH}
+                    },
+                    "required": ["task_code", "author_agent_name", "content"]
+                }
+            },
+            {
+                "name": "edit_task_comment",
+                "description": "Edit your own comment; the previous content is kept in its history",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "comment_id": {"type": "integer"},
+                        "agent_name": {"type": "string"},
+                        "content": {"type": "string", "maxLength": ::task_core::MAX_COMMENT_LENGTH}
+                    },
+                    "required": ["comment_id", "agent_name", "content"]
+                }
+            },
+            {
+                "name": "delete_task_comment",
+                "description": "Delete your own comment; it stays available in history",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "comment_id": {"type": "integer"},
+                        "agent_name": {"type": "string"}
+                    },
+                    "required": ["comment_id", "agent_name"]
+                }
+            },
+            {
+                "name": "get_task_comments",
+                "description": "Get the comments of a task, oldest first, with rendered HTML",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_code": {"type": "string"},
+                        "include_deleted": {"type": "boolean"}
+                    },
+                    "required": ["task_code"]
+                }
+            },
+            {
+                "name": "get_task_comment_history",
+                "description": "Get earlier revisions of a comment",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "comment_id": {"type": "integer"}
+                    },
+                    "required": ["comment_id"]
+                }
+            },
+            {
+                "name": "get_setup_instructions",
+                "description": "Generate AI workspace setup instructions",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "ai_tool_type": {"type": "string"}
+                    },
+                    "required": ["ai_tool_type"]
+                }
+            },
+            {
+                "name": "get_agentic_workflow_description",
+                "description": "Get recommended agent workflow for workspace",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "requested_agent_count": {"type": "integer"}
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "register_agent",
+                "description": "Register an AI agent in the workspace",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "agent_type": {"type": "string"},
+                        "capabilities": {"type": "array", "items": {"type": "string"}},
+                        "description": {"type": "string"},
+                        "style_options": prompt_style_schema.clone()
+                    },
+                    "required": ["agent_name", "agent_type", "capabilities"]
+                }
+            },
+            {
+                "name": "regenerate_agent_prompt",
+                "description": "Regenerate an agent prompt from the current PRD and team and diff it against the stored one",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "style_options": prompt_style_schema,
+                        "apply": {"type": "boolean", "default": false}
+                    },
+                    "required": ["agent_name"]
+                }
+            },
+            {
+                "name": "get_instructions_for_main_ai_file",
+                "description": "Get instructions for creating main AI coordination file",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "file_type": {"type": "string"}
+                    },
+                    "required": []
+                }
+            },
+            {
+                "name": "create_main_ai_file",
+                "description": "Create the main AI coordination file, or update only its Axon-managed sections when it exists",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "content": {"type": "string"},
+                        "existing_content": {"type": "string", "description": "Current file content; read from the project root when omitted"},
+                        "force": {"type": "boolean", "default": false, "description": "Overwrite managed sections edited by hand"}
+                    },
+                    "required": ["content"]
+                }
+            },
+            {
+                "name": "get_setup_status",
+                "description": "Checklist of workspace setup steps (PRD loaded, agents registered, main file created, manifest written) with the next step to take",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "ai_tool_type": {"type": "string", "enum": ["claude-code"], "default": "claude-code"}
+                    }
+                }
+            },
+            {
+                "name": "rollback_setup",
+                "description": "Undo an unfinished workspace setup (coordinators only): remove the files it generated (CLAUDE.md, the manifest) from the project root, keeping text outside the Axon-managed sections, and clear the workspace context with its registered agents. dry_run lists what would be removed",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string", "description": "Workspace to roll back; only the session's workspace root, which is the default"},
+                        "dry_run": {"type": "boolean", "default": false}
+                    }
+                }
+            },
+            {
+                "name": "setup_wizard",
+                "description": "Guided workspace setup: PRD, agent confirmation, CLAUDE.md and manifest, one step per call. Progress is saved, so calling without input resumes where setup stopped; write the returned files to the project root",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "restart": {"type": "boolean", "default": false, "description": "Start over at the PRD step"},
+                        "prd_content": {"type": "string", "description": "PRD Markdown, completing the prd step"},
+                        "agents": {"type": "array", "items": {"type": "string"}, "description": "Suggested agents to register, completing the agents step"},
+                        "main_file_content": {"type": "string", "description": "Coordination instructions for CLAUDE.md, completing the main_file step"},
+                        "confirm": {"type": "boolean", "default": false, "description": "Generate the manifest, completing the manifest step"}
+                    }
+                }
+            },
+            {
+                "name": "verify_workspace",
+                "description": "Check the workspace manifest, agent files, CLAUDE.md function references and database schema, with a fix for each problem",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string", "description": "Workspace to check; only the session's workspace root, which is the default"}
+                    }
+                }
+            },
+            {
+                "name": "list_background_jobs",
+                "description": "Admin: list periodic background jobs with their last-run status",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }
+            },
+            {
+                "name": "cleanup_timed_out_tasks",
+                "description": "Admin: release claims on tasks abandoned for longer than the timeout",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "timeout_minutes": {"type": "integer"}
+                    },
+                    "required": ["timeout_minutes"]
+                }
+            },
+            {
+                "name": "maintain_database",
+                "description": "Admin: run integrity check, WAL checkpoint, VACUUM and ANALYZE; reports file sizes before and after and any corruption findings",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "integrity_check": {"type": "boolean", "default": true},
+                        "checkpoint": {"type": "boolean", "default": true},
+                        "vacuum": {"type": "boolean", "default": true},
+                        "analyze": {"type": "boolean", "default": true}
+                    }
+                }
+            },
+            {
+                "name": "export_audit_log",
+                "description": "Admin: export task, message, work session and comment events as JSON Lines, oldest first, for SIEM ingestion or archival",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "since": {"type": "string", "format": "date-time", "description": "Only events at or after this time"},
+                        "until": {"type": "string", "format": "date-time", "description": "Only events before this time"},
+                        "sources": {"type": "array", "items": {"type": "string", "enum": ["task", "message", "work_session", "comment"]}},
+                        "limit": {"type": "integer", "maximum": ::task_core::MAX_AUDIT_EXPORT_ENTRIES}
+                    }
+                }
+            },
+            {
+                "name": "purge_agent_data",
+                "description": "Admin: remove an agent's personal data. Its tasks are handed to 'removed-agent'; its messages, work sessions and comments are anonymized (default) or deleted, mentions and message targets are cleared, and its registry entry is removed",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "agent_name": {"type": "string"},
+                        "mode": {"type": "string", "enum": ["anonymize", "delete"], "default": "anonymize"}
                     },
-                    {
-                        "name": "get_setup_instructions",
-                        "description": "Generate AI workspace setup instructions",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "ai_tool_type": {"type": "string"}
-                            },
-                            "required": ["ai_tool_type"]
-                        }
+                    "required": ["agent_name"]
+                }
+            },
+            {
+                "name": "merge_workspace",
+                "description": "Admin: merge another project's database file into this one, e.g. after agents worked offline on another machine. Tasks are matched by stable identifier, then by code with the same name and description; a different task with a taken code is imported under CODE-2, CODE-3, ... Imported tasks get new IDs and their messages follow them. Returns a report of imported, matched and renamed tasks and the ID mapping; dry_run reports without changing anything",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "import_path": {"type": "string"},
+                        "dry_run": {"type": "boolean", "default": false}
                     },
-                    {
-                        "name": "get_agentic_workflow_description",
-                        "description": "Get recommended agent workflow for workspace",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "requested_agent_count": {"type": "integer"}
-                            },
-                            "required": []
-                        }
+                    "required": ["import_path"]
+                }
+            },
+            {
+                "name": "register_with_claude_code",
+                "description": "Admin: add or update this server's entry (projects.<project_root>.mcpServers.axon-mcp) in the Claude Code settings of the user running the server, ~/.claude.json unless settings_path is given. The previous file is kept as <file>.<timestamp>.bak; dry_run returns the entry and the one it would replace without writing",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project_root": {"type": "string"},
+                        "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                        "settings_path": {"type": "string"},
+                        "dry_run": {"type": "boolean", "default": false}
                     },
-                    {
-                        "name": "register_agent",
-                        "description": "Register an AI agent in the workspace",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "agent_name": {"type": "string"},
-                                "agent_type": {"type": "string"},
-                                "capabilities": {"type": "array", "items": {"type": "string"}},
-                                "description": {"type": "string"}
-                            },
-                            "required": ["agent_name", "agent_type", "capabilities"]
-                        }
+                    "required": ["project_root", "port"]
+                }
+            },
+            {
+                "name": "run_readonly_query",
+                "description": "Admin: run a single SELECT (or WITH ... SELECT) statement against the live database without locking it. The connection is read-only; results are capped at max_rows and the query is cancelled after timeout_ms",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "sql": {"type": "string"},
+                        "max_rows": {"type": "integer", "minimum": 1, "maximum": ::task_core::MAX_QUERY_ROWS, "default": ::task_core::DEFAULT_QUERY_ROWS},
+                        "timeout_ms": {"type": "integer", "minimum": 1, "maximum": ::task_core::MAX_QUERY_TIMEOUT_MS, "default": ::task_core::DEFAULT_QUERY_TIMEOUT_MS}
                     },
-                    {
-                        "name": "get_instructions_for_main_ai_file",
-                        "description": "Get instructions for creating main AI coordination file",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "file_type": {"type": "string"}
-                            },
-                            "required": []
-                        }
+                    "required": ["sql"]
+                }
+            },
+            {
+                "name": "define_capability",
+                "description": "Define a capability of the catalog, or update its description and aliases. Capabilities given to register_agent, create_task and discover_work are normalized to catalog names",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string", "maxLength": ::task_core::MAX_CAPABILITY_LENGTH},
+                        "description": {"type": "string", "maxLength": 300},
+                        "aliases": {"type": "array", "items": {"type": "string"}}
                     },
-                    {
-                        "name": "create_main_ai_file",
-                        "description": "Create the main AI coordination file",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "content": {"type": "string"}
-                            },
-                            "required": ["content"]
-                        }
+                    "required": ["name"]
+                }
+            },
+            {
+                "name": "list_capabilities",
+                "description": "List the capability catalog",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "include_deprecated": {"type": "boolean", "default": false}
                     }
-                ]
-            });
-            create_success_response(id, tools_list)
+                }
+            },
+            {
+                "name": "deprecate_capability",
+                "description": "Deprecate a capability; uses of it are normalized to its replacement, if given",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {"type": "string"},
+                        "replaced_by": {"type": "string"}
+                    },
+                    "required": ["name"]
+                }
+            }
+        ]
+    })
+}
+
+/// Names of the tools offered by the server
+pub fn tool_names() -> Vec<String> {
+    tool_definitions()["tools"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// Add tool annotations for clients on protocol 2025-03-26 or newer
+fn annotate_tools(tools_list: &mut Value) {
+    if let Some(tools) = tools_list.get_mut("tools").and_then(|t| t.as_array_mut()) {
+        for tool in tools {
+            let read_only = tool
+                .get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(|name| READ_ONLY_METHODS.contains(&name));
+            tool["annotations"] = json!({ "readOnlyHint": read_only });
         }
-        _ => McpError::Protocol(format!("Unknown method: {method}")).to_json_rpc_error(id),
     }
 }
 
-/// SSE endpoint for MCP communication
+/// SSE stream of JSON-RPC messages
+type SseStream = UnboundedReceiverStream<Result<Event, axum::Error>>;
+
+/// Interval of SSE keep-alive comments on idle streams
+const SSE_KEEP_ALIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Legacy SSE endpoint (2024-11-05 HTTP+SSE transport)
 async fn sse_handler<
-    R: TaskRepository + Send + Sync + 'static,
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
     M: TaskMessageRepository + Send + Sync + 'static,
     W: WorkspaceContextRepository + Send + Sync + 'static,
 >(
-    State(_state): State<Arc<McpServerState<R, M, W>>>,
-) -> Result<Sse<UnboundedReceiverStream<Result<axum::response::sse::Event, axum::Error>>>, StatusCode>
-{
-    let (tx, rx) = mpsc::unbounded_channel();
+    State(state): State<Arc<McpServerState<R, M, W>>>,
+    headers: HeaderMap,
+) -> Sse<SseStream> {
+    // The HTTP+SSE transport starts with the URI where clients POST their messages
+    let endpoint_event = Event::default().event("endpoint").data("/mcp/v1/rpc");
 
-    // Send initial connection event (this is legacy SSE, not proper MCP)
-    let welcome_event = axum::response::sse::Event::default().data(
+    let welcome_event = Event::default().data(
         json!({
             "jsonrpc": "2.0",
             "method": "connection_established",
@@ -695,106 +2592,831 @@ async fn sse_handler<
         .to_string(),
     );
 
-    if tx.send(Ok(welcome_event)).is_err() {
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
-    }
+    notification_stream(
+        &state.notifications,
+        None,
+        last_event_id_from_headers(&headers),
+        vec![endpoint_event, welcome_event],
+    )
+}
+
+/// Server-to-client notification stream of a session (GET /mcp)
+async fn session_stream_handler<
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+>(
+    State(state): State<Arc<McpServerState<R, M, W>>>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(session_id) = session_id_from_headers(&headers) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Missing Mcp-Session-Id header. Send an initialize request first",
+        )
+            .into_response();
+    };
+
+    if state.sessions.touch(session_id).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    notification_stream(
+        &state.notifications,
+        Some(session_id.to_string()),
+        last_event_id_from_headers(&headers),
+        Vec::new(),
+    )
+    .into_response()
+}
+
+/// Forward hub notifications into a new SSE stream
+///
+/// Missed notifications after `last_event_id` are replayed first; the forwarder
+/// stops as soon as the client disconnects.
+fn notification_stream(
+    notifications: &NotificationHub,
+    session_id: Option<String>,
+    last_event_id: Option<u64>,
+    initial_events: Vec<Event>,
+) -> Sse<SseStream> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    // Subscribe before reading history so nothing published in between is lost
+    let mut receiver = notifications.subscribe();
+    let replay = last_event_id
+        .map(|id| notifications.replay_after(id, session_id.as_deref()))
+        .unwrap_or_default();
+    let mut last_sent = replay
+        .last()
+        .map(|n| n.event_id)
+        .or(last_event_id)
+        .unwrap_or(0);
+
+    for event in initial_events {
+        let _ = tx.send(Ok(event));
+    }
+    for notification in &replay {
+        let _ = tx.send(Ok(notification_event(notification)));
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tx.closed() => break,
+                received = receiver.recv() => match received {
+                    Ok(notification) => {
+                        if notification.event_id <= last_sent
+                            || !notification.is_visible_to(session_id.as_deref())
+                        {
+                            continue;
+                        }
+                        last_sent = notification.event_id;
+                        if tx.send(Ok(notification_event(&notification))).is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("SSE client lagged behind, {} notifications dropped", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+
+    Sse::new(UnboundedReceiverStream::new(rx))
+        .keep_alive(KeepAlive::new().interval(SSE_KEEP_ALIVE_INTERVAL))
+}
+
+/// Encode a notification as an SSE `message` event with a resumable id
+fn notification_event(notification: &ServerNotification) -> Event {
+    Event::default()
+        .id(notification.event_id.to_string())
+        .data(notification.message.to_string())
+}
+
+/// Read the `Last-Event-ID` header sent by reconnecting SSE clients
+fn last_event_id_from_headers(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// Task-mutating methods whose successful results are announced to SSE streams
+const TASK_CHANGE_METHODS: &[&str] = &[
+    "create_task",
+    "update_task",
+    "set_task_state",
+    "assign_task",
+    "archive_task",
+    "claim_task",
+    "release_task",
+];
+
+/// Method applying a batch of operations; its results list the tasks and messages produced
+const TASK_OPERATIONS_METHOD: &str = "apply_task_operations";
+
+/// Entries of kind `kind` ("task" or "message") in a successful batch response
+fn operation_results<'a>(response: &'a Value, kind: &'a str) -> impl Iterator<Item = &'a Value> {
+    response
+        .pointer("/result/results")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(move |result| result.get(kind))
+}
+
+/// Method applying the edits of the task file; its result lists the applied state changes
+const TASKS_MARKDOWN_METHOD: &str = "sync_tasks_markdown";
+
+/// Announce a successful task mutation as an MCP logging notification
+fn publish_task_change(notifications: &NotificationHub, method: &str, response: &Value) {
+    if method == TASKS_MARKDOWN_METHOD {
+        let edits = response
+            .pointer("/result/applied")
+            .and_then(Value::as_array);
+        for edit in edits.into_iter().flatten() {
+            let task =
+                json!({"id": edit["task_id"], "code": edit["task_code"], "state": edit["to"]});
+            publish_task_changed(notifications, method, &task);
+        }
+        return;
+    }
+    let tasks: Vec<&Value> = if method == TASK_OPERATIONS_METHOD {
+        operation_results(response, "task").collect()
+    } else if TASK_CHANGE_METHODS.contains(&method) {
+        // Unconfirmed duplicates come back without a task
+        response
+            .get("result")
+            .filter(|r| r.get("id").is_some())
+            .into_iter()
+            .collect()
+    } else {
+        return;
+    };
+
+    for task in tasks {
+        publish_task_changed(notifications, method, task);
+    }
+}
+
+fn publish_task_changed(notifications: &NotificationHub, method: &str, task: &Value) {
+    notifications.publish(
+        None,
+        "notifications/message",
+        json!({
+            "level": "info",
+            "logger": "axon-mcp",
+            "data": {
+                "event": "task_changed",
+                "method": method,
+                "task_id": task.get("id"),
+                "code": task.get("code"),
+                "state": task.get("state"),
+                "owner_agent_name": task.get("owner_agent_name"),
+            }
+        }),
+    );
+}
+
+/// Announce `@agent-name` mentions in a new message to the mentioned agents
+fn publish_mentions(notifications: &NotificationHub, method: &str, response: &Value) {
+    let messages: Vec<&Value> = match method {
+        TASK_OPERATIONS_METHOD => operation_results(response, "message").collect(),
+        "create_task_message" => response
+            .get("result")
+            .filter(|r| r.is_object())
+            .into_iter()
+            .collect(),
+        _ => return,
+    };
+
+    for message in messages {
+        publish_message_mentions(notifications, message);
+    }
+}
+
+fn publish_message_mentions(notifications: &NotificationHub, message: &Value) {
+    let author = message.get("author_agent_name").and_then(|a| a.as_str());
+    let content = message
+        .get("content")
+        .and_then(|c| c.as_str())
+        .unwrap_or_default();
+
+    for agent_name in extract_mentions(content) {
+        if Some(agent_name.as_str()) == author {
+            continue;
+        }
+        notifications.publish(
+            None,
+            "notifications/message",
+            json!({
+                "level": "info",
+                "logger": "axon-mcp",
+                "data": {
+                    "event": "mention",
+                    "mentioned_agent_name": agent_name,
+                    "author_agent_name": author,
+                    "task_code": message.get("task_code"),
+                    "message_id": message.get("id"),
+                    "message_type": message.get("message_type"),
+                }
+            }),
+        );
+    }
+}
+
+/// JSON-RPC endpoint for MCP communication
+async fn rpc_handler<
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+>(
+    State(state): State<Arc<McpServerState<R, M, W>>>,
+    listener: Option<Extension<ListenerConfig>>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Response {
+    info!("Received RPC request: {}", request);
+    let received = std::time::Instant::now();
+    let listener = listener
+        .map(|Extension(listener)| listener)
+        .unwrap_or_default();
+    let read_only = state.read_only || listener.read_only;
+
+    // Extract ID first for error responses
+    let id = request.get("id").cloned();
+
+    // MCP-Protocol-Version header (required by 2025-06-18 spec after initialize)
+    let header_version = headers
+        .get("MCP-Protocol-Version")
+        .or_else(|| headers.get("mcp-protocol-version")) // Try lowercase variant
+        .and_then(|v| v.to_str().ok());
+
+    // Set response headers
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+
+    // Reject unknown versions outright so clients can fall back
+    let requested_version = match header_version {
+        Some(version) => match ProtocolVersion::parse(version) {
+            Some(parsed) => Some(parsed),
+            None => {
+                let error = McpError::Protocol(format!(
+                    "Unsupported MCP-Protocol-Version: {version}. Supported versions: {}",
+                    ProtocolVersion::supported_list()
+                ));
+                return (
+                    StatusCode::BAD_REQUEST,
+                    response_headers,
+                    Json(error.to_json_rpc_error(id)),
+                )
+                    .into_response();
+            }
+        },
+        None => None,
+    };
+
+    // Validate that request is not a JSON-RPC batch (forbidden in 2025-06-18)
+    if request.is_array() {
+        let error = McpError::Protocol(
+            "JSON-RPC batching is not supported in MCP 2025-06-18 specification".to_string(),
+        );
+        return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+    }
+
+    // Parse JSON-RPC request - return JSON-RPC errors instead of HTTP errors
+    let method = match request.get("method").and_then(|v| v.as_str()) {
+        Some(method) => method,
+        // A client answering a request of ours, e.g. sampling/createMessage
+        None if request.get("result").is_some() || request.get("error").is_some() => {
+            if !state
+                .client_requests
+                .resolve(session_id_from_headers(&headers), &request)
+            {
+                warn!(
+                    "Dropped a response to an unknown or expired request: {:?}",
+                    id
+                );
+            }
+            return StatusCode::ACCEPTED.into_response();
+        }
+        None => {
+            let error = McpError::Protocol(
+                "Missing or invalid 'method' field in JSON-RPC request".to_string(),
+            );
+            return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+        }
+    };
+
+    let params = request.get("params").unwrap_or(&Value::Null).clone();
+
+    // initialize opens a new session; every other request must belong to a live one
+    if method == "initialize" {
+        let is_admin = state.admin_auth.is_admin_request(&headers);
+        let agent_name = match state.agent_auth.identify(&headers) {
+            Ok(agent_name) => agent_name,
+            Err(error) => {
+                return (response_headers, Json(error.to_json_rpc_error(id))).into_response()
+            }
+        };
+        let (session, result) = initialize_session(&state.sessions, &params, is_admin, agent_name);
+        state
+            .client_requests
+            .session_opened(&session.id, params.get("capabilities"));
+        response_headers.insert(
+            header::HeaderName::from_static("mcp-protocol-version"),
+            session.protocol_version.parse().unwrap(),
+        );
+        response_headers.insert(
+            header::HeaderName::from_static(MCP_SESSION_ID_HEADER),
+            session.id.parse().unwrap(),
+        );
+        return (response_headers, Json(create_success_response(id, result))).into_response();
+    }
+
+    let session = match session_id_from_headers(&headers) {
+        Some(session_id) => match state.sessions.touch(session_id) {
+            Some(session) => Some(session),
+            None => {
+                let error = McpError::Protocol(format!(
+                    "Session not found or expired: {session_id}. Send a new initialize request"
+                ));
+                return (
+                    StatusCode::NOT_FOUND,
+                    response_headers,
+                    Json(error.to_json_rpc_error(id)),
+                )
+                    .into_response();
+            }
+        },
+        None if state.require_session => {
+            let error = McpError::Protocol(
+                "Missing Mcp-Session-Id header. Send an initialize request first".to_string(),
+            );
+            return (
+                StatusCode::BAD_REQUEST,
+                response_headers,
+                Json(error.to_json_rpc_error(id)),
+            )
+                .into_response();
+        }
+        None => None,
+    };
+
+    // Header wins, then the version negotiated for the session, then the spec fallback
+    let protocol_version = requested_version
+        .or_else(|| {
+            session
+                .as_ref()
+                .and_then(|s| ProtocolVersion::parse(&s.protocol_version))
+        })
+        .unwrap_or_else(|| {
+            debug!(
+                "Missing MCP-Protocol-Version header, defaulting to {}",
+                ProtocolVersion::DEFAULT_FALLBACK
+            );
+            ProtocolVersion::DEFAULT_FALLBACK
+        });
+    response_headers.insert(
+        header::HeaderName::from_static("mcp-protocol-version"),
+        protocol_version.as_str().parse().unwrap(),
+    );
+
+    if let Some(session) = &session {
+        debug!(
+            "Handling {} for session {} (protocol {})",
+            method, session.id, protocol_version
+        );
+    }
+
+    // Notifications carry no id and expect no JSON-RPC response body
+    if id.is_none() && method.starts_with("notifications/") {
+        if matches!(
+            method,
+            "notifications/initialized" | "notifications/roots/list_changed"
+        ) {
+            if let Some(session) = &session {
+                discover_project_root(&state, &session.id);
+            }
+        }
+        return (StatusCode::ACCEPTED, response_headers).into_response();
+    }
+
+    if read_only && !is_allowed_when_read_only(method) {
+        warn!("Rejected {} on a read-only server", method);
+        let error = McpError::Forbidden(format!(
+            "{method} is not available: this server is read-only and only serves lookups"
+        ));
+        return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+    }
+
+    // Admin tools need the admin key on this request or on the session's initialize
+    let is_admin =
+        state.admin_auth.is_admin_request(&headers) || session.as_ref().is_some_and(|s| s.is_admin);
+    if is_admin_method(method) && !is_admin {
+        warn!("Rejected admin method {} from non-admin client", method);
+        let error = McpError::Forbidden(format!("{method} requires the admin role"));
+        return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+    }
+
+    // Claimed tasks and work sessions may only be changed by their owner or a coordinator
+    let caller = state.agent_auth.caller(
+        &headers,
+        session.as_ref().and_then(|s| s.agent_name.as_deref()),
+        is_admin,
+    );
+    let agent_auth = match listener.require_identity && !state.agent_auth.require_identity {
+        true => Cow::Owned(AgentAuth {
+            require_identity: true,
+            ..state.agent_auth.clone()
+        }),
+        false => Cow::Borrowed(&state.agent_auth),
+    };
+    let usage_agent = attributed_agent(caller.as_ref().ok(), method, &params);
+    // Only identified callers are watched, so naming an agent cannot get it throttled
+    let watched_agent = caller
+        .as_ref()
+        .ok()
+        .and_then(|caller| caller.agent_name.clone())
+        .filter(|_| is_tool(method));
+    let authorized = match caller {
+        Ok(caller) => authorize_agent_call(&state, &agent_auth, &caller, method, &params).await,
+        Err(error) => Err(error),
+    };
+    if let Err(error) = authorized {
+        warn!("Rejected {}: {}", method, error);
+        return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+    }
+
+    // Agents caught in a loop wait until their throttle lifts
+    let usage_agent = usage_agent.filter(|_| is_tool(method));
+    if let Some(agent_name) = &watched_agent {
+        let now = chrono::Utc::now();
+        if let Some(throttle) = state.runaway_agents.throttle(agent_name, now) {
+            let retry_after_secs = (throttle.until - now).num_seconds().max(1);
+            let error = McpError::Throttled {
+                message: format!(
+                    "{}; calls are rejected for another {retry_after_secs} s. Change approach or ask the coordinator for help",
+                    throttle.pattern.describe()
+                ),
+                retry_after_secs,
+            };
+            return (response_headers, Json(error.to_json_rpc_error(id))).into_response();
+        }
+    }
+
+    // Server-level tools are answered from shared state rather than the task handler
+    if method == "list_background_jobs" {
+        let result = json!({ "jobs": state.background_jobs.snapshot() });
+        return (response_headers, Json(create_success_response(id, result))).into_response();
+    }
+
+    // Workspace tools work in the root the client listed, else the server's own
+    let params = match accepts_project_root(method) {
+        true => {
+            let root = session.as_ref().and_then(|s| s.project_root.as_deref());
+            match with_project_root(params, root) {
+                Ok(params) => params,
+                Err(e) => return (response_headers, Json(e.to_json_rpc_error(id))).into_response(),
+            }
+        }
+        false => params,
+    };
+
+    // Proposals are confirmed by the user of a client that can be asked
+    let confirmation = confirmation_flag(method)
+        .zip(session.as_ref())
+        .filter(|(_, session)| state.client_requests.supports(&session.id, "elicitation"))
+        .map(|(flag, session)| (flag, session.id.clone(), params.clone()));
+
+    let observed_params = watched_agent.as_ref().map(|_| params.clone());
+    let called_at = chrono::Utc::now();
+    let started = std::time::Instant::now();
+    let session_id = session.as_ref().map(|session| session.id.clone());
+    let execution = execute_within_budget(&state, method, params, id.clone(), protocol_version);
+    let mut response = on_behalf_of(session_id, execution).await;
+    if let Some((flag, session_id, mut params)) = confirmation {
+        let timeout = elicitation_timeout(state.request_timeout, received.elapsed());
+        if let Some(timeout) = timeout.filter(|_| needs_confirmation(&response)) {
+            let message = response["result"]["message"].as_str().unwrap_or_default();
+            match confirm_with_user(&state.client_requests, &session_id, message, timeout).await {
+                Ok((_, true)) => {
+                    params[flag] = json!(true);
+                    let execution =
+                        execute_within_budget(&state, method, params, id, protocol_version);
+                    response = on_behalf_of(Some(session_id), execution).await;
+                }
+                Ok((action, false)) => response["result"]["user_response"] = json!(action),
+                Err(e) => warn!("Left the confirmation of {} to the agent: {}", method, e),
+            }
+        }
+    }
+    if let Some((agent_name, params)) = watched_agent.as_ref().zip(observed_params) {
+        let succeeded = response.get("error").is_none();
+        if let Some(pattern) = state
+            .runaway_agents
+            .observe(agent_name, method, &params, succeeded, called_at)
+        {
+            report_runaway_agent(&state, pattern);
+        }
+    }
+    if let Some(agent_name) = usage_agent {
+        record_tool_call(
+            &state,
+            ToolCall {
+                agent_name,
+                tool_name: method.to_string(),
+                called_at,
+                duration_ms: started.elapsed().as_millis() as u64,
+                is_error: response.get("error").is_some(),
+            },
+        );
+    }
+    publish_task_change(&state.notifications, method, &response);
+    publish_mentions(&state.notifications, method, &response);
+
+    if method == "tools/list" {
+        if let Some(result) = response.get_mut("result") {
+            if !is_admin {
+                hide_admin_tools(result);
+            }
+            if read_only {
+                hide_mutating_tools(result);
+            }
+        }
+    }
+
+    (response_headers, Json(response)).into_response()
+}
+
+/// Ask a client with the `roots` capability for its roots and keep its project
+/// root on the session
+fn discover_project_root<R, M, W>(state: &McpServerState<R, M, W>, session_id: &str)
+where
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+{
+    if !state.client_requests.supports(session_id, "roots") {
+        return;
+    }
+    let requests = state.client_requests.clone();
+    let sessions = state.sessions.clone();
+    let configured = state.handler.project_root().map(Path::to_path_buf);
+    let session_id = session_id.to_string();
+    tokio::spawn(async move {
+        match request_project_root(&requests, &session_id, DEFAULT_ROOTS_TIMEOUT).await {
+            Ok(Some(root))
+                if configured
+                    .as_deref()
+                    .is_some_and(|configured| !is_within(Path::new(&root), configured)) =>
+            {
+                warn!(
+                    "Session {} lists root {} outside the server's project root; using the server's",
+                    session_id, root
+                );
+                sessions.set_project_root(&session_id, None);
+            }
+            Ok(root) => {
+                info!(
+                    "Session {} works in project root {}",
+                    session_id,
+                    root.as_deref().unwrap_or("of the server")
+                );
+                sessions.set_project_root(&session_id, root);
+            }
+            Err(e) => warn!("Could not list the roots of session {}: {}", session_id, e),
+        }
+    });
+}
+
+/// Warn about a runaway agent, record it for the coordinator and notify connected clients
+fn report_runaway_agent<R, M, W>(state: &McpServerState<R, M, W>, pattern: RunawayPattern)
+where
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+{
+    warn!("Throttling runaway agent: {}", pattern.describe());
+    state.notifications.publish(
+        None,
+        "notifications/message",
+        json!({
+            "level": "warning",
+            "logger": "axon-mcp",
+            "data": {
+                "event": "runaway_agent",
+                "message": pattern.describe(),
+                "pattern": pattern,
+                "coordinators": state.agent_auth.coordinators,
+            }
+        }),
+    );
+    let repository = state.handler.repository();
+    tokio::spawn(async move {
+        let event = pattern.to_system_event(chrono::Utc::now());
+        match repository.record_system_event(&event).await {
+            Ok(_) | Err(TaskError::UnsupportedOperation(_)) => {}
+            Err(e) => warn!("Failed to record {}: {}", event.event_type, e),
+        }
+    });
+}
+
+/// Store a tool call for usage stats without delaying the response
+fn record_tool_call<R, M, W>(state: &McpServerState<R, M, W>, call: ToolCall)
+where
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+{
+    let repository = state.handler.repository();
+    tokio::spawn(async move {
+        match repository.record_tool_call(&call).await {
+            Ok(()) | Err(TaskError::UnsupportedOperation(_)) => {}
+            Err(e) => warn!(
+                "Failed to record {} call of {}: {}",
+                call.tool_name, call.agent_name, e
+            ),
+        }
+    });
+}
+
+/// Whether `method` is one of the tools offered by the server
+pub(crate) fn is_tool(method: &str) -> bool {
+    static TOOLS: OnceLock<Vec<String>> = OnceLock::new();
+    TOOLS
+        .get_or_init(tool_names)
+        .iter()
+        .any(|name| name == method)
+}
 
-    // Set up heartbeat
-    let heartbeat_tx = tx.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
-        loop {
-            interval.tick().await;
-            let heartbeat = axum::response::sse::Event::default()
-                .event("heartbeat")
-                .data("ping");
+/// Whether tool `method` has an optional `project_root` property
+///
+/// Tools that require one, like `register_with_claude_code`, name a client
+/// project rather than the workspace they work in.
+fn accepts_project_root(method: &str) -> bool {
+    static TOOLS: OnceLock<Vec<String>> = OnceLock::new();
+    TOOLS
+        .get_or_init(|| {
+            tool_definitions()["tools"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|tool| tool["inputSchema"]["properties"]["project_root"].is_object())
+                .filter(|tool| {
+                    let required = tool["inputSchema"]["required"].as_array();
+                    !required.is_some_and(|required| required.contains(&json!("project_root")))
+                })
+                .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+                .collect()
+        })
+        .iter()
+        .any(|name| name == method)
+}
 
-            if heartbeat_tx.send(Ok(heartbeat)).is_err() {
-                break;
+/// Execute `method` through the handler within its time budget
+async fn execute_within_budget<R, M, W>(
+    state: &McpServerState<R, M, W>,
+    method: &str,
+    params: Value,
+    id: Option<Value>,
+    protocol_version: ProtocolVersion,
+) -> Value
+where
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+    M: TaskMessageRepository + Send + Sync + 'static,
+    W: WorkspaceContextRepository + Send + Sync + 'static,
+{
+    let execution =
+        execute_mcp_method(&state.handler, method, params, id.clone(), protocol_version);
+    match state.method_timeouts.budget_for(method) {
+        Some(budget) => match tokio::time::timeout(budget, execution).await {
+            Ok(response) => response,
+            Err(_) => {
+                warn!("Method {} exceeded its {:?} budget", method, budget);
+                state.timeout_metrics.record_overrun(method);
+                McpError::Timeout(format!(
+                    "{method} did not complete within {} ms",
+                    budget.as_millis()
+                ))
+                .to_json_rpc_error(id)
             }
-        }
-    });
+        },
+        None => execution.await,
+    }
+}
+
+/// Check that the caller may perform `method`, looking up the owner of the work it changes
+async fn authorize_agent_call<R, M, W>(
+    state: &McpServerState<R, M, W>,
+    agent_auth: &AgentAuth,
+    caller: &Caller,
+    method: &str,
+    params: &Value,
+) -> Result<(), McpError>
+where
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
+{
+    let mut owned = agent_auth.authorize(caller, method, params)?;
+    if method == "create_task_message" {
+        owned = serde_json::from_value::<CreateTaskMessageParams>(params.clone())
+            .ok()
+            .filter(|message| state.handler.message_changes_task(message))
+            .map(|message| OwnedWork::TaskCode(message.task_code));
+    }
+    let Some(work) = owned else {
+        return Ok(());
+    };
 
-    let stream = UnboundedReceiverStream::new(rx);
-    Ok(Sse::new(stream))
+    // Missing work is reported by the method itself
+    let repository = state.handler.repository();
+    let owner = match work {
+        OwnedWork::Task(id) => repository
+            .get_by_id(id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|task| task.owner_agent_name),
+        OwnedWork::TaskCode(ref code) => repository
+            .get_by_code(code)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|task| task.owner_agent_name),
+        OwnedWork::WorkSession(id) => repository
+            .get_work_session(id)
+            .await
+            .ok()
+            .flatten()
+            .map(|session| session.agent_name),
+    };
+    caller.check_owner(&work, owner.as_deref())
 }
 
-/// JSON-RPC endpoint for MCP communication
-async fn rpc_handler<
-    R: TaskRepository + Send + Sync + 'static,
+/// Terminate a session on client request (DELETE /mcp)
+async fn delete_session_handler<
+    R: TaskRepository + Send + Sync + ?Sized + 'static,
     M: TaskMessageRepository + Send + Sync + 'static,
     W: WorkspaceContextRepository + Send + Sync + 'static,
 >(
     State(state): State<Arc<McpServerState<R, M, W>>>,
     headers: HeaderMap,
-    Json(request): Json<Value>,
-) -> Result<(HeaderMap, Json<Value>), StatusCode> {
-    info!("Received RPC request: {}", request);
-
-    // Extract ID first for error responses
-    let id = request.get("id").cloned();
-
-    // Validate MCP-Protocol-Version header (required by 2025-06-18 spec)
-    let protocol_version = headers
-        .get("MCP-Protocol-Version")
-        .or_else(|| headers.get("mcp-protocol-version")) // Try lowercase variant
-        .and_then(|v| v.to_str().ok());
-
-    // Set response headers
-    let mut response_headers = HeaderMap::new();
-    response_headers.insert(
-        header::HeaderName::from_static("mcp-protocol-version"),
-        MCP_PROTOCOL_VERSION.parse().unwrap(),
-    );
-    response_headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
-
-    // Validate protocol version - default to 2025-03-26 for backward compatibility if missing
-    match protocol_version {
-        Some(version) if version == MCP_PROTOCOL_VERSION => {
-            // Current version - proceed normally
-        }
-        Some(version) if version == "2025-03-26" => {
-            // Backward compatibility - log warning but proceed
-            info!("Client using older MCP protocol version: {}", version);
-        }
-        Some(version) => {
-            // Unsupported version
-            let error = McpError::Protocol(format!("Unsupported MCP-Protocol-Version: {version}. Supported versions: {MCP_PROTOCOL_VERSION}, 2025-03-26"));
-            return Ok((response_headers, Json(error.to_json_rpc_error(id))));
-        }
-        None => {
-            // Missing header - default to backward compatibility
-            info!(
-                "Missing MCP-Protocol-Version header, defaulting to 2025-03-26 compatibility mode"
-            );
+) -> StatusCode {
+    match session_id_from_headers(&headers) {
+        Some(session_id) if state.sessions.remove(session_id) => {
+            state.client_requests.session_closed(session_id);
+            info!("Terminated MCP session {}", session_id);
+            StatusCode::NO_CONTENT
         }
+        Some(_) => StatusCode::NOT_FOUND,
+        None => StatusCode::BAD_REQUEST,
     }
+}
 
-    // Validate that request is not a JSON-RPC batch (forbidden in 2025-06-18)
-    if request.is_array() {
-        let error = McpError::Protocol(
-            "JSON-RPC batching is not supported in MCP 2025-06-18 specification".to_string(),
-        );
-        return Ok((response_headers, Json(error.to_json_rpc_error(id))));
-    }
+/// Read the `Mcp-Session-Id` request header
+fn session_id_from_headers(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(MCP_SESSION_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+}
 
-    // Parse JSON-RPC request - return JSON-RPC errors instead of HTTP errors
-    let method = match request.get("method").and_then(|v| v.as_str()) {
-        Some(method) => method,
-        None => {
-            let error = McpError::Protocol(
-                "Missing or invalid 'method' field in JSON-RPC request".to_string(),
-            );
-            return Ok((response_headers, Json(error.to_json_rpc_error(id))));
-        }
-    };
+/// Create a session for an `initialize` request and build its result
+fn initialize_session(
+    sessions: &SessionStore,
+    params: &Value,
+    is_admin: bool,
+    agent_name: Option<String>,
+) -> (McpSession, Value) {
+    let requested_version = params.get("protocolVersion").and_then(|v| v.as_str());
+    let protocol_version = ProtocolVersion::negotiate(requested_version);
 
-    let params = request.get("params").unwrap_or(&Value::Null).clone();
+    let session = sessions.create(
+        protocol_version.as_str(),
+        params.get("clientInfo").cloned(),
+        is_admin,
+        agent_name,
+    );
+    info!(
+        "Initialized MCP session {} (protocol {})",
+        session.id, session.protocol_version
+    );
 
-    // Execute the method directly through the handler
-    let response = execute_mcp_method(&state.handler, method, params, id).await;
+    let result = json!({
+        "protocolVersion": session.protocol_version,
+        "capabilities": {
+            "tools": {"listChanged": false},
+            "logging": {}
+        },
+        "serverInfo": {
+            "name": "axon-mcp",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    });
 
-    Ok((response_headers, Json(response)))
+    (session, result)
 }
 
 /// Health check endpoint
@@ -808,7 +3430,8 @@ mod tests {
     use ::task_core::error::Result;
     use ::task_core::workspace_setup::WorkspaceContext;
     use ::task_core::{
-        NewTask, RepositoryStats, Task, TaskFilter, TaskMessage, TaskState, UpdateTask,
+        MessageEffect, NewTask, RepositoryStats, Task, TaskCrudRepository, TaskExtensionRepository,
+        TaskFilter, TaskMessage, TaskState, UpdateTask, WorkClaimRepository, WorkSessionRepository,
         WorkspaceContextRepository,
     };
     use async_trait::async_trait;
@@ -819,7 +3442,7 @@ mod tests {
         TestRepository {}
 
         #[async_trait]
-        impl TaskRepository for TestRepository {
+        impl TaskCrudRepository for TestRepository {
             async fn create(&self, task: NewTask) -> Result<Task>;
             async fn update(&self, id: i32, updates: UpdateTask) -> Result<Task>;
             async fn set_state(&self, id: i32, state: TaskState) -> Result<Task>;
@@ -830,13 +3453,24 @@ mod tests {
             async fn archive(&self, id: i32) -> Result<Task>;
             async fn health_check(&self) -> Result<()>;
             async fn get_stats(&self) -> Result<RepositoryStats>;
+        }
+
+        #[async_trait]
+        impl WorkClaimRepository for TestRepository {
             async fn discover_work(&self, agent_name: &str, capabilities: &[String], max_tasks: u32) -> Result<Vec<Task>>;
             async fn claim_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
             async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task>;
+            async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
+        }
+
+        #[async_trait]
+        impl WorkSessionRepository for TestRepository {
             async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32>;
             async fn end_work_session(&self, session_id: i32, notes: Option<String>, productivity_score: Option<f64>) -> Result<()>;
-            async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>>;
         }
+
+        #[async_trait]
+        impl TaskExtensionRepository for TestRepository {}
     }
 
     // Simple mock for testing server creation
@@ -852,9 +3486,11 @@ mod tests {
             message_type: &str,
             content: &str,
             reply_to_message_id: Option<i32>,
+            structured_data: Option<&serde_json::Value>,
         ) -> Result<TaskMessage> {
             Ok(TaskMessage {
                 id: 1,
+                uid: None,
                 task_code: task_code.to_string(),
                 author_agent_name: author_agent_name.to_string(),
                 target_agent_name: None,
@@ -862,6 +3498,7 @@ mod tests {
                 created_at: chrono::Utc::now(),
                 content: content.to_string(),
                 reply_to_message_id,
+                structured_data: structured_data.cloned(),
             })
         }
 
@@ -917,4 +3554,1141 @@ mod tests {
         // Basic test that server can be created
         // Test passes if server creation doesn't panic
     }
+
+    #[tokio::test]
+    async fn test_markdown_edits_announce_task_changes() {
+        let hub = NotificationHub::default();
+        let mut stream = hub.subscribe();
+        let response = json!({"result": {"applied": [
+            {"task_id": 4, "task_code": "MD-01", "from": "InProgress", "to": "Done"}
+        ]}});
+        publish_task_change(&hub, "sync_tasks_markdown", &response);
+
+        let data = stream.recv().await.unwrap().message["params"]["data"].clone();
+        assert_eq!(data["event"], "task_changed");
+        assert_eq!(data["task_id"], 4);
+        assert_eq!(data["state"], "Done");
+    }
+
+    async fn post_tools_list(http_config: HttpConfig) -> axum::response::Response {
+        use tower::ServiceExt;
+
+        let server = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_http_config(http_config);
+
+        let request = axum::http::Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(axum::body::Body::from(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}).to_string(),
+            ))
+            .unwrap();
+
+        server.create_router().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_response_compression_is_configurable() {
+        let compressed = post_tools_list(HttpConfig::default()).await;
+        assert_eq!(compressed.status(), StatusCode::OK);
+        assert_eq!(compressed.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let plain = post_tools_list(HttpConfig {
+            compression: false,
+            ..HttpConfig::default()
+        })
+        .await;
+        assert_eq!(plain.status(), StatusCode::OK);
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_for_allowed_origin() {
+        use tower::ServiceExt;
+
+        let server = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_http_config(HttpConfig {
+            cors: crate::http_config::CorsConfig {
+                allowed_origins: vec!["http://localhost:5173".to_string()],
+                ..Default::default()
+            },
+            ..HttpConfig::default()
+        });
+
+        let request = axum::http::Request::builder()
+            .method("OPTIONS")
+            .uri("/mcp")
+            .header(header::ORIGIN, "http://localhost:5173")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(axum::body::Body::empty())
+            .unwrap();
+
+        let response = server.create_router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://localhost:5173"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_initialize_issues_session_id() {
+        use tower::ServiceExt;
+
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .create_router();
+
+        let rpc = |session_id: Option<&str>, body: Value| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID_HEADER, session_id);
+            }
+            request
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = router
+            .clone()
+            .oneshot(rpc(
+                None,
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "initialize",
+                    "params": {"protocolVersion": "2025-06-18", "clientInfo": {"name": "test"}}
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let session_id = response.headers()[MCP_SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        let response = router
+            .clone()
+            .oneshot(rpc(Some(&session_id), notification.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let response = router
+            .clone()
+            .oneshot(rpc(Some("unknown-session"), notification))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let delete = axum::http::Request::delete("/mcp")
+            .header(MCP_SESSION_ID_HEADER, session_id.as_str())
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.oneshot(delete).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_client_answers_sampling_request() {
+        use tower::ServiceExt;
+
+        let server = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+        let sampler = server.sampler();
+        let mut stream = server.notifications().subscribe();
+        let router = server.create_router();
+        let post = |session_id: Option<&str>, body: Value| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID_HEADER, session_id);
+            }
+            request
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"sampling": {}}}
+        });
+        let response = router
+            .clone()
+            .oneshot(post(None, initialize))
+            .await
+            .unwrap();
+        let session_id = response.headers()[MCP_SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(!sampler.is_available());
+
+        let requesting = Some(session_id.clone());
+        let request = tokio::spawn(on_behalf_of(requesting, async move {
+            assert!(sampler.is_available());
+            sampler
+                .create_message(crate::SamplingRequest {
+                    system_prompt: "Summarize".to_string(),
+                    prompt: "Thread".to_string(),
+                    max_tokens: 50,
+                })
+                .await
+        }));
+        let sent = stream.recv().await.unwrap();
+        assert_eq!(sent.session_id.as_deref(), Some(session_id.as_str()));
+        let answer = json!({
+            "jsonrpc": "2.0",
+            "id": sent.message["id"],
+            "result": {"role": "assistant", "content": {"type": "text", "text": "Done"}, "model": "m"}
+        });
+        let response = router
+            .oneshot(post(Some(&session_id), answer))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(request.await.unwrap().unwrap(), "Done");
+    }
+
+    #[tokio::test]
+    async fn test_protocol_version_negotiation() {
+        use tower::ServiceExt;
+
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_http_config(HttpConfig {
+            compression: false,
+            ..HttpConfig::default()
+        })
+        .create_router();
+
+        let tools_list = |version: &str| {
+            axum::http::Request::post("/mcp")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header("mcp-protocol-version", version)
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = router
+            .clone()
+            .oneshot(tools_list("2024-11-05"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["mcp-protocol-version"], "2024-11-05");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["result"]["tools"][0].get("annotations").is_none());
+
+        let response = router
+            .clone()
+            .oneshot(tools_list("2025-06-18"))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let get_by_id = body["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|t| t["name"] == "get_task_by_id")
+            .unwrap();
+        assert_eq!(get_by_id["annotations"]["readOnlyHint"], true);
+
+        let response = router.oneshot(tools_list("1999-01-01")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("2025-06-18, 2025-03-26, 2024-11-05"));
+    }
+
+    #[tokio::test]
+    async fn test_notification_stream_replays_after_last_event_id() {
+        use http_body_util::BodyExt;
+
+        let hub = NotificationHub::default();
+        let seen = hub.publish(None, "notifications/message", json!({"n": 1}));
+        hub.publish(
+            Some("other".into()),
+            "notifications/message",
+            json!({"n": 2}),
+        );
+        hub.publish(
+            Some("mine".into()),
+            "notifications/message",
+            json!({"n": 3}),
+        );
+
+        let response =
+            notification_stream(&hub, Some("mine".into()), Some(seen), Vec::new()).into_response();
+        let mut body = response.into_body();
+        let frame = body.frame().await.unwrap().unwrap();
+        let text = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+
+        assert!(text.contains("id: 3"));
+        assert!(text.contains(r#""n":3"#));
+    }
+
+    #[tokio::test]
+    async fn test_session_stream_requires_session() {
+        use tower::ServiceExt;
+
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .create_router();
+
+        let request = axum::http::Request::get("/mcp")
+            .header(header::ACCEPT, "text/event-stream")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_list_background_jobs_reports_registry() {
+        use tower::ServiceExt;
+
+        let jobs = JobRegistry::default();
+        jobs.register(
+            "cleanup_timed_out_tasks",
+            std::time::Duration::from_secs(300),
+            std::time::Duration::ZERO,
+        );
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_background_jobs(jobs)
+        .with_admin_api_key(Some("admin-key".into()))
+        .create_router();
+
+        let request = axum::http::Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, "Bearer admin-key")
+            .body(axum::body::Body::from(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "list_background_jobs"}).to_string(),
+            ))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+
+        let job = &body["result"]["jobs"][0];
+        assert_eq!(job["name"], "cleanup_timed_out_tasks");
+        assert_eq!(job["interval_secs"], 300);
+        assert_eq!(job["last_outcome"], "pending");
+    }
+
+    #[tokio::test]
+    async fn test_create_task_requires_confirming_duplicates() {
+        use tower::ServiceExt;
+
+        let mut repository = MockTestRepository::new();
+        repository.expect_list().returning(|_| {
+            Ok(vec![Task::new(
+                1,
+                "AUTH-01".to_string(),
+                "Fix login crash".to_string(),
+                "App crashes after OAuth redirect".to_string(),
+                None,
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            )])
+        });
+        repository.expect_create().times(1).returning(|task| {
+            Ok(Task::new(
+                2,
+                task.code,
+                task.name,
+                task.description,
+                task.owner_agent_name,
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            ))
+        });
+        let router = McpServer::new(
+            Arc::new(repository),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_duplicate_check_policy(DuplicateCheckPolicy {
+            mode: DuplicateCheckMode::Confirm,
+            ..DuplicateCheckPolicy::default()
+        })
+        .create_router();
+
+        let create = |confirm_duplicate: bool| {
+            let params = json!({
+                "code": "AUTH-02",
+                "name": "Fix login crash",
+                "description": "The app crashes after the OAuth redirect",
+                "owner_agent_name": "backend-dev",
+                "confirm_duplicate": confirm_duplicate,
+            });
+            axum::http::Request::post("/mcp")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": "create_task", "params": params})
+                        .to_string(),
+                ))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+
+        let pending = body(router.clone().oneshot(create(false)).await.unwrap()).await;
+        assert_eq!(pending["result"]["status"], "confirmation_required");
+        assert_eq!(
+            pending["result"]["potential_duplicates"][0]["code"],
+            "AUTH-01"
+        );
+
+        let created = body(router.oneshot(create(true)).await.unwrap()).await;
+        assert_eq!(created["result"]["code"], "AUTH-02");
+        assert_eq!(created["result"]["potential_duplicates"][0]["task_id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_user_confirms_duplicate_through_elicitation() {
+        use tower::ServiceExt;
+
+        let mut repository = MockTestRepository::new();
+        repository.expect_list().returning(|_| {
+            Ok(vec![Task::new(
+                1,
+                "AUTH-01".to_string(),
+                "Fix login crash".to_string(),
+                "App crashes after OAuth redirect".to_string(),
+                None,
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            )])
+        });
+        repository.expect_create().times(1).returning(|task| {
+            Ok(Task::new(
+                2,
+                task.code,
+                task.name,
+                task.description,
+                task.owner_agent_name,
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            ))
+        });
+        let server = McpServer::new(
+            Arc::new(repository),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_duplicate_check_policy(DuplicateCheckPolicy {
+            mode: DuplicateCheckMode::Confirm,
+            ..DuplicateCheckPolicy::default()
+        });
+        let mut stream = server.notifications().subscribe();
+        let router = server.create_router();
+        let post = |session_id: Option<&str>, body: Value| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID_HEADER, session_id);
+            }
+            request
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}
+        });
+        let response = router
+            .clone()
+            .oneshot(post(None, initialize))
+            .await
+            .unwrap();
+        let session_id = response.headers()[MCP_SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // The user accepts the first prompt and declines the second
+        let client = router.clone();
+        let user_session = session_id.clone();
+        let user = tokio::spawn(async move {
+            let mut actions = ["accept", "decline"].into_iter().peekable();
+            while let Some(action) = actions.peek().copied() {
+                let sent = stream.recv().await.unwrap();
+                if sent.message["method"] != "elicitation/create" {
+                    continue;
+                }
+                actions.next();
+                assert!(sent.message["params"]["message"]
+                    .as_str()
+                    .unwrap()
+                    .contains("AUTH-02"));
+                let answer = json!({
+                    "jsonrpc": "2.0",
+                    "id": sent.message["id"],
+                    "result": {"action": action, "content": {"confirm": true}}
+                });
+                let response = client
+                    .clone()
+                    .oneshot(post(Some(&user_session), answer))
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::ACCEPTED);
+            }
+        });
+
+        let create = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "create_task",
+            "params": {
+                "code": "AUTH-02",
+                "name": "Fix login crash",
+                "description": "The app crashes after the OAuth redirect",
+                "owner_agent_name": "backend-dev"
+            }
+        });
+        let created = body(
+            router
+                .clone()
+                .oneshot(post(Some(&session_id), create.clone()))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(created["result"]["code"], "AUTH-02");
+
+        let declined = body(
+            router
+                .oneshot(post(Some(&session_id), create))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(declined["result"]["status"], "confirmation_required");
+        assert_eq!(declined["result"]["user_response"], "decline");
+        user.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_prompt_ends_before_request_timeout() {
+        use tower::ServiceExt;
+
+        let mut repository = MockTestRepository::new();
+        repository.expect_list().returning(|_| {
+            Ok(vec![Task::new(
+                1,
+                "AUTH-01".to_string(),
+                "Fix login crash".to_string(),
+                "App crashes after OAuth redirect".to_string(),
+                None,
+                TaskState::Created,
+                chrono::Utc::now(),
+                None,
+            )])
+        });
+        let server = McpServer::new(
+            Arc::new(repository),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_duplicate_check_policy(DuplicateCheckPolicy {
+            mode: DuplicateCheckMode::Confirm,
+            ..DuplicateCheckPolicy::default()
+        })
+        .with_http_config(HttpConfig {
+            request_timeout_secs: 1,
+            ..HttpConfig::default()
+        });
+        let client_requests = server.client_requests.clone();
+        let mut stream = server.notifications().subscribe();
+        let router = server.create_router();
+        let post = |session_id: Option<&str>, body: Value| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID_HEADER, session_id);
+            }
+            request
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}
+        });
+        let response = router
+            .clone()
+            .oneshot(post(None, initialize))
+            .await
+            .unwrap();
+        let session_id = response.headers()[MCP_SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // The user sees the prompt and never answers it
+        let prompted = tokio::spawn(async move {
+            loop {
+                let sent = stream.recv().await.unwrap();
+                if sent.message["method"] == "elicitation/create" {
+                    return;
+                }
+            }
+        });
+        let create = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "create_task",
+            "params": {
+                "code": "AUTH-02",
+                "name": "Fix login crash",
+                "description": "The app crashes after the OAuth redirect",
+                "owner_agent_name": "backend-dev"
+            }
+        });
+        let response = router
+            .oneshot(post(Some(&session_id), create))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let result: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(result["result"]["status"], "confirmation_required");
+        prompted.await.unwrap();
+        assert_eq!(client_requests.pending_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tools_work_in_root_listed_by_client() {
+        use tower::ServiceExt;
+
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("lib.rs"), "// TODO: handle retries\n").unwrap();
+        let mut repository = MockTestRepository::new();
+        repository.expect_get_by_code().returning(|_| Ok(None));
+        let server = McpServer::new(
+            Arc::new(repository),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        );
+        let mut stream = server.notifications().subscribe();
+        let router = server.create_router();
+        let post = |session_id: &str, body: Value| {
+            axum::http::Request::post("/mcp")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(MCP_SESSION_ID_HEADER, session_id)
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"protocolVersion": "2025-06-18", "capabilities": {"roots": {}}}
+        });
+        let request = axum::http::Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(initialize.to_string()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let session_id = response.headers()[MCP_SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        let response = router
+            .clone()
+            .oneshot(post(&session_id, initialized))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let sent = stream.recv().await.unwrap();
+        assert_eq!(sent.message["method"], "roots/list");
+        let uri = format!("file://{}", project.path().display());
+        let answer = json!({
+            "jsonrpc": "2.0",
+            "id": sent.message["id"],
+            "result": {"roots": [{"uri": uri, "name": "project"}]}
+        });
+        let response = router
+            .clone()
+            .oneshot(post(&session_id, answer))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        // The server has no project root of its own, so the scan runs in the
+        // client's
+        let scan = json!({"jsonrpc": "2.0", "id": 2, "method": "scan_code_todos", "params": {}});
+        let mut result = Value::Null;
+        for _ in 0..50 {
+            let response = router
+                .clone()
+                .oneshot(post(&session_id, scan.clone()))
+                .await
+                .unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            result = serde_json::from_slice::<Value>(&bytes).unwrap();
+            if result.get("error").is_none() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(result["result"]["proposed"][0]["path"], "lib.rs");
+    }
+
+    #[tokio::test]
+    async fn test_admin_tools_require_admin_key() {
+        use tower::ServiceExt;
+
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_admin_api_key(Some("admin-key".into()))
+        .create_router();
+
+        let rpc = |authorization: Option<&str>, method: &str| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+            request
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": {"timeout_minutes": 60}})
+                        .to_string(),
+                ))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+        let tool_names = |body: &Value| -> Vec<String> {
+            body["result"]["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let denied = body(
+            router
+                .clone()
+                .oneshot(rpc(Some("Bearer wrong"), "cleanup_timed_out_tasks"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(denied["error"]["code"], -32009);
+
+        let public = body(
+            router
+                .clone()
+                .oneshot(rpc(None, "tools/list"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert!(!tool_names(&public).contains(&"cleanup_timed_out_tasks".to_string()));
+
+        let admin = body(
+            router
+                .oneshot(rpc(Some("Bearer admin-key"), "tools/list"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert!(tool_names(&admin).contains(&"cleanup_timed_out_tasks".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_server_rejects_mutations() {
+        use tower::ServiceExt;
+
+        // The mock has no expectations, so any repository write would panic
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_read_only(true)
+        .create_router();
+
+        let rpc = |method: &str, params: Value| {
+            axum::http::Request::post("/mcp")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+                        .to_string(),
+                ))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+
+        let params = json!({"code": "RO-01", "name": "Blocked", "description": "Read-only"});
+        let rejected = body(
+            router
+                .clone()
+                .oneshot(rpc("create_task", params))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(rejected["error"]["code"], -32009);
+        assert!(rejected["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("read-only"));
+
+        let tools = body(router.oneshot(rpc("tools/list", json!({}))).await.unwrap()).await;
+        let names: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"list_tasks"));
+        assert!(!names.contains(&"create_task"));
+    }
+
+    #[tokio::test]
+    async fn test_listener_requirements() {
+        use tower::ServiceExt;
+
+        let router = McpServer::new(
+            Arc::new(MockTestRepository::new()),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .create_router();
+        let tailnet = ListenerConfig {
+            address: "100.64.0.7:3000".to_string(),
+            access_token: Some("tailnet-secret".to_string()),
+            require_identity: true,
+            read_only: true,
+        };
+        let router = guard_router(router, tailnet, AdminAuth::default());
+
+        let rpc = |method: &str, token: Option<&str>| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": {}});
+            request
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        let response = router
+            .clone()
+            .oneshot(rpc("list_tasks", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let health = axum::http::Request::get("/health")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(health).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router
+            .oneshot(rpc("create_task", Some("tailnet-secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let rejected: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(rejected["error"]["code"], -32009);
+    }
+
+    #[tokio::test]
+    async fn test_only_owner_may_change_claimed_task() {
+        use tower::ServiceExt;
+
+        let claimed = |id| {
+            Task::new(
+                id,
+                "TEST-001".to_string(),
+                "Claimed task".to_string(),
+                "Owned by backend-dev".to_string(),
+                Some("backend-dev".to_string()),
+                TaskState::InProgress,
+                chrono::Utc::now(),
+                None,
+            )
+        };
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_get_by_id()
+            .returning(move |id| Ok(Some(claimed(id))));
+        mock_repo
+            .expect_get_by_code()
+            .returning(move |_| Ok(Some(claimed(1))));
+        mock_repo
+            .expect_set_state()
+            .times(2)
+            .returning(|id, state| {
+                Ok(Task::new(
+                    id,
+                    "TEST-001".to_string(),
+                    "Claimed task".to_string(),
+                    "Owned by backend-dev".to_string(),
+                    Some("backend-dev".to_string()),
+                    state,
+                    chrono::Utc::now(),
+                    None,
+                ))
+            });
+        let router = McpServer::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_agent_auth(AgentAuth {
+            coordinators: vec!["project-lead".into()],
+            ..AgentAuth::default()
+        })
+        .with_message_effects(vec![MessageEffectRule {
+            message_type: "completion".to_string(),
+            effect: MessageEffect::SetState {
+                state: TaskState::Review,
+            },
+        }])
+        .create_router();
+
+        let call = |agent: Option<&str>, method: &str, params: Value| {
+            let mut request =
+                axum::http::Request::post("/mcp").header(header::CONTENT_TYPE, "application/json");
+            if let Some(agent) = agent {
+                request = request.header(crate::agent_identity::AGENT_NAME_HEADER, agent);
+            }
+            request
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params})
+                        .to_string(),
+                ))
+                .unwrap()
+        };
+        let set_state = |agent: &str| {
+            call(
+                Some(agent),
+                "set_task_state",
+                json!({"id": 1, "state": "Review"}),
+            )
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+
+        let denied = body(
+            router
+                .clone()
+                .oneshot(set_state("frontend-dev"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(denied["error"]["code"], -32009);
+
+        // Leaving out the agent name does not get around the owner
+        let anonymous = call(None, "set_task_state", json!({"id": 1, "state": "Review"}));
+        let denied = body(router.clone().oneshot(anonymous).await.unwrap()).await;
+        assert_eq!(denied["error"]["code"], -32009);
+
+        // Neither do changes addressed by task code
+        let artifact = json!({"task_code": "TEST-001", "path": "notes.md", "kind": "doc", "agent_name": "frontend-dev"});
+        let artifact = call(Some("frontend-dev"), "register_artifact", artifact);
+        let denied = body(router.clone().oneshot(artifact).await.unwrap()).await;
+        assert_eq!(denied["error"]["code"], -32009);
+        let completion = json!({
+            "task_code": "TEST-001",
+            "author_agent_name": "frontend-dev",
+            "message_type": "completion",
+            "content": "Done"
+        });
+        let completion = call(Some("frontend-dev"), "create_task_message", completion);
+        let denied = body(router.clone().oneshot(completion).await.unwrap()).await;
+        assert_eq!(denied["error"]["code"], -32009);
+
+        let owner = body(
+            router
+                .clone()
+                .oneshot(set_state("backend-dev"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(owner["result"]["state"], "Review");
+
+        let coordinator = body(router.oneshot(set_state("project-lead")).await.unwrap()).await;
+        assert_eq!(coordinator["result"]["state"], "Review");
+    }
+
+    #[tokio::test]
+    async fn test_runaway_agent_is_throttled() {
+        use tower::ServiceExt;
+
+        let mut mock_repo = MockTestRepository::new();
+        mock_repo
+            .expect_list()
+            .times(3)
+            .returning(|_| Ok(Vec::new()));
+        let server = McpServer::new(
+            Arc::new(mock_repo),
+            Arc::new(SimpleTestMessageRepository),
+            Arc::new(SimpleTestWorkspaceContextRepository),
+            None,
+        )
+        .with_runaway_policy(RunawayPolicy {
+            enabled: true,
+            max_identical_calls_per_minute: 2,
+            ..RunawayPolicy::default()
+        });
+        let mut stream = server.notifications().subscribe();
+        let router = server.create_router();
+        let list_tasks = |agent: &str| {
+            axum::http::Request::post("/mcp")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(crate::agent_identity::AGENT_NAME_HEADER, agent)
+                .body(axum::body::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": "list_tasks", "params": {"state": "Created"}})
+                        .to_string(),
+                ))
+                .unwrap()
+        };
+        let body = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&bytes).unwrap()
+        };
+
+        // The third identical call still runs, then the agent is throttled
+        for _ in 0..3 {
+            let response = body(router.clone().oneshot(list_tasks("poller")).await.unwrap()).await;
+            assert!(response["result"].is_array());
+        }
+        let alert = stream.recv().await.unwrap();
+        assert_eq!(alert.message["params"]["level"], "warning");
+        assert_eq!(
+            alert.message["params"]["data"]["pattern"]["kind"],
+            "repeated_call"
+        );
+        assert_eq!(
+            alert.message["params"]["data"]["pattern"]["agent_name"],
+            "poller"
+        );
+
+        let throttled = body(router.oneshot(list_tasks("poller")).await.unwrap()).await;
+        assert_eq!(throttled["error"]["code"], -32011);
+        assert!(
+            throttled["error"]["data"]["retry_after_secs"]
+                .as_i64()
+                .unwrap()
+                > 0
+        );
+    }
 }
@@ -0,0 +1,217 @@
+//! MCP session management for the Streamable HTTP transport
+//!
+//! Implements `Mcp-Session-Id` issuance on `initialize` and server-side session
+//! state as described by the MCP 2025-06-18 specification. Sessions expire after
+//! a period of inactivity and are purged lazily on access.
+
+use crate::client_requests::ClientRequests;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Header carrying the session identifier in both directions
+pub const MCP_SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Longest idle time a session may be kept, one year; longer TTLs are clamped
+pub const MAX_SESSION_TTL_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Server-side state of one MCP client session
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct McpSession {
+    /// Opaque, globally unique session identifier
+    pub id: String,
+    /// Protocol version negotiated during `initialize`
+    pub protocol_version: String,
+    /// `clientInfo` sent by the client during `initialize`
+    pub client_info: Option<Value>,
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+    /// Last request seen on this session
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether the client presented the admin API key on `initialize`
+    pub is_admin: bool,
+    /// Agent named on `initialize`; every request on the session acts for it
+    pub agent_name: Option<String>,
+    /// Project root listed by the client's `roots`, once it has answered
+    pub project_root: Option<String>,
+}
+
+/// In-memory session registry shared by all request handlers
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    sessions: Arc<Mutex<HashMap<String, McpSession>>>,
+    ttl: Duration,
+    /// Told about sessions that expire, so it stops sending them requests
+    client_requests: Option<ClientRequests>,
+}
+
+impl SessionStore {
+    /// Create a store whose sessions expire after `ttl_secs` seconds of inactivity,
+    /// at most [`MAX_SESSION_TTL_SECS`]
+    pub fn new(ttl_secs: u64) -> Self {
+        let ttl_secs = ttl_secs.min(MAX_SESSION_TTL_SECS) as i64;
+        Self {
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            ttl: Duration::try_seconds(ttl_secs).unwrap_or(Duration::MAX),
+            client_requests: None,
+        }
+    }
+
+    /// Close expired sessions in `client_requests` as well
+    pub fn with_client_requests(mut self, client_requests: ClientRequests) -> Self {
+        self.client_requests = Some(client_requests);
+        self
+    }
+
+    /// Issue a new session for a client that completed `initialize`
+    pub fn create(
+        &self,
+        protocol_version: &str,
+        client_info: Option<Value>,
+        is_admin: bool,
+        agent_name: Option<String>,
+    ) -> McpSession {
+        let now = Utc::now();
+        let session = McpSession {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            protocol_version: protocol_version.to_string(),
+            client_info,
+            created_at: now,
+            last_seen_at: now,
+            is_admin,
+            agent_name,
+            project_root: None,
+        };
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let expired: Vec<String> = sessions
+            .values()
+            .filter(|s| self.is_expired(s, now))
+            .map(|s| s.id.clone())
+            .collect();
+        for id in &expired {
+            sessions.remove(id);
+        }
+        sessions.insert(session.id.clone(), session.clone());
+        drop(sessions);
+        for id in &expired {
+            self.expired(id);
+        }
+        session
+    }
+
+    /// Look up a live session and refresh its activity timestamp
+    ///
+    /// Returns `None` for unknown sessions and for sessions that have expired,
+    /// which are removed in the same step.
+    pub fn touch(&self, session_id: &str) -> Option<McpSession> {
+        let now = Utc::now();
+        let mut sessions = self.sessions.lock().unwrap();
+
+        match sessions.get_mut(session_id) {
+            Some(session) if !self.is_expired(session, now) => {
+                session.last_seen_at = now;
+                Some(session.clone())
+            }
+            Some(_) => {
+                sessions.remove(session_id);
+                drop(sessions);
+                self.expired(session_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Record the project root of a session; returns `false` if it did not exist
+    pub fn set_project_root(&self, session_id: &str, project_root: Option<String>) -> bool {
+        match self.sessions.lock().unwrap().get_mut(session_id) {
+            Some(session) => {
+                session.project_root = project_root;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Terminate a session; returns `false` if it did not exist
+    pub fn remove(&self, session_id: &str) -> bool {
+        self.sessions.lock().unwrap().remove(session_id).is_some()
+    }
+
+    /// Number of sessions currently held, including not yet purged expired ones
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// Whether no sessions are held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn is_expired(&self, session: &McpSession, now: DateTime<Utc>) -> bool {
+        now - session.last_seen_at > self.ttl
+    }
+
+    fn expired(&self, session_id: &str) {
+        if let Some(client_requests) = &self.client_requests {
+            client_requests.session_closed(session_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_session_lifecycle() {
+        let store = SessionStore::new(60);
+        let session = store.create(
+            "2025-06-18",
+            Some(json!({"name": "test-client"})),
+            false,
+            None,
+        );
+
+        let found = store.touch(&session.id).unwrap();
+        assert_eq!(found.protocol_version, "2025-06-18");
+        assert_eq!(found.client_info, Some(json!({"name": "test-client"})));
+
+        assert!(store.remove(&session.id));
+        assert!(store.touch(&session.id).is_none());
+        assert!(!store.remove(&session.id));
+    }
+
+    #[test]
+    fn test_expired_session_is_rejected() {
+        let requests = ClientRequests::new(crate::notifications::NotificationHub::default());
+        let store = SessionStore::new(0).with_client_requests(requests.clone());
+        let session = store.create("2025-06-18", None, false, None);
+        requests.session_opened(&session.id, Some(&serde_json::json!({"sampling": {}})));
+        store
+            .sessions
+            .lock()
+            .unwrap()
+            .get_mut(&session.id)
+            .unwrap()
+            .last_seen_at = Utc::now() - Duration::seconds(5);
+
+        assert!(store.touch(&session.id).is_none());
+        assert!(store.is_empty());
+        assert!(!requests.supports(&session.id, "sampling"));
+    }
+
+    #[test]
+    fn test_session_ttl_is_clamped() {
+        let store = SessionStore::new(u64::MAX);
+        assert_eq!(store.ttl, Duration::seconds(MAX_SESSION_TTL_SECS as i64));
+        let session = store.create("2025-06-18", None, false, None);
+        assert!(store.touch(&session.id).is_some());
+    }
+}
@@ -12,8 +12,9 @@ use std::sync::Arc;
 use task_core::error::{Result, TaskError};
 use task_core::workspace_setup::WorkspaceContext;
 use task_core::{
-    NewTask, ProtocolHandler, RepositoryStats, Task, TaskFilter, TaskMessage,
-    TaskMessageRepository, TaskRepository, TaskState, WorkspaceContextRepository,
+    NewTask, ProtocolHandler, RepositoryStats, Task, TaskCrudRepository, TaskExtensionRepository,
+    TaskFilter, TaskMessage, TaskMessageRepository, TaskState, WorkClaimRepository,
+    WorkSessionRepository, WorkspaceContextRepository,
 };
 
 /// Mock repository for testing audit fixes
@@ -30,12 +31,13 @@ impl AuditTestMockRepository {
 }
 
 #[async_trait]
-impl TaskRepository for AuditTestMockRepository {
+impl TaskCrudRepository for AuditTestMockRepository {
     async fn create(&self, task: NewTask) -> Result<Task> {
         let mut tasks = self.tasks.lock().await;
         let id = tasks.len() as i32 + 1;
         let new_task = Task {
             id,
+            uid: None,
             code: task.code,
             name: task.name,
             description: task.description,
@@ -44,6 +46,9 @@ impl TaskRepository for AuditTestMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -162,7 +167,10 @@ impl TaskRepository for AuditTestMockRepository {
     async fn get_stats(&self) -> Result<RepositoryStats> {
         Ok(RepositoryStats::default())
     }
+}
 
+#[async_trait]
+impl WorkClaimRepository for AuditTestMockRepository {
     async fn discover_work(
         &self,
         _agent_name: &str,
@@ -197,6 +205,13 @@ impl TaskRepository for AuditTestMockRepository {
         Ok(task.clone())
     }
 
+    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl WorkSessionRepository for AuditTestMockRepository {
     async fn start_work_session(&self, task_id: i32, _agent_name: &str) -> Result<i32> {
         Ok(task_id * 100) // Mock session ID
     }
@@ -209,12 +224,11 @@ impl TaskRepository for AuditTestMockRepository {
     ) -> Result<()> {
         Ok(())
     }
-
-    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
-        Ok(vec![])
-    }
 }
 
+#[async_trait]
+impl TaskExtensionRepository for AuditTestMockRepository {}
+
 #[async_trait]
 impl TaskMessageRepository for AuditTestMockRepository {
     async fn create_message(
@@ -225,9 +239,11 @@ impl TaskMessageRepository for AuditTestMockRepository {
         message_type: &str,
         content: &str,
         reply_to_message_id: Option<i32>,
+        structured_data: Option<&serde_json::Value>,
     ) -> Result<TaskMessage> {
         Ok(TaskMessage {
             id: 1,
+            uid: None,
             task_code: task_code.to_string(),
             author_agent_name: author_agent_name.to_string(),
             target_agent_name: target_agent_name.map(|s| s.to_string()),
@@ -235,6 +251,7 @@ impl TaskMessageRepository for AuditTestMockRepository {
             created_at: Utc::now(),
             content: content.to_string(),
             reply_to_message_id,
+            structured_data: structured_data.cloned(),
         })
     }
 
@@ -9,8 +9,9 @@ use std::sync::Arc;
 use task_core::error::{Result, TaskError};
 use task_core::workspace_setup::WorkspaceContext;
 use task_core::{
-    NewTask, RepositoryStats, Task, TaskFilter, TaskMessage, TaskMessageRepository, TaskRepository,
-    TaskState, UpdateTask, WorkspaceContextRepository,
+    NewTask, RepositoryStats, SetupWizardParams, Task, TaskCrudRepository, TaskExtensionRepository,
+    TaskFilter, TaskMessage, TaskMessageRepository, TaskState, UpdateTask, WizardStep,
+    WorkClaimRepository, WorkSessionRepository, WorkspaceContextRepository,
 };
 
 /// Mock repository for testing
@@ -37,7 +38,7 @@ impl MockRepository {
 }
 
 #[async_trait]
-impl TaskRepository for MockRepository {
+impl TaskCrudRepository for MockRepository {
     async fn create(&self, task: NewTask) -> Result<Task> {
         let mut tasks = self.tasks.lock().await;
 
@@ -48,6 +49,7 @@ impl TaskRepository for MockRepository {
 
         let new_task = Task {
             id: self.get_next_id().await,
+            uid: None,
             code: task.code,
             name: task.name,
             description: task.description,
@@ -56,6 +58,9 @@ impl TaskRepository for MockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -218,7 +223,10 @@ impl TaskRepository for MockRepository {
 
         Ok(stats)
     }
+}
 
+#[async_trait]
+impl WorkClaimRepository for MockRepository {
     async fn discover_work(
         &self,
         _agent_name: &str,
@@ -254,6 +262,13 @@ impl TaskRepository for MockRepository {
         Ok(task.clone())
     }
 
+    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl WorkSessionRepository for MockRepository {
     async fn start_work_session(&self, task_id: i32, _agent_name: &str) -> Result<i32> {
         Ok(task_id * 100) // Mock session ID
     }
@@ -266,12 +281,11 @@ impl TaskRepository for MockRepository {
     ) -> Result<()> {
         Ok(())
     }
-
-    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
-        Ok(vec![])
-    }
 }
 
+#[async_trait]
+impl TaskExtensionRepository for MockRepository {}
+
 /// Mock workspace context repository for testing
 #[derive(Clone)]
 struct MockWorkspaceContextRepository;
@@ -299,6 +313,38 @@ impl WorkspaceContextRepository for MockWorkspaceContextRepository {
     }
 }
 
+/// Workspace context repository keeping the context in memory
+#[derive(Default)]
+struct StoredWorkspaceContextRepository {
+    context: tokio::sync::Mutex<Option<WorkspaceContext>>,
+}
+
+#[async_trait]
+impl WorkspaceContextRepository for StoredWorkspaceContextRepository {
+    async fn create(&self, context: WorkspaceContext) -> Result<WorkspaceContext> {
+        *self.context.lock().await = Some(context.clone());
+        Ok(context)
+    }
+
+    async fn get_by_id(&self, _workspace_id: &str) -> Result<Option<WorkspaceContext>> {
+        Ok(self.context.lock().await.clone())
+    }
+
+    async fn update(&self, context: WorkspaceContext) -> Result<WorkspaceContext> {
+        *self.context.lock().await = Some(context.clone());
+        Ok(context)
+    }
+
+    async fn delete(&self, _workspace_id: &str) -> Result<()> {
+        *self.context.lock().await = None;
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl TaskMessageRepository for MockRepository {
     async fn create_message(
@@ -309,9 +355,11 @@ impl TaskMessageRepository for MockRepository {
         message_type: &str,
         content: &str,
         reply_to_message_id: Option<i32>,
+        structured_data: Option<&serde_json::Value>,
     ) -> Result<TaskMessage> {
         Ok(TaskMessage {
             id: 1,
+            uid: None,
             task_code: task_code.to_string(),
             author_agent_name: author_agent_name.to_string(),
             target_agent_name: target_agent_name.map(|s| s.to_string()),
@@ -319,6 +367,7 @@ impl TaskMessageRepository for MockRepository {
             created_at: Utc::now(),
             content: content.to_string(),
             reply_to_message_id,
+            structured_data: structured_data.cloned(),
         })
     }
 
@@ -367,6 +416,36 @@ async fn test_create_task_integration() {
     assert_eq!(task.state, TaskState::Created);
 }
 
+#[tokio::test]
+async fn test_create_task_reports_every_invalid_field() {
+    let repository = Arc::new(MockRepository::new());
+    let workspace_repo = Arc::new(MockWorkspaceContextRepository);
+    let handler = McpTaskHandler::new(repository.clone(), repository, workspace_repo, None);
+
+    let params = CreateTaskParams {
+        code: "9-LIVES".to_string(),
+        name: "Test Task".to_string(),
+        description: "  ".to_string(),
+        owner_agent_name: Some("test-agent".to_string()),
+        confidence_threshold: 0.8,
+        estimated_effort: Some(0),
+        parent_task_id: None,
+        required_capabilities: vec![],
+        priority_score: 5.0,
+        workflow_definition_id: None,
+    };
+
+    match handler.create_task(params).await {
+        Err(TaskError::Validation(message)) => {
+            assert!(message.contains("code: "));
+            assert!(message.contains("description: "));
+            assert!(message.contains("estimated_effort: "));
+            assert!(!message.contains("name: "));
+        }
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
 #[tokio::test]
 async fn test_task_lifecycle_integration() {
     let repository = Arc::new(MockRepository::new());
@@ -649,3 +728,92 @@ async fn test_assign_task_integration() {
     let assigned_task = handler.assign_task(assign_params).await.unwrap();
     assert_eq!(assigned_task.owner_agent_name.as_deref(), Some("new-agent"));
 }
+
+#[tokio::test]
+async fn test_setup_wizard_resumes() {
+    let repository = Arc::new(MockRepository::new());
+    let workspace_repo = Arc::new(StoredWorkspaceContextRepository::default());
+    let handler = McpTaskHandler::new(repository.clone(), repository, workspace_repo, None);
+
+    let state = handler
+        .setup_wizard(SetupWizardParams::default())
+        .await
+        .unwrap();
+    assert_eq!(state.step, WizardStep::Prd);
+    assert_eq!(state.expects.as_deref(), Some("prd_content"));
+
+    let prd = "# Shop\n\n## Overview\nAn online shop with a React frontend and a Node.js \
+               backend.\n\n## Objectives\n- Sell products\n\n## Technical Requirements\n\
+               - React frontend\n- Node.js backend\n\n## User Stories\n- User can pay\n";
+    let state = handler
+        .setup_wizard(SetupWizardParams {
+            prd_content: Some(prd.to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(state.step, WizardStep::Agents);
+
+    // An interrupted setup resumes at the step it stopped at
+    let resumed = handler
+        .setup_wizard(SetupWizardParams::default())
+        .await
+        .unwrap();
+    assert_eq!(resumed.step, WizardStep::Agents);
+    assert_eq!(resumed.completed_steps, [WizardStep::Prd]);
+    let agent = resumed.data["suggested_agents"][0]["name"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let wrong_step = handler
+        .setup_wizard(SetupWizardParams {
+            confirm: true,
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(wrong_step, Err(TaskError::Validation(_))));
+
+    let state = handler
+        .setup_wizard(SetupWizardParams {
+            agents: Some(vec![agent.clone()]),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(state.step, WizardStep::MainFile);
+    assert!(state.files[0].path.starts_with(".claude/agents/"));
+    assert!(state.files[0].content.starts_with("---\nname: "));
+
+    let state = handler
+        .setup_wizard(SetupWizardParams {
+            main_file_content: Some("# Shop\n\nTeam conventions.\n".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(state.step, WizardStep::Manifest);
+    assert_eq!(state.files[0].path, "CLAUDE.md");
+    assert!(state.files[0].content.contains(&format!("`{agent}`")));
+
+    let state = handler
+        .setup_wizard(SetupWizardParams {
+            confirm: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(state.step, WizardStep::Complete);
+    assert_eq!(state.expects, None);
+    assert_eq!(state.files[0].path, ".axon/manifest.json");
+    assert_eq!(state.data["agents"][0], agent.as_str());
+
+    let restarted = handler
+        .setup_wizard(SetupWizardParams {
+            restart: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(restarted.step, WizardStep::Prd);
+}
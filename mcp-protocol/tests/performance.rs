@@ -10,8 +10,9 @@ use std::time::Instant;
 use task_core::error::Result;
 use task_core::workspace_setup::WorkspaceContext;
 use task_core::{
-    NewTask, RepositoryStats, Task, TaskFilter, TaskMessage, TaskMessageRepository, TaskRepository,
-    TaskState, UpdateTask, WorkspaceContextRepository,
+    NewTask, RepositoryStats, Task, TaskCrudRepository, TaskExtensionRepository, TaskFilter,
+    TaskMessage, TaskMessageRepository, TaskState, UpdateTask, WorkClaimRepository,
+    WorkSessionRepository, WorkspaceContextRepository,
 };
 
 /// Fast mock repository for performance testing
@@ -19,10 +20,11 @@ use task_core::{
 struct FastMockRepository;
 
 #[async_trait]
-impl TaskRepository for FastMockRepository {
+impl TaskCrudRepository for FastMockRepository {
     async fn create(&self, task: NewTask) -> Result<Task> {
         Ok(Task {
             id: 1,
+            uid: None,
             code: task.code,
             name: task.name,
             description: task.description,
@@ -31,6 +33,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: task.workflow_definition_id,
             workflow_cursor: None, // NewTask doesn't have workflow_cursor
             priority_score: task.priority_score,
@@ -45,6 +50,7 @@ impl TaskRepository for FastMockRepository {
     async fn update(&self, id: i32, updates: task_core::UpdateTask) -> Result<Task> {
         Ok(Task {
             id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: updates.name.unwrap_or_else(|| "Test Task".to_string()),
             description: updates
@@ -59,6 +65,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: updates.workflow_definition_id.flatten(),
             workflow_cursor: updates.workflow_cursor.flatten(),
             priority_score: updates.priority_score.unwrap_or(5.0),
@@ -73,6 +82,7 @@ impl TaskRepository for FastMockRepository {
     async fn set_state(&self, id: i32, state: TaskState) -> Result<Task> {
         Ok(Task {
             id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -85,6 +95,9 @@ impl TaskRepository for FastMockRepository {
                 None
             },
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -99,6 +112,7 @@ impl TaskRepository for FastMockRepository {
     async fn get_by_id(&self, id: i32) -> Result<Option<Task>> {
         Ok(Some(Task {
             id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -107,6 +121,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -121,6 +138,7 @@ impl TaskRepository for FastMockRepository {
     async fn get_by_code(&self, _code: &str) -> Result<Option<Task>> {
         Ok(Some(Task {
             id: 1,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -129,6 +147,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -144,6 +165,7 @@ impl TaskRepository for FastMockRepository {
         Ok(vec![
             Task {
                 id: 1,
+                uid: None,
                 code: "TEST-001".to_string(),
                 name: "Test Task 1".to_string(),
                 description: "Test description 1".to_string(),
@@ -151,7 +173,10 @@ impl TaskRepository for FastMockRepository {
                 state: TaskState::Created,
                 inserted_at: Utc::now(),
                 done_at: None,
-            claimed_at: None,
+                claimed_at: None,
+                lease_expires_at: None,
+                updated_at: Utc::now(),
+                state_changed_at: Utc::now(),
                 workflow_definition_id: None,
                 workflow_cursor: None,
                 priority_score: 5.0,
@@ -163,6 +188,7 @@ impl TaskRepository for FastMockRepository {
             },
             Task {
                 id: 2,
+                uid: None,
                 code: "TEST-002".to_string(),
                 name: "Test Task 2".to_string(),
                 description: "Test description 2".to_string(),
@@ -170,7 +196,10 @@ impl TaskRepository for FastMockRepository {
                 state: TaskState::InProgress,
                 inserted_at: Utc::now(),
                 done_at: None,
-            claimed_at: None,
+                claimed_at: None,
+                lease_expires_at: None,
+                updated_at: Utc::now(),
+                state_changed_at: Utc::now(),
                 workflow_definition_id: None,
                 workflow_cursor: None,
                 priority_score: 5.0,
@@ -186,6 +215,7 @@ impl TaskRepository for FastMockRepository {
     async fn assign(&self, id: i32, new_owner: &str) -> Result<Task> {
         Ok(Task {
             id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -194,6 +224,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -208,6 +241,7 @@ impl TaskRepository for FastMockRepository {
     async fn archive(&self, id: i32) -> Result<Task> {
         Ok(Task {
             id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -216,6 +250,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -234,7 +271,10 @@ impl TaskRepository for FastMockRepository {
     async fn get_stats(&self) -> Result<RepositoryStats> {
         Ok(RepositoryStats::default())
     }
+}
 
+#[async_trait]
+impl WorkClaimRepository for FastMockRepository {
     async fn discover_work(
         &self,
         _agent_name: &str,
@@ -247,6 +287,7 @@ impl TaskRepository for FastMockRepository {
     async fn claim_task(&self, task_id: i32, agent_name: &str) -> Result<Task> {
         Ok(Task {
             id: task_id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -255,6 +296,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -269,6 +313,7 @@ impl TaskRepository for FastMockRepository {
     async fn release_task(&self, task_id: i32, agent_name: &str) -> Result<Task> {
         Ok(Task {
             id: task_id,
+            uid: None,
             code: "TEST-001".to_string(),
             name: "Test Task".to_string(),
             description: "Test description".to_string(),
@@ -277,6 +322,9 @@ impl TaskRepository for FastMockRepository {
             inserted_at: Utc::now(),
             done_at: None,
             claimed_at: None,
+            lease_expires_at: None,
+            updated_at: Utc::now(),
+            state_changed_at: Utc::now(),
             workflow_definition_id: None,
             workflow_cursor: None,
             priority_score: 5.0,
@@ -288,6 +336,13 @@ impl TaskRepository for FastMockRepository {
         })
     }
 
+    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
+        Ok(vec![])
+    }
+}
+
+#[async_trait]
+impl WorkSessionRepository for FastMockRepository {
     async fn start_work_session(&self, task_id: i32, _agent_name: &str) -> Result<i32> {
         Ok(task_id * 100) // Mock session ID
     }
@@ -300,12 +355,11 @@ impl TaskRepository for FastMockRepository {
     ) -> Result<()> {
         Ok(())
     }
-
-    async fn cleanup_timed_out_tasks(&self, _timeout_minutes: i64) -> Result<Vec<Task>> {
-        Ok(vec![])
-    }
 }
 
+#[async_trait]
+impl TaskExtensionRepository for FastMockRepository {}
+
 #[async_trait]
 impl TaskMessageRepository for FastMockRepository {
     async fn create_message(
@@ -316,9 +370,11 @@ impl TaskMessageRepository for FastMockRepository {
         message_type: &str,
         content: &str,
         reply_to_message_id: Option<i32>,
+        structured_data: Option<&serde_json::Value>,
     ) -> Result<TaskMessage> {
         Ok(TaskMessage {
             id: 1,
+            uid: None,
             task_code: task_code.to_string(),
             author_agent_name: author_agent_name.to_string(),
             target_agent_name: target_agent_name.map(|s| s.to_string()),
@@ -326,6 +382,7 @@ impl TaskMessageRepository for FastMockRepository {
             created_at: Utc::now(),
             content: content.to_string(),
             reply_to_message_id,
+            structured_data: structured_data.cloned(),
         })
     }
 
@@ -510,6 +567,7 @@ async fn test_serialization_performance() {
 
     let task = Task {
         id: 1,
+        uid: None,
         code: "PERF-SERIAL".to_string(),
         name: "Serialization Performance Test".to_string(),
         description: "Testing task serialization performance".to_string(),
@@ -518,6 +576,9 @@ async fn test_serialization_performance() {
         inserted_at: Utc::now(),
         done_at: None,
         claimed_at: None,
+        lease_expires_at: None,
+        updated_at: Utc::now(),
+        state_changed_at: Utc::now(),
         workflow_definition_id: None,
         workflow_cursor: None,
         priority_score: 5.0,
@@ -85,6 +85,7 @@ fn test_task_serialization_format() {
 
     let task = task_core::Task {
         id: 42,
+        uid: None,
         code: "TEST-042".to_string(),
         name: "Test Task".to_string(),
         description: "A test task for serialization".to_string(),
@@ -93,6 +94,9 @@ fn test_task_serialization_format() {
         inserted_at: Utc::now(),
         done_at: None,
         claimed_at: None,
+        lease_expires_at: None,
+        updated_at: Utc::now(),
+        state_changed_at: Utc::now(),
         workflow_definition_id: None,
         workflow_cursor: None,
         priority_score: 5.0,
@@ -180,6 +184,8 @@ fn test_list_params_filter_conversion() {
         completed_after: None,
         completed_before: None,
         limit: Some(10),
+        include_archive: false,
+        ..Default::default()
     };
 
     let filter = params.to_task_filter().unwrap();
@@ -0,0 +1,106 @@
+//! Advertisement of a running server to local clients
+//!
+//! When started with a project root, the server writes the address it
+//! actually bound to `.axon/server.json`, so IDE clients and orchestrators
+//! can find it without a hardcoded port. The file is removed on shutdown.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// Name of the advertisement file inside the `.axon` directory
+pub const ADVERTISEMENT_FILE: &str = "server.json";
+
+/// Contents of `.axon/server.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerAdvertisement {
+    /// Streamable HTTP endpoint of the MCP server
+    pub url: String,
+    /// Bound socket address
+    pub address: String,
+    pub port: u16,
+    /// Process id of the server, to detect stale files
+    pub pid: u32,
+    pub project: Option<String>,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+}
+
+impl ServerAdvertisement {
+    pub fn new(addr: SocketAddr, project: Option<String>) -> Self {
+        Self {
+            url: format!("http://{addr}/mcp"),
+            address: addr.to_string(),
+            port: addr.port(),
+            pid: std::process::id(),
+            project,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Path of the advertisement file of `project_root`
+    pub fn path(project_root: &Path) -> PathBuf {
+        project_root.join(".axon").join(ADVERTISEMENT_FILE)
+    }
+
+    /// Write the advertisement, replacing any previous one atomically
+    pub fn write(&self, project_root: &Path) -> Result<PathBuf> {
+        let path = Self::path(project_root);
+        let temporary = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&temporary, json)
+            .with_context(|| format!("Failed to write {}", temporary.display()))?;
+        std::fs::rename(&temporary, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Read the advertisement of `project_root`, if any
+    pub fn read(project_root: &Path) -> Result<Option<Self>> {
+        let path = Self::path(project_root);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    /// Remove the advertisement of `project_root` if this process wrote it
+    pub fn remove(project_root: &Path) -> Result<()> {
+        if let Some(advertisement) = Self::read(project_root)? {
+            if advertisement.pid == std::process::id() {
+                std::fs::remove_file(Self::path(project_root))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advertisement_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join(".axon")).unwrap();
+        assert_eq!(ServerAdvertisement::read(root.path()).unwrap(), None);
+
+        let addr: SocketAddr = "127.0.0.1:49152".parse().unwrap();
+        let advertisement = ServerAdvertisement::new(addr, Some("demo".to_string()));
+        assert_eq!(advertisement.url, "http://127.0.0.1:49152/mcp");
+        let path = advertisement.write(root.path()).unwrap();
+        assert!(path.ends_with(".axon/server.json"));
+        assert_eq!(
+            ServerAdvertisement::read(root.path()).unwrap(),
+            Some(advertisement)
+        );
+
+        ServerAdvertisement::remove(root.path()).unwrap();
+        assert!(!path.exists());
+    }
+}
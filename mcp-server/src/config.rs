@@ -1,24 +1,165 @@
+use crate::digest::DigestConfig;
+use crate::discovery_script::WorkDiscoveryConfig;
+use crate::embeddings::EmbeddingsConfig;
+use crate::hooks::HooksConfig;
+use crate::integrations::IntegrationsConfig;
+use crate::summaries::ThreadSummariesConfig;
 use anyhow::{Context, Result};
 use config::{Config as ConfigBuilder, Environment, File, FileFormat};
+use mcp_protocol::{
+    AgentAuth, CorsConfig, HttpConfig, ListenerConfig, MethodTimeouts, RunawayPolicy,
+    MAX_SESSION_TTL_SECS,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::path::{Path, PathBuf};
-use sha2::{Sha256, Digest};
+use task_core::{
+    AgentNamePolicy, AnomalyThresholds, ClaimLeasePolicy, DuplicateCheckPolicy, IdStrategy, Locale,
+    MessageCatalog, MessageEffectRule, RebalancePolicy, RetentionPolicy, RetryPolicy,
+    StuckTaskPolicy, TokenBudgets, WorkSessionPolicy, MIN_CONTEXT_TOKEN_BUDGET,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
     pub project: ProjectConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub repository: RepositoryConfig,
+    #[serde(default)]
+    pub workflow: WorkflowConfig,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    #[serde(default)]
+    pub agent_names: AgentNamePolicy,
+    #[serde(default)]
+    pub claim_lease: ClaimLeasePolicy,
+    #[serde(default)]
+    pub work_sessions: WorkSessionPolicy,
+    #[serde(default)]
+    pub agent_performance: AnomalyThresholds,
+    /// When tasks in progress count as stuck, and who is nudged about them
+    #[serde(default)]
+    pub stuck_tasks: StuckTaskPolicy,
+    /// When `rebalance_work` counts agents as overloaded, and how much work it moves
+    #[serde(default)]
+    pub rebalancing: RebalancePolicy,
+    /// Language of workspace setup responses and CLI output
+    #[serde(default)]
+    pub messages: MessageCatalog,
+    #[serde(default)]
+    pub duplicate_check: DuplicateCheckPolicy,
+    #[serde(default)]
+    pub thread_summaries: ThreadSummariesConfig,
+    /// Sizes above which generated CLAUDE.md files and agent prompts are reported,
+    /// and the default budget of task context bundles
+    #[serde(default)]
+    pub token_budgets: TokenBudgets,
+    #[serde(default)]
+    pub integrations: IntegrationsConfig,
+    /// Email digest of project progress; not sent unless configured
+    #[serde(default)]
+    pub digest: Option<DigestConfig>,
+    /// Semantic search over tasks, messages and comments; disabled unless configured
+    #[serde(default)]
+    pub embeddings: Option<EmbeddingsConfig>,
+    /// Script ranking the tasks `discover_work` offers; default order unless configured
+    #[serde(default)]
+    pub work_discovery: Option<WorkDiscoveryConfig>,
+    /// Commands run when tasks are done or blocked
+    #[serde(default)]
+    pub hooks: HooksConfig,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct ProjectConfig {
     /// Project root directory for workspace operations
     pub root: Option<String>,
 }
 
+/// Workflow definition of the workspace
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct WorkflowConfig {
+    /// Task changes applied when messages of a given type are posted
+    #[serde(default)]
+    pub message_effects: Vec<MessageEffectRule>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Run periodic background jobs
+    pub enabled: bool,
+    /// Maximum random delay in seconds added to every job interval
+    pub jitter: u64,
+    /// Interval in seconds between releases of abandoned task claims (0 disables the job)
+    pub task_timeout_cleanup_interval: u64,
+    /// Minutes after which a claimed task without progress is released
+    pub task_timeout_minutes: i64,
+    /// Interval in seconds between runs of the retention policy (0 disables the job)
+    pub retention_interval: u64,
+    /// Interval in seconds between moves of archived tasks to the archive database (0 disables the job)
+    pub archive_interval: u64,
+    /// Interval in seconds between database maintenance runs (0 disables the job)
+    pub maintenance_interval: u64,
+    /// Interval in seconds between checks for agent performance anomalies (0 disables the job)
+    pub anomaly_check_interval: u64,
+    /// Interval in seconds between task history snapshots (0 disables the job)
+    pub history_snapshot_interval: u64,
+    /// Interval in seconds between checks for stuck tasks (0 disables the job)
+    pub stuck_task_check_interval: u64,
+    /// Interval in seconds between syncs of `.axon/tasks.md` (0 disables the job)
+    pub tasks_markdown_sync_interval: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            jitter: 30,
+            task_timeout_cleanup_interval: 300,
+            task_timeout_minutes: 120,
+            retention_interval: 86400,
+            archive_interval: 86400,
+            maintenance_interval: 604800,
+            anomaly_check_interval: 3600,
+            history_snapshot_interval: 86400,
+            stuck_task_check_interval: 900,
+            tasks_markdown_sync_interval: 0,
+        }
+    }
+}
+
+/// Decorators wrapped around the task repository used by the MCP tools
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[serde(default)]
+pub struct RepositoryConfig {
+    /// Log every repository call with its duration (failures as warnings)
+    pub logging: bool,
+    /// Count calls, errors and time per repository method
+    pub metrics: bool,
+    /// Interval in seconds between metrics summaries in the job list (0 disables the job)
+    pub metrics_report_interval: u64,
+    /// Retries of calls failing with a database error
+    pub retry: RetryPolicy,
+}
+
+impl Default for RepositoryConfig {
+    fn default() -> Self {
+        Self {
+            logging: false,
+            metrics: false,
+            metrics_report_interval: 300,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatabaseConfig {
     /// Optional database URL. If not provided, defaults to ~/db.sqlite
@@ -27,16 +168,126 @@ pub struct DatabaseConfig {
     pub max_connections: u32,
     /// Connection timeout in seconds
     pub connection_timeout: u64,
+    /// Open databases migrated by a newer axon-mcp instead of refusing to start
+    #[serde(default)]
+    pub allow_downgrade: bool,
+    /// Record every task change in an append-only history for get_board_at
+    #[serde(default)]
+    pub event_history: bool,
+    /// Stable identifiers assigned to new tasks and messages: integer (none), uuid or ulid
+    #[serde(default)]
+    pub id_strategy: IdStrategy,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ServerConfig {
     /// Listen address for the MCP server
     pub listen_addr: String,
-    /// Port number to listen on
+    /// Port number to listen on; 0 binds a free ephemeral port
     pub port: u16,
     /// Number of worker threads
     pub workers: usize,
+    /// Compress responses with gzip/deflate when the client accepts it
+    #[serde(default = "default_true")]
+    pub compression: bool,
+    /// Keep HTTP connections open between requests
+    #[serde(default = "default_true")]
+    pub keep_alive: bool,
+    /// Idle keep-alive timeout in seconds (0 disables the limit)
+    #[serde(default = "default_keep_alive_timeout")]
+    pub keep_alive_timeout: u64,
+    /// Request timeout in seconds (0 disables the limit)
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
+    /// CORS policy for browser-based MCP clients (disabled when no origins are listed)
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Idle time in seconds after which an MCP session expires
+    #[serde(default = "default_session_ttl")]
+    pub session_ttl: u64,
+    /// Require an Mcp-Session-Id (from initialize) on every request
+    #[serde(default)]
+    pub require_session: bool,
+    /// Execution budgets per MCP method kind, in seconds
+    #[serde(default)]
+    pub method_timeouts: MethodTimeouts,
+    /// API key unlocking admin tools (sent as `Authorization: Bearer`); admin tools are off when unset
+    #[serde(default)]
+    pub admin_api_key: Option<String>,
+    /// Agent identity: reserved agent tokens, coordinators, and whether mutations must name an agent
+    #[serde(default)]
+    pub agent_auth: AgentAuth,
+    /// When agents stuck in loops are throttled and reported to the coordinator
+    #[serde(default)]
+    pub runaway_agents: RunawayPolicy,
+    /// Serve lookups only, rejecting every tool that changes the board
+    #[serde(default)]
+    pub read_only: bool,
+    /// Running in a container: listen on all interfaces and keep the database on the data volume
+    #[serde(default)]
+    pub container: bool,
+    /// Further addresses serving the same board, each with its own access requirements
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
+    /// Write every JSON-RPC request and response to `.axon/recordings/` for replay
+    #[serde(default)]
+    pub record: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_keep_alive_timeout() -> u64 {
+    HttpConfig::default().keep_alive_timeout_secs
+}
+
+fn default_request_timeout() -> u64 {
+    HttpConfig::default().request_timeout_secs
+}
+
+fn default_session_ttl() -> u64 {
+    HttpConfig::default().session_ttl_secs
+}
+
+impl ServerConfig {
+    /// HTTP transport settings for the MCP server
+    pub fn http_config(&self) -> HttpConfig {
+        HttpConfig {
+            compression: self.compression,
+            keep_alive: self.keep_alive,
+            keep_alive_timeout_secs: self.keep_alive_timeout,
+            request_timeout_secs: self.request_timeout,
+            cors: self.cors.clone(),
+            session_ttl_secs: self.session_ttl,
+            require_session: self.require_session,
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "127.0.0.1".to_string(),
+            port: 3000,
+            workers: 4,
+            compression: default_true(),
+            keep_alive: default_true(),
+            keep_alive_timeout: default_keep_alive_timeout(),
+            request_timeout: default_request_timeout(),
+            cors: CorsConfig::default(),
+            session_ttl: default_session_ttl(),
+            require_session: false,
+            method_timeouts: MethodTimeouts::default(),
+            admin_api_key: None,
+            agent_auth: AgentAuth::default(),
+            runaway_agents: RunawayPolicy::default(),
+            read_only: false,
+            container: false,
+            listeners: Vec::new(),
+            record: false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -55,6 +306,29 @@ pub enum LogFormat {
     Compact,
 }
 
+/// Volume the database is kept on in container mode unless DATABASE_URL is set
+pub const CONTAINER_DATA_DIR: &str = "/data";
+
+/// Sections a project's `.axon/config.toml` may set; server, database, logging
+/// and job settings stay with the machine-wide configuration
+pub const PROJECT_CONFIG_SECTIONS: &[&str] = &[
+    "workflow",
+    "claim_lease",
+    "work_sessions",
+    "agent_names",
+    "duplicate_check",
+    "agent_performance",
+    "stuck_tasks",
+    "rebalancing",
+    "retention",
+    "thread_summaries",
+    "token_budgets",
+    "integrations",
+    "digest",
+    "work_discovery",
+    "messages",
+];
+
 impl Config {
     /// Load configuration from environment variables and config files
     pub fn from_env() -> Result<Self> {
@@ -114,7 +388,9 @@ impl Config {
             .context("Failed to deserialize configuration from file")
     }
 
-    /// Apply standard environment variables (DATABASE_URL, LISTEN_ADDR, LOG_LEVEL, PROJECT_ROOT)
+    /// Apply standard environment variables (DATABASE_URL, LISTEN_ADDR, LOG_LEVEL, PROJECT_ROOT,
+    /// AXON_ADMIN_API_KEY, AXON_GITHUB_TOKEN, AXON_JIRA_TOKEN, AXON_LINEAR_API_KEY,
+    /// AXON_SMTP_PASSWORD, AXON_EMBEDDINGS_API_KEY, AXON_SUMMARIES_API_KEY, AXON_LOCALE)
     /// This provides compatibility with common deployment patterns
     fn apply_standard_env_vars(config: &mut Config) {
         if let Ok(database_url) = env::var("DATABASE_URL") {
@@ -132,6 +408,55 @@ impl Config {
         if let Ok(project_root) = env::var("PROJECT_ROOT") {
             config.project.root = Some(project_root);
         }
+
+        if let Ok(admin_api_key) = env::var("AXON_ADMIN_API_KEY") {
+            config.server.admin_api_key = Some(admin_api_key);
+        }
+
+        if let (Ok(token), Some(github)) = (
+            env::var("AXON_GITHUB_TOKEN"),
+            config.integrations.github.as_mut(),
+        ) {
+            github.token = Some(token);
+        }
+
+        if let (Ok(token), Some(jira)) = (
+            env::var("AXON_JIRA_TOKEN"),
+            config.integrations.jira.as_mut(),
+        ) {
+            jira.api_token = Some(token);
+        }
+
+        if let (Ok(api_key), Some(linear)) = (
+            env::var("AXON_LINEAR_API_KEY"),
+            config.integrations.linear.as_mut(),
+        ) {
+            linear.api_key = Some(api_key);
+        }
+
+        if let (Ok(password), Some(digest)) =
+            (env::var("AXON_SMTP_PASSWORD"), config.digest.as_mut())
+        {
+            digest.smtp_password = Some(password);
+        }
+
+        if let (Ok(api_key), Some(embeddings)) = (
+            env::var("AXON_EMBEDDINGS_API_KEY"),
+            config.embeddings.as_mut(),
+        ) {
+            embeddings.api_key = Some(api_key);
+        }
+
+        if let Ok(api_key) = env::var("AXON_SUMMARIES_API_KEY") {
+            config.thread_summaries.api_key = Some(api_key);
+        }
+
+        if let Some(locale) = env::var("AXON_LOCALE")
+            .ok()
+            .and_then(|tag| Locale::from_tag(&tag))
+        {
+            config.messages.locale = locale;
+        }
     }
 
     /// Merge current configuration with environment variables using config crate
@@ -150,6 +475,27 @@ impl Config {
         }
     }
 
+    /// Path of the archive database kept next to a file-based main database
+    ///
+    /// `tasks.sqlite` is archived to `tasks.archive.sqlite`. In-memory
+    /// databases have no archive.
+    pub fn archive_database_path(&self) -> Option<PathBuf> {
+        let url = self.database_url();
+        let path = url.strip_prefix("sqlite://").unwrap_or(&url);
+        let path = path.split('?').next().unwrap_or(path);
+        if path.is_empty() || path.contains(":memory:") {
+            return None;
+        }
+
+        let path = Path::new(path);
+        let stem = path.file_stem()?.to_string_lossy();
+        let file_name = match path.extension() {
+            Some(ext) => format!("{stem}.archive.{}", ext.to_string_lossy()),
+            None => format!("{stem}.archive.sqlite"),
+        };
+        Some(path.with_file_name(file_name))
+    }
+
     /// Get the default database URL using dynamic path resolution
     pub fn default_database_url() -> String {
         // Use dynamic path resolution based on installation scope
@@ -175,6 +521,64 @@ impl Config {
         self.project.root.as_ref().map(PathBuf::from)
     }
 
+    /// Adjust defaults that make no sense inside a container
+    ///
+    /// A loopback listen address becomes `0.0.0.0` so published ports reach the
+    /// server, and without a database URL the database goes to
+    /// [`CONTAINER_DATA_DIR`], where a volume is expected to be mounted.
+    pub fn apply_container_defaults(&mut self) {
+        if !self.server.container {
+            return;
+        }
+        if matches!(self.server.listen_addr.as_str(), "127.0.0.1" | "localhost") {
+            self.server.listen_addr = "0.0.0.0".to_string();
+        }
+        if self.database.url.is_none() {
+            self.database.url = Some(format!("sqlite://{CONTAINER_DATA_DIR}/axon.sqlite"));
+        }
+    }
+
+    /// Per-project settings file, `PROJECT_ROOT/.axon/config.toml`
+    pub fn project_config_path(&self) -> Option<PathBuf> {
+        self.project_root()
+            .map(|root| root.join(".axon").join("config.toml"))
+    }
+
+    /// Layer the project's `.axon/config.toml` over this configuration
+    ///
+    /// Tables are merged key by key, so the project file only needs the
+    /// settings it changes; arrays such as `workflow.message_effects` are
+    /// replaced. Only [`PROJECT_CONFIG_SECTIONS`] may be set. Returns the path
+    /// of the file applied, if the project has one.
+    pub fn apply_project_config(&mut self) -> Result<Option<PathBuf>> {
+        let Some(path) = self.project_config_path().filter(|path| path.is_file()) else {
+            return Ok(None);
+        };
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let overrides: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if let Some(section) = overrides
+            .keys()
+            .find(|section| !PROJECT_CONFIG_SECTIONS.contains(&section.as_str()))
+        {
+            return Err(anyhow::anyhow!(
+                "{} may not set [{}]; project settings are limited to: {}",
+                path.display(),
+                section,
+                PROJECT_CONFIG_SECTIONS.join(", ")
+            ));
+        }
+
+        let mut merged =
+            toml::Table::try_from(&*self).context("Failed to serialize configuration")?;
+        merge_toml_tables(&mut merged, overrides);
+        *self = toml::Value::Table(merged)
+            .try_into()
+            .with_context(|| format!("Invalid project configuration in {}", path.display()))?;
+        Ok(Some(path))
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
         // Validate log level
@@ -198,14 +602,80 @@ impl Config {
         }
 
         // Validate server configuration
-        if self.server.port == 0 {
-            return Err(anyhow::anyhow!("Server port cannot be 0"));
-        }
-
         if self.server.workers == 0 {
             return Err(anyhow::anyhow!("Server workers must be greater than 0"));
         }
 
+        self.server
+            .cors
+            .validate()
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        if self.server.session_ttl > MAX_SESSION_TTL_SECS {
+            return Err(anyhow::anyhow!(
+                "server.session_ttl must not exceed {} seconds (one year). Got: {}",
+                MAX_SESSION_TTL_SECS,
+                self.server.session_ttl
+            ));
+        }
+
+        if self.claim_lease.max_minutes < self.claim_lease.default_minutes {
+            return Err(anyhow::anyhow!(
+                "claim_lease.max_minutes ({}) must not be below claim_lease.default_minutes ({})",
+                self.claim_lease.max_minutes,
+                self.claim_lease.default_minutes
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.agent_performance.score_drop_ratio) {
+            return Err(anyhow::anyhow!(
+                "agent_performance.score_drop_ratio must be between 0.0 and 1.0. Got: {}",
+                self.agent_performance.score_drop_ratio
+            ));
+        }
+
+        if self.stuck_tasks.estimate_factor <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "stuck_tasks.estimate_factor must be greater than 0. Got: {}",
+                self.stuck_tasks.estimate_factor
+            ));
+        }
+
+        self.messages.validate().map_err(|e| anyhow::anyhow!(e))?;
+
+        if !(0.0..=1.0).contains(&self.duplicate_check.threshold) {
+            return Err(anyhow::anyhow!(
+                "duplicate_check.threshold must be between 0.0 and 1.0. Got: {}",
+                self.duplicate_check.threshold
+            ));
+        }
+
+        if self.token_budgets.main_ai_file == 0 || self.token_budgets.agent_prompt == 0 {
+            return Err(anyhow::anyhow!(
+                "token_budgets.main_ai_file and token_budgets.agent_prompt must be positive"
+            ));
+        }
+        if self.token_budgets.task_context < MIN_CONTEXT_TOKEN_BUDGET {
+            return Err(anyhow::anyhow!(
+                "token_budgets.task_context must be at least {}. Got: {}",
+                MIN_CONTEXT_TOKEN_BUDGET,
+                self.token_budgets.task_context
+            ));
+        }
+
+        self.thread_summaries.validate()?;
+        self.integrations.validate()?;
+        if let Some(ref digest) = self.digest {
+            digest.validate()?;
+        }
+        if let Some(ref embeddings) = self.embeddings {
+            embeddings.validate()?;
+        }
+        if let Some(ref work_discovery) = self.work_discovery {
+            work_discovery.validate()?;
+        }
+        self.hooks.validate()?;
+
         if self.database.max_connections == 0 {
             return Err(anyhow::anyhow!(
                 "Database max_connections must be greater than 0"
@@ -240,19 +710,35 @@ impl Default for Config {
                 url: None,
                 max_connections: 5,
                 connection_timeout: 30,
+                allow_downgrade: false,
+                event_history: false,
+                id_strategy: IdStrategy::default(),
             },
-            server: ServerConfig {
-                listen_addr: "127.0.0.1".to_string(),
-                port: 3000,
-                workers: 4,
-            },
+            server: ServerConfig::default(),
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: LogFormat::Pretty,
             },
-            project: ProjectConfig {
-                root: None,
-            },
+            project: ProjectConfig { root: None },
+            jobs: JobsConfig::default(),
+            repository: RepositoryConfig::default(),
+            workflow: WorkflowConfig::default(),
+            retention: RetentionPolicy::default(),
+            agent_names: AgentNamePolicy::default(),
+            claim_lease: ClaimLeasePolicy::default(),
+            work_sessions: WorkSessionPolicy::default(),
+            agent_performance: AnomalyThresholds::default(),
+            stuck_tasks: StuckTaskPolicy::default(),
+            rebalancing: RebalancePolicy::default(),
+            messages: MessageCatalog::default(),
+            duplicate_check: DuplicateCheckPolicy::default(),
+            thread_summaries: ThreadSummariesConfig::default(),
+            token_budgets: TokenBudgets::default(),
+            integrations: IntegrationsConfig::default(),
+            digest: None,
+            embeddings: None,
+            work_discovery: None,
+            hooks: HooksConfig::default(),
         }
     }
 }
@@ -311,16 +797,16 @@ pub fn detect_installation_scope() -> InstallScope {
 /// Find the root of a software project by looking for common marker files
 fn find_project_root(start_path: &Path) -> Option<PathBuf> {
     let markers = [
-        "Cargo.toml",    // Rust
-        ".git",          // Git repository
-        "package.json",  // Node.js
+        "Cargo.toml",     // Rust
+        ".git",           // Git repository
+        "package.json",   // Node.js
         "pyproject.toml", // Python
-        "go.mod",        // Go
-        "Gemfile",       // Ruby
-        "pom.xml",       // Java/Maven
-        "build.gradle",  // Java/Gradle
-        ".project",      // Eclipse
-        "composer.json", // PHP
+        "go.mod",         // Go
+        "Gemfile",        // Ruby
+        "pom.xml",        // Java/Maven
+        "build.gradle",   // Java/Gradle
+        ".project",       // Eclipse
+        "composer.json",  // PHP
     ];
 
     let mut current = start_path;
@@ -383,7 +869,9 @@ pub fn resolve_database_path() -> Result<(PathBuf, InstallScope)> {
         }
         InstallScope::Override => {
             // This should not happen as we check for override at the beginning
-            return Err(anyhow::anyhow!("Unexpected override scope in path resolution"));
+            return Err(anyhow::anyhow!(
+                "Unexpected override scope in path resolution"
+            ));
         }
     };
 
@@ -396,6 +884,20 @@ pub fn resolve_database_url() -> Result<String> {
     Ok(format!("sqlite://{}", db_path.display()))
 }
 
+/// Merge `overrides` into `base`, descending into tables present in both
+fn merge_toml_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => {
+                merge_toml_tables(base, value)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -410,6 +912,129 @@ mod tests {
         assert_eq!(config.logging.level, "info");
     }
 
+    #[test]
+    fn test_bundled_config_files_parse() {
+        for contents in [
+            include_str!("../config/default.toml"),
+            include_str!("../config/production.toml"),
+        ] {
+            let config: Config = ConfigBuilder::builder()
+                .add_source(File::from_str(contents, FileFormat::Toml))
+                .build()
+                .unwrap()
+                .try_deserialize()
+                .unwrap();
+            assert!(config.server.compression);
+            assert_eq!(config.server.method_timeouts.read_timeout, 2);
+        }
+    }
+
+    #[test]
+    fn test_archive_database_path() {
+        let mut config = Config::default();
+        config.database.url = Some("sqlite:///data/axon/tasks.sqlite".to_string());
+        assert_eq!(
+            config.archive_database_path(),
+            Some(PathBuf::from("/data/axon/tasks.archive.sqlite"))
+        );
+
+        config.database.url = Some(":memory:".to_string());
+        assert_eq!(config.archive_database_path(), None);
+    }
+
+    #[test]
+    fn test_workflow_message_effects_parse() {
+        let config: Config = ConfigBuilder::builder()
+            .add_source(File::from_str(
+                include_str!("../config/default.toml"),
+                FileFormat::Toml,
+            ))
+            .add_source(File::from_str(
+                r#"
+                [[workflow.message_effects]]
+                message_type = "blocker"
+                action = "set_state"
+                state = "Blocked"
+
+                [[workflow.message_effects]]
+                message_type = "handoff_accepted"
+                action = "assign_to_author"
+                "#,
+                FileFormat::Toml,
+            ))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+
+        let effects = &config.workflow.message_effects;
+        assert_eq!(effects.len(), 2);
+        assert_eq!(
+            effects[0].effect,
+            task_core::MessageEffect::SetState {
+                state: task_core::TaskState::Blocked
+            }
+        );
+        assert_eq!(effects[1].effect, task_core::MessageEffect::AssignToAuthor);
+    }
+
+    #[test]
+    fn test_apply_project_config() {
+        let project = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.project.root = Some(project.path().display().to_string());
+        config.server.port = 4100;
+        assert_eq!(config.apply_project_config().unwrap(), None);
+
+        std::fs::create_dir(project.path().join(".axon")).unwrap();
+        let path = project.path().join(".axon").join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [claim_lease]
+            default_minutes = 30
+
+            [[workflow.message_effects]]
+            message_type = "blocker"
+            action = "set_state"
+            state = "Blocked"
+            "#,
+        )
+        .unwrap();
+        let max_minutes = config.claim_lease.max_minutes;
+        assert_eq!(config.apply_project_config().unwrap(), Some(path.clone()));
+        assert_eq!(config.claim_lease.default_minutes, 30);
+        assert_eq!(config.claim_lease.max_minutes, max_minutes);
+        assert_eq!(config.workflow.message_effects.len(), 1);
+        assert_eq!(config.server.port, 4100);
+
+        std::fs::write(&path, "[server]\nport = 80\n").unwrap();
+        let err = config.apply_project_config().unwrap_err();
+        assert!(err.to_string().contains("may not set [server]"));
+        assert_eq!(config.server.port, 4100);
+    }
+
+    #[test]
+    fn test_apply_container_defaults() {
+        let mut config = Config::default();
+        config.apply_container_defaults();
+        assert_eq!(config.server.listen_addr, "127.0.0.1");
+        assert_eq!(config.database.url, None);
+
+        config.server.container = true;
+        config.apply_container_defaults();
+        assert_eq!(config.server.listen_addr, "0.0.0.0");
+        assert_eq!(config.database_url(), "sqlite:///data/axon.sqlite");
+
+        let mut config = Config::default();
+        config.server.container = true;
+        config.server.listen_addr = "10.0.0.5".to_string();
+        config.database.url = Some("sqlite:///volume/tasks.sqlite".to_string());
+        config.apply_container_defaults();
+        assert_eq!(config.server.listen_addr, "10.0.0.5");
+        assert_eq!(config.database_url(), "sqlite:///volume/tasks.sqlite");
+    }
+
     #[test]
     fn test_database_url_with_default() {
         let config = Config::default();
@@ -441,6 +1066,14 @@ mod tests {
         let mut invalid_config = Config::default();
         invalid_config.logging.level = "invalid".to_string();
         assert!(invalid_config.validate().is_err());
+
+        let mut invalid_cors = Config::default();
+        invalid_cors.server.cors.allowed_origins = vec!["http://bad\norigin".to_string()];
+        assert!(invalid_cors.validate().is_err());
+
+        let mut endless_sessions = Config::default();
+        endless_sessions.server.session_ttl = u64::MAX;
+        assert!(endless_sessions.validate().is_err());
     }
 
     #[test]
@@ -469,7 +1102,7 @@ mod tests {
     #[test]
     fn test_resolve_database_path_with_override() {
         env::set_var("AXON_MCP_DB", "/custom/path/db.sqlite");
-        
+
         let (path, scope) = resolve_database_path().unwrap();
         assert_eq!(path, PathBuf::from("/custom/path/db.sqlite"));
         assert_eq!(scope, InstallScope::Override);
@@ -503,7 +1136,7 @@ mod tests {
         use tempfile::TempDir;
 
         let temp_dir = TempDir::new().unwrap();
-        
+
         // Initially no marker
         assert!(!has_axon_marker(temp_dir.path()));
 
@@ -0,0 +1,347 @@
+//! Email digest of project progress
+//!
+//! Sends the [`StatusReport`] of the last day or week to the configured
+//! recipients over SMTP. The digest job is registered only when a `[digest]`
+//! section is configured.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::{StatusReport, TaskFilter, TaskRepository, WorkSessionFilter};
+use tracing::info;
+
+use crate::jobs::JobScheduler;
+
+/// How often the digest is sent, and the period it covers
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    /// Length of the period covered by one digest
+    pub fn period(self) -> Duration {
+        match self {
+            DigestFrequency::Daily => Duration::from_secs(24 * 60 * 60),
+            DigestFrequency::Weekly => Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl fmt::Display for DigestFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigestFrequency::Daily => write!(f, "daily"),
+            DigestFrequency::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    #[default]
+    Starttls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// Unencrypted, for local relays only
+    None,
+}
+
+/// `[digest]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// `daily` or `weekly`
+    pub frequency: DigestFrequency,
+    /// Addresses the digest is sent to
+    pub recipients: Vec<String>,
+    /// Sender address, e.g. `Axon <axon@example.com>`
+    pub from: String,
+    /// Subject line; `{frequency}` and `{date}` are replaced
+    pub subject: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    /// SMTP password; `AXON_SMTP_PASSWORD` overrides it when no config file is given
+    pub smtp_password: Option<String>,
+    pub smtp_security: SmtpSecurity,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            frequency: DigestFrequency::Daily,
+            recipients: Vec::new(),
+            from: String::new(),
+            subject: "Axon {frequency} digest - {date}".to_string(),
+            smtp_host: String::new(),
+            smtp_port: 587,
+            smtp_username: None,
+            smtp_password: None,
+            smtp_security: SmtpSecurity::Starttls,
+        }
+    }
+}
+
+impl DigestConfig {
+    /// Check the addresses and SMTP server
+    pub fn validate(&self) -> Result<()> {
+        if self.recipients.is_empty() {
+            anyhow::bail!("digest.recipients must list at least one address");
+        }
+        for address in self.recipients.iter().chain(std::iter::once(&self.from)) {
+            address
+                .parse::<Mailbox>()
+                .with_context(|| format!("Invalid digest email address: '{address}'"))?;
+        }
+        if self.smtp_host.is_empty() {
+            anyhow::bail!("digest.smtp_host is required");
+        }
+        if self.smtp_username.is_some() != self.smtp_password.is_some() {
+            anyhow::bail!("digest.smtp_username and smtp_password must be set together");
+        }
+        Ok(())
+    }
+
+    /// Subject of the digest sent at `now`
+    pub fn subject(&self, now: DateTime<Utc>) -> String {
+        self.subject
+            .replace("{frequency}", &self.frequency.to_string())
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+    }
+}
+
+/// Delivers digests to their recipients
+#[async_trait]
+pub trait DigestMailer: Send + Sync {
+    async fn send(&self, subject: &str, body: &str) -> Result<()>;
+}
+
+/// [`DigestMailer`] sending plain text email over SMTP
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    recipients: Vec<Mailbox>,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &DigestConfig) -> Result<Self> {
+        config.validate()?;
+        let builder = match config.smtp_security {
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)?,
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host)
+            }
+        };
+        let mut builder = builder
+            .port(config.smtp_port)
+            .timeout(Some(Duration::from_secs(30)));
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse()?,
+            recipients: config
+                .recipients
+                .iter()
+                .map(|address| address.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[async_trait]
+impl DigestMailer for SmtpMailer {
+    async fn send(&self, subject: &str, body: &str) -> Result<()> {
+        let mut message = Message::builder().from(self.from.clone()).subject(subject);
+        for recipient in &self.recipients {
+            message = message.to(recipient.clone());
+        }
+        let message = message
+            .body(body.to_string())
+            .context("Failed to build digest email")?;
+        self.transport
+            .send(message)
+            .await
+            .context("Failed to send digest email")?;
+        Ok(())
+    }
+}
+
+/// Status report of the period of `frequency` ending at `now`
+///
+/// Work sessions started before the period are not included.
+pub async fn build_status_report<R: TaskRepository + ?Sized>(
+    repository: &R,
+    frequency: DigestFrequency,
+    now: DateTime<Utc>,
+) -> Result<StatusReport> {
+    let start = now - chrono::Duration::from_std(frequency.period())?;
+    let tasks = repository.list(TaskFilter::default()).await?;
+    let sessions = repository
+        .list_work_sessions(&WorkSessionFilter {
+            since: Some(start),
+            until: Some(now),
+            ..WorkSessionFilter::default()
+        })
+        .await?;
+    Ok(StatusReport::new(&tasks, &sessions, start, now))
+}
+
+/// Build the digest ending at `now` and send it through `mailer`
+pub async fn send_digest<R: TaskRepository + ?Sized>(
+    repository: &R,
+    mailer: &dyn DigestMailer,
+    config: &DigestConfig,
+    now: DateTime<Utc>,
+) -> Result<StatusReport> {
+    let report = build_status_report(repository, config.frequency, now).await?;
+    mailer.send(&config.subject(now), &report.to_text()).await?;
+    Ok(report)
+}
+
+/// Schedule the digest to be sent every day or week
+pub fn register_digest_job<R>(
+    scheduler: &mut JobScheduler,
+    repository: Arc<R>,
+    config: &DigestConfig,
+    jitter: Duration,
+) -> Result<()>
+where
+    R: TaskRepository + 'static,
+{
+    let mailer: Arc<dyn DigestMailer> = Arc::new(SmtpMailer::new(config)?);
+    let config = Arc::new(config.clone());
+    info!(
+        "Sending {} digest to {} recipient(s)",
+        config.frequency,
+        config.recipients.len()
+    );
+    scheduler.register(
+        "email_digest",
+        config.frequency.period(),
+        jitter,
+        move || {
+            let repository = repository.clone();
+            let mailer = mailer.clone();
+            let config = config.clone();
+            async move {
+                let report =
+                    send_digest(repository.as_ref(), mailer.as_ref(), &config, Utc::now()).await?;
+                Ok(format!(
+                    "Sent digest with {} completed and {} blocked task(s) to {} recipient(s)",
+                    report.completed.len(),
+                    report.blocked.len(),
+                    config.recipients.len()
+                ))
+            }
+        },
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::SqliteTaskRepository;
+    use std::sync::Mutex;
+    use task_core::{NewTask, TaskCrudRepository, TaskState, WorkClaimRepository};
+
+    #[derive(Default)]
+    struct FakeMailer {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl DigestMailer for FakeMailer {
+        async fn send(&self, subject: &str, body: &str) -> Result<()> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((subject.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = DigestConfig {
+            recipients: vec!["lead@example.com".to_string()],
+            from: "Axon <axon@example.com>".to_string(),
+            smtp_host: "smtp.example.com".to_string(),
+            ..DigestConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        config.recipients.push("not an address".to_string());
+        assert!(config.validate().is_err());
+        config.recipients.pop();
+        config.smtp_username = Some("axon".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_digest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let database_url = format!("sqlite://{}", dir.path().join("digest.sqlite").display());
+        let repository = SqliteTaskRepository::new(&database_url).await.unwrap();
+        repository.migrate().await.unwrap();
+
+        let mut tasks = Vec::new();
+        for code in ["API-01", "UI-01"] {
+            let task = repository
+                .create(NewTask::new(
+                    code.to_string(),
+                    format!("Build {code}"),
+                    "Work".to_string(),
+                    Some("backend-dev".to_string()),
+                ))
+                .await
+                .unwrap();
+            tasks.push(task);
+        }
+        repository
+            .claim_task(tasks[0].id, "backend-dev")
+            .await
+            .unwrap();
+        repository
+            .set_state(tasks[0].id, TaskState::Done)
+            .await
+            .unwrap();
+
+        let config = DigestConfig {
+            frequency: DigestFrequency::Weekly,
+            ..DigestConfig::default()
+        };
+        let mailer = FakeMailer::default();
+        let now = Utc::now() + chrono::Duration::seconds(1);
+        let report = send_digest(&repository, &mailer, &config, now)
+            .await
+            .unwrap();
+        assert_eq!(report.created, 2);
+        assert_eq!(report.completed[0].code, "API-01");
+
+        let sent = mailer.sent.lock().unwrap();
+        assert_eq!(
+            sent[0].0,
+            format!("Axon weekly digest - {}", now.format("%Y-%m-%d"))
+        );
+        assert!(sent[0].1.contains("Tasks: 2 created, 1 completed"));
+    }
+}
@@ -0,0 +1,279 @@
+//! Scripted work-discovery policies
+//!
+//! The `[work_discovery]` section names a Rhai script that scores the tasks
+//! `discover_work` would offer. The script defines
+//! `fn score(task, agent, workload)`: a number ranks the task, higher first,
+//! while `()` or `false` leaves it out. The script has no file or network
+//! access and is stopped when it exceeds its operation or time budget, in
+//! which case discovery falls back to the default order.
+
+use anyhow::{Context, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use task_core::{DiscoveryAgent, DiscoveryWorkload, Task, TaskError, WorkDiscoveryPolicy};
+use tracing::{debug, info};
+
+/// Function every discovery script must define
+const SCORE_FUNCTION: &str = "score";
+
+/// `[work_discovery]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct WorkDiscoveryConfig {
+    /// Rhai script, relative to the project root
+    pub script: String,
+    /// Operations one `score` call may spend
+    pub max_operations: u64,
+    /// Milliseconds one discovery may spend in the script
+    pub time_limit_ms: u64,
+}
+
+impl Default for WorkDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            script: ".axon/discovery.rhai".to_string(),
+            max_operations: 100_000,
+            time_limit_ms: 100,
+        }
+    }
+}
+
+impl WorkDiscoveryConfig {
+    /// Check the script path and the budgets
+    pub fn validate(&self) -> Result<()> {
+        if self.script.trim().is_empty() {
+            anyhow::bail!("work_discovery.script is required");
+        }
+        if self.max_operations == 0 || self.time_limit_ms == 0 {
+            anyhow::bail!("work_discovery.max_operations and time_limit_ms must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Discovery policy running a Rhai script
+pub struct ScriptedDiscoveryPolicy {
+    name: String,
+    ast: AST,
+    max_operations: u64,
+    time_limit: Duration,
+}
+
+impl ScriptedDiscoveryPolicy {
+    /// Compile `source`; `name` identifies the script in errors and logs
+    pub fn compile(name: &str, source: &str, config: &WorkDiscoveryConfig) -> Result<Self> {
+        let ast = sandboxed_engine(config.max_operations)
+            .compile(source)
+            .map_err(|e| anyhow::anyhow!("{name}: {e}"))?;
+        if !ast
+            .iter_functions()
+            .any(|function| function.name == SCORE_FUNCTION && function.params.len() == 3)
+        {
+            anyhow::bail!("{name} must define fn {SCORE_FUNCTION}(task, agent, workload)");
+        }
+        Ok(Self {
+            name: name.to_string(),
+            ast,
+            max_operations: config.max_operations,
+            time_limit: Duration::from_millis(config.time_limit_ms),
+        })
+    }
+
+    /// Score of `task`, or `None` when the script leaves it out
+    fn score(
+        &self,
+        engine: &Engine,
+        task: &Task,
+        agent: &Dynamic,
+        workload: &Dynamic,
+    ) -> std::result::Result<Option<f64>, String> {
+        let task_value = rhai::serde::to_dynamic(task).map_err(|e| e.to_string())?;
+        let result: Dynamic = engine
+            .call_fn(
+                &mut Scope::new(),
+                &self.ast,
+                SCORE_FUNCTION,
+                (task_value, agent.clone(), workload.clone()),
+            )
+            .map_err(|e| e.to_string())?;
+
+        if result.is_unit() || result.as_bool() == Ok(false) {
+            return Ok(None);
+        }
+        let score = match (result.as_float(), result.as_int()) {
+            (Ok(score), _) => score,
+            (_, Ok(score)) => score as f64,
+            _ => {
+                return Err(format!(
+                    "{SCORE_FUNCTION} returned {} for task {}; expected a number, () or false",
+                    result.type_name(),
+                    task.code
+                ))
+            }
+        };
+        match score.is_nan() {
+            true => Err(format!(
+                "{SCORE_FUNCTION} returned NaN for task {}",
+                task.code
+            )),
+            false => Ok(Some(score)),
+        }
+    }
+}
+
+impl WorkDiscoveryPolicy for ScriptedDiscoveryPolicy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn rank(
+        &self,
+        candidates: Vec<Task>,
+        agent: &DiscoveryAgent,
+        workload: &DiscoveryWorkload,
+    ) -> task_core::Result<Vec<Task>> {
+        let script_error = |e: String| TaskError::Protocol(format!("{}: {e}", self.name));
+        let deadline = Instant::now() + self.time_limit;
+        let mut engine = sandboxed_engine(self.max_operations);
+        engine.on_progress(move |_| {
+            (Instant::now() > deadline).then(|| Dynamic::from("time limit exceeded"))
+        });
+        let agent = rhai::serde::to_dynamic(agent).map_err(|e| script_error(e.to_string()))?;
+        let workload =
+            rhai::serde::to_dynamic(workload).map_err(|e| script_error(e.to_string()))?;
+
+        let mut scored = Vec::with_capacity(candidates.len());
+        for task in candidates {
+            if let Some(score) = self
+                .score(&engine, &task, &agent, &workload)
+                .map_err(script_error)?
+            {
+                scored.push((score, task));
+            }
+        }
+        // Stable, so equal scores keep the repository's order
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        Ok(scored.into_iter().map(|(_, task)| task).collect())
+    }
+}
+
+/// Engine without `eval` whose scripts stop after `max_operations`
+///
+/// `print` and `debug` go to the log at debug level instead of the server's
+/// stdout.
+fn sandboxed_engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .on_print(|text| debug!(target: "discovery_script", "{}", text))
+        .on_debug(|text, source, position| {
+            debug!(
+                target: "discovery_script",
+                "{} @ {}{}",
+                text,
+                source.unwrap_or("script"),
+                position
+            )
+        })
+        .set_max_operations(max_operations)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(64 * 1024)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000)
+        .disable_symbol("eval");
+    engine
+}
+
+/// Load and compile the script named by `config`
+pub fn create_discovery_policy(
+    config: &WorkDiscoveryConfig,
+    project_root: Option<&Path>,
+) -> Result<Arc<dyn WorkDiscoveryPolicy>> {
+    config.validate()?;
+    let path = match project_root {
+        Some(root) => root.join(&config.script),
+        None => Path::new(&config.script).to_path_buf(),
+    };
+    let source = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read discovery script {}", path.display()))?;
+    let policy = ScriptedDiscoveryPolicy::compile(&config.script, &source, config)?;
+    info!("Work discovery ranked by {}", path.display());
+    Ok(Arc::new(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mocks::builders::TaskBuilder;
+
+    fn task(code: &str, priority_score: f64, capabilities: &[&str]) -> Task {
+        Task {
+            owner_agent_name: None,
+            priority_score,
+            required_capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            ..TaskBuilder::new().with_code(code).with_name(code).build()
+        }
+    }
+
+    fn rank(source: &str, workload: DiscoveryWorkload) -> task_core::Result<Vec<String>> {
+        let policy =
+            ScriptedDiscoveryPolicy::compile("test.rhai", source, &WorkDiscoveryConfig::default())
+                .unwrap();
+        let agent = DiscoveryAgent {
+            name: "frontend-dev".to_string(),
+            capabilities: vec!["ui".to_string()],
+        };
+        let candidates = vec![
+            task("LOW", 1.0, &["ui"]),
+            task("HIGH", 9.0, &["ui"]),
+            task("BACKEND", 5.0, &["api"]),
+        ];
+        let ranked = policy.rank(candidates, &agent, &workload)?;
+        Ok(ranked.into_iter().map(|task| task.code).collect())
+    }
+
+    #[test]
+    fn test_script_scores_and_filters() {
+        let source = r#"
+            fn score(task, agent, workload) {
+                print(`scoring ${task.code}`);
+                debug(agent.name);
+                if workload.in_progress >= 3 { return (); }
+                if !task.required_capabilities.all(|c| agent.capabilities.contains(c)) {
+                    return false;
+                }
+                task.priority_score
+            }
+        "#;
+        assert_eq!(
+            rank(source, DiscoveryWorkload::default()).unwrap(),
+            ["HIGH", "LOW"]
+        );
+        let busy = DiscoveryWorkload {
+            in_progress: 3,
+            blocked: 0,
+        };
+        assert!(rank(source, busy).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_script_limits() {
+        let config = WorkDiscoveryConfig::default();
+        let error = ScriptedDiscoveryPolicy::compile("test.rhai", "fn rank(t) { 1 }", &config)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("fn score(task, agent, workload)"));
+
+        let endless = "fn score(task, agent, workload) { loop {} }";
+        let error = rank(endless, DiscoveryWorkload::default()).unwrap_err();
+        assert!(error.to_string().contains("test.rhai"), "{error}");
+
+        let text = r#"fn score(task, agent, workload) { "high" }"#;
+        assert!(rank(text, DiscoveryWorkload::default()).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Embedding providers for semantic search
+//!
+//! The `[embeddings]` section enables the `semantic_search` tool and a
+//! background job keeping the embeddings of tasks, messages and comments up
+//! to date. The `hashing` provider works offline; `openai` calls any
+//! OpenAI-compatible `/embeddings` endpoint.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::{
+    index_embeddings, EmbeddingProvider, HashingEmbedder, TaskError, TaskRepository,
+    EMBEDDING_BATCH_SIZE,
+};
+use tracing::info;
+
+use crate::jobs::JobScheduler;
+
+/// Records embedded per run of the indexing job
+const INDEX_JOB_LIMIT: u32 = 5_000;
+
+/// Dimensions of the hashing provider when not configured
+const DEFAULT_HASHING_DIMENSIONS: usize = 384;
+
+/// Which provider computes embeddings
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderKind {
+    /// Local feature hashing of words; no model or network needed
+    #[default]
+    Hashing,
+    /// OpenAI-compatible embeddings API
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+/// `[embeddings]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub provider: EmbeddingProviderKind,
+    /// Model requested from the API
+    pub model: String,
+    /// Vector dimensions; the API's default for the model when unset
+    pub dimensions: Option<usize>,
+    /// Embeddings endpoint of the API
+    pub api_url: String,
+    /// API key; `AXON_EMBEDDINGS_API_KEY` overrides it when no config file is given
+    pub api_key: Option<String>,
+    /// Interval in seconds between indexing runs (0 disables the background job)
+    pub index_interval: u64,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProviderKind::Hashing,
+            model: "text-embedding-3-small".to_string(),
+            dimensions: None,
+            api_url: "https://api.openai.com/v1/embeddings".to_string(),
+            api_key: None,
+            index_interval: 300,
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    /// Check that the API provider has what it needs
+    pub fn validate(&self) -> Result<()> {
+        if self.dimensions == Some(0) {
+            anyhow::bail!("embeddings.dimensions must be greater than 0");
+        }
+        if self.provider == EmbeddingProviderKind::OpenAi {
+            if !self.api_url.starts_with("https://") && !self.api_url.starts_with("http://") {
+                anyhow::bail!(
+                    "embeddings.api_url must be an http(s) URL. Got: '{}'",
+                    self.api_url
+                );
+            }
+            if self.model.is_empty() {
+                anyhow::bail!("embeddings.model is required for the openai provider");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// [`EmbeddingProvider`] over an OpenAI-compatible embeddings API
+pub struct OpenAiEmbeddings {
+    client: reqwest::Client,
+    config: EmbeddingsConfig,
+    /// Model name including the dimensions, so changing them reindexes everything
+    model_id: String,
+}
+
+impl OpenAiEmbeddings {
+    pub fn new(config: &EmbeddingsConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("axon-mcp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(60))
+            .build()?;
+        let model_id = match config.dimensions {
+            Some(dimensions) => format!("{}@{dimensions}", config.model),
+            None => config.model.clone(),
+        };
+        Ok(Self {
+            client,
+            config: config.clone(),
+            model_id,
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbeddings {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    async fn embed(&self, texts: &[String]) -> task_core::Result<Vec<Vec<f32>>> {
+        let mut body = serde_json::json!({ "model": self.config.model, "input": texts });
+        if let Some(dimensions) = self.config.dimensions {
+            body["dimensions"] = dimensions.into();
+        }
+        let mut request = self.client.post(&self.config.api_url).json(&body);
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TaskError::Protocol(format!("Failed to reach embeddings API: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TaskError::Protocol(format!(
+                "Embeddings API returned status {status}: {body}"
+            )));
+        }
+        let mut response: EmbeddingsResponse = response.json().await.map_err(|e| {
+            TaskError::Protocol(format!("Failed to parse embeddings response: {e}"))
+        })?;
+        response.data.sort_by_key(|data| data.index);
+        Ok(response
+            .data
+            .into_iter()
+            .map(|data| data.embedding)
+            .collect())
+    }
+}
+
+/// Create the provider selected by `config`
+pub fn create_embedding_provider(config: &EmbeddingsConfig) -> Result<Arc<dyn EmbeddingProvider>> {
+    config.validate()?;
+    Ok(match config.provider {
+        EmbeddingProviderKind::Hashing => Arc::new(HashingEmbedder::new(
+            config.dimensions.unwrap_or(DEFAULT_HASHING_DIMENSIONS),
+        )),
+        EmbeddingProviderKind::OpenAi => Arc::new(
+            OpenAiEmbeddings::new(config).context("Failed to set up embeddings API client")?,
+        ),
+    })
+}
+
+/// Schedule indexing of new and changed records
+pub fn register_embedding_index_job<R>(
+    scheduler: &mut JobScheduler,
+    repository: Arc<R>,
+    provider: Arc<dyn EmbeddingProvider>,
+    config: &EmbeddingsConfig,
+    jitter: Duration,
+) where
+    R: TaskRepository + 'static,
+{
+    if config.index_interval == 0 {
+        return;
+    }
+    info!("Indexing embeddings with model {}", provider.model());
+    scheduler.register(
+        "index_embeddings",
+        Duration::from_secs(config.index_interval),
+        jitter,
+        move || {
+            let repository = repository.clone();
+            let provider = provider.clone();
+            async move {
+                let report = index_embeddings(
+                    repository.as_ref(),
+                    provider.as_ref(),
+                    INDEX_JOB_LIMIT,
+                    EMBEDDING_BATCH_SIZE,
+                )
+                .await?;
+                Ok(format!(
+                    "Indexed {} record(s), pruned {} embedding(s)",
+                    report.indexed, report.pruned
+                ))
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_embedding_provider() {
+        let config: EmbeddingsConfig = toml::from_str("dimensions = 64").unwrap();
+        let provider = create_embedding_provider(&config).unwrap();
+        assert_eq!(provider.model(), "hashing-64");
+
+        let config: EmbeddingsConfig =
+            toml::from_str("provider = \"openai\"\nmodel = \"nomic-embed-text\"").unwrap();
+        let provider = create_embedding_provider(&config).unwrap();
+        assert_eq!(provider.model(), "nomic-embed-text");
+
+        let config = EmbeddingsConfig {
+            dimensions: Some(0),
+            ..EmbeddingsConfig::default()
+        };
+        assert!(create_embedding_provider(&config).is_err());
+    }
+}
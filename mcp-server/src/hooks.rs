@@ -0,0 +1,373 @@
+//! Task lifecycle hooks
+//!
+//! Runs user-defined commands when a task is done or blocked, so builds,
+//! tests or custom notifications can be triggered without changing the
+//! server. A hook is a shell command or a WASM module run by a WASI runtime;
+//! either gets the task as JSON on stdin. Hooks follow the state changes the
+//! task handler announces, whether a tool call, a message effect, a pipeline
+//! or the task file moved the task, and fire when a task enters the state.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use task_core::{Task, TaskState};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Lifecycle event a hook is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    TaskDone,
+    TaskBlocked,
+}
+
+impl HookEvent {
+    /// Event fired when a task enters `state`, if any
+    pub fn for_state(state: TaskState) -> Option<Self> {
+        match state {
+            TaskState::Done => Some(HookEvent::TaskDone),
+            TaskState::Blocked => Some(HookEvent::TaskBlocked),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookEvent::TaskDone => write!(f, "on_task_done"),
+            HookEvent::TaskBlocked => write!(f, "on_task_blocked"),
+        }
+    }
+}
+
+/// One hook: a shell command or a WASM module
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct HookConfig {
+    /// Shell command, run with `sh -c` (`cmd /C` on Windows)
+    pub command: Option<String>,
+    /// WASM module, relative to the project root
+    pub wasm: Option<String>,
+    /// WASI runtime the module is run with; defaults to `wasmtime`
+    pub runtime: Option<String>,
+}
+
+impl HookConfig {
+    /// Check that exactly one of `command` and `wasm` is set
+    pub fn validate(&self, event: HookEvent) -> Result<()> {
+        match (&self.command, &self.wasm) {
+            (Some(command), None) if !command.trim().is_empty() => Ok(()),
+            (None, Some(wasm)) if !wasm.trim().is_empty() => Ok(()),
+            _ => anyhow::bail!("Every hooks.{event} entry needs either a command or a wasm module"),
+        }
+    }
+
+    /// Process running the hook
+    fn process(&self) -> Command {
+        match (&self.command, &self.wasm) {
+            (Some(command), _) if cfg!(windows) => {
+                let mut process = Command::new("cmd");
+                process.arg("/C").arg(command);
+                process
+            }
+            (Some(command), _) => {
+                let mut process = Command::new("sh");
+                process.arg("-c").arg(command);
+                process
+            }
+            (None, wasm) => {
+                let mut process = Command::new(self.runtime.as_deref().unwrap_or("wasmtime"));
+                process.arg(wasm.as_deref().unwrap_or_default());
+                process
+            }
+        }
+    }
+}
+
+impl fmt::Display for HookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.command, &self.wasm) {
+            (Some(command), _) => write!(f, "`{command}`"),
+            (None, Some(wasm)) => write!(f, "{wasm}"),
+            (None, None) => write!(f, "(empty hook)"),
+        }
+    }
+}
+
+/// `[hooks]` section of the configuration; no hooks run unless configured
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Hooks run when a task is done
+    pub on_task_done: Vec<HookConfig>,
+    /// Hooks run when a task is blocked
+    pub on_task_blocked: Vec<HookConfig>,
+    /// Seconds a hook may run before it is killed
+    pub timeout: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_task_done: Vec::new(),
+            on_task_blocked: Vec::new(),
+            timeout: 60,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Check every hook and the timeout
+    pub fn validate(&self) -> Result<()> {
+        for hook in &self.on_task_done {
+            hook.validate(HookEvent::TaskDone)?;
+        }
+        for hook in &self.on_task_blocked {
+            hook.validate(HookEvent::TaskBlocked)?;
+        }
+        if self.timeout == 0 {
+            anyhow::bail!("hooks.timeout must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Whether any hook is configured
+    pub fn is_empty(&self) -> bool {
+        self.on_task_done.is_empty() && self.on_task_blocked.is_empty()
+    }
+
+    /// Hooks attached to `event`
+    pub fn hooks(&self, event: HookEvent) -> &[HookConfig] {
+        match event {
+            HookEvent::TaskDone => &self.on_task_done,
+            HookEvent::TaskBlocked => &self.on_task_blocked,
+        }
+    }
+}
+
+/// How a hook run ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutcome {
+    /// Exit code; `None` when the hook was killed or ended by a signal
+    pub exit_code: Option<i32>,
+    /// Last line the hook wrote to stderr
+    pub stderr: String,
+    pub duration: Duration,
+}
+
+impl HookOutcome {
+    pub fn is_success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run `hook` for `event` with the task JSON on stdin
+///
+/// The hook runs in `working_dir` with `AXON_HOOK_EVENT`, `AXON_TASK_ID`,
+/// `AXON_TASK_CODE` and `AXON_TASK_STATE` set, and is killed after `timeout`.
+pub async fn run_hook(
+    hook: &HookConfig,
+    event: HookEvent,
+    task: &Task,
+    working_dir: Option<&PathBuf>,
+    timeout: Duration,
+) -> Result<HookOutcome> {
+    let input = serde_json::to_vec(task).context("Failed to serialize the task")?;
+    let mut process = hook.process();
+    process
+        .env("AXON_HOOK_EVENT", event.to_string())
+        .env("AXON_TASK_ID", task.id.to_string())
+        .env("AXON_TASK_CODE", &task.code)
+        .env("AXON_TASK_STATE", task.state.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = working_dir {
+        process.current_dir(dir);
+    }
+
+    let started = Instant::now();
+    let mut child = process
+        .spawn()
+        .with_context(|| format!("Failed to start hook {hook}"))?;
+    let run = async move {
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that does not read its input closes the pipe early; that is fine
+            let _ = stdin.write_all(&input).await;
+        }
+        child.wait_with_output().await
+    };
+    // Dropping the timed-out run kills the hook
+    let output = match tokio::time::timeout(timeout, run).await {
+        Ok(output) => output.with_context(|| format!("Failed to run hook {hook}"))?,
+        Err(_) => {
+            return Ok(HookOutcome {
+                exit_code: None,
+                stderr: format!("killed after {} s", timeout.as_secs()),
+                duration: started.elapsed(),
+            })
+        }
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(HookOutcome {
+        exit_code: output.status.code(),
+        stderr: stderr.lines().last().unwrap_or_default().to_string(),
+        duration: started.elapsed(),
+    })
+}
+
+/// Runs the configured hooks for tasks entering a state
+pub struct TaskHooks {
+    config: HooksConfig,
+    working_dir: Option<PathBuf>,
+}
+
+impl TaskHooks {
+    /// Hooks run in `working_dir`, usually the project root
+    pub fn new(config: HooksConfig, working_dir: Option<PathBuf>) -> Self {
+        Self {
+            config,
+            working_dir,
+        }
+    }
+
+    /// Run the hooks of the state `task` entered; returns how many ran successfully
+    pub async fn handle(&self, task: &Task) -> usize {
+        let Some(event) = HookEvent::for_state(task.state) else {
+            return 0;
+        };
+
+        let timeout = Duration::from_secs(self.config.timeout);
+        let mut succeeded = 0;
+        for hook in self.config.hooks(event) {
+            match run_hook(hook, event, task, self.working_dir.as_ref(), timeout).await {
+                Ok(outcome) if outcome.is_success() => {
+                    succeeded += 1;
+                    info!(
+                        "Hook {} {} for task {} finished in {:?}",
+                        event, hook, task.code, outcome.duration
+                    );
+                }
+                Ok(outcome) => warn!(
+                    "Hook {} {} for task {} failed (exit code {:?}): {}",
+                    event, hook, task.code, outcome.exit_code, outcome.stderr
+                ),
+                Err(e) => warn!("Hook {} for task {}: {:#}", event, task.code, e),
+            }
+        }
+        succeeded
+    }
+
+    /// Follow the state changes of `receiver` until its sender is dropped
+    pub fn spawn(self: Arc<Self>, mut receiver: broadcast::Receiver<Task>) -> JoinHandle<()> {
+        info!(
+            "Task hooks enabled: {} on_task_done, {} on_task_blocked",
+            self.config.on_task_done.len(),
+            self.config.on_task_blocked.len()
+        );
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(task) => {
+                        let hooks = self.clone();
+                        // Hooks of one task change must not delay those of the next
+                        tokio::spawn(async move { hooks.handle(&task).await });
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Task hooks missed {} state changes", skipped)
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::SqliteTaskRepository;
+    use task_core::{NewTask, TaskCrudRepository, WorkClaimRepository};
+
+    #[test]
+    fn test_validate_hooks() {
+        let mut config = HooksConfig {
+            on_task_done: vec![HookConfig {
+                command: Some("make test".to_string()),
+                ..HookConfig::default()
+            }],
+            ..HooksConfig::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.on_task_blocked.push(HookConfig {
+            command: Some("notify".to_string()),
+            wasm: Some("hooks/notify.wasm".to_string()),
+            runtime: None,
+        });
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("hooks.on_task_blocked"), "{error}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hooks_run_when_a_task_is_done() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let database_url = format!("sqlite://{}", dir.path().join("hooks.sqlite").display());
+        let repository = Arc::new(SqliteTaskRepository::new(&database_url).await.unwrap());
+        repository.migrate().await.unwrap();
+        let task = repository
+            .create(NewTask::new(
+                "HOOK-01".to_string(),
+                "Ship it".to_string(),
+                "Run the hooks".to_string(),
+                None,
+            ))
+            .await
+            .unwrap();
+
+        let config = HooksConfig {
+            on_task_done: vec![HookConfig {
+                command: Some("cat > \"$AXON_TASK_CODE.json\"".to_string()),
+                ..HookConfig::default()
+            }],
+            ..HooksConfig::default()
+        };
+        let hooks = Arc::new(TaskHooks::new(config, Some(dir.path().to_path_buf())));
+
+        let claimed = repository.claim_task(task.id, "backend-dev").await.unwrap();
+        assert_eq!(hooks.handle(&claimed).await, 0);
+        let done = repository
+            .set_state(task.id, TaskState::Done)
+            .await
+            .unwrap();
+        assert_eq!(hooks.handle(&done).await, 1);
+        let written = std::fs::read_to_string(dir.path().join("HOOK-01.json")).unwrap();
+        let written: Task = serde_json::from_str(&written).unwrap();
+        assert_eq!(written.state, TaskState::Done);
+
+        // State changes sent on the channel run the hooks too
+        std::fs::remove_file(dir.path().join("HOOK-01.json")).unwrap();
+        let (sender, receiver) = broadcast::channel(4);
+        let following = hooks.spawn(receiver);
+        sender.send(done).unwrap();
+        drop(sender);
+        following.await.unwrap();
+        for _ in 0..100 {
+            if dir.path().join("HOOK-01.json").exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(dir.path().join("HOOK-01.json").exists());
+    }
+}
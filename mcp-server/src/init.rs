@@ -0,0 +1,176 @@
+//! Workspace bootstrap (`axon-mcp init`)
+//!
+//! Creates everything a project needs before agents start working against the
+//! server: the `.axon/` and `.claude/` directories, a starter
+//! `.axon/config.toml`, a `docs/PRD.md` template matching the sections the
+//! workspace setup tools look for, and optionally an `mcpServers` entry in the
+//! user's Claude Code settings. Existing files are never overwritten, so init
+//! can be re-run safely.
+
+use anyhow::{Context, Result};
+use mcp_protocol::claude_settings::register_with_claude_code;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use task_core::{http_server_entry, ClaudeCodeRegistration};
+
+/// Starter per-project configuration; every setting is commented out
+const STARTER_CONFIG: &str = r#"# Axon MCP project settings
+#
+# Settings here override the global configuration for this project only.
+# Allowed sections: workflow, claim_lease, work_sessions, agent_names,
+# duplicate_check, agent_performance, retention, thread_summaries,
+# token_budgets, integrations, digest and messages.
+
+# [claim_lease]
+# default_minutes = 120
+# max_minutes = 1440
+# affinity_minutes = 15
+
+# [token_budgets]
+# main_ai_file = 8000
+# agent_prompt = 3000
+# task_context = 4000
+
+# [[workflow.message_effects]]
+# message_type = "blocker"
+# action = "set_state"
+# state = "Blocked"
+"#;
+
+/// PRD template with the sections `PrdDocument` extracts
+const PRD_TEMPLATE: &str = r#"# Project Name
+
+## Overview
+One paragraph describing what the project is and who it is for.
+
+## Objectives
+- Main goal of the project
+- Secondary goal
+
+## User Stories
+- As a user, I want to ... so that ...
+- As an administrator, I want to ... so that ...
+
+## Technical Requirements
+- Language, frameworks and storage
+- Interfaces (CLI, HTTP API, UI)
+- Deployment target
+
+## Success Criteria
+- Measurable outcome that marks the project as done
+
+## Constraints
+- Budget, dependencies or platform limitations
+
+## Timeline
+Milestones and target dates.
+"#;
+
+/// What `axon-mcp init` should set up
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    /// Port the server will be started on, used for the Claude Code entry
+    pub port: u16,
+    /// Claude Code settings file to register the server in, if any
+    pub claude_settings: Option<PathBuf>,
+}
+
+/// Files created or left untouched by `axon-mcp init`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InitReport {
+    pub created: Vec<PathBuf>,
+    /// Files that already existed and were kept as they are
+    pub existing: Vec<PathBuf>,
+    /// Entry written to the Claude Code settings, if requested
+    pub registration: Option<ClaudeCodeRegistration>,
+}
+
+/// Bootstrap the workspace in `project_root`
+pub fn init_workspace(project_root: &Path, options: &InitOptions) -> Result<InitReport> {
+    if !project_root.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Project root is not a directory: {}",
+            project_root.display()
+        ));
+    }
+    let project_root = project_root
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", project_root.display()))?;
+
+    let mut report = InitReport::default();
+    for dir in [".axon", ".claude", "docs"] {
+        let path = project_root.join(dir);
+        if !path.is_dir() {
+            std::fs::create_dir_all(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+        }
+    }
+    for (path, contents) in [
+        (
+            project_root.join(".axon").join("config.toml"),
+            STARTER_CONFIG,
+        ),
+        (project_root.join("docs").join("PRD.md"), PRD_TEMPLATE),
+    ] {
+        if path.exists() {
+            report.existing.push(path);
+        } else {
+            std::fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            report.created.push(path);
+        }
+    }
+
+    if let Some(settings) = &options.claude_settings {
+        let registration = register_with_claude_code(
+            settings,
+            &project_root,
+            http_server_entry(options.port),
+            false,
+        )?;
+        report.registration = Some(registration);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use task_core::CLAUDE_CODE_SERVER_NAME;
+
+    #[test]
+    fn test_init_workspace() {
+        let root = tempfile::tempdir().unwrap();
+        let settings = root.path().join("claude.json");
+        std::fs::write(&settings, r#"{"theme": "dark"}"#).unwrap();
+        let options = InitOptions {
+            port: 8499,
+            claude_settings: Some(settings.clone()),
+        };
+
+        let report = init_workspace(root.path(), &options).unwrap();
+        assert_eq!(report.created.len(), 2);
+        assert!(report.existing.is_empty());
+        assert!(root.path().join(".claude").is_dir());
+        let prd = std::fs::read_to_string(root.path().join("docs/PRD.md")).unwrap();
+        assert!(task_core::PrdDocument::from_content(&prd)
+            .unwrap()
+            .is_valid());
+        let starter = std::fs::read_to_string(root.path().join(".axon/config.toml")).unwrap();
+        assert!(starter.parse::<toml::Table>().unwrap().is_empty());
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&settings).unwrap()).unwrap();
+        assert_eq!(written["theme"], "dark");
+        let project = root.path().canonicalize().unwrap().display().to_string();
+        assert_eq!(
+            written["projects"][project.as_str()]["mcpServers"][CLAUDE_CODE_SERVER_NAME],
+            json!({"type": "http", "url": "http://127.0.0.1:8499/mcp"})
+        );
+
+        let report = init_workspace(root.path(), &options).unwrap();
+        assert!(report.created.is_empty());
+        assert_eq!(report.existing.len(), 2);
+    }
+}
@@ -0,0 +1,582 @@
+//! GitHub Issues bridge
+//!
+//! Imports the open issues of a repository as tasks, mapping labels to
+//! required capabilities and assignees to agents, and pushes task state
+//! changes back as issue comments, closing the issue once its task is done.
+//! Issues and tasks are paired through the repository's external links.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::{
+    capability_key, CapabilityCatalog, ExternalLink, NewTask, TaskError, TaskRepository, TaskState,
+    TaskValidator,
+};
+use tracing::{info, warn};
+
+use crate::jobs::JobScheduler;
+
+/// `system` of the external links to GitHub issues
+pub const GITHUB_SYSTEM: &str = "github";
+
+/// Issues requested per page of the GitHub API
+const PAGE_SIZE: usize = 100;
+
+/// `[integrations.github]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct GitHubConfig {
+    /// Repository as `owner/name`
+    pub repo: String,
+    /// Personal access token; `AXON_GITHUB_TOKEN` overrides it when no config file is given
+    pub token: Option<String>,
+    /// API base URL, changed for GitHub Enterprise
+    pub api_url: String,
+    /// Prefix of imported task codes, followed by the issue number
+    pub code_prefix: String,
+    /// Only import issues carrying all of these labels
+    pub labels: Vec<String>,
+    /// Capability each label maps to; other labels become capabilities as they are
+    pub label_capabilities: HashMap<String, String>,
+    /// Agent each GitHub login maps to; other logins are used as agent names when valid
+    pub assignees: HashMap<String, String>,
+    /// Comment on issues when their task changes state, and close them when it is done
+    pub push_state_changes: bool,
+    /// Interval in seconds between syncs (0 disables the background job)
+    pub sync_interval: u64,
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self {
+            repo: String::new(),
+            token: None,
+            api_url: "https://api.github.com".to_string(),
+            code_prefix: "GH".to_string(),
+            labels: Vec::new(),
+            label_capabilities: HashMap::new(),
+            assignees: HashMap::new(),
+            push_state_changes: true,
+            sync_interval: 300,
+        }
+    }
+}
+
+impl GitHubConfig {
+    /// Check that the repository is `owner/name` and a token is set
+    pub fn validate(&self) -> Result<()> {
+        let mut parts = self.repo.split('/');
+        let valid_repo = matches!(
+            (parts.next(), parts.next(), parts.next()),
+            (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty()
+        );
+        if !valid_repo {
+            anyhow::bail!(
+                "integrations.github.repo must be 'owner/name'. Got: '{}'",
+                self.repo
+            );
+        }
+        if self.token.as_deref().is_none_or(str::is_empty) {
+            anyhow::bail!("integrations.github.token (or AXON_GITHUB_TOKEN) is required");
+        }
+        TaskValidator::validate_task_code(&format!("{}-1", self.code_prefix))
+            .context("integrations.github.code_prefix does not form valid task codes")?;
+        Ok(())
+    }
+
+    /// Code of the task imported from issue `number`
+    pub fn task_code(&self, number: u64) -> String {
+        format!("{}-{}", self.code_prefix, number)
+    }
+
+    /// Agent an issue assignee maps to, if any
+    fn agent_for(&self, login: &str) -> Option<String> {
+        if let Some(agent) = self.assignees.get(login) {
+            return Some(agent.clone());
+        }
+        let agent = login.to_lowercase();
+        TaskValidator::validate_agent_name(&agent)
+            .ok()
+            .map(|_| agent)
+    }
+}
+
+/// Issue as returned by the GitHub REST API
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct GitHubIssue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    pub html_url: String,
+    #[serde(default)]
+    pub labels: Vec<GitHubLabel>,
+    #[serde(default)]
+    pub assignees: Vec<GitHubUser>,
+    /// Present when the issue is a pull request
+    #[serde(default)]
+    pub pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct GitHubLabel {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
+/// The GitHub operations the bridge relies on
+#[async_trait]
+pub trait GitHubApi: Send + Sync {
+    /// Open issues carrying all of `labels`, pull requests included
+    async fn list_open_issues(&self, labels: &[String]) -> Result<Vec<GitHubIssue>>;
+
+    /// Post a comment on issue `number`
+    async fn comment_on_issue(&self, number: u64, body: &str) -> Result<()>;
+
+    /// Close issue `number` as completed
+    async fn close_issue(&self, number: u64) -> Result<()>;
+}
+
+/// [`GitHubApi`] over the GitHub REST API, authenticated with a personal access token
+pub struct GitHubClient {
+    client: reqwest::Client,
+    api_url: String,
+    repo: String,
+    token: String,
+}
+
+impl GitHubClient {
+    /// Create a client for the configured repository
+    pub fn new(config: &GitHubConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("axon-mcp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            repo: config.repo.clone(),
+            token: config.token.clone().unwrap_or_default(),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!("{}/repos/{}{}", self.api_url, self.repo, path),
+            )
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    anyhow::bail!("GitHub API returned status {status}: {body}")
+}
+
+#[async_trait]
+impl GitHubApi for GitHubClient {
+    async fn list_open_issues(&self, labels: &[String]) -> Result<Vec<GitHubIssue>> {
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let mut query = vec![
+                ("state", "open".to_string()),
+                ("per_page", PAGE_SIZE.to_string()),
+                ("page", page.to_string()),
+            ];
+            if !labels.is_empty() {
+                query.push(("labels", labels.join(",")));
+            }
+            let response = self
+                .request(reqwest::Method::GET, "/issues")
+                .query(&query)
+                .send()
+                .await
+                .context("Failed to fetch GitHub issues")?;
+            let batch: Vec<GitHubIssue> = check_status(response)
+                .await?
+                .json()
+                .await
+                .context("Failed to parse GitHub issues")?;
+            let last_page = batch.len() < PAGE_SIZE;
+            issues.extend(batch);
+            if last_page {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn comment_on_issue(&self, number: u64, body: &str) -> Result<()> {
+        let response = self
+            .request(reqwest::Method::POST, &format!("/issues/{number}/comments"))
+            .json(&serde_json::json!({ "body": body }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to comment on GitHub issue #{number}"))?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    async fn close_issue(&self, number: u64) -> Result<()> {
+        let response = self
+            .request(reqwest::Method::PATCH, &format!("/issues/{number}"))
+            .json(&serde_json::json!({ "state": "closed", "state_reason": "completed" }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to close GitHub issue #{number}"))?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
+
+/// What one sync did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GitHubSyncReport {
+    /// Issues imported as new tasks
+    pub imported: usize,
+    /// Issues linked to an existing task with the same code
+    pub linked: usize,
+    /// Issues that could not be imported
+    pub skipped: usize,
+    /// State change comments posted
+    pub comments: usize,
+    /// Issues closed because their task is done
+    pub closed: usize,
+}
+
+impl fmt::Display for GitHubSyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Imported {} issue(s), linked {}, skipped {}; posted {} comment(s), closed {} issue(s)",
+            self.imported, self.linked, self.skipped, self.comments, self.closed
+        )
+    }
+}
+
+/// Synchronizes the tasks of a repository with GitHub issues
+pub struct GitHubBridge<R, A> {
+    config: GitHubConfig,
+    repository: Arc<R>,
+    api: A,
+}
+
+impl<R: TaskRepository, A: GitHubApi> GitHubBridge<R, A> {
+    pub fn new(config: GitHubConfig, repository: Arc<R>, api: A) -> Self {
+        Self {
+            config,
+            repository,
+            api,
+        }
+    }
+
+    /// Import new issues, then push state changes of linked tasks
+    pub async fn sync(&self) -> Result<GitHubSyncReport> {
+        let mut report = GitHubSyncReport::default();
+        self.import_issues(&mut report).await?;
+        if self.config.push_state_changes {
+            self.push_state_changes(&mut report).await?;
+        }
+        Ok(report)
+    }
+
+    async fn import_issues(&self, report: &mut GitHubSyncReport) -> Result<()> {
+        let linked: HashSet<String> = self
+            .repository
+            .list_external_links(GITHUB_SYSTEM)
+            .await?
+            .into_iter()
+            .map(|link| link.external_id)
+            .collect();
+        let catalog = match self.repository.list_capabilities().await {
+            Ok(capabilities) => CapabilityCatalog::new(capabilities),
+            Err(TaskError::UnsupportedOperation(_)) => CapabilityCatalog::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let issues = self.api.list_open_issues(&self.config.labels).await?;
+        for issue in issues {
+            if issue.pull_request.is_some() || linked.contains(&issue.number.to_string()) {
+                continue;
+            }
+            let new_task = self.issue_to_task(&issue, &catalog);
+            let task = match self.repository.get_by_code(&new_task.code).await? {
+                Some(existing) => {
+                    report.linked += 1;
+                    existing
+                }
+                None => match TaskValidator::validate_new_task(&new_task) {
+                    Ok(()) => {
+                        report.imported += 1;
+                        self.repository.create(new_task).await?
+                    }
+                    Err(e) => {
+                        warn!("Skipping GitHub issue #{}: {}", issue.number, e);
+                        report.skipped += 1;
+                        continue;
+                    }
+                },
+            };
+            self.repository
+                .save_external_link(&ExternalLink {
+                    system: GITHUB_SYSTEM.to_string(),
+                    task_code: task.code,
+                    external_id: issue.number.to_string(),
+                    url: Some(issue.html_url),
+                    synced_state: Some(task.state),
+                    synced_at: Utc::now(),
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Task to create for an issue
+    fn issue_to_task(&self, issue: &GitHubIssue, catalog: &CapabilityCatalog) -> NewTask {
+        let body = issue.body.as_deref().unwrap_or_default().trim();
+        let description = if body.is_empty() {
+            format!("Imported from {}", issue.html_url)
+        } else {
+            format!("{body}\n\nImported from {}", issue.html_url)
+        };
+        let labels: Vec<String> = issue
+            .labels
+            .iter()
+            .map(|label| {
+                self.config
+                    .label_capabilities
+                    .get(&label.name)
+                    .cloned()
+                    .unwrap_or_else(|| capability_key(&label.name))
+            })
+            .collect();
+        let owner = issue
+            .assignees
+            .iter()
+            .find_map(|user| self.config.agent_for(&user.login));
+
+        NewTask {
+            required_capabilities: catalog.normalize_all(&labels),
+            ..NewTask::new(
+                self.config.task_code(issue.number),
+                issue.title.trim().to_string(),
+                description,
+                owner,
+            )
+        }
+    }
+
+    async fn push_state_changes(&self, report: &mut GitHubSyncReport) -> Result<()> {
+        for mut link in self.repository.list_external_links(GITHUB_SYSTEM).await? {
+            let Some(task) = self.repository.get_by_code(&link.task_code).await? else {
+                continue;
+            };
+            if link.synced_state == Some(task.state) {
+                continue;
+            }
+            let Ok(number) = link.external_id.parse::<u64>() else {
+                warn!("Invalid GitHub issue number '{}'", link.external_id);
+                continue;
+            };
+
+            let owner = task.owner_agent_name.as_deref().unwrap_or("unassigned");
+            let comment = format!(
+                "Task `{}` is now **{}** (owner: {owner}).",
+                task.code, task.state
+            );
+            self.api.comment_on_issue(number, &comment).await?;
+            report.comments += 1;
+
+            let was_finished = matches!(
+                link.synced_state,
+                Some(TaskState::Done | TaskState::Archived)
+            );
+            if matches!(task.state, TaskState::Done | TaskState::Archived) && !was_finished {
+                self.api.close_issue(number).await?;
+                report.closed += 1;
+            }
+
+            link.synced_state = Some(task.state);
+            link.synced_at = Utc::now();
+            self.repository.save_external_link(&link).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Schedule periodic GitHub syncs, if the integration and its job are configured
+pub fn register_github_sync<R>(
+    scheduler: &mut JobScheduler,
+    repository: Arc<R>,
+    config: &GitHubConfig,
+    jitter: Duration,
+) -> Result<()>
+where
+    R: TaskRepository + 'static,
+{
+    if config.sync_interval == 0 {
+        return Ok(());
+    }
+    let bridge = Arc::new(GitHubBridge::new(
+        config.clone(),
+        repository,
+        GitHubClient::new(config)?,
+    ));
+    info!("GitHub sync enabled for {}", config.repo);
+    scheduler.register(
+        "github_sync",
+        Duration::from_secs(config.sync_interval),
+        jitter,
+        move || {
+            let bridge = bridge.clone();
+            async move { Ok(bridge.sync().await?.to_string()) }
+        },
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::SqliteTaskRepository;
+    use std::sync::Mutex;
+    use task_core::{TaskCrudRepository, WorkClaimRepository};
+
+    #[derive(Default)]
+    struct FakeGitHub {
+        issues: Vec<GitHubIssue>,
+        comments: Mutex<Vec<(u64, String)>>,
+        closed: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl GitHubApi for FakeGitHub {
+        async fn list_open_issues(&self, _labels: &[String]) -> Result<Vec<GitHubIssue>> {
+            Ok(self.issues.clone())
+        }
+
+        async fn comment_on_issue(&self, number: u64, body: &str) -> Result<()> {
+            self.comments
+                .lock()
+                .unwrap()
+                .push((number, body.to_string()));
+            Ok(())
+        }
+
+        async fn close_issue(&self, number: u64) -> Result<()> {
+            self.closed.lock().unwrap().push(number);
+            Ok(())
+        }
+    }
+
+    fn issue(number: u64, labels: &[&str], assignee: Option<&str>) -> GitHubIssue {
+        GitHubIssue {
+            number,
+            title: format!("Issue {number}"),
+            body: Some("Steps to reproduce".to_string()),
+            html_url: format!("https://github.com/acme/app/issues/{number}"),
+            labels: labels
+                .iter()
+                .map(|name| GitHubLabel {
+                    name: name.to_string(),
+                })
+                .collect(),
+            assignees: assignee
+                .map(|login| GitHubUser {
+                    login: login.to_string(),
+                })
+                .into_iter()
+                .collect(),
+            pull_request: None,
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = GitHubConfig {
+            repo: "acme/app".to_string(),
+            token: Some("ghp_test".to_string()),
+            ..GitHubConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        config.repo = "acme".to_string();
+        assert!(config.validate().is_err());
+        config.repo = "acme/app".to_string();
+        config.token = None;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_imports_issues_and_pushes_state_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let database_url = format!("sqlite://{}", dir.path().join("github.sqlite").display());
+        let repository = Arc::new(SqliteTaskRepository::new(&database_url).await.unwrap());
+        repository.migrate().await.unwrap();
+
+        let mut pull_request = issue(3, &[], None);
+        pull_request.pull_request = Some(serde_json::json!({}));
+        let api = FakeGitHub {
+            issues: vec![
+                issue(1, &["Bug", "Rust Backend"], Some("Octo-Dev")),
+                issue(2, &["ui"], Some("someone")),
+                pull_request,
+            ],
+            ..FakeGitHub::default()
+        };
+        let config = GitHubConfig {
+            repo: "acme/app".to_string(),
+            token: Some("ghp_test".to_string()),
+            label_capabilities: HashMap::from([("ui".to_string(), "frontend".to_string())]),
+            assignees: HashMap::from([("someone".to_string(), "frontend-dev".to_string())]),
+            ..GitHubConfig::default()
+        };
+        let bridge = GitHubBridge::new(config, repository.clone(), api);
+
+        let report = bridge.sync().await.unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.comments, 0);
+
+        let first = repository.get_by_code("GH-1").await.unwrap().unwrap();
+        assert_eq!(first.owner_agent_name.as_deref(), Some("octo-dev"));
+        assert_eq!(first.required_capabilities, vec!["bug", "rust-backend"]);
+        assert!(first.description.ends_with("issues/1"));
+        let second = repository.get_by_code("GH-2").await.unwrap().unwrap();
+        assert_eq!(second.owner_agent_name.as_deref(), Some("frontend-dev"));
+        assert_eq!(second.required_capabilities, vec!["frontend"]);
+        assert!(repository.get_by_code("GH-3").await.unwrap().is_none());
+
+        // Nothing new on the second run
+        assert_eq!(bridge.sync().await.unwrap(), GitHubSyncReport::default());
+
+        repository.claim_task(first.id, "octo-dev").await.unwrap();
+        repository
+            .set_state(first.id, TaskState::Done)
+            .await
+            .unwrap();
+        let report = bridge.sync().await.unwrap();
+        assert_eq!(report.comments, 1);
+        assert_eq!(report.closed, 1);
+        assert_eq!(*bridge.api.closed.lock().unwrap(), vec![1]);
+        let comments = bridge.api.comments.lock().unwrap();
+        assert_eq!(comments[0].0, 1);
+        assert!(comments[0].1.contains("**Done**"));
+    }
+}
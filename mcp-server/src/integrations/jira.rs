@@ -0,0 +1,231 @@
+//! Jira Cloud adapter for outbound sync
+//!
+//! Creates one issue per task in a Jira project and moves it through the
+//! workflow with the transition leading to the mapped status.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use task_core::Task;
+
+use super::outbound::{ExternalItem, SyncSettings, TrackerAdapter};
+
+/// `[integrations.jira]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(default)]
+pub struct JiraConfig {
+    /// Site URL, e.g. `https://acme.atlassian.net`
+    pub base_url: String,
+    /// Account email the API token belongs to
+    pub email: String,
+    /// API token; `AXON_JIRA_TOKEN` overrides it when no config file is given
+    pub api_token: Option<String>,
+    /// Key of the project issues are created in
+    pub project_key: String,
+    /// Issue type of created issues; defaults to `Task`
+    pub issue_type: Option<String>,
+    /// State mapping, title format, labels and retry settings
+    #[serde(flatten)]
+    pub sync: SyncSettings,
+}
+
+impl JiraConfig {
+    /// Check that the site, credentials and project are set
+    pub fn validate(&self) -> Result<()> {
+        if !self.base_url.starts_with("https://") && !self.base_url.starts_with("http://") {
+            anyhow::bail!(
+                "integrations.jira.base_url must be an http(s) URL. Got: '{}'",
+                self.base_url
+            );
+        }
+        if self.email.is_empty() || self.api_token.as_deref().is_none_or(str::is_empty) {
+            anyhow::bail!(
+                "integrations.jira.email and api_token (or AXON_JIRA_TOKEN) are required"
+            );
+        }
+        if self.project_key.is_empty() {
+            anyhow::bail!("integrations.jira.project_key is required");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedIssue {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transitions {
+    transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transition {
+    id: String,
+    name: String,
+    to: TransitionTarget,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitionTarget {
+    name: String,
+}
+
+/// Transition leading to `status`, matched by target status or transition name
+fn find_transition<'a>(transitions: &'a [Transition], status: &str) -> Option<&'a Transition> {
+    transitions
+        .iter()
+        .find(|t| t.to.name.eq_ignore_ascii_case(status))
+        .or_else(|| {
+            transitions
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(status))
+        })
+}
+
+/// Plain text as an Atlassian Document Format document, one paragraph per line
+fn adf_document(text: &str) -> serde_json::Value {
+    let paragraphs: Vec<serde_json::Value> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::json!({
+                "type": "paragraph",
+                "content": [{ "type": "text", "text": line }]
+            })
+        })
+        .collect();
+    serde_json::json!({ "type": "doc", "version": 1, "content": paragraphs })
+}
+
+/// [`TrackerAdapter`] over the Jira Cloud REST API v3
+pub struct JiraAdapter {
+    client: reqwest::Client,
+    config: JiraConfig,
+}
+
+impl JiraAdapter {
+    pub fn new(config: &JiraConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("axon-mcp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let mut config = config.clone();
+        config.base_url = config.base_url.trim_end_matches('/').to_string();
+        Ok(Self { client, config })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(
+                method,
+                format!("{}/rest/api/3{}", self.config.base_url, path),
+            )
+            .basic_auth(&self.config.email, self.config.api_token.as_ref())
+            .header(reqwest::header::ACCEPT, "application/json")
+    }
+}
+
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    anyhow::bail!("Jira API returned status {status}: {body}")
+}
+
+#[async_trait]
+impl TrackerAdapter for JiraAdapter {
+    fn system(&self) -> &str {
+        "jira"
+    }
+
+    async fn create_item(&self, task: &Task) -> Result<ExternalItem> {
+        let description = format!("{}\n\nAxon task {}", task.description, task.code);
+        let fields = serde_json::json!({
+            "project": { "key": self.config.project_key },
+            "summary": self.config.sync.title(task),
+            "issuetype": { "name": self.config.issue_type.as_deref().unwrap_or("Task") },
+            "description": adf_document(&description),
+            "labels": self.config.sync.labels,
+        });
+        let response = self
+            .request(reqwest::Method::POST, "/issue")
+            .json(&serde_json::json!({ "fields": fields }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to create Jira issue for {}", task.code))?;
+        let issue: CreatedIssue = check_status(response)
+            .await?
+            .json()
+            .await
+            .context("Failed to parse created Jira issue")?;
+        Ok(ExternalItem {
+            url: Some(format!("{}/browse/{}", self.config.base_url, issue.key)),
+            id: issue.key,
+        })
+    }
+
+    async fn update_status(&self, external_id: &str, status: &str) -> Result<()> {
+        let path = format!("/issue/{external_id}/transitions");
+        let response = self
+            .request(reqwest::Method::GET, &path)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch transitions of {external_id}"))?;
+        let Transitions { transitions } = check_status(response)
+            .await?
+            .json()
+            .await
+            .context("Failed to parse Jira transitions")?;
+        let transition = find_transition(&transitions, status).with_context(|| {
+            format!("No transition of {external_id} leads to status '{status}'")
+        })?;
+
+        let response = self
+            .request(reqwest::Method::POST, &path)
+            .json(&serde_json::json!({ "transition": { "id": transition.id } }))
+            .send()
+            .await
+            .with_context(|| format!("Failed to transition {external_id}"))?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_transition_and_adf() {
+        let transitions: Transitions = serde_json::from_value(serde_json::json!({
+            "transitions": [
+                { "id": "11", "name": "Start work", "to": { "name": "In Progress" } },
+                { "id": "31", "name": "Done", "to": { "name": "Closed" } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            find_transition(&transitions.transitions, "in progress")
+                .unwrap()
+                .id,
+            "11"
+        );
+        assert_eq!(
+            find_transition(&transitions.transitions, "Done")
+                .unwrap()
+                .id,
+            "31"
+        );
+        assert!(find_transition(&transitions.transitions, "Review").is_none());
+
+        let doc = adf_document("First line\n\nSecond line");
+        assert_eq!(doc["content"].as_array().unwrap().len(), 2);
+        assert_eq!(doc["content"][1]["content"][0]["text"], "Second line");
+    }
+}
@@ -0,0 +1,190 @@
+//! Linear adapter for outbound sync
+//!
+//! Creates one issue per task in a Linear team through the GraphQL API and
+//! moves it to the workflow state mapped from the task state.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use task_core::Task;
+
+use super::outbound::{ExternalItem, SyncSettings, TrackerAdapter};
+
+/// `[integrations.linear]` section of the configuration
+///
+/// The mapped statuses are workflow state IDs of the team.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct LinearConfig {
+    /// GraphQL endpoint
+    pub api_url: String,
+    /// API key; `AXON_LINEAR_API_KEY` overrides it when no config file is given
+    pub api_key: Option<String>,
+    /// ID of the team issues are created in
+    pub team_id: String,
+    /// State mapping, title format and retry settings
+    #[serde(flatten)]
+    pub sync: SyncSettings,
+}
+
+impl Default for LinearConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.linear.app/graphql".to_string(),
+            api_key: None,
+            team_id: String::new(),
+            sync: SyncSettings::default(),
+        }
+    }
+}
+
+impl LinearConfig {
+    /// Check that the API key and team are set
+    pub fn validate(&self) -> Result<()> {
+        if self.api_key.as_deref().is_none_or(str::is_empty) {
+            anyhow::bail!("integrations.linear.api_key (or AXON_LINEAR_API_KEY) is required");
+        }
+        if self.team_id.is_empty() {
+            anyhow::bail!("integrations.linear.team_id is required");
+        }
+        Ok(())
+    }
+}
+
+const CREATE_ISSUE: &str = "mutation($input: IssueCreateInput!) { \
+    issueCreate(input: $input) { success issue { id identifier url } } }";
+
+const UPDATE_ISSUE: &str = "mutation($id: String!, $input: IssueUpdateInput!) { \
+    issueUpdate(id: $id, input: $input) { success } }";
+
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse {
+    data: Option<serde_json::Value>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+/// Data of a GraphQL response, or its errors
+fn response_data(response: GraphQlResponse) -> Result<serde_json::Value> {
+    if !response.errors.is_empty() {
+        let messages: Vec<&str> = response.errors.iter().map(|e| e.message.as_str()).collect();
+        anyhow::bail!("Linear API error: {}", messages.join("; "));
+    }
+    response.data.context("Linear API returned no data")
+}
+
+/// [`TrackerAdapter`] over the Linear GraphQL API
+pub struct LinearAdapter {
+    client: reqwest::Client,
+    config: LinearConfig,
+}
+
+impl LinearAdapter {
+    pub fn new(config: &LinearConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("axon-mcp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            config: config.clone(),
+        })
+    }
+
+    async fn execute(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .post(&self.config.api_url)
+            .header(
+                reqwest::header::AUTHORIZATION,
+                self.config.api_key.as_deref().unwrap_or_default(),
+            )
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .context("Failed to reach the Linear API")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Linear API returned status {status}: {body}");
+        }
+        response_data(
+            response
+                .json()
+                .await
+                .context("Failed to parse Linear response")?,
+        )
+    }
+}
+
+#[async_trait]
+impl TrackerAdapter for LinearAdapter {
+    fn system(&self) -> &str {
+        "linear"
+    }
+
+    async fn create_item(&self, task: &Task) -> Result<ExternalItem> {
+        let input = serde_json::json!({
+            "teamId": self.config.team_id,
+            "title": self.config.sync.title(task),
+            "description": format!("{}\n\nAxon task {}", task.description, task.code),
+        });
+        let data = self
+            .execute(CREATE_ISSUE, serde_json::json!({ "input": input }))
+            .await
+            .with_context(|| format!("Failed to create Linear issue for {}", task.code))?;
+        let issue = &data["issueCreate"]["issue"];
+        let id = issue["id"]
+            .as_str()
+            .context("Linear did not return the created issue")?;
+        Ok(ExternalItem {
+            id: id.to_string(),
+            url: issue["url"].as_str().map(str::to_string),
+        })
+    }
+
+    async fn update_status(&self, external_id: &str, status: &str) -> Result<()> {
+        let variables = serde_json::json!({ "id": external_id, "input": { "stateId": status } });
+        let data = self
+            .execute(UPDATE_ISSUE, variables)
+            .await
+            .with_context(|| format!("Failed to update Linear issue {external_id}"))?;
+        if data["issueUpdate"]["success"] != serde_json::Value::Bool(true) {
+            anyhow::bail!("Linear rejected the update of issue {external_id}");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_data() {
+        let ok: GraphQlResponse = serde_json::from_value(
+            serde_json::json!({ "data": { "issueUpdate": { "success": true } } }),
+        )
+        .unwrap();
+        assert_eq!(response_data(ok).unwrap()["issueUpdate"]["success"], true);
+
+        let failed: GraphQlResponse = serde_json::from_value(serde_json::json!({
+            "data": null,
+            "errors": [{ "message": "Entity not found" }, { "message": "Forbidden" }]
+        }))
+        .unwrap();
+        let error = response_data(failed).unwrap_err().to_string();
+        assert_eq!(error, "Linear API error: Entity not found; Forbidden");
+    }
+}
@@ -0,0 +1,89 @@
+//! Integrations with external trackers
+//!
+//! Each integration is configured under `[integrations.<name>]` and pairs
+//! tasks with items of the tracker through the repository's external links.
+//! GitHub issues are imported as tasks; Jira and Linear mirror tasks outbound.
+
+pub mod github;
+pub mod jira;
+pub mod linear;
+pub mod outbound;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::TaskRepository;
+
+use crate::jobs::JobScheduler;
+
+pub use github::{GitHubBridge, GitHubClient, GitHubConfig};
+pub use jira::{JiraAdapter, JiraConfig};
+pub use linear::{LinearAdapter, LinearConfig};
+
+/// `[integrations]` section of the configuration; every integration is off unless configured
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct IntegrationsConfig {
+    /// GitHub Issues import and state push-back
+    #[serde(default)]
+    pub github: Option<GitHubConfig>,
+    /// Outbound sync of tasks into Jira
+    #[serde(default)]
+    pub jira: Option<JiraConfig>,
+    /// Outbound sync of tasks into Linear
+    #[serde(default)]
+    pub linear: Option<LinearConfig>,
+}
+
+impl IntegrationsConfig {
+    /// Validate every configured integration
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref github) = self.github {
+            github.validate()?;
+        }
+        if let Some(ref jira) = self.jira {
+            jira.validate()?;
+        }
+        if let Some(ref linear) = self.linear {
+            linear.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Schedule the sync jobs of every configured integration
+pub fn register_integration_jobs<R>(
+    scheduler: &mut JobScheduler,
+    repository: Arc<R>,
+    config: &IntegrationsConfig,
+    jitter: Duration,
+) -> Result<()>
+where
+    R: TaskRepository + 'static,
+{
+    if let Some(ref github) = config.github {
+        github::register_github_sync(scheduler, repository.clone(), github, jitter)
+            .context("Failed to set up GitHub integration")?;
+    }
+    if let Some(ref jira) = config.jira {
+        let adapter = JiraAdapter::new(jira).context("Failed to set up Jira integration")?;
+        outbound::register_outbound_sync(
+            scheduler,
+            repository.clone(),
+            Box::new(adapter),
+            &jira.sync,
+            jitter,
+        );
+    }
+    if let Some(ref linear) = config.linear {
+        let adapter = LinearAdapter::new(linear).context("Failed to set up Linear integration")?;
+        outbound::register_outbound_sync(
+            scheduler,
+            repository,
+            Box::new(adapter),
+            &linear.sync,
+            jitter,
+        );
+    }
+    Ok(())
+}
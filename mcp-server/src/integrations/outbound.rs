@@ -0,0 +1,491 @@
+//! Outbound sync of tasks into external trackers
+//!
+//! A [`TrackerAdapter`] knows how to create an item in a tracker and move it
+//! to another status. [`OutboundSync`] mirrors every task into the tracker
+//! through an adapter: tasks without an external link get an item, and linked
+//! tasks whose state changed since the last push get their item's status
+//! updated. Failed operations are queued and retried with exponential backoff.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use task_core::{ExternalLink, Task, TaskFilter, TaskRepository, TaskState};
+use tracing::{info, warn};
+
+use crate::jobs::JobScheduler;
+
+/// Field mapping and scheduling shared by all outbound trackers
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct SyncSettings {
+    /// Tracker status each task state maps to; unmapped states are not pushed
+    pub states: HashMap<TaskState, String>,
+    /// Title of created items; `{code}` and `{name}` are replaced
+    pub title_format: String,
+    /// Labels added to created items, where the tracker supports them
+    pub labels: Vec<String>,
+    /// Only mirror tasks created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Interval in seconds between syncs (0 disables the background job)
+    pub sync_interval: u64,
+    /// Attempts of a failed operation before it is given up until the task changes again
+    pub max_attempts: u32,
+    /// Seconds before the first retry; doubled after every further failure
+    pub retry_delay: u64,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            states: HashMap::new(),
+            title_format: "[{code}] {name}".to_string(),
+            labels: Vec::new(),
+            since: None,
+            sync_interval: 60,
+            max_attempts: 5,
+            retry_delay: 60,
+        }
+    }
+}
+
+impl SyncSettings {
+    /// Title of the item created for `task`
+    pub fn title(&self, task: &Task) -> String {
+        self.title_format
+            .replace("{code}", &task.code)
+            .replace("{name}", &task.name)
+    }
+
+    /// Tracker status of `state`, if mapped
+    pub fn status(&self, state: TaskState) -> Option<&str> {
+        self.states.get(&state).map(String::as_str)
+    }
+}
+
+/// Item created in an external tracker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalItem {
+    /// ID used to address the item in later calls
+    pub id: String,
+    /// Web URL of the item
+    pub url: Option<String>,
+}
+
+/// An external tracker that tasks are mirrored into
+#[async_trait]
+pub trait TrackerAdapter: Send + Sync {
+    /// `system` of the external links to this tracker's items
+    fn system(&self) -> &str;
+
+    /// Create the item mirroring `task`
+    async fn create_item(&self, task: &Task) -> Result<ExternalItem>;
+
+    /// Move item `external_id` to tracker status `status`
+    async fn update_status(&self, external_id: &str, status: &str) -> Result<()>;
+}
+
+/// A failed operation waiting to be retried
+#[derive(Debug, Clone, PartialEq)]
+struct FailedSync {
+    /// Task state the operation tried to push
+    state: TaskState,
+    attempts: u32,
+    next_attempt: DateTime<Utc>,
+    last_error: String,
+}
+
+/// Failed operations per task code, retried with exponential backoff
+#[derive(Debug, Default)]
+pub struct RetryQueue {
+    failures: HashMap<String, FailedSync>,
+}
+
+impl RetryQueue {
+    /// Whether pushing `state` of task `code` may be attempted at `now`
+    ///
+    /// A failure is forgotten as soon as the task moves to another state.
+    fn is_due(&self, code: &str, state: TaskState, now: DateTime<Utc>, max_attempts: u32) -> bool {
+        match self.failures.get(code) {
+            Some(failure) if failure.state == state => {
+                failure.attempts < max_attempts && now >= failure.next_attempt
+            }
+            _ => true,
+        }
+    }
+
+    /// Queue a failed push of `state` for task `code`
+    fn record_failure(
+        &mut self,
+        code: &str,
+        state: TaskState,
+        error: String,
+        now: DateTime<Utc>,
+        retry_delay: Duration,
+    ) {
+        let attempts = match self.failures.get(code) {
+            Some(failure) if failure.state == state => failure.attempts + 1,
+            _ => 1,
+        };
+        let backoff = retry_delay.saturating_mul(2u32.saturating_pow(attempts - 1));
+        let backoff = chrono::Duration::from_std(backoff).unwrap_or(chrono::Duration::days(1));
+        self.failures.insert(
+            code.to_string(),
+            FailedSync {
+                state,
+                attempts,
+                next_attempt: now + backoff.min(chrono::Duration::days(1)),
+                last_error: error,
+            },
+        );
+    }
+
+    fn clear(&mut self, code: &str) {
+        self.failures.remove(code);
+    }
+
+    /// Queued failures that will be retried, and those given up
+    fn counts(&self, max_attempts: u32) -> (usize, usize) {
+        let given_up = self
+            .failures
+            .values()
+            .filter(|failure| failure.attempts >= max_attempts)
+            .count();
+        (self.failures.len() - given_up, given_up)
+    }
+}
+
+/// What one outbound sync did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OutboundSyncReport {
+    /// Items created for new tasks
+    pub created: usize,
+    /// Items moved to a new status
+    pub updated: usize,
+    /// Operations that failed during this run
+    pub failed: usize,
+    /// Failed operations waiting for a retry
+    pub queued: usize,
+    /// Failed operations given up until their task changes state
+    pub given_up: usize,
+}
+
+impl fmt::Display for OutboundSyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Created {} item(s), updated {}, failed {}; {} queued for retry, {} given up",
+            self.created, self.updated, self.failed, self.queued, self.given_up
+        )
+    }
+}
+
+/// Mirrors the tasks of a repository into an external tracker
+pub struct OutboundSync<R> {
+    repository: Arc<R>,
+    adapter: Box<dyn TrackerAdapter>,
+    settings: SyncSettings,
+    retries: Mutex<RetryQueue>,
+}
+
+impl<R: TaskRepository> OutboundSync<R> {
+    pub fn new(
+        repository: Arc<R>,
+        adapter: Box<dyn TrackerAdapter>,
+        settings: SyncSettings,
+    ) -> Self {
+        Self {
+            repository,
+            adapter,
+            settings,
+            retries: Mutex::new(RetryQueue::default()),
+        }
+    }
+
+    /// Create items for unlinked tasks and push state changes of linked ones
+    pub async fn sync(&self) -> Result<OutboundSyncReport> {
+        self.sync_at(Utc::now()).await
+    }
+
+    async fn sync_at(&self, now: DateTime<Utc>) -> Result<OutboundSyncReport> {
+        let system = self.adapter.system().to_string();
+        let mut links: HashMap<String, ExternalLink> = self
+            .repository
+            .list_external_links(&system)
+            .await?
+            .into_iter()
+            .map(|link| (link.task_code.clone(), link))
+            .collect();
+        let tasks = self
+            .repository
+            .list(TaskFilter {
+                date_from: self.settings.since,
+                ..TaskFilter::default()
+            })
+            .await?;
+
+        let mut report = OutboundSyncReport::default();
+        for task in tasks {
+            let link = links.remove(&task.code);
+            if link.as_ref().and_then(|link| link.synced_state) == Some(task.state) {
+                self.retries.lock().unwrap().clear(&task.code);
+                continue;
+            }
+            let due = self.retries.lock().unwrap().is_due(
+                &task.code,
+                task.state,
+                now,
+                self.settings.max_attempts,
+            );
+            if !due {
+                continue;
+            }
+
+            match self.push(&system, &task, link, now).await {
+                Ok(created) => {
+                    self.retries.lock().unwrap().clear(&task.code);
+                    if created {
+                        report.created += 1;
+                    } else {
+                        report.updated += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to sync task {} to {}: {:#}", task.code, system, e);
+                    report.failed += 1;
+                    self.retries.lock().unwrap().record_failure(
+                        &task.code,
+                        task.state,
+                        format!("{e:#}"),
+                        now,
+                        Duration::from_secs(self.settings.retry_delay),
+                    );
+                }
+            }
+        }
+
+        (report.queued, report.given_up) = self
+            .retries
+            .lock()
+            .unwrap()
+            .counts(self.settings.max_attempts);
+        Ok(report)
+    }
+
+    /// Mirror one task; returns whether an item was created
+    async fn push(
+        &self,
+        system: &str,
+        task: &Task,
+        link: Option<ExternalLink>,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let (mut link, created) = match link {
+            Some(link) => (link, false),
+            None => {
+                let item = self.adapter.create_item(task).await?;
+                let link = ExternalLink {
+                    system: system.to_string(),
+                    task_code: task.code.clone(),
+                    external_id: item.id,
+                    url: item.url,
+                    synced_state: None,
+                    synced_at: now,
+                };
+                // Store the link right away so a failed status update does not duplicate the item
+                self.repository.save_external_link(&link).await?;
+                (link, true)
+            }
+        };
+
+        if let Some(status) = self.settings.status(task.state) {
+            self.adapter
+                .update_status(&link.external_id, status)
+                .await?;
+        }
+        link.synced_state = Some(task.state);
+        link.synced_at = now;
+        self.repository.save_external_link(&link).await?;
+        Ok(created)
+    }
+}
+
+/// Schedule periodic syncs into the tracker of `adapter`
+pub fn register_outbound_sync<R>(
+    scheduler: &mut JobScheduler,
+    repository: Arc<R>,
+    adapter: Box<dyn TrackerAdapter>,
+    settings: &SyncSettings,
+    jitter: Duration,
+) where
+    R: TaskRepository + 'static,
+{
+    if settings.sync_interval == 0 {
+        return;
+    }
+    let name = format!("{}_sync", adapter.system());
+    info!("Outbound sync to {} enabled", adapter.system());
+    let sync = Arc::new(OutboundSync::new(repository, adapter, settings.clone()));
+    scheduler.register(
+        &name,
+        Duration::from_secs(settings.sync_interval),
+        jitter,
+        move || {
+            let sync = sync.clone();
+            async move { Ok(sync.sync().await?.to_string()) }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::SqliteTaskRepository;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use task_core::{NewTask, TaskCrudRepository, TaskExtensionRepository, WorkClaimRepository};
+
+    /// Tracker that fails its first `failures` calls
+    #[derive(Default)]
+    struct FakeTracker {
+        failures: AtomicU32,
+        created: Mutex<Vec<String>>,
+        updates: Mutex<Vec<(String, String)>>,
+    }
+
+    impl FakeTracker {
+        fn fail(&self) -> Result<()> {
+            let remaining = self.failures.load(Ordering::SeqCst);
+            if remaining > 0 {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                anyhow::bail!("tracker unavailable");
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl TrackerAdapter for Arc<FakeTracker> {
+        fn system(&self) -> &str {
+            "fake"
+        }
+
+        async fn create_item(&self, task: &Task) -> Result<ExternalItem> {
+            self.fail()?;
+            let mut created = self.created.lock().unwrap();
+            created.push(task.code.clone());
+            Ok(ExternalItem {
+                id: format!("FAKE-{}", created.len()),
+                url: None,
+            })
+        }
+
+        async fn update_status(&self, external_id: &str, status: &str) -> Result<()> {
+            self.fail()?;
+            self.updates
+                .lock()
+                .unwrap()
+                .push((external_id.to_string(), status.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let now = Utc::now();
+        let mut queue = RetryQueue::default();
+        let delay = Duration::from_secs(60);
+        assert!(queue.is_due("T-1", TaskState::Created, now, 3));
+
+        queue.record_failure("T-1", TaskState::Created, "boom".into(), now, delay);
+        assert!(!queue.is_due("T-1", TaskState::Created, now, 3));
+        assert!(queue.is_due(
+            "T-1",
+            TaskState::Created,
+            now + chrono::Duration::seconds(60),
+            3
+        ));
+        // A new state is pushed right away
+        assert!(queue.is_due("T-1", TaskState::InProgress, now, 3));
+
+        queue.record_failure("T-1", TaskState::Created, "boom".into(), now, delay);
+        assert!(!queue.is_due(
+            "T-1",
+            TaskState::Created,
+            now + chrono::Duration::seconds(119),
+            3
+        ));
+        assert!(queue.is_due(
+            "T-1",
+            TaskState::Created,
+            now + chrono::Duration::seconds(120),
+            3
+        ));
+
+        queue.record_failure("T-1", TaskState::Created, "boom".into(), now, delay);
+        assert!(!queue.is_due(
+            "T-1",
+            TaskState::Created,
+            now + chrono::Duration::days(2),
+            3
+        ));
+        assert_eq!(queue.counts(3), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_sync_mirrors_tasks_and_retries_failures() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let database_url = format!("sqlite://{}", dir.path().join("sync.sqlite").display());
+        let repository = Arc::new(SqliteTaskRepository::new(&database_url).await.unwrap());
+        repository.migrate().await.unwrap();
+        let task = repository
+            .create(NewTask::new(
+                "SYNC-01".to_string(),
+                "Mirror me".to_string(),
+                "Outbound sync".to_string(),
+                None,
+            ))
+            .await
+            .unwrap();
+
+        let tracker = Arc::new(FakeTracker {
+            failures: AtomicU32::new(1),
+            ..FakeTracker::default()
+        });
+        let settings = SyncSettings {
+            states: HashMap::from([(TaskState::InProgress, "In Progress".to_string())]),
+            ..SyncSettings::default()
+        };
+        let sync = OutboundSync::new(repository.clone(), Box::new(tracker.clone()), settings);
+
+        let now = Utc::now();
+        let report = sync.sync_at(now).await.unwrap();
+        assert_eq!((report.failed, report.queued), (1, 1));
+        // Not retried before the backoff has passed
+        assert_eq!(sync.sync_at(now).await.unwrap().created, 0);
+
+        let later = now + chrono::Duration::minutes(2);
+        let report = sync.sync_at(later).await.unwrap();
+        assert_eq!((report.created, report.queued), (1, 0));
+        assert_eq!(*tracker.created.lock().unwrap(), vec!["SYNC-01"]);
+        // Created has no mapped status, so nothing was moved yet
+        assert!(tracker.updates.lock().unwrap().is_empty());
+        assert_eq!(
+            sync.sync_at(later).await.unwrap(),
+            OutboundSyncReport::default()
+        );
+
+        repository.claim_task(task.id, "backend-dev").await.unwrap();
+        let report = sync.sync_at(later).await.unwrap();
+        assert_eq!(report.updated, 1);
+        assert_eq!(
+            *tracker.updates.lock().unwrap(),
+            vec![("FAKE-1".to_string(), "In Progress".to_string())]
+        );
+        let links = repository.list_external_links("fake").await.unwrap();
+        assert_eq!(links[0].synced_state, Some(TaskState::InProgress));
+    }
+}
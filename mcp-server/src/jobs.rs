@@ -0,0 +1,412 @@
+use anyhow::Result;
+use chrono::Utc;
+use database::SqliteTaskRepository;
+use mcp_protocol::JobRegistry;
+use rand::Rng;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use task_core::{
+    check_done_policy, nudge_stuck_tasks, performance_report, record_anomalies,
+    sync_tasks_markdown, AnomalyThresholds, DonePolicy, MaintenanceOptions, RepositoryMetrics,
+    RetentionPolicy, StuckTaskPolicy, Task, TaskCrudRepository, TaskExtensionRepository,
+    WorkClaimRepository, DEFAULT_PERFORMANCE_BUCKET_DAYS, DEFAULT_PERFORMANCE_PERIOD_DAYS,
+};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::config::JobsConfig;
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+type JobFn = Arc<dyn Fn() -> JobFuture + Send + Sync>;
+
+struct ScheduledJob {
+    name: String,
+    interval: Duration,
+    jitter: Duration,
+    run: JobFn,
+}
+
+/// Runs registered jobs periodically and reports their status to a [`JobRegistry`]
+///
+/// Every job runs on its own tokio task. A random delay of up to the job's
+/// jitter is added to each interval so jobs registered together do not hit the
+/// database at the same moment.
+#[derive(Default)]
+pub struct JobScheduler {
+    registry: JobRegistry,
+    jobs: Vec<ScheduledJob>,
+}
+
+impl JobScheduler {
+    /// Create a scheduler without jobs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry that the scheduled jobs report into
+    pub fn registry(&self) -> JobRegistry {
+        self.registry.clone()
+    }
+
+    /// Register a job returning a short summary of what it did
+    pub fn register<F, Fut>(&mut self, name: &str, interval: Duration, jitter: Duration, job: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        self.registry.register(name, interval, jitter);
+        self.jobs.push(ScheduledJob {
+            name: name.to_string(),
+            interval,
+            jitter,
+            run: Arc::new(move || Box::pin(job())),
+        });
+    }
+
+    /// Spawn all registered jobs; they run until the returned handles are aborted
+    pub fn start(self) -> Vec<JoinHandle<()>> {
+        self.jobs
+            .into_iter()
+            .map(|job| {
+                let registry = self.registry.clone();
+                info!(
+                    "Scheduling background job {} every {:?}",
+                    job.name, job.interval
+                );
+                tokio::spawn(async move {
+                    loop {
+                        let delay = job.interval + random_jitter(job.jitter);
+                        if let Ok(delay_chrono) = chrono::Duration::from_std(delay) {
+                            registry.record_scheduled(&job.name, Utc::now() + delay_chrono);
+                        }
+                        tokio::time::sleep(delay).await;
+                        run_job(&registry, &job).await;
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Run a job once, recording its outcome; panics are reported as failures
+async fn run_job(registry: &JobRegistry, job: &ScheduledJob) {
+    debug!("Running background job {}", job.name);
+    registry.record_started(&job.name);
+    let started = Instant::now();
+
+    let result = match tokio::spawn((job.run)()).await {
+        Ok(Ok(summary)) => Ok(summary),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(format!("job panicked: {e}")),
+    };
+
+    if let Err(ref error) = result {
+        warn!("Background job {} failed: {}", job.name, error);
+    }
+    registry.record_finished(&job.name, result, started.elapsed());
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::rng().random_range(0..=max.as_millis() as u64))
+}
+
+/// Create the scheduler with the server's built-in maintenance jobs
+pub fn create_job_scheduler(
+    repository: Arc<SqliteTaskRepository>,
+    config: &JobsConfig,
+    retention: RetentionPolicy,
+    anomaly_thresholds: AnomalyThresholds,
+) -> JobScheduler {
+    let mut scheduler = JobScheduler::new();
+    let jitter = Duration::from_secs(config.jitter);
+
+    if config.task_timeout_cleanup_interval > 0 {
+        let repository = repository.clone();
+        let timeout_minutes = config.task_timeout_minutes;
+        scheduler.register(
+            "cleanup_timed_out_tasks",
+            Duration::from_secs(config.task_timeout_cleanup_interval),
+            jitter,
+            move || {
+                let repository = repository.clone();
+                async move {
+                    let released = repository.cleanup_timed_out_tasks(timeout_minutes).await?;
+                    Ok(format!("Released {} timed-out task(s)", released.len()))
+                }
+            },
+        );
+    }
+
+    if config.retention_interval > 0 && retention.is_enabled() {
+        let repository = repository.clone();
+        scheduler.register(
+            "apply_retention_policies",
+            Duration::from_secs(config.retention_interval),
+            jitter,
+            move || {
+                let repository = repository.clone();
+                async move {
+                    let report = repository.apply_retention(&retention, false).await?;
+                    Ok(format!(
+                        "Purged {} task message(s), {} work session(s), {} comment(s), {} tool call(s), \
+                         {} system event(s)",
+                        report.task_messages,
+                        report.work_sessions,
+                        report.task_comments,
+                        report.tool_calls,
+                        report.system_events
+                    ))
+                }
+            },
+        );
+    }
+
+    if config.archive_interval > 0 && repository.has_archive() {
+        let repository = repository.clone();
+        scheduler.register(
+            "archive_tasks",
+            Duration::from_secs(config.archive_interval),
+            jitter,
+            move || {
+                let repository = repository.clone();
+                async move {
+                    let report = repository.move_archived_tasks().await?;
+                    Ok(format!(
+                        "Moved {} archived task(s) and {} related row(s)",
+                        report.tasks, report.related_rows
+                    ))
+                }
+            },
+        );
+    }
+
+    if config.anomaly_check_interval > 0 {
+        let repository = repository.clone();
+        scheduler.register(
+            "flag_agent_anomalies",
+            Duration::from_secs(config.anomaly_check_interval),
+            jitter,
+            move || {
+                let repository = repository.clone();
+                async move {
+                    let now = Utc::now();
+                    let start =
+                        now - chrono::Duration::days(i64::from(DEFAULT_PERFORMANCE_PERIOD_DAYS));
+                    let report = performance_report(
+                        repository.as_ref(),
+                        start,
+                        now,
+                        DEFAULT_PERFORMANCE_BUCKET_DAYS,
+                        &anomaly_thresholds,
+                    )
+                    .await?;
+                    let recorded =
+                        record_anomalies(repository.as_ref(), &report.anomalies, now).await?;
+                    Ok(format!(
+                        "Found {} anomaly(ies), recorded {} new system event(s)",
+                        report.anomalies.len(),
+                        recorded
+                    ))
+                }
+            },
+        );
+    }
+
+    if config.maintenance_interval > 0 {
+        scheduler.register(
+            "maintain_database",
+            Duration::from_secs(config.maintenance_interval),
+            jitter,
+            move || {
+                let repository = repository.clone();
+                async move {
+                    let report = repository
+                        .maintain_database(&MaintenanceOptions::default())
+                        .await?;
+                    if report.integrity_ok == Some(false) {
+                        anyhow::bail!(
+                            "Integrity check failed: {}",
+                            report.integrity_findings.join("; ")
+                        );
+                    }
+                    Ok(format!(
+                        "Reclaimed {} byte(s), database is {} byte(s)",
+                        report.reclaimed_bytes(),
+                        report.size_after_bytes
+                    ))
+                }
+            },
+        );
+    }
+
+    scheduler
+}
+
+/// Register a job snapshotting the task board for point-in-time reconstruction
+pub fn register_history_snapshot_job(
+    scheduler: &mut JobScheduler,
+    repository: Arc<SqliteTaskRepository>,
+    interval: Duration,
+    jitter: Duration,
+) {
+    scheduler.register("snapshot_task_history", interval, jitter, move || {
+        let repository = repository.clone();
+        async move {
+            let tasks = repository.take_history_snapshot().await?;
+            Ok(format!("Snapshotted {tasks} task(s)"))
+        }
+    });
+}
+
+/// Register a job nudging the owners of stuck tasks and escalating to the coordinator
+pub fn register_stuck_task_job(
+    scheduler: &mut JobScheduler,
+    repository: Arc<SqliteTaskRepository>,
+    policy: StuckTaskPolicy,
+    interval: Duration,
+    jitter: Duration,
+) {
+    scheduler.register("nudge_stuck_tasks", interval, jitter, move || {
+        let repository = repository.clone();
+        let policy = policy.clone();
+        async move {
+            let report = nudge_stuck_tasks(
+                repository.as_ref(),
+                repository.as_ref(),
+                &policy,
+                Utc::now(),
+            )
+            .await?;
+            Ok(format!(
+                "Found {} stuck task(s), sent {} nudge(s) and {} escalation(s)",
+                report.stuck.len(),
+                report.nudged,
+                report.escalated
+            ))
+        }
+    });
+}
+
+/// Register a job syncing `.axon/tasks.md` in the project root with the database
+///
+/// Tasks the job moves to a new state are announced on `state_changes`.
+pub fn register_tasks_markdown_sync_job(
+    scheduler: &mut JobScheduler,
+    repository: Arc<SqliteTaskRepository>,
+    done_policy: DonePolicy,
+    project_root: PathBuf,
+    state_changes: broadcast::Sender<Task>,
+    interval: Duration,
+    jitter: Duration,
+) {
+    scheduler.register("sync_tasks_markdown", interval, jitter, move || {
+        let repository = repository.clone();
+        let done_policy = done_policy.clone();
+        let project_root = project_root.clone();
+        let state_changes = state_changes.clone();
+        async move {
+            let repository = repository.as_ref();
+            let sync = sync_tasks_markdown(repository, &project_root, |task, state| {
+                let task = task.clone();
+                let (done_policy, state_changes) = (&done_policy, &state_changes);
+                async move {
+                    check_done_policy(done_policy, repository, repository, &task, state).await?;
+                    let changed = repository.set_state(task.id, state).await?;
+                    let _ = state_changes.send(changed.clone());
+                    Ok(changed)
+                }
+            })
+            .await?;
+            Ok(format!(
+                "Applied {} edit(s) from {}, {} conflict(s), {} rejected; wrote {} task(s)",
+                sync.applied.len(),
+                sync.path,
+                sync.conflicts.len(),
+                sync.rejected.len(),
+                sync.tasks_written
+            ))
+        }
+    });
+}
+
+/// Register a job summarizing repository call metrics; per-method figures are logged at debug level
+pub fn register_repository_metrics_job(
+    scheduler: &mut JobScheduler,
+    metrics: RepositoryMetrics,
+    interval: Duration,
+    jitter: Duration,
+) {
+    scheduler.register("repository_metrics", interval, jitter, move || {
+        let metrics = metrics.clone();
+        async move {
+            let snapshot = metrics.snapshot();
+            for (method, stats) in &snapshot {
+                debug!(
+                    "Repository method {}: {} call(s), {} error(s), {:?} average",
+                    method,
+                    stats.calls,
+                    stats.errors,
+                    stats.average_time()
+                );
+            }
+            let totals = metrics.totals();
+            Ok(format!(
+                "{} repository call(s), {} error(s) across {} method(s), {:?} average",
+                totals.calls,
+                totals.errors,
+                snapshot.len(),
+                totals.average_time()
+            ))
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_protocol::JobOutcome;
+
+    #[tokio::test]
+    async fn test_jobs_report_status() {
+        let mut scheduler = JobScheduler::new();
+        scheduler.register("ok", Duration::from_millis(10), Duration::ZERO, || async {
+            Ok("done".to_string())
+        });
+        scheduler.register(
+            "failing",
+            Duration::from_millis(10),
+            Duration::ZERO,
+            || async { Err(anyhow::anyhow!("boom")) },
+        );
+        let registry = scheduler.registry();
+
+        let handles = scheduler.start();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handles.iter().for_each(|h| h.abort());
+
+        let jobs = registry.snapshot();
+        let failing = jobs.iter().find(|j| j.name == "failing").unwrap();
+        assert!(failing.failure_count >= 1);
+        assert_eq!(failing.last_error.as_deref(), Some("boom"));
+
+        let ok = jobs.iter().find(|j| j.name == "ok").unwrap();
+        assert!(ok.run_count >= 1);
+        assert_eq!(ok.failure_count, 0);
+        assert_ne!(ok.last_outcome, JobOutcome::Failed);
+    }
+
+    #[test]
+    fn test_random_jitter_is_bounded() {
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(random_jitter(Duration::from_millis(50)) <= Duration::from_millis(50));
+        }
+    }
+}
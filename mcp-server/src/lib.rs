@@ -4,10 +4,22 @@
 //! task management server. It includes configuration management, database setup,
 //! and server initialization.
 
+pub mod advertisement;
 pub mod config;
+pub mod digest;
+pub mod discovery_script;
+pub mod embeddings;
+pub mod hooks;
+pub mod init;
+pub mod integrations;
+pub mod jobs;
 pub mod setup;
+pub mod summaries;
 pub mod telemetry;
 
 pub use config::Config;
-pub use setup::{create_repository, create_server, ensure_database_directory, initialize_app};
+pub use jobs::{create_job_scheduler, JobScheduler};
+pub use setup::{
+    create_repository, create_server, ensure_database_directory, initialize_app, AppServer,
+};
 pub use telemetry::init_telemetry;
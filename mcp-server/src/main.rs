@@ -1,32 +1,64 @@
+mod advertisement;
 mod config;
+mod digest;
+mod discovery_script;
+mod embeddings;
+mod hooks;
+mod init;
+mod integrations;
+mod jobs;
+mod output;
 mod self_update;
 mod setup;
+mod summaries;
 mod telemetry;
 
+use advertisement::ServerAdvertisement;
 use anyhow::{Context, Result};
-use clap::Parser;
-use config::Config;
-use setup::{
-    ensure_database_directory_from_config,
-    initialize_app,
-};
-use telemetry::{
-    init_telemetry, log_config_validation, log_startup_info,
+use chrono::{DateTime, Utc};
+use clap::builder::FalseyValueParser;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use config::{Config, LogFormat};
+use database::SqliteTaskRepository;
+use digest::{send_digest, SmtpMailer};
+use integrations::{GitHubBridge, GitHubClient};
+use mcp_protocol::{claude_settings, ListenerConfig};
+use output::{OutputMode, Printer};
+use serde_json::json;
+use setup::{ensure_database_directory_from_config, initialize_app};
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use task_core::protocol::DEFAULT_WORKSPACE_ID;
+use task_core::{
+    agent_file_path, agent_file_problems, http_server_entry, render_subagent_file,
+    verify_workspace, AuditLogFilter, CheckStatus, CsvExport, MessageCatalog, MessageKey,
+    MigrationStatus, SessionCsvColumn, TaskCrudRepository, TaskCsvColumn, TaskExtensionRepository,
+    TaskFilter, WorkSessionFilter, WorkSessionRepository, WorkspaceContextRepository,
 };
+use telemetry::{init_telemetry, log_config_validation, log_startup_info};
 use tracing::{error, info};
-use std::path::Path;
 
 #[derive(Parser)]
 #[command(name = "axon-mcp")]
 #[command(about = "MCP Task Management Server - HTTP Only")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(group(
+    ArgGroup::new("export").args(["export_audit_log", "export_tasks_csv", "export_sessions_csv"])
+))]
+#[command(group(ArgGroup::new("csv_export").args(["export_tasks_csv", "export_sessions_csv"])))]
+#[command(group(ArgGroup::new("previewable").args(["migrate", "register_with_claude_code"])))]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Start HTTP MCP server
     #[arg(long)]
     start: bool,
 
     /// Port to listen on
-    #[arg(long, default_value = "3000")]
+    #[arg(long, env = "PORT", default_value = "3000", global = true)]
     port: u16,
 
     /// Project name for database scoping (creates axon.PROJECT_NAME.sqlite)
@@ -34,7 +66,7 @@ struct Cli {
     project: Option<String>,
 
     /// Project root directory (required - will create .axon/ and .claude/ subdirectories)
-    #[arg(long, env = "PROJECT_ROOT")]
+    #[arg(long, env = "PROJECT_ROOT", global = true)]
     project_root: Option<String>,
 
     /// Configuration file path
@@ -56,6 +88,137 @@ struct Cli {
     /// Check for updates and install if available
     #[arg(long = "self-update")]
     self_update: bool,
+
+    /// Apply pending database schema migrations and exit
+    #[arg(long)]
+    migrate: bool,
+
+    /// Print applied and pending schema migrations and exit (exit code 1 if any are pending)
+    #[arg(long = "migration-status", conflicts_with = "migrate")]
+    migration_status: bool,
+
+    /// With --migrate, print the SQL of pending migrations instead of applying them;
+    /// with --register-with-claude-code, print the entry without writing it
+    #[arg(long = "dry-run", requires = "previewable")]
+    dry_run: bool,
+
+    /// Open a database migrated by a newer axon-mcp instead of refusing to start
+    #[arg(long = "allow-downgrade")]
+    allow_downgrade: bool,
+
+    /// Serve lookups only, rejecting every tool that changes tasks, messages or agents
+    #[arg(long = "read-only")]
+    read_only: bool,
+
+    /// Container mode: listen on 0.0.0.0 and keep the database in /data unless
+    /// DATABASE_URL is set; --project-root and --project become optional
+    #[arg(long, env = "AXON_CONTAINER", value_parser = FalseyValueParser::new())]
+    container: bool,
+
+    /// Record every JSON-RPC request and response to .axon/recordings/ for replay
+    #[arg(long)]
+    record: bool,
+
+    /// Check that the server on --port answers /health and exit (code 1 if not)
+    #[arg(long)]
+    healthcheck: bool,
+
+    /// Add or update the server entry (at --port) in the Claude Code MCP settings and exit
+    #[arg(long = "register-with-claude-code")]
+    register_with_claude_code: bool,
+
+    /// Claude Code settings file to register in (default: ~/.claude.json)
+    #[arg(long = "claude-settings", value_name = "FILE", global = true)]
+    claude_settings: Option<PathBuf>,
+
+    /// Write the audit log as JSON Lines to FILE ("-" for stdout) and exit
+    #[arg(long = "export-audit-log", value_name = "FILE")]
+    export_audit_log: Option<String>,
+
+    /// Write tasks as CSV to FILE ("-" for stdout) and exit
+    #[arg(long = "export-tasks-csv", value_name = "FILE")]
+    export_tasks_csv: Option<String>,
+
+    /// Write work sessions as CSV to FILE ("-" for stdout) and exit
+    #[arg(long = "export-sessions-csv", value_name = "FILE")]
+    export_sessions_csv: Option<String>,
+
+    /// With a CSV export, comma-separated columns in output order
+    #[arg(long, value_delimiter = ',', requires = "csv_export")]
+    columns: Vec<String>,
+
+    /// With a CSV export, only tasks owned by or sessions of this agent
+    #[arg(long, requires = "csv_export")]
+    agent: Option<String>,
+
+    /// Import GitHub issues and push task state changes once, then exit
+    #[arg(long = "sync-github")]
+    sync_github: bool,
+
+    /// Send the email digest configured under [digest] once, then exit
+    #[arg(long = "send-digest")]
+    send_digest: bool,
+
+    /// With an export, only records at or after this RFC 3339 time
+    #[arg(long, requires = "export")]
+    since: Option<DateTime<Utc>>,
+
+    /// With an export, only records before this RFC 3339 time
+    #[arg(long, requires = "export")]
+    until: Option<DateTime<Utc>>,
+
+    /// Format of stdout: text with emoji, text without emoji, or JSON lines (JSON logs too)
+    #[arg(long, value_enum, default_value_t = OutputMode::Pretty, env = "AXON_OUTPUT")]
+    output: OutputMode,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create .axon/, a starter .axon/config.toml and docs/PRD.md in the project root
+    Init {
+        /// Register the server (at --port) in the Claude Code MCP settings
+        #[arg(long = "register-with-claude-code")]
+        register_with_claude_code: bool,
+    },
+    /// Check the manifest, agent files, CLAUDE.md and database schema of the project
+    Doctor {
+        /// Write missing or invalid sub-agent files from the agent registrations
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Write an OpenAPI document and JSON Schemas of every tool's params and result
+    GenerateApiSpec {
+        /// Directory for openapi.json and schemas/
+        #[arg(long, value_name = "DIR", default_value = "api-spec")]
+        out: PathBuf,
+    },
+    /// Write a typed client of the HTTP transport for TypeScript or Python
+    GenClient {
+        /// Language of the client
+        #[arg(long, value_enum)]
+        lang: ClientLang,
+        /// File to write; axon-client.ts or axon_client.py in the current directory by default
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Language of `gen-client`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClientLang {
+    /// TypeScript, using fetch
+    Ts,
+    /// Python 3.11+, using urllib
+    Py,
+}
+
+impl From<ClientLang> for mcp_protocol::ClientLanguage {
+    fn from(lang: ClientLang) -> Self {
+        match lang {
+            ClientLang::Ts => Self::TypeScript,
+            ClientLang::Py => Self::Python,
+        }
+    }
 }
 
 fn load_config(cli: &Cli) -> Result<Config> {
@@ -70,6 +233,15 @@ fn load_config(cli: &Cli) -> Result<Config> {
         }
     };
 
+    if let Some(ref project_root) = cli.project_root {
+        config.project.root = Some(project_root.clone());
+    }
+
+    // Per-project settings override the global file and environment
+    if let Some(path) = config.apply_project_config()? {
+        info!("Applied project configuration from {}", path.display());
+    }
+
     // Apply CLI overrides for database URL
     if let Some(ref database_url) = cli.database_url {
         info!("Overriding database URL from CLI");
@@ -86,14 +258,31 @@ fn load_config(cli: &Cli) -> Result<Config> {
         }
     }
 
+    if cli.allow_downgrade {
+        config.database.allow_downgrade = true;
+    }
+
+    if cli.read_only {
+        config.server.read_only = true;
+    }
+
+    if cli.container {
+        config.server.container = true;
+    }
+
+    if cli.record {
+        config.server.record = true;
+    }
+
     // Apply CLI overrides for server address
     if let Some(ref listen_addr) = cli.listen_addr {
         config.server.listen_addr = listen_addr.clone();
     }
-    
+
     // Override port from CLI
     config.server.port = cli.port;
-    
+    config.apply_container_defaults();
+
     info!("Server will listen on: {}", config.server_address());
 
     if let Some(ref log_level) = cli.log_level {
@@ -101,20 +290,501 @@ fn load_config(cli: &Cli) -> Result<Config> {
         config.logging.level = log_level.clone();
     }
 
+    if cli.output == OutputMode::Json {
+        config.logging.format = LogFormat::Json;
+    }
+
     Ok(config)
 }
 
+/// Handle --migrate and --migration-status without starting the server
+async fn run_migration_command(cli: &Cli, config: &Config) -> Result<()> {
+    let printer = Printer::new(cli.output);
+    ensure_database_directory_from_config(config).context("Failed to create database directory")?;
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+
+    if cli.migration_status {
+        let status = repository
+            .migration_status()
+            .await
+            .context("Failed to read migration status")?;
+        print_migration_status(printer, &status, &config.messages);
+        if !status.is_up_to_date() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let pending = repository
+        .pending_migrations()
+        .await
+        .context("Failed to read pending migrations")?;
+    if pending.is_empty() {
+        let message = config.messages.format(MessageKey::CliSchemaUpToDate, &[]);
+        printer.success("schema_up_to_date", &message, json!({}));
+        return Ok(());
+    }
+
+    if cli.dry_run {
+        for migration in &pending {
+            printer.text(&format!(
+                "-- Migration {}: {}\n{}\n",
+                migration.version,
+                migration.description,
+                migration.sql.trim_end()
+            ));
+        }
+        let summary = config.messages.format(
+            MessageKey::CliMigrationsDryRun,
+            &[("count", &pending.len())],
+        );
+        let migrations: Vec<_> = pending
+            .iter()
+            .map(|m| json!({"version": m.version, "description": m.description, "sql": m.sql}))
+            .collect();
+        match printer.mode() {
+            OutputMode::Json => printer.info(
+                "pending_migrations",
+                &summary,
+                json!({"migrations": migrations}),
+            ),
+            _ => printer.text(&format!("-- {summary}")),
+        }
+        return Ok(());
+    }
+
+    repository
+        .migrate()
+        .await
+        .context("Failed to run database migrations")?;
+    let applied = config.messages.format(
+        MessageKey::CliMigrationsApplied,
+        &[("count", &pending.len())],
+    );
+    printer.success(
+        "migrations_applied",
+        &applied,
+        json!({"count": pending.len()}),
+    );
+    Ok(())
+}
+
+/// Handle --export-audit-log without starting the server
+async fn run_audit_export(cli: &Cli, config: &Config, path: &str) -> Result<()> {
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+    let entries = repository
+        .audit_log(&AuditLogFilter {
+            since: cli.since,
+            until: cli.until,
+            ..Default::default()
+        })
+        .await
+        .context("Failed to read audit log")?;
+
+    let output: Box<dyn Write> = if path == "-" {
+        Box::new(std::io::stdout().lock())
+    } else {
+        Box::new(std::fs::File::create(path).with_context(|| format!("Failed to create {path}"))?)
+    };
+    let mut output = BufWriter::new(output);
+    for entry in &entries {
+        serde_json::to_writer(&mut output, entry).context("Failed to serialize audit entry")?;
+        output.write_all(b"\n")?;
+    }
+    output.flush()?;
+
+    if path != "-" {
+        let exported = config.messages.format(
+            MessageKey::CliAuditExported,
+            &[("count", &entries.len()), ("path", &path)],
+        );
+        Printer::new(cli.output).success(
+            "audit_log_exported",
+            &exported,
+            json!({"count": entries.len(), "path": path}),
+        );
+    }
+    Ok(())
+}
+
+/// Handle --export-tasks-csv and --export-sessions-csv without starting the server
+async fn run_csv_export(cli: &Cli, config: &Config) -> Result<()> {
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+
+    let (path, export) = if let Some(path) = &cli.export_tasks_csv {
+        let columns = cli
+            .columns
+            .iter()
+            .map(|column| column.parse::<TaskCsvColumn>())
+            .collect::<task_core::Result<Vec<_>>>()?;
+        let tasks = repository
+            .list(TaskFilter {
+                owner: cli.agent.clone(),
+                date_from: cli.since,
+                date_to: cli.until,
+                ..TaskFilter::default()
+            })
+            .await
+            .context("Failed to read tasks")?;
+        (path, CsvExport::tasks(&tasks, &columns, None))
+    } else if let Some(path) = &cli.export_sessions_csv {
+        let columns = cli
+            .columns
+            .iter()
+            .map(|column| column.parse::<SessionCsvColumn>())
+            .collect::<task_core::Result<Vec<_>>>()?;
+        let sessions = repository
+            .list_work_sessions(&WorkSessionFilter {
+                agent_name: cli.agent.clone(),
+                since: cli.since,
+                until: cli.until,
+                ..Default::default()
+            })
+            .await
+            .context("Failed to read work sessions")?;
+        let task_codes: HashMap<i32, String> = repository
+            .list(TaskFilter::default())
+            .await
+            .context("Failed to read tasks")?
+            .into_iter()
+            .map(|task| (task.id, task.code))
+            .collect();
+        (
+            path,
+            CsvExport::sessions(&sessions, &task_codes, &columns, None),
+        )
+    } else {
+        return Ok(());
+    };
+
+    if path == "-" {
+        std::io::stdout()
+            .lock()
+            .write_all(export.content.as_bytes())?;
+    } else {
+        std::fs::write(path, &export.content).with_context(|| format!("Failed to write {path}"))?;
+        let exported = config.messages.format(
+            MessageKey::CliRowsExported,
+            &[("count", &export.rows), ("path", path)],
+        );
+        Printer::new(cli.output).success(
+            "csv_exported",
+            &exported,
+            json!({"count": export.rows, "path": path}),
+        );
+    }
+    Ok(())
+}
+
+/// Handle --send-digest without starting the server
+async fn run_send_digest(config: &Config, printer: Printer) -> Result<()> {
+    let digest = config
+        .digest
+        .as_ref()
+        .context("Email digest is not configured ([digest])")?;
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+    let mailer = SmtpMailer::new(digest)?;
+    let report = send_digest(&repository, &mailer, digest, Utc::now()).await?;
+    let sent = config.messages.format(
+        MessageKey::CliDigestSent,
+        &[
+            ("frequency", &digest.frequency),
+            ("recipients", &digest.recipients.len()),
+            ("completed", &report.completed.len()),
+            ("blocked", &report.blocked.len()),
+        ],
+    );
+    printer.success(
+        "digest_sent",
+        &sent,
+        json!({
+            "frequency": digest.frequency.to_string(),
+            "recipients": digest.recipients.len(),
+            "completed": report.completed.len(),
+            "blocked": report.blocked.len(),
+        }),
+    );
+    Ok(())
+}
+
+/// Handle --sync-github without starting the server
+async fn run_github_sync(config: &Config, printer: Printer) -> Result<()> {
+    let github = config
+        .integrations
+        .github
+        .clone()
+        .context("GitHub integration is not configured ([integrations.github])")?;
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+    let client = GitHubClient::new(&github)?;
+    let report = GitHubBridge::new(github, Arc::new(repository), client)
+        .sync()
+        .await
+        .context("GitHub sync failed")?;
+    printer.success("github_synced", &report.to_string(), json!(report));
+    Ok(())
+}
+
+/// Project root of CLI commands: --project-root, or the current directory
+fn cli_project_root(cli: &Cli) -> Result<PathBuf> {
+    match &cli.project_root {
+        Some(project_root) => Ok(PathBuf::from(project_root)),
+        None => std::env::current_dir().context("Failed to determine current directory"),
+    }
+}
+
+/// Claude Code settings file of CLI commands: --claude-settings, or ~/.claude.json
+fn cli_claude_settings(cli: &Cli) -> Result<PathBuf> {
+    match &cli.claude_settings {
+        Some(path) => Ok(path.clone()),
+        None => claude_settings::default_settings_path()
+            .context("Cannot locate the home directory; pass --claude-settings"),
+    }
+}
+
+/// Write the OpenAPI document and the standalone tool schemas to `out`
+fn run_generate_api_spec(out: &Path, printer: Printer) -> Result<()> {
+    let written = mcp_protocol::write_api_spec(out, env!("CARGO_PKG_VERSION"))
+        .with_context(|| format!("Failed to write the API spec to {}", out.display()))?;
+    let message = format!(
+        "Wrote the OpenAPI document and {} schema(s) to {}",
+        written.len() - 1,
+        out.display()
+    );
+    printer.success("api_spec_generated", &message, json!({ "files": written }));
+    Ok(())
+}
+
+/// Write a typed client for `lang` to `out`
+fn run_gen_client(lang: ClientLang, out: Option<&Path>, printer: Printer) -> Result<()> {
+    let language = mcp_protocol::ClientLanguage::from(lang);
+    let path = out.unwrap_or(Path::new(language.file_name()));
+    let source = mcp_protocol::generate_client(language, env!("CARGO_PKG_VERSION"));
+    std::fs::write(path, source)
+        .with_context(|| format!("Failed to write the client to {}", path.display()))?;
+    let message = format!(
+        "Wrote the {} client to {}",
+        language.as_str(),
+        path.display()
+    );
+    printer.success("client_generated", &message, json!({ "file": path }));
+    Ok(())
+}
+
+/// Bootstrap the project root (current directory unless --project-root is given)
+fn run_init(cli: &Cli, register_with_claude_code: bool, printer: Printer) -> Result<()> {
+    let project_root = cli_project_root(cli)?;
+    let claude_settings = match register_with_claude_code {
+        true => Some(cli_claude_settings(cli)?),
+        false => None,
+    };
+    let options = init::InitOptions {
+        port: cli.port,
+        claude_settings,
+    };
+    let report =
+        init::init_workspace(&project_root, &options).context("Failed to initialize workspace")?;
+
+    let message = format!("Workspace initialized in {}", project_root.display());
+    printer.success("workspace_initialized", &message, json!(report));
+    for path in &report.created {
+        printer.bullet(&format!("Created {}", path.display()));
+    }
+    for path in &report.existing {
+        printer.bullet(&format!("Kept existing {}", path.display()));
+    }
+    if let Some(registration) = &report.registration {
+        printer.bullet(&format!(
+            "Registered axon-mcp in {}",
+            registration.settings_path
+        ));
+    }
+    Ok(())
+}
+
+/// Verify the workspace of the project root, optionally writing missing agent files
+async fn run_doctor(cli: &Cli, config: &Config, fix: bool, printer: Printer) -> Result<()> {
+    let project_root = cli_project_root(cli)?;
+    let repository = SqliteTaskRepository::new(&config.database_url())
+        .await
+        .context("Failed to open database")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade);
+    let agents = match setup::create_workspace_context_repository(config).await {
+        Ok(contexts) => contexts
+            .get_by_id(DEFAULT_WORKSPACE_ID)
+            .await
+            .ok()
+            .flatten()
+            .map(|context| context.registered_agents)
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    if fix {
+        for (name, _) in agent_file_problems(&project_root, &agents) {
+            let Some(agent) = agents.iter().find(|agent| agent.name == name) else {
+                continue;
+            };
+            let path = agent_file_path(&project_root, agent);
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            std::fs::write(&path, render_subagent_file(agent))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            printer.bullet(&format!("Wrote {}", path.display()));
+        }
+    }
+
+    let known_functions = mcp_protocol::tool_names();
+    let verification = verify_workspace(
+        &project_root,
+        &agents,
+        &known_functions,
+        repository.migration_status().await,
+    );
+    for check in &verification.checks {
+        let fields = json!(check);
+        match check.status {
+            CheckStatus::Pass => printer.success(&check.name, &check.message, fields),
+            CheckStatus::Warn => printer.warning(&check.name, &check.message, fields),
+            CheckStatus::Fail => printer.error(&check.name, &check.message, fields),
+        }
+        if let Some(fix) = &check.fix {
+            printer.bullet(fix);
+        }
+    }
+    if !verification.healthy {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Probe the /health endpoint of a running server, for container healthchecks
+async fn run_healthcheck(config: &Config, printer: Printer) -> Result<()> {
+    let host = match config.server.listen_addr.as_str() {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1",
+        host => host,
+    };
+    let url = format!("http://{}:{}/health", host, config.server.port);
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(3))
+        .build()?;
+    match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => {
+            printer.success("healthy", &format!("{url} is healthy"), json!({"url": url}));
+            Ok(())
+        }
+        Ok(response) => {
+            let message = format!("{url} answered {}", response.status());
+            printer.error("unhealthy", &message, json!({"url": url}));
+            std::process::exit(1);
+        }
+        Err(e) => {
+            let message = format!("{url} is unreachable: {e}");
+            printer.error("unhealthy", &message, json!({"url": url}));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Register the server of the project root in the Claude Code settings
+fn run_register_with_claude_code(cli: &Cli, printer: Printer) -> Result<()> {
+    let project_root = cli_project_root(cli)?
+        .canonicalize()
+        .context("Failed to resolve project root")?;
+    let registration = claude_settings::register_with_claude_code(
+        &cli_claude_settings(cli)?,
+        &project_root,
+        http_server_entry(cli.port),
+        cli.dry_run,
+    )?;
+
+    let message = match (registration.dry_run, registration.changed) {
+        (_, false) => format!("Already registered in {}", registration.settings_path),
+        (true, true) => format!("Would register in {}", registration.settings_path),
+        (false, true) => format!("Registered in {}", registration.settings_path),
+    };
+    printer.success("claude_code_registered", &message, json!(registration));
+    if registration.changed {
+        printer.text(&serde_json::to_string_pretty(&registration.entry)?);
+    }
+    if let Some(backup) = &registration.backup_path {
+        printer.bullet(&format!("Previous settings saved to {backup}"));
+    }
+    Ok(())
+}
+
+fn print_migration_status(printer: Printer, status: &MigrationStatus, messages: &MessageCatalog) {
+    let latest = status.latest_version;
+    let version = match status.current_version {
+        Some(version) => messages.format(
+            MessageKey::CliSchemaVersion,
+            &[("version", &version), ("latest", &latest)],
+        ),
+        None => messages.format(MessageKey::CliSchemaVersionNone, &[("latest", &latest)]),
+    };
+    if printer.mode() == OutputMode::Json {
+        printer.info("migration_status", &version, json!(status));
+        return;
+    }
+
+    for migration in &status.migrations {
+        let state = match migration.applied_at {
+            Some(_) if migration.checksum_mismatch => "modified".to_string(),
+            Some(applied_at) => format!("applied {}", applied_at.format("%Y-%m-%d %H:%M:%S")),
+            None => "pending".to_string(),
+        };
+        printer.text(&format!(
+            "{:>4}  {:<45} {}",
+            migration.version, migration.description, state
+        ));
+    }
+    printer.text("");
+    printer.info("migration_status", &version, json!({}));
+    if status.migrations.iter().any(|m| m.checksum_mismatch) {
+        let message = messages.format(MessageKey::CliMigrationsModified, &[]);
+        printer.warning("migrations_modified", &message, json!({}));
+    }
+}
+
 /// Create .axon and .claude directories in project root
 fn create_project_directories(project_root: &str) -> Result<()> {
     let project_path = Path::new(project_root);
-    
+
     // Validate project root exists
     if !project_path.exists() {
-        return Err(anyhow::anyhow!("Project root directory does not exist: {}", project_root));
+        return Err(anyhow::anyhow!(
+            "Project root directory does not exist: {}",
+            project_root
+        ));
     }
-    
+
     if !project_path.is_dir() {
-        return Err(anyhow::anyhow!("Project root is not a directory: {}", project_root));
+        return Err(anyhow::anyhow!(
+            "Project root is not a directory: {}",
+            project_root
+        ));
     }
 
     // Create .axon directory
@@ -125,7 +795,7 @@ fn create_project_directories(project_root: &str) -> Result<()> {
         info!("Created .axon directory: {:?}", axon_dir);
     }
 
-    // Create .claude directory  
+    // Create .claude directory
     let claude_dir = project_path.join(".claude");
     if !claude_dir.exists() {
         std::fs::create_dir_all(&claude_dir)
@@ -143,49 +813,137 @@ async fn main() -> Result<()> {
 
     // Parse CLI arguments
     let cli = Cli::parse();
+    let printer = Printer::new(cli.output);
 
     // Handle special commands first
+    if let Some(Command::Init {
+        register_with_claude_code,
+    }) = &cli.command
+    {
+        return run_init(&cli, *register_with_claude_code, printer);
+    }
+
+    if let Some(Command::Doctor { fix }) = &cli.command {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_doctor(&cli, &config, *fix, printer).await;
+    }
+
+    if let Some(Command::GenerateApiSpec { out }) = &cli.command {
+        return run_generate_api_spec(out, printer);
+    }
+    if let Some(Command::GenClient { lang, out }) = &cli.command {
+        return run_gen_client(*lang, out.as_deref(), printer);
+    }
+
+    if cli.register_with_claude_code {
+        return run_register_with_claude_code(&cli, printer);
+    }
+
+    if cli.healthcheck {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_healthcheck(&config, printer).await;
+    }
+
     if cli.self_update {
         return self_update::self_update(env!("CARGO_PKG_VERSION")).await;
     }
 
+    if cli.migrate || cli.migration_status {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_migration_command(&cli, &config).await;
+    }
+
+    if let Some(path) = &cli.export_audit_log {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_audit_export(&cli, &config, path).await;
+    }
+
+    if cli.sync_github {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_github_sync(&config, printer).await;
+    }
+
+    if cli.send_digest {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_send_digest(&config, printer).await;
+    }
+
+    if cli.export_tasks_csv.is_some() || cli.export_sessions_csv.is_some() {
+        let config = load_config(&cli).context("Failed to load configuration")?;
+        return run_csv_export(&cli, &config).await;
+    }
+
     // Require --start flag
     if !cli.start {
-        println!("🚀 Axon MCP Server - HTTP Only");
-        println!();
-        println!("Usage:");
-        println!("  axon-mcp --start --port=8888 --project=my-project-name --project-root=/path/to/project");
-        println!();
-        println!("This will:");
-        println!("  • Start HTTP MCP server on port 8888");
-        println!("  • Use database: /path/to/project/.axon/axon.my-project-name.sqlite");
-        println!("  • Create .axon/ and .claude/ directories in project root");
-        println!("  • Enable structured request logging");
-        println!();
-        println!("For more options, use: axon-mcp --help");
+        printer.heading("🚀", "Axon MCP Server - HTTP Only");
+        printer.text("");
+        printer.text("Usage:");
+        printer.text("  axon-mcp --start --port=8888 --project=my-project-name --project-root=/path/to/project");
+        printer.text("");
+        printer.text("This will:");
+        printer.bullet("Start HTTP MCP server on port 8888");
+        printer.bullet("Use database: /path/to/project/.axon/axon.my-project-name.sqlite");
+        printer.bullet("Create .axon/ and .claude/ directories in project root");
+        printer.bullet("Enable structured request logging");
+        printer.text("");
+        printer
+            .text("A new project can be bootstrapped (config, PRD template, Claude Code entry):");
+        printer.text("  axon-mcp init --project-root=/path/to/project --port=8888 [--register-with-claude-code]");
+        printer.text("  axon-mcp --register-with-claude-code [--dry-run] --project-root=/path/to/project --port=8888");
+        printer.text("");
+        printer.text(
+            "In a container, the server listens on 0.0.0.0:$PORT with the database in /data:",
+        );
+        printer.text("  axon-mcp --start --container    (healthcheck: axon-mcp --healthcheck)");
+        printer.text("");
+        printer.text("Schema migrations can be checked or applied without starting the server:");
+        printer.text("  axon-mcp --migration-status --database-url=sqlite:///path/to/db.sqlite");
+        printer.text("  axon-mcp --migrate [--dry-run] --database-url=sqlite:///path/to/db.sqlite");
+        printer.text("");
+        printer.text("The audit log can be exported as JSON Lines:");
+        printer.text("  axon-mcp --export-audit-log=audit.jsonl [--since=2025-01-01T00:00:00Z] --database-url=sqlite:///path/to/db.sqlite");
+        printer.text("");
+        printer.text("Tasks and work sessions can be exported as CSV for spreadsheets:");
+        printer.text("  axon-mcp --export-tasks-csv=tasks.csv [--columns=code,name,state,owner] [--agent=NAME] --database-url=sqlite:///path/to/db.sqlite");
+        printer.text("  axon-mcp --export-sessions-csv=sessions.csv [--since=2025-01-01T00:00:00Z] --database-url=sqlite:///path/to/db.sqlite");
+        printer.text("");
+        printer.text("With [integrations.github] configured, GitHub issues can be synced once:");
+        printer.text("  axon-mcp --sync-github --config=axon.toml");
+        printer.text("");
+        printer.text("With [digest] configured, the email digest can be sent on demand:");
+        printer.text("  axon-mcp --send-digest --config=axon.toml");
+        printer.text("");
+        printer.text("For more options, use: axon-mcp --help");
+        if printer.mode() == OutputMode::Json {
+            printer.info("usage", "Pass --start to run the server", json!({}));
+        }
         return Ok(());
     }
 
-    // Validate required parameters
-    if cli.project_root.is_none() {
-        error!("--project-root parameter is required");
+    // Load configuration
+    let config = load_config(&cli).context("Failed to load configuration")?;
+
+    // Validate required parameters (a container keeps its database on the data volume)
+    if cli.project_root.is_none() && !config.server.container {
+        printer.error(
+            "invalid_arguments",
+            "--project-root parameter is required",
+            json!({}),
+        );
         std::process::exit(1);
     }
 
-    if cli.project.is_none() && cli.database_url.is_none() {
-        error!("Either --project or --database-url must be specified");
+    if cli.project.is_none() && cli.database_url.is_none() && !config.server.container {
+        let message = "Either --project or --database-url must be specified";
+        printer.error("invalid_arguments", message, json!({}));
         std::process::exit(1);
     }
 
     // Create project directories (.axon and .claude)
     if let Some(ref project_root) = cli.project_root {
-        create_project_directories(project_root)
-            .context("Failed to create project directories")?;
+        create_project_directories(project_root).context("Failed to create project directories")?;
     }
 
-    // Load configuration
-    let config = load_config(&cli).context("Failed to load configuration")?;
-
     // Initialize telemetry/logging system
     init_telemetry(&config.logging).context("Failed to initialize telemetry")?;
 
@@ -207,7 +965,7 @@ async fn main() -> Result<()> {
 
     // Start HTTP MCP server
     info!("🚀 Starting Axon MCP Server (HTTP Only)");
-    
+
     if let Some(ref project_name) = cli.project {
         info!("📊 Project: {}", project_name);
         info!("💾 Database: axon.{}.sqlite", project_name);
@@ -219,17 +977,80 @@ async fn main() -> Result<()> {
         .await
         .context("Failed to initialize application")?;
 
+    // Bind before announcing, so that port 0 reports the port actually chosen
+    let listener = tokio::net::TcpListener::bind(config.server_address())
+        .await
+        .with_context(|| format!("Failed to listen on {}", config.server_address()))?;
+    let local_addr = listener.local_addr()?;
+    let mut listeners = vec![(listener, ListenerConfig::default())];
+    for extra in &config.server.listeners {
+        let listener = tokio::net::TcpListener::bind(&extra.address)
+            .await
+            .with_context(|| format!("Failed to listen on {}", extra.address))?;
+        listeners.push((listener, extra.clone()));
+    }
+    let project_root = cli.project_root.as_deref().map(Path::new);
+    let advertisement = match project_root {
+        Some(root) => Some(
+            ServerAdvertisement::new(local_addr, cli.project.clone())
+                .write(root)
+                .context("Failed to write server advertisement")?,
+        ),
+        None => None,
+    };
+
     // Print ready message
-    println!("✅ Axon MCP Server is ready!");
-    println!("   📡 Listening on: http://{}", config.server_address());
-    if let Some(ref project_name) = cli.project {
-        println!("   📊 Project: {}", project_name);
-        println!("   💾 Database: axon.{}.sqlite", project_name);
+    let messages = &config.messages;
+    let address = format!("http://{local_addr}");
+    let database = cli
+        .project
+        .as_ref()
+        .map(|project_name| format!("axon.{project_name}.sqlite"));
+    printer.success(
+        "server_ready",
+        &messages.format(MessageKey::CliServerReady, &[]),
+        json!({
+            "address": address,
+            "port": local_addr.port(),
+            "project": cli.project,
+            "database": database,
+            "advertisement": advertisement,
+        }),
+    );
+    printer.text(&format!(
+        "   {}",
+        messages.format(MessageKey::CliListeningOn, &[("address", &address)])
+    ));
+    for (listener, extra) in &listeners[1..] {
+        let address = format!("http://{}", listener.local_addr()?);
+        let mut requirements = Vec::new();
+        if extra.access_token.is_some() {
+            requirements.push("access token");
+        }
+        if extra.require_identity {
+            requirements.push("agent identity");
+        }
+        if extra.read_only {
+            requirements.push("read-only");
+        }
+        let listening = messages.format(MessageKey::CliListeningOn, &[("address", &address)]);
+        match requirements.is_empty() {
+            true => printer.text(&format!("   {listening}")),
+            false => printer.text(&format!("   {listening} ({})", requirements.join(", "))),
+        }
+    }
+    if let (Some(project_name), Some(database)) = (&cli.project, &database) {
+        let project = messages.format(MessageKey::CliProject, &[("project", project_name)]);
+        let database = messages.format(MessageKey::CliDatabase, &[("database", database)]);
+        printer.text(&format!("   {project}\n   {database}"));
     }
-    println!("   📋 Request logging: enabled");
-    println!();
-    println!("Press Ctrl+C to shutdown");
-    println!();
+    printer.text(&format!(
+        "   {}",
+        messages.format(MessageKey::CliRequestLogging, &[])
+    ));
+    printer.text("");
+    printer.text(&messages.format(MessageKey::CliPressCtrlC, &[]));
+    printer.text("");
 
     // Setup graceful shutdown handling
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
@@ -266,25 +1087,38 @@ async fn main() -> Result<()> {
         let _ = shutdown_tx.send(());
     });
 
+    // Clients must not find the address of a stopped server
+    let remove_advertisement = || {
+        if let Some(root) = project_root {
+            if let Err(e) = ServerAdvertisement::remove(root) {
+                error!(error = %e, "Failed to remove server advertisement");
+            }
+        }
+    };
+
     // Start the server with graceful shutdown
-    let server_addr = config.server_address();
     tokio::select! {
-        result = server.serve(&server_addr) => {
+        result = server.serve_listeners(listeners) => {
+            remove_advertisement();
             match result {
                 Ok(_) => {
-                    println!("✅ Axon MCP Server shut down cleanly");
+                    let message = messages.format(MessageKey::CliShutDownCleanly, &[]);
+                    printer.success("server_stopped", &message, json!({}));
                     info!("MCP server shut down cleanly");
                     Ok(())
                 }
                 Err(e) => {
                     error!(error = %e, "MCP server error");
-                    println!("❌ Server error: {}", e);
+                    let message = messages.format(MessageKey::CliServerError, &[("error", &e)]);
+                    printer.error("server_error", &message, json!({}));
                     std::process::exit(3);
                 }
             }
         }
         _ = shutdown_rx => {
-            println!("🛑 Shutdown signal received, stopping server...");
+            remove_advertisement();
+            let message = messages.format(MessageKey::CliShutdownSignal, &[]);
+            printer.info("shutdown_signal", &message, json!({}));
             info!("Shutdown signal received, stopping server");
             // Server will be dropped here, triggering cleanup
             Ok(())
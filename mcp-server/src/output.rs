@@ -0,0 +1,174 @@
+//! Human-facing output of the server binary
+//!
+//! Everything the binary prints for people goes through [`Printer`], which
+//! renders it for the selected [`OutputMode`]: emoji-decorated text, plain
+//! text, or one JSON object per line for supervisors parsing stdout.
+
+use chrono::Utc;
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+
+/// How the binary writes to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputMode {
+    /// Human-friendly text with status emoji
+    #[default]
+    Pretty,
+    /// Human-friendly text without emoji
+    Plain,
+    /// One JSON object per line; logs are JSON as well
+    Json,
+}
+
+/// Outcome reported by a status line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Status {
+    fn emoji(self) -> Option<&'static str> {
+        match self {
+            Status::Info => None,
+            Status::Success => Some("✅"),
+            Status::Warning => Some("⚠️ "),
+            Status::Error => Some("❌"),
+        }
+    }
+
+    fn level(self) -> &'static str {
+        match self {
+            Status::Info | Status::Success => "INFO",
+            Status::Warning => "WARN",
+            Status::Error => "ERROR",
+        }
+    }
+}
+
+/// Renders human-facing output for an [`OutputMode`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Printer {
+    mode: OutputMode,
+}
+
+impl Printer {
+    pub fn new(mode: OutputMode) -> Self {
+        Self { mode }
+    }
+
+    pub fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    /// Render a status line; in JSON mode `event` and `fields` describe it
+    pub fn render(&self, status: Status, event: &str, message: &str, fields: Value) -> String {
+        match (self.mode, status.emoji()) {
+            (OutputMode::Json, _) => {
+                let mut record = Map::new();
+                record.insert("timestamp".to_string(), json!(Utc::now().to_rfc3339()));
+                record.insert("level".to_string(), json!(status.level()));
+                record.insert("event".to_string(), json!(event));
+                record.insert("message".to_string(), json!(message));
+                if let Value::Object(fields) = fields {
+                    record.extend(fields);
+                }
+                Value::Object(record).to_string()
+            }
+            (OutputMode::Pretty, Some(emoji)) => format!("{emoji} {message}"),
+            _ => message.to_string(),
+        }
+    }
+
+    /// Print a status line
+    pub fn status(&self, status: Status, event: &str, message: &str, fields: Value) {
+        println!("{}", self.render(status, event, message, fields));
+    }
+
+    pub fn info(&self, event: &str, message: &str, fields: Value) {
+        self.status(Status::Info, event, message, fields);
+    }
+
+    pub fn success(&self, event: &str, message: &str, fields: Value) {
+        self.status(Status::Success, event, message, fields);
+    }
+
+    pub fn warning(&self, event: &str, message: &str, fields: Value) {
+        self.status(Status::Warning, event, message, fields);
+    }
+
+    pub fn error(&self, event: &str, message: &str, fields: Value) {
+        self.status(Status::Error, event, message, fields);
+    }
+
+    /// Print free text meant only for people; nothing in JSON mode
+    pub fn text(&self, text: &str) {
+        if self.mode != OutputMode::Json {
+            println!("{text}");
+        }
+    }
+
+    /// Print a heading, with `emoji` in pretty mode; nothing in JSON mode
+    pub fn heading(&self, emoji: &str, text: &str) {
+        match self.mode {
+            OutputMode::Pretty => println!("{emoji} {text}"),
+            OutputMode::Plain => println!("{text}"),
+            OutputMode::Json => {}
+        }
+    }
+
+    /// Print an indented list item; nothing in JSON mode
+    pub fn bullet(&self, text: &str) {
+        match self.mode {
+            OutputMode::Pretty => println!("  • {text}"),
+            OutputMode::Plain => println!("  - {text}"),
+            OutputMode::Json => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let fields = json!({"count": 3});
+        let pretty = Printer::new(OutputMode::Pretty);
+        assert_eq!(
+            pretty.render(
+                Status::Success,
+                "exported",
+                "Exported 3 rows",
+                fields.clone()
+            ),
+            "✅ Exported 3 rows"
+        );
+        assert_eq!(
+            pretty.render(Status::Info, "exported", "Exported 3 rows", fields.clone()),
+            "Exported 3 rows"
+        );
+
+        let plain = Printer::new(OutputMode::Plain);
+        assert_eq!(
+            plain.render(Status::Error, "exported", "Exported 3 rows", fields.clone()),
+            "Exported 3 rows"
+        );
+
+        let json = Printer::new(OutputMode::Json);
+        let record: Value = serde_json::from_str(&json.render(
+            Status::Warning,
+            "exported",
+            "Exported 3 rows",
+            fields,
+        ))
+        .unwrap();
+        assert_eq!(record["level"], "WARN");
+        assert_eq!(record["event"], "exported");
+        assert_eq!(record["message"], "Exported 3 rows");
+        assert_eq!(record["count"], 3);
+        assert!(record["timestamp"].is_string());
+    }
+}
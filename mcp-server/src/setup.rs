@@ -1,19 +1,42 @@
 use anyhow::{Context, Result};
 use database::{SqliteTaskRepository, SqliteWorkspaceContextRepository};
-use mcp_protocol::McpServer;
-use std::sync::Arc;
+use mcp_protocol::{McpServer, Recorder, STATE_CHANGE_CAPACITY};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::{
+    load_done_policy, load_pipeline_templates, load_routing_rules, LoggingRepository,
+    MetricsRepository, RepositoryMetrics, RetryingRepository, TaskRepository, DONE_POLICY_FILE,
+    PIPELINES_FILE, ROUTING_RULES_FILE,
+};
+use tokio::sync::broadcast;
 use tracing::{info, warn};
 
-use crate::config::{Config, resolve_database_path, InstallScope};
+use crate::config::{resolve_database_path, Config, InstallScope, RepositoryConfig};
+use crate::digest::register_digest_job;
+use crate::discovery_script::create_discovery_policy;
+use crate::embeddings::{create_embedding_provider, register_embedding_index_job};
+use crate::hooks::TaskHooks;
+use crate::integrations::register_integration_jobs;
+use crate::jobs::{
+    create_job_scheduler, register_history_snapshot_job, register_repository_metrics_job,
+    register_stuck_task_job, register_tasks_markdown_sync_job,
+};
+use crate::summaries::create_thread_summarizer;
+
+/// The MCP server as wired by [`initialize_app`]
+///
+/// Tasks go through a trait object, so the server does not depend on the
+/// storage backend behind it.
+pub type AppServer =
+    McpServer<dyn TaskRepository, SqliteTaskRepository, SqliteWorkspaceContextRepository>;
 
 /// Create a task repository based on the complete configuration
 pub async fn create_repository(config: &Config) -> Result<Arc<SqliteTaskRepository>> {
     info!("Creating task repository");
 
     // Handle legacy database migration before creating repository
-    handle_legacy_database_migration()
-        .context("Failed to handle legacy database migration")?;
+    handle_legacy_database_migration().context("Failed to handle legacy database migration")?;
 
     // Get validated database URL from config (already handles defaults and validation)
     let database_url = config.database_url();
@@ -21,9 +44,12 @@ pub async fn create_repository(config: &Config) -> Result<Arc<SqliteTaskReposito
 
     // Create SQLite repository
     info!("Initializing SQLite repository at: {}", database_url);
-    let repo = SqliteTaskRepository::new(&database_url)
+    let mut repo = SqliteTaskRepository::new(&database_url)
         .await
-        .context("Failed to create SQLite repository")?;
+        .context("Failed to create SQLite repository")?
+        .with_app_version(env!("CARGO_PKG_VERSION"))
+        .with_allow_downgrade(config.database.allow_downgrade)
+        .with_id_strategy(config.database.id_strategy);
 
     // Run database migrations
     info!("Running database migrations");
@@ -31,6 +57,21 @@ pub async fn create_repository(config: &Config) -> Result<Arc<SqliteTaskReposito
         .await
         .context("Failed to run database migrations")?;
 
+    repo.set_event_history(config.database.event_history)
+        .await
+        .context("Failed to configure task event history")?;
+
+    // Attach the archive database when archival is enabled or tasks were archived before
+    if let Some(archive_path) = config.archive_database_path() {
+        if config.jobs.archive_interval > 0 || archive_path.exists() {
+            info!("Using archive database at: {}", archive_path.display());
+            repo = repo
+                .with_archive(&archive_path)
+                .await
+                .context("Failed to open archive database")?;
+        }
+    }
+
     info!("Task repository created successfully");
     Ok(Arc::new(repo))
 }
@@ -63,12 +104,11 @@ pub async fn create_workspace_context_repository(
 
 /// Create and configure the MCP server
 pub fn create_server(
-    repository: Arc<SqliteTaskRepository>,
+    repository: Arc<dyn TaskRepository>,
     message_repository: Arc<SqliteTaskRepository>,
     workspace_context_repository: Arc<SqliteWorkspaceContextRepository>,
     config: &Config,
-) -> Result<McpServer<SqliteTaskRepository, SqliteTaskRepository, SqliteWorkspaceContextRepository>>
-{
+) -> Result<AppServer> {
     info!("Creating MCP server");
 
     let server = McpServer::new(
@@ -77,18 +117,100 @@ pub fn create_server(
         workspace_context_repository,
         config.project_root(),
     );
+    let summarizer = create_thread_summarizer(&config.thread_summaries, server.sampler())?;
+    let server = server
+        .with_http_config(config.server.http_config())
+        .with_method_timeouts(config.server.method_timeouts.clone())
+        .with_admin_api_key(config.server.admin_api_key.clone())
+        .with_agent_auth(config.server.agent_auth.clone())
+        .with_runaway_policy(config.server.runaway_agents)
+        .with_read_only(config.server.read_only)
+        .with_recorder(create_recorder(config)?)
+        .with_message_effects(config.workflow.message_effects.clone())
+        .with_retention_policy(config.retention)
+        .with_claim_lease_policy(config.claim_lease)
+        .with_work_session_policy(config.work_sessions)
+        .with_rebalance_policy(config.rebalancing)
+        .with_anomaly_thresholds(config.agent_performance)
+        .with_message_catalog(config.messages.clone())
+        .with_duplicate_check_policy(config.duplicate_check)
+        .with_token_budgets(config.token_budgets)
+        .with_thread_summaries(config.thread_summaries.policy(), summarizer);
+
+    let server = match config.work_discovery {
+        Some(ref work_discovery) => server.with_discovery_policy(
+            create_discovery_policy(work_discovery, config.project_root().as_deref())
+                .context("Failed to set up the work discovery script")?,
+        ),
+        None => server,
+    };
+
+    let server = match config.project_root() {
+        Some(root) => {
+            let rules = load_routing_rules(&root).context("Failed to load the routing rules")?;
+            if !rules.is_empty() {
+                info!(
+                    "Routing tasks by {} rules from {}",
+                    rules.rules.len(),
+                    ROUTING_RULES_FILE
+                );
+            }
+            let done_policy =
+                load_done_policy(&root).context("Failed to load the definition of done")?;
+            if !done_policy.is_empty() {
+                info!("Enforcing the definition of done from {}", DONE_POLICY_FILE);
+            }
+            let pipelines =
+                load_pipeline_templates(&root).context("Failed to load the pipeline templates")?;
+            if root.join(PIPELINES_FILE).exists() {
+                info!(
+                    "Creating pipelines from {} templates, including those in {}",
+                    pipelines.templates.len(),
+                    PIPELINES_FILE
+                );
+            }
+            server
+                .with_routing_rules(rules)
+                .with_done_policy(done_policy)
+                .with_pipeline_templates(pipelines)
+        }
+        None => server,
+    };
+
+    if config.server.admin_api_key.is_none() {
+        info!("No admin API key configured, admin tools are disabled");
+    }
+    if config.server.read_only {
+        info!("Read-only mode: tools that change tasks, messages or agents are rejected");
+    }
 
     info!("MCP server created successfully");
     Ok(server)
 }
 
+/// Open a new recording in the project root when record mode is on
+fn create_recorder(config: &Config) -> Result<Option<Arc<Recorder>>> {
+    if !config.server.record {
+        return Ok(None);
+    }
+    let project_root = config
+        .project_root()
+        .context("Record mode needs a project root to write .axon/recordings/ to")?;
+    let recorder = Recorder::create(&project_root).context("Failed to start recording")?;
+    info!(
+        "Recording JSON-RPC exchanges to {}",
+        recorder.path().display()
+    );
+    Ok(Some(Arc::new(recorder)))
+}
+
 /// Initialize the complete application
-pub async fn initialize_app(
-    config: &Config,
-) -> Result<McpServer<SqliteTaskRepository, SqliteTaskRepository, SqliteWorkspaceContextRepository>>
-{
+pub async fn initialize_app(config: &Config) -> Result<AppServer> {
     info!("Initializing application");
 
+    // Agent names are validated in the repository and the protocol handler alike
+    config.agent_names.clone().install();
+
     // Create repository
     let repository = create_repository(config)
         .await
@@ -103,14 +225,147 @@ pub async fn initialize_app(
         .await
         .context("Failed to create workspace context repository")?;
 
+    // Tasks moved to a new state by the server or its jobs, followed by the hooks
+    let (state_changes, _) = broadcast::channel(STATE_CHANGE_CAPACITY);
+
+    // Start periodic maintenance jobs before the repository moves into the server
+    let mut scheduler = create_job_scheduler(
+        repository.clone(),
+        &config.jobs,
+        config.retention,
+        config.agent_performance,
+    );
+    register_integration_jobs(
+        &mut scheduler,
+        repository.clone(),
+        &config.integrations,
+        Duration::from_secs(config.jobs.jitter),
+    )?;
+    if let Some(ref digest) = config.digest {
+        register_digest_job(
+            &mut scheduler,
+            repository.clone(),
+            digest,
+            Duration::from_secs(config.jobs.jitter),
+        )
+        .context("Failed to set up email digest")?;
+    }
+    let embedding_provider = match config.embeddings {
+        Some(ref embeddings) => {
+            let provider = create_embedding_provider(embeddings)?;
+            register_embedding_index_job(
+                &mut scheduler,
+                repository.clone(),
+                provider.clone(),
+                embeddings,
+                Duration::from_secs(config.jobs.jitter),
+            );
+            Some(provider)
+        }
+        None => None,
+    };
+    if config.database.event_history && config.jobs.history_snapshot_interval > 0 {
+        register_history_snapshot_job(
+            &mut scheduler,
+            repository.clone(),
+            Duration::from_secs(config.jobs.history_snapshot_interval),
+            Duration::from_secs(config.jobs.jitter),
+        );
+    }
+    if config.jobs.stuck_task_check_interval > 0 {
+        register_stuck_task_job(
+            &mut scheduler,
+            repository.clone(),
+            config.stuck_tasks.clone(),
+            Duration::from_secs(config.jobs.stuck_task_check_interval),
+            Duration::from_secs(config.jobs.jitter),
+        );
+    }
+    if config.jobs.tasks_markdown_sync_interval > 0 {
+        let project_root = config
+            .project_root()
+            .context("Syncing .axon/tasks.md needs a project root")?;
+        let done_policy =
+            load_done_policy(&project_root).context("Failed to load the definition of done")?;
+        register_tasks_markdown_sync_job(
+            &mut scheduler,
+            repository.clone(),
+            done_policy,
+            project_root,
+            state_changes.clone(),
+            Duration::from_secs(config.jobs.tasks_markdown_sync_interval),
+            Duration::from_secs(config.jobs.jitter),
+        );
+    }
+    let (repository, repository_metrics) = decorate_repository(repository, &config.repository);
+    if let Some(metrics) = repository_metrics {
+        if config.repository.metrics_report_interval > 0 {
+            register_repository_metrics_job(
+                &mut scheduler,
+                metrics,
+                Duration::from_secs(config.repository.metrics_report_interval),
+                Duration::from_secs(config.jobs.jitter),
+            );
+        }
+    }
+    let background_jobs = scheduler.registry();
+    if config.jobs.enabled {
+        scheduler.start();
+    } else {
+        info!("Background jobs are disabled");
+    }
+
     // Create server
-    let server = create_server(repository, message_repository, workspace_context_repository, config)
-        .context("Failed to create server")?;
+    let mut server = create_server(
+        repository,
+        message_repository,
+        workspace_context_repository,
+        config,
+    )
+    .context("Failed to create server")?
+    .with_background_jobs(background_jobs);
+    if let Some(provider) = embedding_provider {
+        server = server.with_embedding_provider(provider);
+    }
+    if !config.hooks.is_empty() {
+        let hooks = TaskHooks::new(config.hooks.clone(), config.project_root());
+        Arc::new(hooks).spawn(state_changes.subscribe());
+    }
+    server = server.with_state_changes(state_changes);
 
     info!("Application initialized successfully");
     Ok(server)
 }
 
+/// Wrap the task repository in the decorators enabled by the configuration
+///
+/// Retries sit innermost so metrics count each logical call once, and logging
+/// outermost so logged durations include the retries. Returns the recorded
+/// metrics when the metrics decorator is enabled.
+pub fn decorate_repository(
+    repository: Arc<SqliteTaskRepository>,
+    config: &RepositoryConfig,
+) -> (Arc<dyn TaskRepository>, Option<RepositoryMetrics>) {
+    let mut decorated: Arc<dyn TaskRepository> = repository;
+    if config.retry.is_enabled() {
+        info!(
+            "Retrying failed repository calls up to {} times",
+            config.retry.max_attempts
+        );
+        decorated = Arc::new(RetryingRepository::new(decorated, config.retry));
+    }
+    let mut metrics = None;
+    if config.metrics {
+        let metrics_repository = MetricsRepository::new(decorated);
+        metrics = Some(metrics_repository.metrics());
+        decorated = Arc::new(metrics_repository);
+    }
+    if config.logging {
+        decorated = Arc::new(LoggingRepository::new(decorated));
+    }
+    (decorated, metrics)
+}
+
 /// Ensure the database directory exists using config
 pub fn ensure_database_directory_from_config(config: &Config) -> Result<()> {
     let database_url = config.database_url();
@@ -121,13 +376,13 @@ pub fn ensure_database_directory_from_config(config: &Config) -> Result<()> {
 pub fn ensure_database_directory(database_url: &str) -> Result<()> {
     if let Some(db_path) = database_url.strip_prefix("sqlite://") {
         let db_path = Path::new(db_path);
-        
+
         // Ensure parent directory exists
         if let Some(parent) = db_path.parent() {
             if !parent.exists() {
                 info!("Creating database directory: {}", parent.display());
                 std::fs::create_dir_all(parent).context("Failed to create database directory")?;
-                
+
                 // Set secure permissions on Unix systems (owner only)
                 #[cfg(unix)]
                 {
@@ -137,16 +392,44 @@ pub fn ensure_database_directory(database_url: &str) -> Result<()> {
                         .context("Failed to set directory permissions")?;
                 }
             }
+            check_directory_writable(parent)?;
         }
-        
+
         // Set secure permissions on database file if it exists
         if db_path.exists() {
+            std::fs::OpenOptions::new()
+                .append(true)
+                .open(db_path)
+                .with_context(|| {
+                    format!(
+                        "Database file {} is not writable by this user",
+                        db_path.display()
+                    )
+                })?;
             set_secure_file_permissions(db_path)?;
         }
     }
     Ok(())
 }
 
+/// Fail early when the database directory (e.g. a mounted volume) is read-only
+///
+/// SQLite needs to create journal and WAL files next to the database, so the
+/// directory itself must be writable, not only the database file.
+fn check_directory_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(format!(".axon-write-check-{}", std::process::id()));
+    std::fs::write(&probe, b"").with_context(|| {
+        format!(
+            "Database directory {} is not writable by this user; \
+             mount the volume read-write or give the user running axon-mcp ownership of it",
+            dir.display()
+        )
+    })?;
+    std::fs::remove_file(&probe)
+        .with_context(|| format!("Failed to remove {}", probe.display()))?;
+    Ok(())
+}
+
 /// Set secure file permissions (owner-only access on Unix)
 fn set_secure_file_permissions(file_path: &Path) -> Result<()> {
     #[cfg(unix)]
@@ -155,64 +438,73 @@ fn set_secure_file_permissions(file_path: &Path) -> Result<()> {
         let permissions = std::fs::Permissions::from_mode(0o600);
         std::fs::set_permissions(file_path, permissions)
             .with_context(|| format!("Failed to set permissions for {}", file_path.display()))?;
-        info!("Set secure permissions (0600) for database file: {}", file_path.display());
+        info!(
+            "Set secure permissions (0600) for database file: {}",
+            file_path.display()
+        );
     }
-    
+
     #[cfg(windows)]
     {
         // On Windows, we rely on NTFS permissions set by the parent directory
         // Future enhancement could use Windows ACL API for more granular control
-        info!("Database file permissions managed by system on Windows: {}", file_path.display());
+        info!(
+            "Database file permissions managed by system on Windows: {}",
+            file_path.display()
+        );
     }
-    
+
     Ok(())
 }
 
 /// Handle migration from legacy database location
 pub fn handle_legacy_database_migration() -> Result<()> {
     let (new_db_path, scope) = resolve_database_path()?;
-    
+
     // Only attempt migration if we're in user scope and new DB doesn't exist
     if matches!(scope, InstallScope::User) && !new_db_path.exists() {
         let legacy_path = get_legacy_database_path();
-        
+
         if legacy_path.exists() {
             info!("Legacy database found at: {}", legacy_path.display());
             info!("Migrating to new location: {}", new_db_path.display());
-            
+
             // Ensure parent directory exists
             if let Some(parent) = new_db_path.parent() {
-                std::fs::create_dir_all(parent)
-                    .context("Failed to create migration directory")?;
+                std::fs::create_dir_all(parent).context("Failed to create migration directory")?;
             }
-            
+
             // Copy legacy database to new location
-            std::fs::copy(&legacy_path, &new_db_path)
-                .with_context(|| format!(
-                    "Failed to migrate database from {} to {}", 
-                    legacy_path.display(), 
+            std::fs::copy(&legacy_path, &new_db_path).with_context(|| {
+                format!(
+                    "Failed to migrate database from {} to {}",
+                    legacy_path.display(),
                     new_db_path.display()
-                ))?;
-            
+                )
+            })?;
+
             // Set secure permissions on the migrated database
             set_secure_file_permissions(&new_db_path)
                 .context("Failed to set permissions on migrated database")?;
-            
+
             // Create migration marker to avoid repeated attempts
             let marker_path = new_db_path.with_extension("migrated");
             std::fs::write(&marker_path, "Migrated from legacy location")
                 .context("Failed to create migration marker")?;
-            
+
             // Set secure permissions on marker file too
             set_secure_file_permissions(&marker_path)
                 .context("Failed to set permissions on migration marker")?;
-            
+
             info!("Database migration completed successfully");
-            warn!("Legacy database file remains at {} for backup purposes", legacy_path.display());
+            warn!(
+                "Legacy database file remains at {} for backup purposes",
+                legacy_path.display()
+            );
             warn!("You may safely delete it after verifying the migration worked correctly");
         }
     }
-    
+
     Ok(())
 }
 
@@ -228,6 +520,7 @@ fn get_legacy_database_path() -> PathBuf {
 mod tests {
     use super::*;
     use crate::config::{Config, DatabaseConfig, LogFormat, LoggingConfig, ServerConfig};
+    use task_core::IdStrategy;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -240,19 +533,40 @@ mod tests {
                 url: Some(format!("sqlite://{}", temp_db_path.display())),
                 max_connections: 5,
                 connection_timeout: 30,
+                allow_downgrade: false,
+                event_history: false,
+                id_strategy: IdStrategy::default(),
             },
             server: ServerConfig {
                 listen_addr: "127.0.0.1".to_string(),
                 port: 3000,
                 workers: 4,
+                ..ServerConfig::default()
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: LogFormat::Pretty,
             },
-            project: crate::config::ProjectConfig {
-                root: None,
-            },
+            project: crate::config::ProjectConfig { root: None },
+            jobs: crate::config::JobsConfig::default(),
+            repository: crate::config::RepositoryConfig::default(),
+            workflow: crate::config::WorkflowConfig::default(),
+            retention: task_core::RetentionPolicy::default(),
+            agent_names: task_core::AgentNamePolicy::default(),
+            claim_lease: task_core::ClaimLeasePolicy::default(),
+            work_sessions: task_core::WorkSessionPolicy::default(),
+            agent_performance: task_core::AnomalyThresholds::default(),
+            stuck_tasks: task_core::StuckTaskPolicy::default(),
+            rebalancing: task_core::RebalancePolicy::default(),
+            messages: task_core::MessageCatalog::default(),
+            duplicate_check: task_core::DuplicateCheckPolicy::default(),
+            thread_summaries: crate::summaries::ThreadSummariesConfig::default(),
+            token_budgets: task_core::TokenBudgets::default(),
+            integrations: crate::integrations::IntegrationsConfig::default(),
+            digest: None,
+            embeddings: None,
+            work_discovery: None,
+            hooks: crate::hooks::HooksConfig::default(),
         };
 
         let repo = create_repository(&config).await;
@@ -273,19 +587,40 @@ mod tests {
                 url: Some(database_url),
                 max_connections: 5,
                 connection_timeout: 30,
+                allow_downgrade: false,
+                event_history: false,
+                id_strategy: IdStrategy::default(),
             },
             server: ServerConfig {
                 listen_addr: "127.0.0.1".to_string(),
                 port: 3000,
                 workers: 4,
+                ..ServerConfig::default()
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: LogFormat::Pretty,
             },
-            project: crate::config::ProjectConfig {
-                root: None,
-            },
+            project: crate::config::ProjectConfig { root: None },
+            jobs: crate::config::JobsConfig::default(),
+            repository: crate::config::RepositoryConfig::default(),
+            workflow: crate::config::WorkflowConfig::default(),
+            retention: task_core::RetentionPolicy::default(),
+            agent_names: task_core::AgentNamePolicy::default(),
+            claim_lease: task_core::ClaimLeasePolicy::default(),
+            work_sessions: task_core::WorkSessionPolicy::default(),
+            agent_performance: task_core::AnomalyThresholds::default(),
+            stuck_tasks: task_core::StuckTaskPolicy::default(),
+            rebalancing: task_core::RebalancePolicy::default(),
+            messages: task_core::MessageCatalog::default(),
+            duplicate_check: task_core::DuplicateCheckPolicy::default(),
+            thread_summaries: crate::summaries::ThreadSummariesConfig::default(),
+            token_budgets: task_core::TokenBudgets::default(),
+            integrations: crate::integrations::IntegrationsConfig::default(),
+            digest: None,
+            embeddings: None,
+            work_discovery: None,
+            hooks: crate::hooks::HooksConfig::default(),
         };
 
         let repo = create_repository(&config).await;
@@ -299,19 +634,40 @@ mod tests {
                 url: Some("postgres://invalid".to_string()),
                 max_connections: 5,
                 connection_timeout: 30,
+                allow_downgrade: false,
+                event_history: false,
+                id_strategy: IdStrategy::default(),
             },
             server: ServerConfig {
                 listen_addr: "127.0.0.1".to_string(),
                 port: 3000,
                 workers: 4,
+                ..ServerConfig::default()
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: LogFormat::Pretty,
             },
-            project: crate::config::ProjectConfig {
-                root: None,
-            },
+            project: crate::config::ProjectConfig { root: None },
+            jobs: crate::config::JobsConfig::default(),
+            repository: crate::config::RepositoryConfig::default(),
+            workflow: crate::config::WorkflowConfig::default(),
+            retention: task_core::RetentionPolicy::default(),
+            agent_names: task_core::AgentNamePolicy::default(),
+            claim_lease: task_core::ClaimLeasePolicy::default(),
+            work_sessions: task_core::WorkSessionPolicy::default(),
+            agent_performance: task_core::AnomalyThresholds::default(),
+            stuck_tasks: task_core::StuckTaskPolicy::default(),
+            rebalancing: task_core::RebalancePolicy::default(),
+            messages: task_core::MessageCatalog::default(),
+            duplicate_check: task_core::DuplicateCheckPolicy::default(),
+            thread_summaries: crate::summaries::ThreadSummariesConfig::default(),
+            token_budgets: task_core::TokenBudgets::default(),
+            integrations: crate::integrations::IntegrationsConfig::default(),
+            digest: None,
+            embeddings: None,
+            work_discovery: None,
+            hooks: crate::hooks::HooksConfig::default(),
         };
 
         let repo = create_repository(&config).await;
@@ -327,6 +683,19 @@ mod tests {
         let result = ensure_database_directory(&database_url);
         assert!(result.is_ok());
         assert!(db_path.parent().unwrap().exists());
+        // The write probe is cleaned up
+        assert_eq!(
+            std::fs::read_dir(db_path.parent().unwrap())
+                .unwrap()
+                .count(),
+            0
+        );
+
+        // A file where the directory should be cannot hold a database
+        let blocked = temp_dir.path().join("blocked");
+        std::fs::write(&blocked, "").unwrap();
+        let database_url = format!("sqlite://{}", blocked.join("test.db").display());
+        assert!(ensure_database_directory(&database_url).is_err());
     }
 
     #[tokio::test]
@@ -341,19 +710,40 @@ mod tests {
                 url: Some(database_url),
                 max_connections: 5,
                 connection_timeout: 30,
+                allow_downgrade: false,
+                event_history: false,
+                id_strategy: IdStrategy::default(),
             },
             server: ServerConfig {
                 listen_addr: "127.0.0.1".to_string(),
                 port: 3000,
                 workers: 4,
+                ..ServerConfig::default()
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
                 format: LogFormat::Pretty,
             },
-            project: crate::config::ProjectConfig {
-                root: None,
-            },
+            project: crate::config::ProjectConfig { root: None },
+            jobs: crate::config::JobsConfig::default(),
+            repository: crate::config::RepositoryConfig::default(),
+            workflow: crate::config::WorkflowConfig::default(),
+            retention: task_core::RetentionPolicy::default(),
+            agent_names: task_core::AgentNamePolicy::default(),
+            claim_lease: task_core::ClaimLeasePolicy::default(),
+            work_sessions: task_core::WorkSessionPolicy::default(),
+            agent_performance: task_core::AnomalyThresholds::default(),
+            stuck_tasks: task_core::StuckTaskPolicy::default(),
+            rebalancing: task_core::RebalancePolicy::default(),
+            messages: task_core::MessageCatalog::default(),
+            duplicate_check: task_core::DuplicateCheckPolicy::default(),
+            thread_summaries: crate::summaries::ThreadSummariesConfig::default(),
+            token_budgets: task_core::TokenBudgets::default(),
+            integrations: crate::integrations::IntegrationsConfig::default(),
+            digest: None,
+            embeddings: None,
+            work_discovery: None,
+            hooks: crate::hooks::HooksConfig::default(),
         };
 
         let repo = create_repository(&config).await.unwrap();
@@ -362,4 +752,38 @@ mod tests {
         let server = create_server(repo, message_repo, workspace_context_repo, &config);
         assert!(server.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_decorate_repository() {
+        // A private file: `:memory:` is a shared file in the crate directory
+        let dir = tempfile::tempdir().unwrap();
+        let database = dir.path().join("tasks.sqlite");
+        let repo = Arc::new(
+            SqliteTaskRepository::new(database.to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        repo.migrate().await.unwrap();
+
+        let (_, metrics) = decorate_repository(repo.clone(), &RepositoryConfig::default());
+        assert!(metrics.is_none());
+
+        let config = RepositoryConfig {
+            logging: true,
+            metrics: true,
+            retry: task_core::RetryPolicy {
+                max_attempts: 3,
+                backoff_ms: 1,
+            },
+            ..RepositoryConfig::default()
+        };
+        let (decorated, metrics) = decorate_repository(repo, &config);
+        decorated.health_check().await.unwrap();
+        assert!(decorated.get_by_id(42).await.unwrap().is_none());
+
+        let snapshot = metrics.unwrap().snapshot();
+        assert_eq!(snapshot["health_check"].calls, 1);
+        assert_eq!(snapshot["get_by_id"].calls, 1);
+        assert_eq!(snapshot["get_by_id"].errors, 0);
+    }
 }
@@ -0,0 +1,293 @@
+//! Summarizers for long task threads
+//!
+//! The `[thread_summaries]` section sets when threads are summarized and
+//! which summarizer condenses them. The `extractive` summarizer works
+//! offline; `openai` asks any OpenAI-compatible chat completions endpoint;
+//! `sampling` asks the model of a connected client through MCP sampling.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use mcp_protocol::{ClientSampler, SamplingRequest};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use task_core::{
+    ExtractiveSummarizer, TaskError, TaskMessage, ThreadSummarizer, ThreadSummaryPolicy,
+};
+use tracing::warn;
+
+/// Instructions given to the model with every summarization request
+const SYSTEM_PROMPT: &str = "You maintain the running summary of a discussion between AI agents \
+    working on one task. Merge the new messages into the previous summary. Keep decisions, open \
+    questions, blockers, handoffs and who owns what; drop greetings and repetition. Answer with \
+    the updated summary only, as short markdown bullet points.";
+
+/// Longest summary requested from a client's model
+const MAX_SAMPLED_SUMMARY_TOKENS: u32 = 1024;
+
+/// Which summarizer condenses threads
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SummarizerKind {
+    /// First sentence of every message; no model or network needed
+    #[default]
+    Extractive,
+    /// OpenAI-compatible chat completions API
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// Model of a connected client with the sampling capability; extractive
+    /// otherwise
+    Sampling,
+}
+
+/// `[thread_summaries]` section of the configuration
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ThreadSummariesConfig {
+    /// Messages a thread may have before older ones are summarized (0 disables summaries)
+    pub threshold: u32,
+    /// Most recent messages returned verbatim next to the summary
+    pub keep_recent: u32,
+    pub summarizer: SummarizerKind,
+    /// Model requested from the API
+    pub model: String,
+    /// Chat completions endpoint of the API
+    pub api_url: String,
+    /// API key; `AXON_SUMMARIES_API_KEY` overrides it when no config file is given
+    pub api_key: Option<String>,
+}
+
+impl Default for ThreadSummariesConfig {
+    fn default() -> Self {
+        let policy = ThreadSummaryPolicy::default();
+        Self {
+            threshold: policy.threshold,
+            keep_recent: policy.keep_recent,
+            summarizer: SummarizerKind::Extractive,
+            model: "gpt-4o-mini".to_string(),
+            api_url: "https://api.openai.com/v1/chat/completions".to_string(),
+            api_key: None,
+        }
+    }
+}
+
+impl ThreadSummariesConfig {
+    pub fn policy(&self) -> ThreadSummaryPolicy {
+        ThreadSummaryPolicy {
+            threshold: self.threshold,
+            keep_recent: self.keep_recent,
+        }
+    }
+
+    /// Check that the recent window fits below the threshold and the API is usable
+    pub fn validate(&self) -> Result<()> {
+        if self.threshold > 0 && self.keep_recent >= self.threshold {
+            anyhow::bail!(
+                "thread_summaries.keep_recent ({}) must be below thread_summaries.threshold ({})",
+                self.keep_recent,
+                self.threshold
+            );
+        }
+        if self.summarizer == SummarizerKind::OpenAi {
+            if !self.api_url.starts_with("https://") && !self.api_url.starts_with("http://") {
+                anyhow::bail!(
+                    "thread_summaries.api_url must be an http(s) URL. Got: '{}'",
+                    self.api_url
+                );
+            }
+            if self.model.is_empty() {
+                anyhow::bail!("thread_summaries.model is required for the openai summarizer");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+/// [`ThreadSummarizer`] over an OpenAI-compatible chat completions API
+pub struct OpenAiSummarizer {
+    client: reqwest::Client,
+    config: ThreadSummariesConfig,
+}
+
+impl OpenAiSummarizer {
+    pub fn new(config: &ThreadSummariesConfig) -> Result<Self> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("axon-mcp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(120))
+            .build()?;
+        Ok(Self {
+            client,
+            config: config.clone(),
+        })
+    }
+}
+
+/// Prompt with the previous summary and the new messages of a thread
+fn summary_prompt(task_code: &str, previous: Option<&str>, messages: &[TaskMessage]) -> String {
+    let mut prompt = format!("Task {task_code}\n\nPrevious summary:\n");
+    prompt.push_str(previous.unwrap_or("(none)"));
+    prompt.push_str("\n\nNew messages, oldest first:\n");
+    for message in messages {
+        let _ = write!(
+            prompt,
+            "\n[{}] {} ({})",
+            message.message_type,
+            message.author_agent_name,
+            message.created_at.format("%Y-%m-%d %H:%M")
+        );
+        if let Some(ref target) = message.target_agent_name {
+            let _ = write!(prompt, " to {target}");
+        }
+        let _ = writeln!(prompt, ":\n{}", message.content);
+    }
+    prompt
+}
+
+#[async_trait]
+impl ThreadSummarizer for OpenAiSummarizer {
+    async fn summarize(
+        &self,
+        task_code: &str,
+        previous: Option<&str>,
+        messages: &[TaskMessage],
+    ) -> task_core::Result<String> {
+        let body = serde_json::json!({
+            "model": self.config.model,
+            "messages": [
+                {"role": "system", "content": SYSTEM_PROMPT},
+                {"role": "user", "content": summary_prompt(task_code, previous, messages)},
+            ],
+        });
+        let mut request = self.client.post(&self.config.api_url).json(&body);
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| TaskError::Protocol(format!("Failed to reach summarization API: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(TaskError::Protocol(format!(
+                "Summarization API returned status {status}: {body}"
+            )));
+        }
+        let response: ChatResponse = response.json().await.map_err(|e| {
+            TaskError::Protocol(format!("Failed to parse summarization response: {e}"))
+        })?;
+        response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty())
+            .ok_or_else(|| TaskError::Protocol("Summarization API returned no summary".to_string()))
+    }
+}
+
+/// [`ThreadSummarizer`] asking a connected client's model, falling back to
+/// extractive summaries
+pub struct SamplingSummarizer {
+    sampler: ClientSampler,
+    fallback: ExtractiveSummarizer,
+}
+
+impl SamplingSummarizer {
+    pub fn new(sampler: ClientSampler) -> Self {
+        Self {
+            sampler,
+            fallback: ExtractiveSummarizer::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl ThreadSummarizer for SamplingSummarizer {
+    async fn summarize(
+        &self,
+        task_code: &str,
+        previous: Option<&str>,
+        messages: &[TaskMessage],
+    ) -> task_core::Result<String> {
+        if self.sampler.is_available() {
+            let request = SamplingRequest {
+                system_prompt: SYSTEM_PROMPT.to_string(),
+                prompt: summary_prompt(task_code, previous, messages),
+                max_tokens: MAX_SAMPLED_SUMMARY_TOKENS,
+            };
+            match self.sampler.create_message(request).await {
+                Ok(summary) => return Ok(summary),
+                Err(e) => warn!("Summarizing task {task_code} without the client's model: {e}"),
+            }
+        }
+        self.fallback.summarize(task_code, previous, messages).await
+    }
+}
+
+/// Create the summarizer selected by `config`; `sampler` serves the sampling
+/// summarizer
+pub fn create_thread_summarizer(
+    config: &ThreadSummariesConfig,
+    sampler: ClientSampler,
+) -> Result<Arc<dyn ThreadSummarizer>> {
+    config.validate()?;
+    Ok(match config.summarizer {
+        SummarizerKind::Extractive => Arc::new(ExtractiveSummarizer::default()),
+        SummarizerKind::OpenAi => Arc::new(OpenAiSummarizer::new(config)?),
+        SummarizerKind::Sampling => Arc::new(SamplingSummarizer::new(sampler)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_config_and_prompt() {
+        let config: ThreadSummariesConfig = toml::from_str("threshold = 20").unwrap();
+        assert_eq!(config.policy().keep_recent, 10);
+        let sampler = ClientSampler::new(mcp_protocol::ClientRequests::new(Default::default()));
+        assert!(create_thread_summarizer(&config, sampler).is_ok());
+
+        let config: ThreadSummariesConfig =
+            toml::from_str("threshold = 5\nkeep_recent = 5").unwrap();
+        assert!(config.validate().is_err());
+
+        let message = TaskMessage {
+            id: 1,
+            uid: None,
+            task_code: "API-01".to_string(),
+            author_agent_name: "backend-dev".to_string(),
+            target_agent_name: Some("qa-dev".to_string()),
+            message_type: "handoff".to_string(),
+            created_at: Utc::now(),
+            content: "Ready for testing".to_string(),
+            reply_to_message_id: None,
+            structured_data: None,
+        };
+        let prompt = summary_prompt("API-01", Some("- schema agreed"), &[message]);
+        assert!(prompt.contains("Previous summary:\n- schema agreed\n"));
+        assert!(prompt.contains("[handoff] backend-dev ("));
+        assert!(prompt.ends_with(" to qa-dev:\nReady for testing\n"));
+    }
+}
@@ -1,6 +1,7 @@
 use mcp_server::config::{Config, DatabaseConfig, LogFormat, LoggingConfig, ServerConfig};
 use mcp_server::setup::{create_repository, ensure_database_directory};
 use std::env;
+use task_core::IdStrategy;
 use tempfile::TempDir;
 
 #[tokio::test]
@@ -14,19 +15,40 @@ async fn test_server_startup_with_sqlite() {
             url: Some(database_url),
             max_connections: 5,
             connection_timeout: 30,
+            allow_downgrade: false,
+            event_history: false,
+            id_strategy: IdStrategy::default(),
         },
         server: ServerConfig {
             listen_addr: "127.0.0.1".to_string(),
             port: 3000,
             workers: 4,
+            ..ServerConfig::default()
         },
         logging: LoggingConfig {
             level: "info".to_string(),
             format: LogFormat::Pretty,
         },
-        project: mcp_server::config::ProjectConfig {
-            root: None,
-        },
+        project: mcp_server::config::ProjectConfig { root: None },
+        jobs: mcp_server::config::JobsConfig::default(),
+        repository: mcp_server::config::RepositoryConfig::default(),
+        workflow: mcp_server::config::WorkflowConfig::default(),
+        retention: task_core::RetentionPolicy::default(),
+        agent_names: task_core::AgentNamePolicy::default(),
+        claim_lease: task_core::ClaimLeasePolicy::default(),
+        work_sessions: task_core::WorkSessionPolicy::default(),
+        agent_performance: task_core::AnomalyThresholds::default(),
+        stuck_tasks: task_core::StuckTaskPolicy::default(),
+        rebalancing: task_core::RebalancePolicy::default(),
+        messages: task_core::MessageCatalog::default(),
+        duplicate_check: task_core::DuplicateCheckPolicy::default(),
+        thread_summaries: mcp_server::summaries::ThreadSummariesConfig::default(),
+        token_budgets: task_core::TokenBudgets::default(),
+        integrations: mcp_server::integrations::IntegrationsConfig::default(),
+        digest: None,
+        embeddings: None,
+        work_discovery: None,
+        hooks: mcp_server::hooks::HooksConfig::default(),
     };
 
     let repo = create_repository(&config).await;
@@ -103,10 +125,10 @@ fn test_config_validation_errors() {
     config.database.url = Some("postgres://invalid".to_string());
     assert!(config.validate().is_err());
 
-    // Test invalid port
+    // Port 0 binds an ephemeral port
     config.database.url = None;
     config.server.port = 0;
-    assert!(config.validate().is_err());
+    assert!(config.validate().is_ok());
 
     // Test invalid workers
     config.server.port = 3000;
@@ -126,19 +148,40 @@ fn test_server_address_formatting() {
             url: None,
             max_connections: 5,
             connection_timeout: 30,
+            allow_downgrade: false,
+            event_history: false,
+            id_strategy: IdStrategy::default(),
         },
         server: ServerConfig {
             listen_addr: "0.0.0.0".to_string(),
             port: 8080,
             workers: 2,
+            ..ServerConfig::default()
         },
         logging: LoggingConfig {
             level: "info".to_string(),
             format: LogFormat::Json,
         },
-        project: mcp_server::config::ProjectConfig {
-            root: None,
-        },
+        project: mcp_server::config::ProjectConfig { root: None },
+        jobs: mcp_server::config::JobsConfig::default(),
+        repository: mcp_server::config::RepositoryConfig::default(),
+        workflow: mcp_server::config::WorkflowConfig::default(),
+        retention: task_core::RetentionPolicy::default(),
+        agent_names: task_core::AgentNamePolicy::default(),
+        claim_lease: task_core::ClaimLeasePolicy::default(),
+        work_sessions: task_core::WorkSessionPolicy::default(),
+        agent_performance: task_core::AnomalyThresholds::default(),
+        stuck_tasks: task_core::StuckTaskPolicy::default(),
+        rebalancing: task_core::RebalancePolicy::default(),
+        messages: task_core::MessageCatalog::default(),
+        duplicate_check: task_core::DuplicateCheckPolicy::default(),
+        thread_summaries: mcp_server::summaries::ThreadSummariesConfig::default(),
+        token_budgets: task_core::TokenBudgets::default(),
+        integrations: mcp_server::integrations::IntegrationsConfig::default(),
+        digest: None,
+        embeddings: None,
+        work_discovery: None,
+        hooks: mcp_server::hooks::HooksConfig::default(),
     };
 
     assert_eq!(config.server_address(), "0.0.0.0:8080");
@@ -155,19 +198,40 @@ async fn test_repository_creation_with_migrations() {
             url: Some(database_url),
             max_connections: 3,
             connection_timeout: 15,
+            allow_downgrade: false,
+            event_history: false,
+            id_strategy: IdStrategy::default(),
         },
         server: ServerConfig {
             listen_addr: "127.0.0.1".to_string(),
             port: 3000,
             workers: 4,
+            ..ServerConfig::default()
         },
         logging: LoggingConfig {
             level: "info".to_string(),
             format: LogFormat::Pretty,
         },
-        project: mcp_server::config::ProjectConfig {
-            root: None,
-        },
+        project: mcp_server::config::ProjectConfig { root: None },
+        jobs: mcp_server::config::JobsConfig::default(),
+        repository: mcp_server::config::RepositoryConfig::default(),
+        workflow: mcp_server::config::WorkflowConfig::default(),
+        retention: task_core::RetentionPolicy::default(),
+        agent_names: task_core::AgentNamePolicy::default(),
+        claim_lease: task_core::ClaimLeasePolicy::default(),
+        work_sessions: task_core::WorkSessionPolicy::default(),
+        agent_performance: task_core::AnomalyThresholds::default(),
+        stuck_tasks: task_core::StuckTaskPolicy::default(),
+        rebalancing: task_core::RebalancePolicy::default(),
+        messages: task_core::MessageCatalog::default(),
+        duplicate_check: task_core::DuplicateCheckPolicy::default(),
+        thread_summaries: mcp_server::summaries::ThreadSummariesConfig::default(),
+        token_budgets: task_core::TokenBudgets::default(),
+        integrations: mcp_server::integrations::IntegrationsConfig::default(),
+        digest: None,
+        embeddings: None,
+        work_discovery: None,
+        hooks: mcp_server::hooks::HooksConfig::default(),
     };
 
     let repo = create_repository(&config).await;
@@ -188,19 +252,40 @@ async fn test_multiple_repository_instances() {
             url: Some(database_url),
             max_connections: 10,
             connection_timeout: 30,
+            allow_downgrade: false,
+            event_history: false,
+            id_strategy: IdStrategy::default(),
         },
         server: ServerConfig {
             listen_addr: "127.0.0.1".to_string(),
             port: 3000,
             workers: 4,
+            ..ServerConfig::default()
         },
         logging: LoggingConfig {
             level: "info".to_string(),
             format: LogFormat::Pretty,
         },
-        project: mcp_server::config::ProjectConfig {
-            root: None,
-        },
+        project: mcp_server::config::ProjectConfig { root: None },
+        jobs: mcp_server::config::JobsConfig::default(),
+        repository: mcp_server::config::RepositoryConfig::default(),
+        workflow: mcp_server::config::WorkflowConfig::default(),
+        retention: task_core::RetentionPolicy::default(),
+        agent_names: task_core::AgentNamePolicy::default(),
+        claim_lease: task_core::ClaimLeasePolicy::default(),
+        work_sessions: task_core::WorkSessionPolicy::default(),
+        agent_performance: task_core::AnomalyThresholds::default(),
+        stuck_tasks: task_core::StuckTaskPolicy::default(),
+        rebalancing: task_core::RebalancePolicy::default(),
+        messages: task_core::MessageCatalog::default(),
+        duplicate_check: task_core::DuplicateCheckPolicy::default(),
+        thread_summaries: mcp_server::summaries::ThreadSummariesConfig::default(),
+        token_budgets: task_core::TokenBudgets::default(),
+        integrations: mcp_server::integrations::IntegrationsConfig::default(),
+        digest: None,
+        embeddings: None,
+        work_discovery: None,
+        hooks: mcp_server::hooks::HooksConfig::default(),
     };
 
     // Create multiple repository instances
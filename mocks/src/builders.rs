@@ -222,6 +222,32 @@ impl TaskFilterBuilder {
         self
     }
 
+    /// Also match any of these states
+    pub fn with_states(mut self, states: impl IntoIterator<Item = TaskState>) -> Self {
+        self.filter.states.extend(states);
+        self
+    }
+
+    /// Also match any of these owners
+    pub fn with_owners<S: Into<String>>(mut self, owners: impl IntoIterator<Item = S>) -> Self {
+        self.filter
+            .owners
+            .extend(owners.into_iter().map(Into::into));
+        self
+    }
+
+    /// Filter by code prefix
+    pub fn with_code_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.filter.code_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Filter owned (`true`) or unowned (`false`) tasks
+    pub fn with_has_owner(mut self, has_owner: bool) -> Self {
+        self.filter.has_owner = Some(has_owner);
+        self
+    }
+
     /// Filter by date range from
     pub fn with_date_from(mut self, date_from: DateTime<Utc>) -> Self {
         self.filter.date_from = Some(date_from);
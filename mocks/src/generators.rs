@@ -174,11 +174,17 @@ pub fn task_filter_strategy() -> impl Strategy<Value = TaskFilter> {
         .prop_map(|(owner, state)| TaskFilter {
             owner,
             state,
+            states: Vec::new(),
+            owners: Vec::new(),
+            code_prefix: None,
+            has_owner: None,
             date_from: None,
             date_to: None,
+            modified_since: None,
             completed_after: None,
             completed_before: None,
             limit: None,
             offset: None,
+            include_archive: false,
         })
 }
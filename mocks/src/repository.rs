@@ -14,8 +14,9 @@ use std::sync::{
     Arc,
 };
 use task_core::{
-    repository::RepositoryStats, NewTask, Result, Task, TaskError, TaskFilter, TaskRepository,
-    TaskState, UpdateTask,
+    repository::RepositoryStats, NewTask, Result, Task, TaskCrudRepository, TaskError,
+    TaskExtensionRepository, TaskFilter, TaskGroupBy, TaskGroupCount, TaskState, UpdateTask,
+    WorkClaimRepository, WorkSessionRepository,
 };
 
 /// Mock implementation of TaskRepository for testing
@@ -131,7 +132,7 @@ impl MockTaskRepository {
 }
 
 #[async_trait]
-impl TaskRepository for MockTaskRepository {
+impl TaskCrudRepository for MockTaskRepository {
     async fn create(&self, task: NewTask) -> Result<Task> {
         self.record_call_with_params("create", &format!("code={}", task.code));
 
@@ -187,6 +188,7 @@ impl TaskRepository for MockTaskRepository {
         if let Some(owner) = updates.owner_agent_name {
             task.owner_agent_name = Some(owner);
         }
+        task.updated_at = Utc::now();
 
         Ok(task.clone())
     }
@@ -208,6 +210,8 @@ impl TaskRepository for MockTaskRepository {
         }
 
         task.state = state;
+        task.updated_at = Utc::now();
+        task.state_changed_at = task.updated_at;
 
         // Set completion timestamp if moving to Done
         if state == TaskState::Done {
@@ -246,41 +250,12 @@ impl TaskRepository for MockTaskRepository {
         let tasks = self.tasks.lock();
         let mut result: Vec<Task> = tasks
             .values()
-            .filter(|task| {
-                // Filter by owner
-                if let Some(ref owner) = filter.owner {
-                    if task.owner_agent_name.as_deref() != Some(owner) {
-                        return false;
-                    }
-                }
-
-                // Filter by state
-                if let Some(state) = filter.state {
-                    if task.state != state {
-                        return false;
-                    }
-                }
-
-                // Filter by date range
-                if let Some(date_from) = filter.date_from {
-                    if task.inserted_at < date_from {
-                        return false;
-                    }
-                }
-
-                if let Some(date_to) = filter.date_to {
-                    if task.inserted_at > date_to {
-                        return false;
-                    }
-                }
-
-                true
-            })
+            .filter(|task| filter.matches(task))
             .cloned()
             .collect();
 
         // Sort by creation date (most recent first)
-        result.sort_by(|a, b| b.inserted_at.cmp(&a.inserted_at));
+        result.sort_by_key(|task| std::cmp::Reverse(task.inserted_at));
 
         // Apply pagination
         if let Some(offset) = filter.offset {
@@ -297,6 +272,52 @@ impl TaskRepository for MockTaskRepository {
         Ok(result)
     }
 
+    async fn count_tasks(&self, filter: &TaskFilter) -> Result<u64> {
+        self.record_call("count_tasks");
+
+        // Check for error injection
+        self.check_error_injection()?;
+
+        let tasks = self.tasks.lock();
+        Ok(tasks.values().filter(|task| filter.matches(task)).count() as u64)
+    }
+
+    async fn aggregate_tasks(
+        &self,
+        filter: &TaskFilter,
+        group_by: TaskGroupBy,
+    ) -> Result<Vec<TaskGroupCount>> {
+        self.record_call("aggregate_tasks");
+
+        // Check for error injection
+        self.check_error_injection()?;
+
+        let tasks = self.tasks.lock();
+        let mut counts: HashMap<Option<String>, u64> = HashMap::new();
+        for task in tasks.values().filter(|task| filter.matches(task)) {
+            let keys = match group_by {
+                TaskGroupBy::State => vec![Some(task.state.to_string())],
+                TaskGroupBy::Owner => vec![task.owner_agent_name.clone()],
+                TaskGroupBy::Capability => task
+                    .required_capabilities
+                    .iter()
+                    .cloned()
+                    .map(Some)
+                    .collect(),
+            };
+            for key in keys {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+
+        let mut groups: Vec<TaskGroupCount> = counts
+            .into_iter()
+            .map(|(key, count)| TaskGroupCount { key, count })
+            .collect();
+        TaskGroupCount::sort(&mut groups);
+        Ok(groups)
+    }
+
     async fn assign(&self, id: i32, new_owner: &str) -> Result<Task> {
         self.record_call_with_params("assign", &format!("id={id}, owner={new_owner}"));
 
@@ -316,6 +337,7 @@ impl TaskRepository for MockTaskRepository {
             .ok_or_else(|| TaskError::NotFound(id.to_string()))?;
 
         task.owner_agent_name = Some(new_owner.to_string());
+        task.updated_at = Utc::now();
 
         Ok(task.clone())
     }
@@ -340,6 +362,8 @@ impl TaskRepository for MockTaskRepository {
         }
 
         task.state = TaskState::Archived;
+        task.updated_at = Utc::now();
+        task.state_changed_at = task.updated_at;
 
         Ok(task.clone())
     }
@@ -385,9 +409,10 @@ impl TaskRepository for MockTaskRepository {
 
         Ok(stats)
     }
+}
 
-    // MCP v2 Advanced Multi-Agent Features
-
+#[async_trait]
+impl WorkClaimRepository for MockTaskRepository {
     async fn discover_work(
         &self,
         agent_name: &str,
@@ -461,6 +486,8 @@ impl TaskRepository for MockTaskRepository {
         task.owner_agent_name = Some(agent_name.to_string());
         task.state = TaskState::InProgress;
         task.claimed_at = Some(Utc::now());
+        task.updated_at = Utc::now();
+        task.state_changed_at = task.updated_at;
 
         Ok(task.clone())
     }
@@ -488,10 +515,52 @@ impl TaskRepository for MockTaskRepository {
         task.owner_agent_name = None;
         task.state = TaskState::Created;
         task.claimed_at = None;
+        task.lease_expires_at = None;
+        task.updated_at = Utc::now();
+        task.state_changed_at = task.updated_at;
 
         Ok(task.clone())
     }
 
+    async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>> {
+        self.record_call_with_params(
+            "cleanup_timed_out_tasks",
+            &format!("timeout_minutes={timeout_minutes}"),
+        );
+
+        // Check for error injection
+        self.check_error_injection()?;
+
+        let mut tasks = self.tasks.lock();
+        let now = Utc::now();
+        let timeout_threshold = now - chrono::Duration::minutes(timeout_minutes);
+
+        let mut timed_out_tasks = Vec::new();
+
+        for task in tasks.values_mut() {
+            // Check if task is InProgress and has been claimed for too long
+            if task.state == TaskState::InProgress {
+                if let Some(claimed_at) = task.claimed_at {
+                    if claimed_at < timeout_threshold {
+                        // Release the task
+                        task.state = TaskState::Created;
+                        task.owner_agent_name = None;
+                        task.claimed_at = None;
+                        task.lease_expires_at = None;
+                        task.updated_at = now;
+                        task.state_changed_at = now;
+                        timed_out_tasks.push(task.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(timed_out_tasks)
+    }
+}
+
+#[async_trait]
+impl WorkSessionRepository for MockTaskRepository {
     async fn start_work_session(&self, task_id: i32, agent_name: &str) -> Result<i32> {
         self.record_call_with_params(
             "start_work_session",
@@ -528,34 +597,7 @@ impl TaskRepository for MockTaskRepository {
 
         Ok(())
     }
-
-    async fn cleanup_timed_out_tasks(&self, timeout_minutes: i64) -> Result<Vec<Task>> {
-        self.record_call_with_params("cleanup_timed_out_tasks", &format!("timeout_minutes={timeout_minutes}"));
-
-        // Check for error injection
-        self.check_error_injection()?;
-
-        let mut tasks = self.tasks.lock();
-        let now = Utc::now();
-        let timeout_threshold = now - chrono::Duration::minutes(timeout_minutes);
-        
-        let mut timed_out_tasks = Vec::new();
-        
-        for task in tasks.values_mut() {
-            // Check if task is InProgress and has been claimed for too long
-            if task.state == TaskState::InProgress {
-                if let Some(claimed_at) = task.claimed_at {
-                    if claimed_at < timeout_threshold {
-                        // Release the task
-                        task.state = TaskState::Created;
-                        task.owner_agent_name = None;
-                        task.claimed_at = None;
-                        timed_out_tasks.push(task.clone());
-                    }
-                }
-            }
-        }
-
-        Ok(timed_out_tasks)
-    }
 }
+
+#[async_trait]
+impl TaskExtensionRepository for MockTaskRepository {}
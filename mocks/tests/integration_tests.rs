@@ -4,7 +4,7 @@
 //! and provide the expected testing capabilities.
 
 use mocks::*;
-use task_core::{TaskError, TaskRepository, TaskState};
+use task_core::{TaskCrudRepository, TaskError, TaskState};
 
 #[tokio::test]
 async fn test_mock_repository_basic_operations() {
@@ -188,6 +188,73 @@ async fn test_mock_repository_concurrent_access() {
     assert_eq!(ids.len(), 10); // All IDs should be unique
 }
 
+#[tokio::test]
+async fn test_mock_repository_as_trait_object() {
+    use std::sync::Arc;
+    use task_core::TaskRepository;
+
+    let repo: Arc<dyn TaskRepository> = Arc::new(MockTaskRepository::new());
+    let task = repo
+        .create(
+            NewTaskBuilder::new()
+                .with_code("DYN-001")
+                .with_name("Dynamic")
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let claimed = repo.claim_task(task.id, "test-agent").await.unwrap();
+    assert_eq!(claimed.state, TaskState::InProgress);
+    let session = repo
+        .start_work_session(task.id, "test-agent")
+        .await
+        .unwrap();
+    repo.end_work_session(session, None, None).await.unwrap();
+    assert!(matches!(
+        repo.audit_log(&Default::default()).await,
+        Err(TaskError::UnsupportedOperation(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_repository_decorators_retry_and_record_metrics() {
+    use std::sync::Arc;
+    use task_core::{MetricsRepository, RetryPolicy, RetryingRepository};
+
+    let mock = Arc::new(MockTaskRepository::new());
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        backoff_ms: 1,
+    };
+    let repo = MetricsRepository::new(Arc::new(RetryingRepository::new(mock.clone(), policy)));
+
+    // A database error is retried transparently
+    mock.inject_error(TaskError::Database("database is locked".to_string()));
+    let task = repo.create(create_new_task()).await.unwrap();
+    assert_eq!(
+        mock.call_history()
+            .iter()
+            .filter(|call| call.starts_with("create"))
+            .count(),
+        2
+    );
+
+    // Domain errors are returned on the first attempt
+    mock.inject_error(TaskError::NotFound("gone".to_string()));
+    assert!(matches!(
+        repo.get_by_id(task.id).await,
+        Err(TaskError::NotFound(_))
+    ));
+    assert!(repo.get_by_id(task.id).await.unwrap().is_some());
+
+    let snapshot = repo.metrics().snapshot();
+    assert_eq!(snapshot["create"].calls, 1);
+    assert_eq!(snapshot["create"].errors, 0);
+    assert_eq!(snapshot["get_by_id"].calls, 2);
+    assert_eq!(snapshot["get_by_id"].errors, 1);
+}
+
 #[tokio::test]
 async fn test_mock_repository_stats() {
     let _repo = MockTaskRepository::new();
@@ -94,6 +94,7 @@ Simple command-line tool that converts Markdown files to HTML format with suppor
             "Add error handling".to_string(),
             "Write tests and documentation".to_string(),
         ],
+        classification: None,
     };
 
     info!(